@@ -197,7 +197,7 @@ fn bench_concurrency(c: &mut Criterion) {
 fn bench_render_element_collection(c: &mut Criterion) {
     let mut group = c.benchmark_group("render_path/element_collection");
 
-    for window_count in [10usize, 50usize].iter() {
+    for window_count in [10usize, 50usize, 100usize].iter() {
         group.bench_with_input(
             format!("collect_{}_windows", window_count),
             window_count,
@@ -528,6 +528,93 @@ fn bench_damage_merge_many_windows(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark the compositor's own output-damage merge.
+///
+/// Unlike the simulations above, this calls
+/// `axiom::backend::merge_output_damage` directly, so a change to the merge
+/// used by `render()` shows up here. Regions are one per window, overlapping
+/// and partly off-screen like a busy scrolled workspace.
+fn bench_output_damage_merge(c: &mut Criterion) {
+    use axiom::backend::merge_output_damage;
+    use smithay::utils::{Physical, Point, Rectangle as PhysicalRect, Size};
+
+    let mut group = c.benchmark_group("render_path/output_damage");
+
+    for region_count in [10usize, 100usize, 1000usize].iter() {
+        let mut rng = 0x2545_f491u32;
+        let damage: Vec<PhysicalRect<i32, Physical>> = (0..*region_count)
+            .map(|_| {
+                rng ^= rng << 13;
+                rng ^= rng >> 17;
+                rng ^= rng << 5;
+                let x = (rng % 2400) as i32 - 240;
+                let y = ((rng >> 8) % 1300) as i32 - 110;
+                let w = (rng % 600) as i32 + 1;
+                let h = ((rng >> 4) % 400) as i32 + 1;
+                PhysicalRect::new(Point::from((x, y)), Size::from((w, h)))
+            })
+            .collect();
+        group.bench_with_input(
+            format!("merge_{}_regions", region_count),
+            &damage,
+            |b, damage| {
+                b.iter(|| black_box(merge_output_damage(black_box(damage), (1920, 1080))));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Benchmark concurrent layout queries against a shared workspace.
+///
+/// Several reader threads hold the workspace read lock at once (as pointer
+/// hit tests, IPC snapshots and the renderer do) and query layouts, while
+/// one producer occasionally scrolls. Cache hits take a shared read lock,
+/// so readers shouldn't serialize on the layout cache.
+fn bench_concurrent_layout_reads(c: &mut Criterion) {
+    use std::sync::Arc;
+
+    let mut group = c.benchmark_group("concurrency/layout_reads");
+
+    for readers in [1usize, 4usize, 8usize].iter() {
+        group.bench_with_input(
+            format!("{}_readers_100_windows", readers),
+            readers,
+            |b, &readers| {
+                let config = WorkspaceConfig::default();
+                let mut workspaces = ScrollableWorkspaces::new(&config);
+                for i in 1..=100u64 {
+                    workspaces.add_window(i);
+                }
+                let workspaces = Arc::new(parking_lot::RwLock::new(workspaces));
+
+                b.iter(|| {
+                    std::thread::scope(|scope| {
+                        for _ in 0..readers {
+                            let workspaces = &workspaces;
+                            scope.spawn(move || {
+                                for _ in 0..200 {
+                                    black_box(workspaces.read().calculate_workspace_layouts());
+                                }
+                            });
+                        }
+                        let workspaces = &workspaces;
+                        scope.spawn(move || {
+                            for _ in 0..4 {
+                                workspaces.write().scroll_right();
+                                workspaces.write().scroll_left();
+                            }
+                        });
+                    });
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_workspace_scrolling,
@@ -540,6 +627,8 @@ criterion_group!(
     bench_texture_cache_lookup,
     bench_render_path_preparation,
     bench_damage_merge_many_windows,
+    bench_output_damage_merge,
+    bench_concurrent_layout_reads,
 );
 
 criterion_main!(benches);
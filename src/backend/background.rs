@@ -0,0 +1,247 @@
+//! Per-output backgrounds set at runtime.
+//!
+//! Each output can have its own solid color and wallpaper image, set with
+//! [`AxiomSmithayBackendReal::set_output_background_color`] and
+//! [`AxiomSmithayBackendReal::set_output_wallpaper`], which the IPC
+//! `set_background` and `clear_background` workspace actions call. An
+//! image takes precedence over a color, and a color over the global clear
+//! color. Both sit under the column wallpapers of `workspace.backgrounds`,
+//! which are more specific still. A change cross-fades from the old
+//! background to the new one over [`BACKGROUND_FADE`], so a slideshow
+//! doesn't flash.
+//!
+//! [`AxiomSmithayBackendReal::set_output_background_color`]: super::AxiomSmithayBackendReal::set_output_background_color
+//! [`AxiomSmithayBackendReal::set_output_wallpaper`]: super::AxiomSmithayBackendReal::set_output_wallpaper
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Color every frame is cleared to; what an output without a background of
+/// its own shows.
+pub(super) const CLEAR_COLOR: [f32; 4] = [0.05, 0.05, 0.08, 1.0];
+
+/// How long a background change takes to fade in.
+pub(super) const BACKGROUND_FADE: Duration = Duration::from_millis(400);
+
+/// What an output's background shows once precedence is applied.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum BackgroundFill {
+    Color([f32; 4]),
+    /// Path of a wallpaper image, drawn cropped to cover the output
+    Wallpaper(String),
+}
+
+/// An output's own background settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct OutputBackground {
+    color: Option<[f32; 4]>,
+    wallpaper: Option<String>,
+}
+
+impl OutputBackground {
+    /// The image if there is one, else the color, else the clear color.
+    fn fill(&self) -> BackgroundFill {
+        match (&self.wallpaper, self.color) {
+            (Some(path), _) => BackgroundFill::Wallpaper(path.clone()),
+            (None, Some(color)) => BackgroundFill::Color(color),
+            (None, None) => BackgroundFill::Color(CLEAR_COLOR),
+        }
+    }
+}
+
+/// The background an output is fading away from.
+#[derive(Debug, Clone)]
+struct Fade {
+    from: BackgroundFill,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Fade {
+    fn progress(&self, now: Instant) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        let elapsed = now.saturating_duration_since(self.started);
+        (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+}
+
+/// Every output's background and the fades in progress between them.
+#[derive(Debug, Default)]
+pub(super) struct OutputBackgrounds {
+    outputs: HashMap<String, OutputBackground>,
+    fades: HashMap<String, Fade>,
+}
+
+impl OutputBackgrounds {
+    /// Set output `output`'s color, `None` for the clear color, fading to
+    /// it over `fade`.
+    pub(super) fn set_color(
+        &mut self,
+        output: &str,
+        color: Option<[f32; 4]>,
+        now: Instant,
+        fade: Duration,
+    ) {
+        self.change(output, now, fade, |background| background.color = color);
+    }
+
+    /// Set output `output`'s wallpaper image, `None` to show its color
+    /// again, fading to it over `fade`.
+    pub(super) fn set_wallpaper(
+        &mut self,
+        output: &str,
+        path: Option<String>,
+        now: Instant,
+        fade: Duration,
+    ) {
+        self.change(output, now, fade, |background| background.wallpaper = path);
+    }
+
+    /// Apply `update` to `output`'s background, starting a fade when what
+    /// it shows changes. A change made mid-fade fades on from the
+    /// background that was being faded to.
+    fn change(
+        &mut self,
+        output: &str,
+        now: Instant,
+        fade: Duration,
+        update: impl FnOnce(&mut OutputBackground),
+    ) {
+        let background = self.outputs.entry(output.to_string()).or_default();
+        let from = background.fill();
+        update(background);
+        if background.fill() == from {
+            return;
+        }
+        if *background == OutputBackground::default() {
+            self.outputs.remove(output);
+        }
+        self.fades.insert(
+            output.to_string(),
+            Fade {
+                from,
+                started: now,
+                duration: fade,
+            },
+        );
+    }
+
+    /// How far `output`'s current fade has got, 0.0 - 1.0; `None` when it
+    /// isn't fading.
+    pub(super) fn fade_progress(&self, output: &str, now: Instant) -> Option<f32> {
+        self.fades.get(output).map(|fade| fade.progress(now))
+    }
+
+    /// What to draw for `output` at `now`, bottom first, each with its
+    /// alpha: while fading, the old background under the new one at the
+    /// fade's progress. Empty when it shows only the clear color.
+    pub(super) fn layers(&self, output: &str, now: Instant) -> Vec<(BackgroundFill, f32)> {
+        let to = self
+            .outputs
+            .get(output)
+            .map(OutputBackground::fill)
+            .unwrap_or(BackgroundFill::Color(CLEAR_COLOR));
+        let mut layers = Vec::new();
+        match self.fades.get(output) {
+            Some(fade) if fade.progress(now) < 1.0 => {
+                layers.push((fade.from.clone(), 1.0));
+                layers.push((to, fade.progress(now)));
+            }
+            _ => layers.push((to, 1.0)),
+        }
+        if layers == [(BackgroundFill::Color(CLEAR_COLOR), 1.0)] {
+            layers.clear();
+        }
+        layers
+    }
+
+    /// Wallpaper images still needed, including ones being faded from.
+    pub(super) fn wallpapers(&self) -> impl Iterator<Item = &str> {
+        let current = self.outputs.values().map(|b| b.wallpaper.as_deref());
+        let fading = self.fades.values().map(|fade| match &fade.from {
+            BackgroundFill::Wallpaper(path) => Some(path.as_str()),
+            BackgroundFill::Color(_) => None,
+        });
+        current.chain(fading).flatten()
+    }
+
+    /// Drop the fades that have finished by `now`. Returns whether any
+    /// fade was running, so the frame that completes one still gets drawn.
+    pub(super) fn update(&mut self, now: Instant) -> bool {
+        let fading = !self.fades.is_empty();
+        self.fades.retain(|_, fade| fade.progress(now) < 1.0);
+        fading
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+    #[test]
+    fn test_wallpaper_takes_precedence_over_color() {
+        let mut backgrounds = OutputBackgrounds::default();
+        let now = Instant::now();
+        assert!(backgrounds.layers("DP-1", now).is_empty());
+
+        backgrounds.set_color("DP-1", Some(RED), now, Duration::ZERO);
+        assert_eq!(
+            backgrounds.layers("DP-1", now),
+            [(BackgroundFill::Color(RED), 1.0)]
+        );
+        let wall = BackgroundFill::Wallpaper("/walls/a.png".into());
+        backgrounds.set_wallpaper("DP-1", Some("/walls/a.png".into()), now, Duration::ZERO);
+        assert_eq!(backgrounds.layers("DP-1", now), [(wall.clone(), 1.0)]);
+        // The image still wins when the color changes under it, so there
+        // is nothing to fade
+        backgrounds.update(now);
+        backgrounds.set_color("DP-1", None, now, BACKGROUND_FADE);
+        assert_eq!(backgrounds.fade_progress("DP-1", now), None);
+        assert_eq!(backgrounds.layers("DP-1", now), [(wall, 1.0)]);
+        // Other outputs are untouched
+        assert!(backgrounds.layers("HDMI-A-1", now).is_empty());
+    }
+
+    #[test]
+    fn test_changing_wallpaper_cross_fades_over_time() {
+        let mut backgrounds = OutputBackgrounds::default();
+        let start = Instant::now();
+        backgrounds.set_wallpaper("DP-1", Some("/walls/a.png".into()), start, Duration::ZERO);
+        backgrounds.update(start);
+
+        backgrounds.set_wallpaper("DP-1", Some("/walls/b.png".into()), start, BACKGROUND_FADE);
+        let (a, b) = (
+            BackgroundFill::Wallpaper("/walls/a.png".into()),
+            BackgroundFill::Wallpaper("/walls/b.png".into()),
+        );
+        assert_eq!(backgrounds.fade_progress("DP-1", start), Some(0.0));
+        let mut wanted: Vec<_> = backgrounds.wallpapers().collect();
+        wanted.sort();
+        assert_eq!(wanted, ["/walls/a.png", "/walls/b.png"]);
+
+        let quarter = start + BACKGROUND_FADE / 4;
+        let half = start + BACKGROUND_FADE / 2;
+        let progress = |at| backgrounds.fade_progress("DP-1", at).unwrap();
+        assert!(progress(quarter) > 0.0 && progress(quarter) < progress(half));
+        assert!((progress(half) - 0.5).abs() < 1e-3);
+        assert_eq!(
+            backgrounds.layers("DP-1", half),
+            [(a, 1.0), (b.clone(), progress(half))]
+        );
+
+        // Once done, the old image is let go of and only the new one drawn
+        let done = start + BACKGROUND_FADE;
+        assert!(backgrounds.update(done));
+        assert!(!backgrounds.update(done));
+        assert_eq!(backgrounds.fade_progress("DP-1", done), None);
+        assert_eq!(backgrounds.layers("DP-1", done), [(b, 1.0)]);
+        assert_eq!(
+            backgrounds.wallpapers().collect::<Vec<_>>(),
+            ["/walls/b.png"]
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Compositor-wide brightness, contrast and saturation
+//! (`output.brightness`, `output.contrast`, `output.saturation`).
+//!
+//! When any of them is off its neutral value the scene is composed into an
+//! offscreen layer first (see [`super::layers`]), then drawn to the output
+//! through a colour matrix. Saturation 0 turns the whole desktop grey, which some people need
+//! to read the screen comfortably.
+//!
+//! The same shader draws single windows through their [`ColorFilter`].
+
+use anyhow::Result;
+use smithay::backend::renderer::gles::{
+    GlesRenderer, GlesTexProgram, Uniform, UniformName, UniformType, UniformValue,
+};
+
+use crate::config::OutputConfig;
+use crate::window::ColorFilter;
+
+/// Rec. 709 luma weights: how much red, green and blue each contribute to
+/// perceived brightness.
+const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Texture shader applying `color_matrix` to every sampled pixel.
+const SHADER: &str = r#"
+#version 100
+
+//_DEFINES_
+
+#if defined(EXTERNAL)
+#extension GL_OES_EGL_image_external : require
+#endif
+
+precision mediump float;
+#if defined(EXTERNAL)
+uniform samplerExternalOES tex;
+#else
+uniform sampler2D tex;
+#endif
+
+uniform float alpha;
+varying vec2 v_coords;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+uniform mat4 color_matrix;
+
+void main() {
+    vec4 color = texture2D(tex, v_coords);
+#if defined(NO_ALPHA)
+    color.a = 1.0;
+#endif
+    // Textures are premultiplied; the matrix applies to straight colour
+    if (color.a > 0.0) {
+        vec3 rgb = (color_matrix * vec4(color.rgb / color.a, 1.0)).rgb;
+        color.rgb = clamp(rgb, 0.0, 1.0) * color.a;
+    }
+    color = color * alpha;
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        color = vec4(0.0, 0.2, 0.0, 0.2) + color * 0.8;
+#endif
+    gl_FragColor = color;
+}
+"#;
+
+/// The configured adjustment. `1.0` leaves each property as it is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct ColorAdjust {
+    pub(super) brightness: f32,
+    pub(super) contrast: f32,
+    pub(super) saturation: f32,
+}
+
+impl From<&OutputConfig> for ColorAdjust {
+    fn from(config: &OutputConfig) -> Self {
+        Self {
+            brightness: config.brightness,
+            contrast: config.contrast,
+            saturation: config.saturation,
+        }
+    }
+}
+
+impl ColorAdjust {
+    /// Whether drawing through the adjustment would change nothing, so the
+    /// extra pass can be skipped.
+    pub(super) fn is_identity(&self) -> bool {
+        self.brightness == 1.0 && self.contrast == 1.0 && self.saturation == 1.0
+    }
+
+    /// The affine map from input to output RGB: rows of `[r, g, b, offset]`.
+    ///
+    /// Saturation blends each pixel with its own luma, contrast then pushes
+    /// it away from (or pulls it toward) mid-grey, and brightness scales the
+    /// result.
+    pub(super) fn matrix(&self) -> [[f32; 4]; 3] {
+        let (b, c, s) = (self.brightness, self.contrast, self.saturation);
+        let mut rows = [[0.0; 4]; 3];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, weight) in LUMA.iter().enumerate() {
+                let identity = if i == j { 1.0 } else { 0.0 };
+                row[j] = b * c * ((1.0 - s) * weight + s * identity);
+            }
+            row[3] = b * 0.5 * (1.0 - c);
+        }
+        rows
+    }
+}
+
+/// The affine colour map for a window's filter, in the layout of
+/// [`ColorAdjust::matrix`], or `None` when the window is drawn as is.
+pub(super) fn filter_matrix(filter: ColorFilter) -> Option<[[f32; 4]; 3]> {
+    match filter {
+        ColorFilter::None => None,
+        ColorFilter::Invert => Some([
+            [-1.0, 0.0, 0.0, 1.0],
+            [0.0, -1.0, 0.0, 1.0],
+            [0.0, 0.0, -1.0, 1.0],
+        ]),
+        ColorFilter::Grayscale => Some(
+            ColorAdjust {
+                brightness: 1.0,
+                contrast: 1.0,
+                saturation: 0.0,
+            }
+            .matrix(),
+        ),
+    }
+}
+
+/// `rows` as the `color_matrix` uniform: a column-major `mat4`.
+pub(super) fn matrix_uniform(rows: [[f32; 4]; 3]) -> Uniform<'static> {
+    Uniform::new(
+        "color_matrix",
+        UniformValue::Matrix4x4 {
+            matrices: vec![gl_matrix(rows)],
+            transpose: false,
+        },
+    )
+}
+
+fn gl_matrix(rows: [[f32; 4]; 3]) -> [f32; 16] {
+    let mut m = [0.0; 16];
+    for (col, chunk) in m.chunks_exact_mut(4).enumerate() {
+        for (row, value) in chunk.iter_mut().take(3).enumerate() {
+            *value = rows[row][col];
+        }
+    }
+    m[15] = 1.0;
+    m
+}
+
+/// The colour-matrix shader, compiled the first frame it is needed.
+#[derive(Debug, Default)]
+pub(super) struct ColorPass {
+    program: Option<GlesTexProgram>,
+}
+
+impl ColorPass {
+    /// The colour-matrix texture shader, compiled on first use.
+    pub(super) fn program(&mut self, renderer: &mut GlesRenderer) -> Result<GlesTexProgram> {
+        if let Some(program) = &self.program {
+            return Ok(program.clone());
+        }
+        let program = renderer.compile_custom_texture_shader(
+            SHADER,
+            &[UniformName::new("color_matrix", UniformType::Matrix4x4)],
+        )?;
+        Ok(self.program.insert(program).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjust(brightness: f32, contrast: f32, saturation: f32) -> ColorAdjust {
+        ColorAdjust {
+            brightness,
+            contrast,
+            saturation,
+        }
+    }
+
+    fn apply(matrix: &[[f32; 4]; 3], rgb: [f32; 3]) -> [f32; 3] {
+        matrix.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2] + row[3])
+    }
+
+    fn luma(rgb: [f32; 3]) -> f32 {
+        LUMA.iter().zip(rgb).map(|(w, c)| w * c).sum()
+    }
+
+    #[test]
+    fn test_zero_saturation_is_luminance_preserving_grayscale() {
+        let m = adjust(1.0, 1.0, 0.0).matrix();
+        // Every channel becomes the pixel's luma
+        for row in &m {
+            assert_eq!(row[..3], LUMA);
+            assert_eq!(row[3], 0.0);
+        }
+        assert!((LUMA.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        for rgb in [[1.0, 0.0, 0.0], [0.2, 0.9, 0.4], [1.0, 1.0, 1.0]] {
+            let out = apply(&m, rgb);
+            assert!(out.iter().all(|c| (c - out[0]).abs() < 1e-6), "{:?}", out);
+            assert!((luma(out) - luma(rgb)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_neutral_adjustment_is_identity() {
+        let neutral = adjust(1.0, 1.0, 1.0);
+        assert!(neutral.is_identity());
+        assert_eq!(
+            neutral.matrix(),
+            [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0]
+            ]
+        );
+        assert!(!adjust(1.0, 1.0, 0.0).is_identity());
+        assert_eq!(ColorAdjust::from(&OutputConfig::default()), neutral);
+    }
+
+    #[test]
+    fn test_brightness_and_contrast() {
+        // Half brightness halves every channel
+        let dim = apply(&adjust(0.5, 1.0, 1.0).matrix(), [0.8, 0.4, 0.2]);
+        assert_eq!(dim, [0.4, 0.2, 0.1]);
+        // Zero contrast flattens everything to mid-grey; mid-grey itself is
+        // a fixed point of any contrast
+        let flat = apply(&adjust(1.0, 0.0, 1.0).matrix(), [0.9, 0.1, 0.3]);
+        assert_eq!(flat, [0.5, 0.5, 0.5]);
+        let grey = apply(&adjust(1.0, 1.7, 1.0).matrix(), [0.5, 0.5, 0.5]);
+        assert!(grey.iter().all(|c| (c - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_window_filter_matrices() {
+        assert_eq!(filter_matrix(ColorFilter::None), None);
+        let invert = filter_matrix(ColorFilter::Invert).unwrap();
+        assert_eq!(apply(&invert, [1.0, 0.25, 0.0]), [0.0, 0.75, 1.0]);
+        assert_eq!(
+            filter_matrix(ColorFilter::Grayscale),
+            Some(adjust(1.0, 1.0, 0.0).matrix())
+        );
+    }
+
+    #[test]
+    fn test_gl_matrix_is_column_major() {
+        let m = gl_matrix(adjust(1.0, 0.5, 1.0).matrix());
+        // Offsets sit in the last column, which comes last in memory
+        assert_eq!(&m[12..], &[0.25, 0.25, 0.25, 1.0]);
+        assert_eq!(m[0], 0.5);
+        assert_eq!(m[3], 0.0);
+    }
+}
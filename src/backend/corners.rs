@@ -0,0 +1,385 @@
+//! Rounded window corners (`decoration.corner_radius`), anti-aliased over
+//! `decoration.corner_softness` pixels.
+//!
+//! A window's surface is drawn through a texture shader that fades each
+//! corner out along its signed distance to the rounding circle, with a
+//! `smoothstep` across the softness so the edge has no stair steps. The
+//! distance is measured in physical pixels, so the feather is the same
+//! width on screen at any output scale or zoom. The shader also applies
+//! the window's colour filter and dimming, which would otherwise need the
+//! colour-matrix shader or a square shade quad over the rounded corners.
+
+use anyhow::Result;
+use smithay::backend::renderer::gles::{
+    GlesRenderer, GlesTexProgram, Uniform, UniformName, UniformType, UniformValue,
+};
+use smithay::utils::{Buffer, Logical, Physical, Point, Rectangle, Size, Transform};
+
+use super::color::matrix_uniform;
+
+/// Texture shader masking the corners of `rect` in the texture, with the
+/// colour matrix of [`super::color`] applied first.
+const SHADER: &str = r#"
+#version 100
+
+//_DEFINES_
+
+#if defined(EXTERNAL)
+#extension GL_OES_EGL_image_external : require
+#endif
+
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+#else
+precision mediump float;
+#endif
+#if defined(EXTERNAL)
+uniform samplerExternalOES tex;
+#else
+uniform sampler2D tex;
+#endif
+
+uniform float alpha;
+varying vec2 v_coords;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+uniform mat4 color_matrix;
+// Texture size and the rounded part of it, in buffer pixels
+uniform vec2 tex_size;
+uniform vec4 rect;
+// Physical pixels per buffer pixel along each buffer axis
+uniform vec2 scale;
+// Radii of the buffer's top-left, top-right, bottom-right and bottom-left
+// corners, physical pixels
+uniform vec4 radii;
+uniform float feather;
+
+float coverage() {
+    vec2 size = rect.zw * scale;
+    vec2 q = (v_coords * tex_size - rect.xy) * scale - size * 0.5;
+    float r = q.x < 0.0 ? (q.y < 0.0 ? radii.x : radii.w)
+                        : (q.y < 0.0 ? radii.y : radii.z);
+    // Offset from the centre of the corner's circle, if inside its square
+    vec2 d = abs(q) - (size * 0.5 - r);
+    if (r <= 0.0 || d.x <= 0.0 || d.y <= 0.0)
+        return 1.0;
+    float dist = length(d) - r;
+    if (feather <= 0.0)
+        return step(dist, 0.0);
+    return 1.0 - smoothstep(-0.5 * feather, 0.5 * feather, dist);
+}
+
+void main() {
+    vec4 color = texture2D(tex, v_coords);
+#if defined(NO_ALPHA)
+    color.a = 1.0;
+#endif
+    // Textures are premultiplied; the matrix applies to straight colour
+    if (color.a > 0.0) {
+        vec3 rgb = (color_matrix * vec4(color.rgb / color.a, 1.0)).rgb;
+        color.rgb = clamp(rgb, 0.0, 1.0) * color.a;
+    }
+    color = color * alpha * coverage();
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        color = vec4(0.0, 0.2, 0.0, 0.2) + color * 0.8;
+#endif
+    gl_FragColor = color;
+}
+"#;
+
+/// The colour matrix that changes nothing.
+const IDENTITY: [[f32; 4]; 3] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+];
+
+/// How one window's surfaces are drawn through the corner shader.
+#[derive(Debug, Clone)]
+pub(super) struct WindowCorners {
+    pub(super) program: GlesTexProgram,
+    /// The window's colour filter, darkened by its dim level
+    matrix: [[f32; 4]; 3],
+    /// Top-left, top-right, bottom-right and bottom-left radii in logical
+    /// pixels
+    radii: [f32; 4],
+    /// Width of the anti-aliased edge in physical pixels
+    softness: f32,
+}
+
+impl WindowCorners {
+    /// Corners for a window with colour filter `filter` (if any) dimmed by
+    /// `dim` (0.0 - 1.0).
+    pub(super) fn new(
+        program: GlesTexProgram,
+        filter: Option<[[f32; 4]; 3]>,
+        dim: f32,
+        radii: [f32; 4],
+        softness: f32,
+    ) -> Self {
+        Self {
+            program,
+            matrix: shading_matrix(filter, dim),
+            radii,
+            softness,
+        }
+    }
+
+    /// The same shading with square corners, for subsurfaces.
+    pub(super) fn square(&self) -> Self {
+        Self {
+            radii: [0.0; 4],
+            ..self.clone()
+        }
+    }
+
+    /// Uniforms for drawing `draw_src` of a `tex_size` texture into
+    /// `draw_dst`, where the whole surface shows `src` at `logical` size.
+    pub(super) fn uniforms(
+        &self,
+        tex_size: Size<i32, Buffer>,
+        src: Rectangle<f64, Buffer>,
+        logical: Size<i32, Logical>,
+        (draw_src, draw_dst): (Rectangle<f64, Buffer>, Rectangle<i32, Physical>),
+        transform: Transform,
+    ) -> Vec<Uniform<'static>> {
+        let mask = CornerMask::new(
+            self.radii,
+            self.softness,
+            src,
+            logical,
+            (draw_src, draw_dst),
+            transform,
+        );
+        vec![
+            matrix_uniform(self.matrix),
+            Uniform::new(
+                "tex_size",
+                UniformValue::_2f(tex_size.w as f32, tex_size.h as f32),
+            ),
+            Uniform::new(
+                "rect",
+                UniformValue::_4f(
+                    src.loc.x as f32,
+                    src.loc.y as f32,
+                    src.size.w as f32,
+                    src.size.h as f32,
+                ),
+            ),
+            Uniform::new("scale", UniformValue::_2f(mask.scale.0, mask.scale.1)),
+            Uniform::new(
+                "radii",
+                UniformValue::_4f(mask.radii[0], mask.radii[1], mask.radii[2], mask.radii[3]),
+            ),
+            Uniform::new("feather", UniformValue::_1f(mask.feather)),
+        ]
+    }
+}
+
+/// The colour matrix of `filter`, or the identity, scaled down by `dim` so
+/// the window comes out as dark as under a black quad at that alpha.
+fn shading_matrix(filter: Option<[[f32; 4]; 3]>, dim: f32) -> [[f32; 4]; 3] {
+    filter
+        .unwrap_or(IDENTITY)
+        .map(|row| row.map(|v| v * (1.0 - dim)))
+}
+
+/// The physical-pixel quantities of one masked draw.
+#[derive(Debug, Clone, PartialEq)]
+struct CornerMask {
+    /// Physical pixels per buffer pixel along the buffer's x and y axes
+    scale: (f32, f32),
+    /// Radii of the buffer's top-left, top-right, bottom-right and
+    /// bottom-left corners in physical pixels, capped at half the shorter
+    /// side
+    radii: [f32; 4],
+    feather: f32,
+}
+
+impl CornerMask {
+    fn new(
+        radii: [f32; 4],
+        softness: f32,
+        src: Rectangle<f64, Buffer>,
+        logical: Size<i32, Logical>,
+        (draw_src, draw_dst): (Rectangle<f64, Buffer>, Rectangle<i32, Physical>),
+        transform: Transform,
+    ) -> Self {
+        // Buffer axes are swapped on screen by a quarter-turn transform
+        let dst = transform.transform_size(draw_dst.size);
+        let scale = (
+            (dst.w as f64 / draw_src.size.w) as f32,
+            (dst.h as f64 / draw_src.size.h) as f32,
+        );
+        let logical = transform.transform_size(logical);
+        let per_logical = scale.0 * (src.size.w / logical.w.max(1) as f64) as f32;
+        let max_radius = (src.size.w as f32 * scale.0).min(src.size.h as f32 * scale.1) / 2.0;
+        Self {
+            scale,
+            radii: buffer_radii(radii, transform)
+                .map(|r| (r * per_logical).min(max_radius).max(0.0)),
+            feather: softness.max(0.0),
+        }
+    }
+}
+
+/// `radii` of the top-left, top-right, bottom-right and bottom-left corners
+/// as shown on screen, moved to the buffer corners `transform` draws there.
+fn buffer_radii(radii: [f32; 4], transform: Transform) -> [f32; 4] {
+    const CORNERS: [(i32, i32); 4] = [(0, 0), (1, 0), (1, 1), (0, 1)];
+    let unit = Size::<i32, Logical>::from((1, 1));
+    let mut moved = [0.0; 4];
+    for (radius, (x, y)) in radii.into_iter().zip(CORNERS) {
+        let p = transform.transform_point_in(Point::from((x, y)), &unit);
+        if let Some(i) = CORNERS.iter().position(|&c| c == (p.x, p.y)) {
+            moved[i] = radius;
+        }
+    }
+    moved
+}
+
+/// The corner shader, compiled the first frame a window is rounded.
+#[derive(Debug, Default)]
+pub(super) struct CornerPass {
+    program: Option<GlesTexProgram>,
+}
+
+impl CornerPass {
+    /// The corner texture shader, compiled on first use.
+    pub(super) fn program(&mut self, renderer: &mut GlesRenderer) -> Result<GlesTexProgram> {
+        if let Some(program) = &self.program {
+            return Ok(program.clone());
+        }
+        let program = renderer.compile_custom_texture_shader(
+            SHADER,
+            &[
+                UniformName::new("color_matrix", UniformType::Matrix4x4),
+                UniformName::new("tex_size", UniformType::_2f),
+                UniformName::new("rect", UniformType::_4f),
+                UniformName::new("scale", UniformType::_2f),
+                UniformName::new("radii", UniformType::_4f),
+                UniformName::new("feather", UniformType::_1f),
+            ],
+        )?;
+        Ok(self.program.insert(program).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::color::filter_matrix;
+    use crate::window::ColorFilter;
+
+    fn buffer_rect(w: f64, h: f64) -> Rectangle<f64, Buffer> {
+        Rectangle::new(Point::from((0.0, 0.0)), Size::from((w, h)))
+    }
+
+    fn physical_rect(w: i32, h: i32) -> Rectangle<i32, Physical> {
+        Rectangle::new(Point::from((0, 0)), Size::from((w, h)))
+    }
+
+    #[test]
+    fn test_radii_follow_the_device_pixel_scale() {
+        // A scale-2 buffer shown at 400x300 logical on a scale-2 output
+        let src = buffer_rect(800.0, 600.0);
+        let mask = CornerMask::new(
+            [8.0, 8.0, 0.0, 4.0],
+            1.5,
+            src,
+            Size::from((400, 300)),
+            (src, physical_rect(800, 600)),
+            Transform::Normal,
+        );
+        assert_eq!(mask.scale, (1.0, 1.0));
+        assert_eq!(mask.radii, [16.0, 16.0, 0.0, 8.0]);
+        // The feather is in device pixels whatever the scale
+        assert_eq!(mask.feather, 1.5);
+
+        // The same surface drawn at half size, e.g. zoomed out
+        let mask = CornerMask::new(
+            [8.0; 4],
+            1.0,
+            src,
+            Size::from((400, 300)),
+            (src, physical_rect(400, 300)),
+            Transform::Normal,
+        );
+        assert_eq!(mask.scale, (0.5, 0.5));
+        assert_eq!(mask.radii, [8.0; 4]);
+        assert_eq!(mask.feather, 1.0);
+    }
+
+    #[test]
+    fn test_radii_are_capped_and_axes_follow_the_transform() {
+        // A 100x40 buffer rotated a quarter turn shows as 40x100
+        let src = buffer_rect(100.0, 40.0);
+        let mask = CornerMask::new(
+            [64.0; 4],
+            -1.0,
+            src,
+            Size::from((40, 100)),
+            (src, physical_rect(40, 100)),
+            Transform::_90,
+        );
+        assert_eq!(mask.scale, (1.0, 1.0));
+        assert_eq!(mask.radii, [20.0; 4]);
+        assert_eq!(mask.feather, 0.0);
+    }
+
+    #[test]
+    fn test_unequal_radii_follow_the_surface_transform() {
+        let radii = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(buffer_radii(radii, Transform::Normal), radii);
+        assert_eq!(buffer_radii(radii, Transform::_180), [3.0, 4.0, 1.0, 2.0]);
+        assert_eq!(
+            buffer_radii(radii, Transform::Flipped),
+            [2.0, 1.0, 4.0, 3.0]
+        );
+        assert_eq!(
+            buffer_radii(radii, Transform::Flipped180),
+            [4.0, 3.0, 2.0, 1.0]
+        );
+        // Quarter turns rotate the corners one way or the other...
+        let turns = [[2.0, 3.0, 4.0, 1.0], [4.0, 1.0, 2.0, 3.0]];
+        let (a, b) = (
+            buffer_radii(radii, Transform::_90),
+            buffer_radii(radii, Transform::_270),
+        );
+        assert!(turns.contains(&a) && turns.contains(&b) && a != b);
+        // ...and flipped ones mirror them across a diagonal
+        let mirrors = [[1.0, 4.0, 3.0, 2.0], [3.0, 2.0, 1.0, 4.0]];
+        let (a, b) = (
+            buffer_radii(radii, Transform::Flipped90),
+            buffer_radii(radii, Transform::Flipped270),
+        );
+        assert!(mirrors.contains(&a) && mirrors.contains(&b) && a != b);
+
+        // A titlebar's square top lands on a vertical buffer edge
+        let src = buffer_rect(100.0, 40.0);
+        let mask = CornerMask::new(
+            [0.0, 0.0, 8.0, 8.0],
+            1.0,
+            src,
+            Size::from((40, 100)),
+            (src, physical_rect(40, 100)),
+            Transform::_90,
+        );
+        let [tl, tr, br, bl] = mask.radii;
+        assert!((tl == 0.0 && bl == 0.0) != (tr == 0.0 && br == 0.0));
+    }
+
+    #[test]
+    fn test_dim_darkens_the_filter_matrix() {
+        assert_eq!(shading_matrix(None, 0.0), IDENTITY);
+        let dimmed = shading_matrix(None, 0.25);
+        assert_eq!(dimmed[1], [0.0, 0.75, 0.0, 0.0]);
+        // Inverted colours are dimmed too, offsets included
+        let invert = filter_matrix(ColorFilter::Invert);
+        assert_eq!(shading_matrix(invert, 0.5)[0], [-0.5, 0.0, 0.0, 0.5]);
+    }
+}
@@ -0,0 +1,252 @@
+//! Where the pointer image is shown.
+//!
+//! Drawn into the frame like any other surface, the cursor makes every
+//! pointer move a full recomposite. A DRM/KMS output with a cursor plane can
+//! instead scan the image out on its own and move it with an atomic commit,
+//! leaving the windows alone. [`Cursor`] picks between the two at runtime:
+//! it uses a [`CursorPlane`] when the backend handed it one and the plane
+//! accepts the image, and falls back to the software overlay otherwise. The
+//! winit backend runs nested inside another compositor and has no planes,
+//! so it always takes the software path; so do headless runs.
+
+use smithay::reexports::wayland_server::protocol::wl_shm;
+use smithay::wayland::compositor::{with_states, BufferAssignment, SurfaceAttributes};
+use smithay::wayland::shm::with_buffer_contents;
+use wayland_server::protocol::wl_surface::WlSurface;
+
+/// A cursor image ready for a plane: 8-bit ARGB, little-endian (`B, G, R,
+/// A` in memory), rows packed with no padding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorImage {
+    pub size: (u32, u32),
+    /// Pointer position within the image
+    pub hotspot: (i32, i32),
+    pub argb: Vec<u8>,
+}
+
+/// A hardware plane that shows the cursor on top of the composited frame.
+/// Backends that drive one install it with
+/// [`AxiomSmithayBackendReal::set_cursor_plane`](super::AxiomSmithayBackendReal::set_cursor_plane).
+pub trait CursorPlane: std::fmt::Debug {
+    /// Scan out `image`. `false` if the plane can't take it (too large, a
+    /// format it doesn't support...), in which case nothing changes.
+    fn set_image(&mut self, image: &CursorImage) -> bool;
+    /// Put the image's top-left corner at `(x, y)`.
+    fn set_position(&mut self, x: i32, y: i32);
+    /// Stop showing anything.
+    fn hide(&mut self);
+}
+
+/// Software or hardware cursor, decided per image.
+#[derive(Debug, Default)]
+pub(super) struct Cursor {
+    plane: Option<Box<dyn CursorPlane>>,
+    /// The plane holds the current image, so the frame must not draw it
+    on_plane: bool,
+}
+
+impl Cursor {
+    /// A cursor drawn into every frame; what nested and headless backends use.
+    pub(super) fn software() -> Self {
+        Self::default()
+    }
+
+    /// A cursor that goes on `plane` whenever the plane accepts the image.
+    pub(super) fn with_plane(plane: Box<dyn CursorPlane>) -> Self {
+        Self {
+            plane: Some(plane),
+            on_plane: false,
+        }
+    }
+
+    /// Whether there is a plane to offer images to; without one there's no
+    /// point reading a cursor surface's pixels back.
+    pub(super) fn has_plane(&self) -> bool {
+        self.plane.is_some()
+    }
+
+    /// The client cursor image changed. `None` (a themed cursor, a hidden
+    /// one or an image that couldn't be read) clears the plane and leaves
+    /// the cursor to the software path.
+    pub(super) fn set_image(&mut self, image: Option<&CursorImage>, draw_at: (i32, i32)) {
+        let Some(plane) = self.plane.as_mut() else {
+            return;
+        };
+        self.on_plane = match image {
+            Some(image) if plane.set_image(image) => {
+                plane.set_position(draw_at.0, draw_at.1);
+                true
+            }
+            _ => {
+                plane.hide();
+                false
+            }
+        };
+    }
+
+    /// The pointer moved so the image's top-left is now at `draw_at`.
+    /// Returns whether the frame has to be redrawn to show it there.
+    pub(super) fn moved(&mut self, draw_at: (i32, i32)) -> bool {
+        match self.plane.as_mut() {
+            Some(plane) if self.on_plane => {
+                plane.set_position(draw_at.0, draw_at.1);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether the render pass draws the cursor as an overlay on the frame.
+    pub(super) fn draws_in_software(&self) -> bool {
+        !self.on_plane
+    }
+}
+
+/// Read `surface`'s shm buffer into a [`CursorImage`]. `None` for buffers
+/// that aren't shm or aren't (X|A)RGB8888; those stay software cursors.
+pub(super) fn shm_cursor_image(surface: &WlSurface, hotspot: (i32, i32)) -> Option<CursorImage> {
+    let buffer = with_states(surface, |states| {
+        match &states
+            .cached_state
+            .get::<SurfaceAttributes>()
+            .current()
+            .buffer
+        {
+            Some(BufferAssignment::NewBuffer(buffer)) => Some(buffer.clone()),
+            _ => None,
+        }
+    })?;
+    with_buffer_contents(&buffer, |ptr, len, data| {
+        let opaque = match data.format {
+            wl_shm::Format::Argb8888 => false,
+            wl_shm::Format::Xrgb8888 => true,
+            _ => return None,
+        };
+        let (width, height, stride) = (data.width, data.height, data.stride);
+        if width <= 0 || height <= 0 || stride < width * 4 || data.offset < 0 {
+            return None;
+        }
+        let end = data.offset as usize + stride as usize * height as usize;
+        if end > len {
+            return None;
+        }
+        // SAFETY: the pool mapping is `len` bytes long and the rows read
+        // were just checked to lie within it.
+        let pool = unsafe { std::slice::from_raw_parts(ptr, len) };
+        let mut argb = Vec::with_capacity(width as usize * height as usize * 4);
+        for row in 0..height as usize {
+            let start = data.offset as usize + row * stride as usize;
+            argb.extend_from_slice(&pool[start..start + width as usize * 4]);
+        }
+        if opaque {
+            argb.chunks_exact_mut(4).for_each(|px| px[3] = 0xff);
+        }
+        Some(CursorImage {
+            size: (width as u32, height as u32),
+            hotspot,
+            argb,
+        })
+    })
+    .ok()
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// What a fake plane was asked to do.
+    #[derive(Debug, Default)]
+    struct PlaneLog {
+        image: Option<CursorImage>,
+        position: Option<(i32, i32)>,
+        hidden: bool,
+    }
+
+    /// A plane that takes images up to `max` pixels square.
+    #[derive(Debug)]
+    struct FakePlane {
+        max: u32,
+        log: Rc<RefCell<PlaneLog>>,
+    }
+
+    impl CursorPlane for FakePlane {
+        fn set_image(&mut self, image: &CursorImage) -> bool {
+            let fits = image.size.0 <= self.max && image.size.1 <= self.max;
+            if fits {
+                let mut log = self.log.borrow_mut();
+                log.image = Some(image.clone());
+                log.hidden = false;
+            }
+            fits
+        }
+        fn set_position(&mut self, x: i32, y: i32) {
+            self.log.borrow_mut().position = Some((x, y));
+        }
+        fn hide(&mut self) {
+            self.log.borrow_mut().hidden = true;
+        }
+    }
+
+    fn image(size: u32) -> CursorImage {
+        CursorImage {
+            size: (size, size),
+            hotspot: (1, 1),
+            argb: vec![0xff; (size * size * 4) as usize],
+        }
+    }
+
+    #[test]
+    fn test_software_fallback_draws_cursor_as_overlay() {
+        let mut cursor = Cursor::software();
+        assert!(!cursor.has_plane());
+        cursor.set_image(Some(&image(24)), (10, 10));
+        // Nothing to put it on: the frame draws it, so moving it redraws
+        assert!(cursor.draws_in_software());
+        assert!(cursor.moved((20, 20)));
+        assert!(cursor.draws_in_software());
+    }
+
+    #[test]
+    fn test_plane_moves_cursor_without_redraw() {
+        let log = Rc::new(RefCell::new(PlaneLog::default()));
+        let mut cursor = Cursor::with_plane(Box::new(FakePlane {
+            max: 64,
+            log: log.clone(),
+        }));
+        // No image yet: software until the plane has one
+        assert!(cursor.draws_in_software());
+
+        cursor.set_image(Some(&image(24)), (10, 10));
+        assert!(!cursor.draws_in_software());
+        assert_eq!(log.borrow().position, Some((10, 10)));
+        assert!(!cursor.moved((300, 200)));
+        assert_eq!(log.borrow().position, Some((300, 200)));
+        assert_eq!(log.borrow().image, Some(image(24)));
+    }
+
+    #[test]
+    fn test_plane_falls_back_for_images_it_cannot_take() {
+        let log = Rc::new(RefCell::new(PlaneLog::default()));
+        let mut cursor = Cursor::with_plane(Box::new(FakePlane {
+            max: 64,
+            log: log.clone(),
+        }));
+        cursor.set_image(Some(&image(24)), (0, 0));
+        assert!(!cursor.draws_in_software());
+
+        // Too big for the plane: back to drawing it, plane cleared
+        cursor.set_image(Some(&image(128)), (0, 0));
+        assert!(cursor.draws_in_software());
+        assert!(log.borrow().hidden);
+        assert!(cursor.moved((5, 5)));
+
+        // A themed or hidden cursor also leaves the plane empty
+        cursor.set_image(Some(&image(24)), (0, 0));
+        cursor.set_image(None, (0, 0));
+        assert!(cursor.draws_in_software());
+        assert!(log.borrow().hidden);
+    }
+}
@@ -0,0 +1,377 @@
+//! Toplevel management for taskbars and docks
+//! (`zwlr_foreign_toplevel_management_v1`).
+//!
+//! Every bound manager gets a handle per window, carrying its title, app id,
+//! state and output. Handles are brought up to date once per cycle, after
+//! client dispatch, so a change is reported however it came about: the
+//! window's own client, a key binding, IPC or another taskbar. Requests on a
+//! handle act on the window the way the matching key binding would.
+
+use std::collections::HashMap;
+
+use log::debug;
+use smithay::reexports::wayland_protocols_wlr::foreign_toplevel::v1::server::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New,
+};
+use smithay::utils::SERIAL_COUNTER;
+use wayland_server::backend::ClientId;
+use wayland_server::Resource;
+
+use super::State;
+
+/// What a handle tells its taskbar about a window.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct ToplevelInfo {
+    pub(super) title: String,
+    pub(super) app_id: String,
+    pub(super) maximized: bool,
+    pub(super) minimized: bool,
+    pub(super) activated: bool,
+    pub(super) fullscreen: bool,
+    /// Name of the output the window is on
+    pub(super) output: Option<String>,
+}
+
+impl ToplevelInfo {
+    /// The `state` event's array: one native-endian `u32` per state set.
+    pub(super) fn states(&self) -> Vec<u8> {
+        use zwlr_foreign_toplevel_handle_v1::State as S;
+        [
+            (self.maximized, S::Maximized),
+            (self.minimized, S::Minimized),
+            (self.activated, S::Activated),
+            (self.fullscreen, S::Fullscreen),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .flat_map(|(_, state)| (state as u32).to_ne_bytes())
+        .collect()
+    }
+}
+
+/// Bound managers and the handles announced to them.
+#[derive(Debug, Default)]
+pub(super) struct WlrToplevels {
+    managers: Vec<ZwlrForeignToplevelManagerV1>,
+    /// Bound since the last refresh, not yet told about any window
+    new_managers: Vec<ZwlrForeignToplevelManagerV1>,
+    windows: HashMap<u64, Announced>,
+}
+
+#[derive(Debug)]
+struct Announced {
+    /// What every handle was last told
+    info: ToplevelInfo,
+    handles: Vec<ZwlrForeignToplevelHandleV1>,
+}
+
+/// Advertise the manager global.
+pub(super) fn create_global(dh: &DisplayHandle) {
+    dh.create_global::<State, ZwlrForeignToplevelManagerV1, _>(3, ());
+}
+
+impl State {
+    /// The current info of every window a taskbar should list.
+    fn toplevel_infos(&self) -> HashMap<u64, ToplevelInfo> {
+        let wm = self.window_manager.read();
+        let ws = self.workspace_manager.read();
+        let focused = wm.focused_window_id();
+        self.window_map
+            .keys()
+            .filter_map(|&id| {
+                let w = wm.get_window(id)?;
+                Some((
+                    id,
+                    ToplevelInfo {
+                        title: w.window.title.clone(),
+                        app_id: w.window.app_id.clone().unwrap_or_default(),
+                        maximized: w.properties.maximized,
+                        minimized: w.properties.minimized,
+                        activated: focused == Some(id),
+                        fullscreen: w.properties.fullscreen,
+                        output: ws.window_output_id(id).map(str::to_string),
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Send `handle`'s client the `output_enter` (or `output_leave`) of
+    /// every `wl_output` it bound for output `name`.
+    fn send_output(&self, handle: &ZwlrForeignToplevelHandleV1, name: &str, enter: bool) {
+        let Some(client) = handle.client() else {
+            return;
+        };
+        let Some(output) = self.outputs.iter().find(|o| o.name() == name) else {
+            return;
+        };
+        for wl_output in output.client_outputs(&client) {
+            if enter {
+                handle.output_enter(&wl_output);
+            } else {
+                handle.output_leave(&wl_output);
+            }
+        }
+    }
+
+    /// Tell `handle` whatever differs between `old` and `new`, then `done`.
+    fn send_changes(
+        &self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        old: &ToplevelInfo,
+        new: &ToplevelInfo,
+    ) {
+        if old.title != new.title {
+            handle.title(new.title.clone());
+        }
+        if old.app_id != new.app_id {
+            handle.app_id(new.app_id.clone());
+        }
+        if old.output != new.output {
+            if let Some(name) = &old.output {
+                self.send_output(handle, name, false);
+            }
+            if let Some(name) = &new.output {
+                self.send_output(handle, name, true);
+            }
+        }
+        if old.states() != new.states() {
+            handle.state(new.states());
+        }
+        handle.done();
+    }
+
+    /// Create a handle for window `id` on `manager` and describe the window.
+    fn announce(
+        &self,
+        dh: &DisplayHandle,
+        manager: &ZwlrForeignToplevelManagerV1,
+        id: u64,
+        info: &ToplevelInfo,
+    ) -> Option<ZwlrForeignToplevelHandleV1> {
+        let client = manager.client()?;
+        let handle = client
+            .create_resource::<ZwlrForeignToplevelHandleV1, u64, State>(dh, manager.version(), id)
+            .ok()?;
+        manager.toplevel(&handle);
+        // Title and app id always go out; the rest only if set
+        handle.title(info.title.clone());
+        handle.app_id(info.app_id.clone());
+        let sent = ToplevelInfo {
+            title: info.title.clone(),
+            app_id: info.app_id.clone(),
+            ..Default::default()
+        };
+        self.send_changes(&handle, &sent, info);
+        Some(handle)
+    }
+
+    /// Bring every handle up to date: announce new windows (and every
+    /// window to newly bound managers), report changes and close the
+    /// handles of windows that went away.
+    pub(super) fn refresh_wlr_toplevels(&mut self) {
+        let Some(dh) = self.display_handle.clone() else {
+            return;
+        };
+        let infos = self.toplevel_infos();
+        let mut toplevels = std::mem::take(&mut self.wlr_toplevels);
+        toplevels.managers.retain(|m| m.is_alive());
+        toplevels.new_managers.retain(|m| m.is_alive());
+
+        toplevels.windows.retain(|id, announced| {
+            if infos.contains_key(id) {
+                return true;
+            }
+            for handle in &announced.handles {
+                handle.closed();
+            }
+            false
+        });
+
+        for (&id, info) in &infos {
+            match toplevels.windows.get_mut(&id) {
+                Some(announced) => {
+                    announced.handles.retain(|h| h.is_alive());
+                    if announced.info != *info {
+                        for handle in &announced.handles {
+                            self.send_changes(handle, &announced.info, info);
+                        }
+                        announced.info = info.clone();
+                    }
+                    for manager in &toplevels.new_managers {
+                        announced
+                            .handles
+                            .extend(self.announce(&dh, manager, id, info));
+                    }
+                }
+                None => {
+                    let handles = toplevels
+                        .managers
+                        .iter()
+                        .chain(&toplevels.new_managers)
+                        .filter_map(|manager| self.announce(&dh, manager, id, info))
+                        .collect();
+                    toplevels.windows.insert(
+                        id,
+                        Announced {
+                            info: info.clone(),
+                            handles,
+                        },
+                    );
+                }
+            }
+        }
+
+        let new_managers = std::mem::take(&mut toplevels.new_managers);
+        toplevels.managers.extend(new_managers);
+        self.wlr_toplevels = toplevels;
+    }
+
+    /// Hide or bring back a window, as its minimize button does.
+    fn set_window_minimized(&mut self, window_id: u64, minimized: bool) {
+        if self.window_manager.read().is_minimized(window_id) == minimized {
+            return;
+        }
+        if minimized {
+            self.workspace_manager.write().minimize_window(window_id);
+            self.window_manager.write().minimize_window(window_id);
+        } else {
+            self.workspace_manager.write().restore_window(window_id);
+            self.window_manager.write().restore_window(window_id);
+        }
+        self.needs_redraw = true;
+    }
+
+    /// Bring a window back if minimized and give it keyboard focus.
+    fn activate_window(&mut self, window_id: u64) {
+        self.set_window_minimized(window_id, false);
+        let surface = self
+            .window_map
+            .get(&window_id)
+            .and_then(|surface_id| self.surfaces.get(surface_id))
+            .and_then(|sd| sd.surface.clone())
+            .filter(|surface| surface.is_alive());
+        let Some(surface) = surface else {
+            return;
+        };
+        match self.seat.get_keyboard() {
+            // `focus_changed` records the focus and scrolls the window into view
+            Some(keyboard) => {
+                keyboard.set_focus(self, Some(surface), SERIAL_COUNTER.next_serial());
+            }
+            None => self.update_focus_state(Some(window_id)),
+        }
+    }
+}
+
+impl GlobalDispatch<ZwlrForeignToplevelManagerV1, ()> for State {
+    fn bind(
+        state: &mut State,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrForeignToplevelManagerV1>,
+        _data: &(),
+        data_init: &mut DataInit<'_, State>,
+    ) {
+        let manager = data_init.init(resource, ());
+        state.wlr_toplevels.new_managers.push(manager);
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, (), State> for State {
+    fn request(
+        state: &mut State,
+        _client: &Client,
+        resource: &ZwlrForeignToplevelManagerV1,
+        request: zwlr_foreign_toplevel_manager_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, State>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Request::Stop = request {
+            let toplevels = &mut state.wlr_toplevels;
+            toplevels.managers.retain(|m| m != resource);
+            toplevels.new_managers.retain(|m| m != resource);
+            resource.finished();
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, u64, State> for State {
+    fn request(
+        state: &mut State,
+        _client: &Client,
+        _resource: &ZwlrForeignToplevelHandleV1,
+        request: zwlr_foreign_toplevel_handle_v1::Request,
+        &window_id: &u64,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, State>,
+    ) {
+        use zwlr_foreign_toplevel_handle_v1::Request;
+
+        let Some((maximized, fullscreen)) = state
+            .window_manager
+            .read()
+            .get_window(window_id)
+            .map(|w| (w.properties.maximized, w.properties.fullscreen))
+        else {
+            return;
+        };
+        debug!("📋 Taskbar request for window {}: {:?}", window_id, request);
+        match request {
+            Request::SetMaximized | Request::UnsetMaximized => {
+                if maximized != matches!(request, Request::SetMaximized) {
+                    state.toggle_maximize_window(window_id);
+                }
+            }
+            Request::SetFullscreen { .. } | Request::UnsetFullscreen => {
+                if fullscreen != matches!(request, Request::SetFullscreen { .. }) {
+                    state.toggle_fullscreen_window(window_id);
+                }
+            }
+            Request::SetMinimized => state.set_window_minimized(window_id, true),
+            Request::UnsetMinimized => state.set_window_minimized(window_id, false),
+            Request::Activate { .. } => state.activate_window(window_id),
+            Request::Close => state.request_window_close(window_id),
+            // Where the taskbar button is; only used for minimize animations
+            Request::SetRectangle { .. } => {}
+            _ => {}
+        }
+        state.needs_redraw = true;
+    }
+
+    fn destroyed(
+        state: &mut State,
+        _client: ClientId,
+        resource: &ZwlrForeignToplevelHandleV1,
+        &window_id: &u64,
+    ) {
+        if let Some(announced) = state.wlr_toplevels.windows.get_mut(&window_id) {
+            announced.handles.retain(|h| h != resource);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_states_array_lists_each_set_state() {
+        let info = ToplevelInfo {
+            maximized: true,
+            activated: true,
+            ..Default::default()
+        };
+        let expected: Vec<u8> = [0u32, 2]
+            .iter()
+            .flat_map(|state| state.to_ne_bytes())
+            .collect();
+        assert_eq!(info.states(), expected);
+        assert!(ToplevelInfo::default().states().is_empty());
+    }
+}
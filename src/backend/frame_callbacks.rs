@@ -0,0 +1,186 @@
+//! Frame-callback pacing.
+//!
+//! `wl_surface.frame` callbacks tell a client when it is worth drawing its
+//! next frame. They are only fired for windows that are visible on an
+//! output that just presented; occluded and offscreen windows stop getting
+//! them, so well-behaved clients throttle themselves until they are shown
+//! again.
+
+use crate::window::Rectangle as WindowRectangle;
+use smithay::output::Output;
+use smithay::reexports::wayland_protocols::wp::presentation_time::server::wp_presentation_feedback;
+use smithay::utils::{Monotonic, Time};
+use smithay::wayland::compositor::{with_surface_tree_downward, SurfaceAttributes, TraversalAction};
+use smithay::wayland::presentation::{PresentationFeedbackCachedState, Refresh};
+use std::collections::HashMap;
+use std::time::Duration;
+use wayland_server::protocol::wl_surface::WlSurface;
+
+/// When set to anything but `0`, a window spanning several outputs gets a
+/// callback on each of their refreshes instead of only on its primary
+/// output's (the one showing most of it).
+pub(super) const SPLIT_FRAME_CALLBACKS_ENV: &str = "AXIOM_SPLIT_FRAME_CALLBACKS";
+
+/// Decides which windows are owed a frame callback when an output presents.
+#[derive(Debug, Default)]
+pub(super) struct FrameCallbackScheduler {
+    split_across_outputs: bool,
+    /// Outputs each window was visible on in the last render pass, primary
+    /// output first. Windows missing from the map get no callbacks.
+    visible_on: HashMap<u64, Vec<String>>,
+}
+
+impl FrameCallbackScheduler {
+    pub(super) fn from_env() -> Self {
+        Self {
+            split_across_outputs: std::env::var(SPLIT_FRAME_CALLBACKS_ENV)
+                .is_ok_and(|v| v != "0"),
+            visible_on: HashMap::new(),
+        }
+    }
+
+    /// Replace the visibility recorded by the previous render pass.
+    pub(super) fn set_visibility(&mut self, visible_on: HashMap<u64, Vec<String>>) {
+        self.visible_on = visible_on;
+    }
+
+    /// Windows whose callbacks should fire now that `output` presented.
+    pub(super) fn windows_for_output(&self, output: &str) -> Vec<u64> {
+        self.visible_on
+            .iter()
+            .filter(|(_, outputs)| {
+                if self.split_across_outputs {
+                    outputs.iter().any(|o| o == output)
+                } else {
+                    outputs.first().is_some_and(|o| o == output)
+                }
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// Outputs `rect` overlaps, ordered by overlap area (largest first).
+pub(super) fn outputs_showing(
+    rect: &WindowRectangle,
+    outputs: &[(String, WindowRectangle)],
+) -> Vec<String> {
+    let mut overlaps: Vec<(i64, &String)> = outputs
+        .iter()
+        .filter_map(|(name, geometry)| {
+            let w = (rect.x + rect.width as i32).min(geometry.x + geometry.width as i32)
+                - rect.x.max(geometry.x);
+            let h = (rect.y + rect.height as i32).min(geometry.y + geometry.height as i32)
+                - rect.y.max(geometry.y);
+            (w > 0 && h > 0).then_some((w as i64 * h as i64, name))
+        })
+        .collect();
+    overlaps.sort_by(|a, b| b.0.cmp(&a.0));
+    overlaps.into_iter().map(|(_, name)| name.clone()).collect()
+}
+
+/// Fire every pending frame callback in `surface`'s tree.
+pub(super) fn send_frame_callbacks(surface: &WlSurface, time: Duration) {
+    let time_ms = time.as_millis() as u32;
+    with_surface_tree_downward(
+        surface,
+        (),
+        |_, _, &()| TraversalAction::DoChildren(()),
+        |_, states, &()| {
+            for callback in states
+                .cached_state
+                .get::<SurfaceAttributes>()
+                .current()
+                .frame_callbacks
+                .drain(..)
+            {
+                callback.done(time_ms);
+            }
+        },
+        |_, _, &()| true,
+    );
+}
+
+/// A frame that just reached the screen, as `wp_presentation_feedback`
+/// reports it.
+pub(super) struct PresentedFrame {
+    pub(super) output: Output,
+    pub(super) time: Time<Monotonic>,
+    pub(super) refresh: Duration,
+    /// Frames presented so far, standing in for the vblank counter
+    pub(super) seq: u64,
+}
+
+/// Answer every pending presentation feedback request in `surface`'s tree.
+pub(super) fn send_presentation_feedback(surface: &WlSurface, frame: &PresentedFrame) {
+    with_surface_tree_downward(
+        surface,
+        (),
+        |_, _, &()| TraversalAction::DoChildren(()),
+        |_, states, &()| {
+            for callback in states
+                .cached_state
+                .get::<PresentationFeedbackCachedState>()
+                .current()
+                .callbacks
+                .drain(..)
+            {
+                callback.presented(
+                    &frame.output,
+                    frame.time,
+                    Refresh::Fixed(frame.refresh),
+                    frame.seq,
+                    wp_presentation_feedback::Kind::Vsync,
+                );
+            }
+        },
+        |_, _, &()| true,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outputs() -> Vec<(String, WindowRectangle)> {
+        vec![
+            ("left".to_string(), WindowRectangle::from_loc_and_size((0, 0), (1920, 1080))),
+            ("right".to_string(), WindowRectangle::from_loc_and_size((1920, 0), (1920, 1080))),
+        ]
+    }
+
+    #[test]
+    fn test_occluded_window_gets_no_callback_until_visible() {
+        let mut scheduler = FrameCallbackScheduler::default();
+        // Window 2 is occluded, so the render pass leaves it out
+        scheduler.set_visibility(HashMap::from([(1, vec!["left".to_string()])]));
+        assert_eq!(scheduler.windows_for_output("left"), vec![1]);
+
+        // Window 1 goes away and window 2 is uncovered
+        scheduler.set_visibility(HashMap::from([(2, vec!["left".to_string()])]));
+        assert_eq!(scheduler.windows_for_output("left"), vec![2]);
+        assert!(scheduler.windows_for_output("right").is_empty());
+    }
+
+    #[test]
+    fn test_spanning_window_is_paced_by_primary_output_unless_split() {
+        let rect = WindowRectangle::from_loc_and_size((1500, 0), (800, 600));
+        let showing = outputs_showing(&rect, &outputs());
+        assert_eq!(showing, vec!["left".to_string(), "right".to_string()]);
+        let offscreen = WindowRectangle::from_loc_and_size((4000, 0), (800, 600));
+        assert!(outputs_showing(&offscreen, &outputs()).is_empty());
+
+        let mut scheduler = FrameCallbackScheduler::default();
+        scheduler.set_visibility(HashMap::from([(7, showing.clone())]));
+        assert_eq!(scheduler.windows_for_output("left"), vec![7]);
+        assert!(scheduler.windows_for_output("right").is_empty());
+
+        let mut split = FrameCallbackScheduler {
+            split_across_outputs: true,
+            ..Default::default()
+        };
+        split.set_visibility(HashMap::from([(7, showing)]));
+        assert_eq!(split.windows_for_output("left"), vec![7]);
+        assert_eq!(split.windows_for_output("right"), vec![7]);
+    }
+}
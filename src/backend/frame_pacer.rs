@@ -0,0 +1,154 @@
+//! Vblank-aligned redraw scheduling.
+//!
+//! Ticking on a fixed timer drifts against the display: some refreshes get
+//! two frames and some none, which shows up as uneven motion. The pacer
+//! instead projects the refresh grid forward from the last present and
+//! hands out the next point on it. Winit reports no hardware timestamps, so
+//! the time a vsynced submit returns stands in for the vblank; the same
+//! timestamp goes out in `wp_presentation` feedback.
+
+use std::time::{Duration, Instant};
+
+/// Refresh assumed until an output reports its mode: 60 Hz, in mHz.
+pub(super) const DEFAULT_REFRESH_MHZ: i32 = 60_000;
+
+/// Predicts when the output will next refresh.
+#[derive(Debug, Clone)]
+pub(super) struct FramePacer {
+    refresh: Duration,
+    last_present: Option<Instant>,
+}
+
+impl FramePacer {
+    pub(super) fn new(refresh_mhz: i32) -> Self {
+        Self {
+            refresh: refresh_from_mhz(refresh_mhz),
+            last_present: None,
+        }
+    }
+
+    /// The output's refresh changed, in mHz as `wl_output` reports it.
+    /// Non-positive rates (unknown) keep the previous one.
+    pub(super) fn set_refresh_mhz(&mut self, refresh_mhz: i32) {
+        if refresh_mhz > 0 {
+            self.refresh = refresh_from_mhz(refresh_mhz);
+        }
+    }
+
+    /// Duration of one refresh cycle.
+    pub(super) fn refresh(&self) -> Duration {
+        self.refresh
+    }
+
+    /// A frame reached the screen at `at`.
+    pub(super) fn presented(&mut self, at: Instant) {
+        self.last_present = Some(at);
+    }
+
+    /// When to start the next redraw: the first predicted vblank at or
+    /// after `now` that is also `min_interval` after the last present, so
+    /// a frame-rate cap skips whole refreshes rather than landing between
+    /// them. The cap gets a quarter refresh of slack, or a 60 fps cap on a
+    /// 60 Hz output would lose every other frame to rounding. Before
+    /// anything was presented there is no grid to align to, so this is one
+    /// refresh (or `min_interval`) away.
+    pub(super) fn next_deadline(&self, now: Instant, min_interval: Duration) -> Instant {
+        let Some(last) = self.last_present else {
+            return now + self.refresh.max(min_interval);
+        };
+        let cap = min_interval.saturating_sub(self.refresh / 4);
+        let earliest = now.max(last + cap);
+        let elapsed = earliest.saturating_duration_since(last);
+        let period = self.refresh.as_nanos().max(1);
+        let cycles = elapsed.as_nanos().div_ceil(period).max(1);
+        last + self.refresh * u32::try_from(cycles).unwrap_or(u32::MAX)
+    }
+}
+
+fn refresh_from_mhz(refresh_mhz: i32) -> Duration {
+    let refresh_mhz = if refresh_mhz > 0 {
+        refresh_mhz
+    } else {
+        DEFAULT_REFRESH_MHZ
+    };
+    Duration::from_nanos(1_000_000_000_000 / refresh_mhz as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_next_deadline_at_60hz() {
+        let mut pacer = FramePacer::new(60_000);
+        assert_eq!(pacer.refresh(), Duration::from_nanos(16_666_666));
+        let t0 = Instant::now();
+        pacer.presented(t0);
+
+        // Right after a present: the following vblank
+        assert_eq!(
+            pacer.next_deadline(t0 + 2 * MS, Duration::ZERO),
+            t0 + pacer.refresh()
+        );
+        // Ran long and missed one: skip to the one after, on the same grid
+        assert_eq!(
+            pacer.next_deadline(t0 + 20 * MS, Duration::ZERO),
+            t0 + pacer.refresh() * 2
+        );
+        // Exactly on a vblank counts as that vblank
+        let on_grid = t0 + pacer.refresh() * 3;
+        assert_eq!(pacer.next_deadline(on_grid, Duration::ZERO), on_grid);
+        // A 60 fps cap doesn't hold anything back, a 30 fps one takes
+        // every other refresh
+        assert_eq!(
+            pacer.next_deadline(t0 + MS, Duration::from_secs_f64(1.0 / 60.0)),
+            t0 + pacer.refresh()
+        );
+        assert_eq!(
+            pacer.next_deadline(t0 + MS, Duration::from_secs_f64(1.0 / 30.0)),
+            t0 + pacer.refresh() * 2
+        );
+    }
+
+    #[test]
+    fn test_next_deadline_at_144hz() {
+        let mut pacer = FramePacer::new(60_000);
+        pacer.set_refresh_mhz(144_000);
+        assert_eq!(pacer.refresh(), Duration::from_nanos(6_944_444));
+        let t0 = Instant::now();
+        pacer.presented(t0);
+
+        assert_eq!(
+            pacer.next_deadline(t0 + MS, Duration::ZERO),
+            t0 + pacer.refresh()
+        );
+        assert_eq!(
+            pacer.next_deadline(t0 + 15 * MS, Duration::ZERO),
+            t0 + pacer.refresh() * 3
+        );
+        // A 60 fps cap on a 144 Hz output: the third refresh is the first
+        // one a full 1/60 s after the present
+        assert_eq!(
+            pacer.next_deadline(t0 + MS, Duration::from_secs_f64(1.0 / 60.0)),
+            t0 + pacer.refresh() * 3
+        );
+
+        // Unknown rates leave it alone
+        pacer.set_refresh_mhz(0);
+        assert_eq!(pacer.refresh(), Duration::from_nanos(6_944_444));
+    }
+
+    #[test]
+    fn test_next_deadline_before_first_present() {
+        let pacer = FramePacer::new(0);
+        assert_eq!(pacer.refresh(), Duration::from_nanos(16_666_666));
+        let now = Instant::now();
+        assert_eq!(
+            pacer.next_deadline(now, Duration::ZERO),
+            now + pacer.refresh()
+        );
+        assert_eq!(pacer.next_deadline(now, 40 * MS), now + 40 * MS);
+    }
+}
@@ -0,0 +1,132 @@
+//! Mapping between global compositor coordinates and output-local ones.
+//!
+//! Outputs are placed in one global space and may sit at negative origins
+//! (a monitor left of or above the primary one). Pointer handling and
+//! per-output damage both need to know which output a point or rect lands
+//! on, so the conversions live here rather than in each caller.
+
+use crate::window::Rectangle as WindowRectangle;
+use crate::workspace::ScrollableWorkspaces;
+use smithay::utils::{Physical, Point, Rectangle, Size};
+
+/// Every known output with its global rectangle, in tape ID order.
+pub(super) fn output_layout(ws: &ScrollableWorkspaces) -> Vec<(String, WindowRectangle)> {
+    ws.known_tape_ids()
+        .into_iter()
+        .filter_map(|id| ws.output_geometry(&id).map(|g| (id, g)))
+        .collect()
+}
+
+/// An output's global rectangle in the renderer's coordinate type.
+pub(super) fn output_bounds(geometry: &WindowRectangle) -> Rectangle<i32, Physical> {
+    Rectangle::new(
+        Point::from((geometry.x, geometry.y)),
+        Size::from((geometry.width as i32, geometry.height as i32)),
+    )
+}
+
+/// The output containing global `point`, as an index into `outputs`, and
+/// the point relative to that output's top-left corner. Right and bottom
+/// edges belong to the neighbouring output, so adjacent outputs never both
+/// claim a point.
+pub(super) fn global_to_output(
+    (x, y): (f64, f64),
+    outputs: &[(String, WindowRectangle)],
+) -> Option<(usize, (f64, f64))> {
+    outputs.iter().position(|(_, g)| {
+        x >= g.x as f64
+            && y >= g.y as f64
+            && x < g.x as f64 + g.width as f64
+            && y < g.y as f64 + g.height as f64
+    })
+    .map(|index| {
+        let g = &outputs[index].1;
+        (index, (x - g.x as f64, y - g.y as f64))
+    })
+}
+
+/// Inverse of [`global_to_output`]: the global position of `local` on
+/// output `index`.
+pub(super) fn output_to_global(
+    index: usize,
+    (x, y): (f64, f64),
+    outputs: &[(String, WindowRectangle)],
+) -> Option<(f64, f64)> {
+    let (_, g) = outputs.get(index)?;
+    Some((g.x as f64 + x, g.y as f64 + y))
+}
+
+/// Pull `point` onto the nearest output so relative pointer motion can't
+/// leave the desktop through a gap or past a negative-origin edge. Points
+/// already on an output, or with no outputs to clamp to, are unchanged.
+pub(super) fn clamp_to_outputs(
+    point: (f64, f64),
+    outputs: &[(String, WindowRectangle)],
+) -> (f64, f64) {
+    if global_to_output(point, outputs).is_some() {
+        return point;
+    }
+    outputs
+        .iter()
+        .map(|(_, g)| {
+            let max_x = (g.x + g.width as i32 - 1).max(g.x) as f64;
+            let max_y = (g.y + g.height as i32 - 1).max(g.y) as f64;
+            (point.0.clamp(g.x as f64, max_x), point.1.clamp(g.y as f64, max_y))
+        })
+        .min_by(|a, b| {
+            let dist = |p: &(f64, f64)| (p.0 - point.0).powi(2) + (p.1 - point.1).powi(2);
+            dist(a).total_cmp(&dist(b))
+        })
+        .unwrap_or(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A laptop at the origin, a monitor to its left and one above it.
+    fn negative_origin_topology() -> Vec<(String, WindowRectangle)> {
+        vec![
+            ("laptop".to_string(), WindowRectangle::from_loc_and_size((0, 0), (1920, 1080))),
+            ("left".to_string(), WindowRectangle::from_loc_and_size((-2560, -360), (2560, 1440))),
+            ("above".to_string(), WindowRectangle::from_loc_and_size((0, -1080), (1920, 1080))),
+        ]
+    }
+
+    #[test]
+    fn test_global_to_output_with_negative_origins() {
+        let outputs = negative_origin_topology();
+        assert_eq!(global_to_output((100.0, 50.0), &outputs), Some((0, (100.0, 50.0))));
+        assert_eq!(
+            global_to_output((-100.0, -300.0), &outputs),
+            Some((1, (2460.0, 60.0)))
+        );
+        assert_eq!(global_to_output((10.0, -1.0), &outputs), Some((2, (10.0, 1079.0))));
+        // The shared edge at x = 0 belongs to the laptop, not the left monitor
+        assert_eq!(global_to_output((0.0, 0.0), &outputs).map(|(i, _)| i), Some(0));
+        // Below the left monitor and left of the laptop: no output
+        assert_eq!(global_to_output((-10.0, 1200.0), &outputs), None);
+    }
+
+    #[test]
+    fn test_output_to_global_round_trips() {
+        let outputs = negative_origin_topology();
+        for point in [(5.0, 5.0), (-2559.0, -359.0), (1919.5, -0.5)] {
+            let (index, local) = global_to_output(point, &outputs).unwrap();
+            assert_eq!(output_to_global(index, local, &outputs), Some(point));
+        }
+        assert_eq!(output_to_global(7, (0.0, 0.0), &outputs), None);
+    }
+
+    #[test]
+    fn test_clamp_to_outputs_pulls_points_out_of_gaps() {
+        let outputs = negative_origin_topology();
+        // In the gap under the left monitor: nearest is its bottom edge
+        assert_eq!(clamp_to_outputs((-10.0, 1200.0), &outputs), (-10.0, 1079.0));
+        // Past the far-left edge
+        assert_eq!(clamp_to_outputs((-3000.0, 0.0), &outputs), (-2560.0, 0.0));
+        // Already on an output
+        assert_eq!(clamp_to_outputs((300.0, 300.0), &outputs), (300.0, 300.0));
+        assert_eq!(clamp_to_outputs((-5.0, -5.0), &[]), (-5.0, -5.0));
+    }
+}
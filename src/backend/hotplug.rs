@@ -0,0 +1,169 @@
+//! Output hotplug.
+//!
+//! Outputs can be connected and disconnected at runtime. The winit backend
+//! only ever has its one window, so the hooks here also let tools and tests
+//! simulate monitors coming and going, which is how multi-monitor features
+//! get developed without the hardware. Every change is queued as an
+//! [`OutputChange`] for the compositor to broadcast over IPC.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use log::{info, warn};
+use smithay::output::{Mode as OutputMode, Output, PhysicalProperties, Scale, Subpixel};
+use smithay::utils::Transform;
+
+use super::{AxiomSmithayBackendReal, State};
+use crate::window::Rectangle as WindowRectangle;
+use crate::workspace::{OutputLayout, DEFAULT_LAYOUT};
+
+/// An output appearing or going away.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputChange {
+    pub name: String,
+    pub connected: bool,
+    /// Global rectangle of the output; for a disconnect, the one it had
+    pub geometry: WindowRectangle,
+}
+
+impl AxiomSmithayBackendReal {
+    /// Connect a simulated `width`x`height` output called `name`.
+    ///
+    /// Advertises a new `wl_output` global, gives the output a workspace
+    /// tape and queues an [`OutputChange`].
+    pub fn simulate_output_connect(&mut self, name: &str, width: u32, height: u32) -> Result<()> {
+        if self.state.outputs.iter().any(|o| o.name() == name) {
+            anyhow::bail!("output '{}' is already connected", name);
+        }
+        if width == 0 || height == 0 {
+            anyhow::bail!("output '{}' needs a non-zero size", name);
+        }
+        let dh = self
+            .state
+            .display_handle
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("no display to advertise output '{}' on", name))?;
+
+        let output = Output::new(
+            name.to_string(),
+            PhysicalProperties {
+                size: (0, 0).into(),
+                subpixel: Subpixel::Unknown,
+                make: "Axiom".into(),
+                model: "Simulated".into(),
+            },
+        );
+        output.change_current_state(
+            Some(OutputMode {
+                size: (width as i32, height as i32).into(),
+                refresh: 60_000,
+            }),
+            Some(Transform::Normal),
+            Some(Scale::Integer(1)),
+            None,
+        );
+        let global = output.create_global::<State>(&dh);
+        self.state.output_globals.insert(name.to_string(), global);
+        self.state.outputs.push(output);
+
+        self.sync_output_tapes();
+        self.state
+            .workspace_manager
+            .write()
+            .set_output_viewport(name, width as f64, height as f64);
+        let geometry = self
+            .state
+            .workspace_manager
+            .read()
+            .output_geometry(name)
+            .unwrap_or_else(|| WindowRectangle::from_loc_and_size((0, 0), (width, height)));
+
+        info!("🖥️ Output {} connected ({}x{})", name, width, height);
+        self.queue_output_change(name, true, geometry);
+        Ok(())
+    }
+
+    /// Disconnect output `name`, removing its `wl_output` global. Windows on
+    /// it move to a remaining output. The last output can't be removed.
+    pub fn simulate_output_disconnect(&mut self, name: &str) -> Result<()> {
+        let Some(index) = self.state.outputs.iter().position(|o| o.name() == name) else {
+            anyhow::bail!("unknown output '{}'", name);
+        };
+        if self.state.outputs.len() == 1 {
+            anyhow::bail!("can't disconnect '{}', the only output", name);
+        }
+        let geometry = self
+            .state
+            .workspace_manager
+            .read()
+            .output_geometry(name)
+            .unwrap_or_else(|| WindowRectangle::from_loc_and_size((0, 0), (0, 0)));
+
+        self.state.outputs.remove(index);
+        if let (Some(global), Some(dh)) = (
+            self.state.output_globals.remove(name),
+            self.state.display_handle.as_ref(),
+        ) {
+            dh.remove_global::<State>(global);
+        }
+        self.state.output_scale_factors.remove(name);
+        self.state
+            .output_mirrors
+            .retain(|target, source| target != name && source != name);
+        self.sync_output_tapes();
+
+        info!("🖥️ Output {} disconnected", name);
+        self.queue_output_change(name, false, geometry);
+        Ok(())
+    }
+
+    /// Output changes since the last call, oldest first.
+    pub fn take_output_changes(&mut self) -> Vec<OutputChange> {
+        std::mem::take(&mut self.state.output_changes)
+    }
+
+    fn sync_output_tapes(&mut self) {
+        let live: Vec<String> = self.state.outputs.iter().map(|o| o.name()).collect();
+        self.state
+            .workspace_manager
+            .write()
+            .sync_tapes_with_outputs(&live, &self.state.config.output.order);
+        self.state.apply_output_layouts();
+        self.state.needs_redraw = true;
+    }
+
+    fn queue_output_change(&mut self, name: &str, connected: bool, geometry: WindowRectangle) {
+        self.state.output_changes.push(OutputChange {
+            name: name.to_string(),
+            connected,
+            geometry,
+        });
+    }
+}
+
+impl State {
+    /// Resolve the `[[outputs]]` rules against the connected outputs and
+    /// hand the result to the workspace manager.
+    pub(super) fn apply_output_layouts(&self) {
+        let config = &self.config;
+        let default_layout = &config.window.default_layout;
+        let mut workspaces = self.workspace_manager.write();
+        if !workspaces.has_layout(default_layout) {
+            warn!(
+                "Unknown window.default_layout '{}', tiling {} until it is registered",
+                default_layout, DEFAULT_LAYOUT
+            );
+        }
+        let layouts: HashMap<String, OutputLayout> = self
+            .outputs
+            .iter()
+            .map(|output| {
+                let name = output.name();
+                let model = output.physical_properties().model;
+                let layout = OutputLayout::resolve(&config.outputs, default_layout, &name, &model);
+                (name, layout)
+            })
+            .collect();
+        workspaces.set_output_layouts(default_layout.clone(), layouts);
+    }
+}
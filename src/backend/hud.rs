@@ -0,0 +1,177 @@
+//! Debug HUD (`--hud`, or the `toggle_hud` IPC action).
+//!
+//! A small panel in the top-left corner of the screen showing frames per
+//! second, average frame time, window count, how much of the last frame
+//! was damaged and how many draw calls it took. It is drawn with a solid
+//! fill and its text with the built-in bitmap font of [`super::text`], so
+//! it needs no font files and works whatever else is broken.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use super::text::{ADVANCE, GLYPH_HEIGHT, GLYPH_WIDTH, LINE_HEIGHT};
+
+/// Frames the FPS and frame time are averaged over.
+const SAMPLE_FRAMES: usize = 60;
+/// Screen pixels per font pixel.
+const PIXEL: i32 = 3;
+/// Panel padding and distance from the screen corner, in screen pixels.
+const PADDING: i32 = 6;
+const MARGIN: i32 = 8;
+const PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.65];
+const TEXT_COLOR: [f32; 4] = [0.85, 1.0, 0.55, 1.0];
+
+/// What the render path measured about one frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(super) struct RenderStats {
+    /// Time from binding the output surface to submitting the frame
+    pub(super) frame_time: Duration,
+    /// Share of the output the frame redrew, 0.0 - 1.0
+    pub(super) damage: f32,
+    /// Elements drawn, counting mirrored copies
+    pub(super) draw_calls: u32,
+}
+
+/// A solid rectangle to draw: `(x, y, width, height)` and its colour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct HudFill {
+    pub(super) rect: (i32, i32, i32, i32),
+    pub(super) color: [f32; 4],
+}
+
+/// Text to draw with its top-left corner at `position`, `scale` screen
+/// pixels per font pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct HudText {
+    pub(super) position: (i32, i32),
+    pub(super) text: String,
+    pub(super) color: [f32; 4],
+    pub(super) scale: i32,
+}
+
+/// HUD state: whether it shows, and the stats of recent frames.
+#[derive(Debug, Default)]
+pub(super) struct Hud {
+    pub(super) enabled: bool,
+    /// Intervals between the last presented frames
+    intervals: VecDeque<Duration>,
+    /// Render times of the last presented frames
+    frame_times: VecDeque<Duration>,
+    last_present: Option<Instant>,
+    last: RenderStats,
+    windows: usize,
+}
+
+impl Hud {
+    pub(super) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Record a frame presented at `now`, with `windows` open.
+    pub(super) fn frame_presented(&mut self, now: Instant, stats: RenderStats, windows: usize) {
+        if let Some(last) = self.last_present.replace(now) {
+            push_sample(&mut self.intervals, now.saturating_duration_since(last));
+        }
+        push_sample(&mut self.frame_times, stats.frame_time);
+        self.last = stats;
+        self.windows = windows;
+    }
+
+    /// The HUD's text, one entry per line.
+    pub(super) fn lines(&self) -> Vec<String> {
+        let fps = match average(&self.intervals) {
+            Some(interval) if !interval.is_zero() => 1.0 / interval.as_secs_f64(),
+            _ => 0.0,
+        };
+        let frame_ms = average(&self.frame_times).map_or(0.0, |t| t.as_secs_f64() * 1000.0);
+        vec![
+            format!("FPS {:.1}", fps),
+            format!("MS {:.2}", frame_ms),
+            format!("WIN {}", self.windows),
+            format!("DMG {:.0}%", self.last.damage * 100.0),
+            format!("DRAW {}", self.last.draw_calls),
+        ]
+    }
+
+    /// What draws the HUD: the panel, and the text on top of it. `None`
+    /// while the HUD is off.
+    pub(super) fn draw_commands(&self) -> Option<(HudFill, HudText)> {
+        if !self.enabled {
+            return None;
+        }
+        let lines = self.lines();
+        let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as i32;
+        let rows = lines.len() as i32;
+        let width = ((columns - 1) * ADVANCE + GLYPH_WIDTH) * PIXEL + 2 * PADDING;
+        let height = ((rows - 1) * LINE_HEIGHT + GLYPH_HEIGHT) * PIXEL + 2 * PADDING;
+        let panel = HudFill {
+            rect: (MARGIN, MARGIN, width, height),
+            color: PANEL_COLOR,
+        };
+        let text = HudText {
+            position: (MARGIN + PADDING, MARGIN + PADDING),
+            text: lines.join("\n"),
+            color: TEXT_COLOR,
+            scale: PIXEL,
+        };
+        Some((panel, text))
+    }
+}
+
+fn push_sample(samples: &mut VecDeque<Duration>, sample: Duration) {
+    if samples.len() == SAMPLE_FRAMES {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+fn average(samples: &VecDeque<Duration>) -> Option<Duration> {
+    let count = u32::try_from(samples.len()).ok().filter(|&n| n > 0)?;
+    Some(samples.iter().sum::<Duration>() / count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::text::layout_text;
+
+    #[test]
+    fn test_hud_reports_averages_of_recent_frames() {
+        let mut hud = Hud::new(true);
+        let start = Instant::now();
+        for (i, ms) in [2, 4].into_iter().enumerate() {
+            let stats = RenderStats {
+                frame_time: Duration::from_millis(ms),
+                damage: 0.25,
+                draw_calls: 12,
+            };
+            hud.frame_presented(start + Duration::from_millis(20 * i as u64), stats, 3);
+        }
+        assert_eq!(
+            hud.lines(),
+            vec!["FPS 50.0", "MS 3.00", "WIN 3", "DMG 25%", "DRAW 12"]
+        );
+    }
+
+    #[test]
+    fn test_draw_commands_follow_enabled_flag() {
+        let mut hud = Hud::new(true);
+        let (panel, text) = hud.draw_commands().unwrap();
+        assert_eq!(panel.color, PANEL_COLOR);
+        assert_eq!(text.text.lines().count(), hud.lines().len());
+        // Every glyph lies on the panel
+        let (px, py, pw, ph) = panel.rect;
+        let (x, y) = text.position;
+        for quad in layout_text(x, y, &text.text, text.scale) {
+            let r = quad.dst;
+            assert!(r.loc.x >= px && r.loc.y >= py);
+            assert!(r.loc.x + r.size.w <= px + pw && r.loc.y + r.size.h <= py + ph);
+        }
+
+        hud.enabled = false;
+        assert!(hud.draw_commands().is_none());
+    }
+}
@@ -0,0 +1,98 @@
+//! Text input and input methods (`zwp_text_input_v3`, `zwp_input_method_v2`).
+//!
+//! Smithay ties both protocols to the seat: the client with keyboard focus
+//! gets the `text_input.enter`, enabling it activates the input method, and
+//! preedit and commit strings from the input method are relayed to that
+//! client's text input. While the input method holds a keyboard grab, key
+//! events reach it before the focused client, so it can compose characters
+//! and hand back the result. Compositor key bindings still run first.
+//!
+//! What is left to the compositor is the input method's candidate popups,
+//! which are drawn just below the text cursor of the client they serve.
+
+use log::debug;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::utils::{Logical, Rectangle};
+use smithay::wayland::input_method::{InputMethodHandler, PopupSurface};
+use wayland_server::Resource;
+
+use super::State;
+
+/// Global position of a candidate popup: just below the text cursor at
+/// `cursor` (relative to its surface), with the surface at `parent_origin`.
+pub(super) fn popup_position(
+    parent_origin: (i32, i32),
+    cursor: Rectangle<i32, Logical>,
+) -> (i32, i32) {
+    (
+        parent_origin.0 + cursor.loc.x,
+        parent_origin.1 + cursor.loc.y + cursor.size.h,
+    )
+}
+
+impl State {
+    /// Candidate popups still alive, with their global positions.
+    pub(super) fn ime_popup_positions(&mut self) -> Vec<(WlSurface, (i32, i32))> {
+        self.ime_popups.retain(PopupSurface::alive);
+        self.ime_popups
+            .iter()
+            .filter_map(|popup| {
+                let parent = popup.get_parent()?;
+                let origin = self.window_id_for_surface(&parent.surface).and_then(|id| {
+                    self.window_manager
+                        .read()
+                        .get_window(id)
+                        .map(|w| w.window.position)
+                })?;
+                let position = popup_position(origin, popup.text_input_rectangle());
+                Some((popup.wl_surface().clone(), position))
+            })
+            .collect()
+    }
+}
+
+impl InputMethodHandler for State {
+    fn new_popup(&mut self, surface: PopupSurface) {
+        debug!(
+            "⌨️ New input method popup: surface={}",
+            surface.wl_surface().id().protocol_id()
+        );
+        self.ime_popups.push(surface);
+        self.needs_redraw = true;
+    }
+
+    fn popup_repositioned(&mut self, _surface: PopupSurface) {
+        self.needs_redraw = true;
+    }
+
+    fn dismiss_popup(&mut self, surface: PopupSurface) {
+        self.ime_popups
+            .retain(|popup| popup.wl_surface() != surface.wl_surface());
+        self.needs_redraw = true;
+    }
+
+    /// The parent window's geometry in its own coordinates.
+    fn parent_geometry(&self, parent: &WlSurface) -> Rectangle<i32, Logical> {
+        self.window_id_for_surface(parent)
+            .and_then(|id| {
+                self.window_manager.read().get_window(id).map(|w| {
+                    let (width, height) = w.window.size;
+                    Rectangle::from_size((width as i32, height as i32).into())
+                })
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popup_sits_below_the_text_cursor() {
+        let cursor = Rectangle::new((40, 10).into(), (2, 18).into());
+        assert_eq!(popup_position((100, 200), cursor), (140, 228));
+        // A client that reports no cursor gets the popup at its corner
+        assert_eq!(popup_position((100, 200), Rectangle::default()), (100, 200));
+    }
+}
@@ -7,17 +7,26 @@
 
 use log::{debug, info, warn};
 use smithay::backend::input::{
-    AbsolutePositionEvent, Axis, AxisSource, Event, InputEvent, KeyboardKeyEvent, PointerAxisEvent,
-    PointerButtonEvent, TouchEvent,
+    AbsolutePositionEvent, Axis, AxisSource, ButtonState, Event, GestureBeginEvent,
+    GestureEndEvent, GestureSwipeUpdateEvent, InputEvent, KeyState, KeyboardKeyEvent,
+    PointerAxisEvent, PointerButtonEvent, TouchEvent,
 };
 use smithay::backend::winit;
-use smithay::input::keyboard::FilterResult;
+use smithay::input::keyboard::{FilterResult, Keycode};
 use smithay::input::pointer::{AxisFrame, ButtonEvent, MotionEvent};
 use smithay::input::touch::{DownEvent, MotionEvent as TouchMotionEvent, UpEvent};
 use smithay::utils::{Logical, Point, Serial, SERIAL_COUNTER};
 use wayland_server::Resource;
 
-use super::{AxiomSmithayBackendReal, WindowInteraction};
+use super::geometry::{clamp_to_outputs, output_layout};
+use super::{AxiomSmithayBackendReal, State, WindowInteraction};
+
+/// Floor for either dimension during an interactive resize drag.
+const MIN_INTERACTIVE_SIZE: i32 = 100;
+
+/// Touchpad scroll amount one wheel notch is treated as when high-res
+/// wheel travel is turned into workspace momentum.
+const WHEEL_NOTCH_SCROLL: f64 = 15.0;
 
 impl AxiomSmithayBackendReal {
     /// Resolve the topmost client surface under a logical coordinate, for
@@ -30,13 +39,7 @@ impl AxiomSmithayBackendReal {
         wayland_server::protocol::wl_surface::WlSurface,
         Point<f64, Logical>,
     )> {
-        let floating = self.floating_rects();
-        let under = self
-            .state
-            .workspace_manager
-            .read()
-            .element_under(x, y, &floating);
-        under.and_then(|(window_id, (sx, sy))| {
+        self.window_under(x, y).and_then(|(window_id, (sx, sy))| {
             self.state
                 .window_map
                 .get(&window_id)
@@ -52,82 +55,19 @@ impl AxiomSmithayBackendReal {
     pub(super) fn handle_input_event(&mut self, event: InputEvent<winit::WinitInput>) {
         match event {
             InputEvent::Keyboard { event } => {
-                if let Some(keyboard) = self.state.seat.get_keyboard() {
-                    let serial = SERIAL_COUNTER.next_serial();
-                    let time = Event::time_msec(&event);
-                    let pressed = event.state() == smithay::backend::input::KeyState::Pressed;
-
-                    let input_manager = self.state.input_manager.clone();
-                    let pending_actions = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
-                    let pending_clone = pending_actions.clone();
-
-                    keyboard.input::<(), _>(
-                        &mut self.state,
-                        event.key_code(),
-                        event.state(),
-                        serial,
-                        time,
-                        |_, modifiers, handle| {
-                            if pressed {
-                                let syms = handle.modified_syms();
-                                if let Some(keysym) = syms.first() {
-                                    let key_name = xkbcommon::xkb::keysym_get_name(*keysym);
-
-                                    let mut mod_names: Vec<String> = Vec::new();
-                                    if modifiers.ctrl {
-                                        mod_names.push("Ctrl".to_string());
-                                    }
-                                    if modifiers.alt {
-                                        mod_names.push("Alt".to_string());
-                                    }
-                                    if modifiers.logo {
-                                        mod_names.push("Super".to_string());
-                                    }
-                                    if modifiers.shift {
-                                        mod_names.push("Shift".to_string());
-                                    }
-
-                                    let key_combo = if mod_names.is_empty() {
-                                        key_name.to_lowercase()
-                                    } else {
-                                        format!("{}+{}", mod_names.join("+"), key_name)
-                                    };
-
-                                    let axiom_event = crate::input::InputEvent::Keyboard {
-                                        key: key_combo.clone(),
-                                        modifiers: mod_names,
-                                        pressed: true,
-                                    };
-
-                                    let actions =
-                                        input_manager.write().process_input_event(axiom_event);
-
-                                    if !actions.is_empty() {
-                                        debug!("⌨️ Global shortcut: {}", key_combo);
-                                        *pending_clone.borrow_mut() = actions;
-                                        return FilterResult::Intercept(());
-                                    }
-                                }
-                            }
-                            FilterResult::Forward
-                        },
-                    );
-
-                    // Process any actions that were intercepted
-                    let actions: Vec<_> = pending_actions.borrow_mut().drain(..).collect();
-                    if !actions.is_empty() {
-                        self.process_actions(actions);
-                    }
-                }
+                self.handle_key(event.key_code(), event.state(), Event::time_msec(&event));
             }
 
             InputEvent::PointerMotion { event: _event } => {
                 // ponytail: winit maps PointerMotionEvent to UnusedEvent and never emits
                 // this variant; the delta is always 0.0. Kept for future backends that
-                // send relative motion (e.g. libinput).
-                let new_x = (self.state.pointer_x + 0.0).clamp(0.0, self.state.window_width as f64);
-                let new_y =
-                    (self.state.pointer_y + 0.0).clamp(0.0, self.state.window_height as f64);
+                // send relative motion (e.g. libinput). Motion stays on the outputs,
+                // whatever their origins.
+                let outputs = output_layout(&self.state.workspace_manager.read());
+                let (new_x, new_y) = clamp_to_outputs(
+                    (self.state.pointer_x + 0.0, self.state.pointer_y + 0.0),
+                    &outputs,
+                );
                 self.process_pointer_motion(new_x, new_y);
             }
 
@@ -136,111 +76,51 @@ impl AxiomSmithayBackendReal {
             }
 
             InputEvent::PointerButton { event } => {
-                let serial = SERIAL_COUNTER.next_serial();
-                let time = Event::time_msec(&event);
-
-                // Dismiss active popup grab on any button press outside the popup
-                if let Some(popup_id) = self.state.active_popup_grab {
-                    let dismiss = if let Some(p) = self.state.popups.get(&popup_id) {
-                        let px = self.state.pointer_x as i32;
-                        let py = self.state.pointer_y as i32;
-                        // Find the popup's absolute position by locating its parent window
-                        let (abs_x, abs_y) = self
-                            .state
-                            .window_map
-                            .iter()
-                            .find_map(|(&wid, &sid)| {
-                                if sid == p.parent_surface_id {
-                                    self.state
-                                        .window_manager
-                                        .read()
-                                        .get_window(wid)
-                                        .map(|w| (w.window.position.0, w.window.position.1))
-                                } else {
-                                    None
-                                }
-                            })
-                            .unwrap_or((0, 0));
-                        let popup_x = abs_x + p.x;
-                        let popup_y = abs_y + p.y;
-                        px < popup_x
-                            || px > popup_x + p.width
-                            || py < popup_y
-                            || py > popup_y + p.height
-                    } else {
-                        true
-                    };
-
-                    if dismiss {
-                        if let Some(p) = self.state.popups.remove(&popup_id) {
-                            info!("🗑️ Dismissing popup surface {}", popup_id);
-                            p.surface.send_popup_done();
-                            self.state.needs_redraw = true;
-                        }
-                        self.state.active_popup_grab = None;
-                    }
-                }
+                // Convert MouseButton to u32 button code
+                let button_code = match event.button() {
+                    Some(smithay::backend::input::MouseButton::Left) => 0x110,
+                    Some(smithay::backend::input::MouseButton::Right) => 0x111,
+                    Some(smithay::backend::input::MouseButton::Middle) => 0x112,
+                    None => 0,
+                    _ => 0,
+                };
+                self.handle_pointer_button(button_code, event.state(), Event::time_msec(&event));
+            }
 
-                let pressed = event.state() == smithay::backend::input::ButtonState::Pressed;
-
-                // Decoration hit-testing: close/minimize/maximize buttons
-                // on server-side decorations.
-                if pressed {
-                    if self.handle_decoration_button(
-                        self.state.pointer_x,
-                        self.state.pointer_y,
-                        true,
-                    ) {
-                        // handle_decoration_button already set decoration_consumed_press = true
-                        // on a hit; keep it so the matching release is swallowed below.
+            InputEvent::PointerAxis { event } => {
+                // A wheel step over a titlebar or the background may be bound
+                // to a compositor action; those scrolls don't reach clients.
+                if matches!(event.source(), AxisSource::Wheel | AxisSource::WheelTilt) {
+                    let bound = event
+                        .amount(Axis::Vertical)
+                        .filter(|amount| amount.abs() > 5.0)
+                        .and_then(|amount| self.scroll_binding_at_pointer(amount));
+                    if let Some((action, titlebar_of)) = bound {
+                        self.apply_scroll_binding(action, titlebar_of);
                         return;
                     }
-                } else if self.decoration_consumed_press {
-                    self.handle_decoration_button(
-                        self.state.pointer_x,
-                        self.state.pointer_y,
-                        false,
-                    );
-                    self.decoration_consumed_press = false;
-                    return;
                 }
 
-                if let Some(pointer) = self.state.seat.get_pointer() {
-                    // Convert MouseButton to u32 button code
-                    let button_code = match event.button() {
-                        Some(smithay::backend::input::MouseButton::Left) => 0x110,
-                        Some(smithay::backend::input::MouseButton::Right) => 0x111,
-                        Some(smithay::backend::input::MouseButton::Middle) => 0x112,
-                        None => 0,
-                        _ => 0,
-                    };
-                    let button_event = ButtonEvent {
-                        serial,
-                        time,
-                        button: button_code,
-                        state: event.state(),
-                    };
-                    pointer.button(&mut self.state, &button_event);
-                }
-            }
-
-            InputEvent::PointerAxis { event } => {
                 // Forward axis/scroll events via seat with actual axis values
                 let time = Event::time_msec(&event);
 
                 if let Some(pointer) = self.state.seat.get_pointer() {
                     let mut axis_frame = AxisFrame::new(time);
 
-                    // Extract and forward horizontal/vertical scroll amounts
-                    // Using the `input` crate's Axis enum (Horizontal/Vertical)
-                    if let Some(amount) = event.amount(Axis::Horizontal) {
-                        if amount.abs() > 0.0 {
-                            axis_frame = axis_frame.value(Axis::Horizontal, amount);
+                    // Extract and forward horizontal/vertical scroll amounts,
+                    // plus the high-resolution wheel steps. Smithay only sends
+                    // axis_value120 to clients that bound wl_pointer v8+.
+                    axis_frame = axis_frame.source(event.source());
+                    for axis in [Axis::Horizontal, Axis::Vertical] {
+                        if let Some(amount) = event.amount(axis) {
+                            if amount.abs() > 0.0 {
+                                axis_frame = axis_frame.value(axis, amount);
+                            }
                         }
-                    }
-                    if let Some(amount) = event.amount(Axis::Vertical) {
-                        if amount.abs() > 0.0 {
-                            axis_frame = axis_frame.value(Axis::Vertical, amount);
+                        if let Some(v120) = event.amount_v120(axis) {
+                            if v120 != 0.0 {
+                                axis_frame = axis_frame.v120(axis, v120.round() as i32);
+                            }
                         }
                     }
 
@@ -265,6 +145,24 @@ impl AxiomSmithayBackendReal {
                                 }
                             }
                         }
+                        AxisSource::Wheel | AxisSource::WheelTilt
+                            if event.amount_v120(Axis::Horizontal).is_some() =>
+                        {
+                            // High-res wheels report fractions of a notch;
+                            // whole notches become momentum, the rest waits.
+                            let v120 = event.amount_v120(Axis::Horizontal).unwrap_or(0.0);
+                            let notches =
+                                self.state.input_manager.write().accumulate_scroll_v120(v120);
+                            if notches != 0 {
+                                let speed = self.state.config.workspace.scroll_speed;
+                                let velocity = notches as f64 * WHEEL_NOTCH_SCROLL * speed * 8.0;
+                                self.state
+                                    .workspace_manager
+                                    .write()
+                                    .start_momentum_scroll(velocity);
+                                self.state.needs_redraw = true;
+                            }
+                        }
                         AxisSource::Wheel | AxisSource::WheelTilt => {
                             if let Some(amount) = event.amount(Axis::Horizontal) {
                                 if amount > 5.0 {
@@ -280,6 +178,34 @@ impl AxiomSmithayBackendReal {
                 }
             }
 
+            // Touchpad swipes: the recognizer in InputManager decides on
+            // release whether the swipe maps to a bound action.
+            InputEvent::GestureSwipeBegin { event } => {
+                self.state
+                    .input_manager
+                    .write()
+                    .gesture_swipe_begin(event.fingers());
+            }
+
+            InputEvent::GestureSwipeUpdate { event } => {
+                self.state
+                    .input_manager
+                    .write()
+                    .gesture_swipe_update(event.delta_x(), event.delta_y());
+            }
+
+            InputEvent::GestureSwipeEnd { event } => {
+                let action = self
+                    .state
+                    .input_manager
+                    .write()
+                    .gesture_swipe_end(event.cancelled());
+                if let Some(action) = action {
+                    debug!("👆 Swipe gesture -> {:?}", action);
+                    self.process_actions(vec![action]);
+                }
+            }
+
             InputEvent::TouchDown { event } => {
                 let width = self.state.window_width as i32;
                 let height = self.state.window_height as i32;
@@ -342,10 +268,7 @@ impl AxiomSmithayBackendReal {
                                 return;
                             }
                             Some(crate::decoration::DecorationAction::ToggleMaximize) => {
-                                self.state
-                                    .window_manager
-                                    .write()
-                                    .toggle_fullscreen(window_id);
+                                self.state.toggle_maximize_window(window_id);
                                 self.state.needs_redraw = true;
                                 return;
                             }
@@ -395,48 +318,11 @@ impl AxiomSmithayBackendReal {
                     }
                 }
 
-                // Check for popup dismiss: if there's an active popup grab and
-                // the touch is outside the popup rect, dismiss the popup.
-                if let Some(popup_id) = self.state.active_popup_grab {
-                    let dismiss = if let Some(p) = self.state.popups.get(&popup_id) {
-                        let px = x as i32;
-                        let py = y as i32;
-                        // Find the popup's absolute position by locating its parent window
-                        let (abs_x, abs_y) = self
-                            .state
-                            .window_map
-                            .iter()
-                            .find_map(|(&wid, &sid)| {
-                                if sid == p.parent_surface_id {
-                                    self.state
-                                        .window_manager
-                                        .read()
-                                        .get_window(wid)
-                                        .map(|w| (w.window.position.0, w.window.position.1))
-                                } else {
-                                    None
-                                }
-                            })
-                            .unwrap_or((0, 0));
-                        let popup_x = abs_x + p.x;
-                        let popup_y = abs_y + p.y;
-                        px < popup_x
-                            || px > popup_x + p.width
-                            || py < popup_y
-                            || py > popup_y + p.height
-                    } else {
-                        true
-                    };
-
-                    if dismiss {
-                        if let Some(p) = self.state.popups.remove(&popup_id) {
-                            info!("🗑️ Dismissing popup surface {}", popup_id);
-                            p.surface.send_popup_done();
-                            self.state.needs_redraw = true;
-                        }
-                        self.state.active_popup_grab = None;
-                        return;
-                    }
+                // A touch outside the grabbed menus dismisses them and
+                // goes no further.
+                if self.state.active_popup_grab.is_some() && !self.state.popup_grab_contains(x, y) {
+                    self.state.dismiss_popup_grab();
+                    return;
                 }
 
                 // No decoration consumed — forward to the touch client.
@@ -556,6 +442,141 @@ impl AxiomSmithayBackendReal {
         }
     }
 
+    /// A key press or release from any keyboard, real or virtual: compositor
+    /// bindings get the first look, then the key goes to the seat.
+    pub(super) fn handle_key(&mut self, keycode: Keycode, key_state: KeyState, time: u32) {
+        if let Some(keyboard) = self.state.seat.get_keyboard() {
+            let serial = SERIAL_COUNTER.next_serial();
+            let pressed = key_state == KeyState::Pressed;
+
+            let input_manager = self.state.input_manager.clone();
+            let pending_actions = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+            let pending_clone = pending_actions.clone();
+            let dismiss_popups = std::rc::Rc::new(std::cell::Cell::new(false));
+            let dismiss_clone = dismiss_popups.clone();
+
+            keyboard.input::<(), _>(
+                &mut self.state,
+                keycode,
+                key_state,
+                serial,
+                time,
+                |state, modifiers, handle| {
+                    if pressed {
+                        let syms = handle.modified_syms();
+                        if let Some(keysym) = syms.first() {
+                            let key_name = xkbcommon::xkb::keysym_get_name(*keysym);
+
+                            // Escape closes grabbed menus rather than
+                            // reaching the client. The dismissal waits
+                            // until the keyboard is released below,
+                            // since it moves keyboard focus.
+                            if key_name == "Escape" && state.active_popup_grab.is_some() {
+                                dismiss_clone.set(true);
+                                return FilterResult::Intercept(());
+                            }
+
+                            let mut mod_names: Vec<String> = Vec::new();
+                            if modifiers.ctrl {
+                                mod_names.push("Ctrl".to_string());
+                            }
+                            if modifiers.alt {
+                                mod_names.push("Alt".to_string());
+                            }
+                            if modifiers.logo {
+                                mod_names.push("Super".to_string());
+                            }
+                            if modifiers.shift {
+                                mod_names.push("Shift".to_string());
+                            }
+
+                            let key_combo = if mod_names.is_empty() {
+                                key_name.to_lowercase()
+                            } else {
+                                format!("{}+{}", mod_names.join("+"), key_name)
+                            };
+
+                            let axiom_event = crate::input::InputEvent::Keyboard {
+                                key: key_combo.clone(),
+                                modifiers: mod_names,
+                                pressed: true,
+                            };
+
+                            let actions = input_manager.write().process_input_event(axiom_event);
+
+                            if !actions.is_empty() {
+                                debug!("⌨️ Global shortcut: {}", key_combo);
+                                *pending_clone.borrow_mut() = actions;
+                                return FilterResult::Intercept(());
+                            }
+                        }
+                    }
+                    FilterResult::Forward
+                },
+            );
+
+            if dismiss_popups.get() {
+                self.state.dismiss_popup_grab();
+            }
+
+            // Process any actions that were intercepted
+            let actions: Vec<_> = pending_actions.borrow_mut().drain(..).collect();
+            if !actions.is_empty() {
+                self.process_actions(actions);
+            }
+        }
+    }
+
+    /// A pointer button from any pointing device, real or virtual. `button`
+    /// is the evdev button code.
+    pub(super) fn handle_pointer_button(
+        &mut self,
+        button: u32,
+        button_state: ButtonState,
+        time: u32,
+    ) {
+        let serial = SERIAL_COUNTER.next_serial();
+
+        // A click outside the grabbed menus dismisses them
+        if self.state.active_popup_grab.is_some()
+            && !self
+                .state
+                .popup_grab_contains(self.state.pointer_x, self.state.pointer_y)
+        {
+            self.state.dismiss_popup_grab();
+        }
+
+        let pressed = button_state == ButtonState::Pressed;
+
+        // Decoration hit-testing: close/minimize/maximize buttons
+        // on server-side decorations.
+        if pressed {
+            if self.handle_decoration_button(self.state.pointer_x, self.state.pointer_y, true) {
+                // handle_decoration_button already set decoration_consumed_press = true
+                // on a hit; keep it so the matching release is swallowed below.
+                return;
+            }
+        } else if self.decoration_consumed_press {
+            self.handle_decoration_button(self.state.pointer_x, self.state.pointer_y, false);
+            self.decoration_consumed_press = false;
+            return;
+        }
+
+        if pressed {
+            self.click_to_focus(serial);
+        }
+
+        if let Some(pointer) = self.state.seat.get_pointer() {
+            let button_event = ButtonEvent {
+                serial,
+                time,
+                button,
+                state: button_state,
+            };
+            pointer.button(&mut self.state, &button_event);
+        }
+    }
+
     /// If an interactive window manipulation is active (move or resize),
     /// apply the new pointer position and return `true` so the motion
     /// event is NOT forwarded to Smithay for pointer focus updates.
@@ -572,11 +593,48 @@ impl AxiomSmithayBackendReal {
                 offset_x,
                 offset_y,
             } => {
-                let new_x = (px - offset_x).round() as i32;
-                let new_y = (py - offset_y).round() as i32;
-                let mut wm = self.state.window_manager.write();
-                if let Some(w) = wm.get_window_mut(window_id) {
-                    w.window.set_position(new_x, new_y);
+                let mut new_x = (px - offset_x).round() as i32;
+                let mut new_y = (py - offset_y).round() as i32;
+                let threshold = self.state.config.window.snap_threshold;
+                // Holding Shift drags freely
+                let shift_held = self
+                    .state
+                    .seat
+                    .get_keyboard()
+                    .is_some_and(|k| k.modifier_state().shift);
+                let size = self
+                    .state
+                    .window_manager
+                    .read()
+                    .get_window(window_id)
+                    .map(|w| w.window.size);
+                if let Some(size) = size.filter(|_| threshold > 0 && !shift_held) {
+                    let bounds = {
+                        let ws = self.state.workspace_manager.read();
+                        ws.output_geometry(ws.output_for_window(window_id))
+                            .unwrap_or(crate::window::Rectangle::from_loc_and_size(
+                                (0, 0),
+                                (self.state.window_width, self.state.window_height),
+                            ))
+                    };
+                    let neighbours: Vec<_> = self
+                        .floating_rects()
+                        .into_iter()
+                        .filter(|(id, ..)| *id != window_id)
+                        .map(|(_, x, y, w, h)| {
+                            crate::window::Rectangle::from_loc_and_size((x, y), (w, h))
+                        })
+                        .collect();
+                    (new_x, new_y) =
+                        crate::window::Rectangle::from_loc_and_size((new_x, new_y), size)
+                            .snapped_position(&bounds, &neighbours, threshold);
+                }
+                if self
+                    .state
+                    .window_manager
+                    .write()
+                    .move_window(window_id, new_x, new_y)
+                {
                     self.state.needs_redraw = true;
                 }
             }
@@ -587,51 +645,52 @@ impl AxiomSmithayBackendReal {
                 start_x,
                 start_y,
             } => {
+                use crate::decoration::ResizeEdge;
                 let dx = (px - start_x) as i32;
                 let dy = (py - start_y) as i32;
+                let grows_left = matches!(
+                    edge,
+                    ResizeEdge::Left | ResizeEdge::TopLeft | ResizeEdge::BottomLeft
+                );
+                let grows_up = matches!(
+                    edge,
+                    ResizeEdge::Top | ResizeEdge::TopLeft | ResizeEdge::TopRight
+                );
+                let moves_x = !matches!(edge, ResizeEdge::Top | ResizeEdge::Bottom);
+                let moves_y = !matches!(edge, ResizeEdge::Left | ResizeEdge::Right);
+
+                let raw_w = match (moves_x, grows_left) {
+                    (false, _) => iw as i32,
+                    (true, true) => iw as i32 - dx,
+                    (true, false) => iw as i32 + dx,
+                };
+                let raw_h = match (moves_y, grows_up) {
+                    (false, _) => ih as i32,
+                    (true, true) => ih as i32 - dy,
+                    (true, false) => ih as i32 + dy,
+                };
+                let raw = (
+                    raw_w.max(MIN_INTERACTIVE_SIZE) as u32,
+                    raw_h.max(MIN_INTERACTIVE_SIZE) as u32,
+                );
+
+                // Space between the anchored edge and the output border
+                let out_w = self.state.window_width as i32;
+                let out_h = self.state.window_height as i32;
+                let avail_w = if grows_left { ix + iw as i32 } else { out_w - ix };
+                let avail_h = if grows_up { iy + ih as i32 } else { out_h - iy };
+                let limit = (avail_w.max(1) as u32, avail_h.max(1) as u32);
+
                 let mut wm = self.state.window_manager.write();
-                if let Some(w) = wm.get_window_mut(window_id) {
-                    use crate::decoration::ResizeEdge;
-                    let (new_x, new_y, new_w, new_h) = match edge {
-                        ResizeEdge::Right => (ix, iy, (iw as i32 + dx).max(100) as u32, ih),
-                        ResizeEdge::Bottom => (ix, iy, iw, (ih as i32 + dy).max(100) as u32),
-                        ResizeEdge::BottomRight => {
-                            let w = (iw as i32 + dx).max(100) as u32;
-                            let h = (ih as i32 + dy).max(100) as u32;
-                            (ix, iy, w, h)
-                        }
-                        ResizeEdge::Left => {
-                            let w = (iw as i32 - dx).max(100) as u32;
-                            let x = ix + (iw as i32 - w as i32);
-                            (x, iy, w, ih)
-                        }
-                        ResizeEdge::Top => {
-                            let h = (ih as i32 - dy).max(100) as u32;
-                            let y = iy + (ih as i32 - h as i32);
-                            (ix, y, iw, h)
-                        }
-                        ResizeEdge::TopLeft => {
-                            let w = (iw as i32 - dx).max(100) as u32;
-                            let h = (ih as i32 - dy).max(100) as u32;
-                            let x = ix + (iw as i32 - w as i32);
-                            let y = iy + (ih as i32 - h as i32);
-                            (x, y, w, h)
-                        }
-                        ResizeEdge::TopRight => {
-                            let w = (iw as i32 + dx).max(100) as u32;
-                            let h = (ih as i32 - dy).max(100) as u32;
-                            let y = iy + (ih as i32 - h as i32);
-                            (ix, y, w, h)
-                        }
-                        ResizeEdge::BottomLeft => {
-                            let w = (iw as i32 - dx).max(100) as u32;
-                            let h = (ih as i32 + dy).max(100) as u32;
-                            let x = ix + (iw as i32 - w as i32);
-                            (x, iy, w, h)
-                        }
-                    };
-                    w.window.position = (new_x, new_y);
-                    w.window.size = (new_w, new_h);
+                if let Some(constraints) = wm.get_window(window_id).map(|w| w.properties.constraints) {
+                    // Edges along one axis drive that axis; corners follow width
+                    let (new_w, new_h) = constraints.constrain(raw, moves_x, Some(limit));
+                    let new_x = if grows_left { ix + iw as i32 - new_w as i32 } else { ix };
+                    let new_y = if grows_up { iy + ih as i32 - new_h as i32 } else { iy };
+                    wm.set_window_geometry(
+                        window_id,
+                        crate::window::Rectangle::from_loc_and_size((new_x, new_y), (new_w, new_h)),
+                    );
                     self.state.needs_redraw = true;
                 }
             }
@@ -645,6 +704,13 @@ impl AxiomSmithayBackendReal {
         self.state.pointer_x = x;
         self.state.pointer_y = y;
 
+        // A software cursor only moves on screen with a new frame; one on a
+        // hardware plane moves by itself.
+        let draw_at = State::cursor_draw_position((x, y), self.state.cursor_hotspot);
+        if self.state.cursor.moved(draw_at) && self.state.cursor_surface.is_some() {
+            self.state.needs_redraw = true;
+        }
+
         // Interactive move/resize consumes the motion event.
         if let Some(ref interaction) = self.interaction.clone() {
             if self.handle_interaction(interaction, x, y) {
@@ -656,12 +722,7 @@ impl AxiomSmithayBackendReal {
         let time = 0; // time is not available in the relative motion path
 
         // Find the surface under the pointer and forward motion
-        let floating = self.floating_rects();
-        let under = self
-            .state
-            .workspace_manager
-            .read()
-            .element_under(x, y, &floating);
+        let under = self.window_under(x, y);
         self.maybe_focus_window_under_pointer(under, serial);
 
         if let Some(pointer) = self.state.seat.get_pointer() {
@@ -688,7 +749,83 @@ impl AxiomSmithayBackendReal {
                 time,
                 location: Point::from((x, y)),
             };
+            let leaving = focus.is_none() && pointer.current_focus().is_some();
             pointer.motion(&mut self.state, focus, &motion_event);
+            // No client owns the pointer image once it has left them all.
+            if leaving && !self.state.dnd_active {
+                self.state.reset_cursor();
+            }
+        }
+    }
+
+    /// The topmost window under `(x, y)`, in paint order and honouring
+    /// rounded corners, with the point relative to the top-left of where
+    /// that window is laid out.
+    fn window_under(&self, x: f64, y: f64) -> Option<(u64, (f64, f64))> {
+        let layouts = self.state.workspace_manager.read().calculate_workspace_layouts();
+        let radius = self.state.decoration_manager.read().theme().corner_radius as f64;
+        let wm = self.state.window_manager.read();
+        let window_id = wm.window_at((x, y), radius, &layouts)?;
+        let rect = wm.visible_rect(window_id, &layouts)?;
+        Some((window_id, (x - rect.x as f64, y - rect.y as f64)))
+    }
+
+    /// The scroll binding for a vertical wheel step of `delta` at the
+    /// pointer, with the window whose titlebar the pointer is on, if any.
+    /// Scrolls over window content are never bound.
+    fn scroll_binding_at_pointer(
+        &self,
+        delta: f64,
+    ) -> Option<(crate::input::CompositorAction, Option<u64>)> {
+        use crate::input::ScrollRegion;
+        let (x, y) = (self.state.pointer_x, self.state.pointer_y);
+        let (region, titlebar_of) = match self.window_under(x, y) {
+            None => (ScrollRegion::Background, None),
+            Some((window_id, (_, rel_y))) => {
+                let dm = self.state.decoration_manager.read();
+                let server_side = dm
+                    .get_decoration(window_id)
+                    .is_some_and(|d| d.mode == crate::decoration::DecorationMode::ServerSide);
+                if !server_side || rel_y >= dm.theme().titlebar_height as f64 {
+                    return None;
+                }
+                (ScrollRegion::Titlebar, Some(window_id))
+            }
+        };
+        let action = self.state.input_manager.read().scroll_action(region, delta)?;
+        Some((action, titlebar_of))
+    }
+
+    /// Run a scroll-bound action. Column cycling over a titlebar starts
+    /// from that window rather than the focused one.
+    fn apply_scroll_binding(
+        &mut self,
+        action: crate::input::CompositorAction,
+        titlebar_of: Option<u64>,
+    ) {
+        use crate::input::CompositorAction;
+        match (action, titlebar_of) {
+            (CompositorAction::CycleColumnNext, Some(window_id)) => {
+                self.cycle_column_focus(window_id, true)
+            }
+            (CompositorAction::CycleColumnPrev, Some(window_id)) => {
+                self.cycle_column_focus(window_id, false)
+            }
+            (action, _) => self.process_actions(vec![action]),
+        }
+    }
+
+    /// Focus the window after (`forward`) or before `from` in its column.
+    fn cycle_column_focus(&mut self, from: u64, forward: bool) {
+        let next = self
+            .state
+            .workspace_manager
+            .read()
+            .column_neighbour(from, forward);
+        if let Some(window_id) = next {
+            debug!("🔁 Cycling column focus {} -> {}", from, window_id);
+            self.focus_window_surface(window_id, SERIAL_COUNTER.next_serial());
+            self.state.needs_redraw = true;
         }
     }
 
@@ -699,34 +836,70 @@ impl AxiomSmithayBackendReal {
         self.state.cached_floating_rects.clone()
     }
 
-    /// If configured, move keyboard focus to the window under the pointer.
-    /// This keeps live backend focus behavior aligned with `window.focus_follows_mouse`.
+    /// Feed the window under the pointer to the focus policy and move
+    /// keyboard focus if it says so (follows-mouse after the hover delay).
     fn maybe_focus_window_under_pointer(
         &mut self,
         under: Option<(u64, (f64, f64))>,
         serial: Serial,
     ) {
-        if !self.state.config.window.focus_follows_mouse {
-            return;
+        let focused = self.state.window_manager.read().focused_window_id();
+        let target = self.state.input_manager.write().pointer_hover(
+            under.map(|(window_id, _)| window_id),
+            focused,
+            std::time::Instant::now(),
+        );
+        if let Some(window_id) = target {
+            self.focus_window_surface(window_id, serial);
         }
+    }
 
-        let target_window_id = under.map(|(window_id, _)| window_id);
-        if self.state.window_manager.read().focused_window_id() == target_window_id {
-            return;
+    /// Focus a window whose follows-mouse hover delay elapsed while the
+    /// pointer was resting (no motion event to drive the check).
+    pub(super) fn poll_hover_focus(&mut self) {
+        let focused = self.state.window_manager.read().focused_window_id();
+        let target = self
+            .state
+            .input_manager
+            .write()
+            .poll_hover_focus(focused, std::time::Instant::now());
+        if let Some(window_id) = target {
+            self.focus_window_surface(window_id, SERIAL_COUNTER.next_serial());
         }
+    }
 
-        let target_surface = target_window_id.and_then(|window_id| {
-            self.state
-                .window_map
-                .get(&window_id)
-                .and_then(|surface_id| self.state.surfaces.get(surface_id))
-                .and_then(|sd| sd.surface.as_ref())
-                .filter(|surface| surface.is_alive())
-                .cloned()
-        });
+    /// Give keyboard focus to the window under a button press.
+    fn click_to_focus(&mut self, serial: Serial) {
+        let under = self.window_under(self.state.pointer_x, self.state.pointer_y);
+        let target = self
+            .state
+            .input_manager
+            .write()
+            .pointer_click(under.map(|(window_id, _)| window_id));
+        if let Some(window_id) = target {
+            if self.state.window_manager.read().focused_window_id() != Some(window_id) {
+                self.focus_window_surface(window_id, serial);
+            }
+        }
+    }
+
+    /// Route keyboard focus to a window's surface. The seat's `focus_changed`
+    /// callback then updates `WindowManager` and decoration focus state.
+    fn focus_window_surface(&mut self, window_id: u64, serial: Serial) {
+        let Some(target_surface) = self
+            .state
+            .window_map
+            .get(&window_id)
+            .and_then(|surface_id| self.state.surfaces.get(surface_id))
+            .and_then(|sd| sd.surface.as_ref())
+            .filter(|surface| surface.is_alive())
+            .cloned()
+        else {
+            return;
+        };
 
         if let Some(keyboard) = self.state.seat.get_keyboard() {
-            keyboard.set_focus(&mut self.state, target_surface, serial);
+            keyboard.set_focus(&mut self.state, Some(target_surface), serial);
         }
     }
 
@@ -796,10 +969,7 @@ impl AxiomSmithayBackendReal {
                     return true;
                 }
                 Some(crate::decoration::DecorationAction::ToggleMaximize) => {
-                    self.state
-                        .window_manager
-                        .write()
-                        .toggle_fullscreen(window_id);
+                    self.state.toggle_maximize_window(window_id);
                     self.state.needs_redraw = true;
                     self.decoration_consumed_press = true;
                     return true;
@@ -952,35 +1122,7 @@ impl AxiomSmithayBackendReal {
                 // For resize, send a configure event so the client resizes
                 // its buffer to match the new dimensions.
                 if let WindowInteraction::Resize { window_id, .. } = interaction {
-                    if let Some(&surface_id) = self.state.window_map.get(&window_id) {
-                        if let Some(toplevel) = self.state.toplevels.get(&surface_id) {
-                            let size = self
-                                .state
-                                .window_manager
-                                .read()
-                                .get_window(window_id)
-                                .map(|w| w.window.size);
-                            if let Some((new_w, new_h)) = size {
-                                // Convert physical-pixel window size to
-                                // logical pixels for the configure event,
-                                // matching the tiling reconfigure path.
-                                let scale = self
-                                    .state
-                                    .workspace_manager
-                                    .read()
-                                    .scale_factor_for_window(window_id);
-                                let logical_w = ((new_w as f64 / scale).round() as i32).max(1);
-                                let logical_h = ((new_h as f64 / scale).round() as i32).max(1);
-                                toplevel.with_pending_state(|state| {
-                                    state.size = Some((logical_w, logical_h).into());
-                                });
-                                toplevel.send_configure();
-                                self.state
-                                    .configured_sizes
-                                    .insert(surface_id, (logical_w, logical_h));
-                            }
-                        }
-                    }
+                    self.state.configure_window_size(window_id);
                 }
                 self.decoration_consumed_press = true;
                 return true;
@@ -991,6 +1133,22 @@ impl AxiomSmithayBackendReal {
         }
     }
 
+    /// Swap the focused window with its neighbour in `direction`.
+    fn swap_focused_window(&mut self, direction: crate::workspace::SwapDirection) {
+        let focused_id = self.state.window_manager.read().focused_window_id();
+        if let Some(window_id) = focused_id {
+            if self
+                .state
+                .workspace_manager
+                .write()
+                .swap_focused_with(window_id, direction)
+            {
+                info!("🔀 Input: Swap window {} {:?}", window_id, direction);
+                self.state.needs_redraw = true;
+            }
+        }
+    }
+
     /// Process actions generated by InputManager
     fn process_actions(&mut self, actions: Vec<crate::input::CompositorAction>) {
         use crate::input::CompositorAction;
@@ -1014,10 +1172,8 @@ impl AxiomSmithayBackendReal {
                     let focused_id = self.state.window_manager.read().focused_window_id();
                     if let Some(window_id) = focused_id {
                         info!("🗑️  Input: Close window {}", window_id);
-                        if let Some(&surface_id) = self.state.window_map.get(&window_id) {
-                            self.state.destroy_window(surface_id);
-                            self.state.needs_redraw = true;
-                        }
+                        self.state.request_window_close(window_id);
+                        self.state.needs_redraw = true;
                     }
                 }
                 CompositorAction::ToggleFullscreen => {
@@ -1039,7 +1195,16 @@ impl AxiomSmithayBackendReal {
                 CompositorAction::ToggleFloating => {
                     let focused_id = self.state.window_manager.read().focused_window_id();
                     if let Some(window_id) = focused_id {
-                        self.state.window_manager.write().toggle_floating(window_id);
+                        let area = crate::window::Rectangle::from_loc_and_size(
+                            (0, 0),
+                            (self.state.window_width, self.state.window_height),
+                        );
+                        let mut wm = self.state.window_manager.write();
+                        wm.toggle_floating(window_id);
+                        if wm.get_window(window_id).is_some_and(|w| w.properties.floating) {
+                            let _ = wm.place_floating_window(window_id, area);
+                        }
+                        drop(wm);
                         self.state
                             .workspace_manager
                             .write()
@@ -1098,6 +1263,117 @@ impl AxiomSmithayBackendReal {
                     self.state.needs_redraw = true;
                     info!("📺 Input: Focus next output");
                 }
+                CompositorAction::SwapWindowLeft => {
+                    self.swap_focused_window(crate::workspace::SwapDirection::Left);
+                }
+                CompositorAction::SwapWindowRight => {
+                    self.swap_focused_window(crate::workspace::SwapDirection::Right);
+                }
+                CompositorAction::ToggleSelection => {
+                    let mut wm = self.state.window_manager.write();
+                    if let Some(window_id) = wm.focused_window_id() {
+                        let selected = wm.toggle_selection(window_id);
+                        info!("☑️  Input: Window {} selected: {}", window_id, selected);
+                        self.state.needs_redraw = true;
+                    }
+                }
+                CompositorAction::MoveSelectedHere => {
+                    self.state.move_selected_to_column(None);
+                }
+                CompositorAction::CloseSelected => {
+                    self.state.close_selected_windows();
+                }
+                CompositorAction::CycleColumnNext | CompositorAction::CycleColumnPrev => {
+                    let forward = action == CompositorAction::CycleColumnNext;
+                    let focused_id = self.state.window_manager.read().focused_window_id();
+                    if let Some(window_id) = focused_id {
+                        self.cycle_column_focus(window_id, forward);
+                    }
+                }
+                CompositorAction::CascadeWindows => {
+                    let area = {
+                        let ws = self.state.workspace_manager.read();
+                        ws.output_work_area(ws.focused_output())
+                    };
+                    if let Some(area) = area {
+                        let arranged = self.state.window_manager.write().cascade_windows(area);
+                        info!("🪟 Input: Cascaded {} floating window(s)", arranged);
+                        self.state.needs_redraw = true;
+                    }
+                }
+                CompositorAction::CenterWindow => {
+                    let focused_id = self.state.window_manager.read().focused_window_id();
+                    if let Some(window_id) = focused_id {
+                        let area = {
+                            let ws = self.state.workspace_manager.read();
+                            ws.output_work_area(ws.output_for_window(window_id))
+                        };
+                        if let Some(area) = area {
+                            if self
+                                .state
+                                .window_manager
+                                .write()
+                                .center_window(window_id, area)
+                                .is_some()
+                            {
+                                self.state.needs_redraw = true;
+                            }
+                        }
+                    }
+                }
+                CompositorAction::MoveToScratchpad => {
+                    let focused_id = self.state.window_manager.read().focused_window_id();
+                    if let Some(window_id) = focused_id {
+                        self.state
+                            .workspace_manager
+                            .write()
+                            .set_window_floating(window_id, true);
+                        let next = {
+                            let mut wm = self.state.window_manager.write();
+                            wm.move_to_scratchpad(window_id);
+                            wm.focused_window_id()
+                        };
+                        if let Some(next) = next {
+                            self.focus_window_surface(next, SERIAL_COUNTER.next_serial());
+                        }
+                        info!("📥 Input: Moved window {} to the scratchpad", window_id);
+                        self.state.needs_redraw = true;
+                    }
+                }
+                CompositorAction::ToggleScratchpad => {
+                    // A focused scratchpad window is hidden again; otherwise
+                    // the most recently stashed one is shown.
+                    let target = {
+                        let wm = self.state.window_manager.read();
+                        wm.focused_window_id()
+                            .filter(|&id| wm.is_in_scratchpad(id))
+                            .or_else(|| wm.scratchpad_ids().last().copied())
+                    };
+                    let area = {
+                        let ws = self.state.workspace_manager.read();
+                        ws.output_work_area(ws.focused_output())
+                    };
+                    if let (Some(window_id), Some(area)) = (target, area) {
+                        let (shown, focus) = {
+                            let mut wm = self.state.window_manager.write();
+                            let shown = wm.toggle_scratchpad(window_id, area);
+                            (shown, wm.focused_window_id())
+                        };
+                        if let Some(focus) = focus {
+                            self.focus_window_surface(focus, SERIAL_COUNTER.next_serial());
+                        }
+                        debug!("📥 Input: Scratchpad window {} shown: {:?}", window_id, shown);
+                        self.state.needs_redraw = true;
+                    }
+                }
+                CompositorAction::CycleColorFilter => {
+                    let mut wm = self.state.window_manager.write();
+                    if let Some(window_id) = wm.focused_window_id() {
+                        let filter = wm.cycle_color_filter(window_id);
+                        info!("🎨 Input: Window {} colour filter: {:?}", window_id, filter);
+                        self.state.needs_redraw = true;
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,168 @@
+//! Layered compositing (`output.layered_compositing`).
+//!
+//! Normally the scene is drawn straight into the output's framebuffer. In
+//! layered mode, or whenever a post-process pass is configured, it is
+//! composed into an offscreen texture instead. Each pass then draws the
+//! frame so far through its shader into the next texture, and the last one
+//! draws onto the framebuffer, so effects that need the finished frame see
+//! every window and decoration at once. The direct path stays the default
+//! because the extra copy costs a full-screen draw per frame.
+
+use anyhow::Result;
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTarget, GlesTexProgram, GlesTexture};
+use smithay::backend::renderer::{Bind, Frame, Offscreen, Renderer, Texture};
+use smithay::utils::{Buffer, Physical, Rectangle, Size, Transform};
+
+use super::color::{matrix_uniform, ColorAdjust};
+use crate::config::OutputConfig;
+
+/// A post-process step over the whole composed frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum PostPass {
+    /// Every pixel through a colour matrix, in the layout of
+    /// [`ColorAdjust::matrix`]
+    ColorMatrix([[f32; 4]; 3]),
+}
+
+/// The passes `config` asks for, in order, or `None` to draw straight to
+/// the framebuffer. Layered mode with no passes still composes offscreen
+/// and blits the result unchanged.
+pub(super) fn post_passes(config: &OutputConfig) -> Option<Vec<PostPass>> {
+    let mut passes = Vec::new();
+    let adjust = ColorAdjust::from(config);
+    if !adjust.is_identity() {
+        passes.push(PostPass::ColorMatrix(adjust.matrix()));
+    }
+    (config.layered_compositing || !passes.is_empty()).then_some(passes)
+}
+
+/// Offscreen textures for layered frames, created the first frame one is
+/// needed and kept while the output size stays the same.
+#[derive(Debug, Default)]
+pub(super) struct Layers {
+    /// The composed scene, then every other pass's output
+    scene: Option<GlesTexture>,
+    /// Output of the passes between the scene and the last one
+    scratch: Option<GlesTexture>,
+}
+
+impl Layers {
+    /// Run `draw` against an offscreen layer, then apply `passes` and put
+    /// the result on `framebuffer`. `program` is the colour-matrix shader.
+    pub(super) fn render(
+        &mut self,
+        renderer: &mut GlesRenderer,
+        framebuffer: &mut GlesTarget<'_>,
+        size: Size<i32, Physical>,
+        passes: &[PostPass],
+        program: &GlesTexProgram,
+        draw: impl FnOnce(&mut GlesRenderer, &mut GlesTarget<'_>) -> Result<()>,
+    ) -> Result<()> {
+        let buffer_size = Size::from((size.w, size.h));
+        let mut source = layer(renderer, self.scene.take(), buffer_size)?;
+        {
+            let mut target = renderer.bind(&mut source)?;
+            draw(renderer, &mut target)?;
+        }
+
+        let (last, between) = match passes.split_last() {
+            Some((last, between)) => (Some(last), between),
+            None => (None, passes),
+        };
+        let mut scratch = self.scratch.take();
+        for pass in between {
+            let mut output = layer(renderer, scratch.take(), buffer_size)?;
+            {
+                let mut target = renderer.bind(&mut output)?;
+                apply(renderer, &mut target, &source, size, Some(pass), program)?;
+            }
+            scratch = Some(std::mem::replace(&mut source, output));
+        }
+        apply(renderer, framebuffer, &source, size, last, program)?;
+
+        self.scene = Some(source);
+        self.scratch = scratch;
+        Ok(())
+    }
+}
+
+/// `reuse` if it is still the right size, else a new layer.
+fn layer(
+    renderer: &mut GlesRenderer,
+    reuse: Option<GlesTexture>,
+    size: Size<i32, Buffer>,
+) -> Result<GlesTexture> {
+    match reuse {
+        Some(texture) if texture.size() == size => Ok(texture),
+        _ => Ok(Offscreen::<GlesTexture>::create_buffer(
+            renderer,
+            Fourcc::Abgr8888,
+            size,
+        )?),
+    }
+}
+
+/// Draw `source` over all of `target` through `pass`, or copy it as is.
+fn apply(
+    renderer: &mut GlesRenderer,
+    target: &mut GlesTarget<'_>,
+    source: &GlesTexture,
+    size: Size<i32, Physical>,
+    pass: Option<&PostPass>,
+    program: &GlesTexProgram,
+) -> Result<()> {
+    let full = Rectangle::from_size(size);
+    let mut frame = renderer.render(target, size, Transform::Normal)?;
+    let (program, uniforms) = match pass {
+        Some(PostPass::ColorMatrix(matrix)) => (Some(program), vec![matrix_uniform(*matrix)]),
+        None => (None, Vec::new()),
+    };
+    frame.render_texture_from_to(
+        source,
+        Rectangle::from_size(source.size()).to_f64(),
+        full,
+        &[full],
+        &[],
+        Transform::Normal,
+        1.0,
+        program,
+        &uniforms,
+    )?;
+    let _ = frame.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What a pass does to one straight-alpha pixel, as the shader does it.
+    fn run(passes: &[PostPass], pixel: [f32; 3]) -> [f32; 3] {
+        passes.iter().fold(pixel, |rgb, pass| match pass {
+            PostPass::ColorMatrix(m) => {
+                m.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2] + row[3])
+            }
+        })
+    }
+
+    #[test]
+    fn test_direct_path_unless_layered_or_post_processed() {
+        let mut config = OutputConfig::default();
+        assert_eq!(post_passes(&config), None);
+
+        // Layered without effects: composed offscreen, blitted unchanged
+        config.layered_compositing = true;
+        let passes = post_passes(&config).unwrap();
+        assert!(passes.is_empty());
+        assert_eq!(run(&passes, [1.0, 0.0, 0.0]), [1.0, 0.0, 0.0]);
+
+        // A colour adjustment is a post-process pass, layered or not
+        config.brightness = 0.5;
+        let passes = post_passes(&config).unwrap();
+        assert_eq!(passes.len(), 1);
+        assert_eq!(run(&passes, [1.0, 0.0, 0.0]), [0.5, 0.0, 0.0]);
+        config.layered_compositing = false;
+        assert_eq!(post_passes(&config), Some(passes));
+    }
+}
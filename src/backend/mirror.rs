@@ -0,0 +1,194 @@
+//! Output mirroring (clone mode).
+//!
+//! A mirrored output stops showing its own part of the scene and instead
+//! shows a copy of another output's region, scaled to fit. Outputs with
+//! different resolutions keep the source's aspect ratio: the copy is
+//! letterboxed in the middle of the mirror rather than stretched.
+//!
+//! Only the part of an element on the source is copied, so one straddling
+//! the source's edge is cut off at the edge of the letterbox, and only the
+//! part off the mirror's own region is drawn in place.
+
+use smithay::utils::{Buffer, Physical, Rectangle, Size, Transform};
+
+/// Maps scene geometry on a source output onto the output mirroring it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct MirrorMap {
+    source: Rectangle<i32, Physical>,
+    target: Rectangle<i32, Physical>,
+}
+
+impl MirrorMap {
+    pub(super) fn new(source: Rectangle<i32, Physical>, target: Rectangle<i32, Physical>) -> Self {
+        Self { source, target }
+    }
+
+    /// The copy on the mirror of an element drawn at `geometry` from
+    /// buffer region `src` with `transform`: the part of `src` on the
+    /// source and where to draw it. `None` when it is not on the source.
+    pub(super) fn map(
+        &self,
+        geometry: Rectangle<i32, Physical>,
+        src: Rectangle<f64, Buffer>,
+        transform: Transform,
+    ) -> Option<(Rectangle<f64, Buffer>, Rectangle<i32, Physical>)> {
+        if self.source.size.w <= 0 || self.source.size.h <= 0 {
+            return None;
+        }
+        if geometry.size.w <= 0 || geometry.size.h <= 0 {
+            return None;
+        }
+        let visible = geometry.intersection(self.source)?;
+        let dst = self.map_rect(visible)?;
+
+        // The kept part as a share of the element on screen, turned into
+        // buffer orientation and then into the element's buffer region
+        let share = |offset: i32, total: i32| offset as f64 / total as f64;
+        let kept = Rectangle::<f64, Buffer>::new(
+            (
+                share(visible.loc.x - geometry.loc.x, geometry.size.w),
+                share(visible.loc.y - geometry.loc.y, geometry.size.h),
+            )
+                .into(),
+            (
+                share(visible.size.w, geometry.size.w),
+                share(visible.size.h, geometry.size.h),
+            )
+                .into(),
+        );
+        let kept = transform
+            .invert()
+            .transform_rect_in(kept, &Size::from((1.0, 1.0)));
+        let src = Rectangle::new(
+            (
+                src.loc.x + kept.loc.x * src.size.w,
+                src.loc.y + kept.loc.y * src.size.h,
+            )
+                .into(),
+            (kept.size.w * src.size.w, kept.size.h * src.size.h).into(),
+        );
+        Some((src, dst))
+    }
+
+    /// Where `geometry`, which lies within the source, lands on the mirror.
+    fn map_rect(&self, geometry: Rectangle<i32, Physical>) -> Option<Rectangle<i32, Physical>> {
+        let scale = (self.target.size.w as f64 / self.source.size.w as f64)
+            .min(self.target.size.h as f64 / self.source.size.h as f64);
+        let fitted_w = (self.source.size.w as f64 * scale).round() as i32;
+        let fitted_h = (self.source.size.h as f64 * scale).round() as i32;
+        let origin_x = self.target.loc.x + (self.target.size.w - fitted_w) / 2;
+        let origin_y = self.target.loc.y + (self.target.size.h - fitted_h) / 2;
+
+        let x = origin_x + ((geometry.loc.x - self.source.loc.x) as f64 * scale).round() as i32;
+        let y = origin_y + ((geometry.loc.y - self.source.loc.y) as f64 * scale).round() as i32;
+        let w = (geometry.size.w as f64 * scale).round() as i32;
+        let h = (geometry.size.h as f64 * scale).round() as i32;
+        (w > 0 && h > 0).then(|| Rectangle::new((x, y).into(), (w, h).into()))
+    }
+
+    /// The parts of `rects` off the mirror's own region, whose content is
+    /// replaced by the copy and so must not be drawn.
+    pub(super) fn outside(
+        &self,
+        rects: Vec<Rectangle<i32, Physical>>,
+    ) -> Vec<Rectangle<i32, Physical>> {
+        rects
+            .into_iter()
+            .flat_map(|rect| rect.subtract_rect(self.target))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn laptop_to_projector() -> MirrorMap {
+        MirrorMap::new(
+            Rectangle::new((0, 0).into(), (1920, 1080).into()),
+            Rectangle::new((1920, 0).into(), (1280, 720).into()),
+        )
+    }
+
+    /// Where an element covering all of its 100x100 buffer is copied to.
+    fn copy_of(
+        mirror: &MirrorMap,
+        geometry: Rectangle<i32, Physical>,
+    ) -> Option<Rectangle<i32, Physical>> {
+        let src = Rectangle::new((0.0, 0.0).into(), (100.0, 100.0).into());
+        mirror
+            .map(geometry, src, Transform::Normal)
+            .map(|(_, dst)| dst)
+    }
+
+    #[test]
+    fn test_mirror_draws_source_region_scaled_onto_target() {
+        let mirror = laptop_to_projector();
+        let window = Rectangle::new((960, 540).into(), (300, 150).into());
+        let dst = copy_of(&mirror, window).unwrap();
+        assert_eq!(dst, Rectangle::new((2560, 360).into(), (200, 100).into()));
+
+        // The whole source lands exactly on the whole target
+        let full = Rectangle::new((0, 0).into(), (1920, 1080).into());
+        assert_eq!(
+            copy_of(&mirror, full),
+            Some(Rectangle::new((1920, 0).into(), (1280, 720).into()))
+        );
+    }
+
+    #[test]
+    fn test_mirror_letterboxes_mismatched_aspect_ratio() {
+        // 16:9 source onto a 4:3 mirror: bars above and below
+        let mirror = MirrorMap::new(
+            Rectangle::new((0, 0).into(), (1920, 1080).into()),
+            Rectangle::new((1920, 0).into(), (1024, 768).into()),
+        );
+        let full = Rectangle::new((0, 0).into(), (1920, 1080).into());
+        assert_eq!(
+            copy_of(&mirror, full),
+            Some(Rectangle::new((1920, 96).into(), (1024, 576).into()))
+        );
+    }
+
+    #[test]
+    fn test_mirror_clips_element_straddling_source_edge() {
+        let mirror = laptop_to_projector();
+        // Half of this window hangs off the source's bottom edge
+        let window = Rectangle::new((0, 930).into(), (300, 300).into());
+        let src = Rectangle::new((0.0, 0.0).into(), (600.0, 600.0).into());
+
+        let (src, dst) = mirror.map(window, src, Transform::Normal).unwrap();
+        assert_eq!(
+            src,
+            Rectangle::new((0.0, 0.0).into(), (600.0, 300.0).into())
+        );
+        assert_eq!(dst, Rectangle::new((1920, 620).into(), (200, 100).into()));
+        // Nothing lands below the letterbox
+        assert!(dst.loc.y + dst.size.h <= 720);
+
+        // A buffer drawn upside down keeps its other half
+        let flipped = Rectangle::new((0.0, 0.0).into(), (600.0, 600.0).into());
+        let (src, _) = mirror.map(window, flipped, Transform::_180).unwrap();
+        assert_eq!(
+            src,
+            Rectangle::new((0.0, 300.0).into(), (600.0, 300.0).into())
+        );
+    }
+
+    #[test]
+    fn test_mirror_ignores_elements_off_the_source_and_hides_its_own() {
+        let mirror = laptop_to_projector();
+        let on_target = Rectangle::new((2000, 100).into(), (400, 300).into());
+        assert_eq!(copy_of(&mirror, on_target), None);
+        assert!(mirror.outside(vec![on_target]).is_empty());
+        let on_source = Rectangle::new((100, 100).into(), (400, 300).into());
+        assert_eq!(mirror.outside(vec![on_source]), vec![on_source]);
+
+        // Only the part of a straddling element on the source is drawn in place
+        let straddling = Rectangle::new((1820, 100).into(), (200, 100).into());
+        assert_eq!(
+            mirror.outside(vec![straddling]),
+            vec![Rectangle::new((1820, 100).into(), (100, 100).into())]
+        );
+    }
+}
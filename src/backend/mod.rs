@@ -15,17 +15,47 @@
 pub mod state;
 pub mod winit;
 pub mod screencopy;
+mod background;
 mod clipboard;
+mod color;
+mod corners;
+mod cursor;
+mod foreign_toplevel;
+mod frame_callbacks;
+mod frame_pacer;
+mod geometry;
+mod hotplug;
+mod hud;
+mod ime;
 mod input;
+mod layers;
+mod mirror;
+mod occlusion;
+mod popups;
+mod presenter;
+mod recovery;
 mod render;
+mod security;
+mod text;
+mod upload_queue;
+mod viewport;
+mod virtual_input;
+mod wallpaper;
+mod window_stats;
 
 // Public API re-exports — same as when everything was in mod.rs.
 pub use state::State;
 pub use state::SurfaceData;
 pub use state::PopupState;
 pub use state::PendingCapture;
+pub use presenter::PresentCounters;
+pub use recovery::RendererChange;
+pub use cursor::{CursorImage, CursorPlane};
+pub use hotplug::OutputChange;
+pub use render::{merge_output_damage, partition_output_damage};
 pub use winit::AxiomSmithayBackendReal;
 pub use winit::BackendKind;
+pub use window_stats::WindowFrameStats;
 
 // Private re-exports so sibling submodules can access items from each other
 // via `use super::...`. These bring the names into the `backend` module scope,
@@ -0,0 +1,263 @@
+//! Occlusion from client-declared opaque regions.
+//!
+//! A window hides what is under it only where its client promised the
+//! pixels are opaque (`wl_surface.set_opaque_region`). Full window opacity
+//! isn't enough: a fully opaque window can still have transparent corners
+//! or shadows, and a window that declared a small opaque strip hides
+//! nothing outside it. Without a declared region the protocol says to
+//! assume the surface is transparent, so such a window occludes nothing.
+//!
+//! The same regions trim damage: a commit only repaints the part of the
+//! window that nothing opaque is stacked over.
+//!
+//! Rectangles here are in the layout space the render pass places windows
+//! in, which is what `render_scene_into` tags as `Physical`.
+
+use std::collections::{HashMap, HashSet};
+
+use smithay::utils::{Logical, Physical, Point, Rectangle, Size};
+use smithay::wayland::compositor::{RectangleKind, RegionAttributes};
+
+use crate::window::ColorFilter;
+
+/// A window as the last frame drew it.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct RenderedWindow {
+    pub(super) id: u64,
+    /// Where the client's content went, decorations excluded
+    pub(super) content: Rectangle<i32, Physical>,
+    /// The part of `content` that hides what is below: the client's opaque
+    /// region, or nothing for a translucent window
+    pub(super) opaque_region: Vec<Rectangle<i32, Physical>>,
+    /// Colour filter the content was drawn through
+    pub(super) color_filter: ColorFilter,
+}
+
+/// The opaque part of `region` as disjoint surface-local rectangles.
+pub(super) fn region_rects(region: &RegionAttributes) -> Vec<Rectangle<i32, Logical>> {
+    let mut rects: Vec<Rectangle<i32, Logical>> = Vec::new();
+    for (kind, rect) in &region.rects {
+        match kind {
+            RectangleKind::Add => {
+                // Keep the list disjoint so subtracting later stays exact
+                let added = rect.subtract_rects(rects.iter().copied());
+                rects.extend(added);
+            }
+            RectangleKind::Subtract => {
+                rects = rects
+                    .into_iter()
+                    .flat_map(|r| r.subtract_rect(*rect))
+                    .collect();
+            }
+        }
+    }
+    rects
+}
+
+/// Where a window whose content sits at `content` hides what's below it:
+/// its surface-local `opaque` rectangles moved onto the content and
+/// clipped to it.
+pub(super) fn opaque_area(
+    content: Rectangle<i32, Physical>,
+    opaque: &[Rectangle<i32, Logical>],
+) -> Vec<Rectangle<i32, Physical>> {
+    opaque
+        .iter()
+        .filter_map(|r| {
+            Rectangle::new(
+                Point::from((content.loc.x + r.loc.x, content.loc.y + r.loc.y)),
+                Size::from((r.size.w, r.size.h)),
+            )
+            .intersection(content)
+        })
+        .collect()
+}
+
+/// Whether `content` lies entirely under `covered`, the opaque areas of the
+/// windows in front of it. The areas may overlap and only need to cover it
+/// together, not one by one.
+pub(super) fn is_window_occluded(
+    content: Rectangle<i32, Physical>,
+    covered: &[Rectangle<i32, Physical>],
+) -> bool {
+    content.subtract_rects(covered.iter().copied()).is_empty()
+}
+
+/// Windows in `windows` (back to front) that are hidden entirely by the
+/// ones in front of them.
+pub(super) fn occluded_windows(windows: &[RenderedWindow]) -> HashSet<u64> {
+    let mut covered: Vec<Rectangle<i32, Physical>> = Vec::with_capacity(windows.len());
+    let mut occluded = HashSet::new();
+    for window in windows.iter().rev() {
+        if is_window_occluded(window.content, &covered) {
+            occluded.insert(window.id);
+        }
+        covered.extend(window.opaque_region.iter().copied());
+    }
+    occluded
+}
+
+/// The part of each window's pending `damage` that would show on screen
+/// with `windows` (back to front) stacked as they are: whatever falls
+/// under the opaque region of a window above it is dropped. Damage of a
+/// window that isn't in `windows` is kept whole, since nothing is known
+/// to cover it.
+pub(super) fn visible_damage(
+    windows: &[RenderedWindow],
+    damage: &HashMap<u64, Vec<Rectangle<i32, Physical>>>,
+) -> Vec<Rectangle<i32, Physical>> {
+    let mut visible = Vec::new();
+    let mut covered: Vec<Rectangle<i32, Physical>> = Vec::with_capacity(windows.len());
+    for window in windows.iter().rev() {
+        if let Some(rects) = damage.get(&window.id) {
+            for rect in rects {
+                visible.extend(rect.subtract_rects(covered.iter().copied()));
+            }
+        }
+        covered.extend(window.opaque_region.iter().copied());
+    }
+    for (id, rects) in damage {
+        if !windows.iter().any(|w| w.id == *id) {
+            visible.extend(rects.iter().copied());
+        }
+    }
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect<K>(x: i32, y: i32, w: i32, h: i32) -> Rectangle<i32, K> {
+        Rectangle::new(Point::from((x, y)), Size::from((w, h)))
+    }
+
+    #[test]
+    fn test_partial_opaque_region_only_occludes_within_it() {
+        // A 400x300 window at (100, 100) that is opaque only in its top
+        // 100 rows (say, a solid titlebar over a translucent body)
+        let front = rect(100, 100, 400, 300);
+        let covered = opaque_area(front, &[rect(0, 0, 400, 100)]);
+        assert_eq!(covered, vec![rect(100, 100, 400, 100)]);
+
+        // Entirely behind the opaque strip
+        assert!(is_window_occluded(rect(150, 120, 200, 50), &covered));
+        // Behind the window, but reaching into the translucent part
+        assert!(!is_window_occluded(rect(150, 120, 200, 150), &covered));
+        assert!(!is_window_occluded(rect(150, 250, 200, 100), &covered));
+    }
+
+    #[test]
+    fn test_opaque_areas_occlude_together() {
+        let left = opaque_area(rect(0, 0, 300, 300), &[rect(0, 0, 300, 300)]);
+        let right = opaque_area(rect(300, 0, 300, 300), &[rect(0, 0, 300, 300)]);
+        let covered: Vec<_> = left.into_iter().chain(right).collect();
+        // Neither window covers it alone, both together do
+        assert!(is_window_occluded(rect(200, 50, 200, 200), &covered));
+        assert!(!is_window_occluded(rect(200, 50, 200, 300), &covered));
+        // Nothing declared opaque hides nothing
+        assert!(opaque_area(rect(0, 0, 300, 300), &[]).is_empty());
+        assert!(!is_window_occluded(rect(10, 10, 10, 10), &[]));
+    }
+
+    #[test]
+    fn test_opaque_area_is_clipped_to_the_window() {
+        let covered = opaque_area(rect(100, 100, 200, 200), &[rect(-50, 150, 500, 100)]);
+        assert_eq!(covered, vec![rect(100, 250, 200, 50)]);
+    }
+
+    fn window(id: u64, content: Rectangle<i32, Physical>, opaque: bool) -> RenderedWindow {
+        let local = rect(0, 0, content.size.w, content.size.h);
+        RenderedWindow {
+            id,
+            content,
+            opaque_region: if opaque {
+                opaque_area(content, &[local])
+            } else {
+                Vec::new()
+            },
+            color_filter: ColorFilter::None,
+        }
+    }
+
+    #[test]
+    fn test_opaque_foreground_removes_covered_background_damage() {
+        // Background window 1 fills 0..800; opaque window 2 sits on its
+        // right half
+        let windows = [
+            window(1, rect(0, 0, 800, 600), true),
+            window(2, rect(400, 0, 400, 600), true),
+        ];
+        let damage = HashMap::from([(1, vec![rect(200, 100, 400, 100)])]);
+        assert_eq!(
+            visible_damage(&windows, &damage),
+            vec![rect(200, 100, 200, 100)]
+        );
+        assert_eq!(occluded_windows(&windows), HashSet::new());
+
+        // Fully under the foreground: nothing to repaint
+        let damage = HashMap::from([(1, vec![rect(500, 100, 100, 100)])]);
+        assert!(visible_damage(&windows, &damage).is_empty());
+
+        // The foreground's own damage is never trimmed
+        let damage = HashMap::from([(2, vec![rect(500, 100, 100, 100)])]);
+        assert_eq!(
+            visible_damage(&windows, &damage),
+            vec![rect(500, 100, 100, 100)]
+        );
+    }
+
+    #[test]
+    fn test_translucent_foreground_keeps_background_damage() {
+        let windows = [
+            window(1, rect(0, 0, 800, 600), true),
+            window(2, rect(0, 0, 800, 600), false),
+        ];
+        let damage = HashMap::from([
+            (1, vec![rect(10, 10, 50, 50)]),
+            // Not drawn last frame: kept as is
+            (9, vec![rect(0, 0, 5, 5)]),
+        ]);
+        let mut visible = visible_damage(&windows, &damage);
+        visible.sort_by_key(|r| r.loc.x);
+        assert_eq!(visible, vec![rect(0, 0, 5, 5), rect(10, 10, 50, 50)]);
+        assert!(occluded_windows(&windows).is_empty());
+
+        let windows = [
+            window(1, rect(100, 100, 200, 200), true),
+            window(2, rect(0, 0, 800, 600), true),
+        ];
+        assert_eq!(occluded_windows(&windows), HashSet::from([1]));
+    }
+
+    #[test]
+    fn test_region_rects_applies_adds_and_subtracts_in_order() {
+        let region = RegionAttributes {
+            rects: vec![
+                (RectangleKind::Add, rect(0, 0, 100, 100)),
+                // A transparent hole in the middle
+                (RectangleKind::Subtract, rect(25, 25, 50, 50)),
+            ],
+        };
+        let rects = region_rects(&region);
+        let area: i32 = rects.iter().map(|r| r.size.w * r.size.h).sum();
+        assert_eq!(area, 100 * 100 - 50 * 50);
+        let content = rect(0, 0, 100, 100);
+        let covered = opaque_area(content, &rects);
+        assert!(!is_window_occluded(rect(40, 40, 10, 10), &covered));
+        assert!(is_window_occluded(rect(0, 0, 100, 20), &covered));
+
+        // Overlapping adds don't count twice
+        let region = RegionAttributes {
+            rects: vec![
+                (RectangleKind::Add, rect(0, 0, 60, 10)),
+                (RectangleKind::Add, rect(40, 0, 60, 10)),
+            ],
+        };
+        let area: i32 = region_rects(&region)
+            .iter()
+            .map(|r| r.size.w * r.size.h)
+            .sum();
+        assert_eq!(area, 100 * 10);
+    }
+}
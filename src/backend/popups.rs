@@ -0,0 +1,350 @@
+//! Popup placement and grabs.
+//!
+//! Popups are placed where their `xdg_positioner` asks, then flipped, slid
+//! or resized as it allows so a menu opened near a screen edge stays on the
+//! output's work area.
+//!
+//! A menu that takes an `xdg_popup.grab` owns input until it is dismissed:
+//! the keyboard goes to the topmost popup, and Escape or a click outside
+//! every popup in the chain closes the whole chain. Submenus are closed
+//! before the menus they hang off, as `xdg_popup` requires.
+
+use std::collections::{HashMap, HashSet};
+
+use log::{debug, info};
+use smithay::utils::{Logical, Rectangle, SERIAL_COUNTER};
+use smithay::wayland::shell::xdg::{PopupSurface, PositionerState};
+use wayland_server::Resource;
+
+use super::geometry::{clamp_to_outputs, global_to_output, output_layout};
+use super::State;
+use crate::window::Rectangle as WindowRectangle;
+
+/// Popup geometry relative to a parent at global `parent_origin`, flipped,
+/// slid or resized as `positioner` allows to stay inside global `area`.
+pub(super) fn constrain_popup(
+    positioner: &PositionerState,
+    parent_origin: (i32, i32),
+    area: &WindowRectangle,
+) -> Rectangle<i32, Logical> {
+    let target = Rectangle::new(
+        (area.x - parent_origin.0, area.y - parent_origin.1).into(),
+        (area.width as i32, area.height as i32).into(),
+    );
+    positioner.get_unconstrained_geometry(target)
+}
+
+/// The popups to close, innermost first, to dismiss the chain `popup`
+/// belongs to. `parents` maps each popup to its parent surface; the chain
+/// is rooted at the first popup whose parent is not itself a popup.
+pub(super) fn dismissal_order(parents: &HashMap<u32, u32>, popup: u32) -> Vec<u32> {
+    if !parents.contains_key(&popup) {
+        return Vec::new();
+    }
+    // The seen sets keep a client that parents popups in a cycle from
+    // sending either walk round forever.
+    let mut root = popup;
+    let mut seen = HashSet::from([popup]);
+    while let Some(&parent) = parents.get(&root) {
+        if !parents.contains_key(&parent) || !seen.insert(parent) {
+            break;
+        }
+        root = parent;
+    }
+
+    let mut order = Vec::new();
+    push_subtree(parents, root, &mut HashSet::new(), &mut order);
+    order
+}
+
+/// Append `id` and every popup below it to `order`, children before their
+/// parent.
+fn push_subtree(
+    parents: &HashMap<u32, u32>,
+    id: u32,
+    seen: &mut HashSet<u32>,
+    order: &mut Vec<u32>,
+) {
+    if !seen.insert(id) {
+        return;
+    }
+    let mut children: Vec<u32> = parents
+        .iter()
+        .filter(|&(_, &parent)| parent == id)
+        .map(|(&child, _)| child)
+        .collect();
+    children.sort_unstable();
+    for child in children {
+        push_subtree(parents, child, seen, order);
+    }
+    order.push(id);
+}
+
+impl State {
+    fn popup_parents(&self) -> HashMap<u32, u32> {
+        self.popups
+            .iter()
+            .map(|(&id, popup)| (id, popup.parent_surface_id))
+            .collect()
+    }
+
+    /// Global position of a window's or popup's origin: the offsets of
+    /// each popup up the chain, plus the position of the window at the
+    /// root. Popup coordinates are relative to this.
+    fn surface_origin(&self, surface_id: u32) -> (i32, i32) {
+        let (mut x, mut y) = (0, 0);
+        let mut surface = surface_id;
+        // Bounded so a cycle of parents can't spin forever
+        for _ in 0..=self.popups.len() {
+            let Some(p) = self.popups.get(&surface) else {
+                break;
+            };
+            x += p.x;
+            y += p.y;
+            surface = p.parent_surface_id;
+        }
+        let window_pos = self
+            .window_map
+            .iter()
+            .find(|(_, &id)| id == surface)
+            .and_then(|(&window_id, _)| {
+                self.window_manager
+                    .read()
+                    .get_window(window_id)
+                    .map(|w| w.window.position)
+            })
+            .unwrap_or((0, 0));
+        (window_pos.0 + x, window_pos.1 + y)
+    }
+
+    /// Global rectangle `(x, y, width, height)` of a popup.
+    pub(super) fn popup_global_rect(&self, popup_id: u32) -> Option<(i32, i32, i32, i32)> {
+        let popup = self.popups.get(&popup_id)?;
+        let (x, y) = self.surface_origin(popup_id);
+        Some((x, y, popup.width, popup.height))
+    }
+
+    /// Where a popup of `parent_id` described by `positioner` goes, relative
+    /// to its parent, once moved back inside the work area of the output
+    /// under the parent.
+    pub(super) fn place_popup(
+        &self,
+        parent_id: u32,
+        positioner: &PositionerState,
+    ) -> Rectangle<i32, Logical> {
+        let origin = self.surface_origin(parent_id);
+        let ws = self.workspace_manager.read();
+        let outputs = output_layout(&ws);
+        let point = clamp_to_outputs((origin.0 as f64, origin.1 as f64), &outputs);
+        let work_area = global_to_output(point, &outputs)
+            .and_then(|(index, _)| ws.output_work_area(&outputs[index].0));
+        match work_area {
+            Some(area) => constrain_popup(positioner, origin, &area),
+            None => positioner.get_geometry(),
+        }
+    }
+
+    /// Whether global point (`x`, `y`) lands on any popup of the grabbed
+    /// chain. Clicks there belong to the menus; anywhere else dismisses.
+    pub(super) fn popup_grab_contains(&self, x: f64, y: f64) -> bool {
+        let Some(grab) = self.active_popup_grab else {
+            return false;
+        };
+        dismissal_order(&self.popup_parents(), grab)
+            .into_iter()
+            .filter_map(|id| self.popup_global_rect(id))
+            .any(|(px, py, w, h)| {
+                x >= px as f64 && y >= py as f64 && x < (px + w) as f64 && y < (py + h) as f64
+            })
+    }
+
+    /// Close the popup chain `popup_id` belongs to, innermost popup first,
+    /// and give the keyboard back to the window the chain hangs off.
+    /// Returns the popups closed, in order.
+    pub fn cleanup_popup_hierarchy(&mut self, popup_id: u32) -> Vec<u32> {
+        let order = dismissal_order(&self.popup_parents(), popup_id);
+        let owner = order
+            .last()
+            .and_then(|root| self.popups.get(root))
+            .map(|root| root.parent_surface_id);
+        for id in &order {
+            if let Some(popup) = self.popups.remove(id) {
+                debug!("🗑️ Dismissing popup surface {}", id);
+                popup.surface.send_popup_done();
+            }
+        }
+        if self
+            .active_popup_grab
+            .is_some_and(|grab| order.contains(&grab))
+        {
+            self.active_popup_grab = None;
+            let owner_surface = owner
+                .and_then(|id| self.surfaces.get(&id))
+                .and_then(|sd| sd.surface.clone())
+                .filter(|s| s.is_alive());
+            if let (Some(surface), Some(keyboard)) = (owner_surface, self.seat.get_keyboard()) {
+                keyboard.set_focus(self, Some(surface), SERIAL_COUNTER.next_serial());
+            }
+        }
+        if !order.is_empty() {
+            self.needs_redraw = true;
+        }
+        order
+    }
+
+    /// Dismiss the grabbed popup chain, if there is one. Returns whether
+    /// anything was closed.
+    pub(super) fn dismiss_popup_grab(&mut self) -> bool {
+        let Some(grab) = self.active_popup_grab else {
+            return false;
+        };
+        info!("🗑️ Dismissing grabbed popup chain of surface {}", grab);
+        let closed = self.cleanup_popup_hierarchy(grab);
+        // A grab whose popup is already gone has nothing left to close
+        self.active_popup_grab = None;
+        !closed.is_empty()
+    }
+
+    /// `xdg_popup.grab`: the popup becomes the topmost grab and takes the
+    /// keyboard. A submenu may only grab while its parent holds the grab;
+    /// one that tries anyway is dismissed, as the protocol asks.
+    pub(super) fn start_popup_grab(&mut self, surface: &PopupSurface) {
+        let surface_id = surface.wl_surface().id().protocol_id();
+        let Some(parent) = self.popups.get(&surface_id).map(|p| p.parent_surface_id) else {
+            surface.send_popup_done();
+            return;
+        };
+        if self.popups.contains_key(&parent) && self.active_popup_grab != Some(parent) {
+            info!(
+                "🤚 Popup {} grabbed without its parent {} holding the grab; dismissing",
+                surface_id, parent
+            );
+            if let Some(popup) = self.popups.remove(&surface_id) {
+                popup.surface.send_popup_done();
+            }
+            return;
+        }
+        info!("🤚 Popup grab activated for surface {}", surface_id);
+        self.active_popup_grab = Some(surface_id);
+        if let Some(keyboard) = self.seat.get_keyboard() {
+            keyboard.set_focus(
+                self,
+                Some(surface.wl_surface().clone()),
+                SERIAL_COUNTER.next_serial(),
+            );
+        }
+    }
+
+    /// The client destroyed a popup. The grab falls back to its parent
+    /// when that is a popup too, as when a submenu closes.
+    pub(super) fn forget_popup(&mut self, surface_id: u32) {
+        let Some(popup) = self.popups.remove(&surface_id) else {
+            return;
+        };
+        if self.active_popup_grab == Some(surface_id) {
+            let parent = popup.parent_surface_id;
+            self.active_popup_grab = self.popups.contains_key(&parent).then_some(parent);
+            let target = match self.active_popup_grab {
+                Some(id) => self.popups.get(&id).map(|p| p.surface.wl_surface().clone()),
+                None => self.surfaces.get(&parent).and_then(|sd| sd.surface.clone()),
+            }
+            .filter(|s| s.is_alive());
+            if let (Some(target), Some(keyboard)) = (target, self.seat.get_keyboard()) {
+                keyboard.set_focus(self, Some(target), SERIAL_COUNTER.next_serial());
+            }
+        }
+        self.needs_redraw = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wayland_protocols::xdg::shell::server::xdg_positioner::{
+        Anchor, ConstraintAdjustment, Gravity,
+    };
+
+    fn output() -> WindowRectangle {
+        WindowRectangle::from_loc_and_size((0, 0), (1920, 1080))
+    }
+
+    /// A 200x300 menu hanging below-right of a 20x20 button at (150, 10)
+    /// in its window.
+    fn menu(adjustment: ConstraintAdjustment) -> PositionerState {
+        PositionerState {
+            rect_size: (200, 300).into(),
+            anchor_rect: Rectangle::new((150, 10).into(), (20, 20).into()),
+            anchor_edges: Anchor::BottomRight,
+            gravity: Gravity::BottomRight,
+            constraint_adjustment: adjustment,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_menu_near_right_edge_flips_to_open_leftward() {
+        let positioner = menu(ConstraintAdjustment::FlipX);
+        // Window at x = 1700: unflipped the menu would end at x = 2070
+        let rect = constrain_popup(&positioner, (1700, 100), &output());
+        assert_eq!(rect, Rectangle::new((-50, 30).into(), (200, 300).into()));
+        assert!(1700 + rect.loc.x + rect.size.w <= 1920);
+
+        // With room to spare the positioner is left alone
+        let rect = constrain_popup(&positioner, (100, 100), &output());
+        assert_eq!(rect, positioner.get_geometry());
+    }
+
+    #[test]
+    fn test_menu_near_bottom_edge_slides_up() {
+        let positioner = menu(ConstraintAdjustment::SlideY);
+        // Window at y = 900: the menu would run from 930 to 1230
+        let rect = constrain_popup(&positioner, (100, 900), &output());
+        assert_eq!(rect, Rectangle::new((170, -120).into(), (200, 300).into()));
+        assert_eq!(900 + rect.loc.y + rect.size.h, 1080);
+    }
+
+    #[test]
+    fn test_menu_taller_than_work_area_is_resized() {
+        let positioner = PositionerState {
+            rect_size: (200, 1200).into(),
+            constraint_adjustment: ConstraintAdjustment::SlideY | ConstraintAdjustment::ResizeY,
+            ..menu(ConstraintAdjustment::empty())
+        };
+        // A 40px panel at the top of the output
+        let area = WindowRectangle::from_loc_and_size((0, 40), (1920, 1040));
+        let rect = constrain_popup(&positioner, (100, 100), &area);
+        assert!(100 + rect.loc.y >= 40);
+        assert!(100 + rect.loc.y + rect.size.h <= 1080);
+    }
+
+    /// Window surface 10 with menu 20, whose submenu 30 has submenu 40;
+    /// menu 20 also has a second submenu 31. Window 11 has its own menu 50.
+    fn menu_tree() -> HashMap<u32, u32> {
+        HashMap::from([(20, 10), (30, 20), (31, 20), (40, 30), (50, 11)])
+    }
+
+    #[test]
+    fn test_dismissal_closes_nested_chain_innermost_first() {
+        let parents = menu_tree();
+        // Escape while the deepest submenu holds the grab
+        assert_eq!(dismissal_order(&parents, 40), vec![40, 30, 31, 20]);
+        // Same chain whichever popup of it is asked for
+        assert_eq!(dismissal_order(&parents, 20), vec![40, 30, 31, 20]);
+        assert_eq!(dismissal_order(&parents, 50), vec![50]);
+    }
+
+    #[test]
+    fn test_dismissal_of_unknown_popup_closes_nothing() {
+        assert!(dismissal_order(&menu_tree(), 10).is_empty());
+        assert!(dismissal_order(&HashMap::new(), 20).is_empty());
+    }
+
+    #[test]
+    fn test_dismissal_order_survives_a_parent_cycle() {
+        // A misbehaving client can't send the walk round forever
+        let parents = HashMap::from([(1, 2), (2, 1)]);
+        let order = dismissal_order(&parents, 1);
+        assert_eq!(order.len(), 2);
+        assert!(order.contains(&1) && order.contains(&2));
+    }
+}
@@ -0,0 +1,242 @@
+//! Deciding when to present, and accounting for what was presented.
+//!
+//! The event loop feeds [`Presenter`] resizes and redraw requests and asks
+//! it whether this cycle should draw; keeping that decision out of the loop
+//! lets it be tested without a window. Binding and submitting the output
+//! surface also go through it, so it can count frames presented, frames
+//! dropped on a transient failure and surfaces lost outright. Stutter is
+//! hard to tell apart from a slow client without those numbers.
+
+use log::warn;
+use smithay::backend::SwapBuffersError;
+
+/// Present totals since startup, exposed through the IPC metrics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PresentCounters {
+    /// Frames submitted to the output surface
+    pub presented: u64,
+    /// Frames abandoned because the surface was not ready (a timeout or a
+    /// buffer that was already swapped); the next redraw tries again
+    pub dropped: u64,
+    /// Times the GL context or surface was lost or went out of date
+    pub lost: u64,
+}
+
+/// What the event loop knows when a cycle might draw.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct RedrawInputs {
+    /// Compositor-side state (focus, layout, decorations...) changed since
+    /// the last frame
+    pub(super) requested: bool,
+    /// Clients reported damage since the last frame
+    pub(super) damaged: bool,
+    /// A workspace scroll or focus transition is still moving
+    pub(super) animating: bool,
+    /// The scene has anything to show besides the background
+    pub(super) has_content: bool,
+}
+
+/// Decides which cycles present and counts what happened to each frame.
+#[derive(Debug)]
+pub(super) struct Presenter {
+    counters: PresentCounters,
+    /// Output surface size last reported by the windowing system
+    size: Option<(u32, u32)>,
+    /// Present on the next redraw whatever it asks for: nothing has been
+    /// drawn yet, or the surface was resized and its contents are stale
+    force_next: bool,
+    /// Whether the frame on screen is the bare background
+    showing_empty: bool,
+    /// `showing_empty` for the frame being drawn, applied once it presents
+    drawing_empty: bool,
+}
+
+impl Presenter {
+    pub(super) fn new() -> Self {
+        Self {
+            counters: PresentCounters::default(),
+            size: None,
+            force_next: true,
+            showing_empty: false,
+            drawing_empty: false,
+        }
+    }
+
+    /// The output surface changed size. Returns whether it actually did;
+    /// if so the next redraw presents unconditionally.
+    pub(super) fn on_resize(&mut self, width: u32, height: u32) -> bool {
+        let changed = self.size != Some((width, height));
+        if changed {
+            self.size = Some((width, height));
+            self.force_next = true;
+        }
+        changed
+    }
+
+    /// Present on the next redraw no matter what, e.g. because what is on
+    /// screen was drawn by a renderer that no longer exists.
+    pub(super) fn force_next(&mut self) {
+        self.force_next = true;
+    }
+
+    /// Whether this cycle should draw and present a frame.
+    ///
+    /// Only changed frames are presented: something must have been damaged,
+    /// requested or be mid-animation, otherwise the frame would be identical
+    /// to the one on screen. Once the last window goes, one more frame
+    /// clears it and then the bare background stops being redrawn until
+    /// something shows up again.
+    pub(super) fn on_redraw(&mut self, inputs: RedrawInputs) -> bool {
+        let changed = inputs.requested || inputs.damaged || inputs.animating;
+        let present =
+            self.force_next || (changed && (inputs.has_content || !self.showing_empty));
+        if present {
+            self.drawing_empty = !inputs.has_content;
+        }
+        present
+    }
+
+    /// Account for a failed bind or submit and hand the result back.
+    pub(super) fn check<T>(
+        &mut self,
+        result: Result<T, SwapBuffersError>,
+    ) -> Result<T, SwapBuffersError> {
+        match &result {
+            Ok(_) => {}
+            Err(SwapBuffersError::ContextLost(err)) => {
+                self.counters.lost += 1;
+                warn!(
+                    "🖼️ Output surface lost ({} so far): {}",
+                    self.counters.lost, err
+                );
+            }
+            Err(err) => {
+                self.counters.dropped += 1;
+                warn!("🖼️ Dropped frame ({} so far): {}", self.counters.dropped, err);
+            }
+        }
+        result
+    }
+
+    /// A frame was submitted successfully.
+    pub(super) fn presented(&mut self) {
+        self.counters.presented += 1;
+        self.force_next = false;
+        self.showing_empty = self.drawing_empty;
+    }
+
+    pub(super) fn frame_stats(&self) -> PresentCounters {
+        self.counters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(msg: &str) -> Box<dyn std::error::Error + Send + Sync> {
+        Box::new(std::io::Error::other(msg.to_string()))
+    }
+
+    /// Run one event-loop cycle the way `run_one_cycle_common` does,
+    /// returning whether a frame was presented.
+    fn cycle(presenter: &mut Presenter, requested: bool, has_content: bool) -> bool {
+        let present = presenter.on_redraw(RedrawInputs {
+            requested,
+            has_content,
+            ..Default::default()
+        });
+        if present {
+            presenter.presented();
+        }
+        present
+    }
+
+    #[test]
+    fn test_on_redraw_with_content_presents_each_request() {
+        let mut presenter = Presenter::new();
+        // The first frame always goes out
+        assert!(cycle(&mut presenter, false, true));
+        assert!(!cycle(&mut presenter, false, true));
+        assert!(cycle(&mut presenter, true, true));
+        assert!(cycle(&mut presenter, true, true));
+        assert_eq!(presenter.frame_stats().presented, 3);
+    }
+
+    #[test]
+    fn test_on_redraw_without_content_clears_once_then_idles() {
+        let mut presenter = Presenter::new();
+        assert!(cycle(&mut presenter, true, true));
+        // The last window closed: one frame to clear it, then nothing
+        assert!(cycle(&mut presenter, true, false));
+        assert!(!cycle(&mut presenter, true, false));
+        assert!(!cycle(&mut presenter, true, false));
+
+        // A resize makes the old background stale
+        assert!(presenter.on_resize(1280, 720));
+        assert!(!presenter.on_resize(1280, 720));
+        assert!(cycle(&mut presenter, false, false));
+        assert!(!cycle(&mut presenter, true, false));
+
+        // Content coming back presents again
+        assert!(cycle(&mut presenter, true, true));
+    }
+
+    #[test]
+    fn test_on_redraw_without_damage_does_not_present() {
+        let mut presenter = Presenter::new();
+        assert!(cycle(&mut presenter, false, true));
+        let idle = RedrawInputs {
+            has_content: true,
+            ..Default::default()
+        };
+        for _ in 0..3 {
+            assert!(!presenter.on_redraw(idle));
+        }
+        assert_eq!(presenter.frame_stats().presented, 1);
+
+        // Client damage or a running animation each present on their own
+        assert!(presenter.on_redraw(RedrawInputs {
+            damaged: true,
+            ..idle
+        }));
+        assert!(presenter.on_redraw(RedrawInputs {
+            animating: true,
+            ..idle
+        }));
+    }
+
+    #[test]
+    fn test_failed_present_is_retried() {
+        let mut presenter = Presenter::new();
+        assert!(cycle(&mut presenter, true, true));
+        // Drawing the cleared frame fails, so the window is still on screen
+        assert!(presenter.on_redraw(RedrawInputs {
+            requested: true,
+            ..Default::default()
+        }));
+        assert!(cycle(&mut presenter, true, false));
+    }
+
+    #[test]
+    fn test_counters_track_simulated_surface_errors() {
+        let mut presenter = Presenter::new();
+        assert!(presenter.check(Ok(())).is_ok());
+        presenter.presented();
+
+        let timeout = presenter.check::<()>(Err(SwapBuffersError::TemporaryFailure(failure("timeout"))));
+        assert!(matches!(timeout, Err(SwapBuffersError::TemporaryFailure(_))));
+        let _ = presenter.check::<()>(Err(SwapBuffersError::AlreadySwapped));
+        let lost = presenter.check::<()>(Err(SwapBuffersError::ContextLost(failure("outdated"))));
+        assert!(matches!(lost, Err(SwapBuffersError::ContextLost(_))));
+
+        assert_eq!(
+            presenter.frame_stats(),
+            PresentCounters {
+                presented: 1,
+                dropped: 2,
+                lost: 1,
+            }
+        );
+    }
+}
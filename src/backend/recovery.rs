@@ -0,0 +1,202 @@
+//! Getting the picture back after a GPU reset.
+//!
+//! A driver crash or a GPU reset (TDR) loses the GL context: binding or
+//! presenting the output surface fails with `ContextLost` from then on and
+//! the screen stays frozen on the last frame. The render path reports the
+//! loss here, and the next cycle rebuilds the renderer on a fresh context,
+//! drops every texture made with the old one and redraws all windows,
+//! whose content is uploaded again from the client buffers still attached
+//! to their surfaces. Window placement lives in the workspace and window
+//! managers and is untouched.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use smithay::backend::egl::context::{GlAttributes, PixelFormatRequirements};
+use smithay::backend::egl::EGLContext;
+use smithay::backend::renderer::gles::GlesRenderer;
+
+use super::color::ColorPass;
+use super::corners::CornerPass;
+use super::layers::Layers;
+use super::render::TextureLimits;
+use super::text::TextRenderer;
+use super::upload_queue::UploadQueue;
+use super::wallpaper::WallpaperCache;
+use super::{AxiomSmithayBackendReal, BackendKind};
+
+/// How long to wait before trying again when rebuilding the renderer
+/// fails, e.g. because the GPU is still resetting.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A change in whether the renderer can draw, reported over IPC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RendererChange {
+    /// The GPU context was lost; nothing is presented until it is rebuilt
+    Lost,
+    /// The renderer was rebuilt and `windows` windows were queued to be
+    /// uploaded and drawn again
+    Recovered { windows: usize },
+}
+
+/// Tracks a lost renderer until it has been rebuilt.
+#[derive(Debug, Default)]
+pub(super) struct RendererRecovery {
+    /// The context is lost and no rebuild has worked yet
+    lost: bool,
+    /// Don't try to rebuild again before this
+    retry_at: Option<Instant>,
+    changes: Vec<RendererChange>,
+}
+
+impl RendererRecovery {
+    /// The render path saw the context go. Losing it again before the
+    /// rebuild is not news.
+    pub(super) fn lost(&mut self) {
+        if !self.lost {
+            self.lost = true;
+            self.retry_at = None;
+            self.changes.push(RendererChange::Lost);
+        }
+    }
+
+    /// Whether the renderer should be rebuilt this cycle.
+    pub(super) fn due(&self, now: Instant) -> bool {
+        self.lost && self.retry_at.is_none_or(|at| now >= at)
+    }
+
+    pub(super) fn rebuilt(&mut self, windows: usize) {
+        self.lost = false;
+        self.retry_at = None;
+        self.changes.push(RendererChange::Recovered { windows });
+    }
+
+    pub(super) fn rebuild_failed(&mut self, now: Instant) {
+        self.retry_at = Some(now + RETRY_INTERVAL);
+    }
+
+    pub(super) fn take_changes(&mut self) -> Vec<RendererChange> {
+        std::mem::take(&mut self.changes)
+    }
+}
+
+impl AxiomSmithayBackendReal {
+    /// Renderer losses and recoveries since the last call, oldest first.
+    pub fn take_renderer_changes(&mut self) -> Vec<RendererChange> {
+        self.renderer_recovery.take_changes()
+    }
+
+    /// Rebuild the renderer if the context was lost and it's time to try.
+    pub(super) fn recover_renderer(&mut self) {
+        let now = Instant::now();
+        if !self.renderer_recovery.due(now) {
+            return;
+        }
+        info!("🔧 GPU context lost, rebuilding the renderer");
+
+        // Everything uploaded through the old context is gone with it
+        self.state.texture_cache.clear();
+        self.state.wallpapers = WallpaperCache::default();
+        self.state.texture_limits = TextureLimits::default();
+        self.state.upload_queue = UploadQueue::default();
+        self.state.color_pass = ColorPass::default();
+        self.state.corner_pass = CornerPass::default();
+        self.state.layers = Layers::default();
+        self.state.text_renderer = TextRenderer::default();
+
+        if let Err(e) = self.rebuild_renderer() {
+            warn!(
+                "🔧 Couldn't rebuild the renderer, retrying in {}s: {:#}",
+                RETRY_INTERVAL.as_secs(),
+                e
+            );
+            self.renderer_recovery.rebuild_failed(now);
+            return;
+        }
+
+        // Redraw every window from scratch; the render pass re-imports
+        // their buffers since none are cached any more
+        let windows = self.state.window_map.len();
+        self.state.rendered_windows.clear();
+        self.state.output_damage.clear();
+        self.state.needs_redraw = true;
+        self.presenter.force_next();
+        self.renderer_recovery.rebuilt(windows);
+        info!("✅ Renderer rebuilt, redrawing {} window(s)", windows);
+    }
+
+    /// Replace the winit backend's renderer with one on a new context from
+    /// the same EGL display. The output surface stays; it was created
+    /// with the same attributes, so the new context can bind it.
+    fn rebuild_renderer(&mut self) -> Result<()> {
+        if self.backend_kind == BackendKind::Noop {
+            return Ok(());
+        }
+        let Some(backend) = self.winit_backend.as_mut() else {
+            return Ok(());
+        };
+        let display = backend.renderer().egl_context().display().clone();
+        let attributes = GlAttributes {
+            version: (3, 0),
+            profile: None,
+            debug: cfg!(debug_assertions),
+            vsync: false,
+        };
+        let context =
+            EGLContext::new_with_config(&display, attributes, PixelFormatRequirements::_10_bit())
+                .or_else(|_| {
+                    EGLContext::new_with_config(
+                        &display,
+                        attributes,
+                        PixelFormatRequirements::_8_bit(),
+                    )
+                })
+                .context("failed to create a GL context")?;
+        // SAFETY: the context was just created for this renderer and is not
+        // current on any other thread.
+        let renderer = unsafe { GlesRenderer::new(context) }
+            .map_err(|e| anyhow::anyhow!("failed to create a renderer: {:?}", e))?;
+        *backend.renderer() = renderer;
+        backend
+            .bind()
+            .map(drop)
+            .context("the output surface rejected the new GL context")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loss_is_reported_once_until_rebuilt() {
+        let mut recovery = RendererRecovery::default();
+        let now = Instant::now();
+        assert!(!recovery.due(now));
+
+        recovery.lost();
+        recovery.lost();
+        assert!(recovery.due(now));
+        recovery.rebuilt(2);
+        assert!(!recovery.due(now));
+        assert_eq!(
+            recovery.take_changes(),
+            vec![
+                RendererChange::Lost,
+                RendererChange::Recovered { windows: 2 }
+            ]
+        );
+        assert!(recovery.take_changes().is_empty());
+    }
+
+    #[test]
+    fn test_failed_rebuild_waits_before_retrying() {
+        let mut recovery = RendererRecovery::default();
+        let now = Instant::now();
+        recovery.lost();
+        recovery.rebuild_failed(now);
+        assert!(!recovery.due(now + Duration::from_millis(500)));
+        assert!(recovery.due(now + RETRY_INTERVAL));
+    }
+}
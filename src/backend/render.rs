@@ -6,13 +6,18 @@
 //! no fields were made public for this move.
 
 use crate::decoration::{DecorationMode, WindowDecoration};
+use crate::window::ColorFilter;
 use crate::window::Rectangle as WindowRectangle;
+use crate::workspace::BackgroundBlend;
 use crate::workspace::scale_to_logical;
 use anyhow::Result;
 use log::{debug, warn};
 use smithay::backend::allocator::Fourcc;
-use smithay::backend::renderer::gles::{GlesFrame, GlesRenderer, GlesTarget, GlesTexture};
-use smithay::backend::renderer::ExportMem;
+use smithay::backend::SwapBuffersError;
+use smithay::backend::renderer::gles::{
+    ffi, GlesFrame, GlesRenderer, GlesTarget, GlesTexProgram, GlesTexture,
+};
+use smithay::backend::renderer::{buffer_dimensions, buffer_type, BufferType, ExportMem};
 use smithay::backend::renderer::{
     element::{
         solid::{SolidColorBuffer, SolidColorRenderElement},
@@ -23,63 +28,118 @@ use smithay::backend::renderer::{
 };
 use smithay::utils::{Physical, Point, Rectangle, Size, Transform};
 use smithay::wayland::compositor::{
-    get_children, with_states, BufferAssignment, SubsurfaceCachedState, SurfaceAttributes,
+    get_children, with_states, with_surface_tree_downward, BufferAssignment, SubsurfaceCachedState,
+    SurfaceAttributes, TraversalAction,
 };
 use smithay::wayland::session_lock::LockSurface;
-use smithay::wayland::shell::wlr_layer::LayerSurfaceCachedState;
+use smithay::wayland::shell::wlr_layer::{Layer, LayerSurfaceCachedState};
 use smithay::wayland::shm::with_buffer_contents_mut;
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 use wayland_server::backend::ObjectId;
 use wayland_server::protocol::wl_buffer::WlBuffer;
 use wayland_server::protocol::wl_surface::WlSurface;
 use wayland_server::Resource;
 
+use super::background::{BackgroundFill, CLEAR_COLOR};
+use super::color::{filter_matrix, matrix_uniform};
+use super::corners::WindowCorners;
+use super::frame_callbacks::outputs_showing;
+use super::geometry::{output_bounds, output_layout};
+use super::hud::RenderStats;
+use super::layers::post_passes;
+use super::mirror::MirrorMap;
+use super::occlusion::{self, RenderedWindow};
+use super::upload_queue::{UploadPriority, UploadRequest};
+use super::viewport::{resolve_viewport, surface_viewport, BufferLayout};
+use super::wallpaper::cover_crop;
 use super::{AxiomSmithayBackendReal, State};
 
+/// Opacity of the frame drawn around selected windows, relative to the
+/// theme's focused border colour.
+const SELECTION_HIGHLIGHT_ALPHA: f32 = 0.6;
+
+thread_local! {
+    /// Elements drawn since `render` last reset it, for the debug HUD.
+    /// Counted here so the draw helpers needn't thread a counter through.
+    static DRAW_CALLS: Cell<u32> = const { Cell::new(0) };
+}
+
 impl State {
     /// Calculate workspace layouts, synchronize window geometry, and notify
     /// Wayland clients of size changes. Shared by nested and DRM render paths.
-    fn prepare_render_scene(&mut self) -> HashMap<u64, WindowRectangle> {
+    pub(super) fn prepare_render_scene(&mut self) -> HashMap<u64, WindowRectangle> {
         let mut layouts = self.workspace_manager.read().calculate_workspace_layouts();
 
-        // Fullscreen windows fill the entire output viewport
-        let fullscreen_ids: Vec<u64> = {
+        // Maximized windows fill their output's work area. Re-fitting every
+        // frame keeps them in step with panels appearing or resizing.
+        {
+            let ws = self.workspace_manager.read();
+            let mut wm = self.window_manager.write();
+            let mut maximized = Vec::new();
+            wm.for_each_window(|id, w| {
+                if w.properties.maximized && !w.properties.fullscreen && !w.properties.minimized {
+                    maximized.push(id);
+                }
+            });
+            for window_id in maximized {
+                let Some(area) = ws.output_work_area(ws.output_for_window(window_id)) else {
+                    continue;
+                };
+                wm.maximize_window(window_id, area.clone());
+                if let Some(rect) = layouts.get_mut(&window_id) {
+                    *rect = area;
+                }
+            }
+        }
+
+        // Fullscreen windows are left out of tiling and cover their whole
+        // output, ignoring gaps and reserved insets.
+        {
+            let ws = self.workspace_manager.read();
+            let mut wm = self.window_manager.write();
+            let mut fullscreen = Vec::new();
+            wm.for_each_window(|id, w| {
+                if w.properties.fullscreen && !w.properties.minimized {
+                    fullscreen.push(id);
+                }
+            });
+            for window_id in fullscreen {
+                let output = ws
+                    .output_geometry(ws.output_for_window(window_id))
+                    .unwrap_or(WindowRectangle {
+                        x: 0,
+                        y: 0,
+                        width: self.window_width,
+                        height: self.window_height,
+                    });
+                wm.enter_fullscreen(window_id, output.clone());
+                layouts.insert(window_id, output);
+            }
+        }
+
+        // Scratchpad windows are floating, so tiling never places them; a
+        // shown one is drawn at the geometry it was centred to.
+        {
             let wm = self.window_manager.read();
-            layouts
-                .keys()
-                .filter(|&id| {
-                    wm.get_window(*id)
-                        .map(|w| w.properties.fullscreen)
-                        .unwrap_or(false)
-                })
-                .copied()
-                .collect()
-        };
-        for &window_id in &fullscreen_ids {
-            layouts.insert(
-                window_id,
-                WindowRectangle {
-                    x: 0,
-                    y: 0,
-                    width: self.window_width,
-                    height: self.window_height,
-                },
-            );
+            for window_id in wm.scratchpad_ids() {
+                if let Some(window) = wm.get_window(window_id).filter(|w| !w.properties.minimized) {
+                    layouts.insert(window_id, window.rect());
+                }
+            }
         }
 
         {
             let mut wm = self.window_manager.write();
             for (window_id, layout_rect) in &layouts {
-                // Update window geometry (non-floating, non-fullscreen windows)
-                if let Some(window) = wm.get_window_mut(*window_id) {
-                    if !window.properties.floating {
-                        if !window.properties.fullscreen {
-                            window.window.set_position(layout_rect.x, layout_rect.y);
-                        }
-                        window
-                            .window
-                            .set_size(layout_rect.width, layout_rect.height);
-                    }
+                // Update window geometry (tiled and fullscreen windows)
+                // through the window manager, so listeners hear of it
+                let laid_out = wm
+                    .get_window(*window_id)
+                    .is_some_and(|w| !w.properties.floating || w.properties.fullscreen);
+                if laid_out {
+                    wm.set_window_geometry(*window_id, layout_rect.clone());
                 }
 
                 // Send configure notifications to toplevels (same loop, avoids
@@ -139,20 +199,54 @@ impl AxiomSmithayBackendReal {
         let Some(backend) = self.winit_backend.as_mut() else {
             return Ok(());
         };
-        if let Some(icon) = self.state.cursor_icon {
-            backend.window().set_cursor(icon);
+        if self.state.cursor_surface.is_some() {
+            // Client cursor surfaces are composited in software below.
+            backend.window().set_cursor_visible(false);
+        } else {
+            backend.window().set_cursor_visible(true);
+            if let Some(icon) = self.state.cursor_icon {
+                backend.window().set_cursor(icon);
+            }
         }
+        let started = Instant::now();
+        DRAW_CALLS.set(0);
         {
             // Composite into the bound framebuffer; drop the framebuffer borrow
             // before presenting so `backend.submit` can re-borrow `winit_backend`.
-            let (renderer, mut framebuffer) = backend.bind()?;
+            let (renderer, mut framebuffer) = match self.presenter.check(backend.bind()) {
+                Ok(bound) => bound,
+                Err(e) => {
+                    if matches!(e, SwapBuffersError::ContextLost(_)) {
+                        self.renderer_recovery.lost();
+                    }
+                    return Err(e.into());
+                }
+            };
 
             // When multi-output is enabled, prepare elements per-output.
             // Each output renders its region within the shared framebuffer.
+            //
+            // Outputs whose region took no damage this frame are skipped.
+            // Empty damage means "unknown" and redraws every output, as does
+            // damage that missed all of them.
             #[cfg(feature = "multi-output-experimental")]
             {
                 let outputs = self.state.outputs.clone();
+                let damaged = {
+                    let geometries = output_layout(&self.state.workspace_manager.read());
+                    // Damage on a mirrored source also lands on its mirror,
+                    // so with mirrors active every output is redrawn.
+                    if self.state.output_mirrors.is_empty() {
+                        partition_output_damage(&self.state.output_damage, &geometries)
+                    } else {
+                        HashMap::new()
+                    }
+                };
                 for output in &outputs {
+                    if !damaged.is_empty() && !damaged.contains_key(&output.name()) {
+                        debug!("🖥️ Skipping undamaged output {}", output.name());
+                        continue;
+                    }
                     let _layouts = prepare_render_elements_for_output(&mut self.state, output);
                     render_scene_into(&mut self.state, renderer, &mut framebuffer)?;
                 }
@@ -165,40 +259,44 @@ impl AxiomSmithayBackendReal {
             // Capture screencopy after rendering (if a client requested one).
             Self::capture_screencopy(&mut self.state, renderer, &mut framebuffer);
         }
-        let damage: Option<Vec<Rectangle<i32, Physical>>> = if self.state.output_damage.is_empty() {
-            None
-        } else {
-            // ponytail: bounding-box merge of all output damage for simplicity.
-            // Switch to OutputDamageTracker for per-element occlusion culling.
-            let mut min_x = i32::MAX;
-            let mut min_y = i32::MAX;
-            let mut max_x = i32::MIN;
-            let mut max_y = i32::MIN;
-            for r in &self.state.output_damage {
-                min_x = min_x.min(r.loc.x);
-                min_y = min_y.min(r.loc.y);
-                max_x = max_x.max(r.loc.x + r.size.w);
-                max_y = max_y.max(r.loc.y + r.size.h);
-            }
-            // Clamp to window dimensions
-            let w = self.state.window_width as i32;
-            let h = self.state.window_height as i32;
-            min_x = min_x.max(0);
-            min_y = min_y.max(0);
-            max_x = max_x.min(w);
-            max_y = max_y.min(h);
-            if min_x >= max_x || min_y >= max_y {
-                None
+        // Mirrors repaint a scaled copy of their source, which the damage
+        // rects (in source coordinates) don't cover, so submit everything.
+        // So does the HUD, whose numbers change every frame.
+        let size = (
+            self.state.window_width as i32,
+            self.state.window_height as i32,
+        );
+        let damage: Option<Vec<Rectangle<i32, Physical>>> =
+            if self.state.output_mirrors.is_empty() && !self.state.hud.enabled {
+                merge_output_damage(&self.state.output_damage, size).map(|r| vec![r])
             } else {
-                Some(vec![Rectangle::new(
-                    Point::from((min_x, min_y)),
-                    Size::from((max_x - min_x, max_y - min_y)),
-                )])
+                None
+            };
+        if let Err(e) = self.presenter.check(backend.submit(damage.as_deref())) {
+            if matches!(e, SwapBuffersError::ContextLost(_)) {
+                self.renderer_recovery.lost();
             }
+            return Err(e.into());
+        }
+        self.presenter.presented();
+        self.frame_pacer.presented(Instant::now());
+        let stats = RenderStats {
+            frame_time: started.elapsed(),
+            damage: damage_fraction(&self.state.output_damage, size),
+            draw_calls: DRAW_CALLS.get(),
         };
-        backend.submit(damage.as_deref())?;
+        let windows = self.state.window_manager.read().window_count();
+        let now = Instant::now();
+        self.state.hud.frame_presented(now, stats, windows);
+        if let Some(mode) = self.state.outputs.first().and_then(|o| o.current_mode()) {
+            self.frame_pacer.set_refresh_mhz(mode.refresh);
+        }
         self.state.output_damage.clear();
         backend.window().pre_present_notify();
+        self.state.send_frame_callbacks_after_present(
+            self.frame_pacer.refresh(),
+            self.presenter.frame_stats().presented,
+        );
         Ok(())
     }
 }
@@ -246,6 +344,37 @@ impl AxiomSmithayBackendReal {
         Some((w, h, pixels.to_vec()))
     }
 
+    /// Render the scene and read back only the `w`x`h` rect at `(x, y)`,
+    /// for region screenshots and screencasts.
+    ///
+    /// Coordinates are display pixels with a top-left origin. The rect is
+    /// clamped to the framebuffer, so the returned size is what was
+    /// actually captured. Pixels come back as tightly packed ARGB8888 rows,
+    /// top row first (unlike [`Self::capture_pixels`], which returns GL's
+    /// bottom-up order).
+    pub fn capture_region(&mut self, x: i32, y: i32, w: u32, h: u32) -> Result<(Vec<u8>, u32, u32)> {
+        if self.backend_kind == crate::backend::BackendKind::Noop {
+            anyhow::bail!("the headless backend has no framebuffer to capture");
+        }
+        let framebuffer_size = (self.state.window_width, self.state.window_height);
+        let region = capture_readback_rect((x, y, w, h), framebuffer_size).ok_or_else(|| {
+            anyhow::anyhow!(
+                "capture region {}x{} at ({}, {}) is outside the {}x{} framebuffer",
+                w, h, x, y, framebuffer_size.0, framebuffer_size.1
+            )
+        })?;
+        let backend = self
+            .winit_backend
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("no GL context to capture from"))?;
+        let (renderer, mut framebuffer) = backend.bind()?;
+        render_scene_into(&mut self.state, renderer, &mut framebuffer)?;
+        let mapping = renderer.copy_framebuffer(&framebuffer, region, Fourcc::Argb8888)?;
+        let pixels = renderer.map_texture(&mapping)?;
+        let (w, h) = (region.size.w as u32, region.size.h as u32);
+        Ok((repack_readback_rows(pixels, w, h), w, h))
+    }
+
     /// Capture the current composited frame into a pending screencopy buffer.
     ///
     /// Called from `render()` after `render_scene_into()` has composed into the
@@ -319,9 +448,6 @@ impl AxiomSmithayBackendReal {
 /// Recursively import buffers for a surface and all its subsurface children
 /// into the texture cache.
 fn import_surface_tree(state: &mut State, renderer: &mut GlesRenderer, surface: &WlSurface) {
-    use smithay::backend::renderer::element::texture::TextureBuffer;
-    use smithay::utils::Transform;
-
     let buf: Option<WlBuffer> = with_states(surface, |states| {
         match states
             .cached_state
@@ -340,11 +466,10 @@ fn import_surface_tree(state: &mut State, renderer: &mut GlesRenderer, surface:
     });
     if let Some(ref buf) = buf {
         let bid = buf.id();
-        if !state.texture_cache.contains(&bid) {
+        if !state.texture_cache.contains(&bid) && state.texture_limits.allows(renderer, buf) {
             match renderer.import_buffer(buf, None, &[]) {
                 Some(Ok(tex)) => {
-                    let tb =
-                        TextureBuffer::from_texture(&*renderer, tex, 1, Transform::Normal, None);
+                    let tb = BufferLayout::of(surface).texture_buffer(renderer, tex);
                     state.texture_cache.put(bid.clone(), tb);
                 }
                 Some(Err(e)) => warn!("⚠️ Subsurface buffer import error: {:?}", e),
@@ -357,9 +482,394 @@ fn import_surface_tree(state: &mut State, renderer: &mut GlesRenderer, surface:
     }
 }
 
+/// Bytes copied to upload the shm buffers in `surface`'s tree that aren't
+/// cached yet. Dmabufs are imported without a copy and cost nothing.
+fn pending_upload_bytes(state: &State, surface: &WlSurface) -> u64 {
+    let mut bytes = 0;
+    with_surface_tree_downward(
+        surface,
+        (),
+        |_, _, &()| TraversalAction::DoChildren(()),
+        |_, states, &()| {
+            if let Some(BufferAssignment::NewBuffer(buffer)) = &states
+                .cached_state
+                .get::<SurfaceAttributes>()
+                .current()
+                .buffer
+            {
+                let id = buffer.id();
+                if matches!(buffer_type(buffer), Some(BufferType::Shm))
+                    && !state.texture_cache.contains(&id)
+                    && !state.texture_limits.refused(&id)
+                {
+                    if let Some(size) = buffer_dimensions(buffer) {
+                        bytes += size.w.max(0) as u64 * size.h.max(0) as u64 * 4;
+                    }
+                }
+            }
+        },
+        |_, _, &()| true,
+    );
+    bytes
+}
+
+/// Guards texture uploads against buffers larger than the GPU's maximum
+/// texture dimension. Importing one fails, and since nothing gets cached
+/// the import would be retried (and fail) on every frame.
+#[derive(Debug, Default)]
+pub(super) struct TextureLimits {
+    /// `GL_MAX_TEXTURE_SIZE`, queried on first use. 0 if unknown.
+    max_size: Option<i32>,
+    /// Oversized buffers already reported, so the warning isn't repeated
+    /// every frame.
+    rejected: HashSet<ObjectId>,
+}
+
+impl TextureLimits {
+    /// Whether `buffer` may be uploaded. Oversized buffers are skipped, so
+    /// their surface isn't drawn, and logged once.
+    pub(super) fn allows(&mut self, renderer: &mut GlesRenderer, buffer: &WlBuffer) -> bool {
+        let max = *self
+            .max_size
+            .get_or_insert_with(|| query_max_texture_size(renderer));
+        let Some(size) = buffer_dimensions(buffer) else {
+            return true;
+        };
+        if fits_texture_limit((size.w, size.h), max) {
+            return true;
+        }
+        if self.rejected.insert(buffer.id()) {
+            warn!(
+                "⚠️ Not drawing {}x{} buffer: larger than the GPU's {}px texture limit",
+                size.w, size.h, max
+            );
+        }
+        false
+    }
+
+    /// Whether `buffer` was already refused as oversized.
+    pub(super) fn refused(&self, buffer: &ObjectId) -> bool {
+        self.rejected.contains(buffer)
+    }
+
+    /// Drop bookkeeping for a destroyed buffer.
+    pub(super) fn forget(&mut self, buffer: &ObjectId) {
+        self.rejected.remove(buffer);
+    }
+}
+
+fn query_max_texture_size(renderer: &mut GlesRenderer) -> i32 {
+    renderer
+        .with_context(|gl| {
+            let mut size = 0;
+            // SAFETY: the context is current inside `with_context` and
+            // `size` outlives the call.
+            unsafe { gl.GetIntegerv(ffi::MAX_TEXTURE_SIZE, &mut size) };
+            size
+        })
+        .unwrap_or(0)
+}
+
+/// Whether a `(width, height)` buffer fits a `max`-pixel texture limit.
+/// A limit of 0 or less means unknown and lets everything through.
+fn fits_texture_limit((w, h): (i32, i32), max: i32) -> bool {
+    max <= 0 || (w <= max && h <= max)
+}
+
+/// Merge a frame's damage into the single rect handed to `submit`,
+/// clamped to the `(width, height)` of the output. `None` means nothing on
+/// screen changed (or all damage fell outside the output).
+///
+/// ponytail: bounding-box merge of all output damage for simplicity.
+/// Switch to OutputDamageTracker for per-element occlusion culling.
+pub fn merge_output_damage(
+    damage: &[Rectangle<i32, Physical>],
+    (w, h): (i32, i32),
+) -> Option<Rectangle<i32, Physical>> {
+    if damage.is_empty() {
+        return None;
+    }
+    let mut min_x = i32::MAX;
+    let mut min_y = i32::MAX;
+    let mut max_x = i32::MIN;
+    let mut max_y = i32::MIN;
+    for r in damage {
+        min_x = min_x.min(r.loc.x);
+        min_y = min_y.min(r.loc.y);
+        max_x = max_x.max(r.loc.x + r.size.w);
+        max_y = max_y.max(r.loc.y + r.size.h);
+    }
+    min_x = min_x.max(0);
+    min_y = min_y.max(0);
+    max_x = max_x.min(w);
+    max_y = max_y.min(h);
+    (min_x < max_x && min_y < max_y).then(|| {
+        Rectangle::new(
+            Point::from((min_x, min_y)),
+            Size::from((max_x - min_x, max_y - min_y)),
+        )
+    })
+}
+
+/// Share of a `(width, height)` output that a frame with `damage` redraws.
+/// A frame without damage is submitted whole, so it counts as all of it.
+fn damage_fraction(damage: &[Rectangle<i32, Physical>], (w, h): (i32, i32)) -> f32 {
+    if w <= 0 || h <= 0 {
+        return 0.0;
+    }
+    match merge_output_damage(damage, (w, h)) {
+        Some(r) => {
+            let area = f64::from(r.size.w) * f64::from(r.size.h);
+            (area / (f64::from(w) * f64::from(h))) as f32
+        }
+        None => 1.0,
+    }
+}
+
+/// Split a frame's damage between outputs, clipping each rect to the
+/// output it lands on. Outputs that nothing touched are left out of the
+/// map, so a presenter with per-output scanout can skip them entirely.
+pub fn partition_output_damage(
+    damage: &[Rectangle<i32, Physical>],
+    outputs: &[(String, WindowRectangle)],
+) -> HashMap<String, Vec<Rectangle<i32, Physical>>> {
+    let mut per_output: HashMap<String, Vec<Rectangle<i32, Physical>>> = HashMap::new();
+    for (name, geometry) in outputs {
+        let bounds = output_bounds(geometry);
+        let clipped: Vec<_> = damage.iter().filter_map(|r| r.intersection(bounds)).collect();
+        if !clipped.is_empty() {
+            per_output.insert(name.clone(), clipped);
+        }
+    }
+    per_output
+}
+
+/// Build the texture element for `surface`'s buffer at `location`, cropped
+/// and scaled by its `wp_viewport` if it has one. `None` means the viewport
+/// is invalid for the current buffer and the surface should not be drawn.
+fn viewported_texture_element(
+    surface: &WlSurface,
+    tb: &TextureBuffer<GlesTexture>,
+    location: (f64, f64),
+    alpha: Option<f32>,
+) -> Option<TextureRenderElement<GlesTexture>> {
+    let full = TextureRenderElement::from_texture_buffer(
+        Point::from(location),
+        tb,
+        alpha,
+        None,
+        None,
+        Kind::Unspecified,
+    );
+    let Some((src, dst)) = surface_viewport(surface) else {
+        return Some(full);
+    };
+    // Texture buffers carry the surface's buffer scale and transform, so the
+    // element's unscaled geometry is the surface size the viewport crops.
+    let buffer = full.geometry(smithay::utils::Scale::from(1.0)).size;
+    let (src, dst) = resolve_viewport(Size::from((buffer.w, buffer.h)), src, dst)?;
+    Some(TextureRenderElement::from_texture_buffer(
+        Point::from(location),
+        tb,
+        alpha,
+        Some(src),
+        Some(dst),
+        Kind::Unspecified,
+    ))
+}
+
+/// The four edges `(x, y, w, h)` of a frame `width` pixels thick drawn
+/// just inside `rect`.
+fn selection_frame(rect: &WindowRectangle, width: i32) -> [(i32, i32, i32, i32); 4] {
+    let (w, h) = (rect.width as i32, rect.height as i32);
+    let width = width.min(w / 2).min(h / 2).max(0);
+    [
+        (rect.x, rect.y, w, width),
+        (rect.x, rect.y + h - width, w, width),
+        (rect.x, rect.y + width, width, h - 2 * width),
+        (rect.x + w - width, rect.y + width, width, h - 2 * width),
+    ]
+}
+
+/// `rect` less the squares of its rounded corners, `top` and `bottom`
+/// pixels on a side, as `(x, y, w, h)` rects covering the rest.
+fn corner_free_rects(rect: &WindowRectangle, top: i32, bottom: i32) -> Vec<(i32, i32, i32, i32)> {
+    let (w, h) = (rect.width as i32, rect.height as i32);
+    let top = top.clamp(0, w / 2).min(h / 2);
+    let bottom = bottom.clamp(0, w / 2).min(h / 2);
+    let mut rects = vec![(rect.x, rect.y + top, w, h - top - bottom)];
+    if top > 0 {
+        rects.push((rect.x + top, rect.y, w - 2 * top, top));
+    }
+    if bottom > 0 {
+        rects.push((rect.x + bottom, rect.y + h - bottom, w - 2 * bottom, bottom));
+    }
+    rects.retain(|&(_, _, w, h)| w > 0 && h > 0);
+    rects
+}
+
+/// A window's draw-time scale about its centre, in physical pixels.
+///
+/// The element path only draws axis-aligned rects, so this is the part of
+/// a [`crate::window::WindowTransform`] the renderer can honour; rotation
+/// is carried by the transform but not drawn yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Zoom {
+    center: (f64, f64),
+    factor: f64,
+}
+
+impl Zoom {
+    /// The zoom for a window drawn at `rect` on an output with `scale`, or
+    /// `None` when it is drawn at its natural size.
+    fn for_window(
+        rect: &WindowRectangle,
+        transform: &crate::window::WindowTransform,
+        scale: smithay::utils::Scale<f64>,
+    ) -> Option<Self> {
+        (transform.scale != 1.0).then(|| Self {
+            center: (
+                (rect.x as f64 + rect.width as f64 / 2.0) * scale.x,
+                (rect.y as f64 + rect.height as f64 / 2.0) * scale.y,
+            ),
+            factor: transform.scale.max(0.0),
+        })
+    }
+
+    fn apply(&self, g: Rectangle<i32, Physical>) -> Rectangle<i32, Physical> {
+        let (cx, cy) = self.center;
+        let x = cx + (g.loc.x as f64 - cx) * self.factor;
+        let y = cy + (g.loc.y as f64 - cy) * self.factor;
+        let w = g.size.w as f64 * self.factor;
+        let h = g.size.h as f64 * self.factor;
+        Rectangle::new(
+            Point::from((x.round() as i32, y.round() as i32)),
+            Size::from((w.round() as i32, h.round() as i32)),
+        )
+    }
+}
+
+/// Draw `elem` at its own geometry, then again on every output mirroring
+/// the region it sits in. Elements on a mirror's own region are skipped,
+/// since that region shows the copy instead.
+fn draw_element<E: RenderElement<GlesRenderer>>(
+    frame: &mut GlesFrame<'_, '_>,
+    elem: &E,
+    scale: smithay::utils::Scale<f64>,
+    mirrors: &[MirrorMap],
+) -> Result<()> {
+    draw_element_zoomed(frame, elem, scale, mirrors, None)
+}
+
+/// [`draw_element`], with the element's geometry first scaled by `zoom`.
+fn draw_element_zoomed<E: RenderElement<GlesRenderer>>(
+    frame: &mut GlesFrame<'_, '_>,
+    elem: &E,
+    scale: smithay::utils::Scale<f64>,
+    mirrors: &[MirrorMap],
+    zoom: Option<Zoom>,
+) -> Result<()> {
+    let mut g = elem.geometry(scale);
+    if let Some(zoom) = zoom {
+        g = zoom.apply(g);
+    }
+    for draw in element_draws(g, elem.src(), elem.transform(), mirrors) {
+        elem.draw(frame, draw.src, draw.dst, &draw.damage, &[])?;
+        DRAW_CALLS.set(DRAW_CALLS.get() + 1);
+    }
+    Ok(())
+}
+
+/// [`draw_element_zoomed`] for a surface texture of `tex_size`, through the
+/// corner shader with `corners`' uniforms worked out for each draw.
+fn draw_texture_rounded(
+    frame: &mut GlesFrame<'_, '_>,
+    elem: &TextureRenderElement<GlesTexture>,
+    tex_size: Size<i32, smithay::utils::Buffer>,
+    scale: smithay::utils::Scale<f64>,
+    mirrors: &[MirrorMap],
+    zoom: Option<Zoom>,
+    corners: &WindowCorners,
+) -> Result<()> {
+    let mut g = elem.geometry(scale);
+    if let Some(zoom) = zoom {
+        g = zoom.apply(g);
+    }
+    let unscaled = elem.geometry(smithay::utils::Scale::from(1.0)).size;
+    let logical = Size::from((unscaled.w, unscaled.h));
+    let (src, transform) = (elem.src(), elem.transform());
+    for draw in element_draws(g, src, transform, mirrors) {
+        let uniforms = corners.uniforms(tex_size, src, logical, (draw.src, draw.dst), transform);
+        frame.override_default_tex_program(corners.program.clone(), uniforms);
+        let drawn = elem.draw(frame, draw.src, draw.dst, &draw.damage, &[]);
+        frame.clear_tex_program_override();
+        drawn?;
+        DRAW_CALLS.set(DRAW_CALLS.get() + 1);
+    }
+    Ok(())
+}
+
+/// One draw of an element: `src` of its buffer into `dst`, limited to
+/// `damage`.
+#[derive(Debug, Clone, PartialEq)]
+struct ElementDraw {
+    src: Rectangle<f64, smithay::utils::Buffer>,
+    dst: Rectangle<i32, Physical>,
+    damage: Vec<Rectangle<i32, Physical>>,
+}
+
+/// The draws [`draw_element_zoomed`] makes for an element at `g`: in place,
+/// less any part on a mirror's own region, then its copy on each mirror
+/// showing it.
+fn element_draws(
+    g: Rectangle<i32, Physical>,
+    src: Rectangle<f64, smithay::utils::Buffer>,
+    transform: Transform,
+    mirrors: &[MirrorMap],
+) -> Vec<ElementDraw> {
+    if g.size.w <= 0 || g.size.h <= 0 {
+        return Vec::new();
+    }
+    let mut draws = Vec::new();
+    let in_place = mirrors.iter().fold(vec![g], |rects, m| m.outside(rects));
+    if !in_place.is_empty() {
+        draws.push(ElementDraw {
+            src,
+            dst: g,
+            damage: in_place,
+        });
+    }
+    for (src, dst) in mirrors.iter().filter_map(|m| m.map(g, src, transform)) {
+        draws.push(ElementDraw {
+            src,
+            dst,
+            damage: vec![dst],
+        });
+    }
+    draws
+}
+
+/// Rebuild the geometry mappings for the configured output mirrors. Pairs
+/// whose outputs have gone away are left out until they come back.
+fn update_mirror_maps(state: &mut State) {
+    let ws = state.workspace_manager.read();
+    state.mirror_maps = state
+        .output_mirrors
+        .iter()
+        .filter_map(|(target, source)| {
+            let source = ws.output_geometry(source)?;
+            let target = ws.output_geometry(target)?;
+            Some(MirrorMap::new(output_bounds(&source), output_bounds(&target)))
+        })
+        .collect();
+}
+
 /// Recursively draw a surface and all its subsurface children from the
 /// texture cache. `offset_x/offset_y` is the absolute screen position of
-/// this surface's top-left corner in logical pixels.
+/// this surface's top-left corner in logical pixels. With `corners`, the
+/// surface is drawn through the corner shader and its children through the
+/// same shading with square corners.
+#[allow(clippy::too_many_arguments)]
 fn draw_surface_tree(
     state: &mut State,
     frame: &mut GlesFrame<'_, '_>,
@@ -367,13 +877,10 @@ fn draw_surface_tree(
     offset_x: f64,
     offset_y: f64,
     scale: smithay::utils::Scale<f64>,
+    alpha: f32,
+    zoom: Option<Zoom>,
+    corners: Option<&WindowCorners>,
 ) -> Result<(), anyhow::Error> {
-    use smithay::backend::renderer::element::texture::TextureRenderElement;
-    use smithay::backend::renderer::element::Kind;
-    use smithay::backend::renderer::element::RenderElement;
-    use smithay::backend::renderer::gles::GlesTexture;
-    use smithay::utils::Point;
-
     // Draw this surface's texture if available
     let buf: Option<WlBuffer> = with_states(surface, |states| {
         match states
@@ -388,26 +895,33 @@ fn draw_surface_tree(
         }
     });
     if let Some(buf) = buf {
-        if let Some(tb) = state.texture_cache.get(&buf.id()) {
-            let te = TextureRenderElement::from_texture_buffer(
-                Point::from((offset_x, offset_y)),
-                tb,
-                None,
-                None,
-                None,
-                Kind::Unspecified,
-            );
-            let tg = te.geometry(scale);
-            <TextureRenderElement<GlesTexture> as RenderElement<GlesRenderer>>::draw(
-                &te,
+        // A buffer still waiting for upload draws as the one before it
+        let bid = if state.texture_cache.contains(&buf.id()) {
+            state.upload_queue.set_shown(surface.id(), buf.id());
+            Some(buf.id())
+        } else {
+            state.upload_queue.shown_buffer(&surface.id()).cloned()
+        };
+        let te = bid
+            .and_then(|bid| state.texture_cache.get(&bid))
+            .and_then(|tb| {
+                viewported_texture_element(surface, tb, (offset_x, offset_y), Some(alpha))
+            });
+        match (te, corners.zip(buffer_dimensions(&buf))) {
+            (Some(te), Some((corners, tex_size))) => draw_texture_rounded(
                 frame,
-                te.src(),
-                tg,
-                &[tg],
-                &[],
-            )?;
+                &te,
+                tex_size,
+                scale,
+                &state.mirror_maps,
+                zoom,
+                corners,
+            )?,
+            (Some(te), None) => draw_element_zoomed(frame, &te, scale, &state.mirror_maps, zoom)?,
+            (None, _) => {}
         }
     }
+    let children_corners = corners.map(WindowCorners::square);
     // Draw children (subsurfaces) — their position is relative to this surface
     for child in get_children(surface) {
         let child_offset = with_states(&child, |states| {
@@ -425,6 +939,9 @@ fn draw_surface_tree(
             offset_x + child_offset.0,
             offset_y + child_offset.1,
             scale,
+            alpha,
+            zoom,
+            children_corners.as_ref(),
         )?;
     }
     Ok(())
@@ -435,18 +952,51 @@ fn draw_surface_tree(
 /// Shared by `render` (which then presents) and `capture_pixels` (which reads
 /// the un-swapped back buffer). Mirrors the previous inline `render` body;
 /// the only difference is the caller owns the bind/submit steps.
+///
+/// In layered mode, or with a post-process pass such as a brightness/
+/// contrast/saturation adjustment configured, the scene is composed into an
+/// offscreen layer and copied over through the passes, so screen captures
+/// see the processed frame too.
 fn render_scene_into(
     state: &mut State,
     renderer: &mut GlesRenderer,
     framebuffer: &mut GlesTarget<'_>,
+) -> Result<()> {
+    let Some(passes) = post_passes(&state.config.output) else {
+        return compose_scene(state, renderer, framebuffer);
+    };
+    let program = state.color_pass.program(renderer)?;
+    let size = Size::from((state.window_width as i32, state.window_height as i32));
+    let mut layers = std::mem::take(&mut state.layers);
+    let result = layers.render(
+        renderer,
+        framebuffer,
+        size,
+        &passes,
+        &program,
+        |renderer, target| compose_scene(state, renderer, target),
+    );
+    state.layers = layers;
+    result
+}
+
+/// Draw the current scene into `framebuffer`.
+fn compose_scene(
+    state: &mut State,
+    renderer: &mut GlesRenderer,
+    framebuffer: &mut GlesTarget<'_>,
 ) -> Result<()> {
     let layouts = state.prepare_render_scene(); // HashMap<u64, crate::window::Rectangle>
+    update_mirror_maps(state);
     let scale = smithay::utils::Scale::from(state.focused_output_scale());
 
     // Update surface previous rects for damage tracking and collect render items
     // in a single pass over layouts (avoids iterating the HashMap twice).
-    let mut items: Vec<(u64, WindowRectangle, Option<WindowDecoration>)> =
+    let mut items: Vec<(u64, WindowRectangle, Option<WindowDecoration>, f32)> =
         Vec::with_capacity(layouts.len());
+    let mut dim_levels: HashMap<u64, f32> = HashMap::new();
+    let mut color_filters: HashMap<u64, ColorFilter> = HashMap::new();
+    let mut fullscreen_ids: HashSet<u64> = HashSet::new();
     let wm = state.window_manager.read();
     let dm = state.decoration_manager.read();
     for (window_id, rect) in &layouts {
@@ -468,14 +1018,29 @@ fn render_scene_into(
                 .map(|w| w.properties.fullscreen)
                 .unwrap_or(false);
             let dec = if is_fullscreen {
+                fullscreen_ids.insert(*window_id);
                 None
             } else {
                 dm.get_decoration(*window_id).cloned()
             };
-            items.push((*window_id, rect.clone(), dec));
+            // Per-window opacity from window rules, fading with focus changes.
+            let alpha = wm
+                .get_window(*window_id)
+                .map(|w| w.properties.transition_opacity())
+                .unwrap_or(1.0);
+            if let Some(w) = wm.get_window(*window_id) {
+                if w.properties.dim > 0.0 {
+                    dim_levels.insert(*window_id, w.properties.dim);
+                }
+                if w.properties.color_filter != ColorFilter::None {
+                    color_filters.insert(*window_id, w.properties.color_filter);
+                }
+            }
+            items.push((*window_id, rect.clone(), dec, alpha));
         }
     }
-    let decorations: Vec<(u64, DecorationMode, bool)> = {
+    // Decorated windows with how far each is toward its focused look
+    let decorations: Vec<(u64, DecorationMode, f32)> = {
         let mut decs = Vec::with_capacity(dm.decorations().len());
         for (id, d) in dm.decorations().iter() {
             let is_fullscreen = wm
@@ -483,34 +1048,77 @@ fn render_scene_into(
                 .map(|w| w.properties.fullscreen)
                 .unwrap_or(true);
             if !is_fullscreen {
-                decs.push((*id, d.mode, d.focused));
+                let focus = wm
+                    .get_window(*id)
+                    .map(|w| w.properties.focus_mix)
+                    .unwrap_or(if d.focused { 1.0 } else { 0.0 });
+                decs.push((*id, d.mode, focus));
             }
         }
         decs
     };
+    // Back-to-front in the same order the pointer hit-tests
+    let render_order: HashMap<u64, usize> = wm
+        .get_windows_by_render_order()
+        .into_iter()
+        .enumerate()
+        .map(|(rank, id)| (id, rank))
+        .collect();
     drop(wm);
     drop(dm);
+    items.sort_by_key(|(window_id, ..)| render_order.get(window_id).copied());
 
     let (w, h) = (state.window_width as i32, state.window_height as i32);
 
     // Import client buffers FIRST (before frame creation, to avoid double-borrowing renderer).
     // Walk the full subsurface tree for each visible window so child buffers are cached too.
-    let surfaces_to_import: Vec<WlSurface> = {
+    let surfaces_to_import: Vec<(u64, WlSurface)> = {
         let mut surfaces = Vec::with_capacity(items.len());
-        for (window_id, _rect, _dec) in &items {
+        for (window_id, _rect, _dec, _alpha) in &items {
             if let Some(&surface_id) = state.window_map.get(window_id) {
                 if let Some(t) = state.toplevels.get(&surface_id) {
-                    surfaces.push(t.wl_surface().clone());
+                    surfaces.push((*window_id, t.wl_surface().clone()));
                 }
             }
         }
         surfaces
     };
-    for surface in &surfaces_to_import {
-        import_surface_tree(state, renderer, surface);
+    // Windows whose uploads don't fit this frame's budget keep their old
+    // buffer on screen until a later frame
+    let output_geometries = output_layout(&state.workspace_manager.read());
+    let deferred_uploads = {
+        let focused = state.window_manager.read().focused_window_id();
+        let requests = surfaces_to_import
+            .iter()
+            .filter_map(|(window_id, surface)| {
+                let bytes = pending_upload_bytes(state, surface);
+                let on_screen = layouts
+                    .get(window_id)
+                    .is_some_and(|rect| !outputs_showing(rect, &output_geometries).is_empty());
+                let priority = if focused == Some(*window_id) {
+                    UploadPriority::Focused
+                } else if !on_screen {
+                    UploadPriority::Offscreen
+                } else {
+                    UploadPriority::Visible
+                };
+                (bytes > 0).then_some(UploadRequest {
+                    window_id: *window_id,
+                    priority,
+                    bytes,
+                })
+            })
+            .collect();
+        let budget = u64::from(state.config.performance.upload_budget_mb) * 1024 * 1024;
+        state.upload_queue.schedule(requests, budget)
+    };
+    for (window_id, surface) in &surfaces_to_import {
+        if !deferred_uploads.contains(window_id) {
+            import_surface_tree(state, renderer, surface);
+        }
     }
     // Update SurfaceData.size from imported textures (fixes #19)
-    for surface in &surfaces_to_import {
+    for (_, surface) in &surfaces_to_import {
         let buf: Option<WlBuffer> = with_states(surface, |states| {
             match states
                 .cached_state
@@ -524,14 +1132,10 @@ fn render_scene_into(
         });
         if let Some(ref buf) = buf {
             if let Some(tb) = state.texture_cache.get(&buf.id()) {
-                let te_temp = TextureRenderElement::from_texture_buffer(
-                    Point::from((0.0, 0.0)),
-                    tb,
-                    None,
-                    None,
-                    None,
-                    Kind::Unspecified,
-                );
+                let Some(te_temp) = viewported_texture_element(surface, tb, (0.0, 0.0), None)
+                else {
+                    continue;
+                };
                 let geo = te_temp.geometry(scale);
                 let surface_id = surface.id().protocol_id();
                 if let Some(sd) = state.surfaces.get_mut(&surface_id) {
@@ -540,6 +1144,11 @@ fn render_scene_into(
             }
         }
     }
+    // Input method candidate popups, imported while the renderer is free
+    let ime_popups = state.ime_popup_positions();
+    for (surface, _) in &ime_popups {
+        import_surface_tree(state, renderer, surface);
+    }
     // Import DnD icon texture before frame creation so renderer is available.
     let _dnd_bid: Option<ObjectId> = if state.dnd_active {
         state.dnd_icon.as_ref().and_then(|icon_surface| {
@@ -560,16 +1169,12 @@ fn render_scene_into(
             });
             icon_buf.map(|buf| {
                 let bid = buf.id();
-                if !state.texture_cache.contains(&bid) {
+                if !state.texture_cache.contains(&bid)
+                    && state.texture_limits.allows(renderer, &buf)
+                {
                     match renderer.import_buffer(&buf, None, &[]) {
                         Some(Ok(tex)) => {
-                            let tb = TextureBuffer::from_texture(
-                                &*renderer,
-                                tex,
-                                1,
-                                Transform::Normal,
-                                None,
-                            );
+                            let tb = BufferLayout::of(icon_surface).texture_buffer(renderer, tex);
                             state.texture_cache.put(bid.clone(), tb);
                         }
                         Some(Err(e)) => warn!("⚠️ Failed to import DnD icon buffer: {:?}", e),
@@ -582,6 +1187,15 @@ fn render_scene_into(
     } else {
         None
     };
+    // Import the client cursor surface tree, if any, for the software cursor.
+    // A cursor on a hardware plane isn't part of the frame.
+    let cursor_surface = state
+        .cursor_surface
+        .clone()
+        .filter(|s| s.is_alive() && state.cursor.draws_in_software());
+    if let Some(ref surface) = cursor_surface {
+        import_surface_tree(state, renderer, surface);
+    }
     // Import lock surface textures before frame creation (same reason)
     if state.session_locked {
         state.lock_surfaces.retain(LockSurface::alive);
@@ -604,16 +1218,12 @@ fn render_scene_into(
                 });
             if let Some(buf) = buf {
                 let bid = buf.id();
-                if !state.texture_cache.contains(&bid) {
+                if !state.texture_cache.contains(&bid)
+                    && state.texture_limits.allows(renderer, &buf)
+                {
                     match renderer.import_buffer(&buf, None, &[]) {
                         Some(Ok(tex)) => {
-                            let tb = TextureBuffer::from_texture(
-                                &*renderer,
-                                tex,
-                                1,
-                                Transform::Normal,
-                                None,
-                            );
+                            let tb = BufferLayout::of(lock_surface.wl_surface()).texture_buffer(renderer, tex);
                             state.texture_cache.put(bid.clone(), tb);
                         }
                         Some(Err(e)) => warn!("⚠️ Failed to import lock surface buffer: {:?}", e),
@@ -642,16 +1252,10 @@ fn render_scene_into(
         });
         if let Some(buf) = buf {
             let bid = buf.id();
-            if !state.texture_cache.contains(&bid) {
+            if !state.texture_cache.contains(&bid) && state.texture_limits.allows(renderer, &buf) {
                 match renderer.import_buffer(&buf, None, &[]) {
                     Some(Ok(tex)) => {
-                        let tb = TextureBuffer::from_texture(
-                            &*renderer,
-                            tex,
-                            1,
-                            Transform::Normal,
-                            None,
-                        );
+                        let tb = BufferLayout::of(layer_surface.wl_surface()).texture_buffer(renderer, tex);
                         state.texture_cache.put(bid.clone(), tb);
                     }
                     Some(Err(e)) => {
@@ -665,271 +1269,549 @@ fn render_scene_into(
             }
         }
     }
+    // Output backgrounds and column wallpapers, uploaded before the frame
+    // takes the renderer
+    let now = Instant::now();
+    let (backgrounds, wallpapers) = {
+        let workspaces = state.workspace_manager.read();
+        let outputs = output_layout(&workspaces);
+        let wallpapers: Vec<(WindowRectangle, BackgroundBlend)> = outputs
+            .iter()
+            .filter_map(|(name, geometry)| {
+                Some((geometry.clone(), workspaces.background_blend(name)?))
+            })
+            .collect();
+        let backgrounds: Vec<(WindowRectangle, Vec<(BackgroundFill, f32)>)> = outputs
+            .into_iter()
+            .map(|(name, geometry)| (geometry, state.backgrounds.layers(&name, now)))
+            .filter(|(_, layers)| !layers.is_empty())
+            .collect();
+        (backgrounds, wallpapers)
+    };
+    state.wallpapers.load(
+        renderer,
+        wallpapers
+            .iter()
+            .flat_map(|(_, blend)| [blend.from.as_deref(), blend.to.as_deref()])
+            .flatten()
+            .chain(state.backgrounds.wallpapers()),
+    );
+    // Shaders for per-window colour filters and rounded corners, compiled
+    // while the renderer is still free. Without them filtered windows are
+    // drawn unfiltered and rounded ones square.
+    let filter_program = if color_filters.is_empty() {
+        None
+    } else {
+        state
+            .color_pass
+            .program(renderer)
+            .map_err(|e| warn!("⚠️ Colour filter shader unavailable: {:#}", e))
+            .ok()
+    };
+    let (corner_radius, corner_softness) = {
+        let dm = state.decoration_manager.read();
+        (dm.theme().corner_radius, dm.theme().corner_softness)
+    };
+    let corner_program = if corner_radius > 0.0 {
+        state
+            .corner_pass
+            .program(renderer)
+            .map_err(|e| warn!("⚠️ Rounded corner shader unavailable: {:#}", e))
+            .ok()
+    } else {
+        None
+    };
+    let shaders = WindowShaders {
+        filter: filter_program,
+        corners: corner_program,
+        corner_radius,
+        corner_softness,
+    };
+    // Glyph atlas for the HUD's text, uploaded the first time it shows
+    if state.hud.enabled {
+        if let Err(e) = state.text_renderer.prepare(renderer, &mut state.color_pass) {
+            warn!("⚠️ Text rendering unavailable: {:#}", e);
+        }
+    }
     let mut frame = renderer.render(framebuffer, Size::from((w, h)), Transform::Normal)?;
     frame.clear(
-        Color32F::from([0.05f32, 0.05, 0.08, 1.0]),
+        Color32F::from(CLEAR_COLOR),
         &[Rectangle::new(Point::from((0, 0)), Size::from((w, h)))],
     )?;
 
     // When locked, only render lock screen surfaces (skip normal shell content)
     if state.session_locked {
+        state.frame_callbacks.set_visibility(HashMap::new());
         render_lock_surfaces(state, &mut frame, scale)?;
         let _ = frame.finish()?;
         return Ok(());
     }
+    for (geometry, layers) in &backgrounds {
+        draw_output_background(state, &mut frame, geometry, layers, scale)?;
+    }
+    for (geometry, blend) in &wallpapers {
+        draw_wallpapers(state, &mut frame, geometry, blend, scale)?;
+    }
 
-    // Occlusion culling: process front-to-back to identify fully covered windows,
-    // then draw back-to-front skipping occluded surface trees.
-    // Items are in back-to-front order, so reversed iteration is front-to-back.
-    let mut occluded_windows: HashSet<u64> = HashSet::new();
-    {
+    // Occlusion culling: work out which windows are fully covered by the
+    // opaque parts of those in front, then draw back-to-front skipping
+    // occluded surface trees. Translucent windows let whatever is below
+    // show through, and opaque ones only hide it where the client said so.
+    let windows: Vec<RenderedWindow> = {
         let dm = state.decoration_manager.read();
-        let mut occluded_regions: Vec<Rectangle<i32, Physical>> = Vec::with_capacity(items.len());
-        for (window_id, rect, _dec) in items.iter().rev() {
-            let content = dm.get_content_rect(*window_id, rect.clone());
-            let content_rect: Rectangle<i32, Physical> = Rectangle::new(
-                Point::from((content.x, content.y)),
-                Size::from((content.width as i32, content.height as i32)),
-            );
-            // Check if this window is fully covered by any already-rendered region
-            let covered = occluded_regions
-                .iter()
-                .any(|r| r.contains_rect(content_rect));
-            if covered {
-                occluded_windows.insert(*window_id);
-            }
-            occluded_regions.push(content_rect);
-        }
-    } // dm dropped here, unblocking &mut state in the drawing loop
-
-    for (window_id, rect, dec) in &items {
-        let content = state
-            .decoration_manager
-            .read()
-            .get_content_rect(*window_id, rect.clone());
-        let color: [f32; 4] = match dec {
-            Some(d) if d.focused => [0.2, 0.2, 0.4, 1.0],
-            Some(_) => [0.1, 0.1, 0.2, 1.0],
-            None => [0.3, 0.3, 0.3, 1.0],
-        };
-        let bg = SolidColorBuffer::new((content.width as i32, content.height as i32), color);
-        let bg_elem = SolidColorRenderElement::from_buffer(
-            &bg,
-            Point::from((content.x, content.y)),
+        items
+            .iter()
+            .map(|(window_id, rect, _dec, alpha)| {
+                let content = dm.get_content_rect(*window_id, rect.clone());
+                let content_rect: Rectangle<i32, Physical> = Rectangle::new(
+                    Point::from((content.x, content.y)),
+                    Size::from((content.width as i32, content.height as i32)),
+                );
+                let mut opaque_region = match state.opaque_regions.get(window_id) {
+                    Some(opaque) if *alpha >= 1.0 => occlusion::opaque_area(content_rect, opaque),
+                    _ => Vec::new(),
+                };
+                // Rounded corners let what is below show through
+                if shaders.corners.is_some() && !fullscreen_ids.contains(window_id) {
+                    let radius = shaders.corner_radius.ceil() as i32;
+                    let top = if content.y > rect.y { 0 } else { radius };
+                    let clear: Vec<Rectangle<i32, Physical>> =
+                        corner_free_rects(&content, top, radius)
+                            .into_iter()
+                            .map(|(x, y, w, h)| {
+                                Rectangle::new(Point::from((x, y)), Size::from((w, h)))
+                            })
+                            .collect();
+                    opaque_region = opaque_region
+                        .iter()
+                        .flat_map(|r| clear.iter().filter_map(|c| r.intersection(*c)))
+                        .collect();
+                }
+                RenderedWindow {
+                    id: *window_id,
+                    content: content_rect,
+                    opaque_region,
+                    color_filter: color_filters.get(window_id).copied().unwrap_or_default(),
+                }
+            })
+            .collect()
+    }; // dm dropped here, unblocking &mut state in the drawing loop
+    let occluded_windows = occlusion::occluded_windows(&windows);
+    // Kept for trimming the damage of the next commits
+    state.rendered_windows = windows;
+
+    // Frame callbacks go only to windows that are unoccluded and on an
+    // output, and whose last commit has been uploaded
+    let visibility: HashMap<u64, Vec<String>> = items
+        .iter()
+        .filter(|(window_id, ..)| !occluded_windows.contains(window_id))
+        .filter(|(window_id, ..)| !deferred_uploads.contains(window_id))
+        .filter_map(|(window_id, rect, ..)| {
+            let outputs = outputs_showing(rect, &output_geometries);
+            (!outputs.is_empty()).then_some((*window_id, outputs))
+        })
+        .collect();
+    state.frame_callbacks.set_visibility(visibility);
+
+    // Fullscreen windows sit above normal windows and the background,
+    // bottom and top layers, but below overlay layers.
+    let (fullscreen_items, normal_items): (Vec<_>, Vec<_>) = items
+        .iter()
+        .partition(|(window_id, ..)| fullscreen_ids.contains(window_id));
+    for item in normal_items {
+        draw_window_item(
+            state,
+            &mut frame,
+            item,
+            &occluded_windows,
+            &dim_levels,
+            &shaders,
+            scale,
+        )?;
+    }
+    // SSD decorations: titlebar and buttons laid out and colored by the
+    // decoration theme, matching decoration.rs hit-testing
+    let (theme, fills) = {
+        let dm = state.decoration_manager.read();
+        let fills: Vec<_> = decorations
+            .iter()
+            .filter(|(_, mode, _)| *mode == DecorationMode::ServerSide)
+            .filter_map(|(window_id, _, focus)| {
+                let rect = layouts.get(window_id)?;
+                let fills = dm.titlebar_fills(*window_id, rect.width as i32, *focus);
+                Some(fills.into_iter().map(move |fill| (rect.x, rect.y, fill)))
+            })
+            .flatten()
+            .collect();
+        (dm.theme().clone(), fills)
+    };
+    for (x, y, fill) in fills {
+        let size = (fill.rect.width as i32, fill.rect.height as i32);
+        let buffer = SolidColorBuffer::new(size, fill.color);
+        let elem = SolidColorRenderElement::from_buffer(
+            &buffer,
+            Point::from((x + fill.rect.x, y + fill.rect.y)),
             1.0,
             1.0,
             Kind::Unspecified,
         );
-        let g = bg_elem.geometry(scale);
-        <SolidColorRenderElement as RenderElement<GlesRenderer>>::draw(
-            &bg_elem,
+        draw_element(&mut frame, &elem, scale, &state.mirror_maps)?;
+    }
+    // Selected windows get a thin frame in the focused border colour
+    let selected = state.window_manager.read().selected().to_vec();
+    let mut highlight = theme.border_color_focused;
+    highlight[3] *= SELECTION_HIGHLIGHT_ALPHA;
+    let width = theme.border_width_focused.max(2) as i32;
+    for rect in selected.iter().filter_map(|id| layouts.get(id)) {
+        for (x, y, w, h) in selection_frame(rect, width) {
+            let edge = SolidColorBuffer::new((w, h), highlight);
+            let edge_elem = SolidColorRenderElement::from_buffer(
+                &edge,
+                Point::from((x, y)),
+                1.0,
+                1.0,
+                Kind::Unspecified,
+            );
+            draw_element(&mut frame, &edge_elem, scale, &state.mirror_maps)?;
+        }
+    }
+    render_layer_surfaces(state, &mut frame, scale, (w, h), false)?;
+    for item in fullscreen_items {
+        draw_window_item(
+            state,
             &mut frame,
-            bg_elem.src(),
-            g,
-            &[g],
-            &[],
+            item,
+            &occluded_windows,
+            &dim_levels,
+            &shaders,
+            scale,
         )?;
-        // Draw the full surface tree (including subsurfaces) from the texture cache,
-        // unless this window is fully occluded (behind another opaque window).
-        if !occluded_windows.contains(window_id) {
-            if let Some(&surface_id) = state.window_map.get(window_id) {
-                if let Some(t) = state.toplevels.get(&surface_id) {
-                    let wl_surface = t.wl_surface().clone();
-                    draw_surface_tree(
-                        state,
-                        &mut frame,
-                        &wl_surface,
-                        content.x as f64,
-                        content.y as f64,
-                        scale,
-                    )?;
+    }
+    render_layer_surfaces(state, &mut frame, scale, (w, h), true)?;
+    for (surface, (x, y)) in &ime_popups {
+        draw_surface_tree(
+            state, &mut frame, surface, *x as f64, *y as f64, scale, 1.0, None, None,
+        )?;
+    }
+    // If a DnD session is active with a drag icon, render it
+    // at the current pointer position as an overlay.
+    if state.dnd_active {
+        if let Some(ref icon_surface) = state.dnd_icon {
+            let icon_buf: Option<WlBuffer> = with_states(icon_surface, |states| {
+                match states
+                    .cached_state
+                    .get::<SurfaceAttributes>()
+                    .current()
+                    .buffer
+                {
+                    Some(BufferAssignment::NewBuffer(ref b)) => Some(b.clone()),
+                    Some(BufferAssignment::Removed) => None,
+                    _ => None,
+                }
+            });
+            if let Some(buf) = icon_buf {
+                if let Some(tb) = state.texture_cache.get(&buf.id()) {
+                    let icon_x = state.pointer_x as i32;
+                    let icon_y = state.pointer_y as i32;
+                    let te = TextureRenderElement::from_texture_buffer(
+                        Point::from((icon_x as f64, icon_y as f64)),
+                        tb,
+                        None,
+                        None,
+                        None,
+                        Kind::Unspecified,
+                    );
+                    draw_element(&mut frame, &te, scale, &state.mirror_maps)?;
                 }
             }
         }
     }
-    // SSD decorations: titlebar + 3 buttons with theme colors and symbol shapes.
-    let theme = state.decoration_manager.read().theme().clone();
-    for (window_id, mode, focused) in &decorations {
-        if *mode == DecorationMode::ServerSide {
-            if let Some(rect) = layouts.get(window_id) {
-                let titlebar_h = theme.titlebar_height as i32;
-                let tb_color = if *focused {
-                    theme.titlebar_bg_focused
-                } else {
-                    theme.titlebar_bg_unfocused
-                };
-                let tb = SolidColorBuffer::new((rect.width as i32, titlebar_h), tb_color);
-                let tb_elem = SolidColorRenderElement::from_buffer(
-                    &tb,
-                    Point::from((rect.x, rect.y)),
-                    1.0,
+    // Debug HUD above everything but the cursor
+    if let Some((panel, text)) = state.hud.draw_commands() {
+        let (x, y, w, h) = panel.rect;
+        let buffer = SolidColorBuffer::new((w, h), panel.color);
+        let elem = SolidColorRenderElement::from_buffer(
+            &buffer,
+            Point::from((x, y)),
+            1.0,
+            1.0,
+            Kind::Unspecified,
+        );
+        draw_element(&mut frame, &elem, scale, &state.mirror_maps)?;
+        // Text is laid out in physical pixels; whole ones keep glyphs crisp
+        let position = (
+            (text.position.0 as f64 * scale.x).round() as i32,
+            (text.position.1 as f64 * scale.y).round() as i32,
+        );
+        let glyph_scale = (text.scale as f64 * scale.x).round().max(1.0) as i32;
+        let drawn = state.text_renderer.draw_text(
+            &mut frame,
+            position,
+            &text.text,
+            text.color,
+            glyph_scale,
+        )?;
+        DRAW_CALLS.set(DRAW_CALLS.get() + drawn);
+    }
+    // Software cursor last, so it sits above any drag icon. The hotspot is
+    // subtracted so the click point matches what the client asked for.
+    if let Some(ref surface) = cursor_surface {
+        let (cx, cy) =
+            State::cursor_draw_position((state.pointer_x, state.pointer_y), state.cursor_hotspot);
+        draw_surface_tree(
+            state, &mut frame, surface, cx as f64, cy as f64, scale, 1.0, None, None,
+        )?;
+    }
+    let _ = frame.finish()?;
+    Ok(())
+}
+
+/// Draw an output's own background over the clear colour, each layer at
+/// its alpha. An image that failed to load leaves what is below it.
+fn draw_output_background(
+    state: &State,
+    frame: &mut GlesFrame<'_, '_>,
+    geometry: &WindowRectangle,
+    layers: &[(BackgroundFill, f32)],
+    scale: smithay::utils::Scale<f64>,
+) -> Result<()> {
+    let output_size = (geometry.width as i32, geometry.height as i32);
+    for (fill, alpha) in layers {
+        match fill {
+            BackgroundFill::Color(color) => {
+                let buffer = SolidColorBuffer::new(output_size, *color);
+                let elem = SolidColorRenderElement::from_buffer(
+                    &buffer,
+                    Point::from((geometry.x, geometry.y)),
                     1.0,
+                    *alpha,
                     Kind::Unspecified,
                 );
-                let tg = tb_elem.geometry(scale);
-                <SolidColorRenderElement as RenderElement<GlesRenderer>>::draw(
-                    &tb_elem,
-                    &mut frame,
-                    tb_elem.src(),
-                    tg,
-                    &[tg],
-                    &[],
-                )?;
-
-                // Draw buttons using theme colors and positions matching decoration.rs hit-testing.
-                let btn_size = theme.button_size as i32;
-                let margin = 8i32;
-                let button_y = ((theme.titlebar_height - theme.button_size) / 2) as i32;
-                let sym_color = if *focused {
-                    theme.text_color_focused
-                } else {
-                    theme.text_color_unfocused
+                draw_element(frame, &elem, scale, &state.mirror_maps)?;
+            }
+            BackgroundFill::Wallpaper(path) => {
+                let Some(wallpaper) = state.wallpapers.get(path) else {
+                    continue;
                 };
-
-                // Close button (idx=0)
-                let cx = rect.x + rect.width as i32 - (btn_size + margin);
-                let cy = rect.y + button_y;
-                let cb = SolidColorBuffer::new((btn_size, btn_size), theme.close_normal);
-                let ce = SolidColorRenderElement::from_buffer(
-                    &cb,
-                    Point::from((cx, cy)),
-                    1.0,
-                    1.0,
+                let elem = TextureRenderElement::from_texture_buffer(
+                    Point::from((geometry.x as f64, geometry.y as f64)),
+                    &wallpaper.texture,
+                    Some(*alpha),
+                    Some(cover_crop(wallpaper.size, output_size)),
+                    Some(Size::from(output_size)),
                     Kind::Unspecified,
                 );
-                let cg = ce.geometry(scale);
-                <SolidColorRenderElement as RenderElement<GlesRenderer>>::draw(
-                    &ce,
-                    &mut frame,
-                    ce.src(),
-                    cg,
-                    &[cg],
-                    &[],
-                )?;
-                // × symbol: two thin rectangles forming a cross (+)
-                let s = 3; // stroke width
-                let l = btn_size / 2 - 2; // arm length
-                let center = l + 2; // offset from button edge
-                let h_rect = SolidColorBuffer::new((l * 2 + 1, s), sym_color);
-                let v_rect = SolidColorBuffer::new((s, l * 2 + 1), sym_color);
-                for (x_off, y_off) in [(center - l, center - s / 2), (center - s / 2, center - l)] {
-                    let r = if x_off == center - l {
-                        &h_rect
-                    } else {
-                        &v_rect
-                    };
-                    let re = SolidColorRenderElement::from_buffer(
-                        r,
-                        Point::from((cx + x_off, cy + y_off)),
-                        1.0,
-                        1.0,
-                        Kind::Unspecified,
+                draw_element(frame, &elem, scale, &state.mirror_maps)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Draw an output's column wallpapers over its background: `from` as is,
+/// with `to` faded in over it by `mix`. A column without a wallpaper fades
+/// to or from the background.
+fn draw_wallpapers(
+    state: &State,
+    frame: &mut GlesFrame<'_, '_>,
+    geometry: &WindowRectangle,
+    blend: &BackgroundBlend,
+    scale: smithay::utils::Scale<f64>,
+) -> Result<()> {
+    let mix = blend.mix.clamp(0.0, 1.0) as f32;
+    let from_alpha = if blend.to.is_some() { 1.0 } else { 1.0 - mix };
+    let layers = [(blend.from.as_deref(), from_alpha), (blend.to.as_deref(), mix)];
+    let output_size = (geometry.width as i32, geometry.height as i32);
+    for (path, alpha) in layers {
+        let Some(wallpaper) = path.and_then(|p| state.wallpapers.get(p)) else {
+            continue;
+        };
+        if alpha <= 0.0 {
+            continue;
+        }
+        let elem = TextureRenderElement::from_texture_buffer(
+            Point::from((geometry.x as f64, geometry.y as f64)),
+            &wallpaper.texture,
+            Some(alpha),
+            Some(cover_crop(wallpaper.size, output_size)),
+            Some(Size::from(output_size)),
+            Kind::Unspecified,
+        );
+        draw_element(frame, &elem, scale, &state.mirror_maps)?;
+    }
+    Ok(())
+}
+
+/// Texture shaders windows are drawn through, compiled before the frame
+/// starts.
+struct WindowShaders {
+    /// Colour-matrix shader, when some window has a colour filter
+    filter: Option<GlesTexProgram>,
+    /// Corner shader, when windows have rounded corners
+    corners: Option<GlesTexProgram>,
+    /// `decoration.corner_radius`, in logical pixels
+    corner_radius: f32,
+    /// `decoration.corner_softness`, in physical pixels
+    corner_softness: f32,
+}
+
+/// Draw one window: background quad, surface tree (unless occluded) and
+/// the inactive-dim shade. With rounded corners the dimming and colour
+/// filter are applied by the corner shader instead, and the background
+/// leaves the corners clear.
+fn draw_window_item(
+    state: &mut State,
+    frame: &mut GlesFrame<'_, '_>,
+    (window_id, rect, dec, alpha): &(u64, WindowRectangle, Option<WindowDecoration>, f32),
+    occluded_windows: &HashSet<u64>,
+    dim_levels: &HashMap<u64, f32>,
+    shaders: &WindowShaders,
+    scale: smithay::utils::Scale<f64>,
+) -> Result<()> {
+    let content = state
+        .decoration_manager
+        .read()
+        .get_content_rect(*window_id, rect.clone());
+    let mut color: [f32; 4] = match dec {
+        Some(d) if d.focused => [0.2, 0.2, 0.4, 1.0],
+        Some(_) => [0.1, 0.1, 0.2, 1.0],
+        None => [0.3, 0.3, 0.3, 1.0],
+    };
+    let (zoom, fullscreen) = {
+        let wm = state.window_manager.read();
+        let window = wm.get_window(*window_id);
+        (
+            window.and_then(|w| Zoom::for_window(rect, &w.properties.transform, scale)),
+            window.is_some_and(|w| w.properties.fullscreen),
+        )
+    };
+    let dim = dim_levels.get(window_id).copied().unwrap_or(0.0);
+    let radius = if fullscreen {
+        0.0
+    } else {
+        shaders.corner_radius
+    };
+    // A titlebar above the content has rounded top corners of its own
+    let top = if content.y > rect.y { 0.0 } else { radius };
+    let corners = shaders
+        .corners
+        .clone()
+        .filter(|_| radius > 0.0)
+        .map(|program| {
+            WindowCorners::new(
+                program,
+                window_filter(&state.rendered_windows, *window_id),
+                dim,
+                [top, top, radius, radius],
+                shaders.corner_softness,
+            )
+        });
+    let bg_rects = if corners.is_some() {
+        for channel in &mut color[..3] {
+            *channel *= 1.0 - dim;
+        }
+        corner_free_rects(&content, top.ceil() as i32, radius.ceil() as i32)
+    } else {
+        vec![(
+            content.x,
+            content.y,
+            content.width as i32,
+            content.height as i32,
+        )]
+    };
+    for (x, y, w, h) in bg_rects {
+        let bg = SolidColorBuffer::new((w, h), color);
+        let bg_elem = SolidColorRenderElement::from_buffer(
+            &bg,
+            Point::from((x, y)),
+            1.0,
+            *alpha,
+            Kind::Unspecified,
+        );
+        draw_element_zoomed(frame, &bg_elem, scale, &state.mirror_maps, zoom)?;
+    }
+    // Draw the full surface tree (including subsurfaces) from the texture cache,
+    // unless this window is fully occluded (behind another opaque window).
+    if !occluded_windows.contains(window_id) {
+        if let Some(&surface_id) = state.window_map.get(window_id) {
+            if let Some(t) = state.toplevels.get(&surface_id) {
+                let wl_surface = t.wl_surface().clone();
+                let filter = shaders
+                    .filter
+                    .as_ref()
+                    .filter(|_| corners.is_none())
+                    .zip(window_filter(&state.rendered_windows, *window_id));
+                if let Some((program, matrix)) = filter {
+                    frame.override_default_tex_program(
+                        program.clone(),
+                        vec![matrix_uniform(matrix)],
                     );
-                    let rg = re.geometry(scale);
-                    <SolidColorRenderElement as RenderElement<GlesRenderer>>::draw(
-                        &re,
-                        &mut frame,
-                        re.src(),
-                        rg,
-                        &[rg],
-                        &[],
-                    )?;
                 }
-
-                // Maximize button (idx=1)
-                let mx = rect.x + rect.width as i32 - (btn_size + margin) * 2;
-                let my = rect.y + button_y;
-                let mb = SolidColorBuffer::new((btn_size, btn_size), theme.button_normal);
-                let me = SolidColorRenderElement::from_buffer(
-                    &mb,
-                    Point::from((mx, my)),
-                    1.0,
-                    1.0,
-                    Kind::Unspecified,
-                );
-                let mg = me.geometry(scale);
-                <SolidColorRenderElement as RenderElement<GlesRenderer>>::draw(
-                    &me,
-                    &mut frame,
-                    me.src(),
-                    mg,
-                    &[mg],
-                    &[],
-                )?;
-                // □ symbol: a small filled square
-                let sq_size = btn_size / 2 - 2;
-                let sq_off = (btn_size - sq_size) / 2;
-                let sq = SolidColorBuffer::new((sq_size, sq_size), sym_color);
-                let sq_e = SolidColorRenderElement::from_buffer(
-                    &sq,
-                    Point::from((mx + sq_off, my + sq_off)),
-                    1.0,
-                    1.0,
-                    Kind::Unspecified,
-                );
-                let sq_g = sq_e.geometry(scale);
-                <SolidColorRenderElement as RenderElement<GlesRenderer>>::draw(
-                    &sq_e,
-                    &mut frame,
-                    sq_e.src(),
-                    sq_g,
-                    &[sq_g],
-                    &[],
-                )?;
-
-                // Minimize button (idx=2)
-                let nx = rect.x + rect.width as i32 - (btn_size + margin) * 3;
-                let ny = rect.y + button_y;
-                let nb = SolidColorBuffer::new((btn_size, btn_size), theme.button_normal);
-                let ne = SolidColorRenderElement::from_buffer(
-                    &nb,
-                    Point::from((nx, ny)),
-                    1.0,
-                    1.0,
-                    Kind::Unspecified,
-                );
-                let ng = ne.geometry(scale);
-                <SolidColorRenderElement as RenderElement<GlesRenderer>>::draw(
-                    &ne,
-                    &mut frame,
-                    ne.src(),
-                    ng,
-                    &[ng],
-                    &[],
-                )?;
-                // — symbol: a thin horizontal line
-                let line_w = btn_size / 2 + 2;
-                let line_h = 3;
-                let line_off_y = (btn_size - line_h) / 2;
-                let line_off_x = (btn_size - line_w) / 2;
-                let line = SolidColorBuffer::new((line_w, line_h), sym_color);
-                let line_e = SolidColorRenderElement::from_buffer(
-                    &line,
-                    Point::from((nx + line_off_x, ny + line_off_y)),
-                    1.0,
-                    1.0,
-                    Kind::Unspecified,
+                let drawn = draw_surface_tree(
+                    state,
+                    frame,
+                    &wl_surface,
+                    content.x as f64,
+                    content.y as f64,
+                    scale,
+                    *alpha,
+                    zoom,
+                    corners.as_ref(),
                 );
-                let line_g = line_e.geometry(scale);
-                <SolidColorRenderElement as RenderElement<GlesRenderer>>::draw(
-                    &line_e,
-                    &mut frame,
-                    line_e.src(),
-                    line_g,
-                    &[line_g],
-                    &[],
-                )?;
+                if filter.is_some() {
+                    frame.clear_tex_program_override();
+                }
+                drawn?;
             }
         }
     }
-    // Render layer shell surfaces (panels, bars, etc.)
+    // Inactive dimming: a black quad at `dim` alpha scales the window's
+    // color by `1 - dim`, matching `WindowProperties::dim_multiplier`.
+    if dim > 0.0 && corners.is_none() {
+        let shade = SolidColorBuffer::new(
+            (content.width as i32, content.height as i32),
+            [0.0, 0.0, 0.0, 1.0],
+        );
+        let shade_elem = SolidColorRenderElement::from_buffer(
+            &shade,
+            Point::from((content.x, content.y)),
+            1.0,
+            dim * *alpha,
+            Kind::Unspecified,
+        );
+        draw_element_zoomed(frame, &shade_elem, scale, &state.mirror_maps, zoom)?;
+    }
+    Ok(())
+}
+
+/// The colour matrix window `id` is drawn through, from the filter the
+/// current frame recorded for it.
+fn window_filter(windows: &[RenderedWindow], id: u64) -> Option<[[f32; 4]; 3]> {
+    windows
+        .iter()
+        .find(|w| w.id == id)
+        .and_then(|w| filter_matrix(w.color_filter))
+}
+
+/// Render layer shell surfaces (panels, bars, etc.). `overlay` selects the
+/// overlay layer; otherwise background, bottom and top are drawn.
+fn render_layer_surfaces(
+    state: &mut State,
+    frame: &mut GlesFrame<'_, '_>,
+    scale: smithay::utils::Scale<f64>,
+    (w, h): (i32, i32),
+    overlay: bool,
+) -> Result<()> {
     for layer_surface in state.layer_shell_state.layer_surfaces() {
         // Get anchor and margin from the client's committed state.
-        let (anchor, margin) = with_states(layer_surface.wl_surface(), |states| {
+        let (anchor, margin, layer) = with_states(layer_surface.wl_surface(), |states| {
             let mut cs = states.cached_state.get::<LayerSurfaceCachedState>();
             let s = cs.current();
-            (s.anchor, s.margin)
+            (s.anchor, s.margin, s.layer)
         });
+        if (layer == Layer::Overlay) != overlay {
+            continue;
+        }
         let buf: Option<WlBuffer> = with_states(layer_surface.wl_surface(), |states| match states
             .cached_state
             .get::<SurfaceAttributes>()
@@ -944,14 +1826,11 @@ fn render_scene_into(
             if let Some(tb) = state.texture_cache.get(&buf.id()) {
                 // Create a temporary element at (0,0) just to discover its logical size,
                 // then reposition it according to anchor + margin + output size.
-                let te_temp = TextureRenderElement::from_texture_buffer(
-                    Point::from((0.0, 0.0)),
-                    tb,
-                    None,
-                    None,
-                    None,
-                    Kind::Unspecified,
-                );
+                let Some(te_temp) =
+                    viewported_texture_element(layer_surface.wl_surface(), tb, (0.0, 0.0), None)
+                else {
+                    continue;
+                };
                 let geo = te_temp.geometry(scale);
                 let (tw, th) = (geo.size.w, geo.size.h);
                 let (w, h) = (w, h);
@@ -977,68 +1856,18 @@ fn render_scene_into(
                 } else {
                     ((h - th) / 2).max(0)
                 };
-                let te = TextureRenderElement::from_texture_buffer(
-                    Point::from((pos_x as f64, pos_y as f64)),
+                let Some(te) = viewported_texture_element(
+                    layer_surface.wl_surface(),
                     tb,
+                    (pos_x as f64, pos_y as f64),
                     None,
-                    None,
-                    None,
-                    Kind::Unspecified,
-                );
-                let tg = te.geometry(scale);
-                <TextureRenderElement<GlesTexture> as RenderElement<GlesRenderer>>::draw(
-                    &te,
-                    &mut frame,
-                    te.src(),
-                    tg,
-                    &[tg],
-                    &[],
-                )?;
-            }
-        }
-    }
-    // If a DnD session is active with a drag icon, render it
-    // at the current pointer position as an overlay.
-    if state.dnd_active {
-        if let Some(ref icon_surface) = state.dnd_icon {
-            let icon_buf: Option<WlBuffer> = with_states(icon_surface, |states| {
-                match states
-                    .cached_state
-                    .get::<SurfaceAttributes>()
-                    .current()
-                    .buffer
-                {
-                    Some(BufferAssignment::NewBuffer(ref b)) => Some(b.clone()),
-                    Some(BufferAssignment::Removed) => None,
-                    _ => None,
-                }
-            });
-            if let Some(buf) = icon_buf {
-                if let Some(tb) = state.texture_cache.get(&buf.id()) {
-                    let icon_x = state.pointer_x as i32;
-                    let icon_y = state.pointer_y as i32;
-                    let te = TextureRenderElement::from_texture_buffer(
-                        Point::from((icon_x as f64, icon_y as f64)),
-                        tb,
-                        None,
-                        None,
-                        None,
-                        Kind::Unspecified,
-                    );
-                    let tg = te.geometry(scale);
-                    <TextureRenderElement<GlesTexture> as RenderElement<GlesRenderer>>::draw(
-                        &te,
-                        &mut frame,
-                        te.src(),
-                        tg,
-                        &[tg],
-                        &[],
-                    )?;
-                }
+                ) else {
+                    continue;
+                };
+                draw_element(frame, &te, scale, &state.mirror_maps)?;
             }
         }
     }
-    let _ = frame.finish()?;
     Ok(())
 }
 
@@ -1074,17 +1903,249 @@ fn render_lock_surfaces(
                     None,
                     Kind::Unspecified,
                 );
-                let tg = te.geometry(scale);
-                <TextureRenderElement<GlesTexture> as RenderElement<GlesRenderer>>::draw(
-                    &te,
-                    frame,
-                    te.src(),
-                    tg,
-                    &[tg],
-                    &[],
-                )?;
+                draw_element(frame, &te, scale, &state.mirror_maps)?;
             }
         }
     }
     Ok(())
 }
+
+/// Clamp a capture rect `(x, y, w, h)` in display coordinates (top-left
+/// origin) to the framebuffer and flip it into GL readback coordinates
+/// (bottom-left origin). `None` when none of it is on the framebuffer.
+fn capture_readback_rect(
+    (x, y, w, h): (i32, i32, u32, u32),
+    (fb_w, fb_h): (u32, u32),
+) -> Option<Rectangle<i32, Physical>> {
+    let (fb_w, fb_h) = (fb_w as i64, fb_h as i64);
+    let (x0, y0) = ((x as i64).max(0), (y as i64).max(0));
+    let x1 = (x as i64 + w as i64).min(fb_w);
+    let y1 = (y as i64 + h as i64).min(fb_h);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some(Rectangle::new(
+        Point::from((x0 as i32, (fb_h - y1) as i32)),
+        Size::from(((x1 - x0) as i32, (y1 - y0) as i32)),
+    ))
+}
+
+/// Turn a `w`x`h` ARGB8888 readback into tightly packed rows, top row
+/// first. The mapping may pad rows out to the pack alignment, so the
+/// stride is taken from the buffer length rather than assumed to be
+/// `w * 4`, and GL hands rows over bottom-up.
+fn repack_readback_rows(pixels: &[u8], w: u32, h: u32) -> Vec<u8> {
+    let row_bytes = w as usize * 4;
+    if h == 0 || row_bytes == 0 {
+        return Vec::new();
+    }
+    let stride = (pixels.len() / h as usize).max(row_bytes);
+    let mut out = Vec::with_capacity(row_bytes * h as usize);
+    for row in (0..h as usize).rev() {
+        let start = row * stride;
+        match pixels.get(start..start + row_bytes) {
+            Some(bytes) => out.extend_from_slice(bytes),
+            None => out.resize(out.len() + row_bytes, 0),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zoom_halves_geometry_around_window_center() {
+        let rect = WindowRectangle::from_loc_and_size((100, 100), (400, 200));
+        let half = crate::window::WindowTransform {
+            scale: 0.5,
+            ..Default::default()
+        };
+        let zoom = Zoom::for_window(&rect, &half, smithay::utils::Scale::from(1.0)).unwrap();
+        let g = Rectangle::new(Point::from((100, 100)), Size::from((400, 200)));
+        assert_eq!(
+            zoom.apply(g),
+            Rectangle::new(Point::from((200, 150)), Size::from((200, 100)))
+        );
+        assert_eq!(
+            Zoom::for_window(
+                &rect,
+                &crate::window::WindowTransform::default(),
+                smithay::utils::Scale::from(1.0)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_background_leaves_rounded_corners_clear() {
+        let content = WindowRectangle::from_loc_and_size((10, 20), (100, 50));
+        // Square top under a titlebar, rounded bottom
+        assert_eq!(
+            corner_free_rects(&content, 0, 8),
+            vec![(10, 20, 100, 42), (18, 62, 84, 8)]
+        );
+        assert_eq!(
+            corner_free_rects(&content, 8, 8),
+            vec![(10, 28, 100, 34), (18, 20, 84, 8), (18, 62, 84, 8)]
+        );
+        // Radii past half the shorter side leave the middle band empty
+        assert_eq!(
+            corner_free_rects(&content, 40, 40),
+            vec![(35, 20, 50, 25), (35, 45, 50, 25)]
+        );
+    }
+
+    #[test]
+    fn test_invert_filter_applies_to_its_window_only() {
+        let content = Rectangle::new(Point::from((0, 0)), Size::from((100, 100)));
+        let window = |id, color_filter| RenderedWindow {
+            id,
+            content,
+            opaque_region: Vec::new(),
+            color_filter,
+        };
+        let windows = [window(1, ColorFilter::Invert), window(2, ColorFilter::None)];
+
+        assert_eq!(
+            window_filter(&windows, 1),
+            Some([
+                [-1.0, 0.0, 0.0, 1.0],
+                [0.0, -1.0, 0.0, 1.0],
+                [0.0, 0.0, -1.0, 1.0],
+            ])
+        );
+        assert_eq!(window_filter(&windows, 2), None);
+        assert_eq!(window_filter(&windows, 3), None);
+    }
+
+    #[test]
+    fn test_capture_rect_is_clamped_and_flipped_for_readback() {
+        let fb = (1920, 1080);
+        // Fully inside: y flips against the framebuffer height
+        assert_eq!(
+            capture_readback_rect((100, 200, 300, 100), fb),
+            Some(Rectangle::new((100, 780).into(), (300, 100).into()))
+        );
+        // Hanging off the top-left and bottom-right corners
+        assert_eq!(
+            capture_readback_rect((-10, -20, 30, 40), fb),
+            Some(Rectangle::new((0, 1060).into(), (20, 20).into()))
+        );
+        assert_eq!(
+            capture_readback_rect((1900, 1070, 100, 100), fb),
+            Some(Rectangle::new((1900, 0).into(), (20, 10).into()))
+        );
+        assert_eq!(capture_readback_rect((2000, 0, 10, 10), fb), None);
+        assert_eq!(capture_readback_rect((0, 0, 0, 10), fb), None);
+    }
+
+    #[test]
+    fn test_readback_rows_are_unpadded_and_top_down() {
+        // 1x3 readback padded to 8 bytes per row, bottom row first
+        let padded = [
+            3, 3, 3, 3, 0, 0, 0, 0, //
+            2, 2, 2, 2, 0, 0, 0, 0, //
+            1, 1, 1, 1, 0, 0, 0, 0,
+        ];
+        assert_eq!(
+            repack_readback_rows(&padded, 1, 3),
+            vec![1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3]
+        );
+        let tight: Vec<u8> = (0..16).collect();
+        assert_eq!(
+            repack_readback_rows(&tight, 2, 2),
+            vec![8, 9, 10, 11, 12, 13, 14, 15, 0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        assert!(repack_readback_rows(&[], 0, 4).is_empty());
+    }
+
+    #[test]
+    fn test_oversized_buffer_is_refused_not_uploaded() {
+        assert!(fits_texture_limit((4096, 2160), 8192));
+        assert!(fits_texture_limit((8192, 8192), 8192));
+        assert!(!fits_texture_limit((8193, 16), 8192));
+        assert!(!fits_texture_limit((16, 65536), 16384));
+        // Limit not known yet (no GL context): don't block anything
+        assert!(fits_texture_limit((65536, 65536), 0));
+    }
+
+    #[test]
+    fn test_mirror_output_draws_reference_source_region() {
+        let mirrors = [MirrorMap::new(
+            Rectangle::new(Point::from((0, 0)), Size::from((1920, 1080))),
+            Rectangle::new(Point::from((1920, 0)), Size::from((1280, 720))),
+        )];
+        let src = Rectangle::new(Point::from((0.0, 0.0)), Size::from((300.0, 150.0)));
+
+        // A window on the source: drawn in place, then its whole buffer
+        // scaled onto the mirror
+        let g = Rectangle::new(Point::from((960, 540)), Size::from((300, 150)));
+        let draws = element_draws(g, src, Transform::Normal, &mirrors);
+        assert_eq!(draws.len(), 2);
+        assert_eq!((draws[0].src, draws[0].dst), (src, g));
+        let copy = Rectangle::new(Point::from((2560, 360)), Size::from((200, 100)));
+        assert_eq!((draws[1].src, draws[1].dst), (src, copy));
+
+        // One straddling into the mirror's region: only its part on the
+        // source is drawn, in place and on the mirror
+        let g = Rectangle::new(Point::from((1770, 0)), Size::from((300, 150)));
+        let draws = element_draws(g, src, Transform::Normal, &mirrors);
+        assert_eq!(
+            draws[0].damage,
+            vec![Rectangle::new(
+                Point::from((1770, 0)),
+                Size::from((150, 150))
+            )]
+        );
+        assert_eq!(
+            draws[1].src,
+            Rectangle::new(Point::from((0.0, 0.0)), Size::from((150.0, 150.0)))
+        );
+        assert_eq!(
+            draws[1].dst,
+            Rectangle::new(Point::from((3100, 0)), Size::from((100, 100)))
+        );
+
+        // One on the mirror's own region isn't drawn at all
+        let g = Rectangle::new(Point::from((2000, 100)), Size::from((300, 150)));
+        assert!(element_draws(g, src, Transform::Normal, &mirrors).is_empty());
+    }
+
+    #[test]
+    fn test_damage_on_one_output_leaves_the_other_undrawn() {
+        let outputs = vec![
+            ("Axiom-Output-0".to_string(), WindowRectangle::from_loc_and_size((0, 0), (1920, 1080))),
+            ("Axiom-Output-1".to_string(), WindowRectangle::from_loc_and_size((1920, 0), (1280, 1024))),
+        ];
+        let damage = vec![
+            Rectangle::new(Point::from((100, 100)), Size::from((200, 150))),
+            Rectangle::new(Point::from((1800, 900)), Size::from((100, 100))),
+        ];
+        let per_output = partition_output_damage(&damage, &outputs);
+        assert_eq!(per_output.len(), 1);
+        assert_eq!(per_output["Axiom-Output-0"], damage);
+        assert!(!per_output.contains_key("Axiom-Output-1"));
+
+        // A rect straddling the seam is clipped to each side
+        let straddling = [Rectangle::new(Point::from((1900, 0)), Size::from((40, 10)))];
+        let per_output = partition_output_damage(&straddling, &outputs);
+        assert_eq!(
+            per_output["Axiom-Output-0"],
+            vec![Rectangle::new(Point::from((1900, 0)), Size::from((20, 10)))]
+        );
+        assert_eq!(
+            per_output["Axiom-Output-1"],
+            vec![Rectangle::new(Point::from((1920, 0)), Size::from((20, 10)))]
+        );
+    }
+
+    #[test]
+    fn test_damage_fraction_is_share_of_output_redrawn() {
+        let quarter = [Rectangle::new(Point::from((0, 0)), Size::from((50, 100)))];
+        assert_eq!(damage_fraction(&quarter, (100, 200)), 0.25);
+        // Undamaged frames are submitted whole
+        assert_eq!(damage_fraction(&[], (100, 200)), 1.0);
+    }
+}
@@ -0,0 +1,251 @@
+//! Keeping one client's mistakes away from the others.
+//!
+//! Clients are dispatched one at a time (see
+//! `AxiomSmithayBackendReal::dispatch_clients_isolated`), so a protocol
+//! error or a panicking handler only ends the connection of the client
+//! whose request caused it. [`SecurityManager`] keeps count of those
+//! disconnects per client process and, when `security.ban_after_errors`
+//! is set, turns away new connections from a process that keeps
+//! misbehaving.
+//!
+//! It also decides which clients may inject input through the virtual
+//! keyboard and pointer protocols (`security.allow_virtual_input`).
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+use parking_lot::Mutex;
+use wayland_server::backend::{ClientId, DisconnectReason};
+use wayland_server::protocol::wl_display::{self, WlDisplay};
+use wayland_server::{DisplayHandle, Resource};
+
+use crate::config::SecurityConfig;
+use crate::window::ClientProcess;
+
+/// Disconnects reported by `ClientState::disconnected`, which the Wayland
+/// backend calls without access to `State`; drained once per cycle.
+pub(super) type DisconnectQueue = Arc<Mutex<Vec<(ClientId, DisconnectReason)>>>;
+
+#[derive(Debug, Default)]
+pub(super) struct SecurityManager {
+    disconnects: DisconnectQueue,
+    /// Connected clients and the pid of the process behind each, `None`
+    /// when the connection carried no credentials
+    clients: HashMap<ClientId, Option<i32>>,
+    /// When each process last got a client disconnected for misbehaving,
+    /// oldest first, within `security.error_window_secs`
+    errors: HashMap<i32, VecDeque<Instant>>,
+    banned_until: HashMap<i32, Instant>,
+}
+
+impl SecurityManager {
+    /// The queue a new client's `ClientState` reports its disconnect to.
+    pub(super) fn disconnect_queue(&self) -> DisconnectQueue {
+        self.disconnects.clone()
+    }
+
+    /// A client connected from process `pid`. `false` if that process is
+    /// banned, in which case the caller drops the connection. Clients of
+    /// unknown process are always admitted and never counted against one.
+    pub(super) fn client_connected(
+        &mut self,
+        id: ClientId,
+        pid: Option<i32>,
+        now: Instant,
+    ) -> bool {
+        if pid.is_some_and(|pid| self.is_banned(pid, now)) {
+            return false;
+        }
+        self.clients.insert(id, pid);
+        true
+    }
+
+    /// The clients to dispatch.
+    pub(super) fn clients(&self) -> Vec<ClientId> {
+        self.clients.keys().cloned().collect()
+    }
+
+    /// Forget clients that went away since the last call, counting the
+    /// ones that were disconnected for a protocol error against their
+    /// process.
+    pub(super) fn process_disconnects(&mut self, config: &SecurityConfig, now: Instant) {
+        let disconnects = std::mem::take(&mut *self.disconnects.lock());
+        for (id, reason) in disconnects {
+            let pid = self.clients.remove(&id).flatten();
+            match reason {
+                DisconnectReason::ProtocolError(error) => {
+                    warn!(
+                        "🛡️ Disconnected client {:?} (pid {:?}) for a protocol error on {}@{}: {}",
+                        id, pid, error.object_interface, error.object_id, error.message
+                    );
+                    if let Some(pid) = pid {
+                        self.record_error(pid, config, now);
+                    }
+                }
+                DisconnectReason::ConnectionClosed => {
+                    debug!("Client {:?} (pid {:?}) disconnected", id, pid);
+                }
+            }
+        }
+    }
+
+    /// Count a misbehaving client against process `pid`, banning it once
+    /// it reaches `config.ban_after_errors` within the window.
+    pub(super) fn record_error(&mut self, pid: i32, config: &SecurityConfig, now: Instant) {
+        let window = Duration::from_secs(config.error_window_secs);
+        let errors = self.errors.entry(pid).or_default();
+        errors.push_back(now);
+        while errors
+            .front()
+            .is_some_and(|&at| now.duration_since(at) > window)
+        {
+            errors.pop_front();
+        }
+        if config.ban_after_errors == 0 || errors.len() < config.ban_after_errors as usize {
+            return;
+        }
+        warn!(
+            "🛡️ Refusing new connections from pid {} for {}s after {} protocol errors",
+            pid,
+            config.ban_secs,
+            errors.len()
+        );
+        self.errors.remove(&pid);
+        self.banned_until
+            .insert(pid, now + Duration::from_secs(config.ban_secs));
+    }
+
+    /// The process behind client `id`, `None` when its connection carried
+    /// no credentials.
+    pub(super) fn client_process(&self, id: &ClientId) -> Option<ClientProcess> {
+        let pid = self.clients.get(id).copied().flatten()?;
+        let executable = std::fs::read_link(format!("/proc/{pid}/exe"))
+            .ok()
+            .and_then(|exe| {
+                exe.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .or_else(|| {
+                std::fs::read_to_string(format!("/proc/{pid}/comm"))
+                    .ok()
+                    .map(|comm| comm.trim_end().to_string())
+            });
+        Some(ClientProcess { pid, executable })
+    }
+
+    /// Whether client `id` may use the virtual keyboard and pointer.
+    pub(super) fn may_inject_input(&self, id: &ClientId, config: &SecurityConfig) -> bool {
+        let name = self
+            .clients
+            .get(id)
+            .copied()
+            .flatten()
+            .and_then(|pid| std::fs::read_to_string(format!("/proc/{pid}/comm")).ok());
+        allows_virtual_input(
+            &config.allow_virtual_input,
+            name.as_deref().map(str::trim_end),
+        )
+    }
+
+    /// Whether connections from `pid` are being refused.
+    pub(super) fn is_banned(&mut self, pid: i32, now: Instant) -> bool {
+        match self.banned_until.get(&pid) {
+            Some(&until) if now < until => true,
+            Some(_) => {
+                self.banned_until.remove(&pid);
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+/// Whether a process called `name` may inject input, per
+/// `security.allow_virtual_input`.
+pub(super) fn allows_virtual_input(allow: &[String], name: Option<&str>) -> bool {
+    allow
+        .iter()
+        .any(|allowed| allowed == "*" || Some(allowed.as_str()) == name)
+}
+
+/// Send `wl_display.error(implementation)` to a client, which disconnects
+/// it. A client whose display object can't be found is just dropped.
+pub(super) fn post_implementation_error(dh: &DisplayHandle, id: ClientId, message: &str) {
+    let handle = dh.backend_handle();
+    match handle.object_for_protocol_id(id.clone(), WlDisplay::interface(), 1) {
+        Ok(display) => handle.post_error(
+            display,
+            wl_display::Error::Implementation as u32,
+            std::ffi::CString::new(message).unwrap_or_default(),
+        ),
+        Err(_) => handle.kill_client(id, DisconnectReason::ConnectionClosed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(ban_after_errors: u32) -> SecurityConfig {
+        SecurityConfig {
+            ban_after_errors,
+            error_window_secs: 60,
+            ban_secs: 300,
+            allow_virtual_input: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_errors_are_only_counted_without_a_ban_limit() {
+        let mut security = SecurityManager::default();
+        let now = Instant::now();
+        for _ in 0..10 {
+            security.record_error(42, &config(0), now);
+        }
+        assert!(!security.is_banned(42, now));
+    }
+
+    #[test]
+    fn test_repeat_offender_is_banned_for_a_while() {
+        let mut security = SecurityManager::default();
+        let config = config(3);
+        let start = Instant::now();
+        security.record_error(42, &config, start);
+        security.record_error(42, &config, start + Duration::from_secs(1));
+        assert!(!security.is_banned(42, start + Duration::from_secs(1)));
+        // Another process's errors don't add up with these
+        security.record_error(7, &config, start + Duration::from_secs(1));
+
+        security.record_error(42, &config, start + Duration::from_secs(2));
+        let later = start + Duration::from_secs(10);
+        assert!(security.is_banned(42, later));
+        assert!(!security.is_banned(7, later));
+
+        let after_ban = start + Duration::from_secs(2 + 300);
+        assert!(!security.is_banned(42, after_ban));
+    }
+
+    #[test]
+    fn test_errors_outside_the_window_are_forgotten() {
+        let mut security = SecurityManager::default();
+        let config = config(2);
+        let start = Instant::now();
+        security.record_error(42, &config, start);
+        let later = start + Duration::from_secs(61);
+        security.record_error(42, &config, later);
+        assert!(!security.is_banned(42, later));
+    }
+
+    #[test]
+    fn test_virtual_input_needs_an_allowed_process_name() {
+        let allow = vec!["wayvnc".to_string()];
+        assert!(allows_virtual_input(&allow, Some("wayvnc")));
+        assert!(!allows_virtual_input(&allow, Some("wtype")));
+        // A client whose process can't be identified is never allowed by name
+        assert!(!allows_virtual_input(&allow, None));
+        assert!(!allows_virtual_input(&[], Some("wayvnc")));
+        assert!(allows_virtual_input(&["*".to_string()], None));
+    }
+}
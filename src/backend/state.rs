@@ -11,9 +11,9 @@
 
 use crate::config::AxiomConfig;
 use crate::decoration::DecorationManager;
-use crate::input::InputManager;
+use crate::input::{InputManager, LayerFocusChange, LayerKeyboardMode, DEFAULT_SEAT};
 use crate::window::WindowManager;
-use crate::workspace::ScrollableWorkspaces;
+use crate::workspace::{scale_to_logical, ScrollableWorkspaces};
 use log::{debug, info, warn};
 
 use smithay::{
@@ -25,54 +25,89 @@ use smithay::{
     delegate_compositor, delegate_data_device, delegate_foreign_toplevel_list, delegate_seat,
     delegate_session_lock, delegate_shm, delegate_xdg_shell,
     input::{
-        pointer::{CursorIcon, CursorImageStatus},
+        pointer::{CursorIcon, CursorImageStatus, CursorImageSurfaceData},
         Seat, SeatHandler, SeatState,
     },
     reexports::wayland_protocols::xdg::decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode,
     reexports::wayland_server::{protocol::wl_seat, DisplayHandle},
     output::Output,
-    utils::{Physical, Point, Rectangle, Serial, Size},
+    utils::{Clock, Logical, Monotonic, Physical, Point, Rectangle, Serial, Size},
     wayland::{
         buffer::BufferHandler,
-        compositor::{with_states, CompositorClientState, CompositorHandler, CompositorState},
+        compositor::{
+            with_states, with_surface_tree_downward, BufferAssignment, CompositorClientState,
+            CompositorHandler, CompositorState, Damage, SurfaceAttributes, TraversalAction,
+        },
         foreign_toplevel_list::{
             ForeignToplevelHandle, ForeignToplevelListHandler, ForeignToplevelListState,
         },
         fractional_scale::{self, FractionalScaleHandler, FractionalScaleManagerState},
+        input_method::{InputMethodManagerState, PopupSurface as InputMethodPopup},
         output::OutputHandler,
         selection::{
             data_device::{
-                request_data_device_client_selection, set_data_device_focus, ClientDndGrabHandler,
-                DataDeviceHandler, DataDeviceState, ServerDndGrabHandler,
+                request_data_device_client_selection, set_data_device_focus, with_source_metadata,
+                ClientDndGrabHandler, DataDeviceHandler, DataDeviceState, ServerDndGrabHandler,
             },
             SelectionHandler, SelectionSource, SelectionTarget,
         },
         session_lock::{LockSurface, SessionLockHandler, SessionLockManagerState, SessionLocker},
         shell::{
-            wlr_layer::{Layer, LayerSurface, WlrLayerShellHandler, WlrLayerShellState},
+            wlr_layer::{
+                Anchor, ExclusiveZone, KeyboardInteractivity, Layer, LayerSurface,
+                LayerSurfaceCachedState, WlrLayerShellHandler, WlrLayerShellState,
+            },
             xdg::{
                 decoration::{XdgDecorationHandler, XdgDecorationState},
-                PopupSurface, PositionerState, ToplevelSurface, XdgShellHandler, XdgShellState,
-                XdgToplevelSurfaceData,
+                PopupSurface, PositionerState, SurfaceCachedState, ToplevelSurface,
+                XdgShellHandler, XdgShellState, XdgToplevelSurfaceData,
             },
         },
+        presentation::PresentationState,
         shm::{ShmHandler, ShmState},
+        tearing_control::{TearingControlState, TearingControlSurfaceCachedState},
+        text_input::TextInputManagerState,
+        viewporter::ViewporterState,
     },
 };
 
 use std::collections::{HashMap, HashSet};
 use std::os::unix::io::OwnedFd;
 use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use super::frame_callbacks::{
+    send_frame_callbacks, send_presentation_feedback, FrameCallbackScheduler, PresentedFrame,
+};
+use super::background::OutputBackgrounds;
+use super::hotplug::OutputChange;
+use super::color::ColorPass;
+use super::corners::CornerPass;
+use super::cursor::{shm_cursor_image, Cursor};
+use super::foreign_toplevel::WlrToplevels;
+use super::hud::Hud;
+use super::layers::Layers;
+use super::mirror::MirrorMap;
+use super::occlusion::{self, RenderedWindow};
+use super::render::TextureLimits;
+use super::security::{post_implementation_error, DisconnectQueue, SecurityManager};
+use super::text::TextRenderer;
+use super::upload_queue::UploadQueue;
+use super::virtual_input::VirtualInput;
+use super::wallpaper::WallpaperCache;
+use super::window_stats::WindowStatsTracker;
 
 use wayland_server::{
-    backend::{ClientData, ClientId, DisconnectReason, ObjectId},
+    backend::{ClientData, ClientId, DisconnectReason, GlobalId, ObjectId},
     protocol::{
-        wl_buffer, wl_data_source::WlDataSource, wl_output::WlOutput, wl_surface::WlSurface,
+        wl_buffer, wl_data_device_manager::DndAction, wl_data_source::WlDataSource,
+        wl_output::WlOutput, wl_surface::WlSurface,
     },
     Client, Resource,
 };
 
 use wayland_protocols::xdg::shell::server::xdg_toplevel;
+use wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_v1::PresentationHint;
 
 use smithay::reexports::wayland_protocols_wlr::screencopy::v1::server::zwlr_screencopy_frame_v1;
 use smithay::utils::Buffer as BufferCoord;
@@ -132,12 +167,15 @@ pub struct PopupState {
 
 pub(super) struct ClientState {
     pub(super) compositor_state: CompositorClientState,
+    /// Where this client's disconnect is reported to the `SecurityManager`
+    pub(super) disconnects: DisconnectQueue,
 }
 
 impl ClientData for ClientState {
     fn initialized(&self, _client_id: ClientId) {}
     fn disconnected(&self, client_id: ClientId, reason: DisconnectReason) {
         debug!("Client {:?} disconnected: {:?}", client_id, reason);
+        self.disconnects.lock().push((client_id, reason));
     }
 }
 
@@ -156,6 +194,17 @@ pub struct PendingCapture {
     pub size: Size<i32, BufferCoord>,
 }
 
+/// What `window.max_windows` leaves room for when a toplevel is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowOverflow {
+    /// Under the cap, or no cap set
+    Admit,
+    /// At the cap; the new toplevel's client is disconnected
+    Reject,
+    /// At the cap; this window is asked to close to make room
+    Close(u64),
+}
+
 // ============================================================================
 // Compositor State
 // ============================================================================
@@ -174,11 +223,25 @@ pub struct State {
     pub display_handle: Option<DisplayHandle>,
     pub xdg_decoration_state: Option<XdgDecorationState>,
     pub fractional_scale_manager_state: FractionalScaleManagerState,
+    /// `wp_viewporter` global; viewports are applied when drawing surfaces.
+    pub viewporter_state: ViewporterState,
     pub layer_shell_state: WlrLayerShellState,
     pub session_lock_state: SessionLockManagerState,
+    /// `wp_tearing_control_v1` global; only registered when
+    /// `performance.allow_tearing` is on.
+    pub tearing_control_state: Option<TearingControlState>,
+    /// `wp_presentation` global; feedback is answered as frames present.
+    pub presentation_state: PresentationState,
+    /// `zwp_text_input_manager_v3` global, for clients taking text input.
+    pub text_input_state: TextInputManagerState,
+    /// `zwp_input_method_manager_v2` global, for the input method.
+    pub input_method_state: InputMethodManagerState,
 
     // Seat
     pub seat: Seat<Self>,
+    /// Seats configured in `input.seats` besides the default one, by name.
+    /// Each is its own `wl_seat` global with its own keyboard focus.
+    pub seats: HashMap<String, Seat<Self>>,
 
     // Axiom subsystems
     pub config: AxiomConfig,
@@ -210,6 +273,10 @@ pub struct State {
     // Running state
     pub running: bool,
     pub needs_redraw: bool,
+    /// When the oldest redraw that hasn't made it to the screen yet was
+    /// requested. Stays set while rendering keeps failing; the compositor's
+    /// watchdog reports a stall when it gets too old.
+    pub redraw_pending_since: Option<Instant>,
     /// Pending screencopy capture, processed after the next render.
     pub pending_capture: Option<PendingCapture>,
     /// Whether the session is currently locked (lock screen showing).
@@ -218,11 +285,39 @@ pub struct State {
     /// ponytail: stored here because SessionLockManagerState doesn't expose an
     /// iterator; upgrade to upstream tracking if Smithay adds one.
     pub lock_surfaces: Vec<LockSurface>,
+    /// Candidate popups of the input method, drawn over everything else.
+    pub ime_popups: Vec<InputMethodPopup>,
 
     /// Accumulated output damage regions since last render, in physical coordinates.
     /// Cleared after each frame submit. Only surfaces that changed or moved contribute.
     pub output_damage: Vec<Rectangle<i32, Physical>>,
 
+    /// Damage committed by each window's main surface, held back from
+    /// `output_damage` until `flush_window_damage` drops the part hidden
+    /// under windows stacked above it.
+    pub(super) window_damage: HashMap<u64, Vec<Rectangle<i32, Physical>>>,
+
+    /// Windows as the last frame stacked them, back to front.
+    pub(super) rendered_windows: Vec<RenderedWindow>,
+
+    /// Uploaded `workspace.backgrounds` and output wallpaper images.
+    pub(super) wallpapers: WallpaperCache,
+
+    /// Each output's own background color and wallpaper.
+    pub(super) backgrounds: OutputBackgrounds,
+
+    /// Connected clients and the processes that misbehaved.
+    pub(super) security: SecurityManager,
+
+    /// Input from virtual keyboards and pointers, replayed after dispatch.
+    pub(super) virtual_input: Vec<VirtualInput>,
+
+    /// Taskbars bound to `zwlr_foreign_toplevel_manager_v1` and their handles.
+    pub(super) wlr_toplevels: WlrToplevels,
+
+    /// Debug HUD (`--hud`) and the frame stats it shows.
+    pub(super) hud: Hud,
+
     /// Per-surface previous frame geometry (screen position + size) so we can
     /// damage the old location when a surface moves or resizes.
     pub surface_previous_rects: HashMap<u32, Rectangle<i32, Physical>>,
@@ -231,6 +326,33 @@ pub struct State {
     /// Tracks how many times each surface has committed a buffer so we can
     /// detect which surfaces changed between frames for precise damage tracking.
     pub surface_commit_counters: HashMap<u32, u64>,
+    /// Per-window commit cadence and upload volume, for `GetWindows`.
+    pub(super) window_stats: WindowStatsTracker,
+
+    /// Which windows get `wl_surface.frame` callbacks after a present.
+    /// Visibility is refreshed by every render pass.
+    pub(super) frame_callbacks: FrameCallbackScheduler,
+    /// Epoch for frame-callback timestamps.
+    pub(super) clock_start: Instant,
+    /// `CLOCK_MONOTONIC`, the clock `wp_presentation` timestamps are in
+    pub(super) presentation_clock: Clock<Monotonic>,
+
+    /// Surfaces whose committed tearing-control hint asks for async
+    /// presentation, keyed by protocol ID. Only recorded so far: every
+    /// output still presents FIFO.
+    pub(super) tearing_surfaces: HashSet<u32>,
+    /// Committed opaque region of each window's main surface, surface-local
+    /// and disjoint. Absent means the client declared none.
+    pub(super) opaque_regions: HashMap<u64, Vec<Rectangle<i32, Logical>>>,
+    /// Mirrored outputs: target tape ID to the source it clones.
+    pub(super) output_mirrors: HashMap<String, String>,
+    /// Geometry mappings for `output_mirrors`, rebuilt every render pass.
+    pub(super) mirror_maps: Vec<MirrorMap>,
+    /// `wl_output` global of each connected output, by name, so a
+    /// disconnect can withdraw it.
+    pub(super) output_globals: HashMap<String, GlobalId>,
+    /// Connects and disconnects not yet picked up by the compositor.
+    pub(super) output_changes: Vec<OutputChange>,
 
     // Current window/viewport size (updated via Resized events after dispatch)
     pub window_width: u32,
@@ -245,6 +367,20 @@ pub struct State {
     /// exactly once and reused across frames. Evicted on buffer_destroyed or
     /// LRU order when the cache reaches capacity.
     pub texture_cache: lru::LruCache<ObjectId, TextureBuffer<GlesTexture>>,
+    /// Refuses uploads of buffers over the GPU's max texture size.
+    pub(super) texture_limits: TextureLimits,
+    /// Spreads buffer uploads over frames within `performance.upload_budget_mb`.
+    pub(super) upload_queue: UploadQueue,
+    /// Colour-matrix shader for `output.brightness`/`contrast`/
+    /// `saturation`, window filters and text.
+    pub(super) color_pass: ColorPass,
+    /// Rounded-corner shader for windows (`decoration.corner_radius`).
+    pub(super) corner_pass: CornerPass,
+    /// Offscreen layers for `output.layered_compositing` and post-process
+    /// passes.
+    pub(super) layers: Layers,
+    /// Glyph atlas for drawing text, such as the HUD's.
+    pub(super) text_renderer: TextRenderer,
 
     /// Tracks whether we've sent the initial configure for a surface.
     /// Used to throttle redundant configure events when layout hasn't changed.
@@ -257,8 +393,8 @@ pub struct State {
     /// Active XDG popup surfaces (menus, tooltips, etc.).
     pub popups: HashMap<u32, PopupState>,
 
-    /// If set, a popup grab is active — clicks outside this popup
-    /// surface ID will dismiss it via popup_done().
+    /// Topmost popup holding an `xdg_popup` grab. Escape, or a click
+    /// outside every popup of its chain, dismisses the chain.
     pub active_popup_grab: Option<u32>,
 
     /// Cached clipboard payload served to both X11 and compositor-provided
@@ -301,6 +437,15 @@ pub struct State {
     /// Most recent cursor icon requested via `cursor_image()` callback.
     /// Applied to the winit window at the start of `render()`.
     pub cursor_icon: Option<CursorIcon>,
+    /// Client-provided cursor surface from `wl_pointer.set_cursor`. When set,
+    /// the host cursor is hidden and this surface is drawn in software.
+    pub(super) cursor_surface: Option<WlSurface>,
+    /// Hotspot of `cursor_surface` in logical pixels, relative to its top-left
+    /// corner. Subtracted from the pointer position when drawing.
+    pub(super) cursor_hotspot: (i32, i32),
+    /// Whether `cursor_surface` goes on a hardware cursor plane or is drawn
+    /// into the frame.
+    pub(super) cursor: Cursor,
 
     /// Active drag-and-drop icon surface (set when a client starts a DnD
     /// operation with an icon). Rendered as an overlay at the pointer position.
@@ -313,6 +458,22 @@ pub struct State {
 }
 
 impl State {
+    /// Advertise a `wl_seat` for every seat in `input.seats` other than
+    /// the default one.
+    pub(super) fn extra_seats(
+        seat_state: &mut SeatState<Self>,
+        dh: &DisplayHandle,
+        config: &AxiomConfig,
+    ) -> HashMap<String, Seat<Self>> {
+        config
+            .input
+            .seats
+            .keys()
+            .filter(|name| name.as_str() != DEFAULT_SEAT)
+            .map(|name| (name.clone(), seat_state.new_wl_seat(dh, name.clone())))
+            .collect()
+    }
+
     pub(super) fn keyboard_repeat_settings(config: &AxiomConfig) -> (i32, i32) {
         let delay = config.input.keyboard_repeat_delay.min(i32::MAX as u32) as i32;
         let rate = config.input.keyboard_repeat_rate.min(i32::MAX as u32) as i32;
@@ -398,7 +559,305 @@ impl State {
         })
     }
 
-    fn update_focus_state(&mut self, focused_window_id: Option<u64>) {
+    /// Top-left corner at which a cursor surface is drawn so that its hotspot
+    /// lands exactly on the pointer position.
+    pub(super) fn cursor_draw_position(pointer: (f64, f64), hotspot: (i32, i32)) -> (i32, i32) {
+        (
+            pointer.0.round() as i32 - hotspot.0,
+            pointer.1.round() as i32 - hotspot.1,
+        )
+    }
+
+    /// Offer the client cursor image to the hardware cursor plane, if the
+    /// backend has one. Anything it can't show stays a software cursor.
+    pub(super) fn update_cursor_plane(&mut self) {
+        if !self.cursor.has_plane() {
+            return;
+        }
+        let image = self
+            .cursor_surface
+            .as_ref()
+            .and_then(|surface| shm_cursor_image(surface, self.cursor_hotspot));
+        let draw_at =
+            Self::cursor_draw_position((self.pointer_x, self.pointer_y), self.cursor_hotspot);
+        self.cursor.set_image(image.as_ref(), draw_at);
+    }
+
+    /// Themed cursor shown while a drag is in flight for the given action.
+    pub(super) fn dnd_cursor_icon(action: DndAction) -> CursorIcon {
+        if action.contains(DndAction::Copy) && !action.contains(DndAction::Move) {
+            CursorIcon::Copy
+        } else {
+            CursorIcon::Move
+        }
+    }
+
+    /// Show the themed drag cursor for `action`, replacing any client cursor.
+    pub(super) fn set_dnd_cursor(&mut self, action: DndAction) {
+        self.cursor_surface = None;
+        self.cursor_hotspot = (0, 0);
+        self.cursor_icon = Some(Self::dnd_cursor_icon(action));
+        self.update_cursor_plane();
+        self.needs_redraw = true;
+    }
+
+    /// Drop any client cursor surface and fall back to the default themed cursor.
+    pub(super) fn reset_cursor(&mut self) {
+        self.cursor_surface = None;
+        self.cursor_hotspot = (0, 0);
+        self.cursor_icon = Some(CursorIcon::Default);
+        self.update_cursor_plane();
+        self.needs_redraw = true;
+    }
+
+    /// Mirror a toplevel's committed `xdg_toplevel` min/max size into its
+    /// [`crate::window::WindowConstraints`]. A fixed-size window floats,
+    /// centered on the output, since tiling would have to ignore its size.
+    fn update_toplevel_size_hints(&mut self, surface: &WlSurface) {
+        let Some(window_id) = self.window_id_for_surface(surface) else {
+            return;
+        };
+        let (min, max) = with_states(surface, |states| {
+            let mut cached = states.cached_state.get::<SurfaceCachedState>();
+            let current = cached.current();
+            (
+                (current.min_size.w, current.min_size.h),
+                (current.max_size.w, current.max_size.h),
+            )
+        });
+        let mut constraints = crate::window::WindowConstraints::from_size_hints(min, max);
+
+        let mut wm = self.window_manager.write();
+        let Some(current) = wm.get_window(window_id).map(|w| w.properties.constraints) else {
+            return;
+        };
+        // The hints only bound the size; an aspect lock set another way stays
+        constraints.aspect_ratio = current.aspect_ratio;
+        if current == constraints {
+            return;
+        }
+        if !wm.apply_size_hints(window_id, constraints) {
+            return;
+        }
+        let area = crate::window::Rectangle::from_loc_and_size(
+            (0, 0),
+            (self.window_width, self.window_height),
+        );
+        let _ = wm.place_floating_window(window_id, area);
+        drop(wm);
+        self.workspace_manager
+            .write()
+            .set_window_floating(window_id, true);
+        debug!("📐 Window {} has a fixed size; floating it", window_id);
+        self.needs_redraw = true;
+    }
+
+    /// Put a window that just opened where `placement`
+    /// (`window.open_placement`) says: the focused column, the column under
+    /// the pointer, or floating in the middle of the focused output.
+    pub(super) fn place_new_window(&mut self, window_id: u64, placement: &str) {
+        let mut ws = self.workspace_manager.write();
+        match placement {
+            "cursor" => match ws.column_at(self.pointer_x, self.pointer_y) {
+                Some((output_id, column)) => {
+                    ws.add_window_to_output_column(window_id, &output_id, column);
+                }
+                None => ws.add_window(window_id),
+            },
+            "center" => {
+                ws.add_window(window_id);
+                let area = ws.output_work_area(ws.focused_output()).unwrap_or_else(|| {
+                    crate::window::Rectangle::from_loc_and_size(
+                        (0, 0),
+                        (self.window_width, self.window_height),
+                    )
+                });
+                ws.set_window_floating(window_id, true);
+                drop(ws);
+                let _ = self
+                    .window_manager
+                    .write()
+                    .place_floating_window(window_id, area);
+            }
+            _ => ws.add_window(window_id),
+        }
+    }
+
+    /// Recompute the insets exclusive layer surfaces reserve on the focused
+    /// output. `skip` excludes a surface that is being destroyed.
+    fn update_reserved_insets(&mut self, skip: Option<&WlSurface>) {
+        let mut insets = crate::workspace::ReservedInsets::default();
+        for layer in self.layer_shell_state.layer_surfaces() {
+            if skip.is_some_and(|s| s == layer.wl_surface()) {
+                continue;
+            }
+            let (anchor, margin, zone) = with_states(layer.wl_surface(), |states| {
+                let mut cached = states.cached_state.get::<LayerSurfaceCachedState>();
+                let current = cached.current();
+                (current.anchor, current.margin, current.exclusive_zone)
+            });
+            let ExclusiveZone::Exclusive(zone) = zone else {
+                continue;
+            };
+            // Only surfaces attached to a single edge (optionally spanning
+            // it) reserve space; the zone extends past their margin.
+            let (top, bottom) = (anchor.contains(Anchor::TOP), anchor.contains(Anchor::BOTTOM));
+            let (left, right) = (anchor.contains(Anchor::LEFT), anchor.contains(Anchor::RIGHT));
+            let spans_x = left == right;
+            let spans_y = top == bottom;
+            match (top, bottom, left, right) {
+                (true, false, _, _) if spans_x => insets.top += zone + margin.top.max(0) as u32,
+                (false, true, _, _) if spans_x => {
+                    insets.bottom += zone + margin.bottom.max(0) as u32
+                }
+                (_, _, true, false) if spans_y => insets.left += zone + margin.left.max(0) as u32,
+                (_, _, false, true) if spans_y => {
+                    insets.right += zone + margin.right.max(0) as u32
+                }
+                _ => {}
+            }
+        }
+
+        let mut ws = self.workspace_manager.write();
+        let output_id = ws.focused_output.clone();
+        if ws.reserved_insets(&output_id) != insets {
+            debug!("📏 Reserved insets on {}: {:?}", output_id, insets);
+            ws.set_reserved_insets(&output_id, insets);
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Toggle maximize for a window, sizing it to its output's work area,
+    /// and notify the client via protocol.
+    pub fn toggle_maximize_window(&mut self, window_id: u64) {
+        let work_area = {
+            let ws = self.workspace_manager.read();
+            ws.output_work_area(ws.output_for_window(window_id))
+        };
+        let Some(work_area) = work_area else {
+            return;
+        };
+        self.window_manager
+            .write()
+            .toggle_maximize(window_id, work_area);
+        self.send_maximized_state(window_id);
+    }
+
+    /// Ask the client behind `window_id` to resize its buffer to the size
+    /// the window manager now gives the window.
+    pub fn configure_window_size(&mut self, window_id: u64) {
+        let Some((width, height)) = self
+            .window_manager
+            .read()
+            .get_window(window_id)
+            .map(|w| w.window.size)
+        else {
+            return;
+        };
+        let Some(&surface_id) = self.window_map.get(&window_id) else {
+            return;
+        };
+        let Some(toplevel) = self.toplevels.get(&surface_id) else {
+            return;
+        };
+        // Configure in logical pixels, matching the tiling reconfigure path
+        let scale = self
+            .workspace_manager
+            .read()
+            .scale_factor_for_window(window_id);
+        let logical_w = (scale_to_logical(width as i32, scale).round() as i32).max(1);
+        let logical_h = (scale_to_logical(height as i32, scale).round() as i32).max(1);
+        toplevel.with_pending_state(|state| {
+            state.size = Some((logical_w, logical_h).into());
+        });
+        toplevel.send_configure();
+        self.configured_sizes
+            .insert(surface_id, (logical_w, logical_h));
+    }
+
+    /// Tell the client behind `window_id` whether it is maximized and at
+    /// what size, after the window manager changed either.
+    pub fn send_maximized_state(&mut self, window_id: u64) {
+        let (is_maximized, size) = {
+            let wm = self.window_manager.read();
+            let window = wm.get_window(window_id);
+            (
+                window.is_some_and(|w| w.properties.maximized),
+                window.map(|w| w.window.size),
+            )
+        };
+
+        if let Some(toplevel) = self
+            .window_map
+            .get(&window_id)
+            .and_then(|surface_id| self.toplevels.get(surface_id))
+        {
+            let scale = self.workspace_manager.read().scale_factor_for_window(window_id);
+            toplevel.with_pending_state(|state| {
+                if is_maximized {
+                    state.states.set(xdg_toplevel::State::Maximized);
+                } else {
+                    state.states.unset(xdg_toplevel::State::Maximized);
+                }
+                state.size = size.map(|(w, h)| {
+                    (
+                        (scale_to_logical(w as i32, scale).round() as i32).max(1),
+                        (scale_to_logical(h as i32, scale).round() as i32).max(1),
+                    )
+                        .into()
+                });
+            });
+            toplevel.send_configure();
+        }
+        self.needs_redraw = true;
+    }
+
+    /// Route keyboard focus according to a layer surface's committed
+    /// `keyboard_interactivity`: exclusive surfaces grab the keyboard, and
+    /// dropping back to none/on-demand restores the previous toplevel.
+    fn update_layer_keyboard_focus(&mut self, surface: &WlSurface) {
+        let mode = with_states(surface, |states| {
+            match states
+                .cached_state
+                .get::<LayerSurfaceCachedState>()
+                .current()
+                .keyboard_interactivity
+            {
+                KeyboardInteractivity::Exclusive => LayerKeyboardMode::Exclusive,
+                KeyboardInteractivity::OnDemand => LayerKeyboardMode::OnDemand,
+                KeyboardInteractivity::None => LayerKeyboardMode::None,
+            }
+        });
+        let focused = self.window_manager.read().focused_window_id();
+        let change = self.input_manager.write().set_layer_keyboard_mode(
+            surface.id().protocol_id(),
+            mode,
+            focused,
+        );
+        self.apply_layer_focus_change(change);
+    }
+
+    fn apply_layer_focus_change(&mut self, change: LayerFocusChange) {
+        let target = match change {
+            LayerFocusChange::Unchanged => return,
+            LayerFocusChange::Grab(layer_id) => self
+                .layer_shell_state
+                .layer_surfaces()
+                .map(|l| l.wl_surface().clone())
+                .find(|s| s.id().protocol_id() == layer_id),
+            LayerFocusChange::Restore(window_id) => window_id
+                .and_then(|id| self.window_map.get(&id))
+                .and_then(|surface_id| self.surfaces.get(surface_id))
+                .and_then(|sd| sd.surface.clone())
+                .filter(|s| s.is_alive()),
+        };
+        if let Some(keyboard) = self.seat.get_keyboard() {
+            debug!("⌨️ Layer keyboard focus change: {:?}", change);
+            keyboard.set_focus(self, target, smithay::utils::SERIAL_COUNTER.next_serial());
+        }
+    }
+
+    pub(super) fn update_focus_state(&mut self, focused_window_id: Option<u64>) {
         self.window_manager
             .write()
             .set_focused_window(focused_window_id);
@@ -440,6 +899,12 @@ impl State {
             .surfaces
             .get(&surface_id)
             .and_then(|data| data.window_id);
+        // Clients set these after `new_toplevel` created the window, so the
+        // window rules get another look once each is first known.
+        let first_known = self.surfaces.get(&surface_id).is_some_and(|data| {
+            let had_title = data.title != Self::display_title(None, data.app_id.clone());
+            (data.app_id.is_none() && app_id.is_some()) || (!had_title && title.is_some())
+        });
 
         if let Some(surface_data) = self.surfaces.get_mut(&surface_id) {
             surface_data.title = effective_title.clone();
@@ -447,8 +912,19 @@ impl State {
         }
 
         if let Some(window_id) = window_id {
-            if let Some(window) = self.window_manager.write().get_window_mut(window_id) {
-                window.window.title = effective_title.clone();
+            {
+                let mut wm = self.window_manager.write();
+                wm.set_window_title(window_id, effective_title.clone());
+                wm.set_window_app_id(window_id, app_id.clone());
+                if first_known
+                    && wm.apply_window_rules(
+                        window_id,
+                        app_id.as_deref(),
+                        &self.config.window_rules,
+                    )
+                {
+                    debug!("📏 Window rules applied to window {}", window_id);
+                }
             }
             self.decoration_manager
                 .write()
@@ -470,11 +946,25 @@ impl State {
         );
 
         let visible_title = title.clone();
-        let window_id = self
-            .window_manager
-            .write()
-            .add_window(visible_title.clone());
-        self.workspace_manager.write().add_window(window_id);
+        let client = surface
+            .client()
+            .and_then(|client| self.security.client_process(&client.id()));
+        let window_id = {
+            let mut wm = self.window_manager.write();
+            let id = wm.add_window(visible_title.clone());
+            wm.set_window_app_id(id, app_id.clone());
+            wm.set_window_client(id, client);
+            if wm.apply_window_rules(id, app_id.as_deref(), &self.config.window_rules) {
+                debug!("📏 Window rules applied to window {}", id);
+            }
+            id
+        };
+        let placement = self
+            .config
+            .window
+            .open_placement_for(&self.config.window_rules, app_id.as_deref(), &visible_title)
+            .to_string();
+        self.place_new_window(window_id, &placement);
 
         let surface_data = SurfaceData {
             window_id: Some(window_id),
@@ -503,17 +993,24 @@ impl State {
         if let Some(handle) = self.toplevel_handles.remove(&surface_id) {
             handle.send_closed();
         }
-        // Release the toplevel handle to prevent memory leaks
-        self.toplevels.remove(&surface_id);
+        // Release the toplevel handle to prevent memory leaks, freeing the
+        // textures of its surface tree now instead of when the client gets
+        // around to destroying the buffers (or LRU eviction does).
+        if let Some(toplevel) = self.toplevels.remove(&surface_id) {
+            self.evict_surface_tree_textures(toplevel.wl_surface());
+        }
 
         // Clean up configure tracking
         self.configured_sizes.remove(&surface_id);
         self.pending_configure.remove(&surface_id);
+        self.tearing_surfaces.remove(&surface_id);
 
         if let Some(data) = self.surfaces.remove(&surface_id) {
             if let Some(window_id) = data.window_id {
+                self.opaque_regions.remove(&window_id);
                 info!("Destroying window {} (was: \"{}\")", window_id, data.title);
                 self.window_map.remove(&window_id);
+                self.window_stats.remove(window_id);
                 self.window_manager.write().remove_window(window_id);
                 self.workspace_manager.write().remove_window(window_id);
                 self.decoration_manager.write().remove_window(window_id);
@@ -521,6 +1018,131 @@ impl State {
         }
     }
 
+    /// Ask the client behind `window_id` to close it with
+    /// `xdg_toplevel.close`. A client still holding the window after
+    /// `window.close_grace_ms` is disconnected by
+    /// [`Self::force_overdue_closes`].
+    pub fn request_window_close(&mut self, window_id: u64) {
+        let Some(&surface_id) = self.window_map.get(&window_id) else {
+            return;
+        };
+        let Some(toplevel) = self.toplevels.get(&surface_id) else {
+            // No toplevel to ask, so there is nobody to wait for
+            self.destroy_window(surface_id);
+            return;
+        };
+        toplevel.send_close();
+        let grace = Duration::from_millis(self.config.window.close_grace_ms);
+        self.window_manager
+            .write()
+            .request_close(window_id, Instant::now(), grace);
+    }
+
+    /// What to do with a toplevel about to be created, going by
+    /// `window.max_windows` and `window.overflow_policy`. Windows already
+    /// asked to close don't count towards the cap. With no window left to
+    /// close, `close_oldest` rejects the new one too.
+    pub fn window_overflow(&self) -> WindowOverflow {
+        let Some(max) = self.config.window.max_windows else {
+            return WindowOverflow::Admit;
+        };
+        let wm = self.window_manager.read();
+        if wm.open_window_count() < max as usize {
+            return WindowOverflow::Admit;
+        }
+        match self.config.window.overflow_policy.as_str() {
+            "close_oldest" => wm
+                .least_recently_focused()
+                .map_or(WindowOverflow::Reject, WindowOverflow::Close),
+            _ => WindowOverflow::Reject,
+        }
+    }
+
+    /// Move the selected windows to `column` of the active tape, or to the
+    /// focused column, then clear the selection.
+    pub fn move_selected_to_column(&mut self, column: Option<i32>) {
+        let selected = self.window_manager.read().selected().to_vec();
+        if selected.is_empty() {
+            return;
+        }
+        let mut ws = self.workspace_manager.write();
+        let column = column.unwrap_or_else(|| ws.focused_column_index());
+        let moved = ws.move_windows_to_column(&selected, column);
+        drop(ws);
+        info!("🔀 Moved {} selected window(s) to column {}", moved, column);
+        self.window_manager.write().clear_selection();
+        self.needs_redraw = true;
+    }
+
+    /// Ask every selected window to close, then clear the selection.
+    pub fn close_selected_windows(&mut self) {
+        let selected = self.window_manager.read().selected().to_vec();
+        for window_id in selected {
+            self.request_window_close(window_id);
+        }
+        self.window_manager.write().clear_selection();
+        self.needs_redraw = true;
+    }
+
+    /// Disconnect clients that ignored a close request for longer than the
+    /// grace period and drop their windows.
+    pub fn force_overdue_closes(&mut self) {
+        let overdue = self.window_manager.write().overdue_closes(Instant::now());
+        for window_id in overdue {
+            let Some(&surface_id) = self.window_map.get(&window_id) else {
+                continue;
+            };
+            warn!("Window {} ignored its close request, killing its client", window_id);
+            let client = self
+                .toplevels
+                .get(&surface_id)
+                .and_then(|t| t.wl_surface().client());
+            if let (Some(client), Some(dh)) = (client, &self.display_handle) {
+                dh.backend_handle()
+                    .kill_client(client.id(), DisconnectReason::ConnectionClosed);
+            }
+            self.destroy_window(surface_id);
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Drop cached textures for the buffers attached to `surface` and its
+    /// subsurfaces, and what they were last drawn with. Returns how many
+    /// cache entries were freed.
+    pub fn evict_surface_tree_textures(&mut self, surface: &WlSurface) -> usize {
+        let mut buffers = Vec::new();
+        let mut surfaces = Vec::new();
+        with_surface_tree_downward(
+            surface,
+            (),
+            |_, _, &()| TraversalAction::DoChildren(()),
+            |surface, states, &()| {
+                if let Some(BufferAssignment::NewBuffer(buffer)) =
+                    &states.cached_state.get::<SurfaceAttributes>().current().buffer
+                {
+                    buffers.push(buffer.id());
+                }
+                surfaces.push(surface.id());
+            },
+            |_, _, &()| true,
+        );
+        for surface in &surfaces {
+            self.upload_queue.forget_surface(surface);
+        }
+        let freed = buffers
+            .iter()
+            .filter(|id| self.texture_cache.pop_entry(*id).is_some())
+            .count();
+        if freed > 0 {
+            debug!(
+                "🧹 Freed {} texture(s) for destroyed surface {}",
+                freed,
+                surface.id().protocol_id()
+            );
+        }
+        freed
+    }
+
     /// Check if a window (by Axiom window ID) has a committed surface
     pub fn window_has_buffer(&self, window_id: u64) -> bool {
         self.window_map
@@ -551,24 +1173,44 @@ impl State {
     }
 
     /// Toggle fullscreen for a window and notify the client via protocol.
+    ///
+    /// Fullscreen covers the window's whole output, ignoring gaps and
+    /// reserved insets, and takes the window out of tiling until it leaves.
     pub fn toggle_fullscreen_window(&mut self, window_id: u64) {
+        let output = {
+            let ws = self.workspace_manager.read();
+            ws.output_geometry(ws.output_for_window(window_id))
+        }
+        .unwrap_or_else(|| {
+            crate::window::Rectangle::from_loc_and_size(
+                (0, 0),
+                (self.window_width, self.window_height),
+            )
+        });
         let is_fullscreen = {
             let mut wm = self.window_manager.write();
-            wm.toggle_fullscreen(window_id);
-            wm.get_window(window_id)
-                .map(|w| w.properties.fullscreen)
-                .unwrap_or(false)
+            let currently = wm
+                .get_window(window_id)
+                .is_some_and(|w| w.properties.fullscreen);
+            if currently {
+                wm.exit_fullscreen(window_id);
+                false
+            } else {
+                wm.enter_fullscreen(window_id, output.clone())
+            }
         };
+        self.workspace_manager
+            .write()
+            .set_window_fullscreen(window_id, is_fullscreen);
 
         if let Some(&surface_id) = self.window_map.get(&window_id) {
             if let Some(toplevel) = self.toplevels.get(&surface_id) {
                 let scale = self.focused_output_scale();
+                let logical_w = ((output.width as f64 / scale).round() as i32).max(1);
+                let logical_h = ((output.height as f64 / scale).round() as i32).max(1);
                 toplevel.with_pending_state(|state| {
                     if is_fullscreen {
                         state.states.set(xdg_toplevel::State::Fullscreen);
-                        let logical_w = ((self.window_width as f64 / scale).round() as i32).max(1);
-                        let logical_h =
-                            ((self.window_height as f64 / scale).round() as i32).max(1);
                         state.size = Some((logical_w, logical_h).into());
                     } else {
                         state.states.unset(xdg_toplevel::State::Fullscreen);
@@ -579,14 +1221,11 @@ impl State {
                 });
                 toplevel.send_configure();
                 // Track configured size for exit case (unset)
-                if !is_fullscreen {
-                    self.configured_sizes.remove(&surface_id);
-                } else {
-                    let logical_w = ((self.window_width as f64 / scale).round() as i32).max(1);
-                    let logical_h =
-                        ((self.window_height as f64 / scale).round() as i32).max(1);
+                if is_fullscreen {
                     self.configured_sizes
                         .insert(surface_id, (logical_w, logical_h));
+                } else {
+                    self.configured_sizes.remove(&surface_id);
                 }
                 self.pending_configure.insert(surface_id);
             }
@@ -594,6 +1233,178 @@ impl State {
         self.needs_redraw = true;
     }
 
+    /// Record whether `surface` currently asks to tear.
+    fn update_tearing_hint(&mut self, surface: &WlSurface) {
+        let wants_tearing = with_states(surface, |states| {
+            matches!(
+                states
+                    .cached_state
+                    .get::<TearingControlSurfaceCachedState>()
+                    .current()
+                    .presentation_hint(),
+                PresentationHint::Async
+            )
+        });
+        let surface_id = surface.id().protocol_id();
+        if wants_tearing {
+            self.tearing_surfaces.insert(surface_id);
+        } else {
+            self.tearing_surfaces.remove(&surface_id);
+        }
+    }
+
+    fn update_opaque_region(&mut self, surface: &WlSurface) {
+        let Some(window_id) = self.window_id_for_surface(surface) else {
+            return;
+        };
+        let region = with_states(surface, |states| {
+            states
+                .cached_state
+                .get::<SurfaceAttributes>()
+                .current()
+                .opaque_region
+                .as_ref()
+                .map(occlusion::region_rects)
+        });
+        match region {
+            Some(rects) => {
+                self.opaque_regions.insert(window_id, rects);
+            }
+            None => {
+                self.opaque_regions.remove(&window_id);
+            }
+        }
+    }
+
+    fn push_surface_damage(&mut self, window_id: Option<u64>, rect: Rectangle<i32, Physical>) {
+        match window_id {
+            Some(id) => self.window_damage.entry(id).or_default().push(rect),
+            None => self.output_damage.push(rect),
+        }
+    }
+
+    /// Move pending window damage into `output_damage`, leaving out what
+    /// the opaque regions of windows above it hid in the last frame drawn,
+    /// which is what is on screen until the next one.
+    pub(super) fn flush_window_damage(&mut self) {
+        if self.window_damage.is_empty() {
+            return;
+        }
+        let damage = std::mem::take(&mut self.window_damage);
+        self.output_damage
+            .extend(occlusion::visible_damage(&self.rendered_windows, &damage));
+    }
+
+    /// Fire the frame callbacks owed once every output has presented.
+    /// Windows follow the scheduler's visibility, and their popups follow
+    /// them. Layer, lock, cursor and DnD icon surfaces are on screen
+    /// whenever they are mapped, so they always get one. The nested
+    /// backend presents all outputs together, so a window spanning
+    /// outputs is only signalled once per present, and presentation
+    /// feedback names the first output. `refresh` and `seq` are the
+    /// output's refresh period and the number of frames presented.
+    pub(super) fn send_frame_callbacks_after_present(&mut self, refresh: Duration, seq: u64) {
+        let time = self.clock_start.elapsed();
+        let presented = self.outputs.first().map(|output| PresentedFrame {
+            output: output.clone(),
+            time: self.presentation_clock.now(),
+            refresh,
+            seq,
+        });
+        let signal = |surface: &WlSurface| {
+            send_frame_callbacks(surface, time);
+            if let Some(frame) = &presented {
+                send_presentation_feedback(surface, frame);
+            }
+        };
+        let mut window_ids: HashSet<u64> = HashSet::new();
+        for output in &self.outputs {
+            window_ids.extend(self.frame_callbacks.windows_for_output(&output.name()));
+        }
+
+        let mut signalled: HashSet<u32> = HashSet::new();
+        for window_id in window_ids {
+            let Some(&surface_id) = self.window_map.get(&window_id) else {
+                continue;
+            };
+            if let Some(toplevel) = self.toplevels.get(&surface_id) {
+                signal(toplevel.wl_surface());
+                signalled.insert(surface_id);
+            }
+        }
+        // Popups (possibly nested) of signalled windows
+        loop {
+            let ready: Vec<u32> = self
+                .popups
+                .iter()
+                .filter(|(id, popup)| {
+                    popup.committed
+                        && !signalled.contains(*id)
+                        && signalled.contains(&popup.parent_surface_id)
+                })
+                .map(|(id, _)| *id)
+                .collect();
+            if ready.is_empty() {
+                break;
+            }
+            for id in ready {
+                signal(self.popups[&id].surface.wl_surface());
+                signalled.insert(id);
+            }
+        }
+
+        for layer_surface in self.layer_shell_state.layer_surfaces() {
+            signal(layer_surface.wl_surface());
+        }
+        if self.session_locked {
+            for lock_surface in &self.lock_surfaces {
+                signal(lock_surface.wl_surface());
+            }
+        }
+        for popup in &self.ime_popups {
+            signal(popup.wl_surface());
+        }
+        if let Some(cursor) = self.cursor_surface.as_ref().filter(|s| s.is_alive()) {
+            signal(cursor);
+        }
+        if self.dnd_active {
+            if let Some(icon) = &self.dnd_icon {
+                signal(icon);
+            }
+        }
+    }
+
+    /// Whether the scene has anything to draw besides the background:
+    /// windows, popups, layer or lock surfaces, or a client-drawn cursor.
+    pub(super) fn has_scene_content(&self) -> bool {
+        !self.surfaces.is_empty()
+            || !self.popups.is_empty()
+            || self.layer_shell_state.layer_surfaces().next().is_some()
+            || self.session_locked
+            || self.cursor_surface.is_some()
+            || (self.dnd_active && self.dnd_icon.is_some())
+    }
+
+    /// Re-check the window and workspace managers after a request handler
+    /// panicked, which may have left either half-updated. Workspace entries
+    /// for windows the window manager no longer has are dropped. Returns
+    /// how many things were repaired.
+    pub(super) fn restore_invariants_after_panic(&mut self) -> usize {
+        let mut wm = self.window_manager.write();
+        let mut repaired = wm.restore_invariants();
+        let windows = wm.window_ids();
+        drop(wm);
+        repaired += self
+            .workspace_manager
+            .write()
+            .restore_invariants(|id| windows.binary_search(&id).is_ok());
+        if repaired > 0 {
+            warn!("🛡️ Repaired {} entries after a handler panic", repaired);
+        }
+        self.needs_redraw = true;
+        repaired
+    }
+
     /// Prune surfaces and toplevels whose WlSurface is no longer alive
     /// (e.g. the Wayland client disconnected). Returns count of cleaned entries.
     pub fn prune_dead_surfaces(&mut self) -> usize {
@@ -609,6 +1420,15 @@ impl State {
             self.destroy_window(surface_id);
         }
 
+        // A destroyed cursor surface must not keep the host cursor hidden.
+        if self
+            .cursor_surface
+            .as_ref()
+            .is_some_and(|s| !s.is_alive())
+        {
+            self.reset_cursor();
+        }
+
         if count > 0 {
             info!(
                 "🧹 Pruned {} dead surfaces from disconnected clients",
@@ -635,6 +1455,7 @@ impl BufferHandler for State {
         // Without this the GlesTexture (Arc<GlesTextureInternal>) keeps the GL
         // texture alive forever, leaking it when clients cycle through buffers.
         self.texture_cache.pop_entry(&buffer.id());
+        self.texture_limits.forget(&buffer.id());
     }
 }
 
@@ -661,7 +1482,8 @@ impl CompositorHandler for State {
 
     fn commit(&mut self, surface: &WlSurface) {
         on_commit_buffer_handler::<Self>(surface);
-        self.needs_redraw = true;
+        // No `needs_redraw` here: the damage pushed below is what makes the
+        // presenter draw, so commits that damage nothing on screen are free.
 
         let surface_id = surface.id().protocol_id();
 
@@ -675,22 +1497,111 @@ impl CompositorHandler for State {
 
         // Size is now updated from imported textures in render_scene_into (fix #19).
 
+        // A new buffer scale or transform on the same buffer needs a fresh
+        // texture, or the old one keeps drawing at the old size.
+        if let Some(buffer) = super::viewport::commit_buffer_layout(surface) {
+            self.texture_cache.pop_entry(&buffer);
+        }
+
+        // Toplevels may change their min/max size hints on any commit.
+        if self.toplevels.contains_key(&surface_id) {
+            self.update_toplevel_size_hints(surface);
+            self.update_opaque_region(surface);
+            if self.tearing_control_state.is_some() {
+                self.update_tearing_hint(surface);
+            }
+        }
+
+        // Layer surfaces may change keyboard_interactivity on any commit.
+        let is_layer = self
+            .layer_shell_state
+            .layer_surfaces()
+            .any(|l| l.wl_surface() == surface);
+        if is_layer {
+            self.update_layer_keyboard_focus(surface);
+            self.update_reserved_insets(None);
+        }
+
+        // Animated client cursors commit new frames for the plane to show
+        if self.cursor_surface.as_ref() == Some(surface) {
+            self.update_cursor_plane();
+        }
+
         // Increment commit counter for this surface
         *self.surface_commit_counters.entry(surface_id).or_insert(0) += 1;
 
-        // Track damaged screen region for this surface
-        let rect = self
-            .surface_previous_rects
-            .get(&surface_id)
-            .copied()
-            .unwrap_or(Rectangle::new(
+        // Track damaged screen region for this surface. Once we know where the
+        // surface sits, only the regions the client reported are damaged,
+        // clamped to the surface so bogus rects can't spill onto the screen.
+        let Some(rect) = self.surface_previous_rects.get(&surface_id).copied() else {
+            self.output_damage.push(Rectangle::new(
                 Point::from((0, 0)),
                 Size::from((self.window_width as i32, self.window_height as i32)),
             ));
-        self.output_damage.push(rect);
+            return;
+        };
+        let scale = self.focused_output_scale();
+        let (damage, buffer_scale) = with_states(surface, |states| {
+            let mut attrs = states.cached_state.get::<SurfaceAttributes>();
+            let attrs = attrs.current();
+            (attrs.damage.clone(), attrs.buffer_scale.max(1))
+        });
+        let window_id = self.window_id_for_surface(surface);
+        // Pixels the renderer will upload, for the window's stats
+        let mut uploaded = 0u64;
+        if damage.is_empty() {
+            uploaded = rect.size.w as u64 * rect.size.h as u64;
+            self.push_surface_damage(window_id, rect);
+        }
+        for d in damage {
+            let (x, y, w, h, factor) = match d {
+                Damage::Surface(r) => (r.loc.x, r.loc.y, r.size.w, r.size.h, scale),
+                Damage::Buffer(r) => (
+                    r.loc.x,
+                    r.loc.y,
+                    r.size.w,
+                    r.size.h,
+                    scale / buffer_scale as f64,
+                ),
+            };
+            let local = Rectangle::new(
+                Point::from(((x as f64 * factor) as i32, (y as f64 * factor) as i32)),
+                Size::from((
+                    (w as f64 * factor).ceil() as i32,
+                    (h as f64 * factor).ceil() as i32,
+                )),
+            );
+            if let Some(clamped) = clamp_surface_damage(local, rect.size) {
+                uploaded += clamped.size.w as u64 * clamped.size.h as u64;
+                self.push_surface_damage(
+                    window_id,
+                    Rectangle::new(rect.loc + clamped.loc, clamped.size),
+                );
+            }
+        }
+        if let Some(window_id) = window_id {
+            self.window_stats
+                .record_commit(window_id, Instant::now(), uploaded * 4);
+        }
     }
 }
 
+/// Clamp a surface-local damage rect to the surface's `bounds`.
+///
+/// Returns `None` for rects that are empty or entirely outside the surface,
+/// so a misbehaving client's damage never reaches beyond its own buffer.
+pub(super) fn clamp_surface_damage(
+    damage: Rectangle<i32, Physical>,
+    bounds: Size<i32, Physical>,
+) -> Option<Rectangle<i32, Physical>> {
+    let x0 = damage.loc.x.max(0);
+    let y0 = damage.loc.y.max(0);
+    let x1 = damage.loc.x.saturating_add(damage.size.w).min(bounds.w);
+    let y1 = damage.loc.y.saturating_add(damage.size.h).min(bounds.h);
+    (x1 > x0 && y1 > y0)
+        .then(|| Rectangle::new(Point::from((x0, y0)), Size::from((x1 - x0, y1 - y0))))
+}
+
 impl ShmHandler for State {
     fn shm_state(&self) -> &ShmState {
         &self.shm_state
@@ -718,14 +1629,45 @@ impl SeatHandler for State {
         }
         if let Some(window_id) = focused_window_id {
             debug!("🎯 Wayland focus changed to window {}", window_id);
+            if self.workspace_manager.write().reveal_window(window_id) {
+                self.needs_redraw = true;
+            }
         }
     }
 
     fn cursor_image(&mut self, _seat: &Seat<Self>, image: CursorImageStatus) {
+        // The drag cursor stays in place until the drop; clients under the
+        // pointer must not replace it mid-drag.
+        if self.dnd_active {
+            return;
+        }
         match image {
-            CursorImageStatus::Named(icon) => self.cursor_icon = Some(icon),
-            _ => self.cursor_icon = None,
+            CursorImageStatus::Named(icon) => {
+                self.cursor_surface = None;
+                self.cursor_hotspot = (0, 0);
+                self.cursor_icon = Some(icon);
+            }
+            CursorImageStatus::Surface(surface) => {
+                let hotspot = with_states(&surface, |states| {
+                    states
+                        .data_map
+                        .get::<CursorImageSurfaceData>()
+                        .and_then(|data| data.lock().ok().map(|attrs| attrs.hotspot))
+                        .map(|h| (h.x, h.y))
+                        .unwrap_or((0, 0))
+                });
+                self.cursor_hotspot = hotspot;
+                self.cursor_surface = Some(surface);
+                self.cursor_icon = None;
+            }
+            CursorImageStatus::Hidden => {
+                self.cursor_surface = None;
+                self.cursor_hotspot = (0, 0);
+                self.cursor_icon = None;
+            }
         }
+        self.update_cursor_plane();
+        self.needs_redraw = true;
     }
 }
 
@@ -744,6 +1686,27 @@ impl XdgShellHandler for State {
         let wl_surface = surface.wl_surface().clone();
         let surface_id = wl_surface.id().protocol_id();
 
+        match self.window_overflow() {
+            WindowOverflow::Admit => {}
+            WindowOverflow::Close(window_id) => {
+                info!(
+                    "🪟 window.max_windows reached, closing window {} to make room",
+                    window_id
+                );
+                self.request_window_close(window_id);
+            }
+            WindowOverflow::Reject => {
+                warn!(
+                    "🪟 window.max_windows reached, rejecting toplevel {}",
+                    surface_id
+                );
+                if let (Some(client), Some(dh)) = (wl_surface.client(), &self.display_handle) {
+                    post_implementation_error(dh, client.id(), "too many windows open");
+                }
+                return;
+            }
+        }
+
         // Activate the surface
         surface.with_pending_state(|state| {
             state.states.set(xdg_toplevel::State::Activated);
@@ -805,6 +1768,60 @@ impl XdgShellHandler for State {
         }
     }
 
+    fn maximize_request(&mut self, toplevel: ToplevelSurface) {
+        if let Some(window_id) = self.window_id_for_surface(toplevel.wl_surface()) {
+            let maximized = self
+                .window_manager
+                .read()
+                .get_window(window_id)
+                .is_some_and(|w| w.properties.maximized);
+            if !maximized {
+                self.toggle_maximize_window(window_id);
+            }
+        }
+    }
+
+    fn unmaximize_request(&mut self, toplevel: ToplevelSurface) {
+        if let Some(window_id) = self.window_id_for_surface(toplevel.wl_surface()) {
+            let maximized = self
+                .window_manager
+                .read()
+                .get_window(window_id)
+                .is_some_and(|w| w.properties.maximized);
+            if maximized {
+                self.toggle_maximize_window(window_id);
+            }
+        }
+    }
+
+    fn parent_changed(&mut self, toplevel: ToplevelSurface) {
+        let Some(window_id) = self.window_id_for_surface(toplevel.wl_surface()) else {
+            return;
+        };
+        let parent_id = toplevel
+            .parent()
+            .and_then(|parent| self.window_id_for_surface(&parent));
+
+        let area = crate::window::Rectangle::from_loc_and_size(
+            (0, 0),
+            (self.window_width, self.window_height),
+        );
+        let mut wm = self.window_manager.write();
+        wm.set_window_parent(window_id, parent_id);
+        if parent_id.is_some() {
+            // Transient toplevels are dialogs: float them over their parent
+            wm.set_window_type(window_id, crate::window::WindowType::Dialog);
+            let _ = wm.place_floating_window(window_id, area);
+            drop(wm);
+            self.workspace_manager
+                .write()
+                .set_window_floating(window_id, true);
+        } else {
+            drop(wm);
+        }
+        self.needs_redraw = true;
+    }
+
     fn new_popup(&mut self, surface: PopupSurface, positioner: PositionerState) {
         let surface_id = surface.wl_surface().id().protocol_id();
         let parent_id = surface
@@ -812,8 +1829,8 @@ impl XdgShellHandler for State {
             .map(|s| s.id().protocol_id())
             .unwrap_or(0);
 
-        // Compute popup geometry from the positioner relative to parent
-        let rect = positioner.get_geometry();
+        // Geometry relative to the parent, kept on the parent's output
+        let rect = self.place_popup(parent_id, &positioner);
 
         surface.with_pending_state(|state| {
             state.geometry = rect;
@@ -881,9 +1898,11 @@ impl XdgShellHandler for State {
     }
 
     fn grab(&mut self, surface: PopupSurface, _seat: wl_seat::WlSeat, _serial: Serial) {
-        let surface_id = surface.wl_surface().id().protocol_id();
-        info!("🤚 Popup grab activated for surface {}", surface_id);
-        self.active_popup_grab = Some(surface_id);
+        self.start_popup_grab(&surface);
+    }
+
+    fn popup_destroyed(&mut self, surface: PopupSurface) {
+        self.forget_popup(surface.wl_surface().id().protocol_id());
     }
 
     fn reposition_request(
@@ -893,7 +1912,12 @@ impl XdgShellHandler for State {
         token: u32,
     ) {
         let surface_id = surface.wl_surface().id().protocol_id();
-        let rect = positioner.get_geometry();
+        let parent_id = self
+            .popups
+            .get(&surface_id)
+            .map(|p| p.parent_surface_id)
+            .unwrap_or(0);
+        let rect = self.place_popup(parent_id, &positioner);
 
         if let Some(popup) = self.popups.get_mut(&surface_id) {
             popup.x = rect.loc.x;
@@ -991,11 +2015,20 @@ impl DataDeviceHandler for State {
 impl ClientDndGrabHandler for State {
     fn started(
         &mut self,
-        _source: Option<WlDataSource>,
+        source: Option<WlDataSource>,
         icon: Option<WlSurface>,
         _seat: Seat<Self>,
     ) {
         debug!("🖐️ Client-initiated drag-and-drop started");
+        // Swap to the drag cursor before marking the session active so the
+        // icon composes under a themed cursor rather than the source's image.
+        // The grab answers the source's action negotiation itself, so the
+        // cursor follows the actions the source offers.
+        let action = source
+            .as_ref()
+            .and_then(|source| with_source_metadata(source, |meta| meta.dnd_action).ok())
+            .unwrap_or(DndAction::Move);
+        self.set_dnd_cursor(action);
         self.dnd_active = true;
         self.dnd_icon = icon;
         // If there's an icon surface, register it so commits are picked up
@@ -1023,11 +2056,26 @@ impl ClientDndGrabHandler for State {
             self.surfaces.remove(&id);
         }
         self.dnd_icon = None;
-        self.needs_redraw = true;
+        self.reset_cursor();
     }
 }
 
 impl ServerDndGrabHandler for State {
+    fn action(&mut self, action: DndAction, _seat: Seat<Self>) {
+        // The target picked (or changed) the action for our drag
+        if !action.is_empty() {
+            self.set_dnd_cursor(action);
+        }
+    }
+
+    fn dropped(&mut self, _seat: Seat<Self>) {
+        self.reset_cursor();
+    }
+
+    fn cancelled(&mut self, _seat: Seat<Self>) {
+        self.reset_cursor();
+    }
+
     fn send(&mut self, mime_type: String, fd: OwnedFd, _seat: Seat<Self>) {
         // ponytail: This path is reachable only if a server-initiated DnD
         // (start_dnd) is triggered — currently unreachable, but the handler
@@ -1153,8 +2201,14 @@ impl WlrLayerShellHandler for State {
         self.needs_redraw = true;
     }
 
-    fn layer_destroyed(&mut self, _surface: LayerSurface) {
+    fn layer_destroyed(&mut self, surface: LayerSurface) {
         debug!("📋 Layer surface destroyed");
+        self.update_reserved_insets(Some(surface.wl_surface()));
+        let change = self
+            .input_manager
+            .write()
+            .remove_layer(surface.wl_surface().id().protocol_id());
+        self.apply_layer_focus_change(change);
         self.needs_redraw = true;
     }
 }
@@ -1208,6 +2262,11 @@ smithay::delegate_fractional_scale!(State);
 smithay::delegate_xdg_decoration!(State);
 smithay::delegate_output!(State);
 delegate_session_lock!(State);
+smithay::delegate_tearing_control!(State);
+smithay::delegate_presentation!(State);
+smithay::delegate_viewporter!(State);
+smithay::delegate_text_input_manager!(State);
+smithay::delegate_input_method_manager!(State);
 
 #[cfg(test)]
 mod tests {
@@ -1248,6 +2307,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cursor_draw_position_subtracts_hotspot() {
+        assert_eq!(State::cursor_draw_position((100.0, 50.0), (4, 6)), (96, 44));
+        assert_eq!(State::cursor_draw_position((2.4, 2.6), (0, 0)), (2, 3));
+        // Hotspots larger than the pointer position go off-screen rather than clamp
+        assert_eq!(State::cursor_draw_position((1.0, 1.0), (8, 8)), (-7, -7));
+    }
+
+    #[test]
+    fn test_dnd_cursor_icon_matches_action() {
+        assert_eq!(State::dnd_cursor_icon(DndAction::Copy), CursorIcon::Copy);
+        assert_eq!(State::dnd_cursor_icon(DndAction::Move), CursorIcon::Move);
+        assert_eq!(
+            State::dnd_cursor_icon(DndAction::Copy | DndAction::Move),
+            CursorIcon::Move
+        );
+    }
+
     #[test]
     fn test_preferred_text_mime_type_prefers_utf8_plain_text() {
         let mime = State::preferred_text_mime_type(&[
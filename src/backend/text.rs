@@ -0,0 +1,305 @@
+//! Text drawing with a built-in 3x5 bitmap font.
+//!
+//! The font is rasterised once into a texture atlas of white glyphs on a
+//! transparent background. A string is drawn as one textured quad per
+//! glyph, each sampling its cell of the atlas, tinted to the wanted colour
+//! through the colour-matrix shader of [`ColorPass`]. Only upper case
+//! letters, digits and common punctuation have glyphs; lower case is drawn
+//! in upper case and anything else as `?`.
+
+use anyhow::Result;
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::gles::{GlesFrame, GlesRenderer, GlesTexProgram, GlesTexture};
+use smithay::backend::renderer::ImportMem;
+use smithay::utils::{Buffer, Physical, Point, Rectangle, Size, Transform};
+
+use super::color::{matrix_uniform, ColorPass};
+
+/// Every character with a glyph, in atlas order.
+const CHARSET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789.,:;-+=_%/()[]!?'\"#*<>";
+/// Glyph size in font pixels.
+pub(super) const GLYPH_WIDTH: i32 = 3;
+pub(super) const GLYPH_HEIGHT: i32 = 5;
+/// Distance between the starts of neighbouring glyphs and lines, in font
+/// pixels.
+pub(super) const ADVANCE: i32 = 4;
+pub(super) const LINE_HEIGHT: i32 = 7;
+/// Atlas pixels per font pixel. Glyphs are drawn with the renderer's
+/// (linear) filter, so a finer atlas keeps their edges sharp when scaled.
+const ATLAS_SCALE: i32 = 4;
+/// Glyphs per atlas row, and the size of each glyph's cell in font pixels:
+/// the glyph plus a transparent gutter so sampling doesn't bleed into the
+/// neighbours.
+const ATLAS_COLUMNS: i32 = 16;
+const CELL: (i32, i32) = (GLYPH_WIDTH + 1, GLYPH_HEIGHT + 1);
+
+/// One glyph to draw: the part of the atlas to sample and where it goes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct GlyphQuad {
+    pub(super) src: Rectangle<f64, Buffer>,
+    pub(super) dst: Rectangle<i32, Physical>,
+}
+
+/// Pixel size of the atlas texture.
+fn atlas_size() -> (i32, i32) {
+    let count = CHARSET.chars().count() as i32;
+    let rows = (count + ATLAS_COLUMNS - 1) / ATLAS_COLUMNS;
+    (
+        ATLAS_COLUMNS * CELL.0 * ATLAS_SCALE,
+        rows * CELL.1 * ATLAS_SCALE,
+    )
+}
+
+/// The atlas cell of `c`, or `None` for a character drawn as a gap.
+fn atlas_cell(c: char) -> Option<Rectangle<f64, Buffer>> {
+    if c.is_whitespace() {
+        return None;
+    }
+    let c = c.to_ascii_uppercase();
+    let index = CHARSET
+        .chars()
+        .position(|g| g == c)
+        .or_else(|| CHARSET.chars().position(|g| g == '?'))? as i32;
+    let (column, row) = (index % ATLAS_COLUMNS, index / ATLAS_COLUMNS);
+    Some(Rectangle::new(
+        Point::from((
+            (column * CELL.0 * ATLAS_SCALE) as f64,
+            (row * CELL.1 * ATLAS_SCALE) as f64,
+        )),
+        Size::from((
+            (GLYPH_WIDTH * ATLAS_SCALE) as f64,
+            (GLYPH_HEIGHT * ATLAS_SCALE) as f64,
+        )),
+    ))
+}
+
+/// The quads that draw `text` with its top-left corner at `(x, y)`, at
+/// `scale` screen pixels per font pixel. A newline starts a new line.
+pub(super) fn layout_text(x: i32, y: i32, text: &str, scale: i32) -> Vec<GlyphQuad> {
+    let mut quads = Vec::new();
+    for (row, line) in text.lines().enumerate() {
+        let line_y = y + row as i32 * LINE_HEIGHT * scale;
+        for (column, c) in line.chars().enumerate() {
+            let Some(src) = atlas_cell(c) else {
+                continue;
+            };
+            quads.push(GlyphQuad {
+                src,
+                dst: Rectangle::new(
+                    Point::from((x + column as i32 * ADVANCE * scale, line_y)),
+                    Size::from((GLYPH_WIDTH * scale, GLYPH_HEIGHT * scale)),
+                ),
+            });
+        }
+    }
+    quads
+}
+
+/// The font rasterised as premultiplied RGBA, white where a glyph is lit.
+fn atlas_pixels() -> Vec<u8> {
+    let (width, height) = atlas_size();
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    for c in CHARSET.chars() {
+        let Some(cell) = atlas_cell(c) else {
+            continue;
+        };
+        let (left, top) = (cell.loc.x as i32, cell.loc.y as i32);
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for dy in 0..ATLAS_SCALE {
+                    let y = top + row as i32 * ATLAS_SCALE + dy;
+                    let x = left + column * ATLAS_SCALE;
+                    let start = ((y * width + x) * 4) as usize;
+                    pixels[start..start + (ATLAS_SCALE * 4) as usize].fill(0xff);
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// GL resources for drawing text, created the first frame that needs them.
+#[derive(Debug, Default)]
+pub(super) struct TextRenderer {
+    atlas: Option<GlesTexture>,
+    program: Option<GlesTexProgram>,
+}
+
+impl TextRenderer {
+    /// Upload the atlas and fetch the tinting shader, if not done yet.
+    pub(super) fn prepare(
+        &mut self,
+        renderer: &mut GlesRenderer,
+        color_pass: &mut ColorPass,
+    ) -> Result<()> {
+        if self.program.is_none() {
+            self.program = Some(color_pass.program(renderer)?);
+        }
+        if self.atlas.is_none() {
+            let texture = renderer
+                .import_memory(
+                    &atlas_pixels(),
+                    Fourcc::Abgr8888,
+                    Size::from(atlas_size()),
+                    false,
+                )
+                .map_err(|e| anyhow::anyhow!("failed to upload glyph atlas: {:?}", e))?;
+            self.atlas = Some(texture);
+        }
+        Ok(())
+    }
+
+    /// Draw `text` at `(x, y)` in `color`, `scale` screen pixels per font
+    /// pixel. Draws nothing before [`Self::prepare`]. Returns the number of
+    /// quads drawn.
+    pub(super) fn draw_text(
+        &self,
+        frame: &mut GlesFrame<'_, '_>,
+        (x, y): (i32, i32),
+        text: &str,
+        color: [f32; 4],
+        scale: i32,
+    ) -> Result<u32> {
+        let (Some(atlas), Some(program)) = (&self.atlas, &self.program) else {
+            return Ok(0);
+        };
+        // Every lit texel is white, so a matrix of offsets alone paints it
+        // in `color`
+        let tint = matrix_uniform([
+            [0.0, 0.0, 0.0, color[0]],
+            [0.0, 0.0, 0.0, color[1]],
+            [0.0, 0.0, 0.0, color[2]],
+        ]);
+        let quads = layout_text(x, y, text, scale);
+        for quad in &quads {
+            // Damage is relative to the quad
+            frame.render_texture_from_to(
+                atlas,
+                quad.src,
+                quad.dst,
+                &[Rectangle::from_size(quad.dst.size)],
+                &[],
+                Transform::Normal,
+                color[3],
+                Some(program),
+                std::slice::from_ref(&tint),
+            )?;
+        }
+        Ok(quads.len() as u32)
+    }
+}
+
+/// 3x5 bitmap of `c`, a row per byte with the leftmost pixel in bit 2.
+/// Characters outside [`CHARSET`] are blank.
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b111, 0b100, 0b101, 0b101, 0b111],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        ';' => [0b000, 0b010, 0b000, 0b010, 0b100],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b010, 0b100, 0b100, 0b100, 0b010],
+        ')' => [0b010, 0b001, 0b001, 0b001, 0b010],
+        '[' => [0b110, 0b100, 0b100, 0b100, 0b110],
+        ']' => [0b011, 0b001, 0b001, 0b001, 0b011],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b111, 0b001, 0b010, 0b000, 0b010],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '"' => [0b101, 0b101, 0b000, 0b000, 0b000],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '*' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        _ => [0; 5],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_glyph_is_one_quad_with_its_own_cell() {
+        let quads = layout_text(10, 20, "AB", 2);
+        assert_eq!(quads.len(), 2);
+        assert_ne!(quads[0].src, quads[1].src);
+        assert_eq!(
+            quads[0].dst,
+            Rectangle::new((10, 20).into(), (6, 10).into())
+        );
+        assert_eq!(quads[1].dst.loc, Point::from((18, 20)));
+    }
+
+    #[test]
+    fn test_layout_maps_case_gaps_and_lines() {
+        // Lower case shares the upper case glyph; spaces take room but no quad
+        let quads = layout_text(0, 0, "a A\nz", 1);
+        assert_eq!(quads.len(), 3);
+        assert_eq!(quads[0].src, quads[1].src);
+        assert_eq!(quads[1].dst.loc, Point::from((2 * ADVANCE, 0)));
+        assert_eq!(quads[2].dst.loc, Point::from((0, LINE_HEIGHT)));
+        // Characters without a glyph fall back to '?'
+        assert_eq!(layout_text(0, 0, "~", 1)[0].src, atlas_cell('?').unwrap());
+    }
+
+    #[test]
+    fn test_atlas_cells_lie_inside_the_atlas() {
+        let (width, height) = atlas_size();
+        for c in CHARSET.chars() {
+            let cell = atlas_cell(c).unwrap();
+            assert!(cell.loc.x + cell.size.w <= width as f64);
+            assert!(cell.loc.y + cell.size.h <= height as f64);
+            assert_ne!(glyph(c), [0; 5], "{} has no bitmap", c);
+        }
+        // The top-left pixel of 'A' is unlit, the one right of it lit
+        let pixels = atlas_pixels();
+        let a = atlas_cell('A').unwrap();
+        let texel = |x: i32, y: i32| pixels[((y * width + x) * 4) as usize];
+        assert_eq!(texel(a.loc.x as i32, a.loc.y as i32), 0);
+        assert_eq!(texel(a.loc.x as i32 + ATLAS_SCALE, a.loc.y as i32), 0xff);
+    }
+}
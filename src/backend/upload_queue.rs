@@ -0,0 +1,152 @@
+//! Per-frame budget for client buffer uploads.
+//!
+//! Importing a shm buffer copies it to the GPU inside the frame that first
+//! draws it. When many clients commit large buffers at once, importing them
+//! all can blow the frame budget. Each frame, windows with a buffer not yet
+//! uploaded ask for one, and are admitted most important first until
+//! `performance.upload_budget_mb` is spent; the rest wait for a later frame.
+//! A waiting window keeps showing the buffer it was last drawn with and gets
+//! no frame callbacks, so its client can't commit faster than it is
+//! uploaded.
+
+use std::collections::{HashMap, HashSet};
+
+use wayland_server::backend::ObjectId;
+
+/// Frames a request may wait before it goes ahead of everything else, so
+/// offscreen windows still catch up under constant load.
+const MAX_WAIT_FRAMES: u32 = 8;
+
+/// How much an upload matters to what is on screen, least first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum UploadPriority {
+    /// Not on any output
+    Offscreen,
+    /// On an output
+    Visible,
+    /// The focused window
+    Focused,
+}
+
+/// A window that committed buffers not yet on the GPU.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct UploadRequest {
+    pub(super) window_id: u64,
+    pub(super) priority: UploadPriority,
+    /// Bytes its surface tree's new buffers take to copy
+    pub(super) bytes: u64,
+}
+
+/// Uploads held back by earlier frames, and what each surface last drew.
+#[derive(Debug, Default)]
+pub(super) struct UploadQueue {
+    /// Frames each deferred window has waited
+    waiting: HashMap<u64, u32>,
+    /// Buffer each surface was last drawn with, drawn again while its new
+    /// one waits
+    shown: HashMap<ObjectId, ObjectId>,
+}
+
+impl UploadQueue {
+    /// Admit `requests` within `budget` bytes, most important first, and
+    /// return the windows that must wait. The first request is always
+    /// admitted, so a buffer larger than the whole budget still gets
+    /// through. A budget of 0 admits everything.
+    pub(super) fn schedule(
+        &mut self,
+        mut requests: Vec<UploadRequest>,
+        budget: u64,
+    ) -> HashSet<u64> {
+        let waited = |id: u64| self.waiting.get(&id).copied().unwrap_or(0);
+        requests.sort_by_key(|r| {
+            let starved = waited(r.window_id) >= MAX_WAIT_FRAMES;
+            std::cmp::Reverse((starved, r.priority, waited(r.window_id)))
+        });
+        let mut spent = 0u64;
+        let mut deferred = HashSet::new();
+        for request in &requests {
+            if budget == 0 || spent == 0 || spent + request.bytes <= budget {
+                spent += request.bytes;
+            } else {
+                deferred.insert(request.window_id);
+            }
+        }
+        self.waiting.retain(|id, _| deferred.contains(id));
+        for &id in &deferred {
+            *self.waiting.entry(id).or_default() += 1;
+        }
+        deferred
+    }
+
+    /// The buffer `surface` was last drawn with.
+    pub(super) fn shown_buffer(&self, surface: &ObjectId) -> Option<&ObjectId> {
+        self.shown.get(surface)
+    }
+
+    /// `surface` was just drawn with `buffer`.
+    pub(super) fn set_shown(&mut self, surface: ObjectId, buffer: ObjectId) {
+        self.shown.insert(surface, buffer);
+    }
+
+    /// Forget a destroyed surface.
+    pub(super) fn forget_surface(&mut self, surface: &ObjectId) {
+        self.shown.remove(surface);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(window_id: u64, priority: UploadPriority) -> UploadRequest {
+        UploadRequest {
+            window_id,
+            priority,
+            bytes: 8 * 1024 * 1024,
+        }
+    }
+
+    #[test]
+    fn test_small_budget_uploads_visible_windows_first() {
+        let mut queue = UploadQueue::default();
+        let budget = 16 * 1024 * 1024;
+        // Room for two of the three: the offscreen window waits
+        let requests = vec![
+            request(1, UploadPriority::Offscreen),
+            request(2, UploadPriority::Visible),
+            request(3, UploadPriority::Focused),
+        ];
+        let deferred = queue.schedule(requests, budget);
+        assert_eq!(deferred, HashSet::from([1]));
+
+        // Next frame the focused window commits again and goes first, but
+        // the offscreen one still fits behind it
+        let requests = vec![
+            request(1, UploadPriority::Offscreen),
+            request(3, UploadPriority::Focused),
+        ];
+        assert!(queue.schedule(requests, budget).is_empty());
+
+        // Under constant load the offscreen window waits, but not forever
+        let busy = || {
+            vec![
+                request(1, UploadPriority::Offscreen),
+                request(2, UploadPriority::Visible),
+                request(3, UploadPriority::Focused),
+            ]
+        };
+        for _ in 0..MAX_WAIT_FRAMES {
+            assert!(queue.schedule(busy(), budget).contains(&1));
+        }
+        assert!(!queue.schedule(busy(), budget).contains(&1));
+
+        // A buffer over the whole budget still goes through on its own
+        let huge = UploadRequest {
+            bytes: 4 * budget,
+            ..request(4, UploadPriority::Visible)
+        };
+        assert!(queue.schedule(vec![huge], budget).is_empty());
+        // And a budget of 0 never defers
+        assert!(queue.schedule(busy(), 0).is_empty());
+    }
+}
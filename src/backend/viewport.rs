@@ -0,0 +1,204 @@
+//! How a client buffer maps onto its surface.
+//!
+//! `wl_surface.set_buffer_scale` and `set_buffer_transform` say how the
+//! buffer was drawn: at a multiple of the surface size for HiDPI, and
+//! rotated or flipped to match an output the client expects to be
+//! transformed. Undoing both gives the surface-sized image a viewport then
+//! applies to.
+//!
+//! A `wp_viewporter` viewport lets a client show a sub-rectangle of that
+//! image (the source rect) stretched to an arbitrary surface size (the
+//! destination), so video players and games can hand us oversized or
+//! undersized buffers without rescaling them on the CPU.
+
+use std::cell::Cell;
+
+use smithay::backend::renderer::element::texture::TextureBuffer;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
+use smithay::utils::{Buffer, Logical, Rectangle, Size, Transform};
+use smithay::wayland::compositor::{with_states, BufferAssignment, SurfaceAttributes};
+use smithay::wayland::viewporter::ViewportCachedState;
+use wayland_server::backend::ObjectId;
+use wayland_server::protocol::wl_output;
+use wayland_server::protocol::wl_surface::WlSurface;
+use wayland_server::Resource;
+
+/// A surface's buffer scale and transform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct BufferLayout {
+    pub(super) scale: i32,
+    pub(super) transform: Transform,
+}
+
+impl Default for BufferLayout {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            transform: Transform::Normal,
+        }
+    }
+}
+
+impl BufferLayout {
+    pub(super) fn new(scale: i32, transform: wl_output::Transform) -> Self {
+        Self {
+            scale: scale.max(1),
+            transform: transform.into(),
+        }
+    }
+
+    /// The layout `surface` last committed.
+    pub(super) fn of(surface: &WlSurface) -> Self {
+        with_states(surface, |states| {
+            let mut attrs = states.cached_state.get::<SurfaceAttributes>();
+            let attrs = attrs.current();
+            Self::new(attrs.buffer_scale, attrs.buffer_transform)
+        })
+    }
+
+    /// The surface area a `buffer`-sized buffer covers.
+    pub(super) fn surface_size(&self, buffer: Size<i32, Buffer>) -> Size<i32, Logical> {
+        buffer.to_logical(self.scale, self.transform)
+    }
+
+    /// Wrap a texture imported from the surface's buffer so it is drawn at
+    /// surface size and the right way up.
+    pub(super) fn texture_buffer(
+        &self,
+        renderer: &GlesRenderer,
+        texture: GlesTexture,
+    ) -> TextureBuffer<GlesTexture> {
+        TextureBuffer::from_texture(renderer, texture, self.scale, self.transform, None)
+    }
+}
+
+/// Note the buffer layout `surface` just committed. If it changed, returns
+/// the surface's current buffer: a texture made from that buffer under the
+/// old layout would now be drawn at the wrong size or orientation.
+pub(super) fn commit_buffer_layout(surface: &WlSurface) -> Option<ObjectId> {
+    with_states(surface, |states| {
+        let mut attrs = states.cached_state.get::<SurfaceAttributes>();
+        let attrs = attrs.current();
+        let layout = BufferLayout::new(attrs.buffer_scale, attrs.buffer_transform);
+        states
+            .data_map
+            .insert_if_missing(|| Cell::new(BufferLayout::default()));
+        let last = states.data_map.get::<Cell<BufferLayout>>()?;
+        if last.replace(layout) == layout {
+            return None;
+        }
+        match &attrs.buffer {
+            Some(BufferAssignment::NewBuffer(buffer)) => Some(buffer.id()),
+            _ => None,
+        }
+    })
+}
+
+/// The viewport a client attached to `surface`, if it set one.
+pub(super) fn surface_viewport(
+    surface: &WlSurface,
+) -> Option<(Option<Rectangle<f64, Logical>>, Option<Size<i32, Logical>>)> {
+    with_states(surface, |states| {
+        let mut cached = states.cached_state.get::<ViewportCachedState>();
+        let viewport = cached.current();
+        (viewport.src.is_some() || viewport.size.is_some()).then_some((viewport.src, viewport.size))
+    })
+}
+
+/// Resolve a viewport against the buffer it applies to.
+///
+/// Returns the source rect to sample and the size to draw it at. A missing
+/// source means the whole buffer; a missing destination means the source
+/// size. Returns `None` when the source rect reaches outside the buffer,
+/// which the protocol treats as a client error, so nothing is drawn.
+pub(super) fn resolve_viewport(
+    buffer: Size<i32, Logical>,
+    src: Option<Rectangle<f64, Logical>>,
+    dst: Option<Size<i32, Logical>>,
+) -> Option<(Rectangle<f64, Logical>, Size<i32, Logical>)> {
+    let full = Rectangle::from_size(buffer.to_f64());
+    let src = src.unwrap_or(full);
+    if src.loc.x < 0.0
+        || src.loc.y < 0.0
+        || src.loc.x + src.size.w > full.size.w
+        || src.loc.y + src.size.h > full.size.h
+    {
+        return None;
+    }
+    let dst = dst.unwrap_or_else(|| src.size.to_i32_round());
+    Some((src, dst))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smithay::utils::Point;
+
+    /// Normalised texture coordinates `(u0, v0, u1, v1)` sampled for `src`.
+    fn uv_range(src: Rectangle<f64, Logical>, buffer: Size<i32, Logical>) -> (f64, f64, f64, f64) {
+        let (w, h) = (buffer.w as f64, buffer.h as f64);
+        (
+            src.loc.x / w,
+            src.loc.y / h,
+            (src.loc.x + src.size.w) / w,
+            (src.loc.y + src.size.h) / h,
+        )
+    }
+
+    #[test]
+    fn test_top_left_quarter_viewport_samples_quarter_uv_range() {
+        let buffer = Size::from((1920, 1080));
+        let crop = Rectangle::new((0.0, 0.0).into(), (960.0, 540.0).into());
+        let (src, dst) = resolve_viewport(buffer, Some(crop), Some(Size::from((400, 300)))).unwrap();
+        assert_eq!(uv_range(src, buffer), (0.0, 0.0, 0.5, 0.5));
+        assert_eq!(dst, Size::from((400, 300)));
+
+        // Without a destination the cropped region keeps its own size
+        let (_, dst) = resolve_viewport(buffer, Some(crop), None).unwrap();
+        assert_eq!(dst, Size::from((960, 540)));
+    }
+
+    /// Normalised buffer coordinates sampled at surface point `(x, y)`.
+    fn buffer_uv(layout: BufferLayout, buffer: Size<i32, Buffer>, x: f64, y: f64) -> (f64, f64) {
+        let surface = layout.surface_size(buffer).to_f64();
+        let p = Point::<f64, Logical>::from((x, y)).to_buffer(
+            layout.scale as f64,
+            layout.transform,
+            &surface,
+        );
+        (p.x / buffer.w as f64, p.y / buffer.h as f64)
+    }
+
+    #[test]
+    fn test_rotated_hidpi_buffer_size_and_orientation() {
+        let layout = BufferLayout::new(2, wl_output::Transform::_90);
+        assert_eq!(layout.transform, Transform::_90);
+        // A 200x100 buffer drawn rotated at 2x covers a 50x100 surface
+        let buffer = Size::from((200, 100));
+        assert_eq!(layout.surface_size(buffer), Size::from((50, 100)));
+
+        // Rotated a quarter turn: the surface's top edge runs down the
+        // buffer's right edge
+        assert_eq!(buffer_uv(layout, buffer, 0.0, 0.0), (1.0, 0.0));
+        assert_eq!(buffer_uv(layout, buffer, 50.0, 0.0), (1.0, 1.0));
+        assert_eq!(buffer_uv(layout, buffer, 0.0, 100.0), (0.0, 0.0));
+        assert_eq!(buffer_uv(layout, buffer, 50.0, 100.0), (0.0, 1.0));
+
+        // No scale or transform set: the buffer is the surface
+        let plain = BufferLayout::new(0, wl_output::Transform::Normal);
+        assert_eq!(plain, BufferLayout::default());
+        assert_eq!(plain.surface_size(buffer), Size::from((200, 100)));
+        assert_eq!(buffer_uv(plain, buffer, 0.0, 0.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_viewport_defaults_and_out_of_buffer_source() {
+        let buffer = Size::from((640, 480));
+        let (src, dst) = resolve_viewport(buffer, None, Some(Size::from((320, 240)))).unwrap();
+        assert_eq!(uv_range(src, buffer), (0.0, 0.0, 1.0, 1.0));
+        assert_eq!(dst, Size::from((320, 240)));
+
+        let outside = Rectangle::new((600.0, 0.0).into(), (100.0, 100.0).into());
+        assert!(resolve_viewport(buffer, Some(outside), None).is_none());
+    }
+}
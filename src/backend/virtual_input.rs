@@ -0,0 +1,499 @@
+//! Virtual keyboards and pointers (`zwp_virtual_keyboard_v1`,
+//! `zwlr_virtual_pointer_v1`).
+//!
+//! On-screen keyboards, remote desktop servers and automation tools inject
+//! input through these. Only processes listed in
+//! `security.allow_virtual_input` may: others are refused when they bind the
+//! keyboard manager, and their virtual pointers are inert.
+//!
+//! Requests are queued as [`VirtualInput`] while clients are dispatched and
+//! replayed once dispatch is done, through the same paths as real devices,
+//! so compositor bindings, focus rules and popup grabs all apply.
+
+use std::collections::HashSet;
+
+use log::{debug, warn};
+use parking_lot::Mutex;
+use smithay::backend::input::{Axis, AxisSource, ButtonState, KeyState};
+use smithay::input::keyboard::Keycode;
+use smithay::input::pointer::AxisFrame;
+use smithay::reexports::wayland_protocols_misc::zwp_virtual_keyboard_v1::server::{
+    zwp_virtual_keyboard_manager_v1::{self, ZwpVirtualKeyboardManagerV1},
+    zwp_virtual_keyboard_v1::{self, ZwpVirtualKeyboardV1},
+};
+use smithay::reexports::wayland_protocols_wlr::virtual_pointer::v1::server::{
+    zwlr_virtual_pointer_manager_v1::{self, ZwlrVirtualPointerManagerV1},
+    zwlr_virtual_pointer_v1::{self, ZwlrVirtualPointerV1},
+};
+use smithay::reexports::wayland_server::{
+    protocol::{wl_keyboard, wl_pointer},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, WEnum,
+};
+use wayland_server::backend::ClientId;
+use wayland_server::Resource;
+
+use super::geometry::{clamp_to_outputs, output_layout};
+use super::{AxiomSmithayBackendReal, State};
+use crate::window::Rectangle as WindowRectangle;
+
+/// Input injected by a client, waiting to be replayed.
+#[derive(Debug, Clone)]
+pub(super) enum VirtualInput {
+    /// `keycode` is an evdev code, interpreted through the seat's keymap
+    Key {
+        keycode: u32,
+        pressed: bool,
+        time: u32,
+    },
+    PointerMotion {
+        dx: f64,
+        dy: f64,
+    },
+    /// Position as a fraction of the whole output layout
+    PointerMotionAbsolute {
+        x: f64,
+        y: f64,
+    },
+    PointerButton {
+        button: u32,
+        pressed: bool,
+        time: u32,
+    },
+    PointerAxis(AxisFrame),
+}
+
+/// Advertise both protocols. Who may use them is checked when a client
+/// binds.
+pub(super) fn create_globals(dh: &DisplayHandle) {
+    dh.create_global::<State, ZwpVirtualKeyboardManagerV1, _>(1, ());
+    dh.create_global::<State, ZwlrVirtualPointerManagerV1, _>(2, ());
+}
+
+/// Global point at fraction `(fx, fy)` of the bounding box of `outputs`.
+pub(super) fn layout_point(
+    (fx, fy): (f64, f64),
+    outputs: &[(String, WindowRectangle)],
+) -> (f64, f64) {
+    let Some(left) = outputs.iter().map(|(_, g)| g.x).min() else {
+        return (0.0, 0.0);
+    };
+    let top = outputs.iter().map(|(_, g)| g.y).min().unwrap_or(0);
+    let right = outputs
+        .iter()
+        .map(|(_, g)| g.x + g.width as i32)
+        .max()
+        .unwrap_or(left);
+    let bottom = outputs
+        .iter()
+        .map(|(_, g)| g.y + g.height as i32)
+        .max()
+        .unwrap_or(top);
+    (
+        left as f64 + fx.clamp(0.0, 1.0) * (right - left) as f64,
+        top as f64 + fy.clamp(0.0, 1.0) * (bottom - top) as f64,
+    )
+}
+
+/// Per-keyboard state: whether a keymap was sent, which is required before
+/// any key, and the keys it holds down, released when it goes away.
+#[derive(Debug, Default)]
+pub(super) struct VirtualKeyboard {
+    has_keymap: bool,
+    pressed: HashSet<u32>,
+}
+
+/// Per-pointer state. An unauthorized client's pointer ignores everything.
+#[derive(Debug)]
+pub(super) struct VirtualPointer {
+    authorized: bool,
+    /// Axis events collected until the next `frame`
+    axis_frame: Option<AxisFrame>,
+}
+
+fn may_inject(state: &State, client: &ClientId) -> bool {
+    state
+        .security
+        .may_inject_input(client, &state.config.security)
+}
+
+impl GlobalDispatch<ZwpVirtualKeyboardManagerV1, ()> for State {
+    fn bind(
+        state: &mut State,
+        _dh: &DisplayHandle,
+        client: &Client,
+        resource: New<ZwpVirtualKeyboardManagerV1>,
+        _data: &(),
+        data_init: &mut DataInit<'_, State>,
+    ) {
+        if may_inject(state, &client.id()) {
+            data_init.init(resource, ());
+        } else {
+            warn!("🛡️ Refusing virtual keyboard to client {:?}", client.id());
+            data_init.post_error(
+                resource,
+                zwp_virtual_keyboard_manager_v1::Error::Unauthorized,
+                "virtual input is not allowed for this client",
+            );
+        }
+    }
+}
+
+impl Dispatch<ZwpVirtualKeyboardManagerV1, (), State> for State {
+    fn request(
+        _state: &mut State,
+        _client: &Client,
+        _resource: &ZwpVirtualKeyboardManagerV1,
+        request: zwp_virtual_keyboard_manager_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, State>,
+    ) {
+        if let zwp_virtual_keyboard_manager_v1::Request::CreateVirtualKeyboard { id, .. } = request
+        {
+            // ponytail: every virtual keyboard types on the default seat
+            data_init.init(id, Mutex::new(VirtualKeyboard::default()));
+        }
+    }
+}
+
+impl Dispatch<ZwpVirtualKeyboardV1, Mutex<VirtualKeyboard>, State> for State {
+    fn request(
+        state: &mut State,
+        _client: &Client,
+        resource: &ZwpVirtualKeyboardV1,
+        request: zwp_virtual_keyboard_v1::Request,
+        data: &Mutex<VirtualKeyboard>,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, State>,
+    ) {
+        match request {
+            // ponytail: the client's keymap isn't compiled; keycodes go
+            // through the seat's keymap, which is what clients sending the
+            // standard evdev layout expect. Modifier state likewise follows
+            // the modifier keys pressed, not the `modifiers` request.
+            zwp_virtual_keyboard_v1::Request::Keymap { .. } => {
+                data.lock().has_keymap = true;
+            }
+            zwp_virtual_keyboard_v1::Request::Key {
+                time,
+                key,
+                state: key_state,
+            } => {
+                let mut keyboard = data.lock();
+                if !keyboard.has_keymap {
+                    resource.post_error(
+                        zwp_virtual_keyboard_v1::Error::NoKeymap,
+                        "no keymap sent before key",
+                    );
+                    return;
+                }
+                let pressed = key_state == wl_keyboard::KeyState::Pressed as u32;
+                // A repeated press or a stray release changes nothing
+                let changed = if pressed {
+                    keyboard.pressed.insert(key)
+                } else {
+                    keyboard.pressed.remove(&key)
+                };
+                if changed {
+                    state.virtual_input.push(VirtualInput::Key {
+                        keycode: key,
+                        pressed,
+                        time,
+                    });
+                }
+            }
+            zwp_virtual_keyboard_v1::Request::Modifiers { .. } => {}
+            zwp_virtual_keyboard_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+
+    fn destroyed(
+        state: &mut State,
+        _client: ClientId,
+        _resource: &ZwpVirtualKeyboardV1,
+        data: &Mutex<VirtualKeyboard>,
+    ) {
+        // Keys held by a keyboard that's gone would otherwise stay down
+        for keycode in data.lock().pressed.drain() {
+            state.virtual_input.push(VirtualInput::Key {
+                keycode,
+                pressed: false,
+                time: 0,
+            });
+        }
+    }
+}
+
+impl GlobalDispatch<ZwlrVirtualPointerManagerV1, ()> for State {
+    fn bind(
+        state: &mut State,
+        _dh: &DisplayHandle,
+        client: &Client,
+        resource: New<ZwlrVirtualPointerManagerV1>,
+        _data: &(),
+        data_init: &mut DataInit<'_, State>,
+    ) {
+        // The protocol has no way to refuse, so an unauthorized client gets
+        // pointers that do nothing
+        let authorized = may_inject(state, &client.id());
+        if !authorized {
+            warn!(
+                "🛡️ Virtual pointers of client {:?} will be ignored",
+                client.id()
+            );
+        }
+        data_init.init(resource, authorized);
+    }
+}
+
+impl Dispatch<ZwlrVirtualPointerManagerV1, bool, State> for State {
+    fn request(
+        _state: &mut State,
+        _client: &Client,
+        _resource: &ZwlrVirtualPointerManagerV1,
+        request: zwlr_virtual_pointer_manager_v1::Request,
+        authorized: &bool,
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, State>,
+    ) {
+        let id = match request {
+            zwlr_virtual_pointer_manager_v1::Request::CreateVirtualPointer { id, .. } => id,
+            // ponytail: absolute motion maps onto the whole layout rather
+            // than the requested output
+            zwlr_virtual_pointer_manager_v1::Request::CreateVirtualPointerWithOutput {
+                id, ..
+            } => id,
+            _ => return,
+        };
+        data_init.init(
+            id,
+            Mutex::new(VirtualPointer {
+                authorized: *authorized,
+                axis_frame: None,
+            }),
+        );
+    }
+}
+
+fn scroll_axis(axis: WEnum<wl_pointer::Axis>) -> Option<Axis> {
+    match axis.into_result().ok()? {
+        wl_pointer::Axis::VerticalScroll => Some(Axis::Vertical),
+        wl_pointer::Axis::HorizontalScroll => Some(Axis::Horizontal),
+        _ => None,
+    }
+}
+
+fn scroll_source(source: WEnum<wl_pointer::AxisSource>) -> Option<AxisSource> {
+    match source.into_result().ok()? {
+        wl_pointer::AxisSource::Wheel => Some(AxisSource::Wheel),
+        wl_pointer::AxisSource::Finger => Some(AxisSource::Finger),
+        wl_pointer::AxisSource::Continuous => Some(AxisSource::Continuous),
+        wl_pointer::AxisSource::WheelTilt => Some(AxisSource::WheelTilt),
+        _ => None,
+    }
+}
+
+impl Dispatch<ZwlrVirtualPointerV1, Mutex<VirtualPointer>, State> for State {
+    fn request(
+        state: &mut State,
+        _client: &Client,
+        resource: &ZwlrVirtualPointerV1,
+        request: zwlr_virtual_pointer_v1::Request,
+        data: &Mutex<VirtualPointer>,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, State>,
+    ) {
+        let mut pointer = data.lock();
+        if !pointer.authorized {
+            return;
+        }
+        match request {
+            zwlr_virtual_pointer_v1::Request::Motion { dx, dy, .. } => {
+                state
+                    .virtual_input
+                    .push(VirtualInput::PointerMotion { dx, dy });
+            }
+            zwlr_virtual_pointer_v1::Request::MotionAbsolute {
+                x,
+                y,
+                x_extent,
+                y_extent,
+                ..
+            } => {
+                if x_extent == 0 || y_extent == 0 {
+                    return;
+                }
+                state
+                    .virtual_input
+                    .push(VirtualInput::PointerMotionAbsolute {
+                        x: x as f64 / x_extent as f64,
+                        y: y as f64 / y_extent as f64,
+                    });
+            }
+            zwlr_virtual_pointer_v1::Request::Button {
+                time,
+                button,
+                state: button_state,
+            } => {
+                state.virtual_input.push(VirtualInput::PointerButton {
+                    button,
+                    pressed: button_state == WEnum::Value(wl_pointer::ButtonState::Pressed),
+                    time,
+                });
+            }
+            zwlr_virtual_pointer_v1::Request::Axis { time, axis, value } => {
+                let Some(axis) = scroll_axis(axis) else {
+                    resource
+                        .post_error(zwlr_virtual_pointer_v1::Error::InvalidAxis, "invalid axis");
+                    return;
+                };
+                let frame = pointer
+                    .axis_frame
+                    .take()
+                    .unwrap_or_else(|| AxisFrame::new(time));
+                pointer.axis_frame = Some(frame.value(axis, value));
+            }
+            zwlr_virtual_pointer_v1::Request::AxisDiscrete {
+                time,
+                axis,
+                value,
+                discrete,
+            } => {
+                let Some(axis) = scroll_axis(axis) else {
+                    resource
+                        .post_error(zwlr_virtual_pointer_v1::Error::InvalidAxis, "invalid axis");
+                    return;
+                };
+                let frame = pointer
+                    .axis_frame
+                    .take()
+                    .unwrap_or_else(|| AxisFrame::new(time));
+                pointer.axis_frame = Some(
+                    frame
+                        .value(axis, value)
+                        .v120(axis, discrete.saturating_mul(120)),
+                );
+            }
+            zwlr_virtual_pointer_v1::Request::AxisStop { time, axis } => {
+                let Some(axis) = scroll_axis(axis) else {
+                    resource
+                        .post_error(zwlr_virtual_pointer_v1::Error::InvalidAxis, "invalid axis");
+                    return;
+                };
+                let frame = pointer
+                    .axis_frame
+                    .take()
+                    .unwrap_or_else(|| AxisFrame::new(time));
+                pointer.axis_frame = Some(frame.stop(axis));
+            }
+            zwlr_virtual_pointer_v1::Request::AxisSource { axis_source } => {
+                let Some(source) = scroll_source(axis_source) else {
+                    resource.post_error(
+                        zwlr_virtual_pointer_v1::Error::InvalidAxisSource,
+                        "invalid axis source",
+                    );
+                    return;
+                };
+                let frame = pointer
+                    .axis_frame
+                    .take()
+                    .unwrap_or_else(|| AxisFrame::new(0));
+                pointer.axis_frame = Some(frame.source(source));
+            }
+            zwlr_virtual_pointer_v1::Request::Frame => {
+                if let Some(frame) = pointer.axis_frame.take() {
+                    state.virtual_input.push(VirtualInput::PointerAxis(frame));
+                }
+            }
+            zwlr_virtual_pointer_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl AxiomSmithayBackendReal {
+    /// Replay the input clients injected during the last dispatch.
+    pub(super) fn apply_virtual_input(&mut self) {
+        for input in std::mem::take(&mut self.state.virtual_input) {
+            debug!("🤖 Virtual input: {:?}", input);
+            match input {
+                VirtualInput::Key {
+                    keycode,
+                    pressed,
+                    time,
+                } => {
+                    let key_state = if pressed {
+                        KeyState::Pressed
+                    } else {
+                        KeyState::Released
+                    };
+                    // evdev codes are offset by 8 in xkb
+                    self.handle_key(Keycode::new(keycode.saturating_add(8)), key_state, time);
+                }
+                VirtualInput::PointerMotion { dx, dy } => {
+                    let outputs = output_layout(&self.state.workspace_manager.read());
+                    let (x, y) = clamp_to_outputs(
+                        (self.state.pointer_x + dx, self.state.pointer_y + dy),
+                        &outputs,
+                    );
+                    self.process_pointer_motion(x, y);
+                }
+                VirtualInput::PointerMotionAbsolute { x, y } => {
+                    let outputs = output_layout(&self.state.workspace_manager.read());
+                    let (x, y) = layout_point((x, y), &outputs);
+                    self.process_pointer_motion(x, y);
+                }
+                VirtualInput::PointerButton {
+                    button,
+                    pressed,
+                    time,
+                } => {
+                    let button_state = if pressed {
+                        ButtonState::Pressed
+                    } else {
+                        ButtonState::Released
+                    };
+                    self.handle_pointer_button(button, button_state, time);
+                }
+                VirtualInput::PointerAxis(frame) => {
+                    if let Some(pointer) = self.state.seat.get_pointer() {
+                        pointer.axis(&mut self.state, frame);
+                        pointer.frame(&mut self.state);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(name: &str, x: i32, y: i32, width: u32, height: u32) -> (String, WindowRectangle) {
+        (
+            name.to_string(),
+            WindowRectangle {
+                x,
+                y,
+                width,
+                height,
+            },
+        )
+    }
+
+    #[test]
+    fn test_absolute_motion_spans_the_whole_layout() {
+        let outputs = [
+            output("left", 0, 0, 1920, 1080),
+            output("right", 1920, -200, 1280, 1024),
+        ];
+        assert_eq!(layout_point((0.0, 0.0), &outputs), (0.0, -200.0));
+        assert_eq!(layout_point((1.0, 1.0), &outputs), (3200.0, 1080.0));
+        assert_eq!(layout_point((0.5, 0.5), &outputs), (1600.0, 440.0));
+        // Out of range fractions stay on the layout
+        assert_eq!(layout_point((2.0, -1.0), &outputs), (3200.0, -200.0));
+        assert_eq!(layout_point((0.5, 0.5), &[]), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,130 @@
+//! Column wallpapers (`workspace.backgrounds`).
+//!
+//! Images are decoded and uploaded the first frame they are needed, then
+//! kept as textures for as long as the config names them. A file that can't
+//! be read is remembered as missing, so a typo costs one warning instead of
+//! a decode attempt every frame.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::texture::TextureBuffer;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
+use smithay::backend::renderer::ImportMem;
+use smithay::utils::{Logical, Point, Rectangle, Size, Transform};
+
+/// An uploaded wallpaper and its pixel size.
+#[derive(Debug)]
+pub(super) struct Wallpaper {
+    pub(super) texture: TextureBuffer<GlesTexture>,
+    pub(super) size: (i32, i32),
+}
+
+/// Wallpaper textures keyed by the configured path; `None` for one that
+/// failed to load.
+#[derive(Debug, Default)]
+pub(super) struct WallpaperCache {
+    wallpapers: HashMap<String, Option<Wallpaper>>,
+}
+
+impl WallpaperCache {
+    /// Make sure every path in `wanted` has been loaded (or has failed to),
+    /// and drop the ones no longer wanted.
+    pub(super) fn load<'a>(
+        &mut self,
+        renderer: &mut GlesRenderer,
+        wanted: impl IntoIterator<Item = &'a str>,
+    ) {
+        let wanted: HashSet<&str> = wanted.into_iter().collect();
+        self.wallpapers
+            .retain(|path, _| wanted.contains(path.as_str()));
+        for path in wanted {
+            if self.wallpapers.contains_key(path) {
+                continue;
+            }
+            let wallpaper = match upload(renderer, path) {
+                Ok(wallpaper) => {
+                    info!(
+                        "🖼️ Loaded wallpaper {} ({}x{})",
+                        path, wallpaper.size.0, wallpaper.size.1
+                    );
+                    Some(wallpaper)
+                }
+                Err(e) => {
+                    warn!("🖼️ Can't use wallpaper {}: {:#}", path, e);
+                    None
+                }
+            };
+            self.wallpapers.insert(path.to_string(), wallpaper);
+        }
+    }
+
+    pub(super) fn get(&self, path: &str) -> Option<&Wallpaper> {
+        self.wallpapers.get(path).and_then(Option::as_ref)
+    }
+}
+
+fn upload(renderer: &mut GlesRenderer, path: &str) -> Result<Wallpaper> {
+    let image = image::open(path)
+        .with_context(|| format!("failed to decode {}", path))?
+        .into_rgba8();
+    let (w, h) = image.dimensions();
+    let size = (w as i32, h as i32);
+    // RGBA bytes in memory are DRM's little-endian ABGR
+    let texture = renderer
+        .import_memory(image.as_raw(), Fourcc::Abgr8888, Size::from(size), false)
+        .map_err(|e| anyhow::anyhow!("failed to upload {}: {:?}", path, e))?;
+    Ok(Wallpaper {
+        texture: TextureBuffer::from_texture(renderer, texture, 1, Transform::Normal, None),
+        size,
+    })
+}
+
+/// The part of an `image`-sized wallpaper to show on an `output`-sized
+/// area so it covers all of it without stretching: scaled to fill, with
+/// the overflow cropped evenly from both sides.
+pub(super) fn cover_crop(image: (i32, i32), output: (i32, i32)) -> Rectangle<f64, Logical> {
+    let (iw, ih) = (image.0.max(1) as f64, image.1.max(1) as f64);
+    let (ow, oh) = (output.0.max(1) as f64, output.1.max(1) as f64);
+    let scale = (ow / iw).max(oh / ih);
+    let (w, h) = (ow / scale, oh / scale);
+    Rectangle::new(
+        Point::from(((iw - w) / 2.0, (ih - h) / 2.0)),
+        Size::from((w, h)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> Rectangle<f64, Logical> {
+        Rectangle::new(Point::from((x, y)), Size::from((w, h)))
+    }
+
+    #[test]
+    fn test_cover_crop_fills_without_stretching() {
+        // Same aspect: the whole image
+        assert_eq!(
+            cover_crop((3840, 2160), (1920, 1080)),
+            rect(0.0, 0.0, 3840.0, 2160.0)
+        );
+        // Wider image on a 16:9 output: trim the sides
+        assert_eq!(
+            cover_crop((2400, 1080), (1920, 1080)),
+            rect(240.0, 0.0, 1920.0, 1080.0)
+        );
+        // Square image on a portrait output: trim the sides too
+        assert_eq!(
+            cover_crop((1000, 1000), (500, 1000)),
+            rect(250.0, 0.0, 500.0, 1000.0)
+        );
+        // Taller image: trim top and bottom
+        assert_eq!(
+            cover_crop((1920, 1440), (1920, 1080)),
+            rect(0.0, 180.0, 1920.0, 1080.0)
+        );
+    }
+}
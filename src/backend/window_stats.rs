@@ -0,0 +1,157 @@
+//! Per-window commit and upload statistics, for finding the client behind
+//! jank.
+//!
+//! Every commit of a window's surface is recorded with the bytes it asks
+//! the renderer to upload: its damaged area at 4 bytes per pixel, or the
+//! whole surface when it reports no damage. Rates are taken over the last
+//! second. A window is flagged as high-bandwidth when it uploads both a lot
+//! and most of what every window uploads together, since that is the client
+//! whose copies eat into the frame budget.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+/// How far back the rates look.
+const SAMPLE_WINDOW: Duration = Duration::from_secs(1);
+/// Upload rate below which no window is flagged, however it compares.
+const HIGH_BANDWIDTH_BYTES_PER_SEC: u64 = 64 * 1024 * 1024;
+/// Share of all uploads a window must account for to be flagged.
+const HIGH_BANDWIDTH_SHARE: f64 = 0.5;
+
+/// A window's recent commit cadence and upload volume, as reported by the
+/// `GetWindows` IPC request.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WindowFrameStats {
+    /// Commits over the last second
+    pub commits_per_sec: f64,
+    /// Bytes its commits asked to upload over the last second
+    pub bytes_per_sec: u64,
+    /// Time between its last two commits, in milliseconds
+    pub last_frame_interval_ms: Option<f64>,
+    /// Whether its uploads dominate everyone else's
+    pub high_bandwidth: bool,
+}
+
+#[derive(Debug, Default)]
+struct History {
+    /// When each recent commit happened and what it uploaded
+    commits: VecDeque<(Instant, u64)>,
+    last_interval: Option<Duration>,
+}
+
+/// Commit history of every window.
+#[derive(Debug, Default)]
+pub(super) struct WindowStatsTracker {
+    windows: HashMap<u64, History>,
+    /// Windows already warned about, so each is logged once per episode
+    flagged: HashSet<u64>,
+}
+
+impl WindowStatsTracker {
+    /// Window `window_id` committed at `now`, uploading `bytes`.
+    pub(super) fn record_commit(&mut self, window_id: u64, now: Instant, bytes: u64) {
+        let history = self.windows.entry(window_id).or_default();
+        if let Some(&(last, _)) = history.commits.back() {
+            history.last_interval = Some(now.saturating_duration_since(last));
+        }
+        history.commits.push_back((now, bytes));
+        while history
+            .commits
+            .front()
+            .is_some_and(|&(at, _)| now.saturating_duration_since(at) > SAMPLE_WINDOW)
+        {
+            history.commits.pop_front();
+        }
+    }
+
+    /// Forget a window that went away.
+    pub(super) fn remove(&mut self, window_id: u64) {
+        self.windows.remove(&window_id);
+        self.flagged.remove(&window_id);
+    }
+
+    /// Every window's stats as of `now`. Logs a warning the first time a
+    /// window turns high-bandwidth.
+    pub(super) fn stats(&mut self, now: Instant) -> HashMap<u64, WindowFrameStats> {
+        let recent = |history: &History| {
+            history
+                .commits
+                .iter()
+                .filter(|&&(at, _)| now.saturating_duration_since(at) <= SAMPLE_WINDOW)
+                .fold((0u32, 0u64), |(n, total), &(_, bytes)| {
+                    (n + 1, total + bytes)
+                })
+        };
+        let total: u64 = self.windows.values().map(|h| recent(h).1).sum();
+        let seconds = SAMPLE_WINDOW.as_secs_f64();
+        let mut stats = HashMap::with_capacity(self.windows.len());
+        for (&id, history) in &self.windows {
+            let (commits, bytes) = recent(history);
+            let bytes_per_sec = (bytes as f64 / seconds) as u64;
+            let high_bandwidth = bytes_per_sec >= HIGH_BANDWIDTH_BYTES_PER_SEC
+                && bytes as f64 >= total as f64 * HIGH_BANDWIDTH_SHARE;
+            stats.insert(
+                id,
+                WindowFrameStats {
+                    commits_per_sec: f64::from(commits) / seconds,
+                    bytes_per_sec,
+                    last_frame_interval_ms: history.last_interval.map(|d| d.as_secs_f64() * 1000.0),
+                    high_bandwidth,
+                },
+            );
+        }
+        for (&id, s) in &stats {
+            if !s.high_bandwidth {
+                self.flagged.remove(&id);
+            } else if self.flagged.insert(id) {
+                warn!(
+                    "🐢 Window {} is uploading {} MiB/s over {:.0} commits/s",
+                    id,
+                    s.bytes_per_sec / (1024 * 1024),
+                    s.commits_per_sec
+                );
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequent_large_uploads_are_flagged_high_bandwidth() {
+        let mut tracker = WindowStatsTracker::default();
+        let start = Instant::now();
+        let frame = 1920 * 1080 * 4;
+        // Window 1 uploads a full 1080p buffer at 60 Hz; window 2 blinks a
+        // text cursor twice a second
+        for i in 0..60u32 {
+            let at = start + Duration::from_millis(16) * i;
+            tracker.record_commit(1, at, frame);
+            if i % 30 == 0 {
+                tracker.record_commit(2, at, 2 * 20 * 4);
+            }
+        }
+        let stats = tracker.stats(start + Duration::from_millis(16 * 59));
+        let video = stats[&1];
+        assert!(video.high_bandwidth);
+        assert_eq!(video.commits_per_sec, 60.0);
+        assert_eq!(video.bytes_per_sec, 60 * frame);
+        assert!((video.last_frame_interval_ms.unwrap() - 16.0).abs() < 1e-9);
+        let editor = stats[&2];
+        assert!(!editor.high_bandwidth);
+        assert_eq!(editor.commits_per_sec, 2.0);
+
+        // Seconds later window 1 has gone quiet and is no longer flagged
+        let later = tracker.stats(start + Duration::from_secs(3));
+        assert!(!later[&1].high_bandwidth);
+        assert_eq!(later[&1].commits_per_sec, 0.0);
+
+        tracker.remove(1);
+        assert!(!tracker.stats(start).contains_key(&1));
+    }
+}
@@ -10,12 +10,12 @@
 //! `AxiomSmithayBackendReal` (descendant modules see ancestor privates).
 
 use crate::config::AxiomConfig;
-use crate::decoration::DecorationManager;
+use crate::decoration::{DecorationManager, DecorationTheme};
 use crate::input::InputManager;
-use crate::window::WindowManager;
+use crate::window::{PopupInfo, PopupParent, Rectangle, WindowManager};
 use crate::workspace::ScrollableWorkspaces;
 use anyhow::Result;
-use log::{info, warn};
+use log::{debug, error, info, warn};
 
 use smithay::{
     backend::{
@@ -24,12 +24,14 @@ use smithay::{
         winit::{self, WinitEvent, WinitEventLoop, WinitGraphicsBackend},
     },
     output::{Mode as OutputMode, Output, Scale},
-    reexports::wayland_server::{Display, ListeningSocket},
-    utils::Transform,
+    reexports::wayland_server::{BindError, Display, ListeningSocket},
+    utils::{Clock, Transform},
     wayland::{
         compositor::{CompositorClientState, CompositorState},
         foreign_toplevel_list::ForeignToplevelListState,
         fractional_scale::FractionalScaleManagerState,
+        input_method::InputMethodManagerState,
+        presentation::PresentationState,
         selection::data_device::{set_data_device_focus, DataDeviceState},
         session_lock::SessionLockManagerState,
         shell::{
@@ -37,15 +39,37 @@ use smithay::{
             xdg::{decoration::XdgDecorationState, XdgShellState},
         },
         shm::ShmState,
+        tearing_control::TearingControlState,
+        text_input::TextInputManagerState,
+        viewporter::ViewporterState,
     },
 };
 
 use std::collections::{HashMap, HashSet};
 use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
 
+use wayland_server::backend::DisconnectReason;
 use wayland_server::{Client, Resource};
 
+use super::background::{OutputBackgrounds, BACKGROUND_FADE};
+use super::color::ColorPass;
+use super::corners::CornerPass;
+use super::cursor::{Cursor, CursorPlane};
+use super::foreign_toplevel::WlrToplevels;
+use super::frame_callbacks::FrameCallbackScheduler;
+use super::frame_pacer::{FramePacer, DEFAULT_REFRESH_MHZ};
+use super::hud::Hud;
+use super::layers::Layers;
+use super::presenter::{PresentCounters, Presenter, RedrawInputs};
+use super::recovery::RendererRecovery;
+use super::render::TextureLimits;
+use super::security::{post_implementation_error, SecurityManager};
 use super::state::State;
+use super::text::TextRenderer;
+use super::upload_queue::UploadQueue;
+use super::wallpaper::WallpaperCache;
+use super::window_stats::{WindowFrameStats, WindowStatsTracker};
 
 // ============================================================================
 // Backend Kind
@@ -90,6 +114,24 @@ fn smithay_output_scale(scale: f64) -> Scale {
     }
 }
 
+/// Listen on Wayland socket `name` in `XDG_RUNTIME_DIR`, saying what to do
+/// about it when that fails.
+fn bind_wayland_socket(name: &str) -> Result<ListeningSocket> {
+    ListeningSocket::bind(name).map_err(|e| match e {
+        BindError::AlreadyInUse => anyhow::anyhow!(
+            "Wayland socket '{}' is already in use by another compositor; \
+             choose another --socket-name, or stop the one using it \
+             (--replace only stops a running Axiom instance)",
+            name
+        ),
+        BindError::RuntimeDirNotSet => anyhow::anyhow!(
+            "cannot create Wayland socket '{}': XDG_RUNTIME_DIR is not set",
+            name
+        ),
+        e => anyhow::Error::new(e).context(format!("failed to bind Wayland socket '{}'", name)),
+    })
+}
+
 // ============================================================================
 // Backend Struct
 // ============================================================================
@@ -123,6 +165,12 @@ pub struct AxiomSmithayBackendReal {
     /// `(x, y, time_msec)`. Set on TouchDown, consumed on TouchUp when
     /// the tap thresholds are met.
     pub(super) touch_tap_state: Option<(f64, f64, u32)>,
+    /// Presented/dropped/lost frame counts for the output surface.
+    pub(super) presenter: Presenter,
+    /// Rebuilds the renderer after the GPU context is lost.
+    pub(super) renderer_recovery: RendererRecovery,
+    /// Predicts the output's next vblank from the last present.
+    pub(super) frame_pacer: FramePacer,
 }
 
 /// Type of interactive window manipulation in progress.
@@ -166,11 +214,16 @@ impl AxiomSmithayBackendReal {
         let xdg_shell_state = XdgShellState::new::<State>(&dh);
         let data_device_state = DataDeviceState::new::<State>(&dh);
         let fractional_scale_manager_state = FractionalScaleManagerState::new::<State>(&dh);
+        let viewporter_state = ViewporterState::new::<State>(&dh);
+        let presentation_state = PresentationState::new::<State>(&dh, libc::CLOCK_MONOTONIC as u32);
         let layer_shell_state = WlrLayerShellState::new::<State>(&dh);
         let session_lock_state = SessionLockManagerState::new::<State, _>(&dh, |_| true);
+        let text_input_state = TextInputManagerState::new::<State>(&dh);
+        let input_method_state = InputMethodManagerState::new::<State, _>(&dh, |_| true);
 
         let mut seat_state = smithay::input::SeatState::new();
         let seat = seat_state.new_wl_seat(&dh, "axiom-test");
+        let seats = State::extra_seats(&mut seat_state, &dh, &config);
 
         let (clipboard_update_tx, clipboard_update_rx) = mpsc::channel();
 
@@ -183,9 +236,16 @@ impl AxiomSmithayBackendReal {
             display_handle: Some(display.handle()),
             xdg_decoration_state: None,
             fractional_scale_manager_state,
+            viewporter_state,
             layer_shell_state,
             session_lock_state,
+            tearing_control_state: None,
+            presentation_state,
+            text_input_state,
+            input_method_state,
             seat,
+            seats,
+            hud: Hud::new(config.general.hud),
             config,
             window_manager,
             workspace_manager,
@@ -201,14 +261,21 @@ impl AxiomSmithayBackendReal {
             foreign_toplevel_list_state: ForeignToplevelListState::new::<State>(&display.handle()),
             running: true,
             needs_redraw: true,
+            redraw_pending_since: None,
             pending_capture: None,
             session_locked: false,
             lock_surfaces: Vec::new(),
+            ime_popups: Vec::new(),
             window_width: 1920,
             window_height: 1080,
             pointer_x: 0.0,
             pointer_y: 0.0,
             texture_cache: lru::LruCache::new(std::num::NonZeroUsize::new(256).unwrap()),
+            texture_limits: TextureLimits::default(),
+            color_pass: ColorPass::default(),
+            corner_pass: CornerPass::default(),
+            layers: Layers::default(),
+            text_renderer: TextRenderer::default(),
             configured_sizes: HashMap::new(),
             pending_configure: HashSet::new(),
             popups: HashMap::new(),
@@ -219,12 +286,33 @@ impl AxiomSmithayBackendReal {
             clipboard_source: None,
             clipboard_fetch_pending: false,
             cursor_icon: None,
+            cursor_surface: None,
+            cursor: Cursor::software(),
+            cursor_hotspot: (0, 0),
             dnd_icon: None,
             dnd_active: false,
             cached_floating_rects: Vec::new(),
             output_damage: Vec::new(),
+            window_damage: HashMap::new(),
+            rendered_windows: Vec::new(),
+            wallpapers: WallpaperCache::default(),
+            backgrounds: OutputBackgrounds::default(),
+            security: SecurityManager::default(),
+            virtual_input: Vec::new(),
+            wlr_toplevels: WlrToplevels::default(),
             surface_previous_rects: HashMap::new(),
             surface_commit_counters: HashMap::new(),
+            window_stats: WindowStatsTracker::default(),
+            upload_queue: UploadQueue::default(),
+            frame_callbacks: FrameCallbackScheduler::from_env(),
+            clock_start: Instant::now(),
+            presentation_clock: Clock::new(),
+            tearing_surfaces: HashSet::new(),
+            opaque_regions: HashMap::new(),
+            output_mirrors: HashMap::new(),
+            mirror_maps: Vec::new(),
+            output_globals: HashMap::new(),
+            output_changes: Vec::new(),
         };
 
         Ok(Self {
@@ -240,6 +328,9 @@ impl AxiomSmithayBackendReal {
             interaction: None,
             touch_interaction: None,
             touch_tap_state: None,
+            presenter: Presenter::new(),
+            renderer_recovery: RendererRecovery::default(),
+            frame_pacer: FramePacer::new(DEFAULT_REFRESH_MHZ),
         })
     }
 
@@ -260,6 +351,16 @@ impl AxiomSmithayBackendReal {
         // Capture config.output.order BEFORE config is moved into State.
         let config_output_order = config.output.order.clone();
 
+        // Bind the Wayland socket first, so a name that is taken fails
+        // startup before anything else is set up.
+        let socket_name = config
+            .backend
+            .socket_name
+            .clone()
+            .unwrap_or_else(|| format!("wayland-axiom-{}", std::process::id()));
+        let listener = bind_wayland_socket(&socket_name)?;
+        info!("📡 Wayland socket: {}", socket_name);
+
         // Clone the workspace_manager Arc so we can sync tapes after state
         // construction (the original is moved into State).
         let wm_for_sync = workspace_manager.clone();
@@ -272,8 +373,12 @@ impl AxiomSmithayBackendReal {
         let xdg_shell_state = XdgShellState::new::<State>(&dh);
         let data_device_state = DataDeviceState::new::<State>(&dh);
         let fractional_scale_manager_state = FractionalScaleManagerState::new::<State>(&dh);
+        let viewporter_state = ViewporterState::new::<State>(&dh);
+        let presentation_state = PresentationState::new::<State>(&dh, libc::CLOCK_MONOTONIC as u32);
         let layer_shell_state = WlrLayerShellState::new::<State>(&dh);
         let session_lock_state = SessionLockManagerState::new::<State, _>(&dh, |_| true);
+        let text_input_state = TextInputManagerState::new::<State>(&dh);
+        let input_method_state = InputMethodManagerState::new::<State, _>(&dh, |_| true);
 
         let xdg_decoration_state = if config.features.enable_xdg_decoration_protocol {
             info!("🌐 Registering zxdg_decoration_manager_v1 global");
@@ -282,8 +387,16 @@ impl AxiomSmithayBackendReal {
             None
         };
 
+        let tearing_control_state = if config.performance.allow_tearing {
+            info!("🌐 Registering wp_tearing_control_manager_v1 global");
+            Some(TearingControlState::new::<State>(&dh))
+        } else {
+            None
+        };
+
         let mut seat_state = smithay::input::SeatState::new();
         let seat = seat_state.new_wl_seat(&dh, "axiom");
+        let seats = State::extra_seats(&mut seat_state, &dh, &config);
         let (clipboard_update_tx, clipboard_update_rx) = mpsc::channel();
 
         let output = Output::new(
@@ -305,8 +418,10 @@ impl AxiomSmithayBackendReal {
             Some(Scale::Integer(1)),
             None,
         );
-        output.create_global::<State>(&dh);
+        let output_global = output.create_global::<State>(&dh);
         let _ = dh.create_global::<State, smithay::reexports::wayland_protocols_wlr::screencopy::v1::server::zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1, _>(1, ());
+        super::virtual_input::create_globals(&dh);
+        super::foreign_toplevel::create_global(&dh);
 
         let state = State {
             compositor_state,
@@ -317,9 +432,16 @@ impl AxiomSmithayBackendReal {
             display_handle: Some(display.handle()),
             xdg_decoration_state,
             fractional_scale_manager_state,
+            viewporter_state,
             layer_shell_state,
             session_lock_state,
+            tearing_control_state,
+            presentation_state,
+            text_input_state,
+            input_method_state,
             seat,
+            seats,
+            hud: Hud::new(config.general.hud),
             config,
             window_manager,
             workspace_manager,
@@ -335,14 +457,21 @@ impl AxiomSmithayBackendReal {
             foreign_toplevel_list_state: ForeignToplevelListState::new::<State>(&display.handle()),
             running: true,
             needs_redraw: true,
+            redraw_pending_since: None,
             pending_capture: None,
             session_locked: false,
             lock_surfaces: Vec::new(),
+            ime_popups: Vec::new(),
             window_width: 1920,
             window_height: 1080,
             pointer_x: 0.0,
             pointer_y: 0.0,
             texture_cache: lru::LruCache::new(std::num::NonZeroUsize::new(256).unwrap()),
+            texture_limits: TextureLimits::default(),
+            color_pass: ColorPass::default(),
+            corner_pass: CornerPass::default(),
+            layers: Layers::default(),
+            text_renderer: TextRenderer::default(),
             configured_sizes: HashMap::new(),
             pending_configure: HashSet::new(),
             popups: HashMap::new(),
@@ -353,18 +482,35 @@ impl AxiomSmithayBackendReal {
             clipboard_source: None,
             clipboard_fetch_pending: false,
             cursor_icon: None,
+            cursor_surface: None,
+            cursor: Cursor::software(),
+            cursor_hotspot: (0, 0),
             dnd_icon: None,
             dnd_active: false,
             cached_floating_rects: Vec::new(),
             output_damage: Vec::new(),
+            window_damage: HashMap::new(),
+            rendered_windows: Vec::new(),
+            wallpapers: WallpaperCache::default(),
+            backgrounds: OutputBackgrounds::default(),
+            security: SecurityManager::default(),
+            virtual_input: Vec::new(),
+            wlr_toplevels: WlrToplevels::default(),
             surface_previous_rects: HashMap::new(),
             surface_commit_counters: HashMap::new(),
+            window_stats: WindowStatsTracker::default(),
+            upload_queue: UploadQueue::default(),
+            frame_callbacks: FrameCallbackScheduler::from_env(),
+            clock_start: Instant::now(),
+            presentation_clock: Clock::new(),
+            tearing_surfaces: HashSet::new(),
+            opaque_regions: HashMap::new(),
+            output_mirrors: HashMap::new(),
+            mirror_maps: Vec::new(),
+            output_globals: HashMap::from([("Axiom-Output-0".to_string(), output_global)]),
+            output_changes: Vec::new(),
         };
 
-        let socket_name = format!("wayland-axiom-{}", std::process::id());
-        let listener = ListeningSocket::bind(&socket_name)?;
-        info!("📡 Wayland socket: {}", socket_name);
-
         // Sync workspace tapes with configured outputs.
         // This ensures the tape infrastructure aligns with config.output.order.
         {
@@ -372,6 +518,7 @@ impl AxiomSmithayBackendReal {
             let live_outputs = vec!["Axiom-Output-0".to_string()];
             wm.sync_tapes_with_outputs(&live_outputs, &config_output_order);
         }
+        state.apply_output_layouts();
 
         Ok(Self {
             display,
@@ -386,6 +533,9 @@ impl AxiomSmithayBackendReal {
             interaction: None,
             touch_interaction: None,
             touch_tap_state: None,
+            presenter: Presenter::new(),
+            renderer_recovery: RendererRecovery::default(),
+            frame_pacer: FramePacer::new(DEFAULT_REFRESH_MHZ),
         })
     }
 
@@ -448,6 +598,14 @@ impl AxiomSmithayBackendReal {
 
         self.state.seat.add_pointer();
         self.state.seat.add_touch();
+        for seat in self.state.seats.values_mut() {
+            seat.add_keyboard(
+                smithay::input::keyboard::XkbConfig::default(),
+                repeat_delay,
+                repeat_rate,
+            )?;
+            seat.add_pointer();
+        }
 
         info!("✅ Input devices registered with seat");
 
@@ -503,6 +661,7 @@ impl AxiomSmithayBackendReal {
             info!("📐 Window resized to {}x{} (scale {:.2})", w, h, host_scale);
             self.state.window_width = w;
             self.state.window_height = h;
+            self.presenter.on_resize(w, h);
             let host_scale = host_scale.clamp(1.0, 4.0);
             {
                 let mut wm = self.state.workspace_manager.write();
@@ -565,13 +724,13 @@ impl AxiomSmithayBackendReal {
             loop {
                 match listener.accept() {
                     Ok(Some(stream)) => {
-                        if let Err(e) = self.display.handle().insert_client(
-                            stream,
-                            Arc::new(super::state::ClientState {
-                                compositor_state: CompositorClientState::default(),
-                            }),
-                        ) {
-                            warn!("Failed to insert Wayland client: {e}");
+                        let client_state = Arc::new(super::state::ClientState {
+                            compositor_state: CompositorClientState::default(),
+                            disconnects: self.state.security.disconnect_queue(),
+                        });
+                        match self.display.handle().insert_client(stream, client_state) {
+                            Ok(client) => self.admit_client(&client),
+                            Err(e) => warn!("Failed to insert Wayland client: {e}"),
                         }
                     }
                     Ok(None) => break,
@@ -585,8 +744,13 @@ impl AxiomSmithayBackendReal {
         }
 
         // Dispatch Wayland client events
-        self.display.dispatch_clients(&mut self.state)?;
+        self.dispatch_clients_isolated();
+        self.state.refresh_wlr_toplevels();
         self.display.flush_clients()?;
+        self.state
+            .security
+            .process_disconnects(&self.state.config.security, Instant::now());
+        self.apply_virtual_input();
 
         // Fetch any client selection offered during this dispatch (the
         // selection is only registered in `seat_data` after `new_selection`
@@ -601,18 +765,45 @@ impl AxiomSmithayBackendReal {
         // Update animations after dispatch so newly-created windows (which
         // trigger animate_window_open() during dispatch) get their first
         // integration step before the render pass reads effect states.
-        if self.state.workspace_manager.write().update_animations() {
-            self.state.needs_redraw = true;
-        }
+        let mut animating = self.state.workspace_manager.write().update_animations();
+        animating |= self
+            .state
+            .window_manager
+            .write()
+            .update_focus_transition(&self.state.config.effects);
+        animating |= self.state.backgrounds.update(Instant::now());
+
+        // Follows-mouse focus for a pointer resting on a window
+        self.poll_hover_focus();
+
+        // Clients that ignored a close request past the grace period
+        self.state.force_overdue_closes();
 
         // Prune dead surfaces from disconnected clients
         self.state.prune_dead_surfaces();
 
+        // Client damage, less what is hidden under opaque windows
+        self.state.flush_window_damage();
+
+        // A GPU reset last frame: new renderer, everything redrawn
+        self.recover_renderer();
+
         // Render if needed.
-        if self.state.needs_redraw {
+        let redraw = RedrawInputs {
+            requested: self.state.needs_redraw,
+            damaged: !self.state.output_damage.is_empty(),
+            animating,
+            has_content: self.state.has_scene_content(),
+        };
+        if self.presenter.on_redraw(redraw) {
+            self.state.redraw_pending_since.get_or_insert_with(Instant::now);
             self.render()?;
-            self.state.needs_redraw = false;
+        } else {
+            // Nothing visible changed; don't let stale damage pile up
+            self.state.output_damage.clear();
         }
+        self.state.needs_redraw = false;
+        self.state.redraw_pending_since = None;
 
         Ok(())
     }
@@ -622,6 +813,183 @@ impl AxiomSmithayBackendReal {
         self.run_one_cycle()
     }
 
+    /// Frames presented, dropped and surfaces lost since startup.
+    pub fn present_counters(&self) -> PresentCounters {
+        self.presenter.frame_stats()
+    }
+
+    /// Commit cadence and upload volume of every window over the last
+    /// second, keyed by window id.
+    pub fn window_stats(&mut self) -> HashMap<u64, WindowFrameStats> {
+        self.state.window_stats.stats(Instant::now())
+    }
+
+    /// Every popup open on a window, with what it hangs off, for
+    /// `WindowManager::get_window_tree`.
+    pub fn popups(&self) -> Vec<PopupInfo> {
+        let windows: HashMap<u32, u64> = self
+            .state
+            .window_map
+            .iter()
+            .map(|(&window, &surface)| (surface, window))
+            .collect();
+        self.state
+            .popups
+            .iter()
+            .filter_map(|(&id, popup)| {
+                let parent = popup.parent_surface_id;
+                let parent = if self.state.popups.contains_key(&parent) {
+                    PopupParent::Popup(parent)
+                } else {
+                    PopupParent::Window(*windows.get(&parent)?)
+                };
+                let size = (popup.width.max(0) as u32, popup.height.max(0) as u32);
+                Some(PopupInfo {
+                    id,
+                    parent,
+                    geometry: Rectangle::from_loc_and_size((popup.x, popup.y), size),
+                })
+            })
+            .collect()
+    }
+
+    /// When the next cycle should run: the output's next predicted vblank,
+    /// no sooner than `min_interval` after the last present.
+    pub fn next_frame_deadline(&self, now: Instant, min_interval: Duration) -> Instant {
+        self.frame_pacer.next_deadline(now, min_interval)
+    }
+
+    /// When the oldest not-yet-presented redraw was requested, if any.
+    pub fn redraw_pending_since(&self) -> Option<Instant> {
+        self.state.redraw_pending_since
+    }
+
+    /// Make `target` mirror `source`, or stop mirroring with `None`.
+    ///
+    /// Both are output (tape) IDs. A mirror cannot itself be mirrored, so
+    /// chains are rejected rather than resolved.
+    pub fn set_output_mirror(&mut self, target: &str, source: Option<&str>) -> Result<()> {
+        let Some(source) = source else {
+            self.state.output_mirrors.remove(target);
+            self.state.needs_redraw = true;
+            return Ok(());
+        };
+        let known = self.state.workspace_manager.read().known_tape_ids();
+        for id in [source, target] {
+            if !known.iter().any(|k| k == id) {
+                anyhow::bail!("unknown output '{}'", id);
+            }
+        }
+        if source == target {
+            anyhow::bail!("output '{}' cannot mirror itself", target);
+        }
+        if self.state.output_mirrors.contains_key(source) {
+            anyhow::bail!("output '{}' is already a mirror", source);
+        }
+        if self.state.output_mirrors.values().any(|s| s == target) {
+            anyhow::bail!("output '{}' is mirrored by another output", target);
+        }
+        info!("🪞 Mirroring output {} onto {}", source, target);
+        self.state.output_mirrors.insert(target.to_string(), source.to_string());
+        self.state.needs_redraw = true;
+        Ok(())
+    }
+
+    /// Pick up changed `[effects]`, `[workspace]`, `[input]` and
+    /// `[decoration]` settings, e.g. from a profile switch or reload. Key repeat changes apply to the default
+    /// seat's keyboard straight away.
+    pub fn apply_config(&mut self, config: &AxiomConfig) {
+        self.state.config.effects = config.effects.clone();
+        self.state.config.workspace = config.workspace.clone();
+        self.state.config.input = config.input.clone();
+        self.state.config.decoration = config.decoration.clone();
+        self.state
+            .decoration_manager
+            .write()
+            .update_theme(DecorationTheme::from_config(
+                &config.window,
+                &config.decoration,
+            ));
+        if let Some(keyboard) = self.state.seat.get_keyboard() {
+            let (delay, rate) = State::keyboard_repeat_settings(config);
+            keyboard.change_repeat_info(rate, delay);
+        }
+        self.state.needs_redraw = true;
+    }
+
+    /// Give output `output` a background color of its own, drawn instead
+    /// of the global clear color; `None` goes back to the clear color. A
+    /// wallpaper image, the output's or a column's, still covers it. The
+    /// old background cross-fades to the new one.
+    pub fn set_output_background_color(
+        &mut self,
+        output: &str,
+        color: Option<[f32; 4]>,
+    ) -> Result<()> {
+        self.ensure_known_output(output)?;
+        let fade = self.background_fade();
+        self.state
+            .backgrounds
+            .set_color(output, color, Instant::now(), fade);
+        self.state.needs_redraw = true;
+        Ok(())
+    }
+
+    /// Show the image at `path` behind output `output`'s columns that
+    /// have no `workspace.backgrounds` wallpaper, or `None` to go back to
+    /// its color. The old background cross-fades to the new one, e.g.
+    /// for a slideshow.
+    pub fn set_output_wallpaper(&mut self, output: &str, path: Option<&str>) -> Result<()> {
+        self.ensure_known_output(output)?;
+        let fade = self.background_fade();
+        let path = path.map(str::to_string);
+        self.state
+            .backgrounds
+            .set_wallpaper(output, path, Instant::now(), fade);
+        self.state.needs_redraw = true;
+        Ok(())
+    }
+
+    fn ensure_known_output(&self, output: &str) -> Result<()> {
+        let known = self.state.workspace_manager.read().known_tape_ids();
+        if !known.iter().any(|k| k == output) {
+            anyhow::bail!("unknown output '{}'", output);
+        }
+        Ok(())
+    }
+
+    /// How long background changes fade for; instant with reduce_motion.
+    fn background_fade(&self) -> Duration {
+        if self.state.config.effects.reduce_motion {
+            Duration::ZERO
+        } else {
+            BACKGROUND_FADE
+        }
+    }
+
+    /// Show or hide the debug HUD.
+    pub fn set_hud(&mut self, enabled: bool) {
+        if self.state.hud.enabled != enabled {
+            info!("📊 Debug HUD {}", if enabled { "on" } else { "off" });
+        }
+        self.state.hud.enabled = enabled;
+        self.state.needs_redraw = true;
+    }
+
+    /// Whether the debug HUD is showing.
+    pub fn hud_enabled(&self) -> bool {
+        self.state.hud.enabled
+    }
+
+    /// Show the cursor on `plane` instead of drawing it into each frame,
+    /// whenever the plane can take the client's image. For backends with
+    /// hardware cursor planes; without one the cursor stays in software.
+    pub fn set_cursor_plane(&mut self, plane: Box<dyn CursorPlane>) {
+        self.state.cursor = Cursor::with_plane(plane);
+        self.state.update_cursor_plane();
+        self.state.needs_redraw = true;
+    }
+
     /// Test/debug accessor: clone the cached Wayland→compositor selection
     /// payload (`clipboard_cache`). Used by headless integration tests to
     /// assert the compositor received a client's clipboard offer.
@@ -656,6 +1024,76 @@ impl AxiomSmithayBackendReal {
         }
     }
 
+    /// Start tracking a client that just connected, or drop it straight
+    /// away if its process has been banned for misbehaving. A client
+    /// without credentials is tracked for dispatch but never banned.
+    fn admit_client(&mut self, client: &Client) {
+        let handle = self.display.handle();
+        let pid = client
+            .get_credentials(&handle)
+            .ok()
+            .map(|c| c.pid)
+            .filter(|&pid| pid > 0);
+        if !self
+            .state
+            .security
+            .client_connected(client.id(), pid, Instant::now())
+        {
+            warn!("🛡️ Refusing Wayland client from banned pid {:?}", pid);
+            handle
+                .backend_handle()
+                .kill_client(client.id(), DisconnectReason::ConnectionClosed);
+        }
+    }
+
+    /// Dispatch each client's requests separately. A protocol error already
+    /// disconnects only the client at fault; this does the same for a
+    /// handler that panics on a client's request, which would otherwise
+    /// unwind through the event loop and take every client down with it.
+    ///
+    /// After a caught panic, `AssertUnwindSafe` relies on these being put
+    /// right again:
+    /// - the `parking_lot` locks in `State` are released on unwind and
+    ///   never poisoned, so later frames can take them as usual;
+    /// - the client is disconnected, so its `ClientState::disconnected`
+    ///   reports it to the `SecurityManager` on the next cycle;
+    /// - its surfaces die with the connection, and `prune_dead_surfaces`
+    ///   then drops their windows and focus, and smithay their layer
+    ///   surfaces and popups, exactly as after a clean disconnect;
+    /// - the window and workspace managers, which a handler may have left
+    ///   half-updated, are re-checked straight away by
+    ///   `State::restore_invariants_after_panic`.
+    ///
+    /// Anything else the handler changed before panicking, such as a
+    /// half-applied layout, stays as it was until the next update fixes it.
+    fn dispatch_clients_isolated(&mut self) {
+        for id in self.state.security.clients() {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                self.display
+                    .backend()
+                    .dispatch_single_client(&mut self.state, id.clone())
+            }));
+            match result {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => debug!("Dispatching client {:?} failed: {}", id, e),
+                Err(panic) => {
+                    let message = panic
+                        .downcast_ref::<&str>()
+                        .copied()
+                        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                        .unwrap_or("request handler panicked");
+                    error!("🚨 Disconnecting client {:?}: {}", id, message);
+                    post_implementation_error(
+                        &self.display.handle(),
+                        id,
+                        &format!("compositor error: {}", message),
+                    );
+                    self.state.restore_invariants_after_panic();
+                }
+            }
+        }
+    }
+
     /// Shutdown the backend
     pub fn shutdown(&mut self) -> Result<()> {
         info!("🛑 Shutting down Smithay backend");
@@ -674,6 +1112,8 @@ impl AxiomSmithayBackendReal {
                 // Noop shutdown: nothing to clean up.
             }
         }
+        // Release client textures before the renderer itself goes away.
+        self.state.texture_cache.clear();
 
         Ok(())
     }
@@ -695,6 +1135,7 @@ mod tests {
     use std::fs::File;
     use std::os::unix::io::OwnedFd;
     use std::sync::Arc;
+    use std::time::Instant;
 
     /// Create a headless backend for unit tests with default config.
     fn test_backend() -> AxiomSmithayBackendReal {
@@ -763,6 +1204,41 @@ mod tests {
         ClientDndGrabHandler::dropped(&mut backend.state, None, false, seat);
     }
 
+    /// A drag switches to the move cursor and the drop restores the default.
+    #[test]
+    fn test_dnd_sets_move_cursor_and_restores_default() {
+        use smithay::input::pointer::CursorIcon;
+        let mut backend = test_backend();
+
+        let seat = backend.state.seat.clone();
+        ClientDndGrabHandler::started(&mut backend.state, None, None, seat);
+        assert_eq!(backend.state.cursor_icon, Some(CursorIcon::Move));
+        assert!(backend.state.cursor_surface.is_none());
+
+        let seat = backend.state.seat.clone();
+        ClientDndGrabHandler::dropped(&mut backend.state, None, false, seat);
+        assert_eq!(backend.state.cursor_icon, Some(CursorIcon::Default));
+    }
+
+    /// A server drag's cursor follows the action the target picks.
+    #[test]
+    fn test_server_dnd_cursor_follows_negotiated_action() {
+        use smithay::input::pointer::CursorIcon;
+        use smithay::reexports::wayland_server::protocol::wl_data_device_manager::DndAction;
+        let mut backend = test_backend();
+
+        let seat = backend.state.seat.clone();
+        ServerDndGrabHandler::action(&mut backend.state, DndAction::Copy, seat);
+        assert_eq!(backend.state.cursor_icon, Some(CursorIcon::Copy));
+        let seat = backend.state.seat.clone();
+        ServerDndGrabHandler::action(&mut backend.state, DndAction::Move, seat);
+        assert_eq!(backend.state.cursor_icon, Some(CursorIcon::Move));
+
+        let seat = backend.state.seat.clone();
+        ServerDndGrabHandler::dropped(&mut backend.state, seat);
+        assert_eq!(backend.state.cursor_icon, Some(CursorIcon::Default));
+    }
+
     /// ServerDndGrabHandler::send serves clipboard cache data (or drops fd when empty).
     #[test]
     fn test_dnd_send_no_panic() {
@@ -888,6 +1364,62 @@ mod tests {
         assert_eq!(w.window.position.1, 100, "y unchanged");
     }
 
+    /// Dragging the corner of an aspect-locked window keeps its ratio.
+    #[test]
+    fn test_interactive_resize_keeps_aspect_ratio() {
+        use crate::decoration::ResizeEdge;
+        use crate::window::WindowConstraints;
+        let mut backend = test_backend();
+
+        let wid = backend
+            .state
+            .window_manager
+            .write()
+            .add_window("Video".into());
+        {
+            let mut wm = backend.state.window_manager.write();
+            wm.set_window_constraints(
+                wid,
+                WindowConstraints {
+                    aspect_ratio: Some(16.0 / 9.0),
+                    ..Default::default()
+                },
+            );
+            let w = wm.get_window_mut(wid).unwrap();
+            w.window.position = (100, 100);
+            w.window.size = (640, 360);
+        }
+
+        // Drag the bottom-right corner 320px right and only 10px down
+        let interaction = WindowInteraction::Resize {
+            window_id: wid,
+            edge: ResizeEdge::BottomRight,
+            initial_rect: (100, 100, 640, 360),
+            start_x: 740.0,
+            start_y: 460.0,
+        };
+        backend.handle_interaction(&interaction, 1060.0, 470.0);
+        {
+            let wm = backend.state.window_manager.read();
+            assert_eq!(wm.get_window(wid).unwrap().window.size, (960, 540));
+        }
+
+        // Dragging the top edge far past the output keeps the ratio and the
+        // bottom edge anchored, clamped to the screen top
+        let interaction = WindowInteraction::Resize {
+            window_id: wid,
+            edge: ResizeEdge::Top,
+            initial_rect: (100, 100, 640, 360),
+            start_x: 400.0,
+            start_y: 100.0,
+        };
+        backend.handle_interaction(&interaction, 400.0, -500.0);
+        let wm = backend.state.window_manager.read();
+        let w = wm.get_window(wid).unwrap();
+        assert_eq!(w.window.size, (818, 460));
+        assert_eq!(w.window.position, (100, 0));
+    }
+
     /// Left-edge resize moves the window position and adjusts width.
     #[test]
     fn test_touch_interaction_resize_left_edge() {
@@ -1011,6 +1543,90 @@ mod tests {
         );
     }
 
+    /// Client damage is clipped to the surface; empty or outside rects are dropped.
+    #[test]
+    fn test_surface_damage_clamped_to_surface_bounds() {
+        use crate::backend::state::clamp_surface_damage;
+        use smithay::utils::{Point, Rectangle, Size};
+        let bounds = Size::from((800, 600));
+        let overflowing = Rectangle::new(Point::from((-50, 500)), Size::from((2000, 400)));
+        assert_eq!(
+            clamp_surface_damage(overflowing, bounds),
+            Some(Rectangle::new(Point::from((0, 500)), Size::from((800, 100))))
+        );
+        let outside = Rectangle::new(Point::from((900, 0)), Size::from((100, 100)));
+        assert_eq!(clamp_surface_damage(outside, bounds), None);
+        let empty = Rectangle::new(Point::from((10, 10)), Size::from((0, 40)));
+        assert_eq!(clamp_surface_damage(empty, bounds), None);
+        let huge = Rectangle::new(Point::from((i32::MAX - 1, 0)), Size::from((i32::MAX, 10)));
+        assert_eq!(clamp_surface_damage(huge, bounds), None);
+    }
+
+    /// A panic while a shared manager lock is held must not wedge later
+    /// frames. The managers use parking_lot locks, which don't poison;
+    /// this guards against a switch to `std::sync` locks dropping updates.
+    #[test]
+    fn test_shared_manager_locks_survive_panic_while_held() {
+        let backend = test_backend();
+        let window_manager = backend.state.window_manager.clone();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = window_manager.write();
+            panic!("panic while holding the window manager lock");
+        }));
+        assert!(result.is_err());
+
+        let wid = backend.state.window_manager.write().add_window("After".into());
+        assert!(backend.state.window_manager.read().get_window(wid).is_some());
+        backend.state.workspace_manager.write().add_window(wid);
+        assert!(backend
+            .state
+            .workspace_manager
+            .read()
+            .calculate_workspace_layouts()
+            .contains_key(&wid));
+    }
+
+    /// Laying out a tiled window reports its geometry to window listeners.
+    #[test]
+    fn test_tiling_layout_emits_window_geometry_events() {
+        use crate::window::WindowEvent;
+        use std::sync::{Arc, Mutex};
+        let mut backend = test_backend();
+        let wid = {
+            let mut wm = backend.state.window_manager.write();
+            let wid = wm.add_window("Tiled".into());
+            // Somewhere no layout puts it
+            wm.set_window_geometry(
+                wid,
+                crate::window::Rectangle::from_loc_and_size((-100, -100), (1, 1)),
+            );
+            wid
+        };
+        backend.state.workspace_manager.write().add_window(wid);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        backend
+            .state
+            .window_manager
+            .write()
+            .add_window_listener(Box::new(move |event| sink.lock().unwrap().push(event)));
+
+        let layouts = backend.state.prepare_render_scene();
+        let rect = layouts[&wid].clone();
+
+        let events = events.lock().unwrap();
+        assert!(events.contains(&WindowEvent::Moved {
+            id: wid,
+            x: rect.x,
+            y: rect.y,
+        }));
+        assert!(events.contains(&WindowEvent::Resized {
+            id: wid,
+            width: rect.width,
+            height: rect.height,
+        }));
+    }
+
     /// Window manager tracks fullscreen state correctly for occlusion.
     #[test]
     fn test_fullscreen_window_tracking_for_occlusion() {
@@ -1034,6 +1650,38 @@ mod tests {
         assert!(w.properties.fullscreen, "window marked as fullscreen");
     }
 
+    /// A window the window manager dropped before a handler panicked
+    /// leaves the workspace too when the managers are re-checked.
+    #[test]
+    fn test_restore_invariants_after_panic_drops_orphaned_tiles() {
+        let mut backend = test_backend();
+        let (kept, lost) = {
+            let mut wm = backend.state.window_manager.write();
+            (wm.add_window("kept".into()), wm.add_window("lost".into()))
+        };
+        for id in [kept, lost] {
+            backend.state.workspace_manager.write().add_window(id);
+        }
+        backend.state.window_manager.write().remove_window(lost);
+
+        assert_eq!(backend.state.restore_invariants_after_panic(), 1);
+        let ws = backend.state.workspace_manager.read();
+        let column = ws.focused_column_index();
+        assert_eq!(ws.windows_in_column(column), vec![kept]);
+    }
+
+    /// A destroyed window's tearing hint is forgotten with it.
+    #[test]
+    fn test_destroyed_window_forgets_its_tearing_hint() {
+        let mut backend = test_backend();
+        backend.state.tearing_surfaces.insert(7);
+        backend.state.tearing_surfaces.insert(8);
+
+        backend.state.destroy_window(7);
+        assert!(!backend.state.tearing_surfaces.contains(&7));
+        assert!(backend.state.tearing_surfaces.contains(&8));
+    }
+
     /// Surface commit counters are populated on surface commit.
     #[test]
     fn test_commit_counter_increments_on_commit() {
@@ -1054,4 +1702,193 @@ mod tests {
             "counter incremented to 3 after 3 commits"
         );
     }
+
+    #[test]
+    fn test_simulated_hotplug_registers_output_and_emits_change() {
+        let mut backend = test_backend();
+        backend.simulate_output_connect("HDMI-A-1", 1920, 1080).unwrap();
+        backend.simulate_output_connect("DP-1", 2560, 1440).unwrap();
+
+        // Each connect advertises a wl_output global
+        let dh = backend.state.display_handle.clone().unwrap();
+        let global = backend.state.output_globals["DP-1"].clone();
+        let info = dh.backend_handle().global_info(global).unwrap();
+        assert_eq!(info.interface.name, "wl_output");
+        assert_eq!(backend.state.outputs.len(), 2);
+
+        let changes = backend.take_output_changes();
+        assert_eq!(changes.len(), 2);
+        assert!(changes[1].connected);
+        assert_eq!(changes[1].name, "DP-1");
+        assert_eq!(
+            changes[1].geometry,
+            crate::window::Rectangle::from_loc_and_size((1920, 0), (2560, 1440))
+        );
+        assert!(backend.take_output_changes().is_empty());
+
+        // Duplicates are refused
+        assert!(backend.simulate_output_connect("DP-1", 800, 600).is_err());
+
+        backend.simulate_output_disconnect("HDMI-A-1").unwrap();
+        assert!(!backend.state.output_globals.contains_key("HDMI-A-1"));
+        let changes = backend.take_output_changes();
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].connected);
+        assert_eq!(changes[0].geometry.width, 1920);
+        assert_eq!(
+            backend.state.workspace_manager.read().known_tape_ids(),
+            vec!["DP-1".to_string()]
+        );
+        // The last output stays
+        assert!(backend.simulate_output_disconnect("DP-1").is_err());
+    }
+
+    /// A lost GPU context is rebuilt on the next cycle, and every window is
+    /// queued to be uploaded and drawn again exactly where it was.
+    #[test]
+    fn test_device_lost_reinitializes_renderer_and_redraws_windows() {
+        use crate::backend::RendererChange;
+
+        let mut backend = test_backend();
+        for surface_id in 1..=2 {
+            let wid = backend
+                .state
+                .window_manager
+                .write()
+                .add_window(format!("Window {}", surface_id));
+            backend.state.workspace_manager.write().add_window(wid);
+            backend.state.window_map.insert(wid, surface_id);
+        }
+        let layouts = backend
+            .state
+            .workspace_manager
+            .read()
+            .calculate_workspace_layouts();
+        backend.run_one_cycle().unwrap();
+        assert!(backend.take_renderer_changes().is_empty());
+
+        // What the render path does when bind or submit reports ContextLost
+        backend.renderer_recovery.lost();
+        backend.run_one_cycle().unwrap();
+        assert_eq!(
+            backend.take_renderer_changes(),
+            vec![
+                RendererChange::Lost,
+                RendererChange::Recovered { windows: 2 }
+            ]
+        );
+        assert!(backend.state.texture_cache.is_empty());
+        assert_eq!(
+            backend
+                .state
+                .workspace_manager
+                .read()
+                .calculate_workspace_layouts(),
+            layouts
+        );
+
+        // Recovered: later cycles leave the renderer alone
+        backend.run_one_cycle().unwrap();
+        assert!(backend.take_renderer_changes().is_empty());
+    }
+
+    /// Two 1000x800 outputs side by side on a headless backend.
+    fn two_output_backend() -> AxiomSmithayBackendReal {
+        let backend = test_backend();
+        {
+            let mut ws = backend.state.workspace_manager.write();
+            ws.sync_tapes_with_outputs(&["output-1".to_string(), "output-2".to_string()], &[]);
+            ws.set_output_viewport("output-1", 1000.0, 800.0);
+            ws.set_output_viewport("output-2", 1000.0, 800.0);
+            assert_eq!(ws.focused_output(), "output-1");
+        }
+        backend
+    }
+
+    #[test]
+    fn test_cursor_placement_opens_window_in_column_under_pointer() {
+        let mut backend = two_output_backend();
+        let id = backend
+            .state
+            .window_manager
+            .write()
+            .add_window("terminal".into());
+        // Column 0 of output-2 starts at its middle, x = 1500
+        backend.state.pointer_x = 1600.0;
+        backend.state.pointer_y = 400.0;
+        backend.state.place_new_window(id, "cursor");
+
+        let ws = backend.state.workspace_manager.read();
+        assert_eq!(ws.window_output_id(id), Some("output-2"));
+        let rect = ws.calculate_workspace_layouts()[&id].clone();
+        assert!(rect.contains_point(1600, 400), "window at {:?}", rect);
+    }
+
+    #[test]
+    fn test_center_placement_floats_window_at_output_midpoint() {
+        let mut backend = two_output_backend();
+        let id = backend
+            .state
+            .window_manager
+            .write()
+            .add_window("dialog".into());
+        backend.state.place_new_window(id, "center");
+
+        assert!(backend
+            .state
+            .workspace_manager
+            .read()
+            .is_window_floating(id));
+        let rect = backend
+            .state
+            .window_manager
+            .read()
+            .get_window(id)
+            .unwrap()
+            .rect();
+        let middle = (
+            rect.x + rect.width as i32 / 2,
+            rect.y + rect.height as i32 / 2,
+        );
+        assert_eq!(middle, (500, 400));
+    }
+
+    #[test]
+    fn test_hud_toggle_adds_and_removes_overlay() {
+        let mut backend = test_backend();
+        assert!(backend.state.hud.draw_commands().is_none());
+
+        backend.set_hud(true);
+        assert!(backend.hud_enabled());
+        assert!(backend.state.hud.draw_commands().is_some());
+
+        backend.set_hud(false);
+        assert!(backend.state.hud.draw_commands().is_none());
+    }
+
+    #[test]
+    fn test_output_wallpaper_change_fades_in() {
+        let mut backend = test_backend();
+        backend.simulate_output_connect("DP-1", 2560, 1440).unwrap();
+        let wall = Some("/walls/a.png");
+        assert!(backend.set_output_wallpaper("HDMI-A-9", wall).is_err());
+
+        backend.set_output_wallpaper("DP-1", wall).unwrap();
+        let started = Instant::now();
+        let progress = backend.state.backgrounds.fade_progress("DP-1", started);
+        assert!(progress.is_some_and(|p| p < 1.0));
+        assert!(backend.state.backgrounds.update(started));
+
+        // With reduce_motion the next change shows at once
+        backend.state.config.effects.reduce_motion = true;
+        backend
+            .set_output_background_color("DP-1", Some([0.2, 0.4, 0.6, 1.0]))
+            .unwrap();
+        backend.set_output_wallpaper("DP-1", None).unwrap();
+        let now = Instant::now();
+        let backgrounds = &mut backend.state.backgrounds;
+        assert_eq!(backgrounds.fade_progress("DP-1", now), Some(1.0));
+        backgrounds.update(now);
+        assert_eq!(backgrounds.fade_progress("DP-1", now), None);
+    }
 }
@@ -10,7 +10,8 @@
 //!
 //! Uses a calloop `EventLoop` with two event sources:
 //! - A `Signals` source for SIGTERM/SIGINT handling
-//! - A `Timer` source for frame pacing (drives `tick()` at the configured FPS)
+//! - A `Timer` source for frame pacing (drives `tick()` on the output's
+//!   predicted vblank, no faster than `general.max_fps`)
 //!
 //! All subsystems are shared via `parking_lot::RwLock`. Window-correlated
 //! locks are conventionally taken in the order `workspace` → `window_manager`
@@ -22,21 +23,80 @@ use calloop::signals::{Signal, Signals};
 use calloop::timer::{TimeoutAction, Timer};
 use calloop::EventLoop;
 use log::{debug, info, warn};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::backend::AxiomSmithayBackendReal;
+use crate::backend::{AxiomSmithayBackendReal, RendererChange};
 use crate::config::AxiomConfig;
 use crate::decoration::DecorationManager;
 use crate::input::InputManager;
-use crate::ipc::{AxiomIPCServer, LazyUIMessage, LiveMetrics};
-use crate::window::WindowManager;
+use crate::ipc::{AxiomIPCServer, LazyUIMessage, LiveMetrics, WindowInfo, WorkspaceSnapshot};
+use crate::window::{WindowManager, WindowOperation};
 use crate::workspace::ScrollableWorkspaces;
 
 use std::sync::Arc;
 
+/// How long a requested redraw may go unpresented before the watchdog
+/// reports the renderer as stalled.
+const RENDER_STALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ticks taking longer than this (three frames at 60 Hz) are logged.
+const SLOW_TICK_MS: f32 = 50.0;
+
+/// How often the power source is checked when `[auto_profile]` is set.
+const POWER_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Notices when redraws keep being requested but no frame reaches the
+/// screen (rendering failing every tick, a lost GL context, ...), which
+/// otherwise just looks like a frozen screen.
+#[derive(Debug)]
+struct RenderWatchdog {
+    timeout: Duration,
+    stalled: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum WatchdogEvent {
+    /// A redraw has been pending for this long.
+    Stalled(Duration),
+    /// Frames are being presented again.
+    Recovered,
+}
+
+impl RenderWatchdog {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            stalled: false,
+        }
+    }
+
+    /// Check the oldest pending redraw at `now`. Reports each stall once,
+    /// and once more when it clears.
+    fn check(&mut self, now: Instant, pending_since: Option<Instant>) -> Option<WatchdogEvent> {
+        let waited = pending_since.map(|since| now.saturating_duration_since(since));
+        match (waited, self.stalled) {
+            (Some(waited), false) if waited >= self.timeout => {
+                self.stalled = true;
+                Some(WatchdogEvent::Stalled(waited))
+            }
+            (None, true) => {
+                self.stalled = false;
+                Some(WatchdogEvent::Recovered)
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Main compositor struct that orchestrates all subsystems
 pub struct AxiomCompositor {
     config: AxiomConfig,
+    /// The configuration as loaded, which profiles are applied on top of
+    base_config: AxiomConfig,
+    /// Name of the profile last applied, if any
+    active_profile: Option<String>,
+    /// Power source seen by the last `[auto_profile]` poll
+    on_battery: Option<bool>,
     running: bool,
     _windowed: bool,
 
@@ -56,6 +116,9 @@ pub struct AxiomCompositor {
 
     // Smithay Backend
     smithay_backend: AxiomSmithayBackendReal,
+
+    /// Reports redraws that never make it to the screen.
+    render_watchdog: RenderWatchdog,
 }
 
 impl AxiomCompositor {
@@ -79,11 +142,13 @@ impl AxiomCompositor {
 
         // Initialize server-side decoration manager (must be created before
         // the Smithay backend so it can receive a clone).
-        let minimize_enabled = config.features.enable_minimize;
-        let decoration_manager = Arc::new(parking_lot::RwLock::new(DecorationManager::new(
-            &config.window,
-            minimize_enabled,
-        )));
+        let decoration_manager = Arc::new(parking_lot::RwLock::new(
+            DecorationManager::from_config(&config),
+        ));
+
+        workspace_manager
+            .write()
+            .set_reduce_motion(config.effects.reduce_motion);
 
         let smithay_backend = {
             info!("Initializing Axiom compositor with Smithay backend...");
@@ -102,7 +167,10 @@ impl AxiomCompositor {
         };
 
         Ok(Self {
+            base_config: config.clone(),
             config,
+            active_profile: None,
+            on_battery: None,
             _windowed: windowed,
             workspace_manager,
             window_manager,
@@ -112,6 +180,7 @@ impl AxiomCompositor {
             consecutive_error_count: 0,
             force_next_tick_error: false,
             decoration_manager,
+            render_watchdog: RenderWatchdog::new(RENDER_STALL_TIMEOUT),
             running: true,
         })
     }
@@ -143,14 +212,14 @@ impl AxiomCompositor {
             )
             .map_err(|e| anyhow::anyhow!("Failed to insert signal source: {}", e))?;
 
-        // Frame pacing timer — fires every `interval` and calls tick()
-        let interval = if self.config.general.max_fps == 0 {
-            Duration::from_millis(16) // unbounded → default ~60fps
+        // Frame pacing timer — calls tick() and re-arms for the next vblank
+        let min_interval = if self.config.general.max_fps == 0 {
+            Duration::ZERO // unbounded → every refresh
         } else {
             let clamped = self.config.general.max_fps.clamp(1, 1000);
             Duration::from_secs_f64(1.0 / f64::from(clamped))
         };
-        let timer = Timer::from_duration(interval);
+        let timer = Timer::from_duration(min_interval);
         handle
             .insert_source(
                 timer,
@@ -163,7 +232,11 @@ impl AxiomCompositor {
                             return TimeoutAction::Drop;
                         }
                         // Re-arm timer for next frame
-                        TimeoutAction::ToDuration(interval)
+                        TimeoutAction::ToInstant(
+                            compositor
+                                .smithay_backend
+                                .next_frame_deadline(Instant::now(), min_interval),
+                        )
                     } else {
                         sig_for_timer.stop();
                         TimeoutAction::Drop
@@ -172,6 +245,19 @@ impl AxiomCompositor {
             )
             .map_err(|e| anyhow::anyhow!("Failed to insert timer source: {}", e))?;
 
+        // Power source polling — switches profiles per `[auto_profile]`
+        if self.config.auto_profile.is_enabled() {
+            handle
+                .insert_source(
+                    Timer::immediate(),
+                    |_event, _metadata, compositor: &mut AxiomCompositor| {
+                        compositor.power_source_changed(crate::power::detect_on_battery());
+                        TimeoutAction::ToDuration(POWER_POLL_INTERVAL)
+                    },
+                )
+                .map_err(|e| anyhow::anyhow!("Failed to insert power poll timer: {}", e))?;
+        }
+
         // Run the event loop — dispatches events, calls timer and signal callbacks
         event_loop.run(None, &mut *self, |_| {})?;
 
@@ -214,10 +300,25 @@ impl AxiomCompositor {
                             debug!("Set blur radius {} for window {}", radius, window_id);
                             self.smithay_backend.state.needs_redraw = true;
                         }
+                        LazyUIMessage::WindowOp { window_id, op } => {
+                            self.dispatch_window_op(window_id, &op);
+                        }
                         LazyUIMessage::StartDnd { text, mime_type } => {
                             info!("📱 Starting server DnD with {} bytes via {}", text.len(), mime_type);
                             self.smithay_backend.start_server_dnd(text.into_bytes(), mime_type);
                         }
+                        LazyUIMessage::SetProfile { name } => {
+                            if let Err(e) = self.set_profile(&name) {
+                                warn!("SetProfile {} failed: {:#}", name, e);
+                            }
+                        }
+                        LazyUIMessage::Shutdown => {
+                            info!("Shutdown requested over IPC, shutting down gracefully");
+                            // The frame timer sees `running` cleared and
+                            // stops the event loop
+                            self.shutdown()?;
+                            return Ok(());
+                        }
                         _ => {
                             warn!("Unexpected pending action variant from IPC queue");
                         }
@@ -229,6 +330,30 @@ impl AxiomCompositor {
             }
         }
 
+        // Tell IPC clients about outputs that came or went, whether from the
+        // backend or a hotplug command handled just above.
+        for change in self.smithay_backend.take_output_changes() {
+            let g = &change.geometry;
+            self.ipc_server.broadcast_output_changed(
+                &change.name,
+                change.connected,
+                (g.x, g.y, g.width, g.height),
+            );
+        }
+
+        for change in self.smithay_backend.take_renderer_changes() {
+            match change {
+                RendererChange::Lost => {
+                    self.ipc_server
+                        .broadcast_state_change("renderer", "presenting", "lost");
+                }
+                RendererChange::Recovered { .. } => {
+                    self.ipc_server
+                        .broadcast_state_change("renderer", "lost", "presenting");
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -310,6 +435,173 @@ impl AxiomCompositor {
                     }
                 }
             }
+            "tile_floating" => {
+                let area = crate::window::Rectangle::from_loc_and_size(
+                    (0, 0),
+                    (
+                        self.smithay_backend.state.window_width,
+                        self.smithay_backend.state.window_height,
+                    ),
+                );
+                let (inner_gap, outer_gap) = (
+                    self.config.workspace.inner_gap(),
+                    self.config.workspace.outer_gap(),
+                );
+                let arranged = self
+                    .window_manager
+                    .write()
+                    .tile_floating_into_grid(area, inner_gap, outer_gap);
+                for &window_id in &arranged {
+                    self.smithay_backend.state.configure_window_size(window_id);
+                }
+                debug!(
+                    "WorkspaceCommand tile_floating: arranged {} window(s)",
+                    arranged.len()
+                );
+                self.smithay_backend.state.needs_redraw = true;
+            }
+            "scroll_to_named" => match parameters.get("name").and_then(|v| v.as_str()) {
+                Some(name) => self.scroll_workspace_to_named(name),
+                None => {
+                    warn!("WorkspaceCommand scroll_to_named missing 'name' parameter — no-op")
+                }
+            },
+            "name_column" => {
+                let index = parameters
+                    .get("index")
+                    .and_then(|v| v.as_i64())
+                    .and_then(|v| i32::try_from(v).ok());
+                let name = parameters.get("name").and_then(|v| v.as_str());
+                match (index, name) {
+                    (Some(index), Some(name)) => {
+                        if !self.workspace_manager.write().name_column(index, name) {
+                            warn!("WorkspaceCommand name_column rejected an empty name");
+                        }
+                    }
+                    _ => warn!(
+                        "WorkspaceCommand name_column missing 'index' or 'name' parameter — no-op"
+                    ),
+                }
+            }
+            "scroll_to_window" => match parameters.get("window_id").and_then(|v| v.as_u64()) {
+                Some(id) => self.scroll_workspace_to_window(id),
+                None => {
+                    warn!("WorkspaceCommand scroll_to_window missing 'window_id' parameter — no-op")
+                }
+            },
+            "mirror" => {
+                let source = parameters.get("source").and_then(|v| v.as_str());
+                let target = parameters.get("target").and_then(|v| v.as_str());
+                match (source, target) {
+                    (Some(source), Some(target)) => {
+                        if let Err(e) = self.smithay_backend.set_output_mirror(target, Some(source)) {
+                            warn!("WorkspaceCommand mirror rejected: {}", e);
+                        }
+                    }
+                    _ => warn!(
+                        "WorkspaceCommand mirror missing 'source' or 'target' parameter — no-op"
+                    ),
+                }
+            }
+            "unmirror" => match parameters.get("target").and_then(|v| v.as_str()) {
+                Some(target) => {
+                    let _ = self.smithay_backend.set_output_mirror(target, None);
+                }
+                None => {
+                    warn!("WorkspaceCommand unmirror missing 'target' parameter — no-op")
+                }
+            },
+            "set_background" => {
+                let output = parameters.get("output").and_then(|v| v.as_str());
+                let color = parameters.get("color").and_then(|v| v.as_str());
+                let wallpaper = parameters.get("wallpaper").and_then(|v| v.as_str());
+                match output {
+                    Some(_) if color.is_none() && wallpaper.is_none() => warn!(
+                        "WorkspaceCommand set_background missing 'color' or 'wallpaper' parameter — no-op"
+                    ),
+                    Some(output) => {
+                        if let Err(e) = self.set_output_background(output, color, wallpaper) {
+                            warn!("WorkspaceCommand set_background rejected: {}", e);
+                        }
+                    }
+                    None => {
+                        warn!("WorkspaceCommand set_background missing 'output' parameter — no-op")
+                    }
+                }
+            }
+            "clear_background" => match parameters.get("output").and_then(|v| v.as_str()) {
+                Some(output) => {
+                    if let Err(e) = self.clear_output_background(output) {
+                        warn!("WorkspaceCommand clear_background rejected: {}", e);
+                    }
+                }
+                None => {
+                    warn!("WorkspaceCommand clear_background missing 'output' parameter — no-op")
+                }
+            },
+            "select_window" => match parameters.get("window_id").and_then(|v| v.as_u64()) {
+                Some(id) => {
+                    if !self.window_manager.write().add_to_selection(id) {
+                        debug!("WorkspaceCommand select_window: {} unknown or already selected", id);
+                    }
+                    self.smithay_backend.state.needs_redraw = true;
+                }
+                None => {
+                    warn!("WorkspaceCommand select_window missing 'window_id' parameter — no-op")
+                }
+            },
+            "clear_selection" => {
+                self.window_manager.write().clear_selection();
+                self.smithay_backend.state.needs_redraw = true;
+            }
+            "move_selected_to_column" => {
+                match parameters.get("column").and_then(|v| v.as_i64()) {
+                    Some(column) => self
+                        .smithay_backend
+                        .state
+                        .move_selected_to_column(Some(column as i32)),
+                    None => warn!(
+                        "WorkspaceCommand move_selected_to_column missing 'column' parameter — no-op"
+                    ),
+                }
+            }
+            "close_selected" => self.smithay_backend.state.close_selected_windows(),
+            "hotplug_connect" => {
+                let name = parameters.get("name").and_then(|v| v.as_str());
+                let size = |key: &str| parameters.get(key).and_then(|v| v.as_u64());
+                match (name, size("width"), size("height")) {
+                    (Some(name), Some(width), Some(height)) => {
+                        if let Err(e) = self.smithay_backend.simulate_output_connect(
+                            name,
+                            width as u32,
+                            height as u32,
+                        ) {
+                            warn!("WorkspaceCommand hotplug_connect rejected: {}", e);
+                        }
+                    }
+                    _ => warn!(
+                        "WorkspaceCommand hotplug_connect missing 'name', 'width' or 'height' parameter — no-op"
+                    ),
+                }
+            }
+            "hotplug_disconnect" => match parameters.get("name").and_then(|v| v.as_str()) {
+                Some(name) => {
+                    if let Err(e) = self.smithay_backend.simulate_output_disconnect(name) {
+                        warn!("WorkspaceCommand hotplug_disconnect rejected: {}", e);
+                    }
+                }
+                None => {
+                    warn!("WorkspaceCommand hotplug_disconnect missing 'name' parameter — no-op")
+                }
+            },
+            "toggle_hud" => {
+                // An explicit `enabled` sets the HUD; without one it flips
+                let enabled = parameters
+                    .get("enabled")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(!self.smithay_backend.hud_enabled());
+                self.smithay_backend.set_hud(enabled);
+            }
             // Defensive catch-all. The IPC layer's whitelist already rejects
             // unknown actions, so reaching here means a future handler or
             // schema change introduced a mismatch — surface it loudly.
@@ -364,6 +656,58 @@ impl AxiomCompositor {
         &self.config
     }
 
+    /// Apply config profile `name`: its `[effects]`, `[workspace]` and
+    /// `[input]` settings, on top of the configuration the compositor was
+    /// started with, replace the active ones. Settings changed at runtime
+    /// in those sections are dropped.
+    pub fn set_profile(&mut self, name: &str) -> Result<()> {
+        let profiled = self.base_config.with_profile(name)?;
+        self.config.effects = profiled.effects;
+        self.config.workspace = profiled.workspace;
+        self.config.input = profiled.input;
+        self.active_profile = Some(name.to_string());
+        info!("🎛️ Switched to profile {}", name);
+        self.update_subsystems_config();
+        self.ipc_server
+            .set_config_handle(Arc::new(parking_lot::RwLock::new(self.config.clone())));
+        Ok(())
+    }
+
+    /// Name of the profile last applied, if any
+    pub fn active_profile(&self) -> Option<&str> {
+        self.active_profile.as_deref()
+    }
+
+    /// React to the power source as last detected (see
+    /// [`crate::power::detect_on_battery`]): switch to the profile
+    /// `[auto_profile]` names for it when the source changed, or at the
+    /// first reading if no profile was picked yet, so `--profile` still
+    /// wins at startup.
+    pub fn power_source_changed(&mut self, on_battery: Option<bool>) {
+        let Some(battery) = on_battery else {
+            return;
+        };
+        let first_reading = self.on_battery.is_none();
+        if self.on_battery.replace(battery) == Some(battery)
+            || (first_reading && self.active_profile.is_some())
+        {
+            return;
+        }
+        let auto = &self.base_config.auto_profile;
+        let profile = if battery {
+            &auto.on_battery
+        } else {
+            &auto.on_ac
+        };
+        let Some(name) = profile.clone() else {
+            return;
+        };
+        info!("🔋 Running on {}", if battery { "battery" } else { "AC" });
+        if let Err(e) = self.set_profile(&name) {
+            warn!("Profile {} failed to apply: {:#}", name, e);
+        }
+    }
+
     /// Check if compositor is running in windowed mode
     pub fn is_windowed(&self) -> bool {
         self._windowed
@@ -379,10 +723,30 @@ impl AxiomCompositor {
     /// Frame pacing is handled by the calloop timer in `run()`, so this
     /// method is purely synchronous: process events, render, update metrics.
     fn tick(&mut self) -> Result<()> {
-        use std::time::Instant;
         let frame_start = Instant::now();
         let mut tick_error = false;
 
+        // A redraw still pending from earlier ticks means nothing was presented
+        match self
+            .render_watchdog
+            .check(frame_start, self.smithay_backend.redraw_pending_since())
+        {
+            Some(WatchdogEvent::Stalled(waited)) => {
+                log::error!(
+                    "🚨 Renderer stalled: a redraw has been pending for {:.1}s with no frame presented",
+                    waited.as_secs_f32()
+                );
+                self.ipc_server
+                    .broadcast_state_change("renderer", "presenting", "stalled");
+            }
+            Some(WatchdogEvent::Recovered) => {
+                info!("✅ Renderer recovered, frames are being presented again");
+                self.ipc_server
+                    .broadcast_state_change("renderer", "stalled", "presenting");
+            }
+            None => {}
+        }
+
         // Process events (calls backend.process_events → run_one_cycle → render)
         if self.force_next_tick_error {
             tick_error = true;
@@ -392,6 +756,10 @@ impl AxiomCompositor {
             tick_error = true;
             warn!("Error processing events: {}", e);
         }
+        if !self.running {
+            // Shut down while handling events; nothing left to render to
+            return Ok(());
+        }
 
         // Render frame — post-render monitoring.
         if let Err(e) = self.render_frame() {
@@ -414,16 +782,31 @@ impl AxiomCompositor {
             let active_windows = self.window_manager.read().window_count();
             (frame_time_ms, active_windows, workspace_idx)
         };
-        self.ipc_server.maybe_broadcast_performance_metrics(
-            frame_time_ms,
-            active_windows,
-            workspace_idx,
-        );
-        self.ipc_server.set_live_metrics_snapshot(LiveMetrics {
+        if frame_time_ms > SLOW_TICK_MS {
+            warn!(frame_time_ms = frame_time_ms; "🐢 Slow tick: {:.1}ms", frame_time_ms);
+        }
+        let present = self.smithay_backend.present_counters();
+        let live_metrics = LiveMetrics {
             frame_time_ms,
             active_windows,
             current_workspace: workspace_idx,
-        });
+            frames_presented: present.presented,
+            frames_dropped: present.dropped,
+            surface_lost: present.lost,
+        };
+        self.ipc_server.maybe_broadcast_performance_metrics(&live_metrics);
+        self.ipc_server.set_live_metrics_snapshot(live_metrics);
+        let workspace_snapshot = WorkspaceSnapshot::capture(&self.workspace_manager.read());
+        self.ipc_server.set_workspace_snapshot(workspace_snapshot);
+        let window_ids = self.window_manager.read().window_ids();
+        let windows = self.window_infos(&window_ids);
+        self.ipc_server.set_window_snapshot(windows);
+        let tree = self
+            .window_manager
+            .read()
+            .get_window_tree(&self.smithay_backend.popups());
+        self.ipc_server.set_window_tree_snapshot(tree);
+        self.ipc_server.set_known_windows(window_ids);
 
         // Check stability threshold
         if self.consecutive_error_count >= 5 {
@@ -440,6 +823,32 @@ impl AxiomCompositor {
         Ok(())
     }
 
+    /// Windows `ids` with their frame statistics, as `GetWindows` reports
+    /// them.
+    fn window_infos(&mut self, ids: &[u64]) -> Vec<WindowInfo> {
+        let stats = self.smithay_backend.window_stats();
+        let wm = self.window_manager.read();
+        ids.iter()
+            .filter_map(|&id| {
+                let axiom_window = wm.get_window(id)?;
+                let window = &axiom_window.window;
+                let client = axiom_window.client.as_ref();
+                let stats = stats.get(&id).copied().unwrap_or_default();
+                Some(WindowInfo {
+                    id,
+                    title: window.title.clone(),
+                    app_id: window.app_id.clone(),
+                    pid: client.map(|client| client.pid),
+                    executable: client.and_then(|client| client.executable.clone()),
+                    commits_per_sec: stats.commits_per_sec,
+                    bytes_per_sec: stats.bytes_per_sec,
+                    last_frame_interval_ms: stats.last_frame_interval_ms,
+                    high_bandwidth: stats.high_bandwidth,
+                })
+            })
+            .collect()
+    }
+
     // === Public Workspace Interaction Methods ===
 
     /// Scroll workspace left (for input handling)
@@ -474,6 +883,80 @@ impl AxiomCompositor {
         );
     }
 
+    /// Scroll to the column the user named `name`. Unknown names are a
+    /// no-op so a stale bar binding cannot move the view.
+    pub fn scroll_workspace_to_named(&mut self, name: &str) {
+        let mut wm = self.workspace_manager.write();
+        let old_idx = wm.focused_column_index();
+        if !wm.scroll_to_named(name) {
+            drop(wm);
+            debug!("No column named '{}', ignoring", name);
+            return;
+        }
+        let new_idx = wm.focused_column_index();
+        drop(wm);
+        info!("Scrolling workspace to '{}' (column {})", name, new_idx);
+        self.smithay_backend.state.needs_redraw = true;
+        self.ipc_server.broadcast_state_change(
+            "workspace",
+            &old_idx.to_string(),
+            &new_idx.to_string(),
+        );
+    }
+
+    /// Give output `output` a background `color` (`#RRGGBB`) and/or a
+    /// `wallpaper` image, fading from the old one.
+    fn set_output_background(
+        &mut self,
+        output: &str,
+        color: Option<&str>,
+        wallpaper: Option<&str>,
+    ) -> Result<()> {
+        if let Some(hex) = color {
+            let Some(rgba) = DecorationManager::parse_color(hex) else {
+                anyhow::bail!("background color must be #RRGGBB, got {:?}", hex);
+            };
+            self.smithay_backend
+                .set_output_background_color(output, Some(rgba))?;
+        }
+        if let Some(path) = wallpaper {
+            self.smithay_backend
+                .set_output_wallpaper(output, Some(path))?;
+        }
+        Ok(())
+    }
+
+    /// Take output `output` back to the global clear color.
+    fn clear_output_background(&mut self, output: &str) -> Result<()> {
+        self.smithay_backend.set_output_wallpaper(output, None)?;
+        self.smithay_backend
+            .set_output_background_color(output, None)
+    }
+
+    /// Scroll to the column holding `window_id`, switching to its output.
+    /// Unknown windows are a no-op, like unknown column names.
+    pub fn scroll_workspace_to_window(&mut self, window_id: u64) {
+        let mut wm = self.workspace_manager.write();
+        let old_idx = wm.focused_column_index();
+        if !wm.scroll_to_window(window_id) {
+            drop(wm);
+            debug!("Window {} is in no column, ignoring", window_id);
+            return;
+        }
+        let new_idx = wm.focused_column_index();
+        drop(wm);
+        info!(
+            "Scrolling workspace to window {} (column {})",
+            window_id, new_idx
+        );
+        self.smithay_backend.state.needs_redraw = true;
+        self.ipc_server.broadcast_state_change(
+            "workspace",
+            &old_idx.to_string(),
+            &new_idx.to_string(),
+        );
+    }
+
     /// Add a new window to the current workspace.
     /// Also registers the window with the server-side decoration manager so
     /// titlebar buttons are positioned from real geometry (not a placeholder).
@@ -533,7 +1016,7 @@ impl AxiomCompositor {
 
         if removed {
             self.smithay_backend.state.needs_redraw = true;
-            info!("Removed window {}", window_id);
+            info!(window_id = window_id; "Removed window {}", window_id);
             self.ipc_server.broadcast_state_change(
                 "window",
                 &format!("active:{}", window_id),
@@ -581,7 +1064,7 @@ impl AxiomCompositor {
         let wm_ok = self.window_manager.write().minimize_window(window_id);
         if workspace_ok || wm_ok {
             self.smithay_backend.state.needs_redraw = true;
-            info!("Minimized window {}", window_id);
+            info!(window_id = window_id; "Minimized window {}", window_id);
             self.ipc_server.broadcast_state_change(
                 "window",
                 &format!("active:{}", window_id),
@@ -599,7 +1082,7 @@ impl AxiomCompositor {
         let wm_ok = self.window_manager.write().restore_window(window_id);
         if workspace_ok || wm_ok {
             self.smithay_backend.state.needs_redraw = true;
-            info!("Restored window {}", window_id);
+            info!(window_id = window_id; "Restored window {}", window_id);
             self.ipc_server.broadcast_state_change(
                 "window",
                 "minimized",
@@ -609,6 +1092,53 @@ impl AxiomCompositor {
         workspace_ok || wm_ok
     }
 
+    /// Apply an IPC `WindowOp`. The IPC layer checked the window against
+    /// the last tick's snapshot, but it may have gone since, so a failure
+    /// here is only logged.
+    fn dispatch_window_op(&mut self, window_id: u64, op: &WindowOperation) {
+        let work_area = {
+            let ws = self.workspace_manager.read();
+            ws.output_work_area(ws.output_for_window(window_id))
+        };
+        let Some(work_area) = work_area else {
+            warn!("WindowOp {:?}: no output for window {}", op, window_id);
+            return;
+        };
+        let grace = Duration::from_millis(self.config.window.close_grace_ms);
+        let result = self
+            .window_manager
+            .write()
+            .execute_operation(window_id, op, work_area, grace);
+        if let Err(e) = result {
+            warn!("WindowOp {:?} on window {} failed: {}", op, window_id, e);
+            return;
+        }
+
+        // Keep the layout and the client in step with the window manager
+        match op {
+            WindowOperation::Minimize => {
+                self.workspace_manager.write().minimize_window(window_id);
+            }
+            WindowOperation::Restore => {
+                self.workspace_manager.write().restore_window(window_id);
+            }
+            WindowOperation::Maximize | WindowOperation::Unmaximize => {
+                self.smithay_backend.state.send_maximized_state(window_id);
+            }
+            WindowOperation::Close => {
+                self.smithay_backend.state.request_window_close(window_id);
+            }
+            WindowOperation::SetOpacity { .. } => {}
+        }
+        self.smithay_backend.state.needs_redraw = true;
+        info!(window_id = window_id; "Applied {:?} to window {}", op, window_id);
+        self.ipc_server.broadcast_state_change(
+            "window",
+            &format!("active:{}", window_id),
+            &format!("{:?}", op),
+        );
+    }
+
     /// Toggle fullscreen on a window.
     pub fn toggle_fullscreen(&mut self, window_id: u64) {
         self.smithay_backend.state.toggle_fullscreen_window(window_id);
@@ -670,13 +1200,17 @@ impl AxiomCompositor {
         info!("Propagating configuration changes to subsystems...");
 
         // Update Workspace Manager
-        self.workspace_manager
-            .write()
-            .update_config(self.config.workspace.clone());
+        {
+            let mut workspaces = self.workspace_manager.write();
+            workspaces.update_config(self.config.workspace.clone());
+            workspaces.set_reduce_motion(self.config.effects.reduce_motion);
+        }
 
-        self.smithay_backend.state.needs_redraw = true;
+        self.input_manager.write().update_config(&self.config.input);
 
-        // Future: Update Input Manager, etc.
+        // Colour adjustment is read by the render pass every frame
+        self.smithay_backend.state.config.output = self.config.output.clone();
+        self.smithay_backend.apply_config(&self.config);
     }
 }
 
@@ -691,6 +1225,11 @@ impl AxiomCompositor {
         self.ipc_server.command_sender_for_test()
     }
 
+    /// Path of the IPC socket, for tests that talk to it like a client.
+    pub fn ipc_socket_path(&self) -> &std::path::Path {
+        self.ipc_server.socket_path()
+    }
+
     /// Test/debug accessor — see `AxiomSmithayBackendReal::debug_clipboard_cache`.
     pub fn debug_clipboard_cache(&self) -> Option<Vec<u8>> {
         self.smithay_backend.debug_clipboard_cache()
@@ -714,11 +1253,9 @@ impl AxiomCompositor {
         let ipc_server = AxiomIPCServer::new();
 
         // Initialize server-side decoration manager for tests
-        let minimize_enabled = config.features.enable_minimize;
-        let decoration_manager = Arc::new(parking_lot::RwLock::new(DecorationManager::new(
-            &config.window,
-            minimize_enabled,
-        )));
+        let decoration_manager = Arc::new(parking_lot::RwLock::new(
+            DecorationManager::from_config(&config),
+        ));
 
         // Test Smithay backend (no socket bind, no GPU init)
         let smithay_backend = AxiomSmithayBackendReal::new_for_test(
@@ -730,7 +1267,10 @@ impl AxiomCompositor {
         )?;
 
         Ok(Self {
+            base_config: config.clone(),
             config,
+            active_profile: None,
+            on_battery: None,
             _windowed: false,
             workspace_manager,
             window_manager,
@@ -740,6 +1280,7 @@ impl AxiomCompositor {
             consecutive_error_count: 0,
             force_next_tick_error: false,
             decoration_manager,
+            render_watchdog: RenderWatchdog::new(RENDER_STALL_TIMEOUT),
             running: true, // Test compositor starts in running state
         })
     }
@@ -888,6 +1429,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_render_watchdog_fires_once_per_stall() {
+        let mut watchdog = RenderWatchdog::new(Duration::from_secs(5));
+        let start = Instant::now();
+        assert_eq!(watchdog.check(start, None), None);
+        assert_eq!(watchdog.check(start + Duration::from_secs(4), Some(start)), None);
+        assert_eq!(
+            watchdog.check(start + Duration::from_secs(6), Some(start)),
+            Some(WatchdogEvent::Stalled(Duration::from_secs(6)))
+        );
+        assert_eq!(watchdog.check(start + Duration::from_secs(9), Some(start)), None);
+        assert_eq!(
+            watchdog.check(start + Duration::from_secs(10), None),
+            Some(WatchdogEvent::Recovered)
+        );
+    }
+
+    /// A producer whose redraws stopped reaching the screen trips the
+    /// watchdog; the next presented frame clears it.
+    #[test]
+    #[serial]
+    fn test_stalled_redraw_trips_watchdog() {
+        let mut comp = make_test_compositor();
+        comp.smithay_backend.state.needs_redraw = true;
+        comp.smithay_backend.state.redraw_pending_since =
+            Some(Instant::now() - Duration::from_secs(30));
+        comp.tick_for_test().expect("tick");
+        assert!(comp.render_watchdog.stalled);
+
+        // The headless backend "presents" the redraw during that tick
+        assert_eq!(comp.smithay_backend.redraw_pending_since(), None);
+        comp.tick_for_test().expect("tick");
+        assert!(!comp.render_watchdog.stalled);
+    }
+
+    #[test]
+    #[serial]
+    fn test_set_background_action_reaches_known_outputs_only() {
+        let mut comp = make_test_compositor();
+        comp.smithay_backend
+            .simulate_output_connect("DP-1", 1920, 1080)
+            .unwrap();
+        comp.set_output_background("DP-1", Some("#336699"), None)
+            .unwrap();
+        let bad_color = comp.set_output_background("DP-1", Some("blue"), None);
+        assert!(bad_color.is_err());
+        let unknown = comp.set_output_background("HDMI-A-9", Some("#336699"), None);
+        assert!(unknown.is_err());
+        comp.clear_output_background("DP-1").unwrap();
+
+        comp.smithay_backend.state.needs_redraw = false;
+        let params = serde_json::json!({ "output": "DP-1", "wallpaper": "/walls/a.png" });
+        comp.dispatch_workspace_command("set_background", &params);
+        assert!(comp.smithay_backend.state.needs_redraw);
+    }
+
     #[test]
     #[serial]
     fn test_shutdown_cleans_up() {
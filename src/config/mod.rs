@@ -6,8 +6,9 @@
 //!
 //! The configuration is composed of several sections:
 //! - [`WorkspaceConfig`]: Scrollable workspace behavior
-//! - // Visual effects config removed with effects engine
+//! - [`EffectsConfig`]: Lightweight visual effects (inactive dimming)
 //! - [`WindowConfig`]: Window management and placement
+//! - [`DecorationConfig`]: Server-side titlebar look
 //! - [`InputConfig`]: Input device handling
 //! - [`BindingsConfig`]: Key binding mappings
 //! - [`GeneralConfig`]: Global compositor settings
@@ -15,6 +16,7 @@
 use anyhow::{Context, Result};
 use log::warn;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -29,6 +31,10 @@ pub struct AxiomConfig {
     #[serde(default)]
     pub window: WindowConfig,
 
+    /// Server-side titlebar theme
+    #[serde(default)]
+    pub decoration: DecorationConfig,
+
     /// Input handling and keybindings
     #[serde(default)]
     pub input: InputConfig,
@@ -64,10 +70,333 @@ pub struct AxiomConfig {
     /// General compositor settings
     #[serde(default)]
     pub general: GeneralConfig,
+
+    /// Visual effects applied by the GLES render path
+    #[serde(default)]
+    pub effects: EffectsConfig,
+
+    /// Per-window overrides (`[[window_rules]]`), applied on window creation
+    #[serde(default)]
+    pub window_rules: Vec<WindowRule>,
+
+    /// Per-output layout defaults (`[[outputs]]`), applied to the windows
+    /// tiled on a matching output
+    #[serde(default)]
+    pub outputs: Vec<OutputRule>,
+
+    /// Latency/throughput trade-offs
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+
+    /// How misbehaving clients are dealt with
+    #[serde(default)]
+    pub security: SecurityConfig,
+
+    /// Named sets of `[effects]`, `[workspace]` and `[input]` overrides
+    /// (`[profiles.<name>]`), applied with `--profile`, the `SetProfile`
+    /// IPC request or `[auto_profile]`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ConfigProfile>,
+
+    /// Profiles switched to automatically when the power source changes
+    #[serde(default)]
+    pub auto_profile: AutoProfileConfig,
 }
 
-/// Output configuration (multi-monitor layout)
+/// Latency-related settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PerformanceConfig {
+    /// Advertise `wp_tearing_control_v1` so clients can ask for async
+    /// presentation. Their hints are recorded, but every output still
+    /// presents FIFO, so nothing tears yet.
+    #[serde(default)]
+    pub allow_tearing: bool,
+
+    /// Most client buffer data copied to the GPU in one frame, in MiB.
+    /// Uploads over it wait for later frames, focused and on-screen
+    /// windows first. `0` uploads everything at once.
+    #[serde(default = "PerformanceConfig::default_upload_budget_mb")]
+    pub upload_budget_mb: u32,
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            allow_tearing: false,
+            upload_budget_mb: Self::default_upload_budget_mb(),
+        }
+    }
+}
+
+impl PerformanceConfig {
+    fn default_upload_budget_mb() -> u32 {
+        32
+    }
+}
+
+/// Handling of clients that break the protocol. A client is always
+/// disconnected for a protocol error; these settings decide whether its
+/// process may reconnect.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SecurityConfig {
+    /// Refuse new connections from a process once this many of its
+    /// clients were disconnected for protocol errors within
+    /// `error_window_secs`. `0` never refuses.
+    #[serde(default)]
+    pub ban_after_errors: u32,
+
+    /// How far back protocol errors count towards `ban_after_errors`, in
+    /// seconds
+    #[serde(default = "SecurityConfig::default_error_window_secs")]
+    pub error_window_secs: u64,
+
+    /// How long a process's connections are refused, in seconds
+    #[serde(default = "SecurityConfig::default_ban_secs")]
+    pub ban_secs: u64,
+
+    /// Processes, by name as in `/proc/<pid>/comm`, allowed to inject
+    /// input through `zwp_virtual_keyboard_v1` and
+    /// `zwlr_virtual_pointer_v1`. `"*"` allows every client; empty allows
+    /// none.
+    #[serde(default)]
+    pub allow_virtual_input: Vec<String>,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            ban_after_errors: 0,
+            error_window_secs: Self::default_error_window_secs(),
+            ban_secs: Self::default_ban_secs(),
+            allow_virtual_input: Vec::new(),
+        }
+    }
+}
+
+impl SecurityConfig {
+    fn default_error_window_secs() -> u64 {
+        60
+    }
+    fn default_ban_secs() -> u64 {
+        300
+    }
+}
+
+/// A `[profiles.<name>]` entry. Each section holds only the keys the
+/// profile changes, in the same layout as the top-level section; the rest
+/// keep their value from the main configuration, e.g.
+///
+/// ```toml
+/// [profiles.battery.effects]
+/// reduce_motion = true
+/// dim_inactive = { enabled = false }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConfigProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects: Option<toml::Table>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<toml::Table>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<toml::Table>,
+}
+
+/// `[auto_profile]` section: which profile to apply on each power source.
+/// Unset entries leave the active profile alone when switching to that
+/// source.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AutoProfileConfig {
+    /// Profile applied while running on battery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_battery: Option<String>,
+
+    /// Profile applied while plugged in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_ac: Option<String>,
+}
+
+impl AutoProfileConfig {
+    /// Whether either power source has a profile, so the power source is
+    /// worth watching.
+    pub fn is_enabled(&self) -> bool {
+        self.on_battery.is_some() || self.on_ac.is_some()
+    }
+}
+
+/// Visual effects settings.
+///
+/// Only cheap effects that map onto the existing solid-color/texture render
+/// elements live here; there is no post-processing pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EffectsConfig {
+    /// Dim windows that do not have keyboard focus
+    #[serde(default)]
+    pub dim_inactive: DimInactiveConfig,
+
+    /// How long a window takes to fade between its focused and inactive
+    /// look (dim, opacity and decoration colors), in milliseconds. `0`
+    /// switches instantly.
+    #[serde(default = "EffectsConfig::default_focus_transition_ms")]
+    pub focus_transition_ms: u32,
+
+    /// Skip animations: workspace scrolls land on their target at once and
+    /// focus changes switch the window's look instantly
+    #[serde(default)]
+    pub reduce_motion: bool,
+}
+
+impl Default for EffectsConfig {
+    fn default() -> Self {
+        Self {
+            dim_inactive: DimInactiveConfig::default(),
+            focus_transition_ms: Self::default_focus_transition_ms(),
+            reduce_motion: false,
+        }
+    }
+}
+
+impl EffectsConfig {
+    fn default_focus_transition_ms() -> u32 {
+        150
+    }
+}
+
+/// `[effects.dim_inactive]` section.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DimInactiveConfig {
+    /// Enable dimming of unfocused windows
+    #[serde(default = "DimInactiveConfig::default_enabled")]
+    pub enabled: bool,
+
+    /// How much to darken unfocused windows (0.0 = none, 1.0 = black)
+    #[serde(default = "DimInactiveConfig::default_strength")]
+    pub strength: f32,
+}
+
+impl Default for DimInactiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            strength: Self::default_strength(),
+        }
+    }
+}
+
+impl DimInactiveConfig {
+    fn default_enabled() -> bool {
+        false
+    }
+    fn default_strength() -> f32 {
+        0.2
+    }
+
+    /// Dim amount a window should settle at for the given focus state.
+    pub fn target_dim(&self, focused: bool) -> f32 {
+        if self.enabled && !focused {
+            self.strength
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A single `[[window_rules]]` entry.
+///
+/// Matchers that are left unset act as wildcards, so a rule with neither
+/// `app_id` nor `title` applies to every window. When several rules match,
+/// they are applied in file order and later overrides win.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WindowRule {
+    /// Exact `app_id` to match (e.g. `"foot"`)
+    #[serde(default)]
+    pub app_id: Option<String>,
+
+    /// Substring that must appear in the window title
+    #[serde(default)]
+    pub title: Option<String>,
+
+    /// Opacity while the window is focused (0.0 - 1.0)
+    #[serde(default)]
+    pub opacity: Option<f32>,
+
+    /// Opacity while the window is unfocused; falls back to `opacity`
+    #[serde(default)]
+    pub inactive_opacity: Option<f32>,
+
+    /// Request blur-behind for this window. Ignored while the window is
+    /// fully opaque.
+    #[serde(default)]
+    pub blur: Option<bool>,
+
+    /// Where matching windows open; replaces `window.open_placement`
+    #[serde(default)]
+    pub open_placement: Option<String>,
+}
+
+impl WindowRule {
+    /// Whether this rule applies to a window with the given `app_id` and title.
+    pub fn matches(&self, app_id: Option<&str>, title: &str) -> bool {
+        let app_id_ok = match &self.app_id {
+            Some(wanted) => app_id == Some(wanted.as_str()),
+            None => true,
+        };
+        let title_ok = match &self.title {
+            Some(wanted) => title.contains(wanted.as_str()),
+            None => true,
+        };
+        app_id_ok && title_ok
+    }
+}
+
+/// A single `[[outputs]]` entry.
+///
+/// Matches outputs by connector name and/or model the same way
+/// [`WindowRule`] matches windows: unset matchers are wildcards and later
+/// matching rules override earlier ones. Outputs no rule matches use
+/// `window.default_layout` and the `workspace` gaps.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct OutputRule {
+    /// Exact connector name to match (e.g. `"DP-1"`)
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Exact monitor model to match, as the output reports it
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Name of the layout windows in a column are tiled with on this
+    /// output; same values as `window.default_layout`
+    #[serde(default)]
+    pub layout: Option<String>,
+
+    /// Replaces both `workspace.inner_gap` and `workspace.outer_gap` on
+    /// this output (pixels)
+    #[serde(default)]
+    pub gaps: Option<u32>,
+}
+
+impl OutputRule {
+    /// Whether this rule applies to the output called `name` with `model`.
+    pub fn matches(&self, name: &str, model: &str) -> bool {
+        self.name.as_deref().is_none_or(|wanted| wanted == name)
+            && self.model.as_deref().is_none_or(|wanted| wanted == model)
+    }
+}
+
+/// Whether `name` can name a layout: lowercase ASCII letters, digits and
+/// underscores. Layouts can be registered at runtime, so which names exist
+/// is only known once the compositor is running.
+pub fn is_layout_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+}
+
+/// Output configuration (multi-monitor layout)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct OutputConfig {
     /// Preferred output order for the horizontal strip layout.
     /// Output names (e.g. `"HDMI-A-1"`, `"DP-1"`) listed here appear in
@@ -76,6 +405,43 @@ pub struct OutputConfig {
     /// Leave empty to use the natural DRM enumeration order.
     #[serde(default)]
     pub order: Vec<String>,
+
+    /// Multiplier on every colour channel of the composed frame
+    /// (0.0 = black, 1.0 = unchanged, up to 2.0)
+    #[serde(default = "OutputConfig::default_adjustment")]
+    pub brightness: f32,
+
+    /// How far colours are pushed away from mid-grey
+    /// (0.0 = flat grey, 1.0 = unchanged, up to 2.0)
+    #[serde(default = "OutputConfig::default_adjustment")]
+    pub contrast: f32,
+
+    /// Colour intensity (0.0 = grayscale, 1.0 = unchanged, up to 2.0)
+    #[serde(default = "OutputConfig::default_adjustment")]
+    pub saturation: f32,
+
+    /// Compose every frame offscreen and copy it to the output, even when
+    /// no post-process pass needs the finished frame
+    #[serde(default)]
+    pub layered_compositing: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            order: Vec::new(),
+            brightness: Self::default_adjustment(),
+            contrast: Self::default_adjustment(),
+            saturation: Self::default_adjustment(),
+            layered_compositing: false,
+        }
+    }
+}
+
+impl OutputConfig {
+    fn default_adjustment() -> f32 {
+        1.0
+    }
 }
 
 /// Feature kill-switches. Both flags default to `false` — see the
@@ -132,12 +498,19 @@ impl FeaturesConfig {
 pub struct BackendConfig {
     /// Backend kind name. See [`BackendConfig::default`] for valid values.
     pub kind: String,
+
+    /// Name of the Wayland socket to listen on in `XDG_RUNTIME_DIR`, and
+    /// so the `WAYLAND_DISPLAY` clients get. Unset uses
+    /// `wayland-axiom-<pid>`. Also set by `--socket-name`.
+    #[serde(default)]
+    pub socket_name: Option<String>,
 }
 
 impl Default for BackendConfig {
     fn default() -> Self {
         Self {
             kind: "winit".to_string(),
+            socket_name: None,
         }
     }
 }
@@ -151,15 +524,29 @@ pub struct WorkspaceConfig {
     /// Enable infinite scrolling (vs bounded workspaces)
     pub infinite_scroll: bool,
 
-    /// Auto-scroll to fit content
+    /// Scroll a focused window's column into view when it is off screen
     pub auto_scroll: bool,
 
     /// Width of each virtual workspace column (pixels)
     pub workspace_width: u32,
 
-    /// Gaps between windows (pixels)
+    /// Gaps between windows (pixels). Used for both `inner_gap` and
+    /// `outer_gap` unless they are set.
     pub gaps: u32,
 
+    /// Space between neighbouring windows (pixels)
+    #[serde(default)]
+    pub inner_gap: Option<u32>,
+
+    /// Space between windows and the edges of their column (pixels)
+    #[serde(default)]
+    pub outer_gap: Option<u32>,
+
+    /// Drop all gaps in a column holding a single tiled window, so it
+    /// fills the column
+    #[serde(default)]
+    pub smart_gaps: bool,
+
     /// Enable smooth scrolling animations
     pub smooth_scrolling: bool,
 
@@ -171,9 +558,100 @@ pub struct WorkspaceConfig {
     #[serde(default = "WorkspaceConfig::default_momentum_min_velocity")]
     pub momentum_min_velocity: f64,
 
-    /// Snap-to-column distance threshold in pixels
+    /// Snap-to-column distance threshold in pixels. Unused: a fling that
+    /// has slowed down settles on the nearest column however far off it.
     #[serde(default = "WorkspaceConfig::default_snap_threshold")]
     pub snap_threshold_px: f64,
+
+    /// Rubber-band resistance (0.0-1.0) met by a fling that runs past the
+    /// last occupied column: it slows harder and springs back unless it
+    /// gets half a column out. 0 lets it glide on as before.
+    #[serde(default)]
+    pub edge_resistance: f64,
+
+    /// Scroll movement (pixels) below which window layouts are reused
+    /// rather than recomputed; 0 recomputes on every change
+    #[serde(default = "WorkspaceConfig::default_layout_min_delta")]
+    pub layout_min_delta_px: f64,
+
+    /// Fixed number of columns (0..max_columns) instead of an infinite
+    /// tape. Unset keeps infinite scrolling.
+    #[serde(default)]
+    pub max_columns: Option<u32>,
+
+    /// With `max_columns` set, scrolling past either end wraps around
+    /// instead of stopping at the bound
+    #[serde(default)]
+    pub wrap: bool,
+
+    /// Interpolation curve for animated column scrolls
+    #[serde(default)]
+    pub scroll_easing: EasingFunction,
+
+    /// Wallpaper image per column, keyed by column index (`"0"`, `"-1"`...).
+    /// Neighbouring wallpapers cross-fade while scrolling between columns.
+    #[serde(default)]
+    pub backgrounds: BTreeMap<String, String>,
+}
+
+/// Scroll animation curve, written in TOML as e.g.
+/// `scroll_easing = { kind = "ease_in_out_cubic" }` or
+/// `scroll_easing = { kind = "spring", stiffness = 180.0, damping = 24.0 }`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EasingFunction {
+    Linear,
+    /// Fast start, gentle landing. The historical default.
+    #[default]
+    EaseOutCubic,
+    EaseInOutCubic,
+    /// Damped spring; has no fixed duration and settles on its own.
+    /// Critically damped when `damping == 2 * sqrt(stiffness)`.
+    Spring { stiffness: f64, damping: f64 },
+}
+
+impl EasingFunction {
+    /// Eased progress for `t` in [0, 1], mapping 0 to 0 and 1 to 1.
+    /// Springs are integrated by the workspace tape rather than sampled,
+    /// so they fall back to linear here.
+    pub fn sample(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear | Self::Spring { .. } => t,
+            Self::EaseOutCubic => {
+                let t = t - 1.0;
+                t * t * t + 1.0
+            }
+            Self::EaseInOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let u = -2.0 * t + 2.0;
+                    1.0 - u * u * u / 2.0
+                }
+            }
+        }
+    }
+
+    /// d(sample)/dt, used to derive the scroll velocity mid-animation.
+    pub fn derivative(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear | Self::Spring { .. } => 1.0,
+            Self::EaseOutCubic => {
+                let t = t - 1.0;
+                3.0 * t * t
+            }
+            Self::EaseInOutCubic => {
+                if t < 0.5 {
+                    12.0 * t * t
+                } else {
+                    let u = -2.0 * t + 2.0;
+                    3.0 * u * u
+                }
+            }
+        }
+    }
 }
 
 /// Window management configuration
@@ -201,6 +679,120 @@ pub struct WindowConfig {
 
     /// Default layout algorithm ("horizontal", "vertical")
     pub default_layout: String,
+
+    /// Distance (pixels) within which a dragged floating window snaps to
+    /// output edges and neighbouring window borders. `0` disables snapping;
+    /// holding Shift while dragging skips it for that move.
+    #[serde(default = "WindowConfig::default_snap_threshold")]
+    pub snap_threshold: u32,
+
+    /// How long (milliseconds) a window asked to close may linger before
+    /// its client is disconnected. `0` kills unresponsive clients on the
+    /// next tick.
+    #[serde(default = "WindowConfig::default_close_grace_ms")]
+    pub close_grace_ms: u64,
+
+    /// Most toplevel windows open at once. Unset leaves the count
+    /// unbounded.
+    #[serde(default)]
+    pub max_windows: Option<u32>,
+
+    /// What happens to a new toplevel once `max_windows` are open:
+    /// "reject" disconnects its client with a protocol error,
+    /// "close_oldest" asks the least recently focused window to close.
+    #[serde(default = "WindowConfig::default_overflow_policy")]
+    pub overflow_policy: String,
+
+    /// Where a new toplevel opens: "focused_column" (the focused column
+    /// of the focused output), "cursor" (the column under the pointer, on
+    /// the output under it) or "center" (floating, centered on the
+    /// focused output). Transient dialogs always float over their parent.
+    #[serde(default = "WindowConfig::default_open_placement")]
+    pub open_placement: String,
+}
+
+/// Look of the titlebars drawn for windows with server-side decorations.
+/// Colors are `#RRGGBB`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DecorationConfig {
+    /// Titlebar height in pixels
+    #[serde(default = "DecorationConfig::default_titlebar_height")]
+    pub titlebar_height: u32,
+
+    /// Titlebar background of the focused window
+    #[serde(default = "DecorationConfig::default_active_color")]
+    pub active_color: String,
+
+    /// Titlebar background of other windows
+    #[serde(default = "DecorationConfig::default_inactive_color")]
+    pub inactive_color: String,
+
+    /// Button symbols on the focused window's titlebar
+    #[serde(default = "DecorationConfig::default_active_text_color")]
+    pub active_text_color: String,
+
+    /// Button symbols on other windows' titlebars
+    #[serde(default = "DecorationConfig::default_inactive_text_color")]
+    pub inactive_text_color: String,
+
+    /// Titlebar buttons in GNOME `button-layout` form: those before the
+    /// `:` at the left edge, those after it at the right, in order, e.g.
+    /// `"close,minimize:"` or `"appmenu:minimize,maximize,close"`.
+    /// Minimize only shows with `features.enable_minimize`.
+    #[serde(default = "DecorationConfig::default_button_layout")]
+    pub button_layout: String,
+
+    /// Radius of window corners and the titlebar's top corners, also used
+    /// when hit-testing window corners. Fullscreen windows are square.
+    #[serde(default = "DecorationConfig::default_corner_radius")]
+    pub corner_radius: f32,
+
+    /// Width in device pixels of the anti-aliased edge of rounded window
+    /// corners. `0` gives a hard edge.
+    #[serde(default = "DecorationConfig::default_corner_softness")]
+    pub corner_softness: f32,
+}
+
+impl Default for DecorationConfig {
+    fn default() -> Self {
+        Self {
+            titlebar_height: Self::default_titlebar_height(),
+            active_color: Self::default_active_color(),
+            inactive_color: Self::default_inactive_color(),
+            active_text_color: Self::default_active_text_color(),
+            inactive_text_color: Self::default_inactive_text_color(),
+            button_layout: Self::default_button_layout(),
+            corner_radius: Self::default_corner_radius(),
+            corner_softness: Self::default_corner_softness(),
+        }
+    }
+}
+
+impl DecorationConfig {
+    fn default_titlebar_height() -> u32 {
+        32
+    }
+    fn default_active_color() -> String {
+        "#262626".to_string() // Dark gray
+    }
+    fn default_inactive_color() -> String {
+        "#1A1A1A".to_string() // Darker gray
+    }
+    fn default_active_text_color() -> String {
+        "#FFFFFF".to_string()
+    }
+    fn default_inactive_text_color() -> String {
+        "#B3B3B3".to_string() // Light gray
+    }
+    fn default_button_layout() -> String {
+        ":minimize,maximize,close".to_string()
+    }
+    fn default_corner_radius() -> f32 {
+        8.0
+    }
+    fn default_corner_softness() -> f32 {
+        1.0
+    }
 }
 
 /// Input configuration
@@ -220,6 +812,79 @@ pub struct InputConfig {
 
     /// Natural scrolling
     pub natural_scrolling: bool,
+
+    /// Keyboard focus policy: "click" or "follows_mouse" ("sloppy" is accepted
+    /// as an alias for "follows_mouse")
+    #[serde(default = "InputConfig::default_focus_policy")]
+    pub focus_policy: String,
+
+    /// How long the pointer must rest on a window before follows-mouse focus
+    /// moves to it (milliseconds)
+    #[serde(default = "InputConfig::default_focus_follows_mouse_delay_ms")]
+    pub focus_follows_mouse_delay_ms: u32,
+
+    /// Actions for scrolling over parts of the desktop
+    #[serde(default)]
+    pub mouse_bindings: MouseBindingsConfig,
+
+    /// Touchpad swipe bindings: `"<fingers>_<direction>"` (e.g. `3_left`)
+    /// to an action name. Two-finger motion is left to clients as scrolling.
+    #[serde(default = "InputConfig::default_gestures")]
+    pub gestures: BTreeMap<String, String>,
+
+    /// Extra seats (`wl_seat` globals), each with its own keyboard focus
+    /// and pointer: seat name to the names of the input devices routed to
+    /// it. Devices not listed stay on the default seat, `seat0`.
+    #[serde(default)]
+    pub seats: BTreeMap<String, Vec<String>>,
+}
+
+/// `[input.mouse_bindings]` section. Each field holds an action name as
+/// accepted by the mouse button bindings; an empty string leaves that
+/// scroll to the client (or does nothing over the background).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MouseBindingsConfig {
+    /// Wheel up over a server-side titlebar
+    #[serde(default = "MouseBindingsConfig::default_titlebar_scroll_up")]
+    pub titlebar_scroll_up: String,
+
+    /// Wheel down over a server-side titlebar
+    #[serde(default = "MouseBindingsConfig::default_titlebar_scroll_down")]
+    pub titlebar_scroll_down: String,
+
+    /// Wheel up over the background, where no window is
+    #[serde(default = "MouseBindingsConfig::default_background_scroll_up")]
+    pub background_scroll_up: String,
+
+    /// Wheel down over the background
+    #[serde(default = "MouseBindingsConfig::default_background_scroll_down")]
+    pub background_scroll_down: String,
+}
+
+impl Default for MouseBindingsConfig {
+    fn default() -> Self {
+        Self {
+            titlebar_scroll_up: Self::default_titlebar_scroll_up(),
+            titlebar_scroll_down: Self::default_titlebar_scroll_down(),
+            background_scroll_up: Self::default_background_scroll_up(),
+            background_scroll_down: Self::default_background_scroll_down(),
+        }
+    }
+}
+
+impl MouseBindingsConfig {
+    fn default_titlebar_scroll_up() -> String {
+        "cycle_column_prev".to_string()
+    }
+    fn default_titlebar_scroll_down() -> String {
+        "cycle_column_next".to_string()
+    }
+    fn default_background_scroll_up() -> String {
+        "scroll_left".to_string()
+    }
+    fn default_background_scroll_down() -> String {
+        "scroll_right".to_string()
+    }
 }
 
 /// Key bindings configuration
@@ -264,6 +929,46 @@ pub struct BindingsConfig {
     /// Switch focus to next output
     pub focus_next_output: String,
 
+    /// Swap the focused window with its left neighbour
+    #[serde(default = "BindingsConfig::default_swap_window_left")]
+    pub swap_window_left: String,
+
+    /// Swap the focused window with its right neighbour
+    #[serde(default = "BindingsConfig::default_swap_window_right")]
+    pub swap_window_right: String,
+
+    /// Add the focused window to the selection, or remove it
+    #[serde(default = "BindingsConfig::default_toggle_selection")]
+    pub toggle_selection: String,
+
+    /// Move every selected window into the focused column
+    #[serde(default = "BindingsConfig::default_move_selected_here")]
+    pub move_selected_here: String,
+
+    /// Close every selected window
+    #[serde(default = "BindingsConfig::default_close_selected")]
+    pub close_selected: String,
+
+    /// Cascade the floating windows from the focused output's corner
+    #[serde(default = "BindingsConfig::default_cascade_windows")]
+    pub cascade_windows: String,
+
+    /// Centre the focused floating window on its output
+    #[serde(default = "BindingsConfig::default_center_window")]
+    pub center_window: String,
+
+    /// Send the focused window to the scratchpad
+    #[serde(default = "BindingsConfig::default_move_to_scratchpad")]
+    pub move_to_scratchpad: String,
+
+    /// Show or hide the scratchpad window
+    #[serde(default = "BindingsConfig::default_toggle_scratchpad")]
+    pub toggle_scratchpad: String,
+
+    /// Cycle the focused window's colour filter: invert, grayscale, none
+    #[serde(default = "BindingsConfig::default_cycle_color_filter")]
+    pub cycle_color_filter: String,
+
     /// ── Mouse button bindings ─────────────────────────────────────────
     /// Each field holds an action name (see `CompositorAction` variants):
     ///   "scroll_left", "scroll_right", "close_window",
@@ -306,6 +1011,11 @@ pub struct GeneralConfig {
     /// Default application launcher command
     #[serde(default = "GeneralConfig::default_launcher")]
     pub default_launcher: String,
+
+    /// Show the debug HUD (FPS, frame time, window count, damage, draw
+    /// calls) from startup; `--hud` sets it too
+    #[serde(default)]
+    pub hud: bool,
 }
 
 impl GeneralConfig {
@@ -326,10 +1036,19 @@ impl Default for WorkspaceConfig {
             auto_scroll: true,
             workspace_width: 1920,
             gaps: 10,
+            inner_gap: None,
+            outer_gap: None,
+            smart_gaps: false,
             smooth_scrolling: true,
             momentum_friction: Self::default_momentum_friction(),
             momentum_min_velocity: Self::default_momentum_min_velocity(),
             snap_threshold_px: Self::default_snap_threshold(),
+            edge_resistance: 0.0,
+            layout_min_delta_px: Self::default_layout_min_delta(),
+            max_columns: None,
+            wrap: false,
+            scroll_easing: EasingFunction::default(),
+            backgrounds: BTreeMap::new(),
         }
     }
 }
@@ -344,10 +1063,52 @@ impl Default for WindowConfig {
             inactive_border_color: "#374151".to_string(), // Gray
             gap: 10,
             default_layout: "horizontal".to_string(),
+            snap_threshold: Self::default_snap_threshold(),
+            close_grace_ms: Self::default_close_grace_ms(),
+            max_windows: None,
+            overflow_policy: Self::default_overflow_policy(),
+            open_placement: Self::default_open_placement(),
         }
     }
 }
 
+impl WindowConfig {
+    fn default_snap_threshold() -> u32 {
+        10
+    }
+
+    fn default_close_grace_ms() -> u64 {
+        3000
+    }
+
+    fn default_overflow_policy() -> String {
+        "reject".to_string()
+    }
+
+    fn default_open_placement() -> String {
+        "focused_column".to_string()
+    }
+
+    /// `open_placement` for a window with `app_id` and `title`: the last
+    /// matching rule that sets one wins over the global setting.
+    pub fn open_placement_for<'a>(
+        &'a self,
+        rules: &'a [WindowRule],
+        app_id: Option<&str>,
+        title: &str,
+    ) -> &'a str {
+        rules
+            .iter()
+            .filter(|r| r.matches(app_id, title))
+            .filter_map(|r| r.open_placement.as_deref())
+            .last()
+            .unwrap_or(&self.open_placement)
+    }
+}
+
+/// Values accepted by `window.open_placement` and `window_rules[].open_placement`.
+const OPEN_PLACEMENTS: [&str; 3] = ["focused_column", "cursor", "center"];
+
 impl Default for InputConfig {
     fn default() -> Self {
         Self {
@@ -356,7 +1117,58 @@ impl Default for InputConfig {
             mouse_accel: 0.0,
             touchpad_tap: true,
             natural_scrolling: true,
+            focus_policy: Self::default_focus_policy(),
+            focus_follows_mouse_delay_ms: Self::default_focus_follows_mouse_delay_ms(),
+            mouse_bindings: MouseBindingsConfig::default(),
+            gestures: Self::default_gestures(),
+            seats: BTreeMap::new(),
+        }
+    }
+}
+
+impl InputConfig {
+    fn default_gestures() -> BTreeMap<String, String> {
+        // Content follows the fingers, as on a touchscreen
+        BTreeMap::from([
+            ("3_left".to_string(), "scroll_right".to_string()),
+            ("3_right".to_string(), "scroll_left".to_string()),
+        ])
+    }
+
+    /// Split a gesture key such as `3_left` into finger count and
+    /// direction. Returns `None` for anything not of that shape.
+    pub fn parse_gesture_key(key: &str) -> Option<(u32, &str)> {
+        let (fingers, direction) = key.split_once('_')?;
+        let fingers = fingers.parse().ok()?;
+        matches!(direction, "left" | "right" | "up" | "down").then_some((fingers, direction))
+    }
+
+    /// Problems with `gestures` worth telling the user about. None of them
+    /// stop the config from loading; the offending entries are ignored.
+    pub fn gesture_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for key in self.gestures.keys() {
+            match Self::parse_gesture_key(key) {
+                None => warnings.push(format!(
+                    "input.gestures.{key}: expected \"<fingers>_<left|right|up|down>\""
+                )),
+                Some((2, _)) => warnings.push(format!(
+                    "input.gestures.{key}: two-finger motion is client scrolling and cannot be bound"
+                )),
+                Some((fingers, _)) if !(3..=5).contains(&fingers) => warnings.push(format!(
+                    "input.gestures.{key}: touchpads report swipes with 3 to 5 fingers"
+                )),
+                Some(_) => {}
+            }
         }
+        warnings
+    }
+
+    fn default_focus_policy() -> String {
+        "click".into()
+    }
+    fn default_focus_follows_mouse_delay_ms() -> u32 {
+        150
     }
 }
 
@@ -378,6 +1190,16 @@ impl Default for BindingsConfig {
             launch_launcher: "Super+Space".to_string(),
             quit: "Super+Shift+q".to_string(),
             focus_next_output: "Super+Tab".to_string(),
+            swap_window_left: Self::default_swap_window_left(),
+            swap_window_right: Self::default_swap_window_right(),
+            toggle_selection: Self::default_toggle_selection(),
+            move_selected_here: Self::default_move_selected_here(),
+            close_selected: Self::default_close_selected(),
+            cascade_windows: Self::default_cascade_windows(),
+            center_window: Self::default_center_window(),
+            move_to_scratchpad: Self::default_move_to_scratchpad(),
+            toggle_scratchpad: Self::default_toggle_scratchpad(),
+            cycle_color_filter: Self::default_cycle_color_filter(),
             mouse_back: Self::default_mouse_back(),
             mouse_forward: Self::default_mouse_forward(),
             mouse_middle: Self::default_mouse_middle(),
@@ -393,6 +1215,7 @@ impl Default for GeneralConfig {
             vsync: true,
             default_terminal: Self::default_terminal(),
             default_launcher: Self::default_launcher(),
+            hud: false,
         }
     }
 }
@@ -407,9 +1230,88 @@ impl WorkspaceConfig {
     fn default_snap_threshold() -> f64 {
         48.0
     }
+    fn default_layout_min_delta() -> f64 {
+        0.5
+    }
+
+    /// Gap between neighbouring windows, falling back to `gaps`.
+    pub fn inner_gap(&self) -> u32 {
+        self.inner_gap.unwrap_or(self.gaps)
+    }
+
+    /// Gap between windows and the edges of their column, falling back to
+    /// `gaps`.
+    pub fn outer_gap(&self) -> u32 {
+        self.outer_gap.unwrap_or(self.gaps)
+    }
+
+    /// `backgrounds` by column index, with a leading `~` expanded. Entries
+    /// whose key isn't an index are left out; see
+    /// [`Self::background_warnings`].
+    pub fn column_backgrounds(&self) -> BTreeMap<i32, String> {
+        self.backgrounds
+            .iter()
+            .filter(|(_, path)| !path.is_empty())
+            .filter_map(|(key, path)| {
+                let index = key.trim().parse().ok()?;
+                let path = match (path.strip_prefix('~'), std::env::var("HOME")) {
+                    (Some(rest), Ok(home)) => format!("{home}{rest}"),
+                    _ => path.clone(),
+                };
+                Some((index, path))
+            })
+            .collect()
+    }
+
+    /// Problems with `backgrounds` worth telling the user about. The
+    /// offending entries are ignored.
+    pub fn background_warnings(&self) -> Vec<String> {
+        self.backgrounds
+            .iter()
+            .filter_map(|(key, path)| {
+                if key.trim().parse::<i32>().is_err() {
+                    Some(format!("workspace.backgrounds.{key}: expected a column index"))
+                } else if path.is_empty() {
+                    Some(format!("workspace.backgrounds.{key}: empty image path"))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 impl BindingsConfig {
+    fn default_swap_window_left() -> String {
+        "Super+Shift+H".to_string()
+    }
+    fn default_swap_window_right() -> String {
+        "Super+Shift+L".to_string()
+    }
+    fn default_toggle_selection() -> String {
+        "Super+Ctrl+Space".to_string()
+    }
+    fn default_move_selected_here() -> String {
+        "Super+Ctrl+m".to_string()
+    }
+    fn default_close_selected() -> String {
+        "Super+Ctrl+q".to_string()
+    }
+    fn default_cascade_windows() -> String {
+        "Super+Ctrl+c".to_string()
+    }
+    fn default_center_window() -> String {
+        "Super+c".to_string()
+    }
+    fn default_move_to_scratchpad() -> String {
+        "Super+Ctrl+minus".to_string()
+    }
+    fn default_toggle_scratchpad() -> String {
+        "Super+minus".to_string()
+    }
+    fn default_cycle_color_filter() -> String {
+        "Super+Ctrl+i".to_string()
+    }
     fn default_mouse_back() -> String {
         "scroll_left".to_string()
     }
@@ -437,10 +1339,20 @@ impl AxiomConfig {
         let contents = fs::read_to_string(&expanded_path)
             .with_context(|| format!("Failed to read config file: {}", expanded_path.display()))?;
 
-        let config: AxiomConfig = toml::from_str(&contents)
+        let mut config: AxiomConfig = toml::from_str(&contents)
             .with_context(|| format!("Failed to parse config file: {}", expanded_path.display()))?;
 
+        // `window.focus_follows_mouse` predates `input.focus_policy`; keep
+        // honouring old configs that only set the boolean.
+        if config.window.focus_follows_mouse && config.input.focus_policy == "click" {
+            config.input.focus_policy = "follows_mouse".into();
+        }
+
         config.validate()?;
+        // Catch broken profiles at load rather than when first switched to
+        for name in config.profiles.keys() {
+            config.with_profile(name)?;
+        }
 
         Ok(config)
     }
@@ -457,6 +1369,9 @@ impl AxiomConfig {
         if self.workspace.gaps > 500 {
             anyhow::bail!("gaps must be <= 500");
         }
+        if self.workspace.inner_gap() > 500 || self.workspace.outer_gap() > 500 {
+            anyhow::bail!("inner_gap and outer_gap must be <= 500");
+        }
         if !(0.0..=1.0).contains(&self.workspace.momentum_friction) {
             anyhow::bail!("momentum_friction must be in [0, 1]");
         }
@@ -468,6 +1383,39 @@ impl AxiomConfig {
         if self.workspace.snap_threshold_px < 0.0 || self.workspace.snap_threshold_px > 10_000.0 {
             anyhow::bail!("snap_threshold_px must be in [0, 10000]");
         }
+        if !(0.0..=1.0).contains(&self.workspace.edge_resistance) {
+            anyhow::bail!("workspace.edge_resistance must be in [0, 1]");
+        }
+        if !(0.0..=16.0).contains(&self.workspace.layout_min_delta_px) {
+            anyhow::bail!("workspace.layout_min_delta_px must be in [0, 16]");
+        }
+        if let Some(max_columns) = self.workspace.max_columns {
+            if max_columns == 0 || max_columns > 256 {
+                anyhow::bail!("workspace.max_columns must be in [1, 256]");
+            }
+        }
+        if let EasingFunction::Spring { stiffness, damping } = self.workspace.scroll_easing {
+            if !(stiffness > 0.0 && stiffness <= 10_000.0) {
+                anyhow::bail!("scroll_easing spring stiffness must be in (0, 10000]");
+            }
+            // An undamped spring oscillates forever and never comes to rest
+            if !(damping > 0.0 && damping <= 1_000.0) {
+                anyhow::bail!("scroll_easing spring damping must be in (0, 1000]");
+            }
+        }
+        for warning in self.workspace.background_warnings() {
+            warn!("{} — ignored", warning);
+        }
+
+        // --- backend ---
+        if let Some(name) = &self.backend.socket_name {
+            if name.is_empty() || name.len() > 64 || name.contains('/') {
+                anyhow::bail!(
+                    "backend.socket_name must be a file name of 1-64 characters, got {:?}",
+                    name
+                );
+            }
+        }
 
         // --- window ---
         if self.window.border_width > 100 {
@@ -480,14 +1428,58 @@ impl AxiomConfig {
             log::warn!("window.gap is deprecated — use workspace.gaps instead. This field does not affect layout.");
         }
         let valid_placements = ["smart", "center", "mouse"];
+        if self.window.snap_threshold > 200 {
+            anyhow::bail!("window.snap_threshold must be <= 200");
+        }
+        if self.window.close_grace_ms > 60_000 {
+            anyhow::bail!("window.close_grace_ms must be <= 60000");
+        }
+        if self.window.max_windows == Some(0) {
+            anyhow::bail!("window.max_windows must be at least 1");
+        }
+        if !["reject", "close_oldest"].contains(&self.window.overflow_policy.as_str()) {
+            anyhow::bail!(
+                "Invalid window.overflow_policy: {} (expected reject or close_oldest)",
+                self.window.overflow_policy
+            );
+        }
+        if !OPEN_PLACEMENTS.contains(&self.window.open_placement.as_str()) {
+            anyhow::bail!(
+                "Invalid window.open_placement: {} (expected focused_column, cursor or center)",
+                self.window.open_placement
+            );
+        }
         if !valid_placements.contains(&self.window.placement.as_str()) {
             anyhow::bail!("Invalid window placement: {}", self.window.placement);
         }
-        let valid_layouts = ["horizontal", "vertical"];
-        if !valid_layouts.contains(&self.window.default_layout.as_str()) {
+        if !is_layout_name(&self.window.default_layout) {
             anyhow::bail!("Invalid default_layout: {}", self.window.default_layout);
         }
 
+        // --- decoration ---
+        if !(16..=128).contains(&self.decoration.titlebar_height) {
+            anyhow::bail!("decoration.titlebar_height must be in [16, 128]");
+        }
+        for (field_name, color) in [
+            ("active_color", &self.decoration.active_color),
+            ("inactive_color", &self.decoration.inactive_color),
+            ("active_text_color", &self.decoration.active_text_color),
+            ("inactive_text_color", &self.decoration.inactive_text_color),
+        ] {
+            if crate::decoration::DecorationManager::parse_color(color).is_none() {
+                anyhow::bail!("decoration.{} must be #RRGGBB, got {:?}", field_name, color);
+            }
+        }
+        if let Err(e) = crate::decoration::ButtonLayout::parse(&self.decoration.button_layout) {
+            anyhow::bail!("Invalid decoration.button_layout: {}", e);
+        }
+        if !(0.0..=64.0).contains(&self.decoration.corner_radius) {
+            anyhow::bail!("decoration.corner_radius must be in [0, 64]");
+        }
+        if !(0.0..=8.0).contains(&self.decoration.corner_softness) {
+            anyhow::bail!("decoration.corner_softness must be in [0, 8]");
+        }
+
         // --- input ---
         if self.input.keyboard_repeat_delay > 10_000 {
             anyhow::bail!("keyboard_repeat_delay must be <= 10 000 ms");
@@ -498,6 +1490,30 @@ impl AxiomConfig {
         if !(-1.0..=10.0).contains(&self.input.mouse_accel) {
             anyhow::bail!("mouse_accel must be in [-1, 10]");
         }
+        match self.input.focus_policy.as_str() {
+            "click" | "follows_mouse" | "sloppy" => {}
+            _ => anyhow::bail!("Invalid focus_policy: {}", self.input.focus_policy),
+        }
+        if self.input.focus_follows_mouse_delay_ms > 5_000 {
+            anyhow::bail!("focus_follows_mouse_delay_ms must be <= 5000");
+        }
+        for warning in self.input.gesture_warnings() {
+            warn!("{} — ignored", warning);
+        }
+        let mut seated_devices = std::collections::HashSet::new();
+        for (seat, devices) in &self.input.seats {
+            if seat.is_empty() || seat.len() > 64 {
+                anyhow::bail!("input.seats names must be 1-64 characters");
+            }
+            for device in devices {
+                if !seated_devices.insert(device.as_str()) {
+                    anyhow::bail!(
+                        "input.seats lists device {:?} on more than one seat",
+                        device
+                    );
+                }
+            }
+        }
 
         // --- bindings ---
         for (field_name, binding) in [
@@ -512,6 +1528,16 @@ impl AxiomConfig {
             ("launch_terminal", &self.bindings.launch_terminal),
             ("launch_launcher", &self.bindings.launch_launcher),
             ("quit", &self.bindings.quit),
+            ("swap_window_left", &self.bindings.swap_window_left),
+            ("swap_window_right", &self.bindings.swap_window_right),
+            ("toggle_selection", &self.bindings.toggle_selection),
+            ("move_selected_here", &self.bindings.move_selected_here),
+            ("close_selected", &self.bindings.close_selected),
+            ("cascade_windows", &self.bindings.cascade_windows),
+            ("center_window", &self.bindings.center_window),
+            ("move_to_scratchpad", &self.bindings.move_to_scratchpad),
+            ("toggle_scratchpad", &self.bindings.toggle_scratchpad),
+            ("cycle_color_filter", &self.bindings.cycle_color_filter),
         ] {
             if binding.is_empty() {
                 anyhow::bail!("bindings.{} must not be empty", field_name);
@@ -536,7 +1562,65 @@ impl AxiomConfig {
             );
         }
 
+        // --- effects ---
+        let dim_strength = self.effects.dim_inactive.strength;
+        if !(0.0..=1.0).contains(&dim_strength) {
+            anyhow::bail!("effects.dim_inactive.strength must be in [0, 1]");
+        }
+        if self.effects.focus_transition_ms > 5_000 {
+            anyhow::bail!("effects.focus_transition_ms must be <= 5000");
+        }
+
+        // --- security ---
+        if self.security.ban_after_errors > 0 && self.security.error_window_secs == 0 {
+            anyhow::bail!("security.error_window_secs must be > 0 when ban_after_errors is set");
+        }
+        if self.security.ban_secs > 86_400 {
+            anyhow::bail!("security.ban_secs must be <= 86400");
+        }
+
+        // --- window rules ---
+        for (i, rule) in self.window_rules.iter().enumerate() {
+            for (field_name, value) in [
+                ("opacity", rule.opacity),
+                ("inactive_opacity", rule.inactive_opacity),
+            ] {
+                if let Some(v) = value {
+                    if !(0.0..=1.0).contains(&v) {
+                        anyhow::bail!("window_rules[{}].{} must be in [0, 1]", i, field_name);
+                    }
+                }
+            }
+            if let Some(placement) = rule
+                .open_placement
+                .as_deref()
+                .filter(|p| !OPEN_PLACEMENTS.contains(p))
+            {
+                anyhow::bail!("Invalid window_rules[{}].open_placement: {}", i, placement);
+            }
+        }
+
+        // --- output rules ---
+        for (i, rule) in self.outputs.iter().enumerate() {
+            if rule.gaps.is_some_and(|gaps| gaps > 500) {
+                anyhow::bail!("outputs[{}].gaps must be <= 500", i);
+            }
+            if let Some(layout) = rule.layout.as_deref().filter(|l| !is_layout_name(l)) {
+                anyhow::bail!("Invalid outputs[{}].layout: {}", i, layout);
+            }
+        }
+
         // --- output ---
+        for (field_name, value) in [
+            ("brightness", self.output.brightness),
+            ("contrast", self.output.contrast),
+            ("saturation", self.output.saturation),
+        ] {
+            if !(0.0..=2.0).contains(&value) {
+                anyhow::bail!("output.{} must be in [0, 2]", field_name);
+            }
+        }
+
         // Validate that all entries in output.order are non-empty and
         // contain only valid identifier characters. DRM connector names
         // like "HDMI-A-1" are the expected format.
@@ -571,9 +1655,46 @@ impl AxiomConfig {
             }
         }
 
+        // --- profiles ---
+        for (field_name, profile) in [
+            ("on_battery", &self.auto_profile.on_battery),
+            ("on_ac", &self.auto_profile.on_ac),
+        ] {
+            if let Some(name) = profile.as_ref().filter(|n| !self.profiles.contains_key(*n)) {
+                anyhow::bail!("auto_profile.{} names unknown profile {}", field_name, name);
+            }
+        }
+
         Ok(())
     }
 
+    /// This configuration with profile `name`'s overrides applied. Fails
+    /// for an unknown profile, or one whose overrides don't parse or make
+    /// the configuration invalid.
+    pub fn with_profile(&self, name: &str) -> Result<AxiomConfig> {
+        let profile = self
+            .profiles
+            .get(name)
+            .with_context(|| format!("Unknown profile: {}", name))?;
+        let mut config = self.clone();
+        if let Some(changes) = &profile.effects {
+            config.effects = overlay(&self.effects, changes)
+                .with_context(|| format!("Invalid profiles.{}.effects", name))?;
+        }
+        if let Some(changes) = &profile.workspace {
+            config.workspace = overlay(&self.workspace, changes)
+                .with_context(|| format!("Invalid profiles.{}.workspace", name))?;
+        }
+        if let Some(changes) = &profile.input {
+            config.input = overlay(&self.input, changes)
+                .with_context(|| format!("Invalid profiles.{}.input", name))?;
+        }
+        config
+            .validate()
+            .with_context(|| format!("Profile {} makes the configuration invalid", name))?;
+        Ok(config)
+    }
+
     /// Save configuration to a TOML file (atomic write).
     ///
     /// Writes to a temp file in the same directory and renames, so a
@@ -638,10 +1759,13 @@ impl AxiomConfig {
         // Helper to decide if a section in partial differs from default (meaningfully provided)
         let workspace_changed = partial.workspace != default_config.workspace;
         let window_changed = partial.window != default_config.window;
+        let decoration_changed = partial.decoration != default_config.decoration;
         let input_changed = partial.input != default_config.input;
         let bindings_changed = partial.bindings != default_config.bindings;
         let output_changed = partial.output != default_config.output;
         let general_changed = partial.general != default_config.general;
+        let effects_changed = partial.effects != default_config.effects;
+        let performance_changed = partial.performance != default_config.performance;
 
         if workspace_changed {
             self.workspace = partial.workspace;
@@ -649,6 +1773,9 @@ impl AxiomConfig {
         if window_changed {
             self.window = partial.window;
         }
+        if decoration_changed {
+            self.decoration = partial.decoration;
+        }
         if input_changed {
             self.input = partial.input;
         }
@@ -661,6 +1788,24 @@ impl AxiomConfig {
         if general_changed {
             self.general = partial.general;
         }
+        if effects_changed {
+            self.effects = partial.effects;
+        }
+        if performance_changed {
+            self.performance = partial.performance;
+        }
+        if !partial.window_rules.is_empty() {
+            self.window_rules = partial.window_rules;
+        }
+        if !partial.outputs.is_empty() {
+            self.outputs = partial.outputs;
+        }
+        if !partial.profiles.is_empty() {
+            self.profiles = partial.profiles;
+        }
+        if partial.auto_profile != default_config.auto_profile {
+            self.auto_profile = partial.auto_profile;
+        }
 
         self
     }
@@ -671,6 +1816,29 @@ impl AxiomConfig {
     }
 }
 
+/// `base` with the keys in `changes` replaced, recursing into nested
+/// tables so a profile can change one field of a sub-section.
+fn overlay<T: Serialize + serde::de::DeserializeOwned>(
+    base: &T,
+    changes: &toml::Table,
+) -> Result<T> {
+    fn merge(into: &mut toml::Table, changes: &toml::Table) {
+        for (key, value) in changes {
+            match (into.get_mut(key), value) {
+                (Some(toml::Value::Table(inner)), toml::Value::Table(changes)) => {
+                    merge(inner, changes)
+                }
+                _ => {
+                    into.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+    let mut table = toml::Table::try_from(base)?;
+    merge(&mut table, changes);
+    Ok(table.try_into()?)
+}
+
 #[cfg(test)]
 mod tests;
 
@@ -22,10 +22,19 @@ prop_compose! {
             auto_scroll,
             workspace_width,
             gaps,
+            inner_gap: None,
+            outer_gap: None,
+            smart_gaps: false,
             smooth_scrolling,
             momentum_friction: WorkspaceConfig::default().momentum_friction,
             momentum_min_velocity: WorkspaceConfig::default().momentum_min_velocity,
             snap_threshold_px: WorkspaceConfig::default().snap_threshold_px,
+            edge_resistance: 0.0,
+            layout_min_delta_px: WorkspaceConfig::default().layout_min_delta_px,
+            max_columns: None,
+            wrap: false,
+            scroll_easing: EasingFunction::default(),
+            backgrounds: BTreeMap::new(),
         }
     }
 }
@@ -46,6 +55,9 @@ prop_compose! {
         default_layout in prop_oneof![
             Just("horizontal".to_string()),
             Just("vertical".to_string()),
+            Just("master_stack".to_string()),
+            Just("grid".to_string()),
+            Just("spiral".to_string()),
         ],
     ) -> WindowConfig {
         WindowConfig {
@@ -56,6 +68,11 @@ prop_compose! {
             inactive_border_color,
             gap,
             default_layout,
+            snap_threshold: 10,
+            close_grace_ms: 3000,
+            max_windows: None,
+            overflow_policy: "reject".to_string(),
+            open_placement: "focused_column".to_string(),
         }
     }
 }
@@ -75,6 +92,11 @@ prop_compose! {
             mouse_accel,
             touchpad_tap,
             natural_scrolling,
+            focus_policy: "click".to_string(),
+            focus_follows_mouse_delay_ms: 150,
+            mouse_bindings: MouseBindingsConfig::default(),
+            gestures: BTreeMap::new(),
+            seats: BTreeMap::new(),
         }
     }
 }
@@ -105,6 +127,16 @@ prop_compose! {
             launch_terminal: "Super+Enter".to_string(),
             launch_launcher: "Super+Space".to_string(),
             focus_next_output: "Super+Tab".to_string(),
+            swap_window_left: "Super+Shift+H".to_string(),
+            swap_window_right: "Super+Shift+L".to_string(),
+            toggle_selection: "Super+Ctrl+Space".to_string(),
+            move_selected_here: "Super+Ctrl+m".to_string(),
+            close_selected: "Super+Ctrl+q".to_string(),
+            cascade_windows: "Super+Ctrl+c".to_string(),
+            center_window: "Super+c".to_string(),
+            move_to_scratchpad: "Super+Ctrl+minus".to_string(),
+            toggle_scratchpad: "Super+minus".to_string(),
+            cycle_color_filter: "Super+Ctrl+i".to_string(),
             quit,
             mouse_back: BindingsConfig::default_mouse_back(),
             mouse_forward: BindingsConfig::default_mouse_forward(),
@@ -164,6 +196,7 @@ prop_compose! {
             vsync,
             default_terminal: "xterm".into(),
             default_launcher: "dmenu_run".into(),
+            hud: false,
         }
     }
 }
@@ -196,6 +229,12 @@ prop_compose! {
             // field.
             features: FeaturesConfig::default(),
             output: OutputConfig::default(),
+            outputs: Vec::new(),
+            performance: PerformanceConfig::default(),
+            security: SecurityConfig::default(),
+            decoration: DecorationConfig::default(),
+            profiles: BTreeMap::new(),
+            auto_profile: AutoProfileConfig::default(),
         }
     }
 }
@@ -210,7 +249,10 @@ prop_compose! {
             Just("noop".to_string()),
         ],
     ) -> BackendConfig {
-        BackendConfig { kind }
+        BackendConfig {
+            kind,
+            socket_name: None,
+        }
     }
 }
 
@@ -143,6 +143,138 @@ fn test_configuration_validation() {
 
     invalid_config.workspace.scroll_speed = 15.0;
     assert!(invalid_config.validate().is_err());
+
+    let mut bounded = config.clone();
+    bounded.workspace.max_columns = Some(0);
+    assert!(bounded.validate().is_err());
+    bounded.workspace.max_columns = Some(9);
+    assert!(bounded.validate().is_ok());
+
+    let mut capped = config.clone();
+    capped.window.max_windows = Some(0);
+    assert!(capped.validate().is_err());
+    capped.window.max_windows = Some(2);
+    capped.window.overflow_policy = "queue".to_string();
+    assert!(capped.validate().is_err());
+    capped.window.overflow_policy = "close_oldest".to_string();
+    assert!(capped.validate().is_ok());
+
+    let mut placed = config.clone();
+    placed.window.open_placement = "cursor".to_string();
+    assert!(placed.validate().is_ok());
+    placed.window.open_placement = "random".to_string();
+    assert!(placed.validate().is_err());
+    placed.window.open_placement = "center".to_string();
+    placed.window_rules.push(WindowRule {
+        open_placement: Some("under_mouse".to_string()),
+        ..Default::default()
+    });
+    assert!(placed.validate().is_err());
+
+    let mut graded = config.clone();
+    graded.output.saturation = 0.0;
+    assert!(graded.validate().is_ok());
+    graded.output.brightness = 2.5;
+    assert!(graded.validate().is_err());
+    graded.output.brightness = 1.0;
+    graded.output.contrast = -0.1;
+    assert!(graded.validate().is_err());
+
+    let mut named = config.clone();
+    named.backend.socket_name = Some("wayland-1".to_string());
+    assert!(named.validate().is_ok());
+    named.backend.socket_name = Some("run/wayland-1".to_string());
+    assert!(named.validate().is_err());
+    named.backend.socket_name = Some(String::new());
+    assert!(named.validate().is_err());
+}
+
+#[test]
+fn test_easing_curves_hit_boundaries_and_stay_monotonic() {
+    for easing in [
+        EasingFunction::Linear,
+        EasingFunction::EaseOutCubic,
+        EasingFunction::EaseInOutCubic,
+    ] {
+        assert_eq!(easing.sample(0.0), 0.0, "{:?} at t=0", easing);
+        assert!((easing.sample(1.0) - 1.0).abs() < 1e-12, "{:?} at t=1", easing);
+        let mut previous = 0.0;
+        for i in 1..=10 {
+            let value = easing.sample(i as f64 / 10.0);
+            assert!(value >= previous, "{:?} must not go backwards", easing);
+            previous = value;
+        }
+    }
+
+    assert!((EasingFunction::Linear.sample(0.25) - 0.25).abs() < 1e-12);
+    // Ease-out is ahead of linear early on; ease-in-out is behind then ahead
+    assert!(EasingFunction::EaseOutCubic.sample(0.25) > 0.25);
+    assert!(EasingFunction::EaseInOutCubic.sample(0.25) < 0.25);
+    assert!((EasingFunction::EaseInOutCubic.sample(0.5) - 0.5).abs() < 1e-12);
+    assert!(EasingFunction::EaseInOutCubic.sample(0.75) > 0.75);
+    // Out-of-range t is clamped
+    assert_eq!(EasingFunction::EaseInOutCubic.sample(-1.0), 0.0);
+    assert!((EasingFunction::EaseOutCubic.sample(2.0) - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_scroll_easing_parses_and_validates() {
+    let config: AxiomConfig = toml::from_str(
+        r#"
+[workspace]
+scroll_speed = 1.0
+infinite_scroll = true
+auto_scroll = true
+workspace_width = 1920
+gaps = 10
+smooth_scrolling = true
+scroll_easing = { kind = "spring", stiffness = 180.0, damping = 24.0 }
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.workspace.scroll_easing,
+        EasingFunction::Spring {
+            stiffness: 180.0,
+            damping: 24.0
+        }
+    );
+    assert!(config.validate().is_ok());
+
+    let mut invalid = config.clone();
+    invalid.workspace.scroll_easing = EasingFunction::Spring {
+        stiffness: 0.0,
+        damping: 24.0,
+    };
+    assert!(invalid.validate().is_err());
+    invalid.workspace.scroll_easing = EasingFunction::Spring {
+        stiffness: 180.0,
+        damping: 0.0,
+    };
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_inner_and_outer_gaps_default_from_gaps() {
+    let toml_for = |extra: &str| {
+        format!(
+            "[workspace]\nscroll_speed = 1.0\ninfinite_scroll = true\nauto_scroll = true\n\
+             workspace_width = 1920\ngaps = 12\nsmooth_scrolling = true\n{}",
+            extra
+        )
+    };
+    let config: AxiomConfig = toml::from_str(&toml_for("")).unwrap();
+    assert_eq!(config.workspace.inner_gap(), 12);
+    assert_eq!(config.workspace.outer_gap(), 12);
+
+    let config: AxiomConfig = toml::from_str(&toml_for("outer_gap = 30\n")).unwrap();
+    assert_eq!(config.workspace.inner_gap(), 12);
+    assert_eq!(config.workspace.outer_gap(), 30);
+    assert!(config.validate().is_ok());
+
+    let mut invalid = config.clone();
+    invalid.workspace.inner_gap = Some(501);
+    assert!(invalid.validate().is_err());
 }
 
 #[test]
@@ -172,6 +304,234 @@ fn is_valid_keybinding(binding: &str) -> bool {
                 .all(|c| c.is_alphanumeric() || "+-_".contains(c)))
 }
 
+#[test]
+fn test_window_rules_parse_and_validate() -> Result<()> {
+    let config: AxiomConfig = toml::from_str(
+        r#"
+[[window_rules]]
+app_id = "foot"
+opacity = 1.0
+
+[[window_rules]]
+title = "Launcher"
+opacity = 0.8
+blur = true
+"#,
+    )?;
+    assert_eq!(config.window_rules.len(), 2);
+    assert_eq!(config.window_rules[0].app_id.as_deref(), Some("foot"));
+    assert_eq!(config.window_rules[1].blur, Some(true));
+    assert!(config.window_rules[1].matches(None, "App Launcher"));
+    assert!(config.validate().is_ok());
+
+    let mut invalid = config.clone();
+    invalid.window_rules[0].inactive_opacity = Some(1.5);
+    assert!(invalid.validate().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_output_rules_parse_and_validate() -> Result<()> {
+    let config: AxiomConfig = toml::from_str(
+        r#"
+[window]
+default_layout = "master_stack"
+
+[[outputs]]
+name = "DP-1"
+layout = "vertical"
+
+[[outputs]]
+model = "U3423WE"
+layout = "master_stack"
+gaps = 4
+"#,
+    )?;
+    assert_eq!(config.outputs.len(), 2);
+    assert_eq!(config.outputs[0].layout.as_deref(), Some("vertical"));
+    assert!(config.outputs[0].matches("DP-1", "anything"));
+    assert!(!config.outputs[0].matches("HDMI-A-1", "anything"));
+    assert!(config.outputs[1].matches("HDMI-A-1", "U3423WE"));
+    assert_eq!(config.outputs[1].gaps, Some(4));
+    assert!(config.validate().is_ok());
+
+    let mut invalid = config.clone();
+    invalid.outputs[1].gaps = Some(501);
+    assert!(invalid.validate().is_err());
+
+    // Any well-formed name is accepted, since layouts can be registered
+    // at runtime; malformed ones are not
+    let spiral: AxiomConfig = toml::from_str("[[outputs]]\nlayout = \"spiral\"\n")?;
+    assert!(spiral.validate().is_ok());
+    invalid = config.clone();
+    invalid.outputs[0].layout = Some("Master Stack".to_string());
+    assert!(invalid.validate().is_err());
+    invalid = config.clone();
+    invalid.window.default_layout = String::new();
+    assert!(invalid.validate().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_profiles_override_only_the_keys_they_set() -> Result<()> {
+    let config: AxiomConfig = toml::from_str(
+        r#"
+[effects]
+focus_transition_ms = 200
+dim_inactive = { enabled = true, strength = 0.3 }
+
+[profiles.battery.effects]
+reduce_motion = true
+dim_inactive = { enabled = false }
+
+[profiles.battery.workspace]
+scroll_speed = 2.0
+
+[auto_profile]
+on_battery = "battery"
+"#,
+    )?;
+    assert!(config.validate().is_ok());
+    assert!(config.auto_profile.is_enabled());
+
+    let battery = config.with_profile("battery")?;
+    assert!(battery.effects.reduce_motion);
+    assert!(!battery.effects.dim_inactive.enabled);
+    // Untouched keys, including ones beside a changed one, keep their value
+    assert_eq!(battery.effects.dim_inactive.strength, 0.3);
+    assert_eq!(battery.effects.focus_transition_ms, 200);
+    assert_eq!(battery.workspace.scroll_speed, 2.0);
+    assert_eq!(battery.workspace.gaps, config.workspace.gaps);
+    assert_eq!(battery.input, config.input);
+
+    assert!(config.with_profile("docked").is_err());
+    // A profile must leave a valid configuration behind
+    let mut invalid = config.clone();
+    invalid.profiles.get_mut("battery").unwrap().workspace =
+        Some(toml::from_str("scroll_speed = -1.0")?);
+    assert!(invalid.with_profile("battery").is_err());
+    invalid = config.clone();
+    invalid.auto_profile.on_ac = Some("docked".to_string());
+    assert!(invalid.validate().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_decoration_section_parses_and_validates() -> Result<()> {
+    let config: AxiomConfig = toml::from_str(
+        r#"
+[decoration]
+titlebar_height = 28
+active_color = "#3366CC"
+button_layout = "close,maximize:"
+"#,
+    )?;
+    assert_eq!(config.decoration.titlebar_height, 28);
+    assert_eq!(config.decoration.active_color, "#3366CC");
+    assert_eq!(config.decoration.inactive_color, "#1A1A1A");
+    assert_eq!(config.decoration.corner_radius, 8.0);
+    assert_eq!(config.decoration.corner_softness, 1.0);
+    assert!(config.validate().is_ok());
+
+    let mut invalid = config.clone();
+    invalid.decoration.titlebar_height = 8;
+    assert!(invalid.validate().is_err());
+    invalid = config.clone();
+    invalid.decoration.inactive_text_color = "white".to_string();
+    assert!(invalid.validate().is_err());
+    invalid = config.clone();
+    invalid.decoration.button_layout = ":minimise,close".to_string();
+    assert!(invalid.validate().is_err());
+    invalid = config.clone();
+    invalid.decoration.corner_radius = f32::NAN;
+    assert!(invalid.validate().is_err());
+    invalid = config.clone();
+    invalid.decoration.corner_softness = 12.0;
+    assert!(invalid.validate().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_gestures_parse_and_warn_on_invalid_combos() -> Result<()> {
+    let config: AxiomConfig = toml::from_str(
+        r#"
+[input.gestures]
+3_up = "toggle_fullscreen"
+2_left = "scroll_right"
+6_down = "quit"
+4_sideways = "quit"
+"#,
+    )?;
+    assert_eq!(config.input.gestures.len(), 4);
+    assert_eq!(config.input.gestures["3_up"], "toggle_fullscreen");
+    assert_eq!(InputConfig::parse_gesture_key("3_up"), Some((3, "up")));
+    assert_eq!(InputConfig::parse_gesture_key("4_sideways"), None);
+
+    let warnings = config.input.gesture_warnings();
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings.iter().any(|w| w.contains("2_left") && w.contains("client scrolling")));
+    assert!(warnings.iter().any(|w| w.contains("6_down")));
+    assert!(warnings.iter().any(|w| w.contains("4_sideways")));
+    // Warnings never stop the config from loading
+    assert!(config.validate().is_ok());
+
+    assert!(AxiomConfig::default().input.gesture_warnings().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_workspace_backgrounds_by_column_index() {
+    let config = WorkspaceConfig {
+        backgrounds: BTreeMap::from([
+            ("0".to_string(), "/usr/share/backgrounds/a.png".to_string()),
+            ("-1".to_string(), "/usr/share/backgrounds/b.jpg".to_string()),
+            ("web".to_string(), "/usr/share/backgrounds/c.png".to_string()),
+            ("2".to_string(), String::new()),
+        ]),
+        ..WorkspaceConfig::default()
+    };
+    assert_eq!(
+        config.column_backgrounds(),
+        BTreeMap::from([
+            (-1, "/usr/share/backgrounds/b.jpg".to_string()),
+            (0, "/usr/share/backgrounds/a.png".to_string()),
+        ])
+    );
+    let warnings = config.background_warnings();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings.iter().any(|w| w.contains("backgrounds.web")));
+    assert!(warnings.iter().any(|w| w.contains("backgrounds.2")));
+    assert!(WorkspaceConfig::default().background_warnings().is_empty());
+}
+
+#[test]
+fn test_window_rule_open_placement_overrides_global() {
+    let window = WindowConfig {
+        open_placement: "cursor".to_string(),
+        ..WindowConfig::default()
+    };
+    let rules = vec![
+        WindowRule {
+            app_id: Some("pavucontrol".into()),
+            open_placement: Some("center".into()),
+            ..Default::default()
+        },
+        WindowRule {
+            app_id: Some("pavucontrol".into()),
+            opacity: Some(0.9),
+            ..Default::default()
+        },
+    ];
+    assert_eq!(
+        window.open_placement_for(&rules, Some("pavucontrol"), "Volume"),
+        "center"
+    );
+    assert_eq!(
+        window.open_placement_for(&rules, Some("foot"), "shell"),
+        "cursor"
+    );
+}
+
 #[test]
 fn test_save_and_reload() -> Result<()> {
     let dir = tempdir()?;
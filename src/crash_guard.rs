@@ -0,0 +1,234 @@
+//! Crash-loop detection and safe mode.
+//!
+//! Every startup appends its time and pid to `$XDG_STATE_HOME/axiom/startups`,
+//! and once the compositor has run for [`STABLE_AFTER`] or exits cleanly its
+//! entry and those before it are cleared, so the times left in it are
+//! startups that died early, or ones still running.
+//! When [`CRASH_LIMIT`] of them fall within [`CRASH_WINDOW`], the next start
+//! is in safe mode: animations, dimming and post-process passes are off, so
+//! an effect that takes the compositor down on some GPU can't keep it from
+//! starting at all.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::warn;
+
+use crate::config::{AutoProfileConfig, AxiomConfig};
+
+/// Early deaths within [`CRASH_WINDOW`] that make the next start safe mode.
+pub const CRASH_LIMIT: usize = 3;
+/// How far back early deaths count.
+pub const CRASH_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// How long the compositor must run before its startup stops counting as
+/// a crash.
+pub const STABLE_AFTER: Duration = Duration::from_secs(30);
+
+/// Whether startups that never became stable at `crashes` (Unix seconds)
+/// call for safe mode at `now`.
+pub fn should_enter_safe_mode(crashes: &[u64], now: u64) -> bool {
+    recent(crashes, now).count() >= CRASH_LIMIT
+}
+
+fn recent(crashes: &[u64], now: u64) -> impl Iterator<Item = u64> + '_ {
+    crashes
+        .iter()
+        .copied()
+        .filter(move |&at| is_recent(at, now))
+}
+
+fn is_recent(at: u64, now: u64) -> bool {
+    at <= now && now - at <= CRASH_WINDOW.as_secs()
+}
+
+/// Startup time (Unix seconds) of a line in the startup record.
+fn startup_time(line: &str) -> Option<u64> {
+    line.split_whitespace().next()?.parse().ok()
+}
+
+/// This run's entry in the startup record.
+#[derive(Debug)]
+pub struct CrashGuard {
+    /// The startup record, `None` when there is nowhere to keep it
+    path: Option<PathBuf>,
+    /// This run's line in it, time and pid
+    entry: String,
+    safe_mode: bool,
+}
+
+impl CrashGuard {
+    /// Record this startup in `$XDG_STATE_HOME/axiom/startups`, falling
+    /// back to `~/.local/state`.
+    pub fn startup() -> Self {
+        let Some(state_dir) = crate::session::state_dir() else {
+            warn!("⚠️ No XDG_STATE_HOME or HOME, crash-loop detection is off");
+            return Self {
+                path: None,
+                entry: String::new(),
+                safe_mode: false,
+            };
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self::startup_at(state_dir.join("startups"), now, std::process::id())
+    }
+
+    /// Record a startup of process `pid` at `now` (Unix seconds) in the
+    /// record at `path`, deciding from the earlier ones whether to run in
+    /// safe mode.
+    pub fn startup_at(path: PathBuf, now: u64, pid: u32) -> Self {
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        let crashes: Vec<u64> = existing.lines().filter_map(startup_time).collect();
+        let safe_mode = should_enter_safe_mode(&crashes, now);
+
+        // Only recent startups matter; older ones are dropped as it's
+        // rewritten
+        let entry = format!("{} {}", now, pid);
+        let mut record: String = existing
+            .lines()
+            .filter(|line| startup_time(line).is_some_and(|at| is_recent(at, now)))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        record.push_str(&format!("{}\n", entry));
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, record));
+        if let Err(e) = written {
+            warn!("⚠️ Failed to record startup in {}: {}", path.display(), e);
+        }
+        Self {
+            path: Some(path),
+            entry,
+            safe_mode,
+        }
+    }
+
+    /// Whether recent crashes call for safe mode.
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// The compositor ran long enough, or exited cleanly: forget this
+    /// startup and the ones that led up to it. Entries after this run's,
+    /// such as that of an instance started with `--replace` while this
+    /// one exits, are kept.
+    pub fn mark_stable(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let Ok(record) = fs::read_to_string(path) else {
+            return;
+        };
+        let Some(ours) = record.lines().position(|line| line == self.entry) else {
+            // Already cleared, by an earlier call or a later run
+            return;
+        };
+        let later: String = record
+            .lines()
+            .skip(ours + 1)
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let cleared = if later.is_empty() {
+            fs::remove_file(path)
+        } else {
+            fs::write(path, later)
+        };
+        if let Err(e) = cleared {
+            warn!("⚠️ Failed to clear {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Turn off everything in `config` beyond plain drawing: animations,
+/// inactive dimming, colour adjustment and layered compositing. Profiles
+/// are dropped too, so none can turn them back on.
+pub fn apply_safe_mode(config: &mut AxiomConfig) {
+    config.effects.reduce_motion = true;
+    config.effects.focus_transition_ms = 0;
+    config.effects.dim_inactive.enabled = false;
+    config.output.brightness = 1.0;
+    config.output.contrast = 1.0;
+    config.output.saturation = 1.0;
+    config.output.layered_compositing = false;
+    config.profiles.clear();
+    config.auto_profile = AutoProfileConfig::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_mode_after_enough_recent_crashes() {
+        let now = 1_700_000_000;
+        let window = CRASH_WINDOW.as_secs();
+        assert!(!should_enter_safe_mode(&[], now));
+        assert!(!should_enter_safe_mode(&[now - 60, now - 30], now));
+        assert!(should_enter_safe_mode(&[now - 90, now - 60, now - 30], now));
+        // Crashes older than the window, or from a clock that has since
+        // gone back, don't count
+        assert!(!should_enter_safe_mode(
+            &[now - window - 1, now - 60, now - 30],
+            now
+        ));
+        assert!(!should_enter_safe_mode(
+            &[now + 60, now - 60, now - 30],
+            now
+        ));
+    }
+
+    #[test]
+    fn test_startups_count_until_marked_stable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("axiom").join("startups");
+        let start = 1_700_000_000;
+
+        // Three startups that each die within seconds
+        for i in 0..CRASH_LIMIT as u64 {
+            assert!(!CrashGuard::startup_at(path.clone(), start + i * 10, 100).safe_mode());
+        }
+        let guard = CrashGuard::startup_at(path.clone(), start + 30, 100);
+        assert!(guard.safe_mode());
+
+        // Running stably clears the record
+        guard.mark_stable();
+        assert!(!path.exists());
+        assert!(!CrashGuard::startup_at(path.clone(), start + 60, 100).safe_mode());
+
+        // Crashes spread out further than the window never add up
+        let apart = CRASH_WINDOW.as_secs() + 1;
+        for i in 1..=CRASH_LIMIT as u64 {
+            let guard = CrashGuard::startup_at(path.clone(), start + 60 + apart * i, 100);
+            assert!(!guard.safe_mode());
+        }
+        let record = fs::read_to_string(&path).unwrap();
+        assert_eq!(record.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_replaced_instance_leaves_the_new_startup_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("startups");
+        let start = 1_700_000_000;
+
+        // Replaced before it became stable: only its own entry goes
+        let old = CrashGuard::startup_at(path.clone(), start, 100);
+        let new = CrashGuard::startup_at(path.clone(), start + 10, 200);
+        old.mark_stable();
+        let record = fs::read_to_string(&path).unwrap();
+        assert_eq!(record, format!("{} 200\n", start + 10));
+
+        // Already stable, then cleanly exiting after the next start
+        new.mark_stable();
+        let newer = CrashGuard::startup_at(path.clone(), start + 20, 300);
+        new.mark_stable();
+        let record = fs::read_to_string(&path).unwrap();
+        assert_eq!(record, format!("{} 300\n", start + 20));
+        newer.mark_stable();
+        assert!(!path.exists());
+    }
+}
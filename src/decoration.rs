@@ -15,7 +15,7 @@
 use log::{debug, info};
 use std::collections::HashMap;
 
-use crate::config::WindowConfig;
+use crate::config::{AxiomConfig, DecorationConfig, WindowConfig};
 use crate::window::Rectangle;
 
 /// Decoration mode for windows
@@ -65,6 +65,97 @@ pub struct TitlebarButtons {
     pub maximize: ButtonState,
 }
 
+impl TitlebarButtons {
+    /// State of `button`.
+    pub fn get(&self, button: TitlebarButton) -> &ButtonState {
+        match button {
+            TitlebarButton::Close => &self.close,
+            TitlebarButton::Maximize => &self.maximize,
+            TitlebarButton::Minimize => &self.minimize,
+        }
+    }
+
+    fn get_mut(&mut self, button: TitlebarButton) -> &mut ButtonState {
+        match button {
+            TitlebarButton::Close => &mut self.close,
+            TitlebarButton::Maximize => &mut self.maximize,
+            TitlebarButton::Minimize => &mut self.minimize,
+        }
+    }
+}
+
+/// A titlebar button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitlebarButton {
+    Close,
+    Maximize,
+    Minimize,
+}
+
+/// Which titlebar buttons show and where, as set by a GNOME-style
+/// `button-layout` string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ButtonLayout {
+    /// Buttons at the left edge, from left to right
+    pub left: Vec<TitlebarButton>,
+    /// Buttons at the right edge, from left to right
+    pub right: Vec<TitlebarButton>,
+}
+
+impl Default for ButtonLayout {
+    fn default() -> Self {
+        Self {
+            left: Vec::new(),
+            right: vec![
+                TitlebarButton::Minimize,
+                TitlebarButton::Maximize,
+                TitlebarButton::Close,
+            ],
+        }
+    }
+}
+
+impl ButtonLayout {
+    /// Parse a GNOME `button-layout` such as `appmenu:minimize,maximize,close`:
+    /// buttons named before the `:` sit at the left edge, those after it
+    /// at the right, each side in the order given. `appmenu`, `menu`,
+    /// `icon` and `spacer` are accepted but not drawn, and a button named
+    /// twice shows where it is first named.
+    pub fn parse(layout: &str) -> Result<Self, String> {
+        let (left, right) = layout.split_once(':').unwrap_or((layout, ""));
+        let mut seen = Vec::new();
+        let mut side = |names: &str| -> Result<Vec<TitlebarButton>, String> {
+            let mut buttons = Vec::new();
+            for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                let button = match name {
+                    "close" => TitlebarButton::Close,
+                    "maximize" => TitlebarButton::Maximize,
+                    "minimize" => TitlebarButton::Minimize,
+                    "appmenu" | "menu" | "icon" | "spacer" => continue,
+                    _ => return Err(format!("unknown titlebar button {:?}", name)),
+                };
+                if !seen.contains(&button) {
+                    seen.push(button);
+                    buttons.push(button);
+                }
+            }
+            Ok(buttons)
+        };
+        Ok(Self {
+            left: side(left)?,
+            right: side(right)?,
+        })
+    }
+}
+
+/// One solid rectangle of a server-side titlebar, relative to the
+/// window's top-left corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitlebarFill {
+    pub rect: Rectangle,
+    pub color: [f32; 4], // RGBA
+}
+
 /// Individual button state
 #[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
@@ -122,8 +213,14 @@ pub struct DecorationTheme {
     /// Corner radius for rounded decorations
     pub corner_radius: f32,
 
+    /// Width in device pixels of the anti-aliased edge of rounded corners
+    pub corner_softness: f32,
+
     /// Font size for title text
     pub font_size: f32,
+
+    /// Which buttons the titlebar shows, on which side
+    pub button_layout: ButtonLayout,
 }
 
 /// Server-side decoration manager
@@ -190,9 +287,65 @@ impl Default for DecorationTheme {
             close_hovered: [1.0, 0.3, 0.3, 1.0], // Bright red
             close_pressed: [0.6, 0.1, 0.1, 1.0], // Dark red
             corner_radius: 8.0,
+            corner_softness: 1.0,
             font_size: 14.0,
+            button_layout: ButtonLayout::default(),
+        }
+    }
+}
+
+impl DecorationTheme {
+    /// The theme set by `[window]` borders and the `[decoration]` section.
+    /// Colors or a button layout that don't parse keep their defaults.
+    pub fn from_config(window: &WindowConfig, decoration: &DecorationConfig) -> Self {
+        let defaults = Self::default();
+        let color = |hex: &str, default| DecorationManager::parse_color(hex).unwrap_or(default);
+        Self {
+            titlebar_height: decoration.titlebar_height,
+            border_width_focused: window.border_width,
+            border_color_focused: color(&window.active_border_color, defaults.border_color_focused),
+            border_color_unfocused: color(
+                &window.inactive_border_color,
+                defaults.border_color_unfocused,
+            ),
+            titlebar_bg_focused: color(&decoration.active_color, defaults.titlebar_bg_focused),
+            titlebar_bg_unfocused: color(
+                &decoration.inactive_color,
+                defaults.titlebar_bg_unfocused,
+            ),
+            text_color_focused: color(&decoration.active_text_color, defaults.text_color_focused),
+            text_color_unfocused: color(
+                &decoration.inactive_text_color,
+                defaults.text_color_unfocused,
+            ),
+            button_size: defaults.button_size.min(decoration.titlebar_height),
+            corner_radius: decoration.corner_radius,
+            corner_softness: decoration.corner_softness,
+            button_layout: ButtonLayout::parse(&decoration.button_layout).unwrap_or_default(),
+            ..defaults
         }
     }
+
+    /// Titlebar background `focus` of the way (0.0 - 1.0) from the
+    /// unfocused to the focused color.
+    pub fn titlebar_bg(&self, focus: f32) -> [f32; 4] {
+        mix_rgba(self.titlebar_bg_unfocused, self.titlebar_bg_focused, focus)
+    }
+
+    /// Titlebar text/symbol color at `focus` (see [`Self::titlebar_bg`]).
+    pub fn text_color(&self, focus: f32) -> [f32; 4] {
+        mix_rgba(self.text_color_unfocused, self.text_color_focused, focus)
+    }
+
+    /// Border color at `focus` (see [`Self::titlebar_bg`]).
+    pub fn border_color(&self, focus: f32) -> [f32; 4] {
+        mix_rgba(self.border_color_unfocused, self.border_color_focused, focus)
+    }
+}
+
+fn mix_rgba(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    let t = t.clamp(0.0, 1.0);
+    std::array::from_fn(|i| from[i] + (to[i] - from[i]) * t)
 }
 
 impl DecorationManager {
@@ -206,18 +359,20 @@ impl DecorationManager {
     /// that minimize is a deeper-protocol feature and is currently
     /// off by default.
     pub fn new(config: &WindowConfig, minimize_enabled: bool) -> Self {
-        info!("🎨 Initializing server-side decoration manager...");
+        let theme = DecorationTheme::from_config(config, &DecorationConfig::default());
+        Self::with_theme(theme, minimize_enabled)
+    }
 
-        // Create theme from window config
-        let theme =
-            DecorationTheme {
-                border_width_focused: config.border_width,
-                border_color_focused: Self::parse_color(&config.active_border_color)
-                    .unwrap_or([0.482, 0.235, 0.929, 1.0]), // Default purple
-                border_color_unfocused: Self::parse_color(&config.inactive_border_color)
-                    .unwrap_or([0.216, 0.255, 81.0 / 255.0, 1.0]), // Default gray
-                ..DecorationTheme::default()
-            };
+    /// Construct a decoration manager themed by `config`'s `[window]` and
+    /// `[decoration]` sections, with the minimize button gated by
+    /// `features.enable_minimize`.
+    pub fn from_config(config: &AxiomConfig) -> Self {
+        let theme = DecorationTheme::from_config(&config.window, &config.decoration);
+        Self::with_theme(theme, config.features.enable_minimize)
+    }
+
+    fn with_theme(theme: DecorationTheme, minimize_enabled: bool) -> Self {
+        info!("🎨 Initializing server-side decoration manager...");
 
         info!("✅ Decoration manager initialized with theme:");
         info!("  📏 Titlebar height: {}px", theme.titlebar_height);
@@ -241,7 +396,7 @@ impl DecorationManager {
     }
 
     /// Parse hex color string to RGBA float array
-    fn parse_color(hex: &str) -> Option<[f32; 4]> {
+    pub(crate) fn parse_color(hex: &str) -> Option<[f32; 4]> {
         if !hex.starts_with('#') || hex.len() != 7 {
             return None;
         }
@@ -339,27 +494,7 @@ impl DecorationManager {
                 return;
             }
             decoration.window_width = width;
-
-            if decoration.mode == DecorationMode::ServerSide {
-                let button_size = self.theme.button_size;
-                let titlebar_height = self.theme.titlebar_height;
-                let button_y = (titlebar_height - button_size) / 2;
-                let ww = width;
-                let button_margin = 8;
-                decoration.buttons.close.bounds =
-                    Self::button_rect(ww, button_size, button_y, button_margin, 0);
-                decoration.buttons.maximize.bounds =
-                    Self::button_rect(ww, button_size, button_y, button_margin, 1);
-                if self.minimize_enabled {
-                    decoration.buttons.minimize.bounds =
-                        Self::button_rect(ww, button_size, button_y, button_margin, 2);
-                } else {
-                    // Gate per the `enable_minimize` flag — zero rect so a
-                    // stray click can never land on it (contains_point
-                    // returns false for zero-area rects).
-                    Self::zero_button_bounds(&mut decoration.buttons.minimize);
-                }
-            }
+            Self::place_buttons(&self.theme, self.minimize_enabled, decoration);
 
             debug!("📏 Updated window {} width to {}px", window_id, width);
         }
@@ -409,23 +544,7 @@ impl DecorationManager {
 
         // Update button positions after releasing the mutable borrow
         if let Some(decoration) = self.decorations.get_mut(&window_id) {
-            if decoration.mode == DecorationMode::ServerSide {
-                let button_size = self.theme.button_size;
-                let titlebar_height = self.theme.titlebar_height;
-                let button_y = (titlebar_height - button_size) / 2;
-                let ww = decoration.window_width;
-                let button_margin = 8;
-                decoration.buttons.close.bounds =
-                    Self::button_rect(ww, button_size, button_y, button_margin, 0);
-                decoration.buttons.maximize.bounds =
-                    Self::button_rect(ww, button_size, button_y, button_margin, 1);
-                if self.minimize_enabled {
-                    decoration.buttons.minimize.bounds =
-                        Self::button_rect(ww, button_size, button_y, button_margin, 2);
-                } else {
-                    Self::zero_button_bounds(&mut decoration.buttons.minimize);
-                }
-            }
+            Self::place_buttons(&self.theme, self.minimize_enabled, decoration);
         }
     }
 
@@ -574,25 +693,7 @@ impl DecorationManager {
 
     /// Update button positions based on window size and theme
     fn update_button_positions(&self, _window_id: u64, decoration: &mut WindowDecoration) {
-        if decoration.mode != DecorationMode::ServerSide {
-            return;
-        }
-
-        let button_size = self.theme.button_size;
-        let titlebar_height = self.theme.titlebar_height;
-        let button_y = (titlebar_height - button_size) / 2;
-        let ww = decoration.window_width;
-        let button_margin = 8;
-        decoration.buttons.close.bounds =
-            Self::button_rect(ww, button_size, button_y, button_margin, 0);
-        decoration.buttons.maximize.bounds =
-            Self::button_rect(ww, button_size, button_y, button_margin, 1);
-        if self.minimize_enabled {
-            decoration.buttons.minimize.bounds =
-                Self::button_rect(ww, button_size, button_y, button_margin, 2);
-        } else {
-            Self::zero_button_bounds(&mut decoration.buttons.minimize);
-        }
+        Self::place_buttons(&self.theme, self.minimize_enabled, decoration);
     }
 
     /// Get the current theme
@@ -604,44 +705,176 @@ impl DecorationManager {
     pub fn update_theme(&mut self, theme: DecorationTheme) {
         self.theme = theme;
         info!("🎨 Updated decoration theme");
-        let button_size = self.theme.button_size;
-        let titlebar_height = self.theme.titlebar_height;
-        let button_y = (titlebar_height - button_size) / 2;
-        let button_margin = 8;
-
-        // Update all window button positions using each window's stored width
-        let window_ids: Vec<u64> = self.decorations.keys().copied().collect();
-        for window_id in window_ids {
-            if let Some(decoration) = self.decorations.get_mut(&window_id) {
-                if decoration.mode == DecorationMode::ServerSide {
-                    let ww = decoration.window_width;
-                    decoration.buttons.close.bounds =
-                        Self::button_rect(ww, button_size, button_y, button_margin, 0);
-                    decoration.buttons.maximize.bounds =
-                        Self::button_rect(ww, button_size, button_y, button_margin, 1);
-                    if self.minimize_enabled {
-                        decoration.buttons.minimize.bounds =
-                            Self::button_rect(ww, button_size, button_y, button_margin, 2);
-                    } else {
-                        Self::zero_button_bounds(&mut decoration.buttons.minimize);
-                    }
-                }
+
+        // Update all window titlebars and button positions using each
+        // window's stored width
+        for decoration in self.decorations.values_mut() {
+            if decoration.mode == DecorationMode::ServerSide {
+                decoration.titlebar_height = self.theme.titlebar_height;
             }
+            Self::place_buttons(&self.theme, self.minimize_enabled, decoration);
         }
     }
 
-    /// Helper: compute button rectangle at position `idx` (0 = close, 1 =
-    /// maximize, 2 = minimize) from the right edge.
-    fn button_rect(window_w: i32, size: u32, y: u32, margin: i32, idx: usize) -> Rectangle {
-        Rectangle {
-            x: window_w - (size as i32 + margin) * (idx as i32 + 1),
-            y: y as i32,
+    /// Where the titlebar buttons of a `window_w` wide window sit, in
+    /// `theme.button_layout` order. Minimize is left out unless enabled.
+    fn button_bounds(
+        theme: &DecorationTheme,
+        minimize_enabled: bool,
+        window_w: i32,
+    ) -> Vec<(TitlebarButton, Rectangle)> {
+        let size = theme.button_size;
+        let y = (theme.titlebar_height.saturating_sub(size) / 2) as i32;
+        let step = size as i32 + BUTTON_MARGIN;
+        let rect = |x| Rectangle {
+            x,
+            y,
             width: size,
             height: size,
+        };
+        let shown =
+            |button: &&TitlebarButton| minimize_enabled || **button != TitlebarButton::Minimize;
+        let layout = &theme.button_layout;
+        let left = layout.left.iter().filter(shown);
+        let right: Vec<_> = layout.right.iter().filter(shown).collect();
+        let right_start = window_w - step * right.len() as i32;
+
+        let mut bounds: Vec<_> = left
+            .enumerate()
+            .map(|(i, &button)| (button, rect(BUTTON_MARGIN + step * i as i32)))
+            .collect();
+        bounds.extend(
+            right
+                .into_iter()
+                .enumerate()
+                .map(|(i, &button)| (button, rect(right_start + step * i as i32))),
+        );
+        bounds
+    }
+
+    /// Lay out a server-side titlebar's buttons per the theme. Buttons the
+    /// layout leaves out (and minimize, while the `enable_minimize` flag is
+    /// off) get a zero rect so a stray click can never land on them.
+    fn place_buttons(
+        theme: &DecorationTheme,
+        minimize_enabled: bool,
+        decoration: &mut WindowDecoration,
+    ) {
+        if decoration.mode != DecorationMode::ServerSide {
+            return;
+        }
+        for button in [
+            TitlebarButton::Close,
+            TitlebarButton::Maximize,
+            TitlebarButton::Minimize,
+        ] {
+            let state = decoration.buttons.get_mut(button);
+            state.visible = false;
+            Self::zero_button_bounds(state);
+        }
+        for (button, bounds) in
+            Self::button_bounds(theme, minimize_enabled, decoration.window_width)
+        {
+            let state = decoration.buttons.get_mut(button);
+            state.visible = true;
+            state.bounds = bounds;
         }
     }
+
+    /// The solid fills that draw window `window_id`'s titlebar `width`
+    /// pixels wide at `focus` (0.0 - 1.0): the bar, its top corners
+    /// rounded to `corner_radius` a row at a time, then each button's
+    /// background and symbol. Empty for windows without server-side
+    /// decorations.
+    pub fn titlebar_fills(&self, window_id: u64, width: i32, focus: f32) -> Vec<TitlebarFill> {
+        let Some(decoration) = self.decorations.get(&window_id) else {
+            return Vec::new();
+        };
+        if decoration.mode != DecorationMode::ServerSide {
+            return Vec::new();
+        }
+        let theme = &self.theme;
+        let height = theme.titlebar_height as i32;
+        let fill = |x: i32, y: i32, w: i32, h: i32, color| TitlebarFill {
+            rect: Rectangle {
+                x,
+                y,
+                width: w.max(0) as u32,
+                height: h.max(0) as u32,
+            },
+            color,
+        };
+        let mut fills = Vec::new();
+
+        // Rows within the radius are inset to follow the corner's arc
+        let bg = theme.titlebar_bg(focus);
+        let radius = (theme.corner_radius.max(0.0).round() as i32)
+            .min(height)
+            .min(width / 2);
+        for row in 0..radius {
+            let dy = (radius - row) as f32 - 0.5;
+            let r = radius as f32;
+            let inset = (r - (r * r - dy * dy).max(0.0).sqrt()).round() as i32;
+            fills.push(fill(inset, row, width - inset * 2, 1, bg));
+        }
+        fills.push(fill(0, radius, width, height - radius, bg));
+
+        let symbol = theme.text_color(focus);
+        for (button, bounds) in Self::button_bounds(theme, self.minimize_enabled, width) {
+            let state = decoration.buttons.get(button);
+            let background = match (button, state.pressed, state.hovered) {
+                (TitlebarButton::Close, true, _) => theme.close_pressed,
+                (TitlebarButton::Close, _, true) => theme.close_hovered,
+                (TitlebarButton::Close, ..) => theme.close_normal,
+                (_, true, _) => theme.button_pressed,
+                (_, _, true) => theme.button_hovered,
+                _ => theme.button_normal,
+            };
+            let (x, y, size) = (bounds.x, bounds.y, bounds.width as i32);
+            fills.push(fill(x, y, size, size, background));
+            match button {
+                // ×, drawn as a cross of two thin bars
+                TitlebarButton::Close => {
+                    let (stroke, arm) = (3, size / 2 - 2);
+                    let center = arm + 2;
+                    let long = arm * 2 + 1;
+                    fills.push(fill(
+                        x + center - arm,
+                        y + center - stroke / 2,
+                        long,
+                        stroke,
+                        symbol,
+                    ));
+                    fills.push(fill(
+                        x + center - stroke / 2,
+                        y + center - arm,
+                        stroke,
+                        long,
+                        symbol,
+                    ));
+                }
+                // □, a small filled square
+                TitlebarButton::Maximize => {
+                    let square = size / 2 - 2;
+                    let offset = (size - square) / 2;
+                    fills.push(fill(x + offset, y + offset, square, square, symbol));
+                }
+                // —, a thin horizontal line
+                TitlebarButton::Minimize => {
+                    let (line_w, line_h) = (size / 2 + 2, 3);
+                    let (off_x, off_y) = ((size - line_w) / 2, (size - line_h) / 2);
+                    fills.push(fill(x + off_x, y + off_y, line_w, line_h, symbol));
+                }
+            }
+        }
+        fills
+    }
 }
 
+/// Gap between titlebar buttons, and between the outermost ones and the
+/// window edge
+const BUTTON_MARGIN: i32 = 8;
+
 /// Actions that can be triggered by decoration interactions
 #[derive(Debug, Clone, PartialEq)]
 pub enum DecorationAction {
@@ -722,6 +955,18 @@ mod tests {
         assert!((c[3] - 1.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_theme_colors_blend_with_focus() {
+        let theme = DecorationTheme::default();
+        assert_eq!(theme.border_color(1.0), theme.border_color_focused);
+        assert_eq!(theme.border_color(0.0), theme.border_color_unfocused);
+        let mid = theme.titlebar_bg(0.5);
+        for i in 0..3 {
+            let (lo, hi) = (theme.titlebar_bg_unfocused[i], theme.titlebar_bg_focused[i]);
+            assert!((mid[i] - (lo + hi) / 2.0).abs() < 1e-6);
+        }
+    }
+
     #[test]
     fn test_parse_color_rejects_invalid() {
         assert!(DecorationManager::parse_color("FFAA33").is_none()); // no '#'
@@ -855,4 +1100,123 @@ mod tests {
         assert_eq!(bounds.width, 0);
         assert_eq!(bounds.height, 0);
     }
+
+    #[test]
+    fn test_button_layout_parses_gnome_strings() {
+        use TitlebarButton::*;
+        let parse = |layout| ButtonLayout::parse(layout).unwrap();
+        assert_eq!(parse(":minimize,maximize,close"), ButtonLayout::default());
+        let layout = parse("close,minimize:appmenu");
+        assert_eq!(layout.left, vec![Close, Minimize]);
+        assert!(layout.right.is_empty());
+        // Items Axiom doesn't draw are skipped, repeats show once
+        let layout = parse("appmenu,icon:spacer,maximize,close,maximize");
+        assert!(layout.left.is_empty());
+        assert_eq!(layout.right, vec![Maximize, Close]);
+        // No `:` puts everything on the left
+        assert_eq!(parse("close").left, vec![Close]);
+        assert_eq!(
+            parse(""),
+            ButtonLayout {
+                left: vec![],
+                right: vec![]
+            }
+        );
+        assert!(ButtonLayout::parse("close,shade").is_err());
+    }
+
+    #[test]
+    fn test_left_button_layout_moves_hit_targets() {
+        let decoration = DecorationConfig {
+            button_layout: "close,maximize:".to_string(),
+            ..DecorationConfig::default()
+        };
+        let theme = DecorationTheme::from_config(&WindowConfig::default(), &decoration);
+        let mut mgr = DecorationManager::new(&WindowConfig::default(), true);
+        mgr.update_theme(theme);
+        mgr.add_window(1, "T".into(), true, 800);
+
+        let buttons = &mgr.get_decoration(1).unwrap().buttons;
+        assert_eq!((buttons.close.bounds.x, buttons.maximize.bounds.x), (8, 40));
+        // Left out of the layout: hidden and never hit
+        assert!(!buttons.minimize.visible);
+        assert_eq!(buttons.minimize.bounds.width, 0);
+        assert_eq!(
+            mgr.handle_button_press(1, 12, 12),
+            Some(DecorationAction::Close)
+        );
+        assert_eq!(
+            mgr.handle_button_press(1, 780, 12),
+            Some(DecorationAction::StartMove)
+        );
+    }
+
+    #[test]
+    fn test_theme_colors_propagate_to_titlebar_fills() {
+        let decoration = DecorationConfig {
+            active_color: "#FF0000".to_string(),
+            inactive_color: "#0000FF".to_string(),
+            active_text_color: "#00FF00".to_string(),
+            inactive_text_color: "#000000".to_string(),
+            button_layout: ":close".to_string(),
+            corner_radius: 0.0,
+            ..DecorationConfig::default()
+        };
+        let config = AxiomConfig {
+            decoration,
+            ..AxiomConfig::default()
+        };
+        let mut mgr = DecorationManager::from_config(&config);
+        mgr.add_window(1, "T".into(), true, 800);
+        mgr.add_window(2, "CSD".into(), false, 800);
+
+        let fills = mgr.titlebar_fills(1, 800, 1.0);
+        // The bar, the close button, then the two strokes of its cross
+        assert_eq!(fills.len(), 4);
+        assert_eq!(
+            fills[0].rect,
+            Rectangle {
+                x: 0,
+                y: 0,
+                width: 800,
+                height: 32
+            }
+        );
+        assert_eq!(fills[0].color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(fills[1].rect.x, 800 - 24 - 8);
+        assert_eq!(fills[1].color, mgr.theme().close_normal);
+        assert_eq!(fills[2].color, [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(
+            mgr.titlebar_fills(1, 800, 0.0)[0].color,
+            [0.0, 0.0, 1.0, 1.0]
+        );
+
+        // Hovering a button lightens its background
+        mgr.handle_mouse_motion(1, 780, 12);
+        assert_eq!(
+            mgr.titlebar_fills(1, 800, 1.0)[1].color,
+            mgr.theme().close_hovered
+        );
+        assert!(mgr.titlebar_fills(2, 800, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_corner_radius_rounds_titlebar_top_corners() {
+        let mut mgr = DecorationManager::new(&WindowConfig::default(), false);
+        mgr.add_window(1, "T".into(), true, 800);
+        let radius = mgr.theme().corner_radius as i32;
+        let fills = mgr.titlebar_fills(1, 800, 1.0);
+        let rows = &fills[..radius as usize];
+        // Each row down the arc is inset less, and stays centred
+        assert!(rows.windows(2).all(|w| w[0].rect.x >= w[1].rect.x));
+        assert!(rows[0].rect.x > 0);
+        assert!(rows
+            .iter()
+            .all(|row| row.rect.x * 2 + row.rect.width as i32 == 800));
+        let body = &fills[radius as usize].rect;
+        assert_eq!(
+            (body.y, body.width, body.height),
+            (radius, 800, 32 - radius as u32)
+        );
+    }
 }
@@ -4,9 +4,18 @@
 //! Translates raw input events into compositor actions via configurable
 //! key binding mappings.
 
-use crate::config::{BindingsConfig, InputConfig};
+use crate::config::{BindingsConfig, InputConfig, MouseBindingsConfig};
 use log::{debug, info};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+/// Finger travel (logical px, along the dominant axis) a swipe needs before
+/// it counts as a gesture rather than a brush of the touchpad.
+const SWIPE_THRESHOLD: f64 = 50.0;
+
+/// Seat every input device belongs to unless `input.seats` routes it
+/// elsewhere.
+pub const DEFAULT_SEAT: &str = "seat0";
 
 /// Represents different types of input events
 #[derive(Debug, Clone, PartialEq)]
@@ -70,6 +79,130 @@ pub enum CompositorAction {
     Quit,
     /// Switch focus to the next output (multi-monitor)
     FocusNextOutput,
+    /// Swap the focused window with its previous neighbour
+    SwapWindowLeft,
+    /// Swap the focused window with its next neighbour
+    SwapWindowRight,
+    /// Select or deselect the focused window
+    ToggleSelection,
+    /// Move the selected windows into the focused column
+    MoveSelectedHere,
+    /// Close the selected windows
+    CloseSelected,
+    /// Cascade the floating windows on the focused output
+    CascadeWindows,
+    /// Centre the focused floating window
+    CenterWindow,
+    /// Focus the next window in the focused window's column, wrapping
+    CycleColumnNext,
+    /// Focus the previous window in the focused window's column, wrapping
+    CycleColumnPrev,
+    /// Hide the focused window in the scratchpad
+    MoveToScratchpad,
+    /// Show the scratchpad window, or hide it if it is focused
+    ToggleScratchpad,
+    /// Move the focused window on to its next colour filter
+    CycleColorFilter,
+}
+
+/// Keyboard focus policy, parsed from `input.focus_policy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusPolicy {
+    /// Focus changes only on button press (the default).
+    Click,
+    /// Focus follows the pointer after it rests on a window for the
+    /// configured delay. Empty space keeps the current focus ("sloppy").
+    FollowsMouse,
+}
+
+impl FocusPolicy {
+    /// Parse a config string. Unknown values fall back to `Click`.
+    pub fn from_config_str(s: &str) -> Self {
+        match s {
+            "follows_mouse" | "sloppy" => Self::FollowsMouse,
+            _ => Self::Click,
+        }
+    }
+}
+
+/// Keyboard interactivity requested by a layer-shell surface
+/// (`zwlr_layer_surface_v1.keyboard_interactivity`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerKeyboardMode {
+    /// Never receives keyboard focus (bars, wallpapers)
+    None,
+    /// Grabs keyboard focus while mapped (launchers, lock screens)
+    Exclusive,
+    /// Focusable like a regular window, but does not grab
+    OnDemand,
+}
+
+/// What the backend must do with keyboard focus after a layer-surface change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerFocusChange {
+    /// Nothing to do
+    Unchanged,
+    /// Give keyboard focus to this layer surface (protocol ID)
+    Grab(u32),
+    /// An exclusive layer let go; hand focus back to this window (if any)
+    Restore(Option<u64>),
+}
+
+/// The layer surface currently holding an exclusive keyboard grab.
+#[derive(Debug, Clone, Copy)]
+struct ExclusiveLayer {
+    layer_id: u32,
+    /// Toplevel that had focus before the first exclusive grab
+    previous_focus: Option<u64>,
+}
+
+/// Part of the desktop a scroll happened over, for
+/// [`InputManager::scroll_action`]. Scrolls over a window's content always
+/// go to the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollRegion {
+    /// A window's server-side titlebar
+    Titlebar,
+    /// Nowhere a window is
+    Background,
+}
+
+/// Direction of a touchpad swipe, by its dominant axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl SwipeDirection {
+    fn from_config_str(s: &str) -> Option<Self> {
+        Some(match s {
+            "left" => Self::Left,
+            "right" => Self::Right,
+            "up" => Self::Up,
+            "down" => Self::Down,
+            _ => return None,
+        })
+    }
+}
+
+/// A touchpad swipe in progress.
+#[derive(Debug, Clone, Copy)]
+struct SwipeState {
+    fingers: u32,
+    dx: f64,
+    dy: f64,
+}
+
+/// Keyboard and pointer state one seat keeps to itself.
+#[derive(Debug, Clone, Default)]
+struct SeatInput {
+    /// Modifiers held on this seat's keyboards
+    active_modifiers: Vec<String>,
+    /// Where this seat's pointer is
+    mouse_position: (f64, f64),
 }
 
 /// Processes input events and maps them to compositor actions
@@ -81,14 +214,43 @@ pub struct InputManager {
     /// Mouse button binding mappings (button_code -> action)
     mouse_bindings: HashMap<u32, CompositorAction>,
 
-    /// Current modifier state
-    active_modifiers: Vec<String>,
+    /// Scroll bindings keyed by region and direction (`true` = down)
+    scroll_bindings: HashMap<(ScrollRegion, bool), CompositorAction>,
 
-    /// Mouse state
-    mouse_position: (f64, f64),
+    /// Swipe bindings keyed by finger count and direction
+    gesture_bindings: HashMap<(u32, SwipeDirection), CompositorAction>,
+
+    /// Swipe currently being tracked, if any
+    swipe: Option<SwipeState>,
+
+    /// High-resolution wheel travel not yet adding up to a whole notch,
+    /// in `axis_value120` units
+    scroll_v120_remainder: f64,
+
+    /// Keyboard and pointer state per seat, [`DEFAULT_SEAT`] included
+    seats: BTreeMap<String, SeatInput>,
+
+    /// Input device name -> the seat it is routed to. Devices not listed
+    /// belong to [`DEFAULT_SEAT`].
+    device_seats: HashMap<String, String>,
 
     /// Input configuration (for repeat rate, etc.)
     input_config: InputConfig,
+
+    /// Active keyboard focus policy
+    focus_policy: FocusPolicy,
+
+    /// Hover time required before follows-mouse focus switches
+    focus_delay: Duration,
+
+    /// Window the pointer is currently resting on, and since when
+    hover_target: Option<(u64, Instant)>,
+
+    /// Last reported keyboard mode per layer surface (protocol ID)
+    layer_modes: HashMap<u32, LayerKeyboardMode>,
+
+    /// Layer surface with an exclusive keyboard grab, if any
+    exclusive_layer: Option<ExclusiveLayer>,
 }
 
 impl InputManager {
@@ -142,6 +304,46 @@ impl InputManager {
             bindings_config.focus_next_output.clone(),
             CompositorAction::FocusNextOutput,
         );
+        key_bindings.insert(
+            bindings_config.swap_window_left.clone(),
+            CompositorAction::SwapWindowLeft,
+        );
+        key_bindings.insert(
+            bindings_config.swap_window_right.clone(),
+            CompositorAction::SwapWindowRight,
+        );
+        key_bindings.insert(
+            bindings_config.toggle_selection.clone(),
+            CompositorAction::ToggleSelection,
+        );
+        key_bindings.insert(
+            bindings_config.move_selected_here.clone(),
+            CompositorAction::MoveSelectedHere,
+        );
+        key_bindings.insert(
+            bindings_config.close_selected.clone(),
+            CompositorAction::CloseSelected,
+        );
+        key_bindings.insert(
+            bindings_config.cascade_windows.clone(),
+            CompositorAction::CascadeWindows,
+        );
+        key_bindings.insert(
+            bindings_config.center_window.clone(),
+            CompositorAction::CenterWindow,
+        );
+        key_bindings.insert(
+            bindings_config.move_to_scratchpad.clone(),
+            CompositorAction::MoveToScratchpad,
+        );
+        key_bindings.insert(
+            bindings_config.toggle_scratchpad.clone(),
+            CompositorAction::ToggleScratchpad,
+        );
+        key_bindings.insert(
+            bindings_config.cycle_color_filter.clone(),
+            CompositorAction::CycleColorFilter,
+        );
 
         // Mouse button bindings: driven by config (not hardcoded).
         // Button codes follow Linux input event codes (0x110 = BTN_LEFT, etc.)
@@ -167,62 +369,133 @@ impl InputManager {
             }
         }
 
+        let scroll_bindings = Self::parse_scroll_bindings(&input_config.mouse_bindings);
+        let gesture_bindings = Self::parse_gesture_bindings(&input_config.gestures);
+
         debug!(
-            "🔑 Loaded {} key bindings, {} mouse bindings",
+            "🔑 Loaded {} key bindings, {} mouse bindings, {} scroll bindings, {} gesture bindings",
             key_bindings.len(),
-            mouse_bindings.len()
+            mouse_bindings.len(),
+            scroll_bindings.len(),
+            gesture_bindings.len()
         );
 
+        let mut seats = BTreeMap::new();
+        seats.insert(DEFAULT_SEAT.to_string(), SeatInput::default());
+        let mut device_seats = HashMap::new();
+        for (seat, devices) in &input_config.seats {
+            seats.entry(seat.clone()).or_insert_with(SeatInput::default);
+            for device in devices {
+                device_seats.insert(device.clone(), seat.clone());
+            }
+        }
+
         Self {
             key_bindings,
             mouse_bindings,
-            active_modifiers: Vec::new(),
-            mouse_position: (0.0, 0.0),
+            scroll_bindings,
+            gesture_bindings,
+            swipe: None,
+            scroll_v120_remainder: 0.0,
+            seats,
+            device_seats,
             input_config: input_config.clone(),
+            focus_policy: FocusPolicy::from_config_str(&input_config.focus_policy),
+            focus_delay: Duration::from_millis(input_config.focus_follows_mouse_delay_ms as u64),
+            hover_target: None,
+            layer_modes: HashMap::new(),
+            exclusive_layer: None,
         }
     }
 
-    /// Process an input event and return any triggered actions
+    /// Process an input event on the default seat and return any triggered
+    /// actions
     pub fn process_input_event(&mut self, event: InputEvent) -> Vec<CompositorAction> {
+        self.process_seat_input_event(DEFAULT_SEAT, event)
+    }
+
+    /// Process an input event from one of `seat`'s devices. Events for a
+    /// seat that isn't configured go to the default seat.
+    pub fn process_seat_input_event(
+        &mut self,
+        seat: &str,
+        event: InputEvent,
+    ) -> Vec<CompositorAction> {
+        let seat = if self.seats.contains_key(seat) {
+            seat.to_string()
+        } else {
+            DEFAULT_SEAT.to_string()
+        };
         match event {
             InputEvent::Keyboard {
                 key,
                 modifiers,
                 pressed,
-            } => self.process_keyboard_event(key, modifiers, pressed),
+            } => self.process_keyboard_event(&seat, key, modifiers, pressed),
             InputEvent::MouseButton {
                 button,
                 pressed,
                 x,
                 y,
-            } => self.process_mouse_button(button, pressed, x, y),
+            } => self.process_mouse_button(&seat, button, pressed, x, y),
             InputEvent::MouseMove {
                 x,
                 y,
                 delta_x: _,
                 delta_y: _,
             } => {
-                self.mouse_position = (x, y);
+                self.seat_input(&seat).mouse_position = (x, y);
                 Vec::new() // No actions for simple mouse movement
             }
         }
     }
 
+    fn seat_input(&mut self, seat: &str) -> &mut SeatInput {
+        self.seats.entry(seat.to_string()).or_default()
+    }
+
+    /// Every configured seat, the default seat first.
+    pub fn seat_names(&self) -> Vec<&str> {
+        let mut names = vec![DEFAULT_SEAT];
+        names.extend(
+            self.seats
+                .keys()
+                .map(String::as_str)
+                .filter(|name| *name != DEFAULT_SEAT),
+        );
+        names
+    }
+
+    /// The seat input from `device` is routed to.
+    pub fn seat_for_device(&self, device: &str) -> &str {
+        self.device_seats
+            .get(device)
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_SEAT)
+    }
+
     /// Process keyboard events
     fn process_keyboard_event(
         &mut self,
+        seat: &str,
         key: String,
         modifiers: Vec<String>,
         pressed: bool,
     ) -> Vec<CompositorAction> {
+        let active_modifiers = &mut self.seat_input(seat).active_modifiers;
         if pressed {
             // Update modifier state
             for modifier in &modifiers {
-                if !self.active_modifiers.contains(modifier) {
-                    self.active_modifiers.push(modifier.clone());
+                if !active_modifiers.contains(modifier) {
+                    active_modifiers.push(modifier.clone());
                 }
             }
 
+            // An exclusive layer surface owns every key, including our bindings.
+            if self.exclusive_layer.is_some() {
+                return Vec::new();
+            }
+
             // Create key combination string
             let key_combo = if modifiers.is_empty() {
                 key
@@ -239,7 +512,7 @@ impl InputManager {
             }
         } else {
             // Remove modifiers when keys are released
-            self.active_modifiers.retain(|m| !modifiers.contains(m));
+            active_modifiers.retain(|m| !modifiers.contains(m));
         }
 
         Vec::new()
@@ -248,12 +521,13 @@ impl InputManager {
     /// Process mouse button events
     fn process_mouse_button(
         &mut self,
+        seat: &str,
         button: MouseButton,
         pressed: bool,
         x: f64,
         y: f64,
     ) -> Vec<CompositorAction> {
-        self.mouse_position = (x, y);
+        self.seat_input(seat).mouse_position = (x, y);
 
         if pressed {
             debug!(
@@ -281,7 +555,14 @@ impl InputManager {
 
     /// Get current mouse position
     pub fn mouse_position(&self) -> (f64, f64) {
-        self.mouse_position
+        self.seat_mouse_position(DEFAULT_SEAT)
+    }
+
+    /// Where `seat`'s pointer is; the origin for an unknown seat.
+    pub fn seat_mouse_position(&self, seat: &str) -> (f64, f64) {
+        self.seats
+            .get(seat)
+            .map_or((0.0, 0.0), |input| input.mouse_position)
     }
 
     /// Get keyboard repeat rate configuration
@@ -292,9 +573,283 @@ impl InputManager {
         )
     }
 
+    /// Apply changed `[input]` settings, e.g. from a profile switch.
+    /// Seat assignments stay as they were at startup.
+    pub fn update_config(&mut self, input_config: &InputConfig) {
+        self.scroll_bindings = Self::parse_scroll_bindings(&input_config.mouse_bindings);
+        self.gesture_bindings = Self::parse_gesture_bindings(&input_config.gestures);
+        self.focus_policy = FocusPolicy::from_config_str(&input_config.focus_policy);
+        self.focus_delay = Duration::from_millis(input_config.focus_follows_mouse_delay_ms as u64);
+        self.input_config = input_config.clone();
+    }
+
+    /// Current keyboard focus policy
+    pub fn focus_policy(&self) -> FocusPolicy {
+        self.focus_policy
+    }
+
+    /// Report the window under the pointer after a motion event.
+    ///
+    /// Returns the window that should receive keyboard focus now. In
+    /// `Click` mode this is always `None`; in `FollowsMouse` mode a window is
+    /// returned once the pointer has stayed on it for the configured delay,
+    /// so merely crossing a window on the way elsewhere does not steal focus.
+    pub fn pointer_hover(
+        &mut self,
+        window: Option<u64>,
+        focused: Option<u64>,
+        now: Instant,
+    ) -> Option<u64> {
+        if self.focus_policy == FocusPolicy::Click || self.exclusive_layer.is_some() {
+            self.hover_target = None;
+            return None;
+        }
+        match window {
+            Some(id) if Some(id) != focused => {
+                if !matches!(self.hover_target, Some((target, _)) if target == id) {
+                    self.hover_target = Some((id, now));
+                }
+                self.poll_hover_focus(focused, now)
+            }
+            _ => {
+                self.hover_target = None;
+                None
+            }
+        }
+    }
+
+    /// Check whether a pending hover has outlasted the focus delay. Called
+    /// once per cycle so a pointer resting without motion still focuses.
+    pub fn poll_hover_focus(&mut self, focused: Option<u64>, now: Instant) -> Option<u64> {
+        let (id, since) = self.hover_target?;
+        if Some(id) == focused {
+            self.hover_target = None;
+            return None;
+        }
+        if now.duration_since(since) >= self.focus_delay {
+            self.hover_target = None;
+            Some(id)
+        } else {
+            None
+        }
+    }
+
+    /// Report a button press over `window`. Both policies focus on click;
+    /// any pending hover is cancelled.
+    pub fn pointer_click(&mut self, window: Option<u64>) -> Option<u64> {
+        self.hover_target = None;
+        if self.exclusive_layer.is_some() {
+            return None;
+        }
+        window
+    }
+
+    /// Record a layer surface's committed keyboard interactivity.
+    ///
+    /// `focused_window` is the toplevel holding focus right now; it is
+    /// remembered on the first exclusive grab so it can be restored later.
+    pub fn set_layer_keyboard_mode(
+        &mut self,
+        layer_id: u32,
+        mode: LayerKeyboardMode,
+        focused_window: Option<u64>,
+    ) -> LayerFocusChange {
+        let previous = self.layer_modes.insert(layer_id, mode);
+        let holds_grab = self
+            .exclusive_layer
+            .is_some_and(|grab| grab.layer_id == layer_id);
+        match mode {
+            LayerKeyboardMode::Exclusive if previous != Some(mode) => {
+                let previous_focus = match self.exclusive_layer {
+                    Some(grab) => grab.previous_focus,
+                    None => focused_window,
+                };
+                self.exclusive_layer = Some(ExclusiveLayer {
+                    layer_id,
+                    previous_focus,
+                });
+                self.hover_target = None;
+                LayerFocusChange::Grab(layer_id)
+            }
+            LayerKeyboardMode::Exclusive => LayerFocusChange::Unchanged,
+            _ if holds_grab => self.release_exclusive_layer(),
+            _ => LayerFocusChange::Unchanged,
+        }
+    }
+
+    /// Forget a destroyed layer surface, releasing its grab if it held one.
+    pub fn remove_layer(&mut self, layer_id: u32) -> LayerFocusChange {
+        self.layer_modes.remove(&layer_id);
+        if self
+            .exclusive_layer
+            .is_some_and(|grab| grab.layer_id == layer_id)
+        {
+            self.release_exclusive_layer()
+        } else {
+            LayerFocusChange::Unchanged
+        }
+    }
+
+    /// Layer surface currently holding an exclusive keyboard grab.
+    pub fn exclusive_layer(&self) -> Option<u32> {
+        self.exclusive_layer.map(|grab| grab.layer_id)
+    }
+
+    /// Hand the grab to another mapped exclusive layer if there is one,
+    /// otherwise restore the remembered toplevel focus.
+    fn release_exclusive_layer(&mut self) -> LayerFocusChange {
+        let Some(grab) = self.exclusive_layer.take() else {
+            return LayerFocusChange::Unchanged;
+        };
+        let next = self
+            .layer_modes
+            .iter()
+            .filter(|(&id, &mode)| id != grab.layer_id && mode == LayerKeyboardMode::Exclusive)
+            .map(|(&id, _)| id)
+            .max();
+        match next {
+            Some(layer_id) => {
+                self.exclusive_layer = Some(ExclusiveLayer {
+                    layer_id,
+                    previous_focus: grab.previous_focus,
+                });
+                LayerFocusChange::Grab(layer_id)
+            }
+            None => LayerFocusChange::Restore(grab.previous_focus),
+        }
+    }
+
     /// Check if a modifier is currently active
     pub fn is_modifier_active(&self, modifier: &str) -> bool {
-        self.active_modifiers.contains(&modifier.to_string())
+        self.is_seat_modifier_active(DEFAULT_SEAT, modifier)
+    }
+
+    /// Whether `modifier` is held on one of `seat`'s keyboards.
+    pub fn is_seat_modifier_active(&self, seat: &str, modifier: &str) -> bool {
+        self.seats
+            .get(seat)
+            .is_some_and(|input| input.active_modifiers.iter().any(|m| m == modifier))
+    }
+
+    /// Action bound to scrolling over `region`, if any. `delta` is the
+    /// vertical scroll amount; negative scrolls up.
+    pub fn scroll_action(&self, region: ScrollRegion, delta: f64) -> Option<CompositorAction> {
+        if delta == 0.0 {
+            return None;
+        }
+        self.scroll_bindings.get(&(region, delta > 0.0)).cloned()
+    }
+
+    /// Feed a high-resolution wheel delta in `axis_value120` units (120
+    /// per notch). Returns how many whole notches have built up, negative
+    /// for left/up; the rest carries over to the next call. Reversing
+    /// direction drops travel left over from the old one.
+    pub fn accumulate_scroll_v120(&mut self, value120: f64) -> i32 {
+        if value120 * self.scroll_v120_remainder < 0.0 {
+            self.scroll_v120_remainder = 0.0;
+        }
+        self.scroll_v120_remainder += value120;
+        let notches = (self.scroll_v120_remainder / 120.0).trunc();
+        self.scroll_v120_remainder -= notches * 120.0;
+        notches as i32
+    }
+
+    /// Action bound to a `fingers`-finger swipe towards `direction`, if any.
+    pub fn gesture_action(&self, fingers: u32, direction: SwipeDirection) -> Option<CompositorAction> {
+        self.gesture_bindings.get(&(fingers, direction)).cloned()
+    }
+
+    /// A touchpad swipe started. Finger counts with no bindings at all are
+    /// not tracked.
+    pub fn gesture_swipe_begin(&mut self, fingers: u32) {
+        let bound = self.gesture_bindings.keys().any(|(f, _)| *f == fingers);
+        self.swipe = bound.then_some(SwipeState {
+            fingers,
+            dx: 0.0,
+            dy: 0.0,
+        });
+    }
+
+    /// Add finger travel to the swipe in progress.
+    pub fn gesture_swipe_update(&mut self, dx: f64, dy: f64) {
+        if let Some(swipe) = self.swipe.as_mut() {
+            swipe.dx += dx;
+            swipe.dy += dy;
+        }
+    }
+
+    /// The swipe ended. Returns the bound action when it was not cancelled
+    /// and travelled far enough along its dominant axis.
+    pub fn gesture_swipe_end(&mut self, cancelled: bool) -> Option<CompositorAction> {
+        let swipe = self.swipe.take()?;
+        if cancelled || swipe.dx.abs().max(swipe.dy.abs()) < SWIPE_THRESHOLD {
+            return None;
+        }
+        let direction = if swipe.dx.abs() >= swipe.dy.abs() {
+            if swipe.dx < 0.0 {
+                SwipeDirection::Left
+            } else {
+                SwipeDirection::Right
+            }
+        } else if swipe.dy < 0.0 {
+            SwipeDirection::Up
+        } else {
+            SwipeDirection::Down
+        };
+        self.gesture_action(swipe.fingers, direction)
+    }
+
+    /// Build the swipe binding table from `[input.gestures]`. Two-finger and
+    /// malformed entries were already warned about by config validation and
+    /// are dropped here; unknown action names are skipped like elsewhere.
+    fn parse_gesture_bindings(
+        config: &BTreeMap<String, String>,
+    ) -> HashMap<(u32, SwipeDirection), CompositorAction> {
+        let mut bindings = HashMap::new();
+        for (key, action_str) in config {
+            let Some((fingers, direction)) = InputConfig::parse_gesture_key(key)
+                .filter(|(fingers, _)| (3..=5).contains(fingers))
+                .and_then(|(fingers, d)| Some((fingers, SwipeDirection::from_config_str(d)?)))
+            else {
+                continue;
+            };
+            if action_str.is_empty() {
+                continue;
+            }
+            match Self::parse_action_str(action_str) {
+                Some(action) => {
+                    bindings.insert((fingers, direction), action);
+                }
+                None => debug!("⚠️ Unknown gesture action '{}' for {} — skipped", action_str, key),
+            }
+        }
+        bindings
+    }
+
+    /// Build the scroll binding table from `[input.mouse_bindings]`.
+    /// Empty entries are unbound; unknown action names are skipped the
+    /// same way as for mouse buttons.
+    fn parse_scroll_bindings(
+        config: &MouseBindingsConfig,
+    ) -> HashMap<(ScrollRegion, bool), CompositorAction> {
+        let mut bindings = HashMap::new();
+        for (key, action_str) in [
+            ((ScrollRegion::Titlebar, false), &config.titlebar_scroll_up),
+            ((ScrollRegion::Titlebar, true), &config.titlebar_scroll_down),
+            ((ScrollRegion::Background, false), &config.background_scroll_up),
+            ((ScrollRegion::Background, true), &config.background_scroll_down),
+        ] {
+            if action_str.is_empty() {
+                continue;
+            }
+            match Self::parse_action_str(action_str) {
+                Some(action) => {
+                    bindings.insert(key, action);
+                }
+                None => debug!("⚠️ Unknown scroll binding action '{}' — skipped", action_str),
+            }
+        }
+        bindings
     }
 
     /// Parse a mouse button action string (from config) into a [`CompositorAction`].
@@ -305,6 +860,18 @@ impl InputManager {
             "scroll_right" => CompositorAction::ScrollWorkspaceRight,
             "move_left" => CompositorAction::MoveWindowLeft,
             "move_right" => CompositorAction::MoveWindowRight,
+            "swap_left" => CompositorAction::SwapWindowLeft,
+            "swap_right" => CompositorAction::SwapWindowRight,
+            "toggle_selection" => CompositorAction::ToggleSelection,
+            "move_selected_here" => CompositorAction::MoveSelectedHere,
+            "close_selected" => CompositorAction::CloseSelected,
+            "cascade_windows" => CompositorAction::CascadeWindows,
+            "center_window" => CompositorAction::CenterWindow,
+            "cycle_column_next" => CompositorAction::CycleColumnNext,
+            "cycle_column_prev" => CompositorAction::CycleColumnPrev,
+            "move_to_scratchpad" => CompositorAction::MoveToScratchpad,
+            "toggle_scratchpad" => CompositorAction::ToggleScratchpad,
+            "cycle_color_filter" => CompositorAction::CycleColorFilter,
             "close_window" => CompositorAction::CloseWindow,
             "toggle_fullscreen" => CompositorAction::ToggleFullscreen,
             "toggle_floating" => CompositorAction::ToggleFloating,
@@ -349,6 +916,41 @@ mod tests {
         assert_eq!(manager.mouse_position(), (0.0, 0.0));
     }
 
+    #[test]
+    fn test_seats_keep_separate_keyboard_and_pointer_state() {
+        let (mut input_cfg, bindings_cfg) = make_configs();
+        input_cfg
+            .seats
+            .insert("seat1".into(), vec!["kiosk-keyboard".into()]);
+        let mut manager = InputManager::new(&input_cfg, &bindings_cfg);
+
+        assert_eq!(manager.seat_names(), vec![DEFAULT_SEAT, "seat1"]);
+        assert_eq!(manager.seat_for_device("kiosk-keyboard"), "seat1");
+        assert_eq!(manager.seat_for_device("laptop-keyboard"), DEFAULT_SEAT);
+
+        manager.process_seat_input_event(
+            "seat1",
+            InputEvent::Keyboard {
+                key: "a".into(),
+                modifiers: vec!["Ctrl".into()],
+                pressed: true,
+            },
+        );
+        manager.process_seat_input_event(
+            "seat1",
+            InputEvent::MouseMove {
+                x: 10.0,
+                y: 20.0,
+                delta_x: 0.0,
+                delta_y: 0.0,
+            },
+        );
+        assert!(manager.is_seat_modifier_active("seat1", "Ctrl"));
+        assert!(!manager.is_modifier_active("Ctrl"));
+        assert_eq!(manager.seat_mouse_position("seat1"), (10.0, 20.0));
+        assert_eq!(manager.mouse_position(), (0.0, 0.0));
+    }
+
     #[test]
     fn test_simulate_key_press_known_binding() {
         let (input_cfg, bindings_cfg) = make_configs();
@@ -402,6 +1004,237 @@ mod tests {
         assert!(!manager.is_modifier_active("Super"));
     }
 
+    #[test]
+    fn test_follows_mouse_focuses_after_delay() {
+        use crate::config::WindowConfig;
+        use crate::window::WindowManager;
+
+        let (mut input_cfg, bindings_cfg) = make_configs();
+        input_cfg.focus_policy = "follows_mouse".into();
+        input_cfg.focus_follows_mouse_delay_ms = 100;
+        let mut manager = InputManager::new(&input_cfg, &bindings_cfg);
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let a = wm.add_window("a".into());
+        let b = wm.add_window("b".into());
+        assert_eq!(wm.focused_window_id(), Some(a));
+
+        let t0 = Instant::now();
+        // Crossing into `b` starts the timer but does not focus yet
+        assert_eq!(manager.pointer_hover(Some(b), wm.focused_window_id(), t0), None);
+        let early = t0 + Duration::from_millis(50);
+        assert_eq!(manager.pointer_hover(Some(b), wm.focused_window_id(), early), None);
+
+        // Resting past the delay focuses, even without further motion
+        let late = t0 + Duration::from_millis(120);
+        let target = manager.poll_hover_focus(wm.focused_window_id(), late);
+        assert_eq!(target, Some(b));
+        wm.focus_window(b);
+        assert_eq!(wm.focused_window_id(), Some(b));
+    }
+
+    #[test]
+    fn test_follows_mouse_crossing_restarts_delay() {
+        let (mut input_cfg, bindings_cfg) = make_configs();
+        input_cfg.focus_policy = "sloppy".into();
+        input_cfg.focus_follows_mouse_delay_ms = 100;
+        let mut manager = InputManager::new(&input_cfg, &bindings_cfg);
+        assert_eq!(manager.focus_policy(), FocusPolicy::FollowsMouse);
+
+        let t0 = Instant::now();
+        assert_eq!(manager.pointer_hover(Some(2), Some(1), t0), None);
+        // Moving on to another window before the delay resets the timer
+        let t1 = t0 + Duration::from_millis(80);
+        assert_eq!(manager.pointer_hover(Some(3), Some(1), t1), None);
+        let t2 = t0 + Duration::from_millis(120);
+        assert_eq!(manager.poll_hover_focus(Some(1), t2), None);
+        let t3 = t1 + Duration::from_millis(100);
+        assert_eq!(manager.poll_hover_focus(Some(1), t3), Some(3));
+    }
+
+    #[test]
+    fn test_click_policy_ignores_motion() {
+        let (input_cfg, bindings_cfg) = make_configs();
+        let mut manager = InputManager::new(&input_cfg, &bindings_cfg);
+        assert_eq!(manager.focus_policy(), FocusPolicy::Click);
+
+        let t0 = Instant::now();
+        assert_eq!(manager.pointer_hover(Some(2), Some(1), t0), None);
+        let later = t0 + Duration::from_secs(5);
+        assert_eq!(manager.pointer_hover(Some(2), Some(1), later), None);
+        assert_eq!(manager.poll_hover_focus(Some(1), later), None);
+        assert_eq!(manager.pointer_click(Some(2)), Some(2));
+    }
+
+    #[test]
+    fn test_exclusive_layer_captures_keyboard_and_restores_focus() {
+        let (input_cfg, bindings_cfg) = make_configs();
+        let mut manager = InputManager::new(&input_cfg, &bindings_cfg);
+        let press_quit = |m: &mut InputManager| {
+            m.process_input_event(InputEvent::Keyboard {
+                key: "q".into(),
+                modifiers: vec!["Super".into(), "Shift".into()],
+                pressed: true,
+            })
+        };
+        assert_eq!(press_quit(&mut manager), vec![CompositorAction::Quit]);
+
+        // A bar asks for no keyboard at all
+        assert_eq!(
+            manager.set_layer_keyboard_mode(10, LayerKeyboardMode::None, Some(1)),
+            LayerFocusChange::Unchanged
+        );
+
+        // A launcher grabs the keyboard: bindings and click focus are suppressed
+        assert_eq!(
+            manager.set_layer_keyboard_mode(11, LayerKeyboardMode::Exclusive, Some(1)),
+            LayerFocusChange::Grab(11)
+        );
+        assert_eq!(manager.exclusive_layer(), Some(11));
+        assert!(press_quit(&mut manager).is_empty());
+        assert_eq!(manager.pointer_click(Some(2)), None);
+
+        // Destroying the launcher hands focus back to the old toplevel
+        assert_eq!(manager.remove_layer(11), LayerFocusChange::Restore(Some(1)));
+        assert_eq!(manager.exclusive_layer(), None);
+        assert_eq!(press_quit(&mut manager), vec![CompositorAction::Quit]);
+        assert_eq!(manager.remove_layer(10), LayerFocusChange::Unchanged);
+    }
+
+    #[test]
+    fn test_exclusive_layer_downgrade_releases_grab() {
+        let (input_cfg, bindings_cfg) = make_configs();
+        let mut manager = InputManager::new(&input_cfg, &bindings_cfg);
+        manager.set_layer_keyboard_mode(5, LayerKeyboardMode::Exclusive, Some(3));
+        // Re-committing the same mode is a no-op
+        assert_eq!(
+            manager.set_layer_keyboard_mode(5, LayerKeyboardMode::Exclusive, None),
+            LayerFocusChange::Unchanged
+        );
+        assert_eq!(
+            manager.set_layer_keyboard_mode(5, LayerKeyboardMode::OnDemand, None),
+            LayerFocusChange::Restore(Some(3))
+        );
+    }
+
+    #[test]
+    fn test_scroll_over_background_scrolls_workspace() {
+        let (input, bindings) = make_configs();
+        let im = InputManager::new(&input, &bindings);
+        assert_eq!(
+            im.scroll_action(ScrollRegion::Background, -10.0),
+            Some(CompositorAction::ScrollWorkspaceLeft)
+        );
+        assert_eq!(
+            im.scroll_action(ScrollRegion::Background, 10.0),
+            Some(CompositorAction::ScrollWorkspaceRight)
+        );
+        assert_eq!(im.scroll_action(ScrollRegion::Background, 0.0), None);
+    }
+
+    #[test]
+    fn test_scroll_over_titlebar_cycles_column_windows() {
+        let (mut input, bindings) = make_configs();
+        let im = InputManager::new(&input, &bindings);
+        assert_eq!(
+            im.scroll_action(ScrollRegion::Titlebar, 10.0),
+            Some(CompositorAction::CycleColumnNext)
+        );
+        assert_eq!(
+            im.scroll_action(ScrollRegion::Titlebar, -10.0),
+            Some(CompositorAction::CycleColumnPrev)
+        );
+
+        // Unbound and unknown entries leave the scroll alone
+        input.mouse_bindings.titlebar_scroll_down = String::new();
+        input.mouse_bindings.titlebar_scroll_up = "spin_around".to_string();
+        let im = InputManager::new(&input, &bindings);
+        assert_eq!(im.scroll_action(ScrollRegion::Titlebar, 10.0), None);
+        assert_eq!(im.scroll_action(ScrollRegion::Titlebar, -10.0), None);
+    }
+
+    #[test]
+    fn test_gesture_dispatch_table() {
+        let (mut input, bindings) = make_configs();
+        let im = InputManager::new(&input, &bindings);
+        assert_eq!(
+            im.gesture_action(3, SwipeDirection::Left),
+            Some(CompositorAction::ScrollWorkspaceRight)
+        );
+        assert_eq!(
+            im.gesture_action(3, SwipeDirection::Right),
+            Some(CompositorAction::ScrollWorkspaceLeft)
+        );
+        assert_eq!(im.gesture_action(3, SwipeDirection::Up), None);
+
+        input.gestures = BTreeMap::from([
+            ("4_up".to_string(), "launch_launcher".to_string()),
+            ("2_left".to_string(), "scroll_right".to_string()),
+            ("4_down".to_string(), "spin_around".to_string()),
+            ("three_left".to_string(), "quit".to_string()),
+        ]);
+        let im = InputManager::new(&input, &bindings);
+        assert_eq!(
+            im.gesture_action(4, SwipeDirection::Up),
+            Some(CompositorAction::LaunchLauncher)
+        );
+        // Two-finger, unknown actions and malformed keys never bind
+        assert_eq!(im.gesture_action(2, SwipeDirection::Left), None);
+        assert_eq!(im.gesture_action(4, SwipeDirection::Down), None);
+        assert_eq!(im.gesture_action(3, SwipeDirection::Left), None);
+    }
+
+    #[test]
+    fn test_swipe_recognizer_picks_dominant_axis_past_threshold() {
+        let (input, bindings) = make_configs();
+        let mut im = InputManager::new(&input, &bindings);
+
+        im.gesture_swipe_begin(3);
+        im.gesture_swipe_update(-40.0, 10.0);
+        im.gesture_swipe_update(-30.0, -5.0);
+        assert_eq!(
+            im.gesture_swipe_end(false),
+            Some(CompositorAction::ScrollWorkspaceRight)
+        );
+
+        // Too short, cancelled, or a finger count with nothing bound
+        im.gesture_swipe_begin(3);
+        im.gesture_swipe_update(20.0, 0.0);
+        assert_eq!(im.gesture_swipe_end(false), None);
+        im.gesture_swipe_begin(3);
+        im.gesture_swipe_update(200.0, 0.0);
+        assert_eq!(im.gesture_swipe_end(true), None);
+        im.gesture_swipe_begin(4);
+        im.gesture_swipe_update(200.0, 0.0);
+        assert_eq!(im.gesture_swipe_end(false), None);
+    }
+
+    #[test]
+    fn test_small_value120_steps_add_up_to_a_notch() {
+        let (input, bindings) = make_configs();
+        let mut im = InputManager::new(&input, &bindings);
+        // A high-resolution mouse reporting quarter notches
+        for _ in 0..3 {
+            assert_eq!(im.accumulate_scroll_v120(30.0), 0);
+        }
+        assert_eq!(im.accumulate_scroll_v120(30.0), 1);
+        assert_eq!(im.accumulate_scroll_v120(-30.0), 0);
+        assert_eq!(im.accumulate_scroll_v120(-90.0), -1);
+    }
+
+    #[test]
+    fn test_value120_remainder_carries_over() {
+        let (input, bindings) = make_configs();
+        let mut im = InputManager::new(&input, &bindings);
+        assert_eq!(im.accumulate_scroll_v120(250.0), 2);
+        // 10 left over, so 110 more completes the next notch
+        assert_eq!(im.accumulate_scroll_v120(109.0), 0);
+        assert_eq!(im.accumulate_scroll_v120(1.0), 1);
+        // Switching direction discards the leftover travel
+        assert_eq!(im.accumulate_scroll_v120(100.0), 0);
+        assert_eq!(im.accumulate_scroll_v120(-119.0), 0);
+        assert_eq!(im.accumulate_scroll_v120(-1.0), -1);
+    }
+
     #[test]
     fn test_shutdown() {
         let (input_cfg, bindings_cfg) = make_configs();
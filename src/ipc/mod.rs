@@ -27,6 +27,8 @@ use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::config::AxiomConfig;
+use crate::window::{WindowOperation, WindowTreeNode};
+use crate::workspace::ScrollableWorkspaces;
 
 /// Maximum number of concurrent IPC client connections.
 const MAX_CONNECTIONS: usize = 16;
@@ -35,7 +37,7 @@ const CLIENT_IDLE_TIMEOUT_SECS: u64 = 60;
 
 /// Whitelisted `LazyUIMessage::WorkspaceCommand.action` strings. Unknown actions
 /// are rejected with status `unknown_action` so callers can distinguish
-/// future-supported actions from outright typos. All 10 actions are wired
+/// future-supported actions from outright typos. All 12 actions are wired
 /// end-to-end: the IPC layer validates against this list and forwards known
 /// actions to the compositor via `cmd_tx`, and `AxiomCompositor::process_messages`
 /// dispatches them to the workspace engine (`WorkspaceTape` / `ScrollableWorkspaces`).
@@ -50,10 +52,27 @@ const KNOWN_WORKSPACE_ACTIONS: &[&str] = &[
     "minimize_window",
     "restore_window",
     "toggle_fullscreen",
+    "tile_floating",
+    "scroll_to_named",
+    "name_column",
+    "scroll_to_window",
+    "mirror",
+    "unmirror",
+    "set_background",
+    "clear_background",
+    "select_window",
+    "clear_selection",
+    "move_selected_to_column",
+    "close_selected",
+    "hotplug_connect",
+    "hotplug_disconnect",
+    "toggle_hud",
 ];
 
 /// Maximum accepted scroll speed.
 const MAX_SCROLL_SPEED: f64 = 100.0;
+/// Maximum accepted `output.brightness` / `contrast` / `saturation`.
+const MAX_COLOR_ADJUSTMENT: f64 = 2.0;
 /// Maximum size of a single line from an IPC client (64 KiB).
 const MAX_IPC_LINE_BYTES: usize = 64 * 1024;
 
@@ -86,6 +105,83 @@ pub struct LiveMetrics {
     pub active_windows: u32,
     /// Index of the workspace the user is currently focused on.
     pub current_workspace: i32,
+    /// Frames presented since startup.
+    pub frames_presented: u64,
+    /// Frames dropped because the output surface was not ready.
+    pub frames_dropped: u64,
+    /// Times the output surface was lost or went out of date.
+    pub surface_lost: u64,
+}
+
+/// One column of the active workspace tape as reported by `GetWorkspaces`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceColumnInfo {
+    /// Column index; negative indices are columns left of the origin.
+    pub index: i32,
+    /// Number of windows in the column (0 for an empty column).
+    pub window_count: usize,
+    /// Whether the column holds any window; bars use this for the
+    /// occupied/empty indicator state.
+    pub occupied: bool,
+    /// Window ids in column order.
+    pub windows: Vec<u64>,
+    /// User-assigned name, if the column has one.
+    pub name: Option<String>,
+}
+
+/// Workspace layout answered to `GetWorkspaces`. Like [`LiveMetrics`] it
+/// is pushed from the compositor tick, so queries never touch the
+/// workspace lock from the IPC side.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceSnapshot {
+    pub focused_column: i32,
+    pub scroll_position: f64,
+    /// Columns ordered by index.
+    pub columns: Vec<WorkspaceColumnInfo>,
+}
+
+impl WorkspaceSnapshot {
+    /// Capture the active tape of `workspaces`.
+    pub fn capture(workspaces: &ScrollableWorkspaces) -> Self {
+        Self {
+            focused_column: workspaces.focused_column_index(),
+            scroll_position: workspaces.current_position(),
+            columns: workspaces
+                .column_window_counts()
+                .into_iter()
+                .map(|(index, window_count)| WorkspaceColumnInfo {
+                    index,
+                    window_count,
+                    occupied: window_count > 0,
+                    windows: workspaces.windows_in_column(index),
+                    name: workspaces.column_name(index).map(str::to_owned),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One window as reported by `GetWindows`, with its recent commit cadence
+/// and upload volume.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct WindowInfo {
+    pub id: u64,
+    pub title: String,
+    pub app_id: Option<String>,
+    /// Process behind the window's client, when its connection carried
+    /// credentials.
+    pub pid: Option<i32>,
+    /// File name of that process's executable.
+    pub executable: Option<String>,
+    /// Surface commits over the last second.
+    pub commits_per_sec: f64,
+    /// Bytes its commits asked the renderer to upload over the last second.
+    pub bytes_per_sec: u64,
+    /// Time between its last two commits, if it committed twice.
+    pub last_frame_interval_ms: Option<f64>,
+    /// Whether its uploads dominate those of every other window; the
+    /// likely cause of dropped frames.
+    pub high_bandwidth: bool,
 }
 
 /// Returns true when `action` is in the whitelisted
@@ -114,6 +210,13 @@ pub enum AxiomMessage {
         frame_time: f32,
         active_windows: u32,
         current_workspace: i32,
+        /// Present totals since startup; absent from older senders
+        #[serde(default)]
+        frames_presented: u64,
+        #[serde(default)]
+        frames_dropped: u64,
+        #[serde(default)]
+        surface_lost: u64,
     },
 
     /// User interaction events
@@ -137,6 +240,23 @@ pub enum AxiomMessage {
         value: serde_json::Value,
     },
 
+    /// An output was connected or disconnected. For a disconnect the
+    /// geometry is where the output used to be.
+    /// Wire schema (serde JSON):
+    /// ```json
+    /// {"type":"OutputChanged","timestamp":<u64>,"output":"<str>",
+    ///  "connected":<bool>,"x":<i32>,"y":<i32>,"width":<u32>,"height":<u32>}
+    /// ```
+    OutputChanged {
+        timestamp: u64,
+        output: String,
+        connected: bool,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+
     /// Compositor startup notification
     StartupComplete {
         version: String,
@@ -170,6 +290,58 @@ pub enum AxiomMessage {
         current_workspace: i32,
         note: String,
     },
+
+    /// Column layout of the active workspace tape, answering `GetWorkspaces`.
+    /// Wire schema (serde JSON):
+    /// ```json
+    /// {"type":"Workspaces","focused_column":<i32>,"scroll_position":<f64>,
+    ///  "columns":[{"index":<i32>,"window_count":<usize>,"windows":[<u64>]}]}
+    /// ```
+    Workspaces {
+        focused_column: i32,
+        scroll_position: f64,
+        columns: Vec<WorkspaceColumnInfo>,
+    },
+
+    /// Broadcast when the focused column changes (scroll_to_column,
+    /// scroll_left, scroll_right or any IPC action that moves focus).
+    /// Emitted once per change, not on every scroll animation frame.
+    WorkspaceChanged {
+        timestamp: u64,
+        previous_column: i32,
+        focused_column: i32,
+        scroll_position: f64,
+    },
+
+    /// Every window with its frame statistics, answering `GetWindows`,
+    /// ordered by id.
+    /// Wire schema (serde JSON):
+    /// ```json
+    /// {"type":"Windows","windows":[{"id":<u64>,"title":"<str>",
+    ///  "app_id":"<str>"|null,"pid":<i32>|null,"executable":"<str>"|null,
+    ///  "commits_per_sec":<f64>,"bytes_per_sec":<u64>,
+    ///  "last_frame_interval_ms":<f64>|null,"high_bandwidth":<bool>}]}
+    /// ```
+    Windows { windows: Vec<WindowInfo> },
+
+    /// The window hierarchy, answering `GetWindowTree`: root windows by
+    /// id, each with its dialogs and popups nested under `children`.
+    /// Wire schema (serde JSON):
+    /// ```json
+    /// {"type":"WindowTree","windows":[{"id":<u64>,
+    ///  "kind":"window"|"dialog"|"popup",
+    ///  "geometry":{"x":<i32>,"y":<i32>,"width":<u32>,"height":<u32>},
+    ///  "children":[...]}]}
+    /// ```
+    WindowTree { windows: Vec<WindowTreeNode> },
+
+    /// A request was refused before reaching the compositor. `request`
+    /// is the `type` of the refused message.
+    /// Wire schema (serde JSON):
+    /// ```json
+    /// {"type":"Error","request":"<str>","message":"<str>"}
+    /// ```
+    Error { request: String, message: String },
 }
 
 /// Messages sent from Lazy UI to Axiom (optimization commands)
@@ -196,7 +368,7 @@ pub enum LazyUIMessage {
     /// `unknown_action` ACK. Known actions are forwarded via the mpsc command
     /// channel to the compositor's `process_messages`, which dispatches them
     /// end-to-end to the workspace engine (`WorkspaceTape` /
-    /// `ScrollableWorkspaces`). All 10 actions are wired and executed.
+    /// `ScrollableWorkspaces`). All 12 actions are wired and executed.
     WorkspaceCommand {
         action: String,
         parameters: serde_json::Value,
@@ -205,12 +377,26 @@ pub enum LazyUIMessage {
     /// Per-window blur control. `radius` in pixels (0..=32); 0 disables blur.
     SetWindowBlur { window_id: u64, radius: f32 },
 
+    /// Minimize, maximize, close... window `window_id`, e.g.
+    /// `{"type":"WindowOp","window_id":3,"op":{"kind":"set_opacity","opacity":0.8}}`.
+    /// Unknown windows are answered with an `Error` instead of an ACK.
+    WindowOp { window_id: u64, op: WindowOperation },
+
     /// System health check request
     HealthCheck,
 
     /// Request performance report
     GetPerformanceReport,
 
+    /// Request the column layout of the active workspace tape
+    GetWorkspaces,
+
+    /// Request every window with its frame statistics
+    GetWindows,
+
+    /// Request the window/dialog/popup hierarchy, for debugging
+    GetWindowTree,
+
     /// Set compositor clipboard content
     SetClipboard { text: String },
 
@@ -221,6 +407,15 @@ pub enum LazyUIMessage {
         text: String,
         mime_type: String,
     },
+
+    /// Shut the compositor down as if it got SIGTERM; what `axiom
+    /// --replace` sends to the instance it takes over from
+    Shutdown,
+
+    /// Switch to the `[profiles.<name>]` config profile, e.g.
+    /// `{"type":"SetProfile","name":"battery"}`. Unknown profiles are
+    /// answered with an `Error` instead of an ACK.
+    SetProfile { name: String },
 }
 
 /// Per-client IPC connection state
@@ -262,6 +457,20 @@ pub struct AxiomIPCServer {
     /// so monitoring clients can distinguish "no compositor wired" from
     /// "all metrics legitimately zero".
     live_metrics_handle: Option<Arc<parking_lot::RwLock<LiveMetrics>>>,
+    /// Latest workspace layout pushed by the compositor via
+    /// `set_workspace_snapshot`. `None` until the first push, in which
+    /// case `GetWorkspaces` answers with an empty default snapshot.
+    workspace_snapshot_handle: Option<Arc<parking_lot::RwLock<WorkspaceSnapshot>>>,
+    /// Latest window list pushed by the compositor via
+    /// `set_window_snapshot`, answered to `GetWindows`.
+    window_snapshot_handle: Option<Arc<parking_lot::RwLock<Vec<WindowInfo>>>>,
+    /// Latest window hierarchy pushed by the compositor via
+    /// `set_window_tree_snapshot`, answered to `GetWindowTree`.
+    window_tree_handle: Option<Arc<parking_lot::RwLock<Vec<WindowTreeNode>>>>,
+    /// IDs of the windows the compositor manages, pushed via
+    /// `set_known_windows`. `WindowOp` requests for other IDs are refused;
+    /// while `None` nothing is refused and the compositor has the last word.
+    known_windows_handle: Option<Arc<parking_lot::RwLock<Vec<u64>>>>,
     last_metrics_sent: Instant,
     // Last CPU times for non-blocking CPU usage sampling
     last_cpu_times: Option<(u64, u64)>,
@@ -295,6 +504,10 @@ impl AxiomIPCServer {
             command_sender: cmd_tx,
             config_handle: None,
             live_metrics_handle: None,
+            workspace_snapshot_handle: None,
+            window_snapshot_handle: None,
+            window_tree_handle: None,
+            known_windows_handle: None,
             last_metrics_sent: Instant::now(),
             last_cpu_times: None,
             pending_broadcasts: Vec::new(),
@@ -329,6 +542,73 @@ impl AxiomIPCServer {
             .write() = snapshot;
     }
 
+    /// Replace the window IDs `WindowOp` requests are checked against.
+    /// Called from the compositor tick next to `set_workspace_snapshot`.
+    pub fn set_known_windows(&mut self, ids: Vec<u64>) {
+        *self
+            .known_windows_handle
+            .get_or_insert_with(|| Arc::new(parking_lot::RwLock::new(Vec::new())))
+            .write() = ids;
+    }
+
+    /// Replace the workspace layout served to `GetWorkspaces` queries.
+    /// Called from the compositor tick next to `set_live_metrics_snapshot`.
+    /// Queues a `WorkspaceChanged` broadcast when the focused column
+    /// differs from the previous snapshot; the very first snapshot only
+    /// seeds the handle.
+    pub fn set_workspace_snapshot(&mut self, snapshot: WorkspaceSnapshot) {
+        let previous_column = self
+            .workspace_snapshot_handle
+            .as_ref()
+            .map(|h| h.read().focused_column);
+        if let Some(previous_column) = previous_column {
+            if previous_column != snapshot.focused_column {
+                self.pending_broadcasts.push(AxiomMessage::WorkspaceChanged {
+                    timestamp: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .expect("system clock before UNIX_EPOCH")
+                        .as_secs(),
+                    previous_column,
+                    focused_column: snapshot.focused_column,
+                    scroll_position: snapshot.scroll_position,
+                });
+            }
+        }
+        *self
+            .workspace_snapshot_handle
+            .get_or_insert_with(|| {
+                Arc::new(parking_lot::RwLock::new(WorkspaceSnapshot::default()))
+            })
+            .write() = snapshot;
+    }
+
+    /// Replace the window list served to `GetWindows` queries. Called from
+    /// the compositor tick next to `set_workspace_snapshot`.
+    pub fn set_window_snapshot(&mut self, windows: Vec<WindowInfo>) {
+        *self
+            .window_snapshot_handle
+            .get_or_insert_with(|| Arc::new(parking_lot::RwLock::new(Vec::new())))
+            .write() = windows;
+    }
+
+    /// Replace the hierarchy served to `GetWindowTree` queries. Called from
+    /// the compositor tick next to `set_window_snapshot`.
+    pub fn set_window_tree_snapshot(&mut self, windows: Vec<WindowTreeNode>) {
+        *self
+            .window_tree_handle
+            .get_or_insert_with(|| Arc::new(parking_lot::RwLock::new(Vec::new())))
+            .write() = windows;
+    }
+
+    /// Build the `Workspaces` response for a `GetWorkspaces` query.
+    fn build_workspaces_message(snapshot: WorkspaceSnapshot) -> AxiomMessage {
+        AxiomMessage::Workspaces {
+            focused_column: snapshot.focused_column,
+            scroll_position: snapshot.scroll_position,
+            columns: snapshot.columns,
+        }
+    }
+
     /// Build the WorkspaceCommand ACK UserEvent for the per-client handler.
     /// Schema owned here (single source of truth) so the
     /// `test_workspace_command_ack_schema_includes_status` regression test
@@ -667,8 +947,11 @@ impl AxiomIPCServer {
             message,
             LazyUIMessage::WorkspaceCommand { .. }
                 | LazyUIMessage::SetWindowBlur { .. }
+                | LazyUIMessage::WindowOp { .. }
                 | LazyUIMessage::SetClipboard { .. }
                 | LazyUIMessage::StartDnd { .. }
+                | LazyUIMessage::Shutdown
+                | LazyUIMessage::SetProfile { .. }
         );
 
         if is_command_type {
@@ -682,6 +965,40 @@ impl AxiomIPCServer {
                 }
             }
 
+            // Existence gate (WindowOp only)
+            if let LazyUIMessage::WindowOp { window_id, .. } = message {
+                let known = self
+                    .known_windows_handle
+                    .as_ref()
+                    .is_none_or(|ids| ids.read().contains(&window_id));
+                if !known {
+                    debug!("🚫 Rejecting WindowOp for unknown window {}", window_id);
+                    let error = AxiomMessage::Error {
+                        request: "WindowOp".into(),
+                        message: format!("unknown window {}", window_id),
+                    };
+                    self.queue_message_to_client(fd, &error);
+                    return;
+                }
+            }
+
+            // Existence gate (SetProfile only)
+            if let LazyUIMessage::SetProfile { ref name } = message {
+                let known = self
+                    .config_handle
+                    .as_ref()
+                    .is_none_or(|config| config.read().profiles.contains_key(name));
+                if !known {
+                    debug!("🚫 Rejecting SetProfile for unknown profile {}", name);
+                    let error = AxiomMessage::Error {
+                        request: "SetProfile".into(),
+                        message: format!("unknown profile {}", name),
+                    };
+                    self.queue_message_to_client(fd, &error);
+                    return;
+                }
+            }
+
             // Build the ACK based on message type
             let (cmd_event_type, cmd_details) = match &message {
                 LazyUIMessage::WorkspaceCommand { action, .. } => (
@@ -704,6 +1021,16 @@ impl AxiomIPCServer {
                         "dispatched_via_mpsc": true,
                     }),
                 ),
+                LazyUIMessage::WindowOp { window_id, op } => (
+                    "WindowOpAck",
+                    serde_json::json!({
+                        "window_id": window_id,
+                        "op": op,
+                        "status": "queued_for_compositor_dispatch",
+                        "accepted": true,
+                        "dispatched_via_mpsc": true,
+                    }),
+                ),
                 LazyUIMessage::SetClipboard { text } => (
                     "SetClipboardAck",
                     serde_json::json!({
@@ -723,6 +1050,23 @@ impl AxiomIPCServer {
                         "dispatched_via_mpsc": true,
                     }),
                 ),
+                LazyUIMessage::Shutdown => (
+                    "ShutdownAck",
+                    serde_json::json!({
+                        "status": "queued_for_compositor_dispatch",
+                        "accepted": true,
+                        "dispatched_via_mpsc": true,
+                    }),
+                ),
+                LazyUIMessage::SetProfile { name } => (
+                    "SetProfileAck",
+                    serde_json::json!({
+                        "name": name,
+                        "status": "queued_for_compositor_dispatch",
+                        "accepted": true,
+                        "dispatched_via_mpsc": true,
+                    }),
+                ),
                 _ => unreachable!("is_command_type gated above"),
             };
 
@@ -735,8 +1079,11 @@ impl AxiomIPCServer {
                     let failed_type = match cmd_event_type {
                         "WorkspaceCommandAck" => "WorkspaceCommandAckFailed",
                         "SetWindowBlurAck" => "SetWindowBlurAckFailed",
+                        "WindowOpAck" => "WindowOpAckFailed",
                         "SetClipboardAck" => "SetClipboardAckFailed",
                         "StartDndAck" => "StartDndAckFailed",
+                        "ShutdownAck" => "ShutdownAckFailed",
+                        "SetProfileAck" => "SetProfileAckFailed",
                         _ => "CommandAckFailed",
                     };
                     (
@@ -858,6 +1205,9 @@ impl AxiomIPCServer {
                     frame_time: snapshot.frame_time_ms,
                     active_windows: snapshot.active_windows,
                     current_workspace: snapshot.current_workspace,
+                    frames_presented: snapshot.frames_presented,
+                    frames_dropped: snapshot.frames_dropped,
+                    surface_lost: snapshot.surface_lost,
                 };
                 self.queue_message_to_client(fd, &metrics);
             }
@@ -882,7 +1232,32 @@ impl AxiomIPCServer {
                 };
                 self.queue_message_to_client(fd, &report);
             }
-            _ => {} // WorkspaceCommand, SetWindowBlur, SetClipboard — already dispatched via cmd_tx
+            LazyUIMessage::GetWorkspaces => {
+                let snapshot = self
+                    .workspace_snapshot_handle
+                    .as_ref()
+                    .map(|h| h.read().clone())
+                    .unwrap_or_default();
+                let response = Self::build_workspaces_message(snapshot);
+                self.queue_message_to_client(fd, &response);
+            }
+            LazyUIMessage::GetWindows => {
+                let windows = self
+                    .window_snapshot_handle
+                    .as_ref()
+                    .map(|h| h.read().clone())
+                    .unwrap_or_default();
+                self.queue_message_to_client(fd, &AxiomMessage::Windows { windows });
+            }
+            LazyUIMessage::GetWindowTree => {
+                let windows = self
+                    .window_tree_handle
+                    .as_ref()
+                    .map(|h| h.read().clone())
+                    .unwrap_or_default();
+                self.queue_message_to_client(fd, &AxiomMessage::WindowTree { windows });
+            }
+            _ => {} // WorkspaceCommand, SetWindowBlur, WindowOp, SetClipboard — already dispatched via cmd_tx
         }
     }
 
@@ -972,7 +1347,7 @@ impl AxiomIPCServer {
     ///   wrote to the config-owned path. Callers typically call
     ///   `update_subsystems_config()` and refresh the IPC handle when set.
     /// - `pending_actions`: messages from `WorkspaceCommand` /
-    ///   `SetWindowBlur` (already validated at the per-client layer) that
+    ///   `SetWindowBlur` / `WindowOp` / `Shutdown` / `SetProfile` (already validated at the per-client layer) that
     ///   the compositor owns — they require real subsystem access that the
     ///   IPC server does not hold. Caller is responsible for dispatch.
     pub fn process_messages(
@@ -1017,6 +1392,17 @@ impl AxiomIPCServer {
                                     config.workspace.scroll_speed = val_f64.min(MAX_SCROLL_SPEED);
                                     config_changed = true;
                                 }
+                                "output.brightness" | "output.contrast" | "output.saturation"
+                                    if (0.0..=MAX_COLOR_ADJUSTMENT).contains(&val_f64) =>
+                                {
+                                    let field = match key.as_str() {
+                                        "output.brightness" => &mut config.output.brightness,
+                                        "output.contrast" => &mut config.output.contrast,
+                                        _ => &mut config.output.saturation,
+                                    };
+                                    *field = val_f64 as f32;
+                                    config_changed = true;
+                                }
                                 _ => {}
                             }
                         }
@@ -1025,8 +1411,11 @@ impl AxiomIPCServer {
                     // by the compositor in `AxiomCompositor::process_events`.
                     LazyUIMessage::WorkspaceCommand { .. }
                     | LazyUIMessage::SetWindowBlur { .. }
+                    | LazyUIMessage::WindowOp { .. }
                     | LazyUIMessage::SetClipboard { .. }
-                    | LazyUIMessage::StartDnd { .. } => {
+                    | LazyUIMessage::StartDnd { .. }
+                    | LazyUIMessage::Shutdown
+                    | LazyUIMessage::SetProfile { .. } => {
                         pending_actions.push(message);
                     }
                     _ => {
@@ -1051,12 +1440,7 @@ impl AxiomIPCServer {
     }
 
     /// Rate-limited helper that samples CPU/GPU/memory and enqueues metrics (~10Hz)
-    pub fn maybe_broadcast_performance_metrics(
-        &mut self,
-        frame_time_ms: f32,
-        active_windows: u32,
-        current_workspace: i32,
-    ) {
+    pub fn maybe_broadcast_performance_metrics(&mut self, metrics: &LiveMetrics) {
         const RATE: Duration = Duration::from_millis(100);
         if self.last_metrics_sent.elapsed() < RATE {
             return;
@@ -1075,9 +1459,12 @@ impl AxiomIPCServer {
                 cpu_usage: cpu,
                 memory_usage: mem_mb,
                 gpu_usage: gpu,
-                frame_time: frame_time_ms,
-                active_windows,
-                current_workspace,
+                frame_time: metrics.frame_time_ms,
+                active_windows: metrics.active_windows,
+                current_workspace: metrics.current_workspace,
+                frames_presented: metrics.frames_presented,
+                frames_dropped: metrics.frames_dropped,
+                surface_lost: metrics.surface_lost,
             });
     }
 
@@ -1100,6 +1487,28 @@ impl AxiomIPCServer {
         });
     }
 
+    /// Broadcast an output connecting or disconnecting to all IPC clients.
+    /// `geometry` is `(x, y, width, height)` in global coordinates.
+    pub fn broadcast_output_changed(
+        &mut self,
+        output: &str,
+        connected: bool,
+        (x, y, width, height): (i32, i32, u32, u32),
+    ) {
+        self.pending_broadcasts.push(AxiomMessage::OutputChanged {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system clock before UNIX_EPOCH")
+                .as_secs(),
+            output: output.to_owned(),
+            connected,
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
     /// Sample GPU usage percentage from DRM sysfs (AMD/Intel) or return 0.0.
     fn sample_gpu_usage() -> f32 {
         // Try common paths for GPU utilisation via DRM
@@ -1205,6 +1614,9 @@ impl AxiomIPCServer {
         self.shutdown.store(true, Ordering::Relaxed);
         // Close the listener
         self.listener.take();
+        // Best-effort flush so clients see the final broadcasts (e.g. the
+        // compositor's "shutdown" state change) before they are dropped
+        self.write_to_clients();
         // Disconnect all clients
         let fds: Vec<RawFd> = self.clients.keys().copied().collect();
         for fd in fds {
@@ -1243,6 +1655,8 @@ impl AxiomIPCServer {
             }
             "workspace.auto_scroll" => Some(serde_json::json!(config.workspace.auto_scroll)),
             "workspace.gaps" => Some(serde_json::json!(config.workspace.gaps)),
+            "workspace.inner_gap" => Some(serde_json::json!(config.workspace.inner_gap())),
+            "workspace.outer_gap" => Some(serde_json::json!(config.workspace.outer_gap())),
             "workspace.workspace_width" => {
                 Some(serde_json::json!(config.workspace.workspace_width))
             }
@@ -1255,16 +1669,21 @@ impl AxiomIPCServer {
             "window.border_width" => Some(serde_json::json!(config.window.border_width)),
             "general.max_fps" => Some(serde_json::json!(config.general.max_fps)),
             "general.vsync" => Some(serde_json::json!(config.general.vsync)),
+            "output.brightness" => Some(serde_json::json!(config.output.brightness)),
+            "output.contrast" => Some(serde_json::json!(config.output.contrast)),
+            "output.saturation" => Some(serde_json::json!(config.output.saturation)),
+            "output.layered_compositing" => {
+                Some(serde_json::json!(config.output.layered_compositing))
+            }
             _ => None,
         }
     }
 
-    fn default_socket_path() -> PathBuf {
+    /// Where [`AxiomIPCServer::new`] puts the socket.
+    pub fn default_socket_path() -> PathBuf {
         // Prefer XDG_RUNTIME_DIR (user-private, 0700 by convention).
-        if let Ok(dir) = std::env::var("XDG_RUNTIME_DIR") {
-            if !dir.is_empty() {
-                return PathBuf::from(dir).join("axiom").join("axiom.sock");
-            }
+        if let Some(path) = Self::session_socket_path(std::env::var_os("XDG_RUNTIME_DIR")) {
+            return path;
         }
         // Fallback: use a per-process subdirectory under /tmp to prevent
         // predictable-path symlink attacks. The directory is created in
@@ -1275,6 +1694,29 @@ impl AxiomIPCServer {
             .join("axiom-lazy-ui.sock")
     }
 
+    /// The socket of the instance `--replace` should shut down. Only the
+    /// `XDG_RUNTIME_DIR` socket can be found by a later process: without
+    /// it each instance hides its socket in a directory named after its
+    /// own pid, so this is an error rather than a silent no-op.
+    pub fn replace_socket_path() -> Result<PathBuf> {
+        Self::replace_socket_path_in(std::env::var_os("XDG_RUNTIME_DIR"))
+    }
+
+    fn replace_socket_path_in(runtime_dir: Option<std::ffi::OsString>) -> Result<PathBuf> {
+        Self::session_socket_path(runtime_dir).context(
+            "--replace needs XDG_RUNTIME_DIR: without it the running instance's \
+             IPC socket is under /tmp/axiom-<pid>/ and can't be found",
+        )
+    }
+
+    /// Socket path in `runtime_dir`, shared by every instance in the
+    /// session. `None` when the directory is unset or empty.
+    fn session_socket_path(runtime_dir: Option<std::ffi::OsString>) -> Option<PathBuf> {
+        runtime_dir
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| PathBuf::from(dir).join("axiom").join("axiom.sock"))
+    }
+
     /// Get peer UID via `libc::getsockopt(SO_PEERCRED)` (stable Rust).
     /// Returns `None` on error.
     #[cfg(unix)]
@@ -1376,6 +1818,70 @@ impl AxiomIPCServer {
     }
 }
 
+/// Ask the Axiom instance listening on `socket_path` to shut down, and wait
+/// up to `timeout` for it to go. `Ok(false)` when nothing is listening
+/// there (no socket, or a stale one left by a crash); `Ok(true)` once the
+/// instance has hung up on us, which it does as the last step of its
+/// shutdown. An instance that is still connected when `timeout` runs out
+/// is an error: starting anyway would fight it for the outputs.
+pub fn request_shutdown(socket_path: &Path, timeout: Duration) -> Result<bool> {
+    let mut stream = match UnixStream::connect(socket_path) {
+        Ok(stream) => stream,
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            return Ok(false);
+        }
+        Err(e) => {
+            return Err(e).with_context(|| {
+                format!("failed to connect to Axiom at {}", socket_path.display())
+            })
+        }
+    };
+    info!(
+        "🔁 Asking the Axiom instance at {} to shut down",
+        socket_path.display()
+    );
+    stream
+        .write_all(b"{\"type\":\"Shutdown\"}\n")
+        .with_context(|| format!("failed to send shutdown to {}", socket_path.display()))?;
+
+    // Drain the ACK and final broadcasts until the server closes the socket
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0u8; 4096];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            anyhow::bail!(
+                "the Axiom instance at {} did not shut down within {:.1}s",
+                socket_path.display(),
+                timeout.as_secs_f32()
+            );
+        }
+        stream.set_read_timeout(Some(remaining))?;
+        match stream.read(&mut buf) {
+            Ok(0) => return Ok(true),
+            Ok(_) => {}
+            // A reset is the server going away just as well
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => return Ok(true),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock
+                        | std::io::ErrorKind::TimedOut
+                        | std::io::ErrorKind::Interrupted
+                ) => {}
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("lost the connection to {}", socket_path.display()))
+            }
+        }
+    }
+}
+
 /// Simulate peer credential validation for unit testing.
 /// Returns `true` only when `allowed_uids` is non-empty and contains `uid`.
 /// Exposed as `pub` so external test files (e.g. `tests/ipc_peer_cred.rs`)
@@ -1399,13 +1905,24 @@ mod tests {
             frame_time: 16.67,
             active_windows: 5,
             current_workspace: 2,
+            frames_presented: 1200,
+            frames_dropped: 3,
+            surface_lost: 1,
         };
 
         let json = serde_json::to_string(&message).unwrap();
         println!("Serialized message: {}", json);
+        assert!(json.contains(r#""frames_dropped":3"#));
 
         // Test that we can deserialize it back
         let _deserialized: AxiomMessage = serde_json::from_str(&json).unwrap();
+
+        // Metrics from senders without present counters still parse
+        let old = r#"{"type":"PerformanceMetrics","timestamp":1,"cpu_usage":0.0,"memory_usage":0.0,"gpu_usage":0.0,"frame_time":16.0,"active_windows":1,"current_workspace":0}"#;
+        match serde_json::from_str::<AxiomMessage>(old).unwrap() {
+            AxiomMessage::PerformanceMetrics { frames_dropped, .. } => assert_eq!(frames_dropped, 0),
+            other => panic!("unexpected message: {:?}", other),
+        }
     }
 
     #[test]
@@ -1435,6 +1952,20 @@ mod tests {
         assert!(is_known_workspace_action("remove_window"));
         assert!(is_known_workspace_action("move_focus_left"));
         assert!(is_known_workspace_action("move_focus_right"));
+        assert!(is_known_workspace_action("tile_floating"));
+        assert!(is_known_workspace_action("scroll_to_named"));
+        assert!(is_known_workspace_action("name_column"));
+        assert!(is_known_workspace_action("scroll_to_window"));
+        assert!(is_known_workspace_action("mirror"));
+        assert!(is_known_workspace_action("unmirror"));
+        assert!(is_known_workspace_action("set_background"));
+        assert!(is_known_workspace_action("clear_background"));
+        assert!(is_known_workspace_action("select_window"));
+        assert!(is_known_workspace_action("move_selected_to_column"));
+        assert!(is_known_workspace_action("close_selected"));
+        assert!(is_known_workspace_action("hotplug_connect"));
+        assert!(is_known_workspace_action("hotplug_disconnect"));
+        assert!(is_known_workspace_action("toggle_hud"));
         // Unknown actions should be rejected
         assert!(!is_known_workspace_action("nuke_all_windows"));
         assert!(!is_known_workspace_action(""));
@@ -1442,6 +1973,20 @@ mod tests {
         assert!(!is_known_workspace_action("SCROLL_LEFT")); // case-sensitive
     }
 
+    #[test]
+    fn test_output_changed_broadcast_schema() {
+        let mut server = AxiomIPCServer::new();
+        server.broadcast_output_changed("HDMI-A-1", true, (1920, 0, 2560, 1440));
+        let message = server.pending_broadcasts.last().expect("queued broadcast");
+        let json: serde_json::Value = serde_json::to_value(message).unwrap();
+        assert_eq!(json["type"], "OutputChanged");
+        assert_eq!(json["output"], "HDMI-A-1");
+        assert_eq!(json["connected"], true);
+        assert_eq!(json["x"], 1920);
+        assert_eq!(json["width"], 2560);
+        assert_eq!(json["height"], 1440);
+    }
+
     #[test]
     fn test_performance_report_serialization() {
         // Confirm the typed `PerformanceReport` variant round-trips through
@@ -1497,6 +2042,9 @@ mod tests {
             frame_time_ms: 12.5,
             active_windows: 7,
             current_workspace: 2,
+            frames_presented: 40,
+            frames_dropped: 2,
+            surface_lost: 1,
         });
         let snap = *server
             .live_metrics_handle
@@ -1506,12 +2054,16 @@ mod tests {
         assert!((snap.frame_time_ms - 12.5).abs() < 1e-6);
         assert_eq!(snap.active_windows, 7);
         assert_eq!(snap.current_workspace, 2);
+        assert_eq!(snap.frames_presented, 40);
+        assert_eq!(snap.frames_dropped, 2);
+        assert_eq!(snap.surface_lost, 1);
 
         // Second call replaces (not appends) per `get_or_insert_with` design.
         server.set_live_metrics_snapshot(LiveMetrics {
             frame_time_ms: 99.9,
             active_windows: 2,
             current_workspace: -3,
+            ..Default::default()
         });
         let snap = *server
             .live_metrics_handle
@@ -1534,10 +2086,170 @@ mod tests {
             frame_time_ms: 0.0,
             active_windows: 0,
             current_workspace: 0,
+            ..Default::default()
         });
         assert!(server.live_metrics_handle.is_some());
     }
 
+    #[test]
+    fn test_get_workspaces_response_schema() {
+        let parsed: LazyUIMessage = serde_json::from_str(r#"{"type": "GetWorkspaces"}"#).unwrap();
+        assert!(matches!(parsed, LazyUIMessage::GetWorkspaces));
+
+        let mut server = AxiomIPCServer::new();
+        server.set_workspace_snapshot(WorkspaceSnapshot {
+            focused_column: -1,
+            scroll_position: -1920.0,
+            columns: vec![
+                WorkspaceColumnInfo {
+                    index: -1,
+                    window_count: 2,
+                    occupied: true,
+                    windows: vec![3, 4],
+                    name: Some("web".into()),
+                },
+                WorkspaceColumnInfo {
+                    index: 0,
+                    window_count: 0,
+                    occupied: false,
+                    windows: Vec::new(),
+                    name: None,
+                },
+            ],
+        });
+        let snapshot = server
+            .workspace_snapshot_handle
+            .as_ref()
+            .expect("handle must exist after snapshot call")
+            .read()
+            .clone();
+        let json = serde_json::to_value(AxiomIPCServer::build_workspaces_message(snapshot)).unwrap();
+        assert_eq!(json["type"], "Workspaces");
+        assert_eq!(json["focused_column"], -1);
+        assert_eq!(json["scroll_position"], -1920.0);
+        assert_eq!(json["columns"][0]["index"], -1);
+        assert_eq!(json["columns"][0]["windows"], serde_json::json!([3, 4]));
+        assert_eq!(json["columns"][0]["name"], "web");
+        assert_eq!(json["columns"][1]["window_count"], 0);
+        assert_eq!(json["columns"][1]["occupied"], false);
+    }
+
+    #[test]
+    fn test_get_windows_response_schema() {
+        let parsed: LazyUIMessage = serde_json::from_str(r#"{"type": "GetWindows"}"#).unwrap();
+        assert!(matches!(parsed, LazyUIMessage::GetWindows));
+
+        let mut server = AxiomIPCServer::new();
+        server.set_window_snapshot(vec![WindowInfo {
+            id: 7,
+            title: "mpv".into(),
+            app_id: Some("mpv".into()),
+            pid: Some(4242),
+            executable: Some("mpv".into()),
+            commits_per_sec: 60.0,
+            bytes_per_sec: 497_664_000,
+            last_frame_interval_ms: Some(16.5),
+            high_bandwidth: true,
+        }]);
+        let windows = server
+            .window_snapshot_handle
+            .as_ref()
+            .expect("handle must exist after snapshot call")
+            .read()
+            .clone();
+        let json = serde_json::to_value(AxiomMessage::Windows { windows }).unwrap();
+        assert_eq!(json["type"], "Windows");
+        assert_eq!(json["windows"][0]["id"], 7);
+        assert_eq!(json["windows"][0]["pid"], 4242);
+        assert_eq!(json["windows"][0]["executable"], "mpv");
+        assert_eq!(json["windows"][0]["commits_per_sec"], 60.0);
+        assert_eq!(json["windows"][0]["bytes_per_sec"], 497_664_000u64);
+        assert_eq!(json["windows"][0]["last_frame_interval_ms"], 16.5);
+        assert_eq!(json["windows"][0]["high_bandwidth"], true);
+    }
+
+    #[test]
+    fn test_get_window_tree_response_schema() {
+        use crate::window::{Rectangle, WindowTreeKind};
+
+        let parsed: LazyUIMessage = serde_json::from_str(r#"{"type": "GetWindowTree"}"#).unwrap();
+        assert!(matches!(parsed, LazyUIMessage::GetWindowTree));
+
+        let node = |id, kind, children| WindowTreeNode {
+            id,
+            kind,
+            geometry: Rectangle::from_loc_and_size((0, 0), (640, 480)),
+            children,
+        };
+        let mut server = AxiomIPCServer::new();
+        server.set_window_tree_snapshot(vec![node(
+            1,
+            WindowTreeKind::Window,
+            vec![node(12, WindowTreeKind::Popup, Vec::new())],
+        )]);
+        let windows = server
+            .window_tree_handle
+            .as_ref()
+            .expect("handle must exist after snapshot call")
+            .read()
+            .clone();
+        let json = serde_json::to_value(AxiomMessage::WindowTree { windows }).unwrap();
+        assert_eq!(json["type"], "WindowTree");
+        assert_eq!(json["windows"][0]["kind"], "window");
+        assert_eq!(json["windows"][0]["geometry"]["width"], 640);
+        assert_eq!(json["windows"][0]["children"][0]["id"], 12);
+        assert_eq!(json["windows"][0]["children"][0]["kind"], "popup");
+    }
+
+    #[test]
+    fn test_scrolling_broadcasts_workspace_changed() {
+        let config = crate::config::WorkspaceConfig::default();
+        let mut workspaces = ScrollableWorkspaces::new(&config);
+        workspaces.add_window_to_column(1, 0);
+        workspaces.add_window_to_column(2, 2);
+
+        let mut server = AxiomIPCServer::new();
+        server.set_workspace_snapshot(WorkspaceSnapshot::capture(&workspaces));
+        assert!(
+            server.pending_broadcasts.is_empty(),
+            "seeding the first snapshot must not broadcast"
+        );
+
+        // Same focused column again: nothing new to report
+        server.set_workspace_snapshot(WorkspaceSnapshot::capture(&workspaces));
+        assert!(server.pending_broadcasts.is_empty());
+
+        workspaces.scroll_right();
+        server.set_workspace_snapshot(WorkspaceSnapshot::capture(&workspaces));
+        assert_eq!(server.pending_broadcasts.len(), 1);
+        match &server.pending_broadcasts[0] {
+            AxiomMessage::WorkspaceChanged {
+                previous_column,
+                focused_column,
+                ..
+            } => {
+                assert_eq!(*previous_column, 0);
+                assert_eq!(*focused_column, 1);
+            }
+            other => panic!("expected WorkspaceChanged, got {:?}", other),
+        }
+
+        // GetWorkspaces reflects occupancy, including the empty column 1
+        let snapshot = server
+            .workspace_snapshot_handle
+            .as_ref()
+            .unwrap()
+            .read()
+            .clone();
+        assert_eq!(snapshot.focused_column, 1);
+        let occupancy: Vec<(i32, bool)> = snapshot
+            .columns
+            .iter()
+            .map(|c| (c.index, c.occupied))
+            .collect();
+        assert_eq!(occupancy, vec![(0, true), (1, false), (2, true)]);
+    }
+
     /// Issue #2 regression: WorkspaceCommand ACK must carry the new
     /// `"status": "queued_for_execution"` discriminator alongside the
     /// legacy `"accepted": <bool>` field. The dual-key shim is a temporary
@@ -1694,6 +2406,46 @@ mod tests {
         );
     }
 
+    /// A `WindowOp` naming a window the compositor doesn't have is
+    /// answered with an `Error` and never reaches the command channel.
+    #[test]
+    fn test_window_op_for_unknown_window_is_an_error() {
+        let mut server = AxiomIPCServer::new();
+        server.set_known_windows(vec![1, 2]);
+        let (mut client, server_stream) = UnixStream::pair().unwrap();
+        server_stream.set_nonblocking(true).unwrap();
+        let fd = server_stream.as_raw_fd();
+
+        server.clients.insert(
+            fd,
+            ClientData {
+                stream: server_stream,
+                read_buf: Vec::new(),
+                write_buf: Vec::new(),
+                last_activity: Instant::now(),
+                messages_this_tick: 0,
+            },
+        );
+        server.num_connections.fetch_add(1, Ordering::Relaxed);
+
+        client
+            .write_all(b"{\"type\":\"WindowOp\",\"window_id\":7,\"op\":{\"kind\":\"close\"}}\n")
+            .unwrap();
+        server.poll();
+
+        let mut buf = [0u8; 4096];
+        let n = client.read(&mut buf).unwrap();
+        let response: serde_json::Value = serde_json::from_slice(&buf[..n]).unwrap();
+        assert_eq!(response["type"], "Error");
+        assert_eq!(response["request"], "WindowOp");
+        assert!(response["message"].as_str().unwrap().contains('7'));
+
+        let (_, actions) = server
+            .process_messages(&mut AxiomConfig::default())
+            .unwrap();
+        assert!(actions.is_empty());
+    }
+
     /// Issue #3 hardening: sending a line larger than MAX_IPC_LINE_BYTES
     /// must disconnect the client (clear read_buf, remove from map).
     /// Sends the oversized data through a real socket pair so
@@ -1900,6 +2652,160 @@ mod tests {
         assert!(!validate_peer_credentials_for_test(1000, &[]));
     }
 
+    /// Shutting down delivers queued broadcasts to connected clients before
+    /// hanging up, and removes the socket file.
+    #[test]
+    fn test_shutdown_flushes_broadcasts_and_removes_socket() {
+        use std::io::{BufRead, BufReader};
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let sock_path = tmpdir.path().join("shutdown_test.sock");
+
+        let mut server = AxiomIPCServer::new();
+        server.socket_path = sock_path.clone();
+        let listener = UnixListener::bind(&sock_path).unwrap();
+        listener.set_nonblocking(true).unwrap();
+        server.listener = Some(listener);
+        server.our_uid = unsafe { libc::getuid() };
+
+        let client = UnixStream::connect(&sock_path).unwrap();
+        client
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+        server.accept_new_connections();
+        assert_eq!(server.clients.len(), 1);
+
+        server.broadcast_state_change("compositor", "running", "shutdown");
+        server.shutdown_sync();
+
+        assert!(!sock_path.exists(), "socket file should be removed");
+        assert!(server.clients.is_empty());
+
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).unwrap();
+        match serde_json::from_str::<AxiomMessage>(line.trim()).unwrap() {
+            AxiomMessage::StateChange { new_state, .. } => assert_eq!(new_state, "shutdown"),
+            other => panic!("expected StateChange, got {:?}", other),
+        }
+    }
+
+    /// A mock instance that answers `Shutdown` like the real one: ACK, then
+    /// hang up.
+    fn mock_instance(
+        sock_path: &Path,
+        hang_up: bool,
+    ) -> std::thread::JoinHandle<Option<serde_json::Value>> {
+        use std::io::{BufRead, BufReader};
+
+        let listener = UnixListener::bind(sock_path).unwrap();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let request: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+            let mut stream = stream;
+            stream
+                .write_all(
+                    b"{\"type\":\"UserEvent\",\"event_type\":\"ShutdownAck\",\"details\":{}}\n",
+                )
+                .unwrap();
+            if !hang_up {
+                // Stay connected until the client gives up
+                let _ = reader.read_line(&mut line);
+            }
+            Some(request)
+        })
+    }
+
+    #[test]
+    fn test_request_shutdown_sends_shutdown_and_waits_for_hang_up() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let sock_path = tmpdir.path().join("running.sock");
+        let instance = mock_instance(&sock_path, true);
+
+        let replaced = request_shutdown(&sock_path, Duration::from_secs(5)).unwrap();
+        assert!(replaced);
+        // The request reached the running instance before we returned, i.e.
+        // before the caller goes on to bind its own sockets
+        let request = instance.join().unwrap().expect("a JSON request");
+        assert_eq!(request, serde_json::json!({"type": "Shutdown"}));
+        assert!(matches!(
+            serde_json::from_value::<LazyUIMessage>(request),
+            Ok(LazyUIMessage::Shutdown)
+        ));
+    }
+
+    #[test]
+    fn test_replace_needs_xdg_runtime_dir() {
+        for unset in [None, Some("".into())] {
+            let err = AxiomIPCServer::replace_socket_path_in(unset).unwrap_err();
+            assert!(err.to_string().contains("XDG_RUNTIME_DIR"), "{}", err);
+        }
+        assert_eq!(
+            AxiomIPCServer::replace_socket_path_in(Some("/run/user/1000".into())).unwrap(),
+            PathBuf::from("/run/user/1000/axiom/axiom.sock")
+        );
+    }
+
+    #[test]
+    fn test_request_shutdown_without_running_instance() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let sock_path = tmpdir.path().join("none.sock");
+        assert!(!request_shutdown(&sock_path, Duration::from_secs(1)).unwrap());
+
+        // A socket file nobody listens on any more
+        drop(UnixListener::bind(&sock_path).unwrap());
+        assert!(sock_path.exists());
+        assert!(!request_shutdown(&sock_path, Duration::from_secs(1)).unwrap());
+    }
+
+    #[test]
+    fn test_request_shutdown_times_out_on_unresponsive_instance() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let sock_path = tmpdir.path().join("stuck.sock");
+        let instance = mock_instance(&sock_path, false);
+
+        let err = request_shutdown(&sock_path, Duration::from_millis(200)).unwrap_err();
+        assert!(
+            err.to_string().contains("did not shut down"),
+            "unexpected error: {}",
+            err
+        );
+        assert!(instance.join().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_shutdown_is_queued_for_the_compositor() {
+        let mut server = AxiomIPCServer::new();
+        server.handle_message(-1, LazyUIMessage::Shutdown);
+        let (config_changed, pending) = server
+            .process_messages(&mut AxiomConfig::default())
+            .unwrap();
+        assert!(!config_changed);
+        assert!(matches!(pending.as_slice(), [LazyUIMessage::Shutdown]));
+    }
+
+    #[test]
+    fn test_set_profile_is_queued_only_for_known_profiles() {
+        let mut config = AxiomConfig::default();
+        config
+            .profiles
+            .insert("battery".to_string(), Default::default());
+        let mut server = AxiomIPCServer::new();
+        server.set_config_handle(Arc::new(parking_lot::RwLock::new(config.clone())));
+
+        for name in ["docked", "battery"] {
+            let name = name.to_string();
+            server.handle_message(-1, LazyUIMessage::SetProfile { name });
+        }
+        let (_, pending) = server.process_messages(&mut config).unwrap();
+        assert!(matches!(
+            pending.as_slice(),
+            [LazyUIMessage::SetProfile { name }] if name == "battery"
+        ));
+    }
+
     /// Exercise the full accept_new_connections code path with a real
     /// UnixListener and UnixStream pair so the peer credential check
     /// (which compares `peer_uid == our_uid`) runs in production-like
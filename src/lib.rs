@@ -44,6 +44,11 @@
 //! | [`ipc`] | Unix-socket JSON IPC protocol and server |
 //! | [`config`] | TOML configuration model, loading, and validation |
 //! | [`decoration`] | Server-side decoration geometry and hit-testing |
+//! | [`clock`] | Swappable time source so animations can be stepped in tests |
+//! | [`crash_guard`] | Crash-loop detection and the effects-off safe mode |
+//! | [`logging`] | Text or JSON log output |
+//! | [`power`] | AC/battery detection for config profile switching |
+//! | [`session`] | State kept across restarts, such as column names |
 //!
 //! ## Usage
 //!
@@ -77,11 +82,16 @@ pub use crate::window::{Rectangle, WindowManager};
 pub use crate::workspace::ScrollableWorkspaces;
 
 // Module declarations
+pub mod clock;
 pub mod compositor;
 pub mod config;
+pub mod crash_guard;
 pub mod decoration;
 pub mod input;
 pub mod ipc;
+pub mod logging;
+pub mod power;
+pub mod session;
 pub mod window;
 pub mod workspace;
 
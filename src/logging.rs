@@ -0,0 +1,119 @@
+//! Log output setup.
+//!
+//! Logging goes through the `log` facade and `env_logger` either way. The
+//! default text format is for people reading a terminal; the JSON format
+//! writes one object per line for log pipelines, with the level, module
+//! and any structured key-values a call site attached (`window_id`,
+//! `frame_time_ms`...) as separate fields instead of inside the message.
+
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use log::kv::{Error as KvError, Key, Value, VisitSource};
+use log::Record;
+use serde_json::{Map, Value as JsonValue};
+
+/// How log lines are written to stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// `env_logger`'s human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Install the global logger. `default_level` applies unless `RUST_LOG`
+/// says otherwise.
+pub fn init(format: LogFormat, default_level: &str) {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_level));
+    if format == LogFormat::Json {
+        builder.format(|buf, record| writeln!(buf, "{}", json_line(record)));
+    }
+    builder.init();
+}
+
+/// `record` as a single-line JSON object: `ts` (milliseconds since the
+/// epoch), `level`, `module`, `message`, then the record's key-values.
+/// A key-value can't shadow one of the fixed fields.
+pub fn json_line(record: &Record<'_>) -> String {
+    let mut fields = Map::new();
+    let _ = record.key_values().visit(&mut FieldCollector(&mut fields));
+
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    fields.insert("ts".into(), ts.into());
+    fields.insert("level".into(), record.level().as_str().into());
+    fields.insert(
+        "module".into(),
+        record.module_path().unwrap_or(record.target()).into(),
+    );
+    fields.insert("message".into(), record.args().to_string().into());
+    JsonValue::Object(fields).to_string()
+}
+
+/// Copies key-values into a JSON map, keeping numbers and booleans typed.
+struct FieldCollector<'a>(&'a mut Map<String, JsonValue>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), KvError> {
+        let json = if let Some(v) = value.to_u64() {
+            v.into()
+        } else if let Some(v) = value.to_i64() {
+            v.into()
+        } else if let Some(v) = value.to_f64() {
+            serde_json::Number::from_f64(v).map_or(JsonValue::Null, JsonValue::Number)
+        } else if let Some(v) = value.to_bool() {
+            v.into()
+        } else {
+            value.to_string().into()
+        };
+        self.0.insert(key.as_str().to_owned(), json);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    #[test]
+    fn test_json_line_is_parseable_with_key_fields() {
+        let fields: [(&str, Value<'_>); 3] = [
+            ("window_id", Value::from(42u64)),
+            ("frame_time_ms", Value::from(16.5f64)),
+            ("message", Value::from("shadowed")),
+        ];
+        let line = json_line(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("axiom::compositor")
+                .module_path(Some("axiom::compositor"))
+                .args(format_args!("🐢 Slow tick: {}ms", 16.5))
+                .key_values(&fields)
+                .build(),
+        );
+
+        assert!(!line.contains('\n'));
+        let parsed: JsonValue = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["level"], "WARN");
+        assert_eq!(parsed["module"], "axiom::compositor");
+        assert_eq!(parsed["message"], "🐢 Slow tick: 16.5ms");
+        assert_eq!(parsed["window_id"], 42);
+        assert_eq!(parsed["frame_time_ms"], 16.5);
+        assert!(parsed["ts"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_log_format_parses_from_cli_names() {
+        assert_eq!(LogFormat::from_str("json", true), Ok(LogFormat::Json));
+        assert_eq!(LogFormat::from_str("text", true), Ok(LogFormat::Text));
+        assert!(LogFormat::from_str("yaml", true).is_err());
+        assert_eq!(LogFormat::default(), LogFormat::Text);
+    }
+}
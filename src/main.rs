@@ -14,16 +14,20 @@
 
 use anyhow::Result;
 use clap::Parser;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
 use axiom::compositor::AxiomCompositor;
 use axiom::config::AxiomConfig;
+use axiom::crash_guard::{self, CrashGuard};
 use axiom::input::InputManager;
-use axiom::ipc::AxiomIPCServer;
+use axiom::ipc::{self, AxiomIPCServer};
+use axiom::logging::LogFormat;
+use axiom::session;
 use axiom::window::WindowManager;
 use axiom::workspace::ScrollableWorkspaces;
 use parking_lot::RwLock;
 use std::sync::Arc;
+use std::time::Duration;
 // use axiom::generate_default_config;
 
 #[derive(Parser)]
@@ -42,11 +46,44 @@ struct Cli {
     #[arg(short, long)]
     debug: bool,
 
+    /// Log line format: human-readable text or one JSON object per line
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
     /// Present in a window (always true; retained for compatibility)
     #[arg(short, long)]
     windowed: bool,
+
+    /// Disable animations (same as `effects.reduce_motion = true`)
+    #[arg(long)]
+    no_effects: bool,
+
+    /// Take over from an Axiom instance already running in this session:
+    /// ask it to shut down over IPC before starting. Needs
+    /// `XDG_RUNTIME_DIR`, where the running instance's IPC socket lives
+    #[arg(long)]
+    replace: bool,
+
+    /// Draw the debug HUD: FPS, frame time, window count, damage and
+    /// draw calls (same as `general.hud = true`)
+    #[arg(long)]
+    hud: bool,
+
+    /// Start with the settings of config profile `[profiles.<NAME>]`
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Listen for clients on Wayland socket NAME in `XDG_RUNTIME_DIR`
+    /// instead of `wayland-axiom-<pid>` (same as `backend.socket_name`).
+    /// With `--replace`, the running instance is shut down first, so its
+    /// name can be taken over
+    #[arg(long, value_name = "NAME")]
+    socket_name: Option<String>,
 }
 
+/// How long `--replace` waits for the running instance to exit.
+const REPLACE_TIMEOUT: Duration = Duration::from_secs(5);
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -54,7 +91,7 @@ fn main() -> Result<()> {
     // Config is not loaded yet at this point, so we defer a possible
     // re-init below. The CLI flag always takes priority.
     let log_level = if cli.debug { "debug" } else { "info" };
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
+    axiom::logging::init(cli.log_format, log_level);
 
     // Set global panic handler
     std::panic::set_hook(Box::new(|info| {
@@ -75,7 +112,7 @@ fn main() -> Result<()> {
     info!("📄 Version: {}", env!("CARGO_PKG_VERSION"));
 
     // Load configuration (AxiomConfig::load handles ~ expansion)
-    let config = match AxiomConfig::load(&cli.config) {
+    let mut config = match AxiomConfig::load(&cli.config) {
         Ok(config) => {
             info!("✅ Configuration loaded from: {}", cli.config);
             config
@@ -88,11 +125,51 @@ fn main() -> Result<()> {
     };
 
     // Honor config.general.debug (in addition to the CLI flag).
-    // `log::set_max_level` works after env_logger has been initialized.
+    // `log::set_max_level` works after the logger has been initialized.
     if config.general.debug {
         log::set_max_level(log::LevelFilter::Debug);
         debug!("Debug logging enabled via config");
     }
+    if cli.no_effects {
+        config.effects.reduce_motion = true;
+        info!("🐢 Animations disabled (--no-effects)");
+    }
+    if cli.hud {
+        config.general.hud = true;
+    }
+    if let Some(name) = cli.socket_name {
+        config.backend.socket_name = Some(name);
+        config.validate()?;
+    }
+
+    // Get the old instance out of the way before any socket is bound
+    if cli.replace {
+        let socket_path = AxiomIPCServer::replace_socket_path()?;
+        if ipc::request_shutdown(&socket_path, REPLACE_TIMEOUT)? {
+            info!("🔁 Replaced the running Axiom instance");
+        } else {
+            info!("🔁 No running Axiom instance to replace");
+        }
+    }
+
+    // Startups that keep dying early fall back to plain drawing until one
+    // runs long enough to count as stable
+    let crash_guard = Arc::new(CrashGuard::startup());
+    if crash_guard.safe_mode() {
+        warn!(
+            "🛟 Safe mode: Axiom crashed {} times within {} minutes of starting; effects and profiles are disabled",
+            crash_guard::CRASH_LIMIT,
+            crash_guard::CRASH_WINDOW.as_secs() / 60
+        );
+        crash_guard::apply_safe_mode(&mut config);
+    }
+    {
+        let crash_guard = crash_guard.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(crash_guard::STABLE_AFTER);
+            crash_guard.mark_stable();
+        });
+    }
 
     // Initialize and run compositor
     info!("🏗️  Initializing Axiom compositor...");
@@ -102,6 +179,13 @@ fn main() -> Result<()> {
     let workspace_manager = std::sync::Arc::new(parking_lot::RwLock::new(
         ScrollableWorkspaces::new(&config.workspace),
     ));
+    // Column names given in an earlier run
+    let column_names_path = session::column_names_path();
+    if let Some(path) = &column_names_path {
+        workspace_manager
+            .write()
+            .restore_column_names(session::load_column_names(path));
+    }
     let window_manager = Arc::new(RwLock::new(WindowManager::new(&config.window)));
     let input_manager = Arc::new(RwLock::new(InputManager::new(
         &config.input,
@@ -119,6 +203,12 @@ fn main() -> Result<()> {
         ipc_server,
     )?;
 
+    if let Some(name) = &cli.profile {
+        if let Err(e) = compositor.set_profile(name) {
+            error!("❌ Failed to apply profile: {:#}", e);
+        }
+    }
+
     let socket_name = compositor.socket_name().to_string();
     std::env::set_var("WAYLAND_DISPLAY", &socket_name);
     info!("📡 Exported WAYLAND_DISPLAY={}", socket_name);
@@ -136,6 +226,16 @@ fn main() -> Result<()> {
 
     // Main event loop
     compositor.run()?;
+    crash_guard.mark_stable();
+    if let Some(path) = &column_names_path {
+        if let Err(e) = session::save_column_names(path, workspace_manager.read().column_names()) {
+            warn!(
+                "⚠️ Failed to save column names to {}: {:#}",
+                path.display(),
+                e
+            );
+        }
+    }
 
     info!("👋 Axiom compositor shutting down");
     Ok(())
@@ -159,5 +259,23 @@ mod tests {
             .expect("CLI parse should succeed");
         assert!(cli.debug);
         assert!(cli.windowed);
+        assert!(!cli.replace);
+
+        let cli = Cli::try_parse_from(["axiom", "--replace"]).expect("CLI parse should succeed");
+        assert!(cli.replace);
+        assert!(!cli.hud);
+
+        let cli = Cli::try_parse_from(["axiom", "--hud"]).expect("CLI parse should succeed");
+        assert!(cli.hud);
+        assert_eq!(cli.profile, None);
+
+        let cli = Cli::try_parse_from(["axiom", "--profile", "battery"])
+            .expect("CLI parse should succeed");
+        assert_eq!(cli.profile.as_deref(), Some("battery"));
+        assert_eq!(cli.socket_name, None);
+
+        let cli = Cli::try_parse_from(["axiom", "--socket-name", "wayland-1"])
+            .expect("CLI parse should succeed");
+        assert_eq!(cli.socket_name.as_deref(), Some("wayland-1"));
     }
 }
@@ -0,0 +1,67 @@
+//! Power source detection, for switching config profiles on AC/battery.
+//!
+//! Reads the kernel's `/sys/class/power_supply` class: each entry has a
+//! `type` file, mains adapters an `online` flag and batteries a `status`.
+//! An adapter that is online means AC even while a battery is still
+//! charging or full; otherwise a discharging battery means battery.
+
+use std::fs;
+use std::path::Path;
+
+/// Where the kernel lists power supplies.
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Whether the machine is running on battery. `None` when that can't be
+/// told, e.g. a desktop with no battery or no sysfs.
+pub fn detect_on_battery() -> Option<bool> {
+    detect_on_battery_in(Path::new(POWER_SUPPLY_DIR))
+}
+
+/// [`detect_on_battery`] against a power supply class laid out in `dir`.
+pub fn detect_on_battery_in(dir: &Path) -> Option<bool> {
+    let read = |supply: &Path, file: &str| {
+        fs::read_to_string(supply.join(file))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut discharging = false;
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let supply = entry.path();
+        match read(&supply, "type").as_str() {
+            "Mains" | "USB" if read(&supply, "online") == "1" => return Some(false),
+            "Battery" => discharging |= read(&supply, "status") == "Discharging",
+            _ => {}
+        }
+    }
+    discharging.then_some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(dir: &Path, name: &str, files: &[(&str, &str)]) {
+        let path = dir.join(name);
+        fs::create_dir_all(&path).unwrap();
+        for (file, contents) in files {
+            fs::write(path.join(file), format!("{}\n", contents)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_detect_on_battery_from_power_supply_class() {
+        let dir = tempfile::tempdir().unwrap();
+        // No supplies at all: a desktop, or no sysfs
+        assert_eq!(detect_on_battery_in(dir.path()), None);
+        assert_eq!(detect_on_battery_in(&dir.path().join("missing")), None);
+
+        let battery = [("type", "Battery"), ("status", "Discharging")];
+        supply(dir.path(), "BAT0", &battery);
+        supply(dir.path(), "AC", &[("type", "Mains"), ("online", "0")]);
+        assert_eq!(detect_on_battery_in(dir.path()), Some(true));
+
+        // Plugged in: AC, whatever the battery reports
+        supply(dir.path(), "AC", &[("online", "1")]);
+        assert_eq!(detect_on_battery_in(dir.path()), Some(false));
+    }
+}
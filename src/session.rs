@@ -0,0 +1,66 @@
+//! State kept across restarts in `$XDG_STATE_HOME/axiom`.
+//!
+//! Column names are written to `column_names` when the compositor exits
+//! and read back at the next start, so `scroll_to_named` bindings keep
+//! working after a restart or `--replace`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+/// `$XDG_STATE_HOME/axiom`, falling back to `~/.local/state/axiom`. `None`
+/// when neither variable is usable.
+pub fn state_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))
+        .map(|state_home| state_home.join("axiom"))
+}
+
+/// Where column names are saved.
+pub fn column_names_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("column_names"))
+}
+
+/// Column names saved at `path`: a TOML table of name = column index.
+/// Missing or unreadable files give no names.
+pub fn load_column_names(path: &Path) -> HashMap<String, i32> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return HashMap::new();
+    };
+    toml::from_str(&text).unwrap_or_else(|e| {
+        warn!("⚠️ Ignoring unreadable {}: {}", path.display(), e);
+        HashMap::new()
+    })
+}
+
+/// Save column names to `path`, creating its directory as needed.
+pub fn save_column_names(path: &Path, names: &HashMap<String, i32>) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, toml::to_string(names)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_names_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("axiom").join("column_names");
+        assert!(load_column_names(&path).is_empty());
+
+        let names = HashMap::from([("web".to_string(), 0), ("chat room".to_string(), -2)]);
+        save_column_names(&path, &names).unwrap();
+        assert_eq!(load_column_names(&path), names);
+
+        fs::write(&path, "web = \"not a column\"").unwrap();
+        assert!(load_column_names(&path).is_empty());
+    }
+}
@@ -4,8 +4,23 @@
 //! Manages the lifecycle of AxiomWindow instances and provides
 //! iteration and query interfaces for the compositor.
 
-use crate::config::WindowConfig;
-use std::collections::HashMap;
+use crate::clock::{system_clock, SharedClock};
+use crate::config::{DimInactiveConfig, EffectsConfig, WindowConfig, WindowRule};
+use crate::input::DEFAULT_SEAT;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Focus transition length used by [`WindowManager::step_dim`].
+const DIM_TRANSITION_SECS: f32 = 0.15;
+
+/// Clamp per-step dt so a stalled frame doesn't jump the transition.
+const MAX_DIM_DT_SECS: f32 = 0.1;
+
+/// Diagonal step between successive floating windows that would otherwise
+/// open at the same spot.
+const CASCADE_OFFSET: i32 = 32;
 
 /// Backend-agnostic window record that the Smithay backend populates with the
 /// raw geometry it receives from Wayland. Axiom-specific behaviour lives in
@@ -16,6 +31,8 @@ pub struct BackendWindow {
     pub id: u64,
     /// Window title (updated by the backend on every `set_title`).
     pub title: String,
+    /// Application ID (updated by the backend on every `set_app_id`).
+    pub app_id: Option<String>,
     /// Top-left position in compositor logical pixels.
     pub position: (i32, i32),
     /// Width and height in compositor logical pixels.
@@ -28,6 +45,7 @@ impl BackendWindow {
         Self {
             id,
             title,
+            app_id: None,
             position: (0, 0),
             size: (800, 600),
         }
@@ -43,7 +61,7 @@ impl BackendWindow {
 }
 
 /// Rectangle for window positioning and sizing
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Rectangle {
     pub x: i32,
     pub y: i32,
@@ -72,6 +90,37 @@ impl Rectangle {
             && y < self.y + self.height as i32
     }
 
+    /// Like [`Self::contains_point`], but with the corners rounded off to
+    /// `radius`: points in the cut-away part of a corner are outside. The
+    /// radius is capped at half the shorter side.
+    pub fn contains_rounded(&self, x: f64, y: f64, radius: f64) -> bool {
+        let (left, top) = (self.x as f64, self.y as f64);
+        let (right, bottom) = (left + self.width as f64, top + self.height as f64);
+        if x < left || y < top || x >= right || y >= bottom {
+            return false;
+        }
+        let r = radius.min(self.width as f64 / 2.0).min(self.height as f64 / 2.0);
+        if r <= 0.0 {
+            return true;
+        }
+        // Centre of the corner circle nearest the point, if it is in a corner
+        let cx = if x < left + r {
+            left + r
+        } else if x >= right - r {
+            right - r
+        } else {
+            return true;
+        };
+        let cy = if y < top + r {
+            top + r
+        } else if y >= bottom - r {
+            bottom - r
+        } else {
+            return true;
+        };
+        (x - cx).powi(2) + (y - cy).powi(2) <= r * r
+    }
+
     /// Returns `true` if this rectangle intersects another rectangle.
     /// Two rectangles intersect if they share any area; edge-only touches
     /// are not considered intersection (exclusive right/bottom edges).
@@ -81,6 +130,58 @@ impl Rectangle {
             && self.y < other.y + other.height as i32
             && self.y + self.height as i32 > other.y
     }
+
+    /// Where this rectangle should land when dragged to its current
+    /// position, snapping its edges to the `bounds` edges or to a
+    /// neighbour's border when they are within `threshold` pixels.
+    ///
+    /// Each axis snaps independently to its nearest candidate. Neighbours
+    /// only count when they line up with this rectangle on the other axis,
+    /// so a window far above another doesn't snap to its side.
+    pub fn snapped_position(
+        &self,
+        bounds: &Rectangle,
+        neighbours: &[Rectangle],
+        threshold: u32,
+    ) -> (i32, i32) {
+        let threshold = threshold as i32;
+        let (w, h) = (self.width as i32, self.height as i32);
+
+        // Candidate (offset to apply) for each edge pair, nearest wins
+        let nearest = |pairs: &mut dyn Iterator<Item = (i32, i32)>| {
+            pairs
+                .map(|(edge, target)| target - edge)
+                .filter(|d| d.abs() <= threshold)
+                .min_by_key(|d| d.abs())
+                .unwrap_or(0)
+        };
+
+        let (left, right) = (self.x, self.x + w);
+        let (top, bottom) = (self.y, self.y + h);
+        let overlaps_vertically =
+            |n: &&Rectangle| top < n.y + n.height as i32 + threshold && bottom > n.y - threshold;
+        let overlaps_horizontally =
+            |n: &&Rectangle| left < n.x + n.width as i32 + threshold && right > n.x - threshold;
+
+        let mut x_pairs = [(left, bounds.x), (right, bounds.x + bounds.width as i32)]
+            .into_iter()
+            .chain(
+                neighbours
+                    .iter()
+                    .filter(overlaps_vertically)
+                    .flat_map(|n| [(left, n.x + n.width as i32), (right, n.x)]),
+            );
+        let mut y_pairs = [(top, bounds.y), (bottom, bounds.y + bounds.height as i32)]
+            .into_iter()
+            .chain(
+                neighbours
+                    .iter()
+                    .filter(overlaps_horizontally)
+                    .flat_map(|n| [(top, n.y + n.height as i32), (bottom, n.y)]),
+            );
+
+        (self.x + nearest(&mut x_pairs), self.y + nearest(&mut y_pairs))
+    }
 }
 
 /// Enhanced window wrapper for Axiom-specific functionality
@@ -94,6 +195,267 @@ pub struct AxiomWindow {
 
     /// Window-specific properties
     pub properties: WindowProperties,
+
+    /// Transient parent (e.g. the main window of a dialog)
+    pub parent_id: Option<u64>,
+
+    /// Geometry to return to when leaving maximized state
+    pub saved_rect: Option<Rectangle>,
+
+    /// Geometry to return to when leaving fullscreen
+    pub fullscreen_restore: Option<Rectangle>,
+
+    /// Process that owns the window, when the backend could tell
+    pub client: Option<ClientProcess>,
+}
+
+/// The process behind a client, from the credentials of its connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientProcess {
+    pub pid: i32,
+    /// Name of its executable, if it could be read from `/proc`
+    pub executable: Option<String>,
+}
+
+/// Role of a window as far as keyboard focus is concerned.
+///
+/// Transient surfaces such as tooltips, notifications and menus must never
+/// take keyboard focus away from the window the user is typing into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowType {
+    /// Regular application window
+    #[default]
+    Normal,
+    /// Modal or transient dialog; focusable like a normal window
+    Dialog,
+    /// Hover tooltip
+    Tooltip,
+    /// Desktop notification bubble
+    Notification,
+    /// Dropdown or context menu
+    Menu,
+}
+
+impl WindowType {
+    /// Whether windows of this type may ever hold keyboard focus.
+    pub fn accepts_focus(self) -> bool {
+        !matches!(self, Self::Tooltip | Self::Notification | Self::Menu)
+    }
+}
+
+/// What a node of [`WindowManager::get_window_tree`] stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowTreeKind {
+    /// A window with no transient parent
+    Window,
+    /// A dialog, or any window with a transient parent
+    Dialog,
+    /// An `xdg_popup` (menu, tooltip...)
+    Popup,
+}
+
+/// One node of the window hierarchy, with everything that hangs off it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowTreeNode {
+    /// Window ID, or the surface protocol ID of a popup
+    pub id: u64,
+    pub kind: WindowTreeKind,
+    /// Global geometry for windows; relative to the parent for popups
+    pub geometry: Rectangle,
+    /// Dialogs, then popups, each by ID
+    pub children: Vec<WindowTreeNode>,
+}
+
+/// What an `xdg_popup` is attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopupParent {
+    /// A managed window, by window ID
+    Window(u64),
+    /// Another popup, by surface protocol ID
+    Popup(u32),
+}
+
+/// An open `xdg_popup`, as the backend reports it for
+/// [`WindowManager::get_window_tree`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PopupInfo {
+    /// Surface protocol ID
+    pub id: u32,
+    pub parent: PopupParent,
+    /// Geometry relative to the parent
+    pub geometry: Rectangle,
+}
+
+/// Size limits a window must respect, whether it is resized by layout,
+/// IPC or an interactive drag.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WindowConstraints {
+    /// Smallest allowed `(width, height)`
+    pub min_size: Option<(u32, u32)>,
+    /// Largest allowed `(width, height)`
+    pub max_size: Option<(u32, u32)>,
+    /// Locked `width / height` ratio
+    pub aspect_ratio: Option<f32>,
+}
+
+impl WindowConstraints {
+    /// Build constraints from `xdg_toplevel.set_min_size` / `set_max_size`
+    /// hints, where `0` on an axis means "no limit".
+    ///
+    /// xdg_toplevel can't express an aspect ratio, so none is set: limits
+    /// that happen to share a ratio (400x300 .. 800x600) don't lock it.
+    pub fn from_size_hints(min: (i32, i32), max: (i32, i32)) -> Self {
+        let axis = |v: i32| u32::try_from(v).ok().filter(|&v| v > 0);
+        let min_size = match (axis(min.0), axis(min.1)) {
+            (None, None) => None,
+            (w, h) => Some((w.unwrap_or(1), h.unwrap_or(1))),
+        };
+        let max_size = match (axis(max.0), axis(max.1)) {
+            (None, None) => None,
+            (w, h) => Some((w.unwrap_or(u32::MAX), h.unwrap_or(u32::MAX))),
+        };
+
+        Self {
+            min_size,
+            max_size,
+            aspect_ratio: None,
+        }
+    }
+
+    /// The size the window is pinned to, if min and max are equal.
+    pub fn fixed_size(&self) -> Option<(u32, u32)> {
+        match (self.min_size, self.max_size) {
+            (Some(lo), Some(hi)) if lo == hi => Some(lo),
+            _ => None,
+        }
+    }
+
+    /// Fit a requested size to these constraints.
+    ///
+    /// `width_driven` picks which axis wins when an aspect ratio is locked
+    /// (the one the user is dragging). `limit` is an extra upper bound, e.g.
+    /// the space left on the output; when it conflicts with `min_size` the
+    /// limit wins so the window never leaves the screen.
+    pub fn constrain(
+        &self,
+        (width, height): (u32, u32),
+        width_driven: bool,
+        limit: Option<(u32, u32)>,
+    ) -> (u32, u32) {
+        let (min_w, min_h) = self.min_size.unwrap_or((1, 1));
+        let (mut max_w, mut max_h) = self.max_size.unwrap_or((u32::MAX, u32::MAX));
+        if let Some((lw, lh)) = limit {
+            max_w = max_w.min(lw);
+            max_h = max_h.min(lh);
+        }
+        let (max_w, max_h) = (max_w.max(1), max_h.max(1));
+        let (min_w, min_h) = (min_w.clamp(1, max_w), min_h.clamp(1, max_h));
+
+        let mut w = width.clamp(min_w, max_w);
+        let mut h = height.clamp(min_h, max_h);
+
+        if let Some(ratio) = self.aspect_ratio.filter(|r| r.is_finite() && *r > 0.0) {
+            let h_for = |w: u32| ((w as f32 / ratio).round() as u32).max(1);
+            let w_for = |h: u32| ((h as f32 * ratio).round() as u32).max(1);
+            if width_driven {
+                h = h_for(w);
+            } else {
+                w = w_for(h);
+            }
+            // Shrink along the ratio until both axes fit their maximums
+            if h > max_h {
+                h = max_h;
+                w = w_for(h);
+            }
+            if w > max_w {
+                w = max_w;
+                h = h_for(w);
+            }
+        }
+        (w, h)
+    }
+}
+
+/// Colour filter the renderer applies to one window's content, e.g. to
+/// read a bright document at night.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorFilter {
+    /// Drawn as the client painted it
+    #[default]
+    None,
+    /// Every colour replaced by its opposite
+    Invert,
+    /// Colours reduced to their luminance
+    Grayscale,
+}
+
+impl ColorFilter {
+    /// The filter after this one in the `cycle_color_filter` rotation.
+    pub fn next(self) -> Self {
+        match self {
+            Self::None => Self::Invert,
+            Self::Invert => Self::Grayscale,
+            Self::Grayscale => Self::None,
+        }
+    }
+}
+
+/// Scale and rotation applied to a window when it is drawn, both around
+/// the centre of its rectangle. Translation is the window's own position.
+/// Used by effects such as overview thumbnails and open/close zooms; the
+/// window's geometry and input region are unaffected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowTransform {
+    /// Uniform scale factor (1.0 = natural size)
+    pub scale: f64,
+    /// Clockwise rotation in radians
+    pub rotation: f64,
+}
+
+impl Default for WindowTransform {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl WindowTransform {
+    /// Whether drawing with this transform changes nothing.
+    pub fn is_identity(&self) -> bool {
+        self.scale == 1.0 && self.rotation == 0.0
+    }
+
+    /// Corners of `rect` once transformed, clockwise from the top-left.
+    pub fn quad(&self, rect: &Rectangle) -> [(f64, f64); 4] {
+        let (cx, cy) = (
+            rect.x as f64 + rect.width as f64 / 2.0,
+            rect.y as f64 + rect.height as f64 / 2.0,
+        );
+        let (hw, hh) = (rect.width as f64 / 2.0, rect.height as f64 / 2.0);
+        let (sin, cos) = self.rotation.sin_cos();
+        [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)].map(|(dx, dy)| {
+            let (dx, dy) = (dx * self.scale, dy * self.scale);
+            (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+        })
+    }
+}
+
+/// A state change requested for one window by ID, e.g. from IPC.
+/// Applied with [`WindowManager::execute_operation`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WindowOperation {
+    Minimize,
+    Restore,
+    Maximize,
+    Unmaximize,
+    /// Ask the client to close; see [`WindowManager::request_close`]
+    Close,
+    /// Set [`WindowProperties::opacity`] (0.0 - 1.0)
+    SetOpacity { opacity: f32 },
 }
 
 /// Per-window properties that the compositor reads when applying layout or
@@ -125,8 +487,35 @@ pub struct WindowProperties {
     /// Custom window opacity (0.0 - 1.0)
     pub opacity: f32,
 
+    /// Opacity used while the window does not have focus (0.0 - 1.0)
+    pub inactive_opacity: f32,
+
+    /// Whether the window asked for blur-behind via a window rule
+    pub blur: bool,
+
+    /// Current inactive-dim amount (0.0 = full brightness). Follows
+    /// `focus_mix`, scaled by the configured strength.
+    pub dim: f32,
+
+    /// How far the window is from its inactive look (0.0) to its focused
+    /// look (1.0). Animated by [`WindowManager::update_focus_transition`]
+    /// so dim, opacity and decoration colors fade on focus changes.
+    pub focus_mix: f32,
+
     /// Custom border radius (for effects)
     pub border_radius: u32,
+
+    /// Focus role; non-focusable types are skipped by every focus path
+    pub window_type: WindowType,
+
+    /// Size limits applied on every resize
+    pub constraints: WindowConstraints,
+
+    /// Scale and rotation the window is drawn with
+    pub transform: WindowTransform,
+
+    /// Colour filter the window's content is drawn through
+    pub color_filter: ColorFilter,
 }
 
 impl Default for WindowProperties {
@@ -137,9 +526,50 @@ impl Default for WindowProperties {
             maximized: false,
             minimized: false,
             opacity: 1.0,
+            inactive_opacity: 1.0,
+            blur: false,
+            dim: 0.0,
+            focus_mix: 1.0,
             border_radius: 0,
+            window_type: WindowType::Normal,
+            constraints: WindowConstraints::default(),
+            transform: WindowTransform::default(),
+            color_filter: ColorFilter::None,
+        }
+    }
+}
+
+impl WindowProperties {
+    /// Opacity the renderer should draw this window with.
+    pub fn effective_opacity(&self, focused: bool) -> f32 {
+        if focused {
+            self.opacity
+        } else {
+            self.inactive_opacity
         }
     }
+
+    /// Opacity at the current point of the focus transition, between
+    /// `inactive_opacity` and `opacity`.
+    pub fn transition_opacity(&self) -> f32 {
+        self.inactive_opacity + (self.opacity - self.inactive_opacity) * self.focus_mix
+    }
+
+    /// Whether blur-behind is in effect. There is nothing to blur through
+    /// an opaque window, so full opacity always disables it.
+    pub fn blur_behind(&self, focused: bool) -> bool {
+        self.blur && self.effective_opacity(focused) < 1.0
+    }
+
+    /// Factor the window's sampled color is multiplied by after dimming.
+    pub fn dim_multiplier(&self) -> f32 {
+        1.0 - self.dim
+    }
+
+    /// Whether the window can take keyboard focus right now.
+    pub fn is_focusable(&self) -> bool {
+        !self.minimized && self.window_type.accepts_focus()
+    }
 }
 
 impl AxiomWindow {
@@ -149,8 +579,71 @@ impl AxiomWindow {
             window: BackendWindow::new(id, title),
             workspace_position: 0.0,
             properties: WindowProperties::default(),
+            parent_id: None,
+            saved_rect: None,
+            fullscreen_restore: None,
+            client: None,
         }
     }
+
+    /// Current geometry as a [`Rectangle`].
+    pub fn rect(&self) -> Rectangle {
+        Rectangle::from_loc_and_size(self.window.position, self.window.size)
+    }
+}
+
+/// Change notification delivered to listeners registered with
+/// [`WindowManager::add_window_listener`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowEvent {
+    /// A window was added to management
+    Created { id: u64 },
+    /// A window was removed from management
+    Removed { id: u64 },
+    /// A window's top-left corner moved
+    Moved { id: u64, x: i32, y: i32 },
+    /// A window's size changed
+    Resized { id: u64, width: u32, height: u32 },
+    /// A window's title changed
+    TitleChanged { id: u64, title: String },
+    /// A window's application ID changed
+    AppIdChanged { id: u64, app_id: Option<String> },
+}
+
+/// Callback invoked for every [`WindowEvent`]. Listeners run while the
+/// window manager is borrowed, so they must not call back into it.
+pub type WindowListener = Box<dyn Fn(WindowEvent) + Send + Sync>;
+
+/// Registered listeners; a newtype so [`WindowManager`] can stay `Debug`.
+#[derive(Default)]
+struct WindowListeners(Vec<WindowListener>);
+
+impl fmt::Debug for WindowListeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WindowListeners({})", self.0.len())
+    }
+}
+
+/// Handle returned by [`WindowManager::add_focus_listener`], used to
+/// unregister the listener again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+/// Callback invoked with the newly focused window (or `None`) whenever
+/// keyboard focus changes. Same re-entrancy rule as [`WindowListener`].
+pub type FocusListener = Box<dyn Fn(Option<u64>) + Send + Sync>;
+
+/// Registered focus listeners keyed by their [`ListenerId`].
+#[derive(Default)]
+struct FocusListeners {
+    next_id: u64,
+    entries: Vec<(ListenerId, FocusListener)>,
+}
+
+impl fmt::Debug for FocusListeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FocusListeners({})", self.entries.len())
+    }
 }
 
 /// Central store of all managed windows. Owns every [`AxiomWindow`] keyed by
@@ -166,16 +659,179 @@ pub struct WindowManager {
 
     /// Currently focused window
     focused_window: Option<u64>,
+
+    /// Keyboard focus of every seat other than the default one, whose
+    /// focus is `focused_window`
+    seat_focus: HashMap<String, u64>,
+
+    /// Window IDs bottom-to-top; focusing a window raises it
+    stacking_order: Vec<u64>,
+
+    /// Previously focused windows, most recent last (no duplicates)
+    focus_history: Vec<u64>,
+
+    /// Timestamp of the last focus-transition step
+    last_transition_update: Instant,
+
+    /// Time source for focus transitions.
+    clock: SharedClock,
+
+    /// Subscribers to window lifecycle and geometry events
+    listeners: WindowListeners,
+
+    /// Windows picked for group operations, in the order they were added
+    selection: Vec<u64>,
+
+    /// Windows asked to close, with the deadline after which they are
+    /// closed by force
+    pending_closes: HashMap<u64, Instant>,
+
+    /// Windows sent to the scratchpad, oldest first. A hidden scratchpad
+    /// window is minimized and out of the stacking order; shown, it floats
+    /// above the tiled windows.
+    scratchpad: Vec<u64>,
+
+    /// Subscribers to keyboard focus changes. `WindowManager` is not
+    /// `Clone`; any copy of window state taken for layout is built from
+    /// [`AxiomWindow`] values and carries no listeners.
+    focus_listeners: FocusListeners,
 }
 
 impl WindowManager {
     /// Create an empty `WindowManager`. The `_config` argument is retained
     /// for future config-driven defaults.
-    pub fn new(_config: &WindowConfig) -> Self {
+    pub fn new(config: &WindowConfig) -> Self {
+        Self::with_clock(config, system_clock())
+    }
+
+    /// Create a `WindowManager` whose focus transitions read time from
+    /// `clock` instead of the system clock.
+    pub fn with_clock(_config: &WindowConfig, clock: SharedClock) -> Self {
         Self {
             windows: HashMap::new(),
             next_window_id: 1,
             focused_window: None,
+            seat_focus: HashMap::new(),
+            stacking_order: Vec::new(),
+            focus_history: Vec::new(),
+            last_transition_update: clock.now(),
+            clock,
+            selection: Vec::new(),
+            pending_closes: HashMap::new(),
+            scratchpad: Vec::new(),
+            listeners: WindowListeners::default(),
+            focus_listeners: FocusListeners::default(),
+        }
+    }
+
+    /// Subscribe to keyboard focus changes. Keep the returned id and pass it
+    /// to [`Self::remove_focus_listener`] when the observer goes away, so
+    /// short-lived subscribers don't accumulate.
+    pub fn add_focus_listener(&mut self, listener: FocusListener) -> ListenerId {
+        let id = ListenerId(self.focus_listeners.next_id);
+        self.focus_listeners.next_id += 1;
+        self.focus_listeners.entries.push((id, listener));
+        id
+    }
+
+    /// Unregister a focus listener. Returns `false` if it was already gone.
+    pub fn remove_focus_listener(&mut self, id: ListenerId) -> bool {
+        let before = self.focus_listeners.entries.len();
+        self.focus_listeners.entries.retain(|(lid, _)| *lid != id);
+        self.focus_listeners.entries.len() != before
+    }
+
+    /// Single write path for `focused_window`; notifies focus listeners
+    /// when the value actually changes.
+    fn change_focus(&mut self, focus: Option<u64>) {
+        if self.focused_window == focus {
+            return;
+        }
+        self.focused_window = focus;
+        if let Some(id) = focus {
+            self.stacking_order.retain(|&w| w != id);
+            self.stacking_order.push(id);
+            self.focus_history.retain(|&w| w != id);
+            self.focus_history.push(id);
+        }
+        for (_, listener) in &self.focus_listeners.entries {
+            listener(focus);
+        }
+    }
+
+    /// Subscribe to window creation, removal, geometry and metadata
+    /// changes, so bars and IPC clients can observe without polling.
+    pub fn add_window_listener(&mut self, listener: WindowListener) {
+        self.listeners.0.push(listener);
+    }
+
+    fn emit(&self, event: WindowEvent) {
+        for listener in &self.listeners.0 {
+            listener(event.clone());
+        }
+    }
+
+    /// Update a window's position and/or size, emitting `Moved` / `Resized`
+    /// for whichever actually changed.
+    fn set_geometry(&mut self, id: u64, position: Option<(i32, i32)>, size: Option<(u32, u32)>) {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return;
+        };
+        let moved = position.filter(|&p| p != window.window.position);
+        let resized = size.filter(|&s| s != window.window.size);
+        if let Some((x, y)) = moved {
+            window.window.set_position(x, y);
+            self.emit(WindowEvent::Moved { id, x, y });
+        }
+        if let Some((width, height)) = resized {
+            if let Some(window) = self.windows.get_mut(&id) {
+                window.window.set_size(width, height);
+            }
+            self.emit(WindowEvent::Resized { id, width, height });
+        }
+    }
+
+    /// Move a window's top-left corner. Returns `false` if it doesn't exist.
+    pub fn move_window(&mut self, id: u64, x: i32, y: i32) -> bool {
+        if !self.windows.contains_key(&id) {
+            return false;
+        }
+        self.set_geometry(id, Some((x, y)), None);
+        true
+    }
+
+    /// Set a window's position and size as-is, bypassing constraints. Used
+    /// when the caller has already fitted the geometry (interactive resize).
+    pub fn set_window_geometry(&mut self, id: u64, rect: Rectangle) {
+        self.set_geometry(id, Some((rect.x, rect.y)), Some((rect.width, rect.height)));
+    }
+
+    /// Update a window's title.
+    pub fn set_window_title(&mut self, id: u64, title: String) {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return;
+        };
+        if window.window.title != title {
+            window.window.title = title.clone();
+            self.emit(WindowEvent::TitleChanged { id, title });
+        }
+    }
+
+    /// Update a window's application ID.
+    pub fn set_window_app_id(&mut self, id: u64, app_id: Option<String>) {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return;
+        };
+        if window.window.app_id != app_id {
+            window.window.app_id = app_id.clone();
+            self.emit(WindowEvent::AppIdChanged { id, app_id });
+        }
+    }
+
+    /// Record the process that owns a window.
+    pub fn set_window_client(&mut self, id: u64, client: Option<ClientProcess>) {
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.client = client;
         }
     }
 
@@ -190,13 +846,19 @@ impl WindowManager {
             window: backend_window,
             workspace_position: 0.0, // Start at workspace 0
             properties: WindowProperties::default(),
+            parent_id: None,
+            saved_rect: None,
+            fullscreen_restore: None,
+            client: None,
         };
 
         self.windows.insert(id, axiom_window);
+        self.stacking_order.push(id);
+        self.emit(WindowEvent::Created { id });
 
         // Focus the new window if no window is currently focused
         if self.focused_window.is_none() {
-            self.focused_window = Some(id);
+            self.change_focus(Some(id));
         }
 
         id
@@ -204,15 +866,237 @@ impl WindowManager {
 
     /// Remove a window from management.
     ///
-    /// If the removed window was focused, focus moves to the next available
-    /// window (any remaining ID), so the compositor never loses track of the
-    /// active window. Returns `None` when the window doesn't exist.
+    /// If the removed window was focused, focus moves to the most recently
+    /// focused remaining window (or, failing that, any remaining ID), so the
+    /// compositor never loses track of the active window. The ID is also
+    /// dropped from the stacking order and focus history, and children lose
+    /// their parent link. Returns `None` when the window doesn't exist.
     pub fn remove_window(&mut self, id: u64) -> Option<AxiomWindow> {
         if self.focused_window == Some(id) {
             // Re-focus a sibling before clearing focus.
-            self.focused_window = self.windows.keys().filter(|&&k| k != id).max().copied();
+            let accepts = |k: u64| {
+                k != id
+                    && self
+                        .windows
+                        .get(&k)
+                        .is_some_and(|w| w.properties.window_type.accepts_focus())
+            };
+            let next = self
+                .focus_history
+                .iter()
+                .rev()
+                .copied()
+                .find(|&k| accepts(k))
+                .or_else(|| self.windows.keys().copied().filter(|&k| accepts(k)).max());
+            self.change_focus(next);
+        }
+        let removed = self.windows.remove(&id)?;
+        self.pending_closes.remove(&id);
+        self.selection.retain(|&w| w != id);
+        self.scratchpad.retain(|&w| w != id);
+        self.stacking_order.retain(|&w| w != id);
+        self.focus_history.retain(|&w| w != id);
+        self.seat_focus.retain(|_, w| *w != id);
+        for window in self.windows.values_mut() {
+            if window.parent_id == Some(id) {
+                window.parent_id = None;
+            }
+        }
+        self.emit(WindowEvent::Removed { id });
+        Some(removed)
+    }
+
+    /// Drop bookkeeping that names windows which no longer exist, or names
+    /// one twice, and move focus off a window that is gone. A handler that
+    /// panicked part way through adding or removing a window can leave
+    /// these out of step. Returns how many entries were repaired.
+    pub fn restore_invariants(&mut self) -> usize {
+        let mut repaired = 0;
+        let windows = &self.windows;
+        for ids in [
+            &mut self.stacking_order,
+            &mut self.focus_history,
+            &mut self.selection,
+            &mut self.scratchpad,
+        ] {
+            let before = ids.len();
+            let mut seen = HashSet::new();
+            ids.retain(|id| windows.contains_key(id) && seen.insert(*id));
+            repaired += before - ids.len();
+        }
+        let before = self.seat_focus.len() + self.pending_closes.len();
+        self.seat_focus.retain(|_, id| windows.contains_key(id));
+        self.pending_closes.retain(|id, _| windows.contains_key(id));
+        repaired += before - self.seat_focus.len() - self.pending_closes.len();
+
+        let ids: HashSet<u64> = self.windows.keys().copied().collect();
+        for window in self.windows.values_mut() {
+            if window.parent_id.is_some_and(|p| !ids.contains(&p)) {
+                window.parent_id = None;
+                repaired += 1;
+            }
+        }
+        if let Some(&max) = ids.iter().max() {
+            if self.next_window_id <= max {
+                self.next_window_id = max + 1;
+                repaired += 1;
+            }
+        }
+        if self.focused_window.is_some_and(|id| !ids.contains(&id)) {
+            let next = self.focus_history.last().copied();
+            self.change_focus(next);
+            repaired += 1;
+        }
+        repaired
+    }
+
+    /// Add window `id` to the selection. Returns `false` if it is unknown
+    /// or already selected.
+    pub fn add_to_selection(&mut self, id: u64) -> bool {
+        if !self.windows.contains_key(&id) || self.selection.contains(&id) {
+            return false;
+        }
+        self.selection.push(id);
+        true
+    }
+
+    /// Select window `id`, or deselect it if it already was. Returns
+    /// whether it is selected afterwards.
+    pub fn toggle_selection(&mut self, id: u64) -> bool {
+        if let Some(pos) = self.selection.iter().position(|&w| w == id) {
+            self.selection.remove(pos);
+            return false;
+        }
+        self.add_to_selection(id)
+    }
+
+    /// Deselect every window.
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    /// Selected window IDs, in the order they were selected.
+    pub fn selected(&self) -> &[u64] {
+        &self.selection
+    }
+
+    /// Whether window `id` is part of the selection.
+    pub fn is_selected(&self, id: u64) -> bool {
+        self.selection.contains(&id)
+    }
+
+    /// Record that window `id` was asked to close. If it is still around
+    /// `grace` after `now`, [`Self::overdue_closes`] reports it so the
+    /// backend can close it by force. Asking again keeps the first deadline,
+    /// so repeated requests don't extend the grace period. Returns `false`
+    /// for unknown windows.
+    pub fn request_close(&mut self, id: u64, now: Instant, grace: Duration) -> bool {
+        if !self.windows.contains_key(&id) {
+            return false;
+        }
+        self.pending_closes.entry(id).or_insert(now + grace);
+        true
+    }
+
+    /// Whether window `id` has been asked to close and hasn't gone yet.
+    pub fn is_closing(&self, id: u64) -> bool {
+        self.pending_closes.contains_key(&id)
+    }
+
+    /// Windows that are open and not on their way out.
+    pub fn open_window_count(&self) -> usize {
+        self.windows.len() - self.pending_closes.len()
+    }
+
+    /// The window that has gone longest without focus, for making room
+    /// when too many are open. Windows never focused come first, oldest
+    /// first, then the least recently focused. Never the focused window or
+    /// one already asked to close.
+    pub fn least_recently_focused(&self) -> Option<u64> {
+        let candidate =
+            |id: &u64| Some(*id) != self.focused_window && !self.pending_closes.contains_key(id);
+        self.windows
+            .keys()
+            .filter(|id| !self.focus_history.contains(id))
+            .filter(|id| candidate(id))
+            .min()
+            .copied()
+            .or_else(|| self.focus_history.iter().copied().find(candidate))
+    }
+
+    /// Windows whose close grace period ran out by `now`. Each is reported
+    /// once; the caller is expected to destroy it.
+    pub fn overdue_closes(&mut self, now: Instant) -> Vec<u64> {
+        let mut overdue: Vec<u64> = self
+            .pending_closes
+            .iter()
+            .filter(|(_, &deadline)| deadline <= now)
+            .map(|(&id, _)| id)
+            .collect();
+        overdue.sort_unstable();
+        for id in &overdue {
+            self.pending_closes.remove(id);
+        }
+        overdue
+    }
+
+    /// The topmost window whose visible shape contains `(x, y)`.
+    ///
+    /// Walks [`Self::get_windows_by_render_order`] from the top, so hits
+    /// agree with what is painted. Windows are tested at
+    /// [`Self::visible_rect`]. Corners are rounded to `corner_radius`
+    /// (fullscreen windows are square), so a click in the transparent part
+    /// of a corner falls through to the window below.
+    pub fn window_at(
+        &self,
+        (x, y): (f64, f64),
+        corner_radius: f64,
+        layouts: &HashMap<u64, Rectangle>,
+    ) -> Option<u64> {
+        self.get_windows_by_render_order()
+            .into_iter()
+            .rev()
+            .find(|&id| {
+                let Some(rect) = self.visible_rect(id, layouts) else {
+                    return false;
+                };
+                let fullscreen = self.windows[&id].properties.fullscreen;
+                let radius = if fullscreen { 0.0 } else { corner_radius };
+                rect.contains_rounded(x, y, radius)
+            })
+    }
+
+    /// Where window `id` is shown: floating, maximized and fullscreen
+    /// windows at their own geometry, tiled windows only at their entry in
+    /// `layouts`, so ones scrolled out of view have none. Minimized windows
+    /// have none either.
+    pub fn visible_rect(&self, id: u64, layouts: &HashMap<u64, Rectangle>) -> Option<Rectangle> {
+        let window = self.windows.get(&id)?;
+        let props = &window.properties;
+        if props.minimized {
+            None
+        } else if props.floating || props.maximized || props.fullscreen {
+            Some(window.rect())
+        } else {
+            layouts.get(&id).cloned()
         }
-        self.windows.remove(&id)
+    }
+
+    /// Window IDs in paint order, bottom first: the stacking order, with
+    /// fullscreen windows lifted above the rest so they occlude everything.
+    pub fn get_windows_by_render_order(&self) -> Vec<u64> {
+        let mut order = self.stacking_order.clone();
+        order.sort_by_key(|id| {
+            self.windows
+                .get(id)
+                .is_some_and(|w| w.properties.fullscreen)
+        });
+        order
+    }
+
+    /// Previously focused window IDs, most recent last.
+    pub fn focus_history(&self) -> &[u64] {
+        &self.focus_history
     }
 
     /// Iterate over all managed windows via a closure (avoids per-frame allocation).
@@ -232,6 +1116,13 @@ impl WindowManager {
         self.windows.len() as u32
     }
 
+    /// IDs of every managed window, minimized ones included, ascending.
+    pub fn window_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.windows.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
     /// Borrow a window by ID. Returns `None` if no window with that ID exists.
     pub fn get_window(&self, id: u64) -> Option<&AxiomWindow> {
         self.windows.get(&id)
@@ -242,41 +1133,553 @@ impl WindowManager {
         self.windows.get_mut(&id)
     }
 
-    /// Focus a window
-    pub fn focus_window(&mut self, id: u64) {
-        if self.windows.contains_key(&id) {
-            self.focused_window = Some(id);
-        }
-    }
-
-    /// Set focus to a specific window ID or clear focus entirely.
-    pub fn set_focused_window(&mut self, id: Option<u64>) {
-        match id {
-            Some(id) if self.windows.contains_key(&id) => {
-                self.focused_window = Some(id);
+    /// Apply every matching `[[window_rules]]` entry to a window, in order.
+    /// Returns `true` if at least one rule matched.
+    pub fn apply_window_rules(
+        &mut self,
+        id: u64,
+        app_id: Option<&str>,
+        rules: &[WindowRule],
+    ) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        let mut matched = false;
+        for rule in rules
+            .iter()
+            .filter(|r| r.matches(app_id, &window.window.title))
+        {
+            matched = true;
+            let props = &mut window.properties;
+            if let Some(opacity) = rule.opacity {
+                props.opacity = opacity;
+                // An unfocused override is optional; follow the focused value
+                props.inactive_opacity = opacity;
             }
-            Some(_) => {}
-            None => {
-                self.focused_window = None;
+            if let Some(inactive) = rule.inactive_opacity {
+                props.inactive_opacity = inactive;
+            }
+            if let Some(blur) = rule.blur {
+                props.blur = blur;
             }
         }
+        matched
     }
 
-    /// Get the currently focused window id
-    pub fn focused_window_id(&self) -> Option<u64> {
-        self.focused_window
+    /// Resize a window, fitting the request to its [`WindowConstraints`].
+    /// Returns the size actually applied.
+    pub fn resize_window(&mut self, id: u64, width: u32, height: u32) -> Option<(u32, u32)> {
+        let size = self
+            .windows
+            .get(&id)?
+            .properties
+            .constraints
+            .constrain((width, height), true, None);
+        self.set_geometry(id, None, Some(size));
+        Some(size)
     }
 
-    /// Toggle fullscreen for a window
-    pub fn toggle_fullscreen(&mut self, id: u64) {
+    /// Replace a window's size constraints.
+    pub fn set_window_constraints(&mut self, id: u64, constraints: WindowConstraints) {
         if let Some(window) = self.windows.get_mut(&id) {
-            window.properties.fullscreen = !window.properties.fullscreen;
+            window.properties.constraints = constraints;
         }
     }
 
-    /// Toggle the floating state of a window. Floating windows are
-    /// positioned by the user rather than auto-tiled.
-    pub fn toggle_floating(&mut self, id: u64) {
+    /// Apply constraints parsed from client size hints. A window pinned to
+    /// a single size can't be tiled, so it is switched to floating at that
+    /// size. Returns `true` when the window was made floating.
+    pub fn apply_size_hints(&mut self, id: u64, constraints: WindowConstraints) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        window.properties.constraints = constraints;
+        let Some(size) = constraints.fixed_size() else {
+            return false;
+        };
+        let newly_floating = !window.properties.floating;
+        window.properties.floating = true;
+        self.set_geometry(id, None, Some(size));
+        newly_floating
+    }
+
+    /// Record a window's transient parent. Self-parenting and unknown
+    /// parents are ignored.
+    pub fn set_window_parent(&mut self, id: u64, parent: Option<u64>) {
+        let parent = parent.filter(|&p| p != id && self.windows.contains_key(&p));
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.parent_id = parent;
+        }
+    }
+
+    /// The window hierarchy: every window without a transient parent at the
+    /// root, its dialogs and `popups` below it, and sub-popups below the
+    /// popups they were opened from. Popups of unknown parents are left out.
+    /// A window caught in a parent cycle becomes a root, so every window
+    /// shows up exactly once.
+    pub fn get_window_tree(&self, popups: &[PopupInfo]) -> Vec<WindowTreeNode> {
+        let ids = self.window_ids();
+        let mut builder = WindowTreeBuilder {
+            windows: &self.windows,
+            dialogs: HashMap::new(),
+            popups: HashMap::new(),
+            placed: HashSet::new(),
+        };
+        for &id in &ids {
+            if let Some(parent) = self.windows[&id].parent_id {
+                builder.dialogs.entry(parent).or_default().push(id);
+            }
+        }
+        let mut popups: Vec<&PopupInfo> = popups.iter().collect();
+        popups.sort_by_key(|p| p.id);
+        for popup in popups {
+            builder.popups.entry(popup.parent).or_default().push(popup);
+        }
+
+        let is_root = |id: &u64| {
+            self.windows[id]
+                .parent_id
+                .is_none_or(|p| !self.windows.contains_key(&p))
+        };
+        let mut roots: Vec<WindowTreeNode> = ids
+            .iter()
+            .filter(|id| is_root(id))
+            .map(|&id| builder.window(id))
+            .collect();
+        for id in ids {
+            if !builder.placed.contains(&PopupParent::Window(id)) {
+                roots.push(builder.window(id));
+            }
+        }
+        roots
+    }
+
+    /// Make a window floating and give it a sensible initial position.
+    ///
+    /// The window is centered over its parent when it has one, otherwise over
+    /// `area` (the focused output). If another floating window already sits
+    /// at that spot, the new one is cascaded down-right by a fixed offset so
+    /// several dialogs opened together stay distinguishable. The result is
+    /// clamped to `area`. Returns the chosen position.
+    pub fn place_floating_window(&mut self, id: u64, area: Rectangle) -> Option<(i32, i32)> {
+        let (w, h) = self.windows.get(&id)?.window.size;
+        let anchor = self
+            .windows
+            .get(&id)
+            .and_then(|win| win.parent_id)
+            .and_then(|pid| self.windows.get(&pid))
+            .map(AxiomWindow::rect)
+            .unwrap_or_else(|| area.clone());
+
+        let base_x = anchor.x + (anchor.width as i32 - w as i32) / 2;
+        let base_y = anchor.y + (anchor.height as i32 - h as i32) / 2;
+        let occupied: Vec<(i32, i32)> = self
+            .windows
+            .iter()
+            .filter(|(&k, win)| k != id && win.properties.floating && !win.properties.minimized)
+            .map(|(_, win)| win.window.position)
+            .collect();
+
+        let mut pos = (base_x, base_y);
+        while occupied.contains(&pos) {
+            pos = (pos.0 + CASCADE_OFFSET, pos.1 + CASCADE_OFFSET);
+        }
+
+        // Keep the window on the output where possible
+        let max_x = area.x + (area.width as i32 - w as i32).max(0);
+        let max_y = area.y + (area.height as i32 - h as i32).max(0);
+        let pos = (pos.0.clamp(area.x, max_x), pos.1.clamp(area.y, max_y));
+
+        self.windows.get_mut(&id)?.properties.floating = true;
+        self.set_geometry(id, Some(pos), None);
+        Some(pos)
+    }
+
+    /// Stack every visible floating window diagonally from the top-left of
+    /// `area`, bottom of the stacking order first, each `CASCADE_OFFSET`
+    /// further down-right than the last. Windows are shrunk to fit `area`
+    /// within their constraints, and the cascade starts over at the corner
+    /// when the next window would run off it. Returns the number of
+    /// windows arranged.
+    pub fn cascade_windows(&mut self, area: Rectangle) -> usize {
+        let ids: Vec<u64> = self
+            .stacking_order
+            .iter()
+            .copied()
+            .filter(|id| {
+                self.windows
+                    .get(id)
+                    .is_some_and(|w| w.properties.floating && !w.properties.minimized)
+            })
+            .collect();
+
+        let mut step = 0;
+        for &id in &ids {
+            let window = &self.windows[&id];
+            let size = window.properties.constraints.constrain(
+                window.window.size,
+                true,
+                Some((area.width, area.height)),
+            );
+            let mut pos = (area.x + step * CASCADE_OFFSET, area.y + step * CASCADE_OFFSET);
+            if step > 0
+                && (pos.0 + size.0 as i32 > area.x + area.width as i32
+                    || pos.1 + size.1 as i32 > area.y + area.height as i32)
+            {
+                step = 0;
+                pos = (area.x, area.y);
+            }
+            self.set_geometry(id, Some(pos), Some(size));
+            step += 1;
+        }
+        ids.len()
+    }
+
+    /// Centre floating window `id` in `area`, shrinking it within its
+    /// constraints first if it doesn't fit. Tiled and unknown windows are
+    /// left alone. Returns the new position.
+    pub fn center_window(&mut self, id: u64, area: Rectangle) -> Option<(i32, i32)> {
+        let window = self.windows.get(&id).filter(|w| w.properties.floating)?;
+        let (w, h) = window.properties.constraints.constrain(
+            window.window.size,
+            true,
+            Some((area.width, area.height)),
+        );
+        let pos = (
+            area.x + (area.width as i32 - w as i32) / 2,
+            area.y + (area.height as i32 - h as i32) / 2,
+        );
+        self.set_geometry(id, Some(pos), Some((w, h)));
+        Some(pos)
+    }
+
+    /// Arrange every visible floating window into a non-overlapping grid
+    /// covering `area`, `inner_gap` pixels apart and `outer_gap` pixels in
+    /// from its edges. Windows are ordered by ID. Returns the IDs of the
+    /// windows arranged, whose clients need configuring at their new size.
+    pub fn tile_floating_into_grid(
+        &mut self,
+        area: Rectangle,
+        inner_gap: u32,
+        outer_gap: u32,
+    ) -> Vec<u64> {
+        let mut ids: Vec<u64> = self
+            .windows
+            .iter()
+            .filter(|(_, w)| w.properties.floating && !w.properties.minimized)
+            .map(|(k, _)| *k)
+            .collect();
+        if ids.is_empty() {
+            return ids;
+        }
+        ids.sort_unstable();
+
+        let n = ids.len() as u32;
+        let cols = (n as f64).sqrt().ceil() as u32;
+        let rows = n.div_ceil(cols);
+        let cell_w = area.width.saturating_sub(2 * outer_gap + inner_gap * (cols - 1)) / cols;
+        let cell_h = area.height.saturating_sub(2 * outer_gap + inner_gap * (rows - 1)) / rows;
+
+        for (i, id) in ids.iter().enumerate() {
+            let (col, row) = (i as u32 % cols, i as u32 / cols);
+            let x = area.x + (outer_gap + col * (cell_w + inner_gap)) as i32;
+            let y = area.y + (outer_gap + row * (cell_h + inner_gap)) as i32;
+            self.set_geometry(*id, Some((x, y)), Some((cell_w.max(1), cell_h.max(1))));
+        }
+        ids
+    }
+
+    /// Focus a window. Tooltips, notifications and menus are ignored.
+    pub fn focus_window(&mut self, id: u64) {
+        if self.accepts_focus(id) {
+            self.change_focus(Some(id));
+        }
+    }
+
+    /// Whether `id` exists and its type may hold keyboard focus.
+    fn accepts_focus(&self, id: u64) -> bool {
+        self.windows
+            .get(&id)
+            .is_some_and(|w| w.properties.window_type.accepts_focus())
+    }
+
+    /// Change a window's focus role. If the window loses the right to hold
+    /// focus while focused, focus moves to the next focusable window.
+    pub fn set_window_type(&mut self, id: u64, window_type: WindowType) {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return;
+        };
+        window.properties.window_type = window_type;
+        if self.focused_window == Some(id) && !window_type.accepts_focus() {
+            let next = self
+                .get_next_focusable_window(Some(id))
+                .filter(|&next| next != id);
+            self.change_focus(next);
+        }
+    }
+
+    /// Next window after `current` (by ID, wrapping) that can take focus.
+    /// Minimized windows and non-focusable types are skipped.
+    pub fn get_next_focusable_window(&self, current: Option<u64>) -> Option<u64> {
+        let mut ids: Vec<u64> = self
+            .windows
+            .iter()
+            .filter(|(_, w)| w.properties.is_focusable())
+            .map(|(k, _)| *k)
+            .collect();
+        ids.sort_unstable();
+        match current {
+            Some(cur) => ids
+                .iter()
+                .copied()
+                .find(|&k| k > cur)
+                .or_else(|| ids.first().copied()),
+            None => ids.first().copied(),
+        }
+    }
+
+    /// Previous window before `current` (by ID, wrapping) that can take focus.
+    pub fn get_previous_focusable_window(&self, current: Option<u64>) -> Option<u64> {
+        let mut ids: Vec<u64> = self
+            .windows
+            .iter()
+            .filter(|(_, w)| w.properties.is_focusable())
+            .map(|(k, _)| *k)
+            .collect();
+        ids.sort_unstable();
+        match current {
+            Some(cur) => ids
+                .iter()
+                .rev()
+                .copied()
+                .find(|&k| k < cur)
+                .or_else(|| ids.last().copied()),
+            None => ids.last().copied(),
+        }
+    }
+
+    /// Cycle focus forward. Returns the newly focused window, if any.
+    pub fn focus_next_window(&mut self) -> Option<u64> {
+        let next = self.get_next_focusable_window(self.focused_window)?;
+        self.change_focus(Some(next));
+        Some(next)
+    }
+
+    /// Cycle focus backward. Returns the newly focused window, if any.
+    pub fn focus_previous_window(&mut self) -> Option<u64> {
+        let prev = self.get_previous_focusable_window(self.focused_window)?;
+        self.change_focus(Some(prev));
+        Some(prev)
+    }
+
+    /// Set focus to a specific window ID or clear focus entirely.
+    /// Windows whose type cannot hold focus are ignored.
+    pub fn set_focused_window(&mut self, id: Option<u64>) {
+        match id {
+            Some(id) if self.accepts_focus(id) => {
+                self.change_focus(Some(id));
+            }
+            Some(_) => {}
+            None => {
+                self.change_focus(None);
+            }
+        }
+    }
+
+    /// Get the currently focused window id
+    pub fn focused_window_id(&self) -> Option<u64> {
+        self.focused_window
+    }
+
+    /// Set or clear the keyboard focus of `seat`. The default seat's focus
+    /// is the one [`Self::set_focused_window`] sets; every other seat
+    /// focuses independently and doesn't raise the window.
+    pub fn set_seat_focus(&mut self, seat: &str, id: Option<u64>) {
+        if seat == DEFAULT_SEAT {
+            self.set_focused_window(id);
+            return;
+        }
+        match id {
+            Some(id) if self.accepts_focus(id) => {
+                self.seat_focus.insert(seat.to_string(), id);
+            }
+            Some(_) => {}
+            None => {
+                self.seat_focus.remove(seat);
+            }
+        }
+    }
+
+    /// The window `seat` is typing into, if any.
+    pub fn seat_focused_window(&self, seat: &str) -> Option<u64> {
+        if seat == DEFAULT_SEAT {
+            return self.focused_window;
+        }
+        self.seat_focus.get(seat).copied()
+    }
+
+    /// Advance focus transitions by the clock time since the last call.
+    /// Returns `true` while any window is still transitioning.
+    pub fn update_focus_transition(&mut self, effects: &EffectsConfig) -> bool {
+        let now = self.clock.now();
+        let dt = now
+            .duration_since(self.last_transition_update)
+            .as_secs_f32()
+            .min(MAX_DIM_DT_SECS);
+        self.last_transition_update = now;
+        let duration = if effects.reduce_motion {
+            0.0
+        } else {
+            effects.focus_transition_ms as f32 / 1000.0
+        };
+        self.step_focus_transition(&effects.dim_inactive, duration, dt)
+    }
+
+    /// [`Self::step_focus_transition`] with the default transition length.
+    pub fn step_dim(&mut self, config: &DimInactiveConfig, dt: f32) -> bool {
+        self.step_focus_transition(config, DIM_TRANSITION_SECS, dt)
+    }
+
+    /// Move every window's `focus_mix` toward its focus state by `dt`
+    /// seconds of a `duration`-long transition, and its dim along with it.
+    /// Returns `true` while any window has not reached its target.
+    pub fn step_focus_transition(
+        &mut self,
+        dim: &DimInactiveConfig,
+        duration: f32,
+        dt: f32,
+    ) -> bool {
+        let max_step = if duration > 0.0 { dt / duration } else { 1.0 };
+        let focused = self.focused_window;
+        let mut animating = false;
+        for (id, window) in &mut self.windows {
+            let target = if focused == Some(*id) { 1.0 } else { 0.0 };
+            let current = window.properties.focus_mix;
+            let mix = current + (target - current).clamp(-max_step, max_step);
+            window.properties.focus_mix = mix;
+            window.properties.dim = dim.target_dim(false) * (1.0 - mix);
+            if (target - mix).abs() > f32::EPSILON {
+                animating = true;
+            }
+        }
+        animating
+    }
+
+    /// Maximize a window into `work_area` (its output minus reserved
+    /// insets), remembering the current geometry for [`Self::unmaximize_window`].
+    /// Calling it again on a maximized window just re-fits it, e.g. after a
+    /// panel appears. Returns `false` if the window doesn't exist.
+    pub fn maximize_window(&mut self, id: u64, work_area: Rectangle) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        if !window.properties.maximized {
+            window.saved_rect = Some(window.rect());
+            window.properties.maximized = true;
+        }
+        self.set_window_geometry(id, work_area);
+        true
+    }
+
+    /// Leave maximized state, restoring the geometry saved on maximize.
+    /// Returns `false` if the window doesn't exist or wasn't maximized.
+    pub fn unmaximize_window(&mut self, id: u64) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        if !window.properties.maximized {
+            return false;
+        }
+        window.properties.maximized = false;
+        if let Some(rect) = window.saved_rect.take() {
+            self.set_window_geometry(id, rect);
+        }
+        true
+    }
+
+    /// Toggle maximized state. Returns the new state.
+    pub fn toggle_maximize(&mut self, id: u64, work_area: Rectangle) -> bool {
+        let maximized = self
+            .windows
+            .get(&id)
+            .is_some_and(|w| w.properties.maximized);
+        if maximized {
+            self.unmaximize_window(id);
+            false
+        } else {
+            self.maximize_window(id, work_area)
+        }
+    }
+
+    /// Make a window cover `output` entirely, ignoring gaps and reserved
+    /// insets. The previous geometry is kept for [`Self::exit_fullscreen`].
+    /// Returns `false` if the window doesn't exist.
+    pub fn enter_fullscreen(&mut self, id: u64, output: Rectangle) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        if !window.properties.fullscreen {
+            window.fullscreen_restore = Some(window.rect());
+            window.properties.fullscreen = true;
+        }
+        self.set_window_geometry(id, output);
+        true
+    }
+
+    /// Leave fullscreen, returning to the tiled or floating geometry the
+    /// window had before. Returns `false` if it wasn't fullscreen.
+    pub fn exit_fullscreen(&mut self, id: u64) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        if !window.properties.fullscreen {
+            return false;
+        }
+        window.properties.fullscreen = false;
+        if let Some(rect) = window.fullscreen_restore.take() {
+            self.set_window_geometry(id, rect);
+        }
+        true
+    }
+
+    /// Set the scale and rotation window `id` is drawn with. Returns
+    /// `false` if the window doesn't exist.
+    pub fn set_window_transform(&mut self, id: u64, transform: WindowTransform) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        window.properties.transform = transform;
+        true
+    }
+
+    /// Set the colour filter window `id` is drawn through. Returns `false`
+    /// if the window doesn't exist.
+    pub fn set_window_color_filter(&mut self, id: u64, filter: ColorFilter) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        window.properties.color_filter = filter;
+        true
+    }
+
+    /// Move window `id` on to the next colour filter, returning the one now
+    /// applied.
+    pub fn cycle_color_filter(&mut self, id: u64) -> Option<ColorFilter> {
+        let window = self.windows.get_mut(&id)?;
+        window.properties.color_filter = window.properties.color_filter.next();
+        Some(window.properties.color_filter)
+    }
+
+    /// Toggle fullscreen for a window
+    pub fn toggle_fullscreen(&mut self, id: u64) {
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.properties.fullscreen = !window.properties.fullscreen;
+        }
+    }
+
+    /// Toggle the floating state of a window. Floating windows are
+    /// positioned by the user rather than auto-tiled.
+    pub fn toggle_floating(&mut self, id: u64) {
         if let Some(window) = self.windows.get_mut(&id) {
             window.properties.floating = !window.properties.floating;
         }
@@ -302,12 +1705,13 @@ impl WindowManager {
         // If the minimized window was focused, drop focus to a visible
         // sibling if one exists; otherwise leave focus = None.
         if self.focused_window == Some(id) {
-            self.focused_window = self
+            let next = self
                 .windows
                 .iter()
-                .filter(|(_, w)| !w.properties.minimized)
+                .filter(|(_, w)| w.properties.is_focusable())
                 .map(|(k, _)| *k)
                 .max();
+            self.change_focus(next);
         }
         true
     }
@@ -349,11 +1753,162 @@ impl WindowManager {
             .collect()
     }
 
+    /// Send window `id` to the scratchpad: it becomes floating and is
+    /// hidden, leaving the stacking order and giving up focus. Returns
+    /// `false` for unknown windows and ones already hidden there.
+    pub fn move_to_scratchpad(&mut self, id: u64) -> bool {
+        let Some(window) = self.windows.get_mut(&id) else {
+            return false;
+        };
+        if self.scratchpad.contains(&id) && window.properties.minimized {
+            return false;
+        }
+        window.properties.floating = true;
+        if !self.scratchpad.contains(&id) {
+            self.scratchpad.push(id);
+        }
+        self.minimize_window(id);
+        self.stacking_order.retain(|&w| w != id);
+        true
+    }
+
+    /// Show scratchpad window `id` floating in the middle of `area` and
+    /// focus it, or hide it again if it is already showing. Returns whether
+    /// the window is now visible, or `None` if it isn't in the scratchpad.
+    pub fn toggle_scratchpad(&mut self, id: u64, area: Rectangle) -> Option<bool> {
+        if !self.scratchpad.contains(&id) {
+            return None;
+        }
+        if self.restore_window(id) {
+            self.center_window(id, area);
+            self.stacking_order.push(id);
+            self.focus_window(id);
+            Some(true)
+        } else {
+            self.minimize_window(id);
+            self.stacking_order.retain(|&w| w != id);
+            Some(false)
+        }
+    }
+
+    /// Whether `id` lives in the scratchpad, shown or not.
+    pub fn is_in_scratchpad(&self, id: u64) -> bool {
+        self.scratchpad.contains(&id)
+    }
+
+    /// Scratchpad window IDs, oldest first.
+    pub fn scratchpad_ids(&self) -> Vec<u64> {
+        self.scratchpad.clone()
+    }
+
+    /// Apply `op` to window `id`. `work_area` is where a maximized window
+    /// goes and `close_grace` how long a closing window gets before it is
+    /// reported overdue. Operations that change nothing (minimizing a
+    /// minimized window) succeed; an unknown window or an out-of-range
+    /// opacity is an error. `Close` only records the request, sending
+    /// `xdg_toplevel.close` is up to the caller.
+    pub fn execute_operation(
+        &mut self,
+        id: u64,
+        op: &WindowOperation,
+        work_area: Rectangle,
+        close_grace: Duration,
+    ) -> anyhow::Result<()> {
+        if !self.windows.contains_key(&id) {
+            anyhow::bail!("unknown window {}", id);
+        }
+        match *op {
+            WindowOperation::Minimize => {
+                self.minimize_window(id);
+            }
+            WindowOperation::Restore => {
+                self.restore_window(id);
+            }
+            WindowOperation::Maximize => {
+                self.maximize_window(id, work_area);
+            }
+            WindowOperation::Unmaximize => {
+                self.unmaximize_window(id);
+            }
+            WindowOperation::Close => {
+                self.request_close(id, Instant::now(), close_grace);
+            }
+            WindowOperation::SetOpacity { opacity } => {
+                if !(0.0..=1.0).contains(&opacity) {
+                    anyhow::bail!("opacity {} is outside 0.0..=1.0", opacity);
+                }
+                if let Some(window) = self.windows.get_mut(&id) {
+                    window.properties.opacity = opacity;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Drop every managed window. The `WindowManager` itself stays usable;
     /// subsequent calls to [`add_window`](Self::add_window) start mapping
     /// from ID 1 again.
     pub fn shutdown(&mut self) {
         self.windows.clear();
+        self.stacking_order.clear();
+        self.focus_history.clear();
+        self.pending_closes.clear();
+        self.selection.clear();
+        self.scratchpad.clear();
+    }
+}
+
+/// Builds [`WindowManager::get_window_tree`] with every lookup indexed
+/// once up front.
+struct WindowTreeBuilder<'a> {
+    windows: &'a HashMap<u64, AxiomWindow>,
+    /// Windows by transient parent, each list by ID
+    dialogs: HashMap<u64, Vec<u64>>,
+    /// Popups by parent, each list by ID
+    popups: HashMap<PopupParent, Vec<&'a PopupInfo>>,
+    /// Nodes already placed; a client can parent popups in a cycle, and
+    /// windows can be parented in one too
+    placed: HashSet<PopupParent>,
+}
+
+impl WindowTreeBuilder<'_> {
+    fn window(&mut self, id: u64) -> WindowTreeNode {
+        self.placed.insert(PopupParent::Window(id));
+        let window = &self.windows[&id];
+        let mut children = Vec::new();
+        for child in self.dialogs.get(&id).cloned().unwrap_or_default() {
+            if !self.placed.contains(&PopupParent::Window(child)) {
+                children.push(self.window(child));
+            }
+        }
+        children.extend(self.popups_of(PopupParent::Window(id)));
+        let is_dialog =
+            window.parent_id.is_some() || window.properties.window_type == WindowType::Dialog;
+        WindowTreeNode {
+            id,
+            kind: if is_dialog {
+                WindowTreeKind::Dialog
+            } else {
+                WindowTreeKind::Window
+            },
+            geometry: window.rect(),
+            children,
+        }
+    }
+
+    fn popups_of(&mut self, parent: PopupParent) -> Vec<WindowTreeNode> {
+        let mut nodes = Vec::new();
+        for popup in self.popups.get(&parent).cloned().unwrap_or_default() {
+            if self.placed.insert(PopupParent::Popup(popup.id)) {
+                nodes.push(WindowTreeNode {
+                    id: u64::from(popup.id),
+                    kind: WindowTreeKind::Popup,
+                    geometry: popup.geometry.clone(),
+                    children: self.popups_of(PopupParent::Popup(popup.id)),
+                });
+            }
+        }
+        nodes
     }
 }
 
@@ -414,6 +1969,49 @@ mod tests {
         assert!(wm.get_window(id).is_none());
     }
 
+    #[test]
+    fn test_remove_window_cleans_every_tracking_structure() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let a = wm.add_window("a".into());
+        let b = wm.add_window("b".into());
+        let dialog = wm.add_window("dialog".into());
+        wm.set_window_parent(dialog, Some(b));
+        wm.focus_window(b);
+        wm.focus_window(dialog);
+        wm.focus_window(b);
+
+        assert!(wm.remove_window(b).is_some());
+        assert!(wm.get_window(b).is_none());
+        assert!(!wm.get_windows_by_render_order().contains(&b));
+        assert!(!wm.focus_history().contains(&b));
+        assert_eq!(wm.get_window(dialog).unwrap().parent_id, None);
+        // Focus falls back to the most recently focused survivor
+        assert_eq!(wm.focused_window_id(), Some(dialog));
+        assert_eq!(wm.get_windows_by_render_order(), vec![a, dialog]);
+    }
+
+    #[test]
+    fn test_restore_invariants_after_a_half_finished_removal() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let a = wm.add_window("a".into());
+        let b = wm.add_window("b".into());
+        let dialog = wm.add_window("dialog".into());
+        wm.set_window_parent(dialog, Some(b));
+        wm.focus_window(a);
+        wm.focus_window(b);
+        // As if a handler panicked right after dropping the window itself
+        wm.windows.remove(&b);
+        wm.stacking_order.push(a);
+
+        assert!(wm.restore_invariants() > 0);
+        assert_eq!(wm.focused_window_id(), Some(a));
+        assert_eq!(wm.get_window(dialog).unwrap().parent_id, None);
+        assert!(!wm.focus_history().contains(&b));
+        assert_eq!(wm.get_windows_by_render_order(), vec![dialog, a]);
+        // A consistent manager is left alone
+        assert_eq!(wm.restore_invariants(), 0);
+    }
+
     #[test]
     fn test_remove_focused_window_clears_focus() {
         let mut wm = WindowManager::new(&WindowConfig::default());
@@ -520,10 +2118,910 @@ mod tests {
         assert!(!wm.restore_window(id));
     }
 
+    #[test]
+    fn test_window_rule_opacity_one_disables_blur_behind() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let term = wm.add_window("shell".into());
+        let launcher = wm.add_window("launcher".into());
+        let rules = vec![
+            WindowRule {
+                blur: Some(true),
+                opacity: Some(0.85),
+                ..Default::default()
+            },
+            WindowRule {
+                app_id: Some("foot".into()),
+                opacity: Some(1.0),
+                ..Default::default()
+            },
+        ];
+        assert!(wm.apply_window_rules(term, Some("foot"), &rules));
+        assert!(wm.apply_window_rules(launcher, Some("fuzzel"), &rules));
+
+        let term_props = &wm.get_window(term).unwrap().properties;
+        assert_eq!(term_props.opacity, 1.0);
+        assert!(term_props.blur);
+        assert!(!term_props.blur_behind(true));
+        assert!(!term_props.blur_behind(false));
+
+        let launcher_props = &wm.get_window(launcher).unwrap().properties;
+        assert!(launcher_props.blur_behind(true));
+    }
+
+    #[test]
+    fn test_window_rule_inactive_opacity_override() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("editor".into());
+        let rules = vec![WindowRule {
+            title: Some("edit".into()),
+            opacity: Some(0.95),
+            inactive_opacity: Some(0.7),
+            ..Default::default()
+        }];
+        assert!(wm.apply_window_rules(id, None, &rules));
+        let props = &wm.get_window(id).unwrap().properties;
+        assert_eq!(props.effective_opacity(true), 0.95);
+        assert_eq!(props.effective_opacity(false), 0.7);
+
+        let other = wm.add_window("browser".into());
+        assert!(!wm.apply_window_rules(other, None, &rules));
+        assert_eq!(wm.get_window(other).unwrap().properties.opacity, 1.0);
+    }
+
+    #[test]
+    fn test_dim_inactive_multiplier_matches_strength() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let focused = wm.add_window("focused".into());
+        let inactive = wm.add_window("inactive".into());
+        let config = DimInactiveConfig {
+            enabled: true,
+            strength: 0.3,
+        };
+
+        // Half-way through the transition the inactive window is still fading
+        assert!(wm.step_dim(&config, DIM_TRANSITION_SECS / 2.0));
+        let mid = wm.get_window(inactive).unwrap().properties.dim_multiplier();
+        assert!(mid > 0.7 && mid < 1.0);
+
+        assert!(!wm.step_dim(&config, DIM_TRANSITION_SECS));
+        let inactive_mult = wm.get_window(inactive).unwrap().properties.dim_multiplier();
+        assert!((inactive_mult - 0.7).abs() < 1e-6);
+        assert_eq!(
+            wm.get_window(focused).unwrap().properties.dim_multiplier(),
+            1.0
+        );
+
+        // Focus change fades the old focus in and the new focus back up
+        wm.focus_window(inactive);
+        wm.step_dim(&config, DIM_TRANSITION_SECS);
+        assert_eq!(wm.get_window(inactive).unwrap().properties.dim, 0.0);
+        assert!((wm.get_window(focused).unwrap().properties.dim - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_focus_change_fades_old_focus_to_inactive_look() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let a = wm.add_window("a".into());
+        let b = wm.add_window("b".into());
+        wm.get_window_mut(a).unwrap().properties.inactive_opacity = 0.6;
+        let dim = DimInactiveConfig {
+            enabled: true,
+            strength: 0.4,
+        };
+        wm.focus_window(a);
+        wm.step_focus_transition(&dim, 0.2, 1.0);
+        assert_eq!(wm.get_window(a).unwrap().properties.transition_opacity(), 1.0);
+
+        wm.focus_window(b);
+        let mut previous = 1.0;
+        for _ in 0..3 {
+            assert!(wm.step_focus_transition(&dim, 0.2, 0.05));
+            let props = &wm.get_window(a).unwrap().properties;
+            // Strictly between the two looks, and further along each frame
+            assert!(props.focus_mix > 0.0 && props.focus_mix < previous);
+            assert!(props.transition_opacity() > 0.6 && props.transition_opacity() < 1.0);
+            assert!(props.dim > 0.0 && props.dim < 0.4);
+            previous = props.focus_mix;
+        }
+
+        assert!(!wm.step_focus_transition(&dim, 0.2, 0.05));
+        let props = &wm.get_window(a).unwrap().properties;
+        assert!((props.transition_opacity() - 0.6).abs() < 1e-6);
+        assert!((props.dim - 0.4).abs() < 1e-6);
+        assert_eq!(wm.get_window(b).unwrap().properties.focus_mix, 1.0);
+    }
+
+    #[test]
+    fn test_focus_transition_progress_is_the_same_at_60_and_144_hz() {
+        use crate::clock::{Clock, ManualClock};
+        use std::sync::Arc;
+
+        let effects = EffectsConfig {
+            focus_transition_ms: 500,
+            ..EffectsConfig::default()
+        };
+        // Focus mix of the newly focused window at every twelfth of a
+        // second, with frames drawn at `hz`
+        let mixes = |hz: u64| {
+            let clock = Arc::new(ManualClock::new());
+            let mut wm = WindowManager::with_clock(&WindowConfig::default(), clock.clone());
+            let a = wm.add_window("a".into());
+            let b = wm.add_window("b".into());
+            wm.focus_window(a);
+            wm.step_focus_transition(&effects.dim_inactive, 0.0, 0.0);
+            wm.focus_window(b);
+            let start = clock.now();
+            let mut mixes = Vec::new();
+            for frame in 1..=hz {
+                let at = start + Duration::from_nanos(frame * 1_000_000_000 / hz);
+                clock.advance(at - clock.now());
+                wm.update_focus_transition(&effects);
+                if frame % (hz / 12) == 0 {
+                    mixes.push(wm.get_window(b).unwrap().properties.focus_mix);
+                }
+            }
+            mixes
+        };
+        let (at_60, at_144) = (mixes(60), mixes(144));
+        for (a, b) in at_60.iter().zip(&at_144) {
+            assert!((a - b).abs() < 1e-4, "{a} at 60 Hz, {b} at 144 Hz");
+        }
+        // A sixth of the way through the half-second transition after
+        // 1/12 s, and done soon after the half second
+        assert!((at_60[0] - 1.0 / 6.0).abs() < 1e-4);
+        assert_eq!(at_144[6], 1.0);
+    }
+
+    #[test]
+    fn test_reduce_motion_switches_focus_look_instantly() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let a = wm.add_window("a".into());
+        let b = wm.add_window("b".into());
+        let effects = EffectsConfig {
+            dim_inactive: DimInactiveConfig {
+                enabled: true,
+                strength: 0.4,
+            },
+            focus_transition_ms: 5000,
+            reduce_motion: true,
+        };
+        wm.focus_window(a);
+        wm.update_focus_transition(&effects);
+        wm.focus_window(b);
+
+        // One frame lands both windows on their new look
+        assert!(!wm.update_focus_transition(&effects));
+        assert_eq!(wm.get_window(a).unwrap().properties.focus_mix, 0.0);
+        assert!((wm.get_window(a).unwrap().properties.dim - 0.4).abs() < 1e-6);
+        assert_eq!(wm.get_window(b).unwrap().properties.focus_mix, 1.0);
+    }
+
+    #[test]
+    fn test_dim_inactive_disabled_keeps_full_brightness() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let _ = wm.add_window("a".into());
+        let b = wm.add_window("b".into());
+        assert!(!wm.step_dim(&DimInactiveConfig::default(), 1.0));
+        assert_eq!(wm.get_window(b).unwrap().properties.dim, 0.0);
+    }
+
+    #[test]
+    fn test_notification_never_next_focus_target() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let editor = wm.add_window("editor".into());
+        let toast = wm.add_window("toast".into());
+        let browser = wm.add_window("browser".into());
+        wm.set_window_type(toast, WindowType::Notification);
+
+        assert_eq!(wm.get_next_focusable_window(Some(editor)), Some(browser));
+        assert_eq!(wm.get_next_focusable_window(Some(browser)), Some(editor));
+        assert_eq!(wm.get_previous_focusable_window(Some(browser)), Some(editor));
+
+        // Cycling never lands on the notification
+        for _ in 0..4 {
+            assert_ne!(wm.focus_next_window(), Some(toast));
+        }
+
+        // Direct focus requests are refused too
+        wm.focus_window(toast);
+        assert_ne!(wm.focused_window_id(), Some(toast));
+        wm.set_focused_window(Some(toast));
+        assert_ne!(wm.focused_window_id(), Some(toast));
+    }
+
+    #[test]
+    fn test_set_window_type_moves_focus_off_menu() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let a = wm.add_window("a".into());
+        let b = wm.add_window("menu".into());
+        wm.focus_window(b);
+        wm.set_window_type(b, WindowType::Menu);
+        assert_eq!(wm.focused_window_id(), Some(a));
+
+        // Removing the focused window never hands focus to a tooltip
+        let tip = wm.add_window("tip".into());
+        wm.set_window_type(tip, WindowType::Tooltip);
+        let _ = wm.remove_window(a);
+        assert_eq!(wm.focused_window_id(), None);
+    }
+
+    #[test]
+    fn test_resize_window_applies_constraints() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("constrained".into());
+        wm.set_window_constraints(
+            id,
+            WindowConstraints {
+                min_size: Some((300, 200)),
+                max_size: Some((1200, 900)),
+                aspect_ratio: None,
+            },
+        );
+        assert_eq!(wm.resize_window(id, 100, 100), Some((300, 200)));
+        assert_eq!(wm.resize_window(id, 5000, 5000), Some((1200, 900)));
+        assert_eq!(wm.get_window(id).unwrap().window.size, (1200, 900));
+    }
+
+    #[test]
+    fn test_constraints_keep_aspect_within_limit() {
+        let c = WindowConstraints {
+            aspect_ratio: Some(16.0 / 9.0),
+            ..Default::default()
+        };
+        assert_eq!(c.constrain((1600, 100), true, None), (1600, 900));
+        assert_eq!(c.constrain((100, 450), false, None), (800, 450));
+        // Limited to 1000x500: height caps first, width follows the ratio
+        assert_eq!(c.constrain((1600, 900), true, Some((1000, 500))), (889, 500));
+    }
+
+    #[test]
+    fn test_constraints_from_size_hints() {
+        let none = WindowConstraints::from_size_hints((0, 0), (0, 0));
+        assert_eq!(none, WindowConstraints::default());
+
+        let min_only = WindowConstraints::from_size_hints((400, 0), (0, 0));
+        assert_eq!(min_only.min_size, Some((400, 1)));
+        assert_eq!(min_only.max_size, None);
+
+        let bounded = WindowConstraints::from_size_hints((200, 100), (0, 600));
+        assert_eq!(bounded.max_size, Some((u32::MAX, 600)));
+        assert_eq!(bounded.aspect_ratio, None);
+
+        // Limits sharing a ratio are just limits
+        let proportional = WindowConstraints::from_size_hints((400, 300), (800, 600));
+        assert_eq!(proportional.aspect_ratio, None);
+        assert_eq!(proportional.fixed_size(), None);
+        assert_eq!(proportional.constrain((700, 350), true, None), (700, 350));
+
+        let fixed = WindowConstraints::from_size_hints((500, 400), (500, 400));
+        assert_eq!(fixed.fixed_size(), Some((500, 400)));
+        assert_eq!(fixed.aspect_ratio, None);
+    }
+
+    #[test]
+    fn test_fixed_size_hints_make_window_floating() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("splash".into());
+        let fixed = WindowConstraints::from_size_hints((500, 400), (500, 400));
+
+        assert!(wm.apply_size_hints(id, fixed));
+        let w = wm.get_window(id).unwrap();
+        assert!(w.properties.floating);
+        assert_eq!(w.window.size, (500, 400));
+        // Re-applying the same hints is not a new transition
+        assert!(!wm.apply_size_hints(id, fixed));
+
+        let resizable = wm.add_window("editor".into());
+        let hints = WindowConstraints::from_size_hints((300, 200), (0, 0));
+        assert!(!wm.apply_size_hints(resizable, hints));
+        assert!(!wm.get_window(resizable).unwrap().properties.floating);
+    }
+
+    #[test]
+    fn test_move_window_emits_moved_event() {
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        wm.add_window_listener(Box::new(move |e| sink.lock().push(e)));
+
+        let id = wm.add_window("observed".into());
+        assert!(wm.move_window(id, 120, -40));
+        // Moving to the same spot is not a change
+        assert!(wm.move_window(id, 120, -40));
+        assert_eq!(wm.resize_window(id, 640, 480), Some((640, 480)));
+        wm.set_window_title(id, "renamed".into());
+        wm.remove_window(id);
+
+        assert_eq!(
+            *events.lock(),
+            vec![
+                WindowEvent::Created { id },
+                WindowEvent::Moved { id, x: 120, y: -40 },
+                WindowEvent::Resized {
+                    id,
+                    width: 640,
+                    height: 480
+                },
+                WindowEvent::TitleChanged {
+                    id,
+                    title: "renamed".into()
+                },
+                WindowEvent::Removed { id },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_removed_focus_listener_stops_firing() {
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let a = wm.add_window("a".into());
+        let b = wm.add_window("b".into());
+
+        let first = Arc::new(Mutex::new(Vec::new()));
+        let second = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&first);
+        let first_id = wm.add_focus_listener(Box::new(move |f| sink.lock().push(f)));
+        let sink = Arc::clone(&second);
+        let second_id = wm.add_focus_listener(Box::new(move |f| sink.lock().push(f)));
+        assert_ne!(first_id, second_id);
+
+        wm.focus_window(b);
+        assert!(wm.remove_focus_listener(first_id));
+        assert!(!wm.remove_focus_listener(first_id));
+        wm.focus_window(a);
+        // Re-focusing the same window is not a change
+        wm.focus_window(a);
+
+        assert_eq!(*first.lock(), vec![Some(b)]);
+        assert_eq!(*second.lock(), vec![Some(b), Some(a)]);
+    }
+
+    #[test]
+    fn test_maximize_fills_work_area_and_restore_returns_saved_rect() {
+        // 1920x1080 output with a 32px top bar and a 48px left dock
+        let work_area = Rectangle::from_loc_and_size((48, 32), (1872, 1048));
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("app".into());
+        wm.set_window_geometry(id, Rectangle::from_loc_and_size((210, 140), (733, 511)));
+
+        assert!(wm.maximize_window(id, work_area.clone()));
+        let w = wm.get_window(id).unwrap();
+        assert!(w.properties.maximized);
+        assert_eq!(w.rect(), work_area);
+
+        assert!(wm.unmaximize_window(id));
+        let w = wm.get_window(id).unwrap();
+        assert!(!w.properties.maximized);
+        assert_eq!(w.rect(), Rectangle::from_loc_and_size((210, 140), (733, 511)));
+        assert!(!wm.unmaximize_window(id));
+    }
+
+    #[test]
+    fn test_fullscreen_covers_output_and_restores_geometry() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("game".into());
+        let floating = Rectangle::from_loc_and_size((300, 200), (640, 480));
+        wm.set_window_geometry(id, floating.clone());
+
+        // Second output to the right of a 1920px one
+        let output = Rectangle::from_loc_and_size((1920, 0), (2560, 1440));
+        assert!(wm.enter_fullscreen(id, output.clone()));
+        assert_eq!(wm.get_window(id).unwrap().rect(), output);
+        // Re-entering keeps the original restore point
+        assert!(wm.enter_fullscreen(id, output));
+
+        assert!(wm.exit_fullscreen(id));
+        let w = wm.get_window(id).unwrap();
+        assert!(!w.properties.fullscreen);
+        assert_eq!(w.rect(), floating);
+        assert!(!wm.exit_fullscreen(id));
+    }
+
+    fn output_area() -> Rectangle {
+        Rectangle::from_loc_and_size((0, 0), (1920, 1080))
+    }
+
+    #[test]
+    fn test_place_floating_window_centers_over_parent() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let parent = wm.add_window("main".into());
+        wm.get_window_mut(parent).unwrap().window.set_position(100, 200);
+        wm.get_window_mut(parent).unwrap().window.set_size(1000, 800);
+        let dialog = wm.add_window("dialog".into());
+        wm.get_window_mut(dialog).unwrap().window.set_size(400, 300);
+        wm.set_window_parent(dialog, Some(parent));
+
+        let pos = wm.place_floating_window(dialog, output_area());
+        assert_eq!(pos, Some((100 + 300, 200 + 250)));
+        assert!(wm.get_window(dialog).unwrap().properties.floating);
+    }
+
+    #[test]
+    fn test_window_tree_nests_dialogs_popups_and_sub_popups() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let parent = wm.add_window("main".into());
+        let other = wm.add_window("other".into());
+        let dialog = wm.add_window("dialog".into());
+        wm.set_window_parent(dialog, Some(parent));
+        let menu = Rectangle::from_loc_and_size((10, 20), (200, 100));
+        let popup = |id, parent| PopupInfo {
+            id,
+            parent,
+            geometry: menu.clone(),
+        };
+        // Two menus on the main window, a submenu off the second, and a
+        // popup whose parent is already gone
+        let popups = [
+            popup(31, PopupParent::Popup(22)),
+            popup(22, PopupParent::Window(parent)),
+            popup(21, PopupParent::Window(parent)),
+            popup(40, PopupParent::Popup(99)),
+        ];
+
+        let tree = wm.get_window_tree(&popups);
+        let shape = |node: &WindowTreeNode| (node.id, node.kind, node.children.len());
+        assert_eq!(tree.len(), 2);
+        assert_eq!(shape(&tree[0]), (parent, WindowTreeKind::Window, 3));
+        assert_eq!(shape(&tree[1]), (other, WindowTreeKind::Window, 0));
+        let children: Vec<_> = tree[0].children.iter().map(shape).collect();
+        assert_eq!(
+            children,
+            vec![
+                (dialog, WindowTreeKind::Dialog, 0),
+                (21, WindowTreeKind::Popup, 0),
+                (22, WindowTreeKind::Popup, 1),
+            ]
+        );
+        let submenu = &tree[0].children[2].children[0];
+        assert_eq!(shape(submenu), (31, WindowTreeKind::Popup, 0));
+        assert_eq!(submenu.geometry, menu);
+        assert_eq!(tree[0].geometry, wm.get_window(parent).unwrap().rect());
+    }
+
+    #[test]
+    fn test_place_floating_window_cascades_successive_dialogs() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let mut positions = Vec::new();
+        for i in 0..3 {
+            let id = wm.add_window(format!("dialog {}", i));
+            wm.get_window_mut(id).unwrap().window.set_size(400, 300);
+            positions.push(wm.place_floating_window(id, output_area()).unwrap());
+        }
+        let (cx, cy) = ((1920 - 400) / 2, (1080 - 300) / 2);
+        assert_eq!(
+            positions,
+            vec![
+                (cx, cy),
+                (cx + CASCADE_OFFSET, cy + CASCADE_OFFSET),
+                (cx + 2 * CASCADE_OFFSET, cy + 2 * CASCADE_OFFSET),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cascade_windows_steps_diagonally_from_the_corner() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let area = Rectangle::from_loc_and_size((0, 40), (1920, 1040));
+        let ids: Vec<u64> = (0..4)
+            .map(|i| {
+                let id = wm.add_window(format!("float {}", i));
+                wm.get_window_mut(id).unwrap().window.set_size(600, 400);
+                let _ = wm.place_floating_window(id, output_area());
+                id
+            })
+            .collect();
+        let _ = wm.add_window("tiled".into());
+
+        assert_eq!(wm.cascade_windows(area), 4);
+        let positions: Vec<(i32, i32)> = ids
+            .iter()
+            .map(|&id| wm.get_window(id).unwrap().window.position)
+            .collect();
+        assert_eq!(positions[0], (0, 40));
+        for pair in positions.windows(2) {
+            assert!(pair[1].0 > pair[0].0 && pair[1].1 > pair[0].1);
+            assert_eq!(pair[1].0 - pair[0].0, CASCADE_OFFSET);
+        }
+    }
+
+    #[test]
+    fn test_two_seats_focus_independently() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let editor = wm.add_window("editor".into());
+        let kiosk = wm.add_window("kiosk".into());
+
+        wm.set_seat_focus(DEFAULT_SEAT, Some(editor));
+        wm.set_seat_focus("seat1", Some(kiosk));
+        assert_eq!(wm.focused_window_id(), Some(editor));
+        assert_eq!(wm.seat_focused_window(DEFAULT_SEAT), Some(editor));
+        assert_eq!(wm.seat_focused_window("seat1"), Some(kiosk));
+
+        // Moving one seat's focus leaves the other where it was
+        wm.set_seat_focus(DEFAULT_SEAT, Some(kiosk));
+        assert_eq!(wm.seat_focused_window("seat1"), Some(kiosk));
+        wm.set_seat_focus("seat1", Some(editor));
+        assert_eq!(wm.seat_focused_window(DEFAULT_SEAT), Some(kiosk));
+
+        // Closing a window drops it from every seat that focused it
+        wm.remove_window(editor);
+        assert_eq!(wm.seat_focused_window("seat1"), None);
+        assert_eq!(wm.seat_focused_window(DEFAULT_SEAT), Some(kiosk));
+        assert_eq!(wm.seat_focused_window("seat2"), None);
+    }
+
+    #[test]
+    fn test_color_filter_applies_to_one_window() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let reader = wm.add_window("pdf".into());
+        let other = wm.add_window("terminal".into());
+
+        assert!(wm.set_window_color_filter(reader, ColorFilter::Invert));
+        assert_eq!(
+            wm.get_window(reader).unwrap().properties.color_filter,
+            ColorFilter::Invert
+        );
+        assert_eq!(
+            wm.get_window(other).unwrap().properties.color_filter,
+            ColorFilter::None
+        );
+        assert!(!wm.set_window_color_filter(999, ColorFilter::Grayscale));
+
+        // The keybind cycles Invert -> Grayscale -> None
+        assert_eq!(wm.cycle_color_filter(reader), Some(ColorFilter::Grayscale));
+        assert_eq!(wm.cycle_color_filter(reader), Some(ColorFilter::None));
+        assert_eq!(wm.cycle_color_filter(999), None);
+    }
+
+    #[test]
+    fn test_center_window_uses_area_midpoint_within_constraints() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        // A 30px panel along the top is reserved
+        let area = Rectangle::from_loc_and_size((0, 30), (1920, 1050));
+        let id = wm.add_window("dialog".into());
+        wm.get_window_mut(id).unwrap().window.set_size(400, 300);
+        let _ = wm.place_floating_window(id, output_area());
+
+        assert_eq!(wm.center_window(id, area), Some((760, 405)));
+
+        // Too big for the area: shrunk to fit, even past its minimum height
+        wm.set_window_constraints(
+            id,
+            WindowConstraints {
+                min_size: Some((800, 1200)),
+                ..Default::default()
+            },
+        );
+        wm.get_window_mut(id).unwrap().window.set_size(2500, 1500);
+        assert_eq!(wm.center_window(id, area), Some((0, 30)));
+        assert_eq!(wm.get_window(id).unwrap().window.size, (1920, 1050));
+
+        let tiled = wm.add_window("tiled".into());
+        assert_eq!(wm.center_window(tiled, area), None);
+    }
+
+    #[test]
+    fn test_window_transform_scales_and_rotates_around_center() {
+        let rect = Rectangle::from_loc_and_size((100, 50), (400, 200));
+        let identity = WindowTransform::default();
+        assert!(identity.is_identity());
+        assert_eq!(
+            identity.quad(&rect),
+            [(100.0, 50.0), (500.0, 50.0), (500.0, 250.0), (100.0, 250.0)]
+        );
+
+        // Half size, still centred on (300, 150)
+        let half = WindowTransform {
+            scale: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            half.quad(&rect),
+            [(200.0, 100.0), (400.0, 100.0), (400.0, 200.0), (200.0, 200.0)]
+        );
+
+        // A quarter turn swaps the extents around the same centre
+        let turned = WindowTransform {
+            rotation: std::f64::consts::FRAC_PI_2,
+            ..Default::default()
+        };
+        let quad = turned.quad(&rect);
+        assert!((quad[0].0 - 400.0).abs() < 1e-9 && (quad[0].1 - (-50.0)).abs() < 1e-9);
+        assert!((quad[2].0 - 200.0).abs() < 1e-9 && (quad[2].1 - 350.0).abs() < 1e-9);
+
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("thumb".into());
+        assert!(wm.set_window_transform(id, half));
+        assert_eq!(wm.get_window(id).unwrap().properties.transform, half);
+        assert!(!wm.set_window_transform(99, half));
+    }
+
+    #[test]
+    fn test_scratchpad_hides_window_until_toggled() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let area = Rectangle::from_loc_and_size((0, 30), (1920, 1050));
+        let term = wm.add_window("term".into());
+        let editor = wm.add_window("editor".into());
+        wm.get_window_mut(term).unwrap().window.set_size(800, 600);
+        wm.focus_window(term);
+
+        assert!(wm.move_to_scratchpad(term));
+        assert!(!wm.move_to_scratchpad(term));
+        assert!(wm.is_in_scratchpad(term));
+        assert!(wm.get_window(term).unwrap().properties.floating);
+        assert_eq!(wm.focused_window_id(), Some(editor));
+        assert!(!wm.get_windows_by_render_order().contains(&term));
+        assert_eq!(wm.window_at((960.0, 555.0), 0.0, &HashMap::new()), None);
+
+        // Shown: floating in the middle of the area, on top and focused
+        assert_eq!(wm.toggle_scratchpad(term, area.clone()), Some(true));
+        assert_eq!(wm.get_window(term).unwrap().window.position, (560, 255));
+        assert_eq!(wm.get_windows_by_render_order().last(), Some(&term));
+        assert_eq!(wm.focused_window_id(), Some(term));
+        assert_eq!(wm.window_at((960.0, 555.0), 0.0, &HashMap::new()), Some(term));
+
+        // Toggling again hides it without leaving the scratchpad
+        assert_eq!(wm.toggle_scratchpad(term, area.clone()), Some(false));
+        assert!(wm.is_minimized(term));
+        assert_eq!(wm.focused_window_id(), Some(editor));
+        assert_eq!(wm.scratchpad_ids(), vec![term]);
+
+        assert_eq!(wm.toggle_scratchpad(editor, area), None);
+        wm.remove_window(term);
+        assert!(wm.scratchpad_ids().is_empty());
+    }
+
+    #[test]
+    fn test_tile_floating_into_grid_has_no_overlap() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let tiled = wm.add_window("tiled".into());
+        for i in 0..5 {
+            let id = wm.add_window(format!("float {}", i));
+            let _ = wm.place_floating_window(id, output_area());
+        }
+        assert_eq!(wm.tile_floating_into_grid(output_area(), 10, 10).len(), 5);
+
+        let mut rects = Vec::new();
+        wm.for_each_window(|id, w| {
+            if id != tiled {
+                rects.push(w.rect());
+            }
+        });
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a.x >= 0 && a.x + a.width as i32 <= 1920);
+            for b in &rects[i + 1..] {
+                assert!(!a.intersects(b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_drag_snaps_to_output_edge_within_threshold() {
+        let dragged = Rectangle::from_loc_and_size((7, 300), (400, 300));
+        assert_eq!(dragged.snapped_position(&output_area(), &[], 10), (0, 300));
+
+        // Right and bottom edges snap too
+        let dragged = Rectangle::from_loc_and_size((1512, 774), (400, 300));
+        assert_eq!(dragged.snapped_position(&output_area(), &[], 10), (1520, 780));
+
+        // Outside the threshold nothing moves
+        let dragged = Rectangle::from_loc_and_size((25, 300), (400, 300));
+        assert_eq!(dragged.snapped_position(&output_area(), &[], 10), (25, 300));
+    }
+
+    #[test]
+    fn test_drag_snaps_to_adjacent_window_border() {
+        let neighbour = Rectangle::from_loc_and_size((500, 200), (600, 400));
+        // Left edge 4px right of the neighbour's right border
+        let dragged = Rectangle::from_loc_and_size((1104, 250), (300, 200));
+        assert_eq!(
+            dragged.snapped_position(&output_area(), &[neighbour.clone()], 10),
+            (1100, 250)
+        );
+
+        // Same horizontal gap, but nowhere near it vertically: no snap
+        let below = Rectangle::from_loc_and_size((1104, 700), (300, 200));
+        assert_eq!(
+            below.snapped_position(&output_area(), &[neighbour], 10),
+            (1104, 700)
+        );
+    }
+
+    #[test]
+    fn test_selection_keeps_order_and_drops_removed_windows() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let a = wm.add_window("a".into());
+        let b = wm.add_window("b".into());
+        let c = wm.add_window("c".into());
+
+        assert!(wm.add_to_selection(c));
+        assert!(wm.add_to_selection(a));
+        assert!(!wm.add_to_selection(a));
+        assert!(!wm.add_to_selection(999));
+        assert!(wm.toggle_selection(b));
+        assert_eq!(wm.selected(), &[c, a, b]);
+
+        assert!(!wm.toggle_selection(a));
+        wm.remove_window(c);
+        assert_eq!(wm.selected(), &[b]);
+        wm.clear_selection();
+        assert!(wm.selected().is_empty());
+    }
+
+    #[test]
+    fn test_close_request_cleared_when_client_exits_in_time() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("editor".into());
+        let start = Instant::now();
+        assert!(wm.request_close(id, start, Duration::from_secs(3)));
+        assert!(wm.is_closing(id));
+
+        // The client honours xdg_toplevel.close and its window goes away
+        wm.remove_window(id);
+        assert!(!wm.is_closing(id));
+        assert!(wm.overdue_closes(start + Duration::from_secs(10)).is_empty());
+        assert!(!wm.request_close(id, start, Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_ignored_close_request_becomes_overdue_after_grace() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("stuck".into());
+        let start = Instant::now();
+        wm.request_close(id, start, Duration::from_secs(3));
+        // A second request must not push the deadline back
+        wm.request_close(id, start + Duration::from_secs(2), Duration::from_secs(3));
+
+        assert!(wm.overdue_closes(start + Duration::from_millis(2999)).is_empty());
+        assert_eq!(wm.overdue_closes(start + Duration::from_secs(3)), vec![id]);
+        // Reported once only
+        assert!(wm.overdue_closes(start + Duration::from_secs(4)).is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_focused_skips_focused_and_closing_windows() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let first = wm.add_window("first".into());
+        let second = wm.add_window("second".into());
+        let third = wm.add_window("third".into());
+        // `first` got focus on creation; the others never had it
+        assert_eq!(wm.least_recently_focused(), Some(second));
+
+        wm.focus_window(second);
+        wm.focus_window(third);
+        wm.focus_window(first);
+        assert_eq!(wm.least_recently_focused(), Some(second));
+
+        wm.request_close(second, Instant::now(), Duration::from_secs(3));
+        assert_eq!(wm.open_window_count(), 2);
+        assert_eq!(wm.least_recently_focused(), Some(third));
+
+        wm.remove_window(third);
+        // Only the focused window is left to close
+        assert_eq!(wm.least_recently_focused(), None);
+    }
+
+    /// Two 400x300 floating windows, the second overlapping the first's
+    /// bottom-right corner and stacked above it.
+    fn overlapping_floating_pair() -> (WindowManager, u64, u64) {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let lower = wm.add_window("lower".into());
+        let upper = wm.add_window("upper".into());
+        for (id, pos) in [(lower, (100, 100)), (upper, (300, 250))] {
+            wm.toggle_floating(id);
+            wm.set_window_geometry(id, Rectangle::from_loc_and_size(pos, (400, 300)));
+        }
+        (wm, lower, upper)
+    }
+
+    #[test]
+    fn test_window_at_hits_visible_area() {
+        let (wm, lower, upper) = overlapping_floating_pair();
+        let none = HashMap::new();
+        assert_eq!(wm.window_at((150.0, 150.0), 8.0, &none), Some(lower));
+        assert_eq!(wm.window_at((650.0, 500.0), 8.0, &none), Some(upper));
+        assert_eq!(wm.window_at((50.0, 50.0), 8.0, &none), None);
+    }
+
+    #[test]
+    fn test_window_at_rounded_corner_falls_through() {
+        let (wm, lower, upper) = overlapping_floating_pair();
+        let none = HashMap::new();
+        // The upper window's top-left corner is cut away, exposing `lower`
+        assert_eq!(wm.window_at((301.0, 251.0), 16.0, &none), Some(lower));
+        assert_eq!(wm.window_at((301.0, 251.0), 0.0, &none), Some(upper));
+        // Just inside the curve still hits the upper window
+        assert_eq!(wm.window_at((310.0, 260.0), 16.0, &none), Some(upper));
+        // Nothing below the lower window's own corner
+        assert_eq!(wm.window_at((100.5, 100.5), 16.0, &none), None);
+    }
+
+    #[test]
+    fn test_window_at_prefers_topmost_of_overlapping_windows() {
+        let (mut wm, lower, upper) = overlapping_floating_pair();
+        let none = HashMap::new();
+        assert_eq!(wm.window_at((400.0, 300.0), 8.0, &none), Some(upper));
+        wm.focus_window(lower);
+        assert_eq!(wm.window_at((400.0, 300.0), 8.0, &none), Some(lower));
+
+        // Tiled windows are hit only where the current layout puts them
+        let tiled = wm.add_window("tiled".into());
+        assert_eq!(wm.window_at((1000.0, 800.0), 8.0, &none), None);
+        let layouts =
+            HashMap::from([(tiled, Rectangle::from_loc_and_size((900, 700), (400, 300)))]);
+        assert_eq!(wm.window_at((1000.0, 800.0), 8.0, &layouts), Some(tiled));
+    }
+
+    #[test]
+    fn test_fullscreen_window_paints_and_hits_above_stacking_order() {
+        let (mut wm, lower, upper) = overlapping_floating_pair();
+        let none = HashMap::new();
+        wm.toggle_fullscreen(lower);
+        wm.focus_window(upper);
+        assert_eq!(wm.get_windows_by_render_order(), vec![upper, lower]);
+        // Inside both: the fullscreen window is painted over the floating one
+        assert_eq!(wm.window_at((400.0, 300.0), 0.0, &none), Some(lower));
+
+        wm.minimize_window(lower);
+        assert_eq!(wm.visible_rect(lower, &none), None);
+        assert_eq!(wm.window_at((400.0, 300.0), 0.0, &none), Some(upper));
+    }
+
     #[test]
     fn test_shutdown_clears_windows() {
         let mut wm = WindowManager::new(&WindowConfig::default());
         let _ = wm.add_window("test".into());
         wm.shutdown();
     }
+
+    #[test]
+    fn test_execute_operation_applies_ops_and_rejects_unknown_windows() {
+        let mut wm = WindowManager::new(&WindowConfig::default());
+        let id = wm.add_window("target".into());
+        let grace = Duration::from_secs(1);
+
+        wm.execute_operation(id, &WindowOperation::Minimize, output_area(), grace)
+            .unwrap();
+        assert!(wm.is_minimized(id));
+        wm.execute_operation(id, &WindowOperation::Restore, output_area(), grace)
+            .unwrap();
+        assert!(!wm.is_minimized(id));
+
+        wm.execute_operation(id, &WindowOperation::Maximize, output_area(), grace)
+            .unwrap();
+        assert!(wm.get_window(id).unwrap().properties.maximized);
+        assert_eq!(wm.get_window(id).unwrap().rect(), output_area());
+
+        let half = WindowOperation::SetOpacity { opacity: 0.5 };
+        wm.execute_operation(id, &half, output_area(), grace)
+            .unwrap();
+        assert_eq!(wm.get_window(id).unwrap().properties.opacity, 0.5);
+        let too_bright = WindowOperation::SetOpacity { opacity: 1.5 };
+        assert!(wm
+            .execute_operation(id, &too_bright, output_area(), grace)
+            .is_err());
+        assert_eq!(wm.get_window(id).unwrap().properties.opacity, 0.5);
+
+        wm.execute_operation(id, &WindowOperation::Close, output_area(), grace)
+            .unwrap();
+        assert!(wm.is_closing(id));
+
+        assert!(wm
+            .execute_operation(99, &WindowOperation::Minimize, output_area(), grace)
+            .is_err());
+    }
+
+    #[test]
+    fn test_window_operation_wire_format() {
+        let op: WindowOperation = serde_json::from_str(r#"{"kind":"minimize"}"#).unwrap();
+        assert_eq!(op, WindowOperation::Minimize);
+        let op: WindowOperation =
+            serde_json::from_str(r#"{"kind":"set_opacity","opacity":0.25}"#).unwrap();
+        assert_eq!(op, WindowOperation::SetOpacity { opacity: 0.25 });
+    }
 }
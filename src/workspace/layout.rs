@@ -0,0 +1,245 @@
+//! Column tiling algorithms.
+//!
+//! Every window sharing a workspace column is placed by a [`LayoutEngine`],
+//! looked up by name in a [`LayoutRegistry`]. The built-in engines are
+//! registered under the names `window.default_layout` and `[[outputs]]`
+//! rules accept; code embedding the compositor can register more through
+//! [`super::ScrollableWorkspaces::register_layout`] and select them the
+//! same way.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::window::Rectangle;
+
+/// Layout used when none is configured, or the configured one is unknown.
+pub const DEFAULT_LAYOUT: &str = "horizontal";
+
+/// A tiling algorithm for the windows sharing a workspace column.
+pub trait LayoutEngine: Send + Sync {
+    /// Place `windows`, in column order, inside `bounds`: `inner_gap`
+    /// pixels between neighbours and `outer_gap` in from the edges.
+    /// Windows missing from the result are not shown.
+    fn arrange(
+        &self,
+        windows: &[u64],
+        bounds: &Rectangle,
+        inner_gap: u32,
+        outer_gap: u32,
+    ) -> Vec<(u64, Rectangle)>;
+}
+
+/// A built-in engine: one of the `tile_*` functions below.
+struct Tiler(fn(&Rectangle, usize, u32, u32) -> Vec<Rectangle>);
+
+impl LayoutEngine for Tiler {
+    fn arrange(
+        &self,
+        windows: &[u64],
+        bounds: &Rectangle,
+        inner_gap: u32,
+        outer_gap: u32,
+    ) -> Vec<(u64, Rectangle)> {
+        windows
+            .iter()
+            .copied()
+            .zip((self.0)(bounds, windows.len(), inner_gap, outer_gap))
+            .collect()
+    }
+}
+
+/// Stands in if the default layout has been replaced by nothing usable.
+static FALLBACK: Tiler = Tiler(tile_column);
+
+/// Layout engines by name.
+#[derive(Clone)]
+pub struct LayoutRegistry {
+    engines: HashMap<String, Arc<dyn LayoutEngine>>,
+}
+
+impl Default for LayoutRegistry {
+    /// A registry holding the built-in layouts.
+    fn default() -> Self {
+        let mut registry = Self {
+            engines: HashMap::new(),
+        };
+        for (name, tile) in [
+            (DEFAULT_LAYOUT, tile_column as fn(&Rectangle, usize, u32, u32) -> Vec<Rectangle>),
+            ("vertical", tile_row),
+            ("master_stack", tile_master_stack),
+            ("grid", tile_grid),
+            ("spiral", tile_spiral),
+        ] {
+            registry.register(name, Arc::new(Tiler(tile)));
+        }
+        registry
+    }
+}
+
+impl fmt::Debug for LayoutRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl LayoutRegistry {
+    /// Register `engine` as `name`, replacing and returning any engine
+    /// already registered under that name.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        engine: Arc<dyn LayoutEngine>,
+    ) -> Option<Arc<dyn LayoutEngine>> {
+        self.engines.insert(name.into(), engine)
+    }
+
+    /// Whether an engine is registered as `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.engines.contains_key(name)
+    }
+
+    /// The engine registered as `name`, falling back to [`DEFAULT_LAYOUT`].
+    pub fn get_or_default(&self, name: &str) -> &dyn LayoutEngine {
+        self.engines
+            .get(name)
+            .or_else(|| self.engines.get(DEFAULT_LAYOUT))
+            .map_or(&FALLBACK as &dyn LayoutEngine, |engine| engine.as_ref())
+    }
+
+    /// Registered layout names, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.engines.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+/// Split `bounds` into `count` stacked tiles, `inner_gap` apart from each
+/// other and `outer_gap` in from the column's edges.
+///
+/// When the configured gaps would leave less than a pixel per tile (many
+/// windows in a short column) they shrink in proportion, and the side gaps
+/// give way in a very narrow column, so every tile keeps a positive size
+/// inside `bounds` rather than being pushed off-screen.
+pub(super) fn tile_column(
+    bounds: &Rectangle,
+    count: usize,
+    inner_gap: u32,
+    outer_gap: u32,
+) -> Vec<Rectangle> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let n = count as i64;
+    let height = bounds.height as i64;
+    let width = bounds.width as i64;
+    let (inner, outer) = (inner_gap as i64, outer_gap as i64);
+
+    let needed = 2 * outer + (n - 1) * inner;
+    let available = (height - n).max(0);
+    let (gap_in, gap_out) = if needed <= available {
+        (inner, outer)
+    } else {
+        (inner * available / needed, outer * available / needed)
+    };
+    let gap_x = outer.min((width - 1) / 2).max(0);
+    // Never let the integer division hand out 0px tiles: the stride would
+    // then be just the gap and 1px windows would overlap their neighbours.
+    let tile_height = ((height - 2 * gap_out - (n - 1) * gap_in) / n).max(1);
+    let tile_width = (width - 2 * gap_x).max(1);
+
+    (0..n)
+        .map(|i| Rectangle {
+            x: bounds.x + gap_x as i32,
+            y: bounds.y + (gap_out + i * (tile_height + gap_in)) as i32,
+            width: tile_width as u32,
+            height: tile_height as u32,
+        })
+        .collect()
+}
+
+/// Split `bounds` into `count` tiles side by side: [`tile_column`] turned
+/// on its side.
+fn tile_row(bounds: &Rectangle, count: usize, inner_gap: u32, outer_gap: u32) -> Vec<Rectangle> {
+    let transposed = Rectangle {
+        x: bounds.y,
+        y: bounds.x,
+        width: bounds.height,
+        height: bounds.width,
+    };
+    tile_column(&transposed, count, inner_gap, outer_gap)
+        .into_iter()
+        .map(|r| Rectangle {
+            x: r.y,
+            y: r.x,
+            width: r.height,
+            height: r.width,
+        })
+        .collect()
+}
+
+/// Give the first of `count` tiles the left three fifths of `bounds` and
+/// stack the rest in the remainder. With fewer than two windows there is
+/// no stack and this is a plain [`tile_column`].
+fn tile_master_stack(
+    bounds: &Rectangle,
+    count: usize,
+    inner_gap: u32,
+    outer_gap: u32,
+) -> Vec<Rectangle> {
+    if count < 2 {
+        return tile_column(bounds, count, inner_gap, outer_gap);
+    }
+    // A two-tile row already has the outer gaps and one inner gap between
+    // master and stack; only the split point moves.
+    let mut tiles = tile_row(bounds, 2, inner_gap, outer_gap);
+    let total = tiles[0].width + tiles[1].width;
+    let master_width = (total * 3 / 5).max(1);
+    tiles[1].x += master_width as i32 - tiles[0].width as i32;
+    tiles[1].width = (total - master_width).max(1);
+    tiles[0].width = master_width;
+
+    let stack = tiles[1].clone();
+    tiles.truncate(1);
+    tiles.extend(tile_column(&stack, count - 1, inner_gap, 0));
+    tiles
+}
+
+/// Lay `count` tiles out in rows of equal width, as close to square as the
+/// count allows; a short last row shares its width between fewer tiles.
+fn tile_grid(bounds: &Rectangle, count: usize, inner_gap: u32, outer_gap: u32) -> Vec<Rectangle> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let columns = (count as f64).sqrt().ceil() as usize;
+    let rows = count.div_ceil(columns);
+    tile_column(bounds, rows, inner_gap, outer_gap)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(row, row_bounds)| {
+            let in_row = columns.min(count - row * columns);
+            tile_row(&row_bounds, in_row, inner_gap, 0)
+        })
+        .collect()
+}
+
+/// Give each tile half of the space left, splitting side by side and then
+/// top and bottom in turn; the last tile takes whatever remains.
+fn tile_spiral(bounds: &Rectangle, count: usize, inner_gap: u32, outer_gap: u32) -> Vec<Rectangle> {
+    let Some(mut rest) = tile_column(bounds, 1, 0, outer_gap).pop() else {
+        return Vec::new();
+    };
+    let mut tiles = Vec::with_capacity(count);
+    for i in 0..count {
+        if i + 1 == count {
+            tiles.push(rest);
+            break;
+        }
+        let split = if i % 2 == 0 { tile_row } else { tile_column };
+        let mut halves = split(&rest, 2, inner_gap, 0);
+        rest = halves.pop().unwrap_or_else(|| rest.clone());
+        tiles.extend(halves);
+    }
+    tiles
+}
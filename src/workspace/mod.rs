@@ -8,12 +8,18 @@
 //! - [`ScrollableWorkspaces`]: Manager that holds multiple tapes.
 
 use log::{debug, info, warn};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use crate::config::WorkspaceConfig;
+use crate::clock::{system_clock, SharedClock};
+use crate::config::{EasingFunction, OutputRule, WorkspaceConfig};
 use crate::window::Rectangle;
 
+mod layout;
+
+pub use layout::{LayoutEngine, LayoutRegistry, DEFAULT_LAYOUT};
+
 /// Maximum number of workspace columns allowed per tape.
 /// Prevents unbounded memory growth from a malicious or runaway client.
 /// When the limit is reached, the oldest empty column is evicted to make
@@ -41,13 +47,32 @@ const MIN_MOMENTUM_VELOCITY: f64 = 10.0;
 /// Maximum delta time (seconds) to prevent huge jumps after pauses.
 const MAX_DT_SECONDS: f64 = 1.0 / 30.0;
 
+/// Integration step for spring and rubber-band motion, in clock time.
+/// They advance by whole steps, so where they are at a given time does
+/// not depend on how often frames sample them.
+const PHYSICS_STEP: Duration = Duration::from_nanos(4_166_667);
+
+/// A fling always comes to rest below this speed (px/s), even with
+/// `momentum_min_velocity` at 0.
+const MOMENTUM_REST_VELOCITY: f64 = 1.0;
+
+/// A spring scroll settles once it is this close to the target (px)...
+const SPRING_REST_DISTANCE: f64 = 0.5;
+
+/// ...and moving slower than this (px/s).
+const SPRING_REST_VELOCITY: f64 = 5.0;
+
+/// Pull (per second squared, per pixel of overshoot) back toward the last
+/// occupied column at full `edge_resistance`.
+const RUBBER_BAND_STIFFNESS: f64 = 200.0;
+
 /// How long to keep empty columns before cleanup (seconds).
 const COLUMN_CLEANUP_INTERVAL_SECS: u64 = 1;
 
 /// How long empty columns survive before eviction (seconds).
 const EMPTY_COLUMN_TTL_SECS: u64 = 30;
 
-/// Velocity decay factor per frame when idle.
+/// Velocity decay factor per 1/60 s when idle.
 const IDLE_VELOCITY_DECAY: f64 = 0.9;
 
 /// Velocity threshold below which idle velocity is zeroed.
@@ -136,6 +161,18 @@ impl WorkspaceColumn {
     }
 }
 
+/// Which neighbour [`ScrollableWorkspaces::swap_focused_with`] trades
+/// places with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapDirection {
+    /// The previous window in the column, or the last window of the
+    /// column to the left when already first.
+    Left,
+    /// The next window in the column, or the first window of the column
+    /// to the right when already last.
+    Right,
+}
+
 /// Scroll animation state
 #[derive(Debug, Clone, Copy)]
 pub enum ScrollState {
@@ -150,9 +187,93 @@ pub enum ScrollState {
         start_time: Instant,
         start_position: f64,
         velocity: f64,
+        /// Position of the outermost occupied column once the fling has
+        /// run past it with `edge_resistance` on. From then on the fling
+        /// is slowed and pulled back toward it.
+        rubber_band: Option<f64>,
     },
 }
 
+/// Index mapping for swapping columns `a` and `b`.
+fn swap_index(a: i32, b: i32) -> impl Fn(i32) -> i32 + Copy {
+    move |i| {
+        if i == a {
+            b
+        } else if i == b {
+            a
+        } else {
+            i
+        }
+    }
+}
+
+/// Index mapping for moving column `from` to `to`, shifting the
+/// columns in between toward the vacated slot.
+fn move_index(from: i32, to: i32) -> impl Fn(i32) -> i32 + Copy {
+    move |i| {
+        if i == from {
+            to
+        } else if from < to && i > from && i <= to {
+            i - 1
+        } else if to < from && i >= to && i < from {
+            i + 1
+        } else {
+            i
+        }
+    }
+}
+
+/// How one output's tape tiles its columns, resolved from the
+/// `[[outputs]]` rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLayout {
+    /// Name of the [`LayoutEngine`] columns are tiled with
+    pub layout: String,
+    /// Replaces the configured inner and outer gaps when set
+    pub gaps: Option<u32>,
+}
+
+impl Default for OutputLayout {
+    fn default() -> Self {
+        Self {
+            layout: DEFAULT_LAYOUT.to_string(),
+            gaps: None,
+        }
+    }
+}
+
+impl OutputLayout {
+    /// Layout for output `name` with `model`: `default_layout` and the
+    /// configured gaps, overridden by every matching rule in order.
+    pub fn resolve(rules: &[OutputRule], default_layout: &str, name: &str, model: &str) -> Self {
+        let mut layout = Self {
+            layout: default_layout.to_string(),
+            gaps: None,
+        };
+        for rule in rules.iter().filter(|rule| rule.matches(name, model)) {
+            if let Some(name) = &rule.layout {
+                layout.layout = name.clone();
+            }
+            if let Some(gaps) = rule.gaps {
+                layout.gaps = Some(gaps);
+            }
+        }
+        layout
+    }
+}
+
+/// Column wallpapers to draw behind a tape, cross-fading while it scrolls
+/// from one column to the next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackgroundBlend {
+    /// Wallpaper of the column at or left of the scroll position
+    pub from: Option<String>,
+    /// Wallpaper of the column to its right
+    pub to: Option<String>,
+    /// How far the tape is from `from`'s column to `to`'s, 0.0..1.0
+    pub mix: f64,
+}
+
 /// A single scrollable tape of workspaces (corresponds to one output/monitor)
 #[derive(Debug)]
 pub struct WorkspaceTape {
@@ -187,6 +308,9 @@ pub struct WorkspaceTape {
     /// Animation easing parameters
     last_update: Instant,
 
+    /// Clock time spring and rubber-band motion has been integrated up to
+    physics_time: Instant,
+
     /// Last time cleanup was performed
     last_cleanup: Instant,
 
@@ -195,10 +319,23 @@ pub struct WorkspaceTape {
     /// `calculate_workspace_layouts` to produce logical-space window
     /// rectangles that HiDPI-aware clients can consume directly.
     scale_factor: f64,
+
+    /// Jump straight to scroll targets instead of animating
+    /// (`effects.reduce_motion`).
+    reduce_motion: bool,
+
+    /// Time source for animations and column cleanup.
+    clock: SharedClock,
 }
 
 impl WorkspaceTape {
     pub fn new(config: &WorkspaceConfig) -> Self {
+        Self::with_clock(config, system_clock())
+    }
+
+    /// Create a tape that reads time from `clock` instead of the system clock.
+    pub fn with_clock(config: &WorkspaceConfig, clock: SharedClock) -> Self {
+        let now = clock.now();
         let mut tape = Self {
             config: config.clone(),
             current_position: 0.0,
@@ -210,9 +347,12 @@ impl WorkspaceTape {
             scroll_state: ScrollState::Idle,
             viewport_width: DEFAULT_VIEWPORT_WIDTH,
             viewport_height: DEFAULT_VIEWPORT_HEIGHT,
-            last_update: Instant::now(),
-            last_cleanup: Instant::now(),
+            last_update: now,
+            physics_time: now,
+            last_cleanup: now,
             scale_factor: 1.0,
+            reduce_motion: false,
+            clock,
         };
 
         // Create the initial workspace column
@@ -225,6 +365,24 @@ impl WorkspaceTape {
         self.config = config;
     }
 
+    /// Turn instant scrolling on or off. Turning it on lands a scroll that
+    /// is still in flight.
+    pub fn set_reduce_motion(&mut self, reduce_motion: bool) {
+        self.reduce_motion = reduce_motion;
+        if reduce_motion && !matches!(self.scroll_state, ScrollState::Idle) {
+            self.jump_to(self.target_position);
+        }
+    }
+
+    /// Stop any scroll and sit at `position`.
+    fn jump_to(&mut self, position: f64) {
+        let position = self.clamp_position(position);
+        self.current_position = position;
+        self.target_position = position;
+        self.scroll_velocity = 0.0;
+        self.scroll_state = ScrollState::Idle;
+    }
+
     /// Check if a window exists in any column
     pub fn window_exists(&self, window_id: u64) -> bool {
         self.columns
@@ -300,12 +458,101 @@ impl WorkspaceTape {
         self.ensure_column(self.focused_column)
     }
 
-    /// Scroll to a specific column (animated)
+    /// Whether the tape grows without bound in both directions.
+    /// A configured `max_columns` turns infinite scrolling off.
+    pub fn is_infinite_scroll_enabled(&self) -> bool {
+        self.config.infinite_scroll && self.config.max_columns.is_none()
+    }
+
+    /// Keep only the windows `keep` accepts, visiting columns left to
+    /// right, and put a scroll position that is no longer a number back on
+    /// the focused column. Returns how many things were repaired.
+    fn restore_invariants(&mut self, mut keep: impl FnMut(u64) -> bool) -> usize {
+        let mut repaired = 0;
+        let mut indices: Vec<i32> = self.columns.keys().copied().collect();
+        indices.sort_unstable();
+        for index in indices {
+            if let Some(column) = self.columns.get_mut(&index) {
+                let before = column.windows.len();
+                column.windows.retain(|&id| keep(id));
+                repaired += before - column.windows.len();
+            }
+        }
+        if let Some((min, max)) = self.column_bounds() {
+            if !(min..=max).contains(&self.focused_column) {
+                self.focused_column = self.focused_column.clamp(min, max);
+                repaired += 1;
+            }
+        }
+        let positions = [
+            self.current_position,
+            self.target_position,
+            self.scroll_velocity,
+        ];
+        if positions.iter().any(|p| !p.is_finite()) {
+            self.jump_to(self.focused_column as f64 * self.config.workspace_width as f64);
+            repaired += 1;
+        }
+        repaired
+    }
+
+    /// Inclusive column range when `max_columns` bounds the tape.
+    fn column_bounds(&self) -> Option<(i32, i32)> {
+        self.config
+            .max_columns
+            .map(|n| (0, n.clamp(1, MAX_COLUMNS as u32) as i32 - 1))
+    }
+
+    /// `position` held between the first and last column of a bounded
+    /// tape.
+    fn clamp_position(&self, position: f64) -> f64 {
+        let width = self.config.workspace_width as f64;
+        match self.column_bounds() {
+            Some((min, max)) => position.clamp(min as f64 * width, max as f64 * width),
+            None => position,
+        }
+    }
+
+    /// Move the scroll position by `delta`, stopping at the ends of a
+    /// bounded tape. Every physics step moves through here. Returns
+    /// `false` if a bound cut the move short.
+    fn advance_position(&mut self, delta: f64) -> bool {
+        let position = self.current_position + delta;
+        self.current_position = self.clamp_position(position);
+        self.current_position == position
+    }
+
+    /// Column `delta` steps away from the focused one, clamped or
+    /// wrapped at the bounds of a bounded tape.
+    fn step_column(&self, delta: i32) -> i32 {
+        let target = self.focused_column + delta;
+        match self.column_bounds() {
+            None => target,
+            Some((min, max)) if self.config.wrap => min + (target - min).rem_euclid(max - min + 1),
+            Some((min, max)) => target.clamp(min, max),
+        }
+    }
+
+    /// Scroll to a specific column (animated). On a bounded tape the
+    /// index is clamped into range.
     pub fn scroll_to_column(&mut self, column_index: i32) {
+        let column_index = match self.column_bounds() {
+            Some((min, max)) => column_index.clamp(min, max),
+            None => column_index,
+        };
         self.ensure_column(column_index);
 
         let target_pos = column_index as f64 * self.config.workspace_width as f64;
-        let current_time = Instant::now();
+        if self.reduce_motion {
+            self.focused_column = column_index;
+            self.jump_to(target_pos);
+            info!(
+                "📱 Jumped to column {} (position: {:.1})",
+                column_index, target_pos
+            );
+            return;
+        }
+        let current_time = self.clock.now();
 
         // Calculate animation duration based on distance
         let distance = (target_pos - self.current_position).abs();
@@ -321,6 +568,7 @@ impl WorkspaceTape {
             target_position: target_pos,
             duration,
         };
+        self.physics_time = current_time;
 
         self.focused_column = column_index;
         self.target_position = target_pos;
@@ -333,25 +581,37 @@ impl WorkspaceTape {
 
     /// Scroll left by one workspace
     pub fn scroll_left(&mut self) {
-        let new_column = self.focused_column - 1;
+        let new_column = self.step_column(-1);
         self.scroll_to_column(new_column);
     }
 
     /// Scroll right by one workspace
     pub fn scroll_right(&mut self) {
-        let new_column = self.focused_column + 1;
+        let new_column = self.step_column(1);
         self.scroll_to_column(new_column);
     }
 
-    /// Start momentum scrolling (for gesture input)
+    /// Start momentum scrolling (for gesture input). With reduced motion
+    /// there is no glide; the tape steps one column in the fling direction.
     pub fn start_momentum_scroll(&mut self, velocity: f64) {
+        if self.reduce_motion {
+            if velocity.abs() > MIN_MOMENTUM_VELOCITY {
+                let column = self.step_column(velocity.signum() as i32);
+                self.scroll_to_column(column);
+            }
+            return;
+        }
         if velocity.abs() > MIN_MOMENTUM_VELOCITY {
             // Minimum velocity threshold
+            let now = self.clock.now();
             self.scroll_state = ScrollState::Momentum {
-                start_time: Instant::now(),
+                start_time: now,
                 start_position: self.current_position,
                 velocity: velocity * self.config.scroll_speed,
+                rubber_band: None,
             };
+            self.scroll_velocity = velocity * self.config.scroll_speed;
+            self.physics_time = now;
             debug!("🏃 Started momentum scroll with velocity: {:.1}", velocity);
         }
     }
@@ -398,16 +658,160 @@ impl WorkspaceTape {
 
     /// Move the focused window to the left column
     pub fn move_window_left(&mut self, window_id: u64) -> bool {
-        let target_column = self.focused_column - 1;
+        let target_column = self.step_column(-1);
+        if target_column == self.focused_column {
+            return false;
+        }
         self.move_window_to_column(window_id, target_column)
     }
 
     /// Move the focused window to the right column
     pub fn move_window_right(&mut self, window_id: u64) -> bool {
-        let target_column = self.focused_column + 1;
+        let target_column = self.step_column(1);
+        if target_column == self.focused_column {
+            return false;
+        }
         self.move_window_to_column(window_id, target_column)
     }
 
+    /// The window after (`forward`) or before `window_id` in its column,
+    /// wrapping around at the ends. `None` when the window is alone in its
+    /// column or not on this tape.
+    pub fn column_neighbour(&self, window_id: u64, forward: bool) -> Option<u64> {
+        let windows = &self
+            .columns
+            .values()
+            .find(|c| c.windows.contains(&window_id))?
+            .windows;
+        if windows.len() < 2 {
+            return None;
+        }
+        let pos = windows.iter().position(|&id| id == window_id)?;
+        let next = if forward {
+            (pos + 1) % windows.len()
+        } else {
+            (pos + windows.len() - 1) % windows.len()
+        };
+        Some(windows[next])
+    }
+
+    /// Trade places with the neighbouring window in `direction`. Inside a
+    /// column this reorders the stack; at the column's end the window
+    /// swaps with the nearest window of the adjacent column, or moves
+    /// into it when that column is empty. Column sizes are unchanged
+    /// either way. The view follows a window that was in the focused
+    /// column.
+    pub fn swap_window(&mut self, window_id: u64, direction: SwapDirection) -> bool {
+        let Some((&column_index, column)) = self
+            .columns
+            .iter_mut()
+            .find(|(_, c)| c.windows.contains(&window_id))
+        else {
+            return false;
+        };
+        let pos = column
+            .windows
+            .iter()
+            .position(|&id| id == window_id)
+            .expect("column contains window");
+        let neighbour = match direction {
+            SwapDirection::Left => pos.checked_sub(1),
+            SwapDirection::Right => Some(pos + 1).filter(|&p| p < column.windows.len()),
+        };
+        if let Some(neighbour) = neighbour {
+            column.windows.swap(pos, neighbour);
+            info!("🔀 Swapped window {} within column {}", window_id, column_index);
+            return true;
+        }
+
+        let target_index = column_index
+            + match direction {
+                SwapDirection::Left => -1,
+                SwapDirection::Right => 1,
+            };
+        if !self.in_bounds(target_index) {
+            return false;
+        }
+        let target = self.ensure_column(target_index);
+        let other = match direction {
+            SwapDirection::Left => target.windows.pop(),
+            SwapDirection::Right => {
+                (!target.windows.is_empty()).then(|| target.windows.remove(0))
+            }
+        };
+        match direction {
+            SwapDirection::Left => target.windows.push(window_id),
+            SwapDirection::Right => target.windows.insert(0, window_id),
+        }
+        let column = self.columns.get_mut(&column_index).expect("source column exists");
+        match other {
+            Some(other) => column.windows[pos] = other,
+            None => {
+                column.windows.remove(pos);
+            }
+        }
+        info!(
+            "🔀 Swapped window {} from column {} into column {}",
+            window_id, column_index, target_index
+        );
+        if column_index == self.focused_column {
+            self.scroll_to_column(target_index);
+        }
+        true
+    }
+
+    /// Exchange the contents of columns `a` and `b`, windows included.
+    /// Either slot may be empty or absent, but not both. Returns `false`
+    /// when there is nothing to swap or an index is outside the tape's
+    /// bounds.
+    pub fn swap_columns(&mut self, a: i32, b: i32) -> bool {
+        if a == b || !self.in_bounds(a) || !self.in_bounds(b) {
+            return false;
+        }
+        if !self.columns.contains_key(&a) && !self.columns.contains_key(&b) {
+            return false;
+        }
+        self.reindex_columns(swap_index(a, b));
+        info!("🔀 Swapped columns {} and {}", a, b);
+        true
+    }
+
+    /// Move column `from` to position `to`, shifting the columns in
+    /// between by one to close the gap. Returns `false` when `from` does
+    /// not exist or `to` is outside the tape's bounds.
+    pub fn move_column(&mut self, from: i32, to: i32) -> bool {
+        if from == to || !self.columns.contains_key(&from) || !self.in_bounds(to) {
+            return false;
+        }
+        self.reindex_columns(move_index(from, to));
+        info!("🔀 Moved column {} to {}", from, to);
+        true
+    }
+
+    fn in_bounds(&self, index: i32) -> bool {
+        self.column_bounds()
+            .is_none_or(|(min, max)| (min..=max).contains(&index))
+    }
+
+    /// Rebuild the column map with every column at `remap(index)`,
+    /// refreshing positions to match. The focused column follows its
+    /// content and the view scrolls along with it.
+    fn reindex_columns(&mut self, remap: impl Fn(i32) -> i32) {
+        let width = self.config.workspace_width as f64;
+        self.columns = std::mem::take(&mut self.columns)
+            .into_values()
+            .map(|mut column| {
+                column.index = remap(column.index);
+                column.position = column.index as f64 * width;
+                (column.index, column)
+            })
+            .collect();
+        let focused = remap(self.focused_column);
+        if focused != self.focused_column {
+            self.scroll_to_column(focused);
+        }
+    }
+
     /// Get all windows in the currently focused column. This is the
     /// **logical-target** column (the column the user's last input
     /// commanded), updated instantly in `scroll_to_column()`,
@@ -435,6 +839,26 @@ impl WorkspaceTape {
             .unwrap_or_default()
     }
 
+    /// Windows in the column at `index`, in column order. Unknown
+    /// indices (including negative ones that were never created)
+    /// yield an empty list rather than creating the column.
+    pub fn windows_in_column(&self, index: i32) -> Vec<u64> {
+        self.columns
+            .get(&index)
+            .map(|column| column.windows.clone())
+            .unwrap_or_default()
+    }
+
+    /// Window count for every column on this tape, keyed and ordered
+    /// by column index. Empty columns that still exist are reported
+    /// with a count of zero.
+    pub fn column_window_counts(&self) -> BTreeMap<i32, usize> {
+        self.columns
+            .iter()
+            .map(|(index, column)| (*index, column.windows.len()))
+            .collect()
+    }
+
     /// The column index that the user is currently looking at.
     /// Returns `focused_column` once scrolling has settled, and the
     /// column whose center contains `current_position` while a
@@ -477,6 +901,42 @@ impl WorkspaceTape {
         }
     }
 
+    /// Index of the column holding `window_id`, if any.
+    pub fn column_of_window(&self, window_id: u64) -> Option<i32> {
+        self.columns
+            .values()
+            .find(|c| c.windows.contains(&window_id))
+            .map(|c| c.index)
+    }
+
+    /// Whether column `index` will be fully on screen once the current
+    /// scroll settles. The scroll target always counts, even when the
+    /// column is wider than the viewport.
+    fn is_column_in_view(&self, index: i32) -> bool {
+        if index == self.focused_column {
+            return true;
+        }
+        let half = self.viewport_width / 2.0;
+        let left = index as f64 * self.config.workspace_width as f64;
+        let right = left + self.config.workspace_width as f64;
+        left >= self.target_position - half && right <= self.target_position + half
+    }
+
+    /// Scroll the column holding `window_id` into view if it isn't
+    /// already (`workspace.auto_scroll`). Returns whether a scroll started.
+    pub fn reveal_window(&mut self, window_id: u64) -> bool {
+        if !self.config.auto_scroll {
+            return false;
+        }
+        match self.column_of_window(window_id) {
+            Some(index) if !self.is_column_in_view(index) => {
+                self.scroll_to_column(index);
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Get all visible columns based on current viewport
     pub fn get_visible_columns(&self) -> Vec<&WorkspaceColumn> {
         let left_bound = self.current_position - self.viewport_width / 2.0;
@@ -495,11 +955,30 @@ impl WorkspaceTape {
     /// Returns `true` if the scroll position (or any state affecting layout)
     /// actually changed this frame.
     pub fn update_animations(&mut self) -> bool {
-        let now = Instant::now();
+        let now = self.clock.now();
+        let frame_dt = now
+            .duration_since(self.last_update)
+            .as_secs_f64()
+            .min(MAX_DT_SECONDS);
         self.last_update = now;
         let old_position = self.current_position;
+        let easing = self.config.scroll_easing;
 
         match self.scroll_state {
+            ScrollState::Scrolling {
+                target_position, ..
+            } if matches!(easing, EasingFunction::Spring { .. }) => {
+                if let EasingFunction::Spring { stiffness, damping } = easing {
+                    let steps = self.physics_steps(now);
+                    if self.step_spring(target_position, stiffness, damping, steps) {
+                        self.current_position = target_position;
+                        self.scroll_velocity = 0.0;
+                        self.scroll_state = ScrollState::Idle;
+                        debug!("✅ Spring scroll settled at position {:.1}", target_position);
+                    }
+                }
+            }
+
             ScrollState::Scrolling {
                 start_time,
                 start_position,
@@ -520,61 +999,36 @@ impl WorkspaceTape {
                 } else {
                     // Calculate eased position
                     let progress = elapsed.as_secs_f64() / duration.as_secs_f64();
-                    let eased_progress = self.ease_out_cubic(progress);
+                    let eased_progress = easing.sample(progress);
 
                     self.current_position =
                         start_position + (target_position - start_position) * eased_progress;
 
                     // Calculate velocity for smooth transitions
                     self.scroll_velocity = (target_position - start_position)
-                        * self.ease_out_cubic_derivative(progress)
+                        * easing.derivative(progress)
                         / duration.as_secs_f64();
                 }
             }
 
             ScrollState::Momentum {
-                start_time,
-                start_position,
-                velocity,
+                rubber_band: Some(edge),
+                ..
             } => {
-                let elapsed = now.duration_since(start_time).as_secs_f64();
-                // Clamp dt to prevent huge jumps after GC pauses, debug
-                // breakpoints, or system suspend (max ~33 ms = 30 FPS).
-                let dt = elapsed.min(MAX_DT_SECONDS);
-                let friction: f64 = self.config.momentum_friction.clamp(0.0, 0.9999);
-
-                // Apply friction to velocity using clamped dt
-                let current_velocity = velocity * friction.powf(dt * 60.0);
-
-                if current_velocity.abs() < self.config.momentum_min_velocity {
-                    // Momentum has died down, snap to nearest column if close enough
-                    let nearest_column =
-                        (self.current_position / self.config.workspace_width as f64).round() as i32;
-                    let target_pos = nearest_column as f64 * self.config.workspace_width as f64;
-                    if (self.current_position - target_pos).abs() <= self.config.snap_threshold_px {
-                        self.scroll_to_column(nearest_column);
-                    } else {
-                        // If not close, continue deceleration towards target.
-                        // Clamp to prevent overshoot oscillation.
-                        let raw_pos = start_position + velocity * elapsed;
-                        self.current_position = if velocity > 0.0 {
-                            raw_pos.min(target_pos + self.config.snap_threshold_px)
-                        } else {
-                            raw_pos.max(target_pos - self.config.snap_threshold_px)
-                        };
-                        self.scroll_velocity = current_velocity;
-                    }
-                } else {
-                    // Update position based on momentum, clamped to avoid
-                    // overshoot oscillation when near the target.
-                    self.current_position = start_position + velocity * dt;
-                    self.scroll_velocity = current_velocity;
-                }
+                let steps = self.physics_steps(now);
+                self.step_rubber_band(edge, steps);
+            }
+
+            ScrollState::Momentum {
+                rubber_band: None, ..
+            } => {
+                let steps = self.physics_steps(now);
+                self.step_momentum(steps);
             }
 
             ScrollState::Idle => {
                 // Gradually reduce any remaining velocity
-                self.scroll_velocity *= IDLE_VELOCITY_DECAY;
+                self.scroll_velocity *= IDLE_VELOCITY_DECAY.powf(frame_dt * 60.0);
                 if self.scroll_velocity.abs() < IDLE_VELOCITY_ZERO_THRESHOLD {
                     self.scroll_velocity = 0.0;
                 }
@@ -591,21 +1045,138 @@ impl WorkspaceTape {
         (self.current_position - old_position).abs() > f64::EPSILON
     }
 
-    /// Ease-out cubic function for smooth animations
-    fn ease_out_cubic(&self, t: f64) -> f64 {
-        let t = t - 1.0;
-        t * t * t + 1.0
+    /// Position of the outermost occupied column on the side `position` has
+    /// scrolled past, or `None` while it is between occupied columns.
+    fn overshot_edge(&self, position: f64) -> Option<f64> {
+        let occupied = self
+            .columns
+            .values()
+            .filter(|c| !c.is_empty())
+            .map(|c| c.index);
+        let first = occupied.clone().min()?;
+        let last = occupied.max()?;
+        let width = self.config.workspace_width as f64;
+        if position > last as f64 * width {
+            Some(last as f64 * width)
+        } else if position < first as f64 * width {
+            Some(first as f64 * width)
+        } else {
+            None
+        }
+    }
+
+    /// Advance a free fling by `steps` physics steps, slowing it by
+    /// `momentum_friction`. Once it is slower than `momentum_min_velocity`
+    /// it scrolls to the nearest column. With `edge_resistance` on, the
+    /// steps left once it runs past the outermost occupied column go to
+    /// the rubber band. On a bounded tape a fling that reaches the last
+    /// column stops dead there.
+    fn step_momentum(&mut self, steps: u32) {
+        let friction = self.config.momentum_friction.clamp(0.0, 0.9999);
+        let rest_velocity = MOMENTUM_REST_VELOCITY.max(self.config.momentum_min_velocity);
+        let width = self.config.workspace_width as f64;
+        let h = PHYSICS_STEP.as_secs_f64();
+        for step in 1..=steps {
+            self.scroll_velocity *= friction.powf(h * 60.0);
+            if !self.advance_position(self.scroll_velocity * h) {
+                self.scroll_velocity = 0.0;
+                self.scroll_to_column((self.current_position / width).round() as i32);
+                return;
+            }
+            if self.config.edge_resistance > 0.0 {
+                if let Some(edge) = self.overshot_edge(self.current_position) {
+                    if let ScrollState::Momentum { rubber_band, .. } = &mut self.scroll_state {
+                        *rubber_band = Some(edge);
+                    }
+                    self.step_rubber_band(edge, steps - step);
+                    return;
+                }
+            }
+            if self.scroll_velocity.abs() < rest_velocity {
+                self.scroll_velocity = 0.0;
+                self.scroll_to_column((self.current_position / width).round() as i32);
+                return;
+            }
+        }
+    }
+
+    /// Advance a fling that ran past the occupied column at `edge` by
+    /// `steps` physics steps. Moving outward it meets extra drag on top of
+    /// the usual friction, and a spring pulls it back toward the edge
+    /// throughout. Where it turns around it commits to a new column if it
+    /// got at least half a column out; otherwise it snaps back once it
+    /// returns to the edge.
+    fn step_rubber_band(&mut self, edge: f64, steps: u32) {
+        let resistance = self.config.edge_resistance.clamp(0.0, 1.0);
+        let friction = self.config.momentum_friction.clamp(0.0, 0.9999);
+        let width = self.config.workspace_width as f64;
+        let edge_column = (edge / width).round() as i32;
+        let h = PHYSICS_STEP.as_secs_f64();
+        for _ in 0..steps {
+            let overshoot = self.current_position - edge;
+            let outward = self.scroll_velocity * overshoot > 0.0;
+            let drag = if outward {
+                friction * (1.0 - resistance)
+            } else {
+                friction
+            };
+            let velocity = self.scroll_velocity * drag.powf(h * 60.0)
+                - RUBBER_BAND_STIFFNESS * resistance * overshoot * h;
+            if outward && velocity * overshoot <= 0.0 && overshoot.abs() >= width / 2.0 {
+                self.scroll_velocity = 0.0;
+                self.scroll_to_column(edge_column + overshoot.signum() as i32);
+                return;
+            }
+            self.scroll_velocity = velocity;
+            if !self.advance_position(velocity * h) {
+                self.scroll_velocity = 0.0;
+                self.scroll_to_column((self.current_position / width).round() as i32);
+                return;
+            }
+            if (self.current_position - edge) * overshoot <= 0.0 {
+                self.scroll_to_column(edge_column);
+                return;
+            }
+        }
     }
 
-    /// Derivative of ease-out cubic for velocity calculation
-    fn ease_out_cubic_derivative(&self, t: f64) -> f64 {
-        let t = t - 1.0;
-        3.0 * t * t
+    /// Physics steps due by `now`, marked as taken. As with frame times,
+    /// anything over `MAX_DT_SECONDS` behind is dropped rather than
+    /// replayed after a stall.
+    fn physics_steps(&mut self, now: Instant) -> u32 {
+        let max_lag = Duration::from_secs_f64(MAX_DT_SECONDS);
+        if now.saturating_duration_since(self.physics_time) > max_lag {
+            self.physics_time = now - max_lag;
+        }
+        let mut steps = 0;
+        while self.physics_time + PHYSICS_STEP <= now {
+            self.physics_time += PHYSICS_STEP;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// Advance a spring scroll toward `target` by `steps` physics steps
+    /// using semi-implicit Euler. The velocity lives in `scroll_velocity`
+    /// so it carries across frames and into a retargeted scroll. Returns
+    /// `true` once the spring has come to rest.
+    fn step_spring(&mut self, target: f64, stiffness: f64, damping: f64, steps: u32) -> bool {
+        let h = PHYSICS_STEP.as_secs_f64();
+        for _ in 0..steps {
+            let accel =
+                -stiffness * (self.current_position - target) - damping * self.scroll_velocity;
+            self.scroll_velocity += accel * h;
+            if !self.advance_position(self.scroll_velocity * h) {
+                self.scroll_velocity = 0.0;
+            }
+        }
+        (self.current_position - target).abs() < SPRING_REST_DISTANCE
+            && self.scroll_velocity.abs() < SPRING_REST_VELOCITY
     }
 
     /// Clean up empty columns that haven't been used recently
     fn cleanup_empty_columns(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.now();
         let cleanup_threshold = Duration::from_secs(EMPTY_COLUMN_TTL_SECS);
 
         let columns_to_remove: Vec<i32> = self
@@ -649,6 +1220,53 @@ impl WorkspaceTape {
     }
 }
 
+/// Space along each output edge claimed by exclusive layer-shell surfaces
+/// (panels, docks). Maximized windows stay inside what is left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReservedInsets {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+impl ReservedInsets {
+    /// Shrink `rect` by these insets, never below 1x1.
+    pub fn shrink(&self, rect: &Rectangle) -> Rectangle {
+        Rectangle {
+            x: rect.x + self.left as i32,
+            y: rect.y + self.top as i32,
+            width: rect.width.saturating_sub(self.left + self.right).max(1),
+            height: rect.height.saturating_sub(self.top + self.bottom).max(1),
+        }
+    }
+}
+
+/// What a cached layout map was computed from.
+#[derive(Debug, Clone, PartialEq)]
+struct LayoutCacheKey {
+    /// Output IDs and viewport sizes, in output order
+    topology: String,
+    /// Each tape's scroll position and the movement it tolerates before
+    /// the layout is recomputed
+    positions: Vec<(f64, f64)>,
+}
+
+impl LayoutCacheKey {
+    /// Whether a layout computed for `self` can stand in for `current`.
+    fn covers(&self, current: &LayoutCacheKey) -> bool {
+        self.topology == current.topology
+            && self.positions.len() == current.positions.len()
+            && self
+                .positions
+                .iter()
+                .zip(&current.positions)
+                .all(|(&(cached, _), &(now, min_delta))| {
+                    cached == now || (cached - now).abs() < min_delta
+                })
+    }
+}
+
 /// Scrollable workspace manager (Top-level Multi-Monitor)
 #[derive(Debug)]
 pub struct ScrollableWorkspaces {
@@ -665,10 +1283,16 @@ pub struct ScrollableWorkspaces {
     /// This avoids recomputing all window rectangles on every pointer motion
     /// (the hot path for `element_under`).
     ///
-    /// The cache key is a lightweight string signature spanning all known
-    /// output tapes instead of just the focused tape's scroll position, which
-    /// makes the cache safe for multi-monitor layouts.
-    cached_layouts: parking_lot::Mutex<Option<(String, HashMap<u64, Rectangle>)>>,
+    /// The cache key spans all known output tapes instead of just the
+    /// focused tape's scroll position, which makes the cache safe for
+    /// multi-monitor layouts. While a tape scrolls, movement smaller than
+    /// its `layout_min_delta_px` still hits the cache, so sub-pixel jitter
+    /// from scroll animations doesn't recompute every window rect.
+    ///
+    /// An `RwLock` so concurrent readers of the workspace (pointer hit
+    /// tests, IPC snapshots, the renderer) can all hit the cache at once
+    /// instead of queueing behind each other.
+    cached_layouts: parking_lot::RwLock<Option<(LayoutCacheKey, HashMap<u64, Rectangle>)>>,
 
     /// Stable left-to-right output ordering used by multi-monitor layout
     /// calculation. Each tape occupies a horizontal segment in this order.
@@ -696,6 +1320,36 @@ pub struct ScrollableWorkspaces {
     /// skips these windows so they are not auto-tiled. Must be kept in sync
     /// with `WindowManager`'s `properties.floating` by the caller.
     floating_windows: HashSet<u64>,
+
+    /// Per-output insets reserved by exclusive layer surfaces.
+    reserved_insets: HashMap<String, ReservedInsets>,
+
+    /// Fullscreen windows. They keep their column slot (so leaving
+    /// fullscreen re-tiles them in place) but get no tiled rectangle;
+    /// the backend gives them the whole output instead.
+    fullscreen_windows: HashSet<u64>,
+
+    /// User-assigned column names ("web", "code", ...) mapped to column
+    /// indices on whichever tape is active. Keyed by index rather than by
+    /// column object, so a name outlives the column being evicted and
+    /// applies again when `ensure_column` recreates it.
+    column_names: HashMap<String, i32>,
+
+    /// Layout for outputs without an entry in `output_layouts`.
+    default_layout: String,
+
+    /// Column tiling algorithms `default_layout` and `output_layouts`
+    /// refer to by name.
+    layout_engines: LayoutRegistry,
+
+    /// Handed to every tape; see [`WorkspaceTape::set_reduce_motion`].
+    reduce_motion: bool,
+
+    /// Per-output layout and gap overrides, keyed by tape ID.
+    output_layouts: HashMap<String, OutputLayout>,
+
+    /// Time source handed to every tape, so all outputs animate in step.
+    clock: SharedClock,
 }
 
 impl ScrollableWorkspaces {
@@ -706,15 +1360,28 @@ impl ScrollableWorkspaces {
 
     /// Create a new scrollable workspace manager with a default tape.
     pub fn new(config: &WorkspaceConfig) -> Self {
+        Self::with_clock(config, system_clock())
+    }
+
+    /// Like [`Self::new`], but animations read time from `clock`.
+    pub fn with_clock(config: &WorkspaceConfig, clock: SharedClock) -> Self {
         let mut manager = Self {
             config: config.clone(),
             tapes: HashMap::new(),
             focused_output: "default".to_string(),
-            cached_layouts: parking_lot::Mutex::new(None),
+            cached_layouts: parking_lot::RwLock::new(None),
             output_order: vec!["default".to_string()],
             minimized_windows: HashSet::new(),
             originating_column: HashMap::new(),
             floating_windows: HashSet::new(),
+            reserved_insets: HashMap::new(),
+            fullscreen_windows: HashSet::new(),
+            column_names: HashMap::new(),
+            default_layout: DEFAULT_LAYOUT.to_string(),
+            layout_engines: LayoutRegistry::default(),
+            reduce_motion: false,
+            output_layouts: HashMap::new(),
+            clock,
         };
 
         // Create default tape
@@ -763,7 +1430,7 @@ impl ScrollableWorkspaces {
         // built lazily), still mark it minimized so a future layout
         // query that DOES see it will filter accordingly.
         self.minimized_windows.insert(window_id);
-        *self.cached_layouts.lock() = None;
+        *self.cached_layouts.write() = None;
         debug!(
             "📦 Workspace: minimized window {} (removed_from_column={}, origin={:?})",
             window_id, removed_anywhere, last_column
@@ -798,7 +1465,7 @@ impl ScrollableWorkspaces {
             let tape = self.active_tape_mut();
             tape.add_window_to_column(window_id, target_column);
         }
-        *self.cached_layouts.lock() = None;
+        *self.cached_layouts.write() = None;
         debug!(
             "📦 Workspace: restored window {} to column {} (focused = {})",
             window_id, target_column, focused_column
@@ -826,10 +1493,22 @@ impl ScrollableWorkspaces {
         }
         self.tapes.entry(output_id.to_string()).or_insert_with(|| {
             info!("Creating workspace tape for output: {}", output_id);
-            WorkspaceTape::new(&self.config)
+            let mut tape = WorkspaceTape::with_clock(&self.config, self.clock.clone());
+            tape.set_reduce_motion(self.reduce_motion);
+            tape
         })
     }
 
+    /// Make every tape, including ones created later, jump to scroll
+    /// targets instead of animating.
+    pub fn set_reduce_motion(&mut self, reduce_motion: bool) {
+        self.reduce_motion = reduce_motion;
+        for tape in self.tapes.values_mut() {
+            tape.set_reduce_motion(reduce_motion);
+        }
+        *self.cached_layouts.write() = None;
+    }
+
     /// Return the currently focused output ID.
     pub fn focused_output(&self) -> &str {
         &self.focused_output
@@ -861,7 +1540,77 @@ impl ScrollableWorkspaces {
     /// Set the viewport size for a specific output tape.
     pub fn set_output_viewport(&mut self, output_id: &str, width: f64, height: f64) {
         self.ensure_tape(output_id).set_viewport_size(width, height);
-        *self.cached_layouts.lock() = None;
+        *self.cached_layouts.write() = None;
+    }
+
+    /// Record the insets reserved by exclusive layer surfaces on an output.
+    pub fn set_reserved_insets(&mut self, output_id: &str, insets: ReservedInsets) {
+        if insets == self.reserved_insets(output_id) {
+            return;
+        }
+        if insets == ReservedInsets::default() {
+            self.reserved_insets.remove(output_id);
+        } else {
+            self.reserved_insets.insert(output_id.to_string(), insets);
+        }
+        // Tiled windows stay inside the work area, which just changed
+        *self.cached_layouts.write() = None;
+    }
+
+    /// Insets reserved on an output (zero if none were recorded).
+    pub fn reserved_insets(&self, output_id: &str) -> ReservedInsets {
+        self.reserved_insets.get(output_id).copied().unwrap_or_default()
+    }
+
+    /// Full rectangle of an output in the horizontal strip laid out by
+    /// `output_order`, matching the origins `calculate_workspace_layouts` uses.
+    pub fn output_geometry(&self, output_id: &str) -> Option<Rectangle> {
+        let mut origin_x: i32 = 0;
+        for id in &self.output_order {
+            let Some(tape) = self.tapes.get(id) else {
+                continue;
+            };
+            if id == output_id {
+                return Some(Rectangle {
+                    x: origin_x,
+                    y: 0,
+                    width: tape.viewport_width as u32,
+                    height: tape.viewport_height as u32,
+                });
+            }
+            origin_x = origin_x.saturating_add(tape.viewport_width as i32);
+        }
+        None
+    }
+
+    /// Output rectangle minus its reserved insets: the area a maximized
+    /// window fills.
+    pub fn output_work_area(&self, output_id: &str) -> Option<Rectangle> {
+        let geometry = self.output_geometry(output_id)?;
+        Some(self.reserved_insets(output_id).shrink(&geometry))
+    }
+
+    /// An output's viewport minus its reserved insets, relative to the
+    /// output's top-left corner: the area tiled windows are laid out in.
+    /// Insets covering the whole viewport leave an empty rectangle.
+    pub fn viewport_work_area(&self, output_id: &str) -> Option<Rectangle> {
+        let tape = self.tapes.get(output_id)?;
+        let insets = self.reserved_insets(output_id);
+        let width = tape.viewport_width as u32;
+        let height = tape.viewport_height as u32;
+        Some(Rectangle {
+            x: insets.left.min(width) as i32,
+            y: insets.top.min(height) as i32,
+            width: width.saturating_sub(insets.left.saturating_add(insets.right)),
+            height: height.saturating_sub(insets.top.saturating_add(insets.bottom)),
+        })
+    }
+
+    /// Output a window belongs to, falling back to the focused output for
+    /// windows not on any tape (floating, minimized).
+    pub fn output_for_window(&self, window_id: u64) -> &str {
+        self.window_output_id(window_id)
+            .unwrap_or(self.focused_output.as_str())
     }
 
     /// Return the total virtual desktop size when all outputs are laid out in a
@@ -885,17 +1634,28 @@ impl ScrollableWorkspaces {
         }
     }
 
-    fn layout_cache_signature(&self) -> String {
+    fn layout_cache_key(&self) -> LayoutCacheKey {
         let mut parts = Vec::new();
+        let mut positions = Vec::new();
         for output_id in &self.output_order {
             if let Some(tape) = self.tapes.get(output_id) {
                 parts.push(format!(
-                    "{}:{:.6}:{:.0}x{:.0}",
-                    output_id, tape.current_position, tape.viewport_width, tape.viewport_height
+                    "{}:{:.0}x{:.0}",
+                    output_id, tape.viewport_width, tape.viewport_height
                 ));
+                // A tape at rest gets exact layouts, so nothing is left
+                // short of where a scroll landed
+                let min_delta = match tape.scroll_state {
+                    ScrollState::Idle => 0.0,
+                    _ => tape.config.layout_min_delta_px,
+                };
+                positions.push((tape.current_position, min_delta));
             }
         }
-        parts.join("|")
+        LayoutCacheKey {
+            topology: parts.join("|"),
+            positions,
+        }
     }
 
     /// Synchronize workspace tapes with the currently live output IDs.
@@ -911,7 +1671,7 @@ impl ScrollableWorkspaces {
             self.ensure_tape("default");
             self.output_order = vec!["default".to_string()];
             self.focused_output = "default".to_string();
-            *self.cached_layouts.lock() = None;
+            *self.cached_layouts.write() = None;
             return;
         }
 
@@ -953,6 +1713,7 @@ impl ScrollableWorkspaces {
             if stale_id == fallback_focus {
                 continue;
             }
+            self.reserved_insets.remove(&stale_id);
             let Some(stale_tape) = self.tapes.remove(&stale_id) else {
                 continue;
             };
@@ -987,7 +1748,7 @@ impl ScrollableWorkspaces {
         }
 
         self.focused_output = fallback_focus;
-        *self.cached_layouts.lock() = None;
+        *self.cached_layouts.write() = None;
     }
 
     /// Get the active tape (read-only reference).
@@ -1073,7 +1834,7 @@ impl ScrollableWorkspaces {
         self.active_tape_mut().set_viewport_size(width, height);
         // Viewport resize invalidates every cached layout — the new
         // dimensions change column widths and window tiling.
-        *self.cached_layouts.lock() = None;
+        *self.cached_layouts.write() = None;
     }
 
     /// Add a window to a specific column on the active tape.
@@ -1098,6 +1859,57 @@ impl ScrollableWorkspaces {
         self.active_tape_mut().start_momentum_scroll(velocity);
     }
 
+    /// Bring a newly focused window's column into view on whichever tape
+    /// holds it. Floating and minimized windows aren't on a column and
+    /// never scroll. Returns whether a scroll started.
+    pub fn reveal_window(&mut self, window_id: u64) -> bool {
+        if self.floating_windows.contains(&window_id)
+            || self.minimized_windows.contains(&window_id)
+        {
+            return false;
+        }
+        let Some(output_id) = self.window_output_id(window_id).map(str::to_owned) else {
+            return false;
+        };
+        self.tapes
+            .get_mut(&output_id)
+            .is_some_and(|tape| tape.reveal_window(window_id))
+    }
+
+    /// Wallpapers behind output `output_id`'s tape at its current scroll
+    /// position (`workspace.backgrounds`). `None` when neither column in
+    /// view has one.
+    pub fn background_blend(&self, output_id: &str) -> Option<BackgroundBlend> {
+        let tape = self.tapes.get(output_id)?;
+        let width = self.config.workspace_width as f64;
+        if width <= 0.0 {
+            return None;
+        }
+        let position = tape.current_position / width;
+        let left = position.floor();
+        let backgrounds = self.config.column_backgrounds();
+        let from = backgrounds.get(&(left as i32)).cloned();
+        let to = backgrounds.get(&(left as i32 + 1)).cloned();
+        if from.is_none() && to.is_none() {
+            return None;
+        }
+        Some(BackgroundBlend {
+            from,
+            to,
+            mix: position - left,
+        })
+    }
+
+    /// Scroll the active tape to column `index` (animated).
+    pub fn scroll_to_column(&mut self, index: i32) {
+        self.active_tape_mut().scroll_to_column(index);
+    }
+
+    /// Whether the active tape scrolls without bound.
+    pub fn is_infinite_scroll_enabled(&self) -> bool {
+        self.active_tape().is_infinite_scroll_enabled()
+    }
+
     /// Scroll the active tape left by one workspace.
     pub fn scroll_left(&mut self) {
         self.active_tape_mut().scroll_left();
@@ -1113,6 +1925,37 @@ impl ScrollableWorkspaces {
         self.active_tape_mut().add_window(window_id);
     }
 
+    /// Add a window to column `column_index` on `output_id`'s tape.
+    pub fn add_window_to_output_column(
+        &mut self,
+        window_id: u64,
+        output_id: &str,
+        column_index: i32,
+    ) {
+        self.ensure_tape(output_id)
+            .add_window_to_column(window_id, column_index);
+    }
+
+    /// Output and column index under the point `(x, y)`, with columns
+    /// placed as `calculate_workspace_layouts` places them. The column
+    /// need not exist yet.
+    pub fn column_at(&self, x: f64, y: f64) -> Option<(String, i32)> {
+        self.output_order.iter().find_map(|output_id| {
+            let geometry = self.output_geometry(output_id)?;
+            if !geometry.contains_point(x.floor() as i32, y.floor() as i32) {
+                return None;
+            }
+            let tape = self.tapes.get(output_id)?;
+            let work_area = self.viewport_work_area(output_id)?;
+            // Column `i` starts at `i * workspace_width - current_position`
+            // past the middle of the work area
+            let middle = geometry.x as f64 + work_area.x as f64 + work_area.width as f64 / 2.0;
+            let position = x - middle + tape.current_position;
+            let index = (position / tape.config.workspace_width as f64).floor() as i32;
+            Some((output_id.clone(), index))
+        })
+    }
+
     /// Remove a window from all tapes. Returns the column index if found.
     pub fn remove_window(&mut self, window_id: u64) -> Option<i32> {
         // Search all tapes (a window is unique across all workspaces)
@@ -1130,11 +1973,57 @@ impl ScrollableWorkspaces {
         self.minimized_windows.remove(&window_id);
         self.originating_column.remove(&window_id);
         self.floating_windows.remove(&window_id);
-        *self.cached_layouts.lock() = None;
+        self.fullscreen_windows.remove(&window_id);
+        *self.cached_layouts.write() = None;
 
         removed_from
     }
 
+    /// Drop windows `is_window` doesn't know from every tape and set, keep
+    /// each remaining window in one column only, and make `output_order`
+    /// list every tape, once. A handler that panicked part way through
+    /// moving or removing a window can leave these out of step. Returns
+    /// how many things were repaired.
+    pub fn restore_invariants(&mut self, is_window: impl Fn(u64) -> bool) -> usize {
+        let mut repaired = 0;
+        let mut tape_ids: Vec<String> = self.tapes.keys().cloned().collect();
+        tape_ids.sort();
+        let mut placed = HashSet::new();
+        for tape_id in &tape_ids {
+            if let Some(tape) = self.tapes.get_mut(tape_id) {
+                repaired += tape.restore_invariants(|id| is_window(id) && placed.insert(id));
+            }
+        }
+
+        let before = self.minimized_windows.len()
+            + self.originating_column.len()
+            + self.floating_windows.len()
+            + self.fullscreen_windows.len();
+        self.minimized_windows.retain(|&id| is_window(id));
+        self.originating_column.retain(|&id, _| is_window(id));
+        self.floating_windows.retain(|&id| is_window(id));
+        self.fullscreen_windows.retain(|&id| is_window(id));
+        repaired += before
+            - self.minimized_windows.len()
+            - self.originating_column.len()
+            - self.floating_windows.len()
+            - self.fullscreen_windows.len();
+
+        let before = self.output_order.len();
+        let mut seen = HashSet::new();
+        self.output_order.retain(|id| seen.insert(id.clone()));
+        repaired += before - self.output_order.len();
+        for tape_id in tape_ids {
+            if !seen.contains(&tape_id) {
+                self.output_order.push(tape_id);
+                repaired += 1;
+            }
+        }
+
+        *self.cached_layouts.write() = None;
+        repaired
+    }
+
     /// Move a window left on the active tape.
     pub fn move_window_left(&mut self, window_id: u64) -> bool {
         self.active_tape_mut().move_window_left(window_id)
@@ -1145,11 +2034,155 @@ impl ScrollableWorkspaces {
         self.active_tape_mut().move_window_right(window_id)
     }
 
+    /// Move every window in `window_ids` to column `target_column` of the
+    /// active tape, keeping their relative order. Windows that aren't tiled
+    /// on the active tape are skipped. Returns how many moved.
+    pub fn move_windows_to_column(&mut self, window_ids: &[u64], target_column: i32) -> usize {
+        let tape = self.active_tape_mut();
+        let moved = window_ids
+            .iter()
+            .filter(|&&id| tape.move_window_to_column(id, target_column))
+            .count();
+        if moved > 0 {
+            *self.cached_layouts.write() = None;
+        }
+        moved
+    }
+
+    /// The next (`forward`) or previous window in `window_id`'s column,
+    /// on whichever tape holds it. Wraps around at the column's ends.
+    pub fn column_neighbour(&self, window_id: u64, forward: bool) -> Option<u64> {
+        self.tapes
+            .values()
+            .find_map(|tape| tape.column_neighbour(window_id, forward))
+    }
+
+    /// Swap the focused window with its neighbour on the active tape,
+    /// keeping every column's size. Unlike [`Self::move_window_left`],
+    /// the window only leaves its column when it is already at the end
+    /// of it.
+    pub fn swap_focused_with(&mut self, window_id: u64, direction: SwapDirection) -> bool {
+        if !self.active_tape_mut().swap_window(window_id, direction) {
+            return false;
+        }
+        *self.cached_layouts.write() = None;
+        true
+    }
+
+    /// Swap two columns on the active tape, carrying their windows.
+    /// Column names and minimized windows' home columns follow the
+    /// content.
+    pub fn swap_columns(&mut self, a: i32, b: i32) -> bool {
+        if !self.active_tape_mut().swap_columns(a, b) {
+            return false;
+        }
+        self.remap_column_references(swap_index(a, b));
+        true
+    }
+
+    /// Move a column on the active tape to a new index, shifting the
+    /// columns in between. Names and home columns follow the content.
+    pub fn move_column(&mut self, from: i32, to: i32) -> bool {
+        if !self.active_tape_mut().move_column(from, to) {
+            return false;
+        }
+        self.remap_column_references(move_index(from, to));
+        true
+    }
+
+    fn remap_column_references(&mut self, remap: impl Fn(i32) -> i32) {
+        for index in self.column_names.values_mut() {
+            *index = remap(*index);
+        }
+        for index in self.originating_column.values_mut() {
+            *index = remap(*index);
+        }
+        *self.cached_layouts.write() = None;
+    }
+
     /// Get windows in the focused column of the active tape.
     pub fn get_focused_column_windows(&self) -> Vec<u64> {
         self.active_tape().get_focused_column_windows()
     }
 
+    /// Windows in column `index` of the active tape.
+    pub fn windows_in_column(&self, index: i32) -> Vec<u64> {
+        self.active_tape().windows_in_column(index)
+    }
+
+    /// Per-column window counts on the active tape, ordered by index.
+    pub fn column_window_counts(&self) -> BTreeMap<i32, usize> {
+        self.active_tape().column_window_counts()
+    }
+
+    /// Give column `index` a name. A column carries at most one name, and
+    /// a name points at one column, so reusing either replaces the old
+    /// assignment. Empty names are rejected.
+    pub fn name_column(&mut self, index: i32, name: impl Into<String>) -> bool {
+        let name = name.into();
+        if name.is_empty() {
+            return false;
+        }
+        self.column_names.retain(|_, i| *i != index);
+        debug!("🏷️ Named column {} \"{}\"", index, name);
+        self.column_names.insert(name, index);
+        true
+    }
+
+    /// Drop a column name. Returns the index it pointed at.
+    pub fn unname_column(&mut self, name: &str) -> Option<i32> {
+        self.column_names.remove(name)
+    }
+
+    /// Name assigned to column `index`, if any.
+    pub fn column_name(&self, index: i32) -> Option<&str> {
+        self.column_names
+            .iter()
+            .find(|(_, i)| **i == index)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Scroll the active tape to the column called `name`, recreating
+    /// the column if it was evicted while empty. Returns `false` for an
+    /// unknown name.
+    pub fn scroll_to_named(&mut self, name: &str) -> bool {
+        match self.column_names.get(name).copied() {
+            Some(index) => {
+                self.scroll_to_column(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scroll to the column holding `window_id`, focusing the output whose
+    /// tape it is on. Returns `false` if no tape has the window.
+    pub fn scroll_to_window(&mut self, window_id: u64) -> bool {
+        let Some(output_id) = self.window_output_id(window_id).map(str::to_string) else {
+            return false;
+        };
+        let Some(tape) = self.tapes.get_mut(&output_id) else {
+            return false;
+        };
+        let Some(column) = tape.column_of_window(window_id) else {
+            return false;
+        };
+        tape.scroll_to_column(column);
+        self.focused_output = output_id;
+        true
+    }
+
+    /// All column names, for saving alongside the rest of the session.
+    pub fn column_names(&self) -> &HashMap<String, i32> {
+        &self.column_names
+    }
+
+    /// Replace the column names with a previously saved set.
+    pub fn restore_column_names(&mut self, names: HashMap<String, i32>) {
+        self.column_names = names;
+        self.column_names.retain(|name, _| !name.is_empty());
+    }
+
     /// Update animations on all tapes.
     /// Only invalidates the layout cache when at least one tape's scroll
     /// position actually changed, so the hot path avoids unnecessary
@@ -1160,16 +2193,52 @@ impl ScrollableWorkspaces {
             changed |= tape.update_animations();
         }
         if changed {
-            *self.cached_layouts.lock() = None;
+            *self.cached_layouts.write() = None;
         }
         changed
     }
 
+    /// Set how each output tiles its columns. Outputs missing from
+    /// `layouts` use `default_layout` with the configured gaps. Names
+    /// without a registered engine tile like [`DEFAULT_LAYOUT`].
+    pub fn set_output_layouts(
+        &mut self,
+        default_layout: String,
+        layouts: HashMap<String, OutputLayout>,
+    ) {
+        self.default_layout = default_layout;
+        self.output_layouts = layouts;
+        *self.cached_layouts.write() = None;
+    }
+
+    /// The layout windows on `output_id` are tiled with.
+    pub fn output_layout(&self, output_id: &str) -> OutputLayout {
+        self.output_layouts
+            .get(output_id)
+            .cloned()
+            .unwrap_or_else(|| OutputLayout {
+                layout: self.default_layout.clone(),
+                gaps: None,
+            })
+    }
+
+    /// Make `engine` available as layout `name`, replacing any layout of
+    /// that name. Outputs already set to `name` switch to it right away.
+    pub fn register_layout(&mut self, name: impl Into<String>, engine: Arc<dyn LayoutEngine>) {
+        self.layout_engines.register(name, engine);
+        *self.cached_layouts.write() = None;
+    }
+
+    /// Whether a layout engine is registered as `name`.
+    pub fn has_layout(&self, name: &str) -> bool {
+        self.layout_engines.contains(name)
+    }
+
     /// Calculate layout rectangles for all visible windows across all tapes.
     pub fn calculate_workspace_layouts(&self) -> HashMap<u64, Rectangle> {
-        let signature = self.layout_cache_signature();
-        if let Some((cached_sig, ref cached)) = &*self.cached_layouts.lock() {
-            if *cached_sig == signature {
+        let key = self.layout_cache_key();
+        if let Some((cached_key, ref cached)) = &*self.cached_layouts.read() {
+            if cached_key.covers(&key) {
                 return cached.clone();
             }
         }
@@ -1182,50 +2251,59 @@ impl ScrollableWorkspaces {
                 continue;
             };
             let visible_columns = tape.get_visible_columns();
+            let output_layout = self.output_layout(output_id);
+            let Some(work_area) = self.viewport_work_area(output_id) else {
+                continue;
+            };
+            let work_left = output_origin_x as f64 + work_area.x as f64;
+            let work_right = work_left + work_area.width as f64;
 
             for column in visible_columns {
                 let column_offset = column.position - tape.current_position;
-                let column_left =
-                    output_origin_x as f64 + (tape.viewport_width / 2.0) + column_offset;
+                let column_left = work_left + (work_area.width as f64 / 2.0) + column_offset;
 
-                if column_left + tape.config.workspace_width as f64 >= output_origin_x as f64
-                    && column_left <= output_origin_x as f64 + tape.viewport_width
+                if column_left + tape.config.workspace_width as f64 >= work_left
+                    && column_left <= work_right
                 {
                     let column_bounds = Rectangle {
                         x: column_left as i32,
-                        y: 0,
+                        y: work_area.y,
                         width: tape.config.workspace_width,
-                        height: tape.viewport_height as u32,
+                        height: work_area.height,
                     };
 
                     if !column.windows.is_empty() {
-                        let gap = tape.config.gaps as i32;
-                        let total_gap_space = gap * (column.windows.len() as i32 + 1);
-                        let available =
-                            (column_bounds.height as i32).saturating_sub(total_gap_space);
-                        let window_count = column.windows.len() as i32;
-                        let window_height = if window_count > 0 && available > 0 {
-                            available / window_count
+                        let tiled: Vec<u64> = column
+                            .windows
+                            .iter()
+                            .copied()
+                            .filter(|id| !self.fullscreen_windows.contains(id))
+                            .collect();
+                        let lone_window = tiled
+                            .iter()
+                            .filter(|id| {
+                                !self.minimized_windows.contains(*id)
+                                    && !self.floating_windows.contains(*id)
+                            })
+                            .count()
+                            == 1;
+                        let (inner_gap, outer_gap) = if tape.config.smart_gaps && lone_window {
+                            (0, 0)
+                        } else if let Some(gaps) = output_layout.gaps {
+                            (gaps, gaps)
                         } else {
-                            1
+                            (tape.config.inner_gap(), tape.config.outer_gap())
                         };
+                        let engine = self.layout_engines.get_or_default(&output_layout.layout);
+                        let tiles = engine.arrange(&tiled, &column_bounds, inner_gap, outer_gap);
 
-                        for (i, &window_id) in column.windows.iter().enumerate() {
+                        for (window_id, window_rect) in tiles {
                             if self.minimized_windows.contains(&window_id) {
                                 continue;
                             }
                             if self.floating_windows.contains(&window_id) {
                                 continue;
                             }
-                            let y = gap + i as i32 * (window_height + gap);
-                            let width = column_bounds.width.saturating_sub(2 * gap as u32).max(1);
-                            let height = (window_height as u32).max(1);
-                            let window_rect = Rectangle {
-                                x: column_bounds.x + gap,
-                                y,
-                                width,
-                                height,
-                            };
                             layouts.insert(window_id, window_rect);
                         }
                     }
@@ -1235,7 +2313,7 @@ impl ScrollableWorkspaces {
             output_origin_x = output_origin_x.saturating_add(tape.viewport_width as i32);
         }
 
-        *self.cached_layouts.lock() = Some((signature, layouts.clone()));
+        *self.cached_layouts.write() = Some((key, layouts.clone()));
         layouts
     }
 
@@ -1316,7 +2394,7 @@ impl ScrollableWorkspaces {
                 duration,
                 ..
             } => {
-                let elapsed = Instant::now().duration_since(start_time);
+                let elapsed = self.clock.now().duration_since(start_time);
                 (elapsed.as_secs_f64() / duration.as_secs_f64()).clamp(0.0, 1.0)
             }
             _ => 0.0,
@@ -1340,7 +2418,25 @@ impl ScrollableWorkspaces {
         }
         // Always invalidate: going floating (stale tiled rect in cache)
         // or coming back (window needs a new tiled rect).
-        *self.cached_layouts.lock() = None;
+        *self.cached_layouts.write() = None;
+    }
+
+    /// Mark a window fullscreen (or not). Fullscreen windows are skipped by
+    /// `calculate_workspace_layouts` so their column siblings use the space.
+    pub fn set_window_fullscreen(&mut self, window_id: u64, fullscreen: bool) {
+        let changed = if fullscreen {
+            self.fullscreen_windows.insert(window_id)
+        } else {
+            self.fullscreen_windows.remove(&window_id)
+        };
+        if changed {
+            *self.cached_layouts.write() = None;
+        }
+    }
+
+    /// Check whether a window is fullscreen on the workspace layer.
+    pub fn is_window_fullscreen(&self, window_id: u64) -> bool {
+        self.fullscreen_windows.contains(&window_id)
     }
 
     /// Toggle the floating state for a window.
@@ -1367,7 +2463,9 @@ impl ScrollableWorkspaces {
         self.output_order.clear();
         self.minimized_windows.clear();
         self.originating_column.clear();
-        *self.cached_layouts.lock() = None;
+        self.reserved_insets.clear();
+        self.fullscreen_windows.clear();
+        *self.cached_layouts.write() = None;
     }
 }
 
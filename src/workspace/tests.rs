@@ -4,7 +4,9 @@
 //! momentum scrolling, and state consistency.
 
 use super::*;
+use crate::clock::{Clock, ManualClock};
 use crate::config::WorkspaceConfig;
+use std::sync::Arc;
 
 #[test]
 fn test_workspace_creation() {
@@ -441,6 +443,590 @@ fn test_workspace_bounds_checking() {
     }
 }
 
+#[test]
+fn test_bounded_columns_wrap_around() {
+    let config = WorkspaceConfig {
+        max_columns: Some(4),
+        wrap: true,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    assert!(!workspaces.is_infinite_scroll_enabled());
+
+    workspaces.scroll_to_column(3);
+    assert_eq!(workspaces.focused_column_index(), 3);
+    workspaces.scroll_right();
+    assert_eq!(workspaces.focused_column_index(), 0);
+    workspaces.scroll_left();
+    assert_eq!(workspaces.focused_column_index(), 3);
+    assert!(!workspaces.column_window_counts().contains_key(&4));
+    assert!(!workspaces.column_window_counts().contains_key(&-1));
+}
+
+#[test]
+fn test_bounded_columns_clamp_without_wrap() {
+    let config = WorkspaceConfig {
+        max_columns: Some(4),
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+
+    workspaces.scroll_left();
+    assert_eq!(workspaces.focused_column_index(), 0);
+    for _ in 0..10 {
+        workspaces.scroll_right();
+    }
+    assert_eq!(workspaces.focused_column_index(), 3);
+    // Jumps past the end are clamped too
+    workspaces.scroll_to_column(42);
+    assert_eq!(workspaces.focused_column_index(), 3);
+
+    workspaces.add_window(7);
+    assert!(!workspaces.move_window_right(7));
+    assert_eq!(workspaces.windows_in_column(3), vec![7]);
+    assert_eq!(workspaces.column_window_counts().len(), 4);
+}
+
+#[test]
+fn test_fling_stops_at_the_last_column_of_a_bounded_tape() {
+    for edge_resistance in [0.0, 0.5] {
+        let clock = Arc::new(ManualClock::new());
+        let config = WorkspaceConfig {
+            max_columns: Some(3),
+            edge_resistance,
+            ..WorkspaceConfig::default()
+        };
+        let last = 2.0 * config.workspace_width as f64;
+        let mut workspaces = ScrollableWorkspaces::with_clock(&config, clock.clone());
+        workspaces.add_window_to_column(1, 2);
+        workspaces.start_momentum_scroll(20000.0);
+
+        let mut furthest: f64 = 0.0;
+        for _ in 0..300 {
+            clock.advance(Duration::from_millis(16));
+            workspaces.update_animations();
+            furthest = furthest.max(workspaces.current_position());
+        }
+        assert_eq!(furthest, last);
+        assert_eq!(workspaces.current_position(), last);
+        assert_eq!(workspaces.focused_column_index(), 2);
+        assert!(matches!(
+            workspaces.active_tape().scroll_state,
+            ScrollState::Idle
+        ));
+    }
+}
+
+#[test]
+fn test_restore_invariants_after_a_half_finished_move() {
+    let mut workspaces = ScrollableWorkspaces::new(&WorkspaceConfig::default());
+    workspaces.ensure_tape("DP-1");
+    workspaces.add_window_to_column(1, 0);
+    workspaces.add_window_to_column(2, 1);
+    // As if a handler panicked between adding a window's new slot and
+    // dropping its old one, after window 2 was destroyed
+    workspaces.ensure_tape("DP-1").add_window_to_column(1, 3);
+    workspaces.minimized_windows.insert(2);
+    workspaces.output_order.push("DP-1".to_string());
+    workspaces.active_tape_mut().current_position = f64::NAN;
+
+    let is_window = |id: u64| id == 1;
+    assert!(workspaces.restore_invariants(is_window) > 0);
+    let counts: usize = workspaces
+        .tapes
+        .values()
+        .flat_map(|tape| tape.columns.values())
+        .map(|column| column.windows.len())
+        .sum();
+    assert_eq!(counts, 1);
+    assert!(workspaces.minimized_windows.is_empty());
+    assert_eq!(
+        workspaces
+            .output_order
+            .iter()
+            .filter(|id| *id == "DP-1")
+            .count(),
+        1
+    );
+    assert_eq!(workspaces.current_position(), 0.0);
+    // Nothing left to repair
+    assert_eq!(workspaces.restore_invariants(is_window), 0);
+}
+
+#[test]
+fn test_spring_scroll_settles_on_target() {
+    let critically_damped = WorkspaceConfig {
+        scroll_easing: EasingFunction::Spring {
+            stiffness: 400.0,
+            damping: 40.0,
+        },
+        ..WorkspaceConfig::default()
+    };
+    let mut tape = WorkspaceTape::new(&critically_damped);
+    let target = 1920.0;
+    // Four physics steps make a 60 Hz frame
+    let frame_steps = 4;
+    let mut settled_after = None;
+    for frame in 0..600 {
+        assert!(
+            tape.current_position <= target + SPRING_REST_DISTANCE,
+            "critically damped spring must not overshoot"
+        );
+        if tape.step_spring(target, 400.0, 40.0, frame_steps) {
+            settled_after = Some(frame);
+            break;
+        }
+    }
+    assert!(settled_after.is_some(), "spring never came to rest");
+
+    // An underdamped spring overshoots but still settles
+    let mut tape = WorkspaceTape::new(&critically_damped);
+    let mut max_position: f64 = 0.0;
+    let mut settled = false;
+    for _ in 0..2000 {
+        settled = tape.step_spring(target, 400.0, 8.0, frame_steps);
+        max_position = max_position.max(tape.current_position);
+        if settled {
+            break;
+        }
+    }
+    assert!(settled);
+    assert!(max_position > target);
+}
+
+#[test]
+fn test_lightly_damped_spring_still_settles() {
+    // The least damping worth configuring still comes to rest, so the
+    // scroll animation (and its redraws) stop
+    let mut tape = WorkspaceTape::new(&WorkspaceConfig::default());
+    let target = 1920.0;
+    let one_minute = (60.0 / PHYSICS_STEP.as_secs_f64()) as u32;
+    let mut settled = false;
+    for _ in 0..one_minute {
+        if tape.step_spring(target, 400.0, 1.0, 1) {
+            settled = true;
+            break;
+        }
+    }
+    assert!(settled, "spring never came to rest");
+}
+
+#[test]
+fn test_spring_scroll_runs_through_update_animations() {
+    let config = WorkspaceConfig {
+        scroll_easing: EasingFunction::Spring {
+            stiffness: 400.0,
+            damping: 40.0,
+        },
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.scroll_right();
+    assert!(workspaces.is_scrolling());
+    let target = config.workspace_width as f64;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while workspaces.is_scrolling() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(4));
+        workspaces.update_animations();
+    }
+    assert!(!workspaces.is_scrolling());
+    assert_eq!(workspaces.current_position(), target);
+}
+
+#[test]
+fn test_crowded_column_shrinks_gaps_to_keep_tiles_on_screen() {
+    // 20 windows at a 48px gap need 1008px of gaps alone in an 800px column
+    let config = WorkspaceConfig {
+        gaps: 48,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.set_viewport_size(1280.0, 800.0);
+    for id in 1..=20 {
+        workspaces.add_window(id);
+    }
+    let mut rects: Vec<Rectangle> = workspaces
+        .calculate_workspace_layouts()
+        .into_values()
+        .collect();
+    rects.sort_by_key(|r| r.y);
+    assert_eq!(rects.len(), 20);
+    for r in &rects {
+        assert!(r.height > 0);
+        assert!(r.y >= 0 && r.y + r.height as i32 <= 800, "tile off-screen: {:?}", r);
+    }
+    for pair in rects.windows(2) {
+        assert!(!pair[0].intersects(&pair[1]), "{:?} overlaps {:?}", pair[0], pair[1]);
+    }
+
+    // A column narrower than both side gaps keeps a visible width
+    let narrow = Rectangle {
+        x: 0,
+        y: 0,
+        width: 80,
+        height: 800,
+    };
+    for tile in layout::tile_column(&narrow, 20, 48, 48) {
+        assert!(tile.width > 0 && tile.x >= 0 && tile.x + tile.width as i32 <= 80);
+        assert!(tile.y + tile.height as i32 <= 800);
+    }
+}
+
+#[test]
+fn test_outer_gap_at_column_edges_and_inner_gap_between_windows() {
+    let config = WorkspaceConfig {
+        workspace_width: 600,
+        inner_gap: Some(8),
+        outer_gap: Some(24),
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.set_viewport_size(1280.0, 800.0);
+    for id in 1..=3 {
+        workspaces.add_window(id);
+    }
+    let mut rects: Vec<Rectangle> = workspaces
+        .calculate_workspace_layouts()
+        .into_values()
+        .collect();
+    rects.sort_by_key(|r| r.y);
+
+    let column_left = 640;
+    for r in &rects {
+        assert_eq!(r.x - column_left, 24);
+        assert_eq!(r.width, 600 - 2 * 24);
+    }
+    assert_eq!(rects[0].y, 24);
+    for pair in rects.windows(2) {
+        assert_eq!(pair[1].y - (pair[0].y + pair[0].height as i32), 8);
+    }
+    // Rounding leftovers land at the bottom, never eating into the margin
+    let last = &rects[2];
+    assert!(800 - (last.y + last.height as i32) >= 24);
+}
+
+#[test]
+fn test_smart_gaps_only_apply_to_a_lone_window() {
+    let config = WorkspaceConfig {
+        workspace_width: 600,
+        gaps: 16,
+        smart_gaps: true,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.set_viewport_size(1280.0, 800.0);
+    workspaces.add_window(1);
+
+    let layouts = workspaces.calculate_workspace_layouts();
+    assert_eq!(
+        layouts[&1],
+        Rectangle {
+            x: 640,
+            y: 0,
+            width: 600,
+            height: 800
+        }
+    );
+
+    workspaces.add_window(2);
+    let mut rects: Vec<Rectangle> = workspaces
+        .calculate_workspace_layouts()
+        .into_values()
+        .collect();
+    rects.sort_by_key(|r| r.y);
+    assert_eq!(rects[0].y, 16);
+    assert_eq!(rects[0].x, 640 + 16);
+    assert_eq!(rects[1].y - (rects[0].y + rects[0].height as i32), 16);
+}
+
+#[test]
+fn test_manual_clock_gives_exact_scroll_progress() {
+    let clock = Arc::new(ManualClock::new());
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::with_clock(&config, clock.clone());
+    workspaces.scroll_right();
+    let ScrollState::Scrolling {
+        duration,
+        target_position,
+        ..
+    } = workspaces.active_tape().scroll_state
+    else {
+        panic!("scroll_right should start a scroll animation");
+    };
+    assert_eq!(workspaces.scroll_progress(), 0.0);
+
+    clock.advance(duration / 4);
+    assert!(workspaces.update_animations());
+    assert_eq!(workspaces.scroll_progress(), 0.25);
+    assert_eq!(
+        workspaces.current_position(),
+        target_position * config.scroll_easing.sample(0.25)
+    );
+
+    // Nothing moves while the clock stands still
+    assert!(!workspaces.update_animations());
+
+    clock.advance(duration - duration / 4);
+    workspaces.update_animations();
+    assert!(!workspaces.is_scrolling());
+    assert_eq!(workspaces.current_position(), target_position);
+}
+
+#[test]
+fn test_edge_resistance_slows_fling_past_last_column_and_pulls_it_back() {
+    let clock = Arc::new(ManualClock::new());
+    let resisted_config = WorkspaceConfig {
+        edge_resistance: 0.5,
+        ..WorkspaceConfig::default()
+    };
+    let mut resisted = ScrollableWorkspaces::with_clock(&resisted_config, clock.clone());
+    let mut free = ScrollableWorkspaces::with_clock(&WorkspaceConfig::default(), clock.clone());
+    for workspaces in [&mut resisted, &mut free] {
+        workspaces.add_window_to_column(1, 0);
+        workspaces.start_momentum_scroll(3000.0);
+    }
+    let frame = || {
+        clock.advance(Duration::from_millis(16));
+    };
+
+    // Both flings leave column 0, the last one holding a window
+    frame();
+    resisted.update_animations();
+    free.update_animations();
+    assert!(resisted.current_position() > 0.0);
+    assert!(matches!(
+        resisted.active_tape().scroll_state,
+        ScrollState::Momentum {
+            rubber_band: Some(edge),
+            ..
+        } if edge == 0.0
+    ));
+
+    // Past it the resisted fling loses speed much faster
+    for _ in 0..2 {
+        frame();
+        resisted.update_animations();
+        free.update_animations();
+    }
+    let velocity = |ws: &ScrollableWorkspaces| ws.active_tape().scroll_velocity;
+    assert!(velocity(&resisted) < velocity(&free) / 2.0);
+
+    // It turns around and settles back on column 0
+    let mut furthest = resisted.current_position();
+    for _ in 0..120 {
+        frame();
+        resisted.update_animations();
+        furthest = furthest.max(resisted.current_position());
+    }
+    assert!(furthest < resisted_config.workspace_width as f64 / 2.0);
+    assert!(!resisted.is_scrolling());
+    assert_eq!(resisted.current_position(), 0.0);
+    assert_eq!(resisted.focused_column_index(), 0);
+}
+
+#[test]
+fn test_fling_half_a_column_past_the_edge_commits_to_new_column() {
+    let clock = Arc::new(ManualClock::new());
+    let config = WorkspaceConfig {
+        edge_resistance: 0.5,
+        ..WorkspaceConfig::default()
+    };
+    let width = config.workspace_width as f64;
+    let mut workspaces = ScrollableWorkspaces::with_clock(&config, clock.clone());
+    workspaces.add_window_to_column(1, 0);
+    let tape = workspaces.active_tape_mut();
+    tape.current_position = 0.6 * width;
+    tape.scroll_velocity = 1.0;
+    tape.scroll_state = ScrollState::Momentum {
+        start_time: clock.now(),
+        start_position: 0.6 * width,
+        velocity: 1.0,
+        rubber_band: Some(0.0),
+    };
+
+    // It turns around well out, so it carries on to column 1
+    clock.advance(Duration::from_millis(16));
+    workspaces.update_animations();
+    assert_eq!(workspaces.focused_column_index(), 1);
+    clock.advance(Duration::from_secs(1));
+    workspaces.update_animations();
+    assert_eq!(workspaces.current_position(), width);
+}
+
+/// Scroll position at every twelfth of a second of a scroll to column 1
+/// under `easing`, with frames drawn at `hz`.
+fn scroll_positions(easing: EasingFunction, hz: u64) -> Vec<f64> {
+    let clock = Arc::new(ManualClock::new());
+    let config = WorkspaceConfig {
+        scroll_easing: easing,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::with_clock(&config, clock.clone());
+    let start = clock.now();
+    workspaces.scroll_to_column(1);
+    let mut positions = Vec::new();
+    for frame in 1..=hz {
+        let at = start + Duration::from_nanos(frame * 1_000_000_000 / hz);
+        clock.advance(at - clock.now());
+        workspaces.update_animations();
+        if frame % (hz / 12) == 0 {
+            positions.push(workspaces.current_position());
+        }
+    }
+    positions
+}
+
+#[test]
+fn test_scroll_progress_is_the_same_at_60_and_144_hz() {
+    let eased = EasingFunction::EaseOutCubic;
+    assert_eq!(scroll_positions(eased, 60), scroll_positions(eased, 144));
+
+    // Springs are integrated in fixed steps of clock time, so a faster
+    // display samples the same curve rather than drifting from it
+    let spring = EasingFunction::Spring {
+        stiffness: 180.0,
+        damping: 24.0,
+    };
+    let at_60 = scroll_positions(spring, 60);
+    let at_144 = scroll_positions(spring, 144);
+    assert_eq!(at_60.len(), 12);
+    for (a, b) in at_60.iter().zip(&at_144) {
+        assert!(
+            (a - b).abs() <= SPRING_REST_DISTANCE,
+            "{a} at 60 Hz, {b} at 144 Hz"
+        );
+    }
+    assert!(at_60[1] > 0.0 && at_60[1] < at_60[11]);
+}
+
+/// A fling of `velocity` with frames drawn at `hz`: where it is every
+/// twelfth of a second for its first half second, and where it rests five
+/// seconds in, if it has stopped by then.
+fn fling_positions(velocity: f64, hz: u64) -> (Vec<f64>, Option<f64>) {
+    let clock = Arc::new(ManualClock::new());
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::with_clock(&config, clock.clone());
+    let start = clock.now();
+    workspaces.start_momentum_scroll(velocity);
+    let mut positions = Vec::new();
+    for frame in 1..=5 * hz {
+        let at = start + Duration::from_nanos(frame * 1_000_000_000 / hz);
+        clock.advance(at - clock.now());
+        workspaces.update_animations();
+        if frame % (hz / 12) == 0 && positions.len() < 6 {
+            positions.push(workspaces.current_position());
+        }
+    }
+    let rest = (!workspaces.is_scrolling()).then(|| workspaces.current_position());
+    (positions, rest)
+}
+
+#[test]
+fn test_fling_is_the_same_at_60_and_144_hz() {
+    let (at_60, rest_60) = fling_positions(4000.0, 60);
+    let (at_144, rest_144) = fling_positions(4000.0, 144);
+    // It glides on well past the first frames, the same at either rate
+    assert_eq!(at_60.len(), 6);
+    assert!(at_60[0] > 0.0 && at_60[0] < at_60[5]);
+    assert_eq!(at_60, at_144);
+
+    // Both come to rest on the same column
+    let width = WorkspaceConfig::default().workspace_width as f64;
+    assert_eq!(rest_60, rest_144);
+    assert_eq!(rest_60, Some(width));
+}
+
+#[test]
+fn test_swap_focused_with_reorders_within_column() {
+    let mut workspaces = ScrollableWorkspaces::new(&WorkspaceConfig::default());
+    for id in [1, 2, 3] {
+        workspaces.add_window_to_column(id, 0);
+    }
+
+    assert!(workspaces.swap_focused_with(2, SwapDirection::Right));
+    assert_eq!(workspaces.windows_in_column(0), vec![1, 3, 2]);
+    assert!(workspaces.swap_focused_with(2, SwapDirection::Left));
+    assert!(workspaces.swap_focused_with(2, SwapDirection::Left));
+    assert_eq!(workspaces.windows_in_column(0), vec![2, 1, 3]);
+    // Reordering doesn't leave the column
+    assert_eq!(workspaces.focused_column_index(), 0);
+}
+
+#[test]
+fn test_swap_focused_with_crosses_columns_at_the_edge() {
+    let mut workspaces = ScrollableWorkspaces::new(&WorkspaceConfig::default());
+    workspaces.add_window_to_column(1, 0);
+    workspaces.add_window_to_column(2, 0);
+    workspaces.add_window_to_column(3, 1);
+    workspaces.add_window_to_column(4, 1);
+
+    // Last in column 0: trades places with the first window of column 1
+    assert!(workspaces.swap_focused_with(2, SwapDirection::Right));
+    assert_eq!(workspaces.windows_in_column(0), vec![1, 3]);
+    assert_eq!(workspaces.windows_in_column(1), vec![2, 4]);
+    assert_eq!(workspaces.focused_column_index(), 1);
+
+    // First in column 1 going left: back to where it started
+    assert!(workspaces.swap_focused_with(2, SwapDirection::Left));
+    assert_eq!(workspaces.windows_in_column(0), vec![1, 2]);
+    assert_eq!(workspaces.windows_in_column(1), vec![3, 4]);
+
+    // An empty neighbouring column just takes the window
+    assert!(workspaces.swap_focused_with(1, SwapDirection::Left));
+    assert_eq!(workspaces.windows_in_column(-1), vec![1]);
+    assert_eq!(workspaces.windows_in_column(0), vec![2]);
+    assert!(!workspaces.swap_focused_with(99, SwapDirection::Left));
+}
+
+#[test]
+fn test_swap_columns_exchanges_windows_and_positions() {
+    let config = WorkspaceConfig::default();
+    let width = config.workspace_width as f64;
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.add_window_to_column(1, 0);
+    workspaces.add_window_to_column(2, 0);
+    workspaces.add_window_to_column(3, 2);
+    workspaces.name_column(2, "chat");
+
+    assert!(workspaces.swap_columns(0, 2));
+    assert_eq!(workspaces.windows_in_column(0), vec![3]);
+    assert_eq!(workspaces.windows_in_column(2), vec![1, 2]);
+    let tape = workspaces.active_tape();
+    assert_eq!(tape.columns[&0].index, 0);
+    assert_eq!(tape.columns[&2].position, 2.0 * width);
+    // Focus followed the windows that were in column 0
+    assert_eq!(workspaces.focused_column_index(), 2);
+    assert_eq!(workspaces.column_name(0), Some("chat"));
+
+    // Swapping into an empty slot moves the column there
+    assert!(workspaces.swap_columns(2, 5));
+    assert_eq!(workspaces.windows_in_column(5), vec![1, 2]);
+    assert!(!workspaces.column_window_counts().contains_key(&2));
+    assert!(!workspaces.swap_columns(7, 8));
+    assert!(!workspaces.swap_columns(0, 0));
+}
+
+#[test]
+fn test_move_column_shifts_columns_in_between() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    for (window, column) in [(10, 0), (11, 1), (12, 2), (13, 3)] {
+        workspaces.add_window_to_column(window, column);
+    }
+
+    assert!(workspaces.move_column(0, 2));
+    let order: Vec<Vec<u64>> = (0..4).map(|i| workspaces.windows_in_column(i)).collect();
+    assert_eq!(order, vec![vec![11], vec![12], vec![10], vec![13]]);
+    assert_eq!(workspaces.focused_column_index(), 2);
+
+    assert!(workspaces.move_column(3, 0));
+    let order: Vec<Vec<u64>> = (0..4).map(|i| workspaces.windows_in_column(i)).collect();
+    assert_eq!(order, vec![vec![13], vec![11], vec![12], vec![10]]);
+    assert_eq!(workspaces.focused_column_index(), 3);
+
+    assert!(!workspaces.move_column(9, 0));
+}
+
 /// Create 3 windows, remove the middle one, and verify the remaining
 /// two windows keep their original order (correct indices in the
 /// column's window list).
@@ -647,6 +1233,406 @@ fn test_multi_monitor_layout_coordinates_are_offset_per_output() {
     );
 }
 
+#[test]
+fn test_column_at_finds_output_and_column_under_point() {
+    let config = WorkspaceConfig {
+        workspace_width: 400,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.sync_tapes_with_outputs(&["output-1".to_string(), "output-2".to_string()], &[]);
+    workspaces.set_output_viewport("output-1", 1000.0, 800.0);
+    workspaces.set_output_viewport("output-2", 1000.0, 800.0);
+
+    // Column 0 of output-2 starts at its middle, x = 1500
+    assert_eq!(
+        workspaces.column_at(1910.0, 400.0),
+        Some(("output-2".to_string(), 1))
+    );
+    assert_eq!(
+        workspaces.column_at(1490.0, 400.0),
+        Some(("output-2".to_string(), -1))
+    );
+    assert_eq!(
+        workspaces.column_at(600.0, 10.0),
+        Some(("output-1".to_string(), 0))
+    );
+    assert_eq!(workspaces.column_at(600.0, 900.0), None);
+
+    workspaces.add_window_to_output_column(7, "output-2", 1);
+    assert_eq!(workspaces.window_output_id(7), Some("output-2"));
+    let rect = workspaces.calculate_workspace_layouts()[&7].clone();
+    assert!(rect.x >= 1900 && rect.x < 2300, "window at x = {}", rect.x);
+}
+
+#[test]
+fn test_output_layout_resolve_applies_matching_rules_in_order() {
+    let rules = vec![
+        OutputRule {
+            model: Some("U3423WE".to_string()),
+            layout: Some("master_stack".to_string()),
+            gaps: Some(4),
+            ..OutputRule::default()
+        },
+        OutputRule {
+            name: Some("DP-1".to_string()),
+            layout: Some("vertical".to_string()),
+            ..OutputRule::default()
+        },
+    ];
+    assert_eq!(
+        OutputLayout::resolve(&rules, "horizontal", "DP-1", "U3423WE"),
+        OutputLayout {
+            layout: "vertical".to_string(),
+            gaps: Some(4),
+        }
+    );
+    // No rule matches: the global default with the configured gaps
+    assert_eq!(
+        OutputLayout::resolve(&rules, "horizontal", "HDMI-A-1", "Generic"),
+        OutputLayout::default()
+    );
+}
+
+/// Two 1000x800 outputs, `DP-1` then `HDMI-A-1`, with `DP-1` tiling with
+/// `layout` and two windows in each output's first column.
+fn two_outputs_with_layout(layout: &str) -> ScrollableWorkspaces {
+    let config = WorkspaceConfig {
+        workspace_width: 600,
+        gaps: 0,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    let outputs = ["DP-1".to_string(), "HDMI-A-1".to_string()];
+    workspaces.sync_tapes_with_outputs(&outputs, &[]);
+    for output in &outputs {
+        workspaces.set_output_viewport(output, 1000.0, 800.0);
+    }
+    let rules = vec![OutputRule {
+        name: Some("DP-1".to_string()),
+        layout: Some(layout.to_string()),
+        ..OutputRule::default()
+    }];
+    let layouts = outputs
+        .iter()
+        .map(|name| {
+            let layout = OutputLayout::resolve(&rules, "horizontal", name, "Generic");
+            (name.clone(), layout)
+        })
+        .collect();
+    workspaces.set_output_layouts("horizontal".to_string(), layouts);
+
+    workspaces.focused_output = "DP-1".to_string();
+    workspaces.add_window(1);
+    workspaces.add_window(2);
+    workspaces.focused_output = "HDMI-A-1".to_string();
+    workspaces.add_window(3);
+    workspaces.add_window(4);
+    workspaces
+}
+
+#[test]
+fn test_window_on_named_output_uses_that_outputs_layout() {
+    let workspaces = two_outputs_with_layout("vertical");
+    assert_eq!(workspaces.output_layout("DP-1").layout, "vertical");
+    let layouts = workspaces.calculate_workspace_layouts();
+
+    // DP-1 puts the pair side by side
+    assert_eq!(
+        layouts[&1],
+        Rectangle {
+            x: 500,
+            y: 0,
+            width: 300,
+            height: 800,
+        }
+    );
+    assert_eq!(layouts[&2].x, 800);
+    assert_eq!(layouts[&2].y, 0);
+
+    // HDMI-A-1 has no rule and keeps the default stacking
+    assert_eq!(
+        layouts[&3],
+        Rectangle {
+            x: 1500,
+            y: 0,
+            width: 600,
+            height: 400,
+        }
+    );
+    assert_eq!(layouts[&4].y, 400);
+}
+
+#[test]
+fn test_master_stack_layout_gives_first_window_the_larger_share() {
+    let mut workspaces = two_outputs_with_layout("master_stack");
+    workspaces.focused_output = "DP-1".to_string();
+    workspaces.add_window(5);
+    let layouts = workspaces.calculate_workspace_layouts();
+
+    assert_eq!(
+        layouts[&1],
+        Rectangle {
+            x: 500,
+            y: 0,
+            width: 360,
+            height: 800,
+        }
+    );
+    for (id, y) in [(2, 0), (5, 400)] {
+        assert_eq!(
+            layouts[&id],
+            Rectangle {
+                x: 860,
+                y,
+                width: 240,
+                height: 400,
+            }
+        );
+    }
+}
+
+#[test]
+fn test_output_gap_override_replaces_configured_gaps() {
+    let mut workspaces = two_outputs_with_layout("horizontal");
+    let mut layouts = HashMap::new();
+    layouts.insert(
+        "HDMI-A-1".to_string(),
+        OutputLayout {
+            layout: "horizontal".to_string(),
+            gaps: Some(10),
+        },
+    );
+    workspaces.set_output_layouts("horizontal".to_string(), layouts);
+    let rects = workspaces.calculate_workspace_layouts();
+
+    assert_eq!(rects[&1].x, 500);
+    assert_eq!(
+        rects[&3],
+        Rectangle {
+            x: 1510,
+            y: 10,
+            width: 580,
+            height: 385,
+        }
+    );
+}
+
+#[test]
+fn test_output_work_area_subtracts_reserved_insets() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.sync_tapes_with_outputs(&["output-1".to_string(), "output-2".to_string()], &[]);
+    workspaces.set_output_viewport("output-1", 1000.0, 800.0);
+    workspaces.set_output_viewport("output-2", 1200.0, 900.0);
+    workspaces.set_reserved_insets(
+        "output-2",
+        ReservedInsets {
+            top: 30,
+            bottom: 40,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        workspaces.output_geometry("output-2"),
+        Some(Rectangle::from_loc_and_size((1000, 0), (1200, 900)))
+    );
+    assert_eq!(
+        workspaces.output_work_area("output-2"),
+        Some(Rectangle::from_loc_and_size((1000, 30), (1200, 830)))
+    );
+    // No insets recorded: work area is the whole output
+    assert_eq!(
+        workspaces.output_work_area("output-1"),
+        Some(Rectangle::from_loc_and_size((0, 0), (1000, 800)))
+    );
+    assert_eq!(workspaces.output_work_area("missing"), None);
+}
+
+#[test]
+fn test_viewport_work_area_with_partial_insets() {
+    let config = WorkspaceConfig {
+        workspace_width: 600,
+        gaps: 0,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.set_output_viewport("default", 1000.0, 800.0);
+    workspaces.add_window(1);
+    assert_eq!(workspaces.calculate_workspace_layouts()[&1].height, 800);
+
+    // A 30px top bar and a 200px dock on the left
+    workspaces.set_reserved_insets(
+        "default",
+        ReservedInsets {
+            top: 30,
+            left: 200,
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        workspaces.viewport_work_area("default"),
+        Some(Rectangle::from_loc_and_size((200, 30), (800, 770)))
+    );
+    // The cached layout is dropped and the column recentred in what is left
+    assert_eq!(
+        workspaces.calculate_workspace_layouts()[&1],
+        Rectangle::from_loc_and_size((600, 30), (600, 770))
+    );
+    assert_eq!(workspaces.viewport_work_area("missing"), None);
+}
+
+#[test]
+fn test_viewport_work_area_clamps_when_insets_consume_viewport() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.set_output_viewport("default", 1000.0, 800.0);
+
+    workspaces.set_reserved_insets(
+        "default",
+        ReservedInsets {
+            top: 500,
+            bottom: 500,
+            left: 1000,
+            ..Default::default()
+        },
+    );
+    let area = workspaces.viewport_work_area("default").unwrap();
+    assert_eq!((area.width, area.height), (0, 0));
+    assert_eq!((area.x, area.y), (1000, 500));
+
+    // Insets large enough to overflow when summed
+    workspaces.set_reserved_insets(
+        "default",
+        ReservedInsets {
+            left: u32::MAX,
+            right: u32::MAX,
+            ..Default::default()
+        },
+    );
+    let area = workspaces.viewport_work_area("default").unwrap();
+    assert_eq!((area.x, area.width, area.height), (1000, 0, 800));
+}
+
+#[test]
+fn test_fullscreen_window_is_excluded_from_layout() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.add_window(1001);
+    workspaces.add_window(1002);
+    let before = workspaces.calculate_workspace_layouts();
+
+    workspaces.set_window_fullscreen(1001, true);
+    let layouts = workspaces.calculate_workspace_layouts();
+    assert!(!layouts.contains_key(&1001));
+    // The remaining window takes the whole column height
+    assert!(layouts[&1002].height > before[&1002].height);
+    // Still owned by its column, so leaving fullscreen re-tiles in place
+    assert!(workspaces.window_exists(1001));
+
+    workspaces.set_window_fullscreen(1001, false);
+    assert_eq!(workspaces.calculate_workspace_layouts(), before);
+}
+
+#[test]
+fn test_column_window_counts_across_populated_and_empty_columns() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.add_window_to_column(1, -2);
+    workspaces.add_window_to_column(2, 0);
+    workspaces.add_window_to_column(3, 0);
+    workspaces.add_window_to_column(4, 3);
+    // Column 3 loses its only window but stays around empty
+    workspaces.remove_window(4);
+
+    let counts = workspaces.column_window_counts();
+    assert_eq!(
+        counts.into_iter().collect::<Vec<_>>(),
+        vec![(-2, 1), (0, 2), (3, 0)]
+    );
+
+    assert_eq!(workspaces.windows_in_column(-2), vec![1]);
+    assert_eq!(workspaces.windows_in_column(0), vec![2, 3]);
+    assert!(workspaces.windows_in_column(3).is_empty());
+    // Querying a column that was never created does not create it
+    assert!(workspaces.windows_in_column(-7).is_empty());
+    assert!(!workspaces.column_window_counts().contains_key(&-7));
+}
+
+#[test]
+fn test_name_column_and_scroll_to_named() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.add_window_to_column(1, 2);
+
+    assert!(workspaces.name_column(2, "web"));
+    assert!(workspaces.name_column(-1, "chat"));
+    assert!(!workspaces.name_column(0, ""));
+    assert_eq!(workspaces.column_name(2), Some("web"));
+
+    assert!(workspaces.scroll_to_named("web"));
+    assert_eq!(workspaces.focused_column_index(), 2);
+    assert!(workspaces.scroll_to_named("chat"));
+    assert_eq!(workspaces.focused_column_index(), -1);
+    assert!(!workspaces.scroll_to_named("missing"));
+    assert_eq!(workspaces.focused_column_index(), -1);
+
+    // Renaming a column replaces its old name
+    workspaces.name_column(2, "code");
+    assert_eq!(workspaces.column_name(2), Some("code"));
+    assert!(!workspaces.scroll_to_named("web"));
+}
+
+#[test]
+fn test_scroll_to_window_scrolls_to_its_column_and_output() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.sync_tapes_with_outputs(&["output-1".to_string(), "output-2".to_string()], &[]);
+    workspaces.add_window_to_output_column(7, "output-2", 3);
+    assert_eq!(workspaces.focused_output(), "output-1");
+
+    assert!(workspaces.scroll_to_window(7));
+    assert_eq!(workspaces.focused_output(), "output-2");
+    assert_eq!(workspaces.focused_column_index(), 3);
+    assert!(workspaces.is_scrolling());
+}
+
+#[test]
+fn test_scroll_to_missing_window_is_a_no_op() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.add_window_to_column(1, 2);
+
+    assert!(!workspaces.scroll_to_window(42));
+    assert_eq!(workspaces.focused_column_index(), 0);
+    assert!(!workspaces.is_scrolling());
+}
+
+#[test]
+fn test_named_empty_column_survives_eviction() {
+    let config = WorkspaceConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.active_tape_mut().ensure_column(4);
+    workspaces.name_column(4, "music");
+
+    // Empty columns are garbage-collected; the name must outlive that
+    workspaces.active_tape_mut().columns.remove(&4);
+    assert!(!workspaces.column_window_counts().contains_key(&4));
+    assert_eq!(workspaces.column_name(4), Some("music"));
+
+    assert!(workspaces.scroll_to_named("music"));
+    assert_eq!(workspaces.focused_column_index(), 4);
+    assert_eq!(workspaces.column_window_counts().get(&4), Some(&0));
+
+    // Names round-trip through a saved session
+    let saved = workspaces.column_names().clone();
+    let mut restored = ScrollableWorkspaces::new(&config);
+    restored.restore_column_names(saved);
+    assert!(restored.scroll_to_named("music"));
+    assert_eq!(restored.focused_column_index(), 4);
+}
+
 #[test]
 fn test_virtual_desktop_size_sums_output_widths() {
     let config = WorkspaceConfig::default();
@@ -1239,5 +2225,324 @@ mod property_tests {
                 "set_output_viewport should create the tape"
             );
         }
+
+        /// Tiles in a column have positive size, never overlap (neighbours
+        /// stay one gap apart, the configured gap whenever it fits), stay
+        /// inside the column, and use up all but the rounding remainder of
+        /// its height.
+        #[test]
+        fn test_column_tiles_fit_without_overlap(
+            count in 1usize..32,
+            gaps in 0u32..64,
+            workspace_width in 100u32..4000,
+            viewport_w in 320u32..7680,
+            viewport_h in 100u32..2160,
+        ) {
+            let config = WorkspaceConfig {
+                workspace_width,
+                gaps,
+                ..WorkspaceConfig::default()
+            };
+            let mut workspaces = ScrollableWorkspaces::new(&config);
+            workspaces.set_viewport_size(viewport_w as f64, viewport_h as f64);
+            for id in 1..=count as u64 {
+                workspaces.add_window(id);
+            }
+
+            let layouts = workspaces.calculate_workspace_layouts();
+            prop_assert_eq!(layouts.len(), count);
+            let mut rects: Vec<Rectangle> = layouts.into_values().collect();
+            rects.sort_by_key(|r| r.y);
+
+            let column_left = (viewport_w as f64 / 2.0) as i32;
+            let gap = rects[0].y;
+            prop_assert!((0..=gaps as i32).contains(&gap));
+            if viewport_h as i64 >= gaps as i64 * (count as i64 + 1) + count as i64 {
+                prop_assert_eq!(gap, gaps as i32);
+            }
+
+            for r in &rects {
+                prop_assert!(r.width > 0 && r.height > 0, "degenerate tile {:?}", r);
+                prop_assert!(r.x >= column_left);
+                prop_assert!(r.x + r.width as i32 <= column_left + workspace_width as i32);
+                prop_assert!(r.y >= 0);
+                prop_assert!(r.y + r.height as i32 <= viewport_h as i32);
+            }
+            for pair in rects.windows(2) {
+                prop_assert_eq!(pair[0].y + pair[0].height as i32 + gap, pair[1].y);
+            }
+            let last = rects.last().unwrap();
+            let leftover = viewport_h as i32 - (last.y + last.height as i32) - gap;
+            prop_assert!(
+                (0..count as i32).contains(&leftover),
+                "{}px of the column left unused",
+                leftover
+            );
+        }
+    }
+}
+
+#[test]
+fn test_column_neighbour_wraps_within_the_column() {
+    let mut workspaces = ScrollableWorkspaces::new(&WorkspaceConfig::default());
+    for id in [1, 2, 3] {
+        workspaces.add_window_to_column(id, 0);
     }
+    workspaces.add_window_to_column(4, 1);
+
+    assert_eq!(workspaces.column_neighbour(1, true), Some(2));
+    assert_eq!(workspaces.column_neighbour(3, true), Some(1));
+    assert_eq!(workspaces.column_neighbour(1, false), Some(3));
+    // Alone in its column, or unknown
+    assert_eq!(workspaces.column_neighbour(4, true), None);
+    assert_eq!(workspaces.column_neighbour(99, true), None);
+}
+
+#[test]
+fn test_move_selected_windows_to_another_column_together() {
+    use crate::config::WindowConfig;
+    use crate::window::WindowManager;
+
+    let mut wm = WindowManager::new(&WindowConfig::default());
+    let mut workspaces = ScrollableWorkspaces::new(&WorkspaceConfig::default());
+    let ids: Vec<u64> = (0..3).map(|i| wm.add_window(format!("w{i}"))).collect();
+    workspaces.add_window_to_column(ids[0], 0);
+    workspaces.add_window_to_column(ids[1], 0);
+    workspaces.add_window_to_column(ids[2], 1);
+
+    wm.add_to_selection(ids[1]);
+    wm.add_to_selection(ids[2]);
+    assert_eq!(workspaces.move_windows_to_column(wm.selected(), 3), 2);
+    assert_eq!(workspaces.windows_in_column(3), vec![ids[1], ids[2]]);
+    assert_eq!(workspaces.windows_in_column(0), vec![ids[0]]);
+    assert!(workspaces.windows_in_column(1).is_empty());
+
+    // Windows the tape doesn't hold are skipped
+    assert_eq!(workspaces.move_windows_to_column(&[999], 3), 0);
+}
+
+#[test]
+fn test_reduce_motion_scroll_to_column_lands_immediately() {
+    let config = WorkspaceConfig::default();
+    let width = config.workspace_width as f64;
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.set_reduce_motion(true);
+
+    workspaces.scroll_to_column(3);
+    assert_eq!(workspaces.current_position(), 3.0 * width);
+    assert_eq!(workspaces.focused_column_index(), 3);
+    assert!(!workspaces.update_animations());
+
+    // A fling steps one column instead of gliding
+    workspaces.start_momentum_scroll(-500.0);
+    assert_eq!(workspaces.current_position(), 2.0 * width);
+
+    // Tapes for outputs that appear later inherit the setting
+    workspaces.sync_tapes_with_outputs(&["default".to_string(), "DP-1".to_string()], &[]);
+    workspaces.focused_output = "DP-1".to_string();
+    workspaces.scroll_right();
+    assert_eq!(workspaces.current_position(), width);
+}
+
+#[test]
+fn test_enabling_reduce_motion_lands_scroll_in_flight() {
+    let config = WorkspaceConfig::default();
+    let width = config.workspace_width as f64;
+    let clock = Arc::new(ManualClock::new());
+    let mut workspaces = ScrollableWorkspaces::with_clock(&config, clock.clone());
+    workspaces.scroll_to_column(2);
+    clock.advance(Duration::from_millis(16));
+    workspaces.update_animations();
+    assert!(workspaces.current_position() < 2.0 * width);
+
+    workspaces.set_reduce_motion(true);
+    assert_eq!(workspaces.current_position(), 2.0 * width);
+}
+
+#[test]
+fn test_sub_threshold_scroll_reuses_cached_layout() {
+    let config = WorkspaceConfig::default();
+    let clock = Arc::new(ManualClock::new());
+    let mut workspaces = ScrollableWorkspaces::with_clock(&config, clock.clone());
+    workspaces.add_window(1);
+    workspaces.scroll_to_column(1);
+    clock.advance(Duration::from_millis(16));
+    workspaces.update_animations();
+    let before = workspaces.calculate_workspace_layouts();
+    let cached_position = |ws: &ScrollableWorkspaces| {
+        ws.cached_layouts.read().as_ref().map(|(key, _)| key.positions[0].0)
+    };
+    let built_at = cached_position(&workspaces);
+
+    // A 0.01px step mid-scroll keeps the layout it was built with
+    workspaces.active_tape_mut().current_position += 0.01;
+    assert_eq!(workspaces.calculate_workspace_layouts(), before);
+    assert_eq!(cached_position(&workspaces), built_at);
+
+    // Past the threshold it is recomputed
+    workspaces.active_tape_mut().current_position += 1.0;
+    workspaces.calculate_workspace_layouts();
+    assert_ne!(cached_position(&workspaces), built_at);
+
+    // At rest every change counts
+    workspaces.set_reduce_motion(true);
+    workspaces.calculate_workspace_layouts();
+    workspaces.active_tape_mut().current_position += 0.01;
+    let landed = cached_position(&workspaces);
+    workspaces.calculate_workspace_layouts();
+    assert_ne!(cached_position(&workspaces), landed);
+}
+
+/// Shows only the first window of a column, filling it.
+struct Single;
+
+impl LayoutEngine for Single {
+    fn arrange(
+        &self,
+        windows: &[u64],
+        bounds: &Rectangle,
+        _inner_gap: u32,
+        _outer_gap: u32,
+    ) -> Vec<(u64, Rectangle)> {
+        windows.first().map(|&id| (id, bounds.clone())).into_iter().collect()
+    }
+}
+
+/// One 1000x800 output with windows 1..=count in a 600px column.
+fn one_column_with(count: u64, layout: &str) -> ScrollableWorkspaces {
+    let config = WorkspaceConfig {
+        workspace_width: 600,
+        gaps: 0,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.set_output_viewport("default", 1000.0, 800.0);
+    workspaces.set_output_layouts(layout.to_string(), HashMap::new());
+    for id in 1..=count {
+        workspaces.add_window(id);
+    }
+    workspaces
+}
+
+#[test]
+fn test_registered_layout_is_selected_by_name() {
+    let mut workspaces = one_column_with(2, "single");
+    assert!(!workspaces.has_layout("single"));
+    // Unknown until registered: tiles like the default
+    assert_eq!(workspaces.calculate_workspace_layouts().len(), 2);
+
+    workspaces.register_layout("single", Arc::new(Single));
+    assert!(workspaces.has_layout("single"));
+    let layouts = workspaces.calculate_workspace_layouts();
+    assert_eq!(layouts.len(), 1);
+    assert_eq!(
+        layouts[&1],
+        Rectangle {
+            x: 500,
+            y: 0,
+            width: 600,
+            height: 800,
+        }
+    );
+}
+
+#[test]
+fn test_grid_and_spiral_layouts() {
+    let rect = |x, y, width, height| Rectangle {
+        x,
+        y,
+        width,
+        height,
+    };
+    // Two per row; the third window has the bottom row to itself
+    let grid = one_column_with(3, "grid").calculate_workspace_layouts();
+    assert_eq!(grid[&1], rect(500, 0, 300, 400));
+    assert_eq!(grid[&2], rect(800, 0, 300, 400));
+    assert_eq!(grid[&3], rect(500, 400, 600, 400));
+
+    // Halve the remaining space, alternating direction
+    let spiral = one_column_with(3, "spiral").calculate_workspace_layouts();
+    assert_eq!(spiral[&1], rect(500, 0, 300, 800));
+    assert_eq!(spiral[&2], rect(800, 0, 300, 400));
+    assert_eq!(spiral[&3], rect(800, 400, 300, 400));
+}
+
+#[test]
+fn test_focusing_off_screen_window_scrolls_its_column_into_view() {
+    let config = WorkspaceConfig {
+        workspace_width: 800,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.set_viewport_size(1600.0, 900.0);
+    workspaces.add_window_to_column(1, 0);
+    workspaces.add_window_to_column(2, -1);
+    workspaces.add_window_to_column(3, 3);
+
+    // The neighbour to the left is already on screen
+    assert!(!workspaces.reveal_window(2));
+    assert!(!workspaces.is_scrolling());
+    assert_eq!(workspaces.focused_column_index(), 0);
+
+    // Three columns away: scroll toward it
+    assert!(workspaces.reveal_window(3));
+    assert!(workspaces.is_scrolling());
+    assert_eq!(workspaces.focused_column_index(), 3);
+    assert_eq!(workspaces.active_tape().target_position, 3.0 * 800.0);
+
+    // Unknown windows don't move anything
+    assert!(!workspaces.reveal_window(99));
+}
+
+#[test]
+fn test_reveal_window_respects_auto_scroll_off() {
+    let config = WorkspaceConfig {
+        auto_scroll: false,
+        ..WorkspaceConfig::default()
+    };
+    let mut workspaces = ScrollableWorkspaces::new(&config);
+    workspaces.add_window_to_column(1, 0);
+    workspaces.add_window_to_column(2, 3);
+
+    assert!(!workspaces.reveal_window(2));
+    assert!(!workspaces.is_scrolling());
+    assert_eq!(workspaces.focused_column_index(), 0);
+}
+
+#[test]
+fn test_column_backgrounds_cross_fade_mid_scroll() {
+    let config = WorkspaceConfig {
+        scroll_easing: EasingFunction::Linear,
+        backgrounds: std::collections::BTreeMap::from([
+            ("0".to_string(), "/walls/dawn.png".to_string()),
+            ("1".to_string(), "/walls/dusk.png".to_string()),
+        ]),
+        ..WorkspaceConfig::default()
+    };
+    let clock = Arc::new(ManualClock::new());
+    let mut workspaces = ScrollableWorkspaces::with_clock(&config, clock.clone());
+
+    let settled = workspaces.background_blend("default").unwrap();
+    assert_eq!(settled.from.as_deref(), Some("/walls/dawn.png"));
+    assert_eq!(settled.mix, 0.0);
+
+    workspaces.scroll_to_column(1);
+    let ScrollState::Scrolling { duration, .. } = workspaces.active_tape().scroll_state else {
+        panic!("scroll_to_column should start a scroll animation");
+    };
+    clock.advance(duration / 2);
+    workspaces.update_animations();
+    let blend = workspaces.background_blend("default").unwrap();
+    assert_eq!(blend.from.as_deref(), Some("/walls/dawn.png"));
+    assert_eq!(blend.to.as_deref(), Some("/walls/dusk.png"));
+    assert!((blend.mix - 0.5).abs() < 1e-6, "mix was {}", blend.mix);
+
+    // Columns without a wallpaper leave the plain background
+    clock.advance(duration);
+    workspaces.update_animations();
+    workspaces.scroll_to_column(3);
+    clock.advance(Duration::from_secs(5));
+    workspaces.update_animations();
+    assert_eq!(workspaces.background_blend("default"), None);
+    assert_eq!(workspaces.background_blend("HDMI-A-1"), None);
 }
@@ -0,0 +1,158 @@
+//! A client disconnected for a protocol error, or banned with
+//! `security.ban_after_errors`, leaves the other clients working.
+
+mod common;
+
+use anyhow::Result;
+use axiom::{compositor::AxiomCompositor, config::AxiomConfig};
+use common::make_headless_compositor;
+use std::os::fd::AsFd;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use wayland_client::{
+    delegate_noop,
+    globals::{registry_queue_init, GlobalList, GlobalListContents},
+    protocol::{wl_buffer, wl_compositor, wl_registry, wl_shm, wl_shm_pool, wl_surface},
+    Connection, Dispatch, EventQueue, QueueHandle,
+};
+
+struct TestClient;
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for TestClient {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(TestClient: ignore wl_compositor::WlCompositor);
+delegate_noop!(TestClient: ignore wl_surface::WlSurface);
+delegate_noop!(TestClient: ignore wl_shm::WlShm);
+delegate_noop!(TestClient: ignore wl_shm_pool::WlShmPool);
+delegate_noop!(TestClient: ignore wl_buffer::WlBuffer);
+
+fn connect() -> Result<(Connection, GlobalList, EventQueue<TestClient>)> {
+    let conn = Connection::connect_to_env()?;
+    let (globals, queue) = registry_queue_init::<TestClient>(&conn)?;
+    Ok((conn, globals, queue))
+}
+
+/// Connect and send a buffer with a negative width. Returns the protocol
+/// error the compositor answered with, if any.
+fn send_invalid_buffer() -> Result<Option<String>> {
+    let (conn, globals, mut queue) = connect()?;
+    let qh = queue.handle();
+    let shm: wl_shm::WlShm = globals.bind(&qh, 1..=1, ())?;
+    let file = tempfile::tempfile()?;
+    file.set_len(4096)?;
+    let pool = shm.create_pool(file.as_fd(), 4096, &qh, ());
+    let _buffer = pool.create_buffer(0, -16, 16, 64, wl_shm::Format::Argb8888, &qh, ());
+    let _ = queue.roundtrip(&mut TestClient);
+    Ok(conn.protocol_error().map(|e| e.message))
+}
+
+/// Create and commit a surface on an existing connection.
+fn create_surface(globals: &GlobalList, queue: &mut EventQueue<TestClient>) -> Result<()> {
+    let qh = queue.handle();
+    let compositor: wl_compositor::WlCompositor = globals.bind(&qh, 1..=4, ())?;
+    let surface = compositor.create_surface(&qh, ());
+    surface.commit();
+    queue.roundtrip(&mut TestClient)?;
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Outcome {
+    bad_client_error: Option<String>,
+    good_client: Result<(), String>,
+    reconnect: Result<(), String>,
+}
+
+/// A well-behaved client connects, a second one sends an invalid request,
+/// then the first creates a surface and the misbehaving process tries to
+/// connect again.
+fn run_clients() -> Result<Outcome> {
+    let (_good_conn, good_globals, mut good_queue) = connect()?;
+    let bad_client_error = send_invalid_buffer()?;
+    let good_client = create_surface(&good_globals, &mut good_queue).map_err(|e| e.to_string());
+    let reconnect = connect()
+        .and_then(|(_conn, _globals, mut queue)| {
+            Ok(queue.roundtrip(&mut TestClient).map(|_| ())?)
+        })
+        .map_err(|e| e.to_string());
+    Ok(Outcome {
+        bad_client_error,
+        good_client,
+        reconnect,
+    })
+}
+
+/// Tick `compositor` while the clients run, returning what they saw.
+fn run_against(compositor: &mut AxiomCompositor) -> Result<Outcome> {
+    let socket_name = format!("wayland-axiom-{}", std::process::id());
+    std::env::set_var("WAYLAND_DISPLAY", &socket_name);
+
+    let (tx, rx) = mpsc::channel();
+    let clients = thread::spawn(move || {
+        let _ = tx.send(run_clients().map_err(|e| e.to_string()));
+    });
+
+    let mut outcome = None;
+    for _ in 0..400 {
+        compositor.tick_for_test()?;
+        if let Ok(result) = rx.try_recv() {
+            outcome = Some(result);
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    let _ = clients.join();
+    outcome
+        .expect("clients did not finish")
+        .map_err(|e| anyhow::anyhow!("client error: {e}"))
+}
+
+#[test]
+#[serial_test::serial]
+fn test_invalid_request_disconnects_only_that_client() -> Result<()> {
+    let (mut compositor, _) = make_headless_compositor(AxiomConfig::default())?;
+    let outcome = run_against(&mut compositor)?;
+
+    assert!(
+        outcome.bad_client_error.is_some(),
+        "invalid request was not answered with a protocol error: {outcome:?}"
+    );
+    assert_eq!(outcome.good_client, Ok(()), "{outcome:?}");
+    // No ban configured: the process may come back
+    assert_eq!(outcome.reconnect, Ok(()), "{outcome:?}");
+
+    // And the compositor is still running
+    compositor.tick_for_test()?;
+    assert!(compositor.is_running());
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_misbehaving_process_is_refused_when_banned() -> Result<()> {
+    let mut config = AxiomConfig::default();
+    config.security.ban_after_errors = 1;
+    let (mut compositor, _) = make_headless_compositor(config)?;
+    let outcome = run_against(&mut compositor)?;
+
+    assert!(outcome.bad_client_error.is_some(), "{outcome:?}");
+    // Connections made before the ban are left alone
+    assert_eq!(outcome.good_client, Ok(()), "{outcome:?}");
+    assert!(
+        outcome.reconnect.is_err(),
+        "banned process was let back in: {outcome:?}"
+    );
+    Ok(())
+}
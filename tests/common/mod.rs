@@ -0,0 +1,36 @@
+//! Shared setup for the headless client tests.
+//!
+//! The compositor runs in-process on the Noop backend and is ticked from
+//! the test thread; clients run on a worker thread because the
+//! `wayland-client` API blocks on round trips.
+
+use anyhow::Result;
+use axiom::{
+    compositor::AxiomCompositor, config::AxiomConfig, input::InputManager, ipc::AxiomIPCServer,
+    window::WindowManager, workspace::ScrollableWorkspaces,
+};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// A compositor on the Noop backend built from `config`, and its window
+/// manager for the test to inspect.
+pub fn make_headless_compositor(
+    mut config: AxiomConfig,
+) -> Result<(AxiomCompositor, Arc<RwLock<WindowManager>>)> {
+    config.backend.kind = "noop".to_string();
+    let workspace_manager = Arc::new(RwLock::new(ScrollableWorkspaces::new(&config.workspace)));
+    let window_manager = Arc::new(RwLock::new(WindowManager::new(&config.window)));
+    let input_manager = Arc::new(RwLock::new(InputManager::new(
+        &config.input,
+        &config.bindings,
+    )));
+    let compositor = AxiomCompositor::new(
+        config,
+        false,
+        workspace_manager,
+        window_manager.clone(),
+        input_manager,
+        AxiomIPCServer::new(),
+    )?;
+    Ok((compositor, window_manager))
+}
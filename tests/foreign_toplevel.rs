@@ -0,0 +1,240 @@
+//! Taskbars see every window through `zwlr_foreign_toplevel_manager_v1`
+//! and can activate one through its handle.
+
+mod common;
+
+use anyhow::Result;
+use axiom::config::AxiomConfig;
+use common::make_headless_compositor;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use wayland_client::{
+    backend::ObjectId,
+    delegate_noop,
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{wl_compositor, wl_registry, wl_seat, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+const TITLES: [&str; 2] = ["first", "second"];
+
+/// What a handle has told us so far.
+#[derive(Default)]
+struct Toplevel {
+    handle: Option<ZwlrForeignToplevelHandleV1>,
+    title: String,
+    activated: bool,
+    /// Whether a `done` followed the first batch of events
+    done: bool,
+}
+
+#[derive(Default)]
+struct TestClient {
+    toplevels: HashMap<ObjectId, Toplevel>,
+}
+
+impl TestClient {
+    /// The described toplevels, once one for each of our windows is.
+    fn described(&self) -> Option<Vec<&Toplevel>> {
+        let described: Vec<&Toplevel> = self.toplevels.values().filter(|t| t.done).collect();
+        (described.len() == TITLES.len()).then_some(described)
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for TestClient {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(TestClient: ignore wl_compositor::WlCompositor);
+delegate_noop!(TestClient: ignore wl_surface::WlSurface);
+delegate_noop!(TestClient: ignore wl_seat::WlSeat);
+delegate_noop!(TestClient: ignore xdg_toplevel::XdgToplevel);
+
+impl Dispatch<xdg_wm_base::XdgWmBase, ()> for TestClient {
+    fn event(
+        _: &mut Self,
+        wm_base: &xdg_wm_base::XdgWmBase,
+        event: xdg_wm_base::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<xdg_surface::XdgSurface, ()> for TestClient {
+    fn event(
+        _: &mut Self,
+        xdg_surface: &xdg_surface::XdgSurface,
+        event: xdg_surface::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_surface::Event::Configure { serial } = event {
+            xdg_surface.ack_configure(serial);
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for TestClient {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+            state.toplevels.entry(toplevel.id()).or_default().handle = Some(toplevel);
+        }
+    }
+
+    wayland_client::event_created_child!(TestClient, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for TestClient {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        use zwlr_foreign_toplevel_handle_v1::{Event, State};
+
+        let toplevel = state.toplevels.entry(handle.id()).or_default();
+        match event {
+            Event::Title { title } => toplevel.title = title,
+            Event::State { state: states } => {
+                toplevel.activated = states
+                    .chunks_exact(4)
+                    .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                    .any(|s| s == State::Activated as u32);
+            }
+            Event::Done => toplevel.done = true,
+            Event::Closed => {
+                state.toplevels.remove(&handle.id());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Open a toplevel per entry of `TITLES`, wait until the taskbar lists
+/// both and one is active, then activate the other. Returns the title of
+/// the window activated once the compositor reports it active.
+fn activate_inactive_window() -> Result<String> {
+    let conn = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<TestClient>(&conn)?;
+    let qh = queue.handle();
+    let mut client = TestClient::default();
+
+    let compositor: wl_compositor::WlCompositor = globals.bind(&qh, 1..=4, ())?;
+    let wm_base: xdg_wm_base::XdgWmBase = globals.bind(&qh, 1..=1, ())?;
+    let seat: wl_seat::WlSeat = globals.bind(&qh, 1..=7, ())?;
+    let _manager: ZwlrForeignToplevelManagerV1 = globals.bind(&qh, 1..=3, ())?;
+
+    let mut windows = Vec::new();
+    for title in TITLES {
+        let surface = compositor.create_surface(&qh, ());
+        let xdg_surface = wm_base.get_xdg_surface(&surface, &qh, ());
+        let toplevel = xdg_surface.get_toplevel(&qh, ());
+        toplevel.set_title(title.to_string());
+        surface.commit();
+        windows.push((surface, xdg_surface, toplevel));
+    }
+
+    let mut target = None;
+    for _ in 0..400 {
+        queue.roundtrip(&mut client)?;
+        let inactive = client
+            .described()
+            .filter(|described| described.iter().any(|t| t.activated))
+            .and_then(|described| described.into_iter().find(|t| !t.activated));
+        if let Some(toplevel) = inactive {
+            toplevel.handle.as_ref().unwrap().activate(&seat);
+            target = Some(toplevel.title.clone());
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    let title =
+        target.ok_or_else(|| anyhow::anyhow!("taskbar never saw both windows with one active"))?;
+
+    for _ in 0..400 {
+        queue.roundtrip(&mut client)?;
+        if client
+            .toplevels
+            .values()
+            .any(|t| t.title == title && t.activated)
+        {
+            return Ok(title);
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    anyhow::bail!("activated window never reported active")
+}
+
+#[test]
+#[serial_test::serial]
+fn test_taskbar_lists_windows_and_activates_one() -> Result<()> {
+    let (mut compositor, window_manager) = make_headless_compositor(AxiomConfig::default())?;
+    let socket_name = format!("wayland-axiom-{}", std::process::id());
+    std::env::set_var("WAYLAND_DISPLAY", &socket_name);
+
+    let (tx, rx) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        let _ = tx.send(activate_inactive_window().map_err(|e| e.to_string()));
+    });
+
+    let mut focused = false;
+    let mut outcome = None;
+    for _ in 0..2000 {
+        compositor.tick_for_test()?;
+        if !focused && window_manager.read().window_count() == TITLES.len() {
+            compositor.debug_focus_first_client_for_test();
+            focused = true;
+        }
+        if let Ok(result) = rx.try_recv() {
+            outcome = Some(result);
+            break;
+        }
+        thread::sleep(Duration::from_millis(2));
+    }
+    let _ = handle.join();
+    let title = outcome
+        .expect("client did not finish")
+        .map_err(|e| anyhow::anyhow!("client error: {e}"))?;
+
+    let wm = window_manager.read();
+    let focused_title = wm
+        .focused_window_id()
+        .and_then(|id| wm.get_window(id))
+        .map(|w| w.window.title.clone());
+    assert_eq!(focused_title, Some(title));
+    Ok(())
+}
@@ -43,6 +43,9 @@ fn test_ipc_message_protocol() -> Result<()> {
         frame_time: 16.67,
         active_windows: 5,
         current_workspace: 2,
+        frames_presented: 0,
+        frames_dropped: 0,
+        surface_lost: 0,
     };
 
     let json = serde_json::to_string(&perf_message)?;
@@ -562,6 +565,45 @@ fn test_ipc_optimize_config_flow() -> Result<()> {
     Ok(())
 }
 
+/// Test IPC SetConfig adjusts the output colour live and ignores
+/// out-of-range values
+#[test]
+fn test_ipc_set_output_color_adjustment() -> Result<()> {
+    use axiom::config::AxiomConfig;
+    use axiom::ipc::LazyUIMessage;
+
+    let mut config = AxiomConfig::default();
+    let mut ipc_server = AxiomIPCServer::new();
+
+    ipc_server.start()?;
+
+    let sender = ipc_server.command_sender_for_test();
+    for (key, value) in [
+        ("output.saturation", 0.0),
+        ("output.brightness", 0.8),
+        ("output.contrast", 3.0),
+    ] {
+        sender
+            .send(LazyUIMessage::SetConfig {
+                key: key.into(),
+                value: serde_json::json!(value),
+            })
+            .unwrap();
+    }
+
+    let (changed, actions) = ipc_server.process_messages(&mut config)?;
+
+    assert!(changed, "SetConfig should change config");
+    assert!(actions.is_empty(), "no pending actions for config changes");
+    assert_eq!(config.output.saturation, 0.0);
+    assert!((config.output.brightness - 0.8).abs() < f32::EPSILON);
+    assert_eq!(config.output.contrast, 1.0, "out-of-range contrast ignored");
+
+    ipc_server.shutdown_sync();
+
+    Ok(())
+}
+
 /// Test that SetClipboard IPC message is correctly forwarded
 #[test]
 fn test_ipc_set_clipboard_flow() -> Result<()> {
@@ -635,6 +677,109 @@ fn test_compositor_set_clipboard_dispatch() -> Result<()> {
     Ok(())
 }
 
+/// Test that switching config profiles, over IPC or on a power source
+/// change, swaps the active effect settings
+#[test]
+#[serial_test::serial]
+fn test_compositor_profile_switch_changes_effects() -> Result<()> {
+    use axiom::config::AxiomConfig;
+    use axiom::ipc::LazyUIMessage;
+
+    let config: AxiomConfig = toml::from_str(
+        r#"
+[effects]
+dim_inactive = { enabled = true }
+
+[profiles.battery.effects]
+reduce_motion = true
+dim_inactive = { enabled = false }
+
+[profiles.docked.effects]
+focus_transition_ms = 300
+
+[auto_profile]
+on_ac = "docked"
+"#,
+    )?;
+    let (mut compositor, _ws, _wm, _im) = make_test_compositor(config)?;
+    assert!(!compositor.config().effects.reduce_motion);
+
+    let sender = compositor.ipc_command_sender();
+    sender
+        .send(LazyUIMessage::SetProfile {
+            name: "battery".into(),
+        })
+        .unwrap();
+    compositor.tick_for_test()?;
+    assert_eq!(compositor.active_profile(), Some("battery"));
+    assert!(compositor.config().effects.reduce_motion);
+    assert!(!compositor.config().effects.dim_inactive.enabled);
+
+    // The profile picked at startup survives the first power reading, but
+    // plugging in later switches to the AC profile, dropping battery's
+    // settings
+    compositor.power_source_changed(Some(false));
+    assert_eq!(compositor.active_profile(), Some("battery"));
+    compositor.power_source_changed(Some(true));
+    assert_eq!(compositor.active_profile(), Some("battery"));
+    compositor.power_source_changed(Some(false));
+    assert_eq!(compositor.active_profile(), Some("docked"));
+    let effects = &compositor.config().effects;
+    assert!(!effects.reduce_motion);
+    assert!(effects.dim_inactive.enabled);
+    assert_eq!(effects.focus_transition_ms, 300);
+
+    assert!(compositor.set_profile("missing").is_err());
+    assert_eq!(compositor.active_profile(), Some("docked"));
+
+    Ok(())
+}
+
+/// Test that a WindowOp sent over the IPC socket is acknowledged and
+/// applied to the window it names
+#[test]
+#[serial_test::serial]
+fn test_compositor_window_op_minimize() -> Result<()> {
+    use axiom::config::AxiomConfig;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let config = AxiomConfig::default();
+    let (mut compositor, ws, wm, _im) = make_test_compositor(config)?;
+    let id = wm.write().add_window("target".into());
+    ws.write().add_window(id);
+    // One tick so the IPC server learns the window exists
+    compositor.tick_for_test()?;
+
+    let mut client = UnixStream::connect(compositor.ipc_socket_path())?;
+    client.set_read_timeout(Some(std::time::Duration::from_secs(2)))?;
+    writeln!(
+        client,
+        r#"{{"type":"WindowOp","window_id":{},"op":{{"kind":"minimize"}}}}"#,
+        id
+    )?;
+    // Accept, read and acknowledge, then dispatch to the window manager
+    compositor.tick_for_test()?;
+    compositor.tick_for_test()?;
+
+    assert!(wm.read().is_minimized(id), "window should be minimized");
+
+    let mut reader = BufReader::new(client);
+    let ack = (0..16)
+        .filter_map(|_| {
+            let mut line = String::new();
+            reader.read_line(&mut line).ok()?;
+            serde_json::from_str::<serde_json::Value>(&line).ok()
+        })
+        .find(|msg| msg["event_type"] == "WindowOpAck")
+        .expect("WindowOp should be acknowledged");
+    assert_eq!(ack["details"]["window_id"], id);
+    assert_eq!(ack["details"]["op"]["kind"], "minimize");
+    assert_eq!(ack["details"]["accepted"], true);
+
+    Ok(())
+}
+
 /// Test that StartDnd IPC command is forwarded through the pipeline
 #[test]
 #[serial_test::serial]
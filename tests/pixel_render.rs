@@ -226,11 +226,13 @@ fn run_client(done: Arc<AtomicBool>, result_tx: mpsc::Sender<String>) {
     done.store(true, Ordering::SeqCst);
 }
 
-#[test]
-#[ignore]
-#[serial_test::serial]
-fn test_pixel_render_shows_client_and_respects_titlebar() -> Result<()> {
-    let mut config = AxiomConfig::default();
+/// Start the Winit backend with `config`, run the red client of
+/// [`run_client`] against it and tick until a frame has over 2000 pixels
+/// `is_client` accepts. Returns the backend with that frame.
+fn composite_client(
+    mut config: AxiomConfig,
+    is_client: fn(u8, u8, u8, u8) -> bool,
+) -> Result<(AxiomSmithayBackendReal, u32, u32, Vec<u8>)> {
     // Use the real Winit/GL backend (the only one that actually renders pixels).
     config.backend.kind = "winit".to_string();
 
@@ -266,14 +268,16 @@ fn test_pixel_render_shows_client_and_respects_titlebar() -> Result<()> {
     let client_handle = thread::spawn(move || run_client(client_done, tx));
 
     // Tick the compositor: accept the client, dispatch, render. Capture once
-    // the client's red buffer has actually been composited.
+    // the client's buffer has actually been composited.
     let captured: Option<(u32, u32, Vec<u8>)> = {
         let mut captured: Option<(u32, u32, Vec<u8>)> = None;
         for _ in 0..240 {
             backend.run_one_cycle()?;
             if let Some((cw, ch, px)) = backend.capture_pixels() {
-                // Count red pixels inline to decide when we have a real frame.
-                if px.len() == (cw as usize) * (ch as usize) * 4 && red_pixel_count(&px) > 2000 {
+                // Count client pixels inline to decide when we have a real frame.
+                if px.len() == (cw as usize) * (ch as usize) * 4
+                    && pixel_count(&px, is_client) > 2000
+                {
                     captured = Some((cw, ch, px));
                     break;
                 }
@@ -289,9 +293,17 @@ fn test_pixel_render_shows_client_and_respects_titlebar() -> Result<()> {
 
     let (w, h, pixels) = captured.ok_or_else(|| {
         anyhow::anyhow!(
-            "capture_pixels returned None / no red frame — no GL frame available (display/GL missing?)"
+            "capture_pixels returned None / no client frame — no GL frame available (display/GL missing?)"
         )
     })?;
+    Ok((backend, w, h, pixels))
+}
+
+#[test]
+#[ignore]
+#[serial_test::serial]
+fn test_pixel_render_shows_client_and_respects_titlebar() -> Result<()> {
+    let (mut backend, w, h, pixels) = composite_client(AxiomConfig::default(), is_red)?;
 
     assert_eq!(
         pixels.len(),
@@ -364,6 +376,89 @@ fn test_pixel_render_shows_client_and_respects_titlebar() -> Result<()> {
         band_fraction * 100.0
     );
 
+    // 5) A sub-region capture over the client returns just that rect,
+    //    top row first, all client-red.
+    let (sub_x, sub_y) = (min_x as i32 + 8, (min_dy as i32 + max_dy as i32) / 2);
+    let (sub, sub_w, sub_h) = backend.capture_region(sub_x, sub_y, 24, 16)?;
+    assert_eq!((sub_w, sub_h), (24, 16), "sub-region capture has wrong size");
+    assert_eq!(sub.len(), 24 * 16 * 4, "sub-region buffer is not tightly packed");
+    assert_eq!(
+        pixel_count(&sub, is_red),
+        24 * 16,
+        "sub-region over the client is not entirely client-red"
+    );
+
+    // A rect hanging off the top-left corner is clamped to the framebuffer
+    let (_, clamped_w, clamped_h) = backend.capture_region(-10, -10, 20, 30)?;
+    assert_eq!((clamped_w, clamped_h), (10, 20));
+
+    Ok(())
+}
+
+#[test]
+#[ignore]
+#[serial_test::serial]
+fn test_layered_compositing_runs_post_pass_before_blit() -> Result<()> {
+    // Layered mode with a half-brightness pass: the red client must reach
+    // the screen at half intensity, and nothing at full
+    let mut config = AxiomConfig::default();
+    config.output.layered_compositing = true;
+    config.output.brightness = 0.5;
+    let (_backend, _, _, pixels) = composite_client(config, is_half_red)?;
+
+    assert!(pixel_count(&pixels, is_half_red) > 2000);
+    assert_eq!(
+        pixel_count(&pixels, is_red),
+        0,
+        "full-intensity red reached the screen — the post-process pass did not run"
+    );
+    Ok(())
+}
+
+#[test]
+#[ignore]
+#[serial_test::serial]
+fn test_rounded_corner_edge_is_feathered() -> Result<()> {
+    let mut config = AxiomConfig::default();
+    config.decoration.corner_radius = 24.0;
+    config.decoration.corner_softness = 2.0;
+    let (_backend, w, h, pixels) = composite_client(config, is_red)?;
+
+    // Display-space pixel, with readback rows bottom-up
+    let at = |x: u32, y: u32| {
+        let o = (((h - 1 - y) * w + x) * 4) as usize;
+        (pixels[o], pixels[o + 1], pixels[o + 2], pixels[o + 3])
+    };
+    let (mut min_x, mut max_y) = (u32::MAX, 0);
+    for y in 0..h {
+        for x in 0..w {
+            let (r, g, b, a) = at(x, y);
+            if is_red(r, g, b, a) {
+                min_x = min_x.min(x);
+                max_y = max_y.max(y);
+            }
+        }
+    }
+    assert!(min_x < w, "no client pixels found");
+
+    // The client's bottom-left corner is cut away entirely...
+    let (r, g, b, a) = at(min_x, max_y);
+    assert!(
+        !is_red(r, g, b, a) && !is_partly_red(r, g, b, a),
+        "corner pixel is red ({r}, {g}, {b}) — the corner was not rounded"
+    );
+    // ...and its curve fades in over intermediate values, not a hard step
+    let partly_red = (0..24)
+        .flat_map(|dx| (0..24).map(move |dy| (min_x + dx, max_y - dy)))
+        .filter(|&(x, y)| {
+            let (r, g, b, a) = at(x, y);
+            is_partly_red(r, g, b, a)
+        })
+        .count();
+    assert!(
+        partly_red >= 8,
+        "only {partly_red} partly red pixels along the rounded corner — edge is aliased"
+    );
     Ok(())
 }
 
@@ -372,11 +467,24 @@ fn is_red(r: u8, g: u8, b: u8, a: u8) -> bool {
     r >= 180 && g <= 80 && b <= 80 && a >= 128
 }
 
-fn red_pixel_count(px: &[u8]) -> usize {
+/// Client red blended part way into what is behind it: redder than grey,
+/// short of [`is_red`].
+#[inline]
+fn is_partly_red(r: u8, g: u8, b: u8, a: u8) -> bool {
+    !is_red(r, g, b, a) && r >= g.saturating_add(40) && r >= b.saturating_add(40)
+}
+
+/// Client red after a half-brightness pass.
+#[inline]
+fn is_half_red(r: u8, g: u8, b: u8, a: u8) -> bool {
+    (112..=144).contains(&r) && g <= 40 && b <= 40 && a >= 128
+}
+
+fn pixel_count(px: &[u8], matches: fn(u8, u8, u8, u8) -> bool) -> usize {
     let mut n = 0;
     let mut i = 0;
     while i + 3 < px.len() {
-        if is_red(px[i], px[i + 1], px[i + 2], px[i + 3]) {
+        if matches(px[i], px[i + 1], px[i + 2], px[i + 3]) {
             n += 1;
         }
         i += 4;
@@ -14,8 +14,12 @@
 
 use anyhow::Result;
 use axiom::{
-    compositor::AxiomCompositor, config::AxiomConfig, input::InputManager, ipc::AxiomIPCServer,
-    window::WindowManager, workspace::ScrollableWorkspaces,
+    compositor::AxiomCompositor,
+    config::{AxiomConfig, WindowRule},
+    input::InputManager,
+    ipc::AxiomIPCServer,
+    window::WindowManager,
+    workspace::ScrollableWorkspaces,
 };
 use parking_lot::RwLock;
 use std::os::fd::AsFd;
@@ -62,6 +66,9 @@ fn make_headless_compositor(
     Ok((compositor, window_manager))
 }
 
+/// The app_id the test client gives its toplevel.
+const SMOKE_APP_ID: &str = "axiom-smoke";
+
 struct ClientState {
     compositor: Option<wl_compositor::WlCompositor>,
     shm: Option<wl_shm::WlShm>,
@@ -83,7 +90,10 @@ impl ClientState {
 
         let surface = compositor.create_surface(qh, ());
         let xdg_surface = wm_base.get_xdg_surface(&surface, qh, ());
-        let _toplevel = xdg_surface.get_toplevel(qh, ());
+        // Like real clients, name the toplevel only after creating it
+        let toplevel = xdg_surface.get_toplevel(qh, ());
+        toplevel.set_title("Axiom smoke test".to_string());
+        toplevel.set_app_id(SMOKE_APP_ID.to_string());
         self.toplevel_created = true;
         surface.commit();
         self.surface = Some(surface);
@@ -242,17 +252,12 @@ fn run_client(done: Arc<AtomicBool>, result_tx: mpsc::Sender<String>) {
     done.store(true, Ordering::SeqCst);
 }
 
-#[test]
-#[serial_test::serial]
-fn test_real_client_connects_and_maps_toplevel() -> Result<()> {
-    let config = AxiomConfig::default();
-    let (mut compositor, window_manager) = make_headless_compositor(config)?;
-
-    // Point the client at the compositor's socket. `AxiomSmithayBackendReal::new`
-    // binds `wayland-axiom-<pid>` in XDG_RUNTIME_DIR, so we expose it via
-    // WAYLAND_DISPLAY before the client connects.
-    let socket_name = format!("wayland-axiom-{}", std::process::id());
-    std::env::set_var("WAYLAND_DISPLAY", &socket_name);
+/// Run [`run_client`] against `compositor`, ticking it until the client is
+/// done. Returns the client's report.
+fn connect_client(compositor: &mut AxiomCompositor) -> Result<String> {
+    // Point the client at the compositor's socket, bound in XDG_RUNTIME_DIR,
+    // by exposing its name via WAYLAND_DISPLAY before the client connects.
+    std::env::set_var("WAYLAND_DISPLAY", compositor.socket_name());
 
     let done = Arc::new(AtomicBool::new(false));
     let (tx, rx) = mpsc::channel();
@@ -270,7 +275,16 @@ fn test_real_client_connects_and_maps_toplevel() -> Result<()> {
     // Give the client thread a chance to report once `done` is set.
     let client_msg = rx.recv_timeout(Duration::from_secs(5)).unwrap_or_default();
     let _ = client_handle.join();
+    Ok(client_msg)
+}
 
+#[test]
+#[serial_test::serial]
+fn test_real_client_connects_and_maps_toplevel() -> Result<()> {
+    let config = AxiomConfig::default();
+    let (mut compositor, window_manager) = make_headless_compositor(config)?;
+
+    let client_msg = connect_client(&mut compositor)?;
     assert_eq!(client_msg, "ok", "Wayland client failed: {client_msg}");
 
     let count = window_manager.read().window_count();
@@ -281,3 +295,79 @@ fn test_real_client_connects_and_maps_toplevel() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[serial_test::serial]
+fn test_real_client_window_records_client_process() -> Result<()> {
+    let config = AxiomConfig::default();
+    let (mut compositor, window_manager) = make_headless_compositor(config)?;
+
+    let client_msg = connect_client(&mut compositor)?;
+    assert_eq!(client_msg, "ok", "Wayland client failed: {client_msg}");
+
+    // The client runs on a thread of this process, so its connection's
+    // credentials are ours
+    let wm = window_manager.read();
+    let id = *wm.window_ids().first().expect("client toplevel is tracked");
+    let client = wm
+        .get_window(id)
+        .and_then(|window| window.client.clone())
+        .expect("window records its client process");
+    assert_eq!(client.pid, std::process::id() as i32);
+    let exe = std::env::current_exe()?;
+    assert_eq!(
+        client.executable.as_deref(),
+        exe.file_name().and_then(|name| name.to_str())
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_real_client_connects_on_named_socket() -> Result<()> {
+    let name = format!("wayland-axiom-named-{}", std::process::id());
+    let mut config = AxiomConfig::default();
+    config.backend.socket_name = Some(name.clone());
+    let (mut compositor, window_manager) = make_headless_compositor(config.clone())?;
+    assert_eq!(compositor.socket_name(), name);
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR")?;
+    assert!(std::path::Path::new(&runtime_dir).join(&name).exists());
+
+    let client_msg = connect_client(&mut compositor)?;
+    assert_eq!(client_msg, "ok", "Wayland client failed: {client_msg}");
+    assert!(window_manager.read().window_count() >= 1);
+
+    // A second compositor can't take a name that is in use
+    let err = match make_headless_compositor(config) {
+        Ok(_) => panic!("binding a socket name in use should fail"),
+        Err(e) => format!("{e:#}"),
+    };
+    assert!(err.contains("already in use"), "unexpected error: {err}");
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_real_client_window_rules_match_app_id_set_after_creation() -> Result<()> {
+    let mut config = AxiomConfig::default();
+    config.window_rules.push(WindowRule {
+        app_id: Some(SMOKE_APP_ID.to_string()),
+        opacity: Some(0.5),
+        blur: Some(false),
+        ..Default::default()
+    });
+    let (mut compositor, window_manager) = make_headless_compositor(config)?;
+
+    let client_msg = connect_client(&mut compositor)?;
+    assert_eq!(client_msg, "ok", "Wayland client failed: {client_msg}");
+
+    let wm = window_manager.read();
+    let id = *wm.window_ids().first().expect("client toplevel is tracked");
+    let window = wm.get_window(id).expect("window exists");
+    assert_eq!(window.properties.opacity, 0.5);
+    assert!(!window.properties.blur);
+
+    Ok(())
+}
@@ -223,6 +223,75 @@ fn test_stress_rapid_create_destroy() -> Result<()> {
     Ok(())
 }
 
+/// Soak test: thousands of open/close cycles with windows spread across
+/// columns and in every state. Nothing should be left behind in the
+/// window manager's or workspace's per-window bookkeeping, and the column
+/// set must not grow with the number of windows ever opened.
+#[test]
+fn test_soak_window_churn_leaves_no_stale_state() -> Result<()> {
+    let config = AxiomConfig::default();
+    let mut workspaces = ScrollableWorkspaces::new(&config.workspace);
+    let window_config = axiom::config::WindowConfig::default();
+    let mut wm = WindowManager::new(&window_config);
+
+    let mut open: Vec<u64> = Vec::new();
+    for i in 0..5000u64 {
+        let id = wm.add_window(format!("Soak {}", i));
+        workspaces.add_window(id);
+        open.push(id);
+        match i % 7 {
+            1 => {
+                wm.minimize_window(id);
+                workspaces.minimize_window(id);
+            }
+            2 => workspaces.set_window_floating(id, true),
+            3 => workspaces.set_window_fullscreen(id, true),
+            4 => wm.focus_window(id),
+            _ => {}
+        }
+        // Wander over a few columns so windows don't all share one
+        if i % 3 == 0 {
+            if (i / 3) % 8 < 4 {
+                workspaces.scroll_right();
+            } else {
+                workspaces.scroll_left();
+            }
+        }
+        // Keep a handful open; close the oldest (sometimes the focused one)
+        if open.len() > 8 {
+            let victim = open.remove(0);
+            workspaces.remove_window(victim);
+            assert!(wm.remove_window(victim).is_some(), "window {} known", victim);
+        }
+        assert!(
+            wm.focus_history().iter().all(|id| wm.get_window(*id).is_some()),
+            "focus history references a closed window at iteration {}",
+            i
+        );
+    }
+    assert!(
+        workspaces.active_column_count() <= 8,
+        "column set grew to {} columns",
+        workspaces.active_column_count()
+    );
+
+    for id in open.drain(..) {
+        workspaces.remove_window(id);
+        wm.remove_window(id);
+    }
+
+    assert_eq!(wm.window_count(), 0);
+    assert!(wm.get_windows_by_render_order().is_empty());
+    assert!(wm.focus_history().is_empty());
+    assert_eq!(wm.focused_window_id(), None);
+    assert!(wm.minimized_ids().is_empty());
+    assert_eq!(workspaces.minimized_window_count(), 0);
+    assert!(workspaces.floating_window_ids().is_empty());
+    assert!(workspaces.calculate_workspace_layouts().is_empty());
+
+    Ok(())
+}
+
 /// Test workspace switching with many windows distributed across columns.
 #[test]
 fn test_stress_workspace_switch() -> Result<()> {
@@ -0,0 +1,334 @@
+//! Text an input method commits through `zwp_input_method_v2` reaches the
+//! focused client's `zwp_text_input_v3`.
+
+mod common;
+
+use std::os::unix::io::AsRawFd;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use axiom::config::AxiomConfig;
+use common::make_headless_compositor;
+
+use wayland_client::{
+    delegate_noop,
+    protocol::{wl_compositor, wl_registry, wl_seat, wl_surface},
+    Connection, Dispatch, EventQueue, QueueHandle,
+};
+use wayland_protocols::wp::text_input::zv3::client::{
+    zwp_text_input_manager_v3, zwp_text_input_v3,
+};
+use wayland_protocols::xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base};
+use wayland_protocols_misc::zwp_input_method_v2::client::{
+    zwp_input_method_manager_v2, zwp_input_method_v2,
+};
+
+/// What the input method commits; must reach the text input unchanged.
+const COMPOSED: &str = "你好";
+
+#[derive(Default)]
+struct ClientState {
+    compositor: Option<wl_compositor::WlCompositor>,
+    wm_base: Option<xdg_wm_base::XdgWmBase>,
+    seat: Option<wl_seat::WlSeat>,
+    text_input_manager: Option<zwp_text_input_manager_v3::ZwpTextInputManagerV3>,
+    input_method_manager: Option<zwp_input_method_manager_v2::ZwpInputMethodManagerV2>,
+    text_input: Option<zwp_text_input_v3::ZwpTextInputV3>,
+    input_method: Option<zwp_input_method_v2::ZwpInputMethodV2>,
+    surface: Option<wl_surface::WlSurface>,
+    /// Input method side: activated by the compositor and not yet answered
+    ime_active: bool,
+    ime_done_count: u32,
+    ime_committed: bool,
+    /// Text input side: the last string committed, applied on `done`
+    pending_commit: Option<String>,
+    received: Option<String>,
+}
+
+impl ClientState {
+    fn init_text_input(&mut self, qh: &QueueHandle<Self>) {
+        let Some(seat) = self.seat.as_ref() else {
+            return;
+        };
+        if self.text_input.is_none() {
+            if let Some(mgr) = self.text_input_manager.as_ref() {
+                self.text_input = Some(mgr.get_text_input(seat, qh, ()));
+            }
+        }
+        if self.input_method.is_none() {
+            if let Some(mgr) = self.input_method_manager.as_ref() {
+                self.input_method = Some(mgr.get_input_method(seat, qh, ()));
+            }
+        }
+    }
+
+    fn init_xdg_surface(&mut self, qh: &QueueHandle<Self>) {
+        let (wm_base, compositor) = match (self.wm_base.as_ref(), self.compositor.as_ref()) {
+            (Some(wm_base), Some(compositor)) => (wm_base, compositor),
+            _ => return,
+        };
+        if self.surface.is_some() {
+            return;
+        }
+        let surface = compositor.create_surface(qh, ());
+        let xdg_surface = wm_base.get_xdg_surface(&surface, qh, ());
+        let _toplevel = xdg_surface.get_toplevel(qh, ());
+        surface.commit();
+        self.surface = Some(surface);
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name, interface, ..
+        } = event
+        {
+            match interface.as_str() {
+                "wl_compositor" => {
+                    state.compositor =
+                        Some(registry.bind::<wl_compositor::WlCompositor, _, _>(name, 1, qh, ()));
+                    state.init_xdg_surface(qh);
+                }
+                // Only the default seat; the focus is granted on that one
+                "wl_seat" if state.seat.is_none() => {
+                    state.seat = Some(registry.bind::<wl_seat::WlSeat, _, _>(name, 7, qh, ()));
+                    state.init_text_input(qh);
+                }
+                "zwp_text_input_manager_v3" => {
+                    state.text_input_manager = Some(
+                        registry.bind::<zwp_text_input_manager_v3::ZwpTextInputManagerV3, _, _>(
+                            name,
+                            1,
+                            qh,
+                            (),
+                        ),
+                    );
+                    state.init_text_input(qh);
+                }
+                "zwp_input_method_manager_v2" => {
+                    state.input_method_manager = Some(
+                        registry
+                            .bind::<zwp_input_method_manager_v2::ZwpInputMethodManagerV2, _, _>(
+                                name,
+                                1,
+                                qh,
+                                (),
+                            ),
+                    );
+                    state.init_text_input(qh);
+                }
+                "xdg_wm_base" => {
+                    state.wm_base =
+                        Some(registry.bind::<xdg_wm_base::XdgWmBase, _, _>(name, 1, qh, ()));
+                    state.init_xdg_surface(qh);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+delegate_noop!(ClientState: ignore wl_compositor::WlCompositor);
+delegate_noop!(ClientState: ignore wl_surface::WlSurface);
+delegate_noop!(ClientState: ignore wl_seat::WlSeat);
+delegate_noop!(ClientState: ignore zwp_text_input_manager_v3::ZwpTextInputManagerV3);
+delegate_noop!(ClientState: ignore zwp_input_method_manager_v2::ZwpInputMethodManagerV2);
+
+impl Dispatch<xdg_wm_base::XdgWmBase, ()> for ClientState {
+    fn event(
+        _: &mut Self,
+        wm_base: &xdg_wm_base::XdgWmBase,
+        event: xdg_wm_base::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<xdg_surface::XdgSurface, ()> for ClientState {
+    fn event(
+        _: &mut Self,
+        xdg_surface: &xdg_surface::XdgSurface,
+        event: xdg_surface::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_surface::Event::Configure { serial, .. } = event {
+            xdg_surface.ack_configure(serial);
+        }
+    }
+}
+
+impl Dispatch<xdg_toplevel::XdgToplevel, ()> for ClientState {
+    fn event(
+        _: &mut Self,
+        _: &xdg_toplevel::XdgToplevel,
+        _: xdg_toplevel::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+/// The application side: enable text input as soon as our surface gains
+/// focus, and keep whatever the input method commits.
+impl Dispatch<zwp_text_input_v3::ZwpTextInputV3, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        text_input: &zwp_text_input_v3::ZwpTextInputV3,
+        event: zwp_text_input_v3::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwp_text_input_v3::Event::Enter { .. } => {
+                text_input.enable();
+                text_input.set_cursor_rectangle(10, 10, 2, 16);
+                text_input.commit();
+            }
+            zwp_text_input_v3::Event::CommitString { text } => {
+                state.pending_commit = text;
+            }
+            zwp_text_input_v3::Event::Done { .. } => {
+                if let Some(text) = state.pending_commit.take() {
+                    state.received = Some(text);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The input method side: commit a composed string once activated.
+impl Dispatch<zwp_input_method_v2::ZwpInputMethodV2, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        input_method: &zwp_input_method_v2::ZwpInputMethodV2,
+        event: zwp_input_method_v2::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwp_input_method_v2::Event::Activate => state.ime_active = true,
+            zwp_input_method_v2::Event::Deactivate => state.ime_active = false,
+            zwp_input_method_v2::Event::Done => {
+                state.ime_done_count += 1;
+                if state.ime_active && !state.ime_committed {
+                    input_method.commit_string(COMPOSED.to_string());
+                    input_method.commit(state.ime_done_count);
+                    state.ime_committed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Drive the client until the text input has received a commit, or give up.
+/// Sends back what it received.
+fn run_client(result_tx: mpsc::Sender<Result<Option<String>, String>>) {
+    let res = (|| -> Result<Option<String>> {
+        let conn = Connection::connect_to_env()?;
+        let mut event_queue: EventQueue<ClientState> = conn.new_event_queue();
+        let qh = event_queue.handle();
+
+        let display = conn.display();
+        display.get_registry(&qh, ());
+
+        let mut state = ClientState::default();
+
+        for _ in 0..2048 {
+            let _ = event_queue.flush();
+            if let Some(guard) = event_queue.prepare_read() {
+                let fd = guard.connection_fd().as_raw_fd();
+                let mut pfd = libc::pollfd {
+                    fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                // SAFETY: `pfd` points to a single valid, initialized element.
+                unsafe {
+                    libc::poll(&mut pfd as *mut libc::pollfd, 1, 5);
+                }
+                if pfd.revents & libc::POLLIN != 0 {
+                    let _ = guard.read();
+                }
+            }
+            event_queue.dispatch_pending(&mut state)?;
+            if state.received.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        Ok(state.received)
+    })();
+
+    let _ = result_tx.send(res.map_err(|e| format!("client error: {e:?}")));
+}
+
+#[test]
+#[serial_test::serial]
+fn test_input_method_commit_reaches_focused_text_input() -> Result<()> {
+    let config = AxiomConfig::default();
+    let (mut compositor, window_manager) = make_headless_compositor(config)?;
+
+    let socket_name = format!("wayland-axiom-{}", std::process::id());
+    std::env::set_var("WAYLAND_DISPLAY", &socket_name);
+
+    let (tx, rx) = mpsc::channel();
+    let client_handle = thread::spawn(move || run_client(tx));
+
+    let mut ticks = 0;
+    while window_manager.read().window_count() < 1 && ticks < 200 {
+        compositor.tick_for_test()?;
+        ticks += 1;
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(
+        window_manager.read().window_count() >= 1,
+        "compositor did not track the client toplevel"
+    );
+
+    // Focusing the toplevel sends its text input `enter`
+    compositor.debug_focus_first_client_for_test();
+
+    // Tick until the client has seen the commit (or given up)
+    let mut received = None;
+    for _ in 0..2000 {
+        compositor.tick_for_test()?;
+        thread::sleep(Duration::from_millis(5));
+        if let Ok(result) = rx.try_recv() {
+            received = Some(result);
+            break;
+        }
+    }
+    let _ = client_handle.join();
+
+    let received = received
+        .or_else(|| rx.recv_timeout(Duration::from_secs(5)).ok())
+        .expect("client did not finish");
+    assert_eq!(
+        received,
+        Ok(Some(COMPOSED.to_string())),
+        "the input method's commit did not reach the focused text input"
+    );
+    Ok(())
+}
@@ -0,0 +1,256 @@
+//! `zwp_virtual_keyboard_v1` input reaches the focused client, but only
+//! from processes in `security.allow_virtual_input`.
+
+mod common;
+
+use anyhow::Result;
+use axiom::{compositor::AxiomCompositor, config::AxiomConfig, window::WindowManager};
+use common::make_headless_compositor;
+use parking_lot::RwLock;
+use std::io::Write;
+use std::os::fd::AsFd;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use wayland_client::{
+    delegate_noop,
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{wl_compositor, wl_keyboard, wl_registry, wl_seat, wl_surface},
+    Connection, Dispatch, QueueHandle, WEnum,
+};
+use wayland_protocols::xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base};
+use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::{
+    zwp_virtual_keyboard_manager_v1, zwp_virtual_keyboard_v1,
+};
+
+/// evdev code of the key typed, `KEY_A`
+const KEY_A: u32 = 30;
+
+#[derive(Default)]
+struct TestClient {
+    keyboard: Option<wl_keyboard::WlKeyboard>,
+    /// evdev codes of the keys pressed on our surface
+    keys: Vec<u32>,
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for TestClient {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(TestClient: ignore wl_compositor::WlCompositor);
+delegate_noop!(TestClient: ignore wl_surface::WlSurface);
+delegate_noop!(TestClient: ignore xdg_toplevel::XdgToplevel);
+delegate_noop!(TestClient: ignore zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1);
+delegate_noop!(TestClient: ignore zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1);
+
+impl Dispatch<wl_seat::WlSeat, ()> for TestClient {
+    fn event(
+        state: &mut Self,
+        seat: &wl_seat::WlSeat,
+        event: wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+        {
+            if capabilities.contains(wl_seat::Capability::Keyboard) && state.keyboard.is_none() {
+                state.keyboard = Some(seat.get_keyboard(qh, ()));
+            }
+        }
+    }
+}
+
+impl Dispatch<wl_keyboard::WlKeyboard, ()> for TestClient {
+    fn event(
+        state: &mut Self,
+        _: &wl_keyboard::WlKeyboard,
+        event: wl_keyboard::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_keyboard::Event::Key {
+            key,
+            state: WEnum::Value(wl_keyboard::KeyState::Pressed),
+            ..
+        } = event
+        {
+            state.keys.push(key);
+        }
+    }
+}
+
+impl Dispatch<xdg_wm_base::XdgWmBase, ()> for TestClient {
+    fn event(
+        _: &mut Self,
+        wm_base: &xdg_wm_base::XdgWmBase,
+        event: xdg_wm_base::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<xdg_surface::XdgSurface, ()> for TestClient {
+    fn event(
+        _: &mut Self,
+        xdg_surface: &xdg_surface::XdgSurface,
+        event: xdg_surface::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_surface::Event::Configure { serial } = event {
+            xdg_surface.ack_configure(serial);
+        }
+    }
+}
+
+/// Open a toplevel, wait for a keyboard, then type `KEY_A` on a virtual
+/// keyboard. Returns the keys our own `wl_keyboard` saw pressed.
+fn type_on_virtual_keyboard() -> Result<Vec<u32>> {
+    let conn = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<TestClient>(&conn)?;
+    let qh = queue.handle();
+    let mut client = TestClient::default();
+
+    let compositor: wl_compositor::WlCompositor = globals.bind(&qh, 1..=4, ())?;
+    let wm_base: xdg_wm_base::XdgWmBase = globals.bind(&qh, 1..=1, ())?;
+    let seat: wl_seat::WlSeat = globals.bind(&qh, 1..=7, ())?;
+    let manager: zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1 =
+        globals.bind(&qh, 1..=1, ())?;
+
+    let surface = compositor.create_surface(&qh, ());
+    let xdg_surface = wm_base.get_xdg_surface(&surface, &qh, ());
+    let _toplevel = xdg_surface.get_toplevel(&qh, ());
+    surface.commit();
+
+    // The seat gains its keyboard when the test grants focus
+    for _ in 0..400 {
+        queue.roundtrip(&mut client)?;
+        if client.keyboard.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    anyhow::ensure!(client.keyboard.is_some(), "seat never got a keyboard");
+
+    let keymap = "xkb_keymap { };\0";
+    let mut file = tempfile::tempfile()?;
+    file.write_all(keymap.as_bytes())?;
+    let virtual_keyboard = manager.create_virtual_keyboard(&seat, &qh, ());
+    virtual_keyboard.keymap(
+        wl_keyboard::KeymapFormat::XkbV1 as u32,
+        file.as_fd(),
+        keymap.len() as u32,
+    );
+    virtual_keyboard.key(0, KEY_A, wl_keyboard::KeyState::Pressed as u32);
+    virtual_keyboard.key(1, KEY_A, wl_keyboard::KeyState::Released as u32);
+
+    for _ in 0..400 {
+        queue.roundtrip(&mut client)?;
+        if !client.keys.is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    Ok(client.keys)
+}
+
+/// Bind the virtual keyboard manager. Returns the protocol error the
+/// compositor answered with, if any.
+fn bind_virtual_keyboard_manager() -> Result<Option<(u32, String)>> {
+    let conn = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<TestClient>(&conn)?;
+    let qh = queue.handle();
+    let _manager: zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1 =
+        globals.bind(&qh, 1..=1, ())?;
+    let _ = queue.roundtrip(&mut TestClient::default());
+    Ok(conn.protocol_error().map(|e| (e.code, e.object_interface)))
+}
+
+/// Tick `compositor` while `client` runs on another thread. Once a window
+/// shows up it is given keyboard focus.
+fn run_against<T: Send + 'static>(
+    compositor: &mut AxiomCompositor,
+    window_manager: &RwLock<WindowManager>,
+    client: fn() -> Result<T>,
+) -> Result<T> {
+    let socket_name = format!("wayland-axiom-{}", std::process::id());
+    std::env::set_var("WAYLAND_DISPLAY", &socket_name);
+
+    let (tx, rx) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        let _ = tx.send(client().map_err(|e| e.to_string()));
+    });
+
+    let mut focused = false;
+    let mut outcome = None;
+    for _ in 0..2000 {
+        compositor.tick_for_test()?;
+        if !focused && window_manager.read().window_count() > 0 {
+            compositor.debug_focus_first_client_for_test();
+            focused = true;
+        }
+        if let Ok(result) = rx.try_recv() {
+            outcome = Some(result);
+            break;
+        }
+        thread::sleep(Duration::from_millis(2));
+    }
+    let _ = handle.join();
+    outcome
+        .expect("client did not finish")
+        .map_err(|e| anyhow::anyhow!("client error: {e}"))
+}
+
+#[test]
+#[serial_test::serial]
+fn test_virtual_key_reaches_focused_window() -> Result<()> {
+    let mut config = AxiomConfig::default();
+    // Allow this test process by name rather than with "*"
+    let name = std::fs::read_to_string("/proc/self/comm")?;
+    config.security.allow_virtual_input = vec![name.trim_end().to_string()];
+    let (mut compositor, window_manager) = make_headless_compositor(config)?;
+
+    let keys = run_against(&mut compositor, &window_manager, type_on_virtual_keyboard)?;
+    assert_eq!(keys, vec![KEY_A]);
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_unauthorized_client_cannot_create_virtual_keyboard() -> Result<()> {
+    // Nothing is allowed by default
+    let (mut compositor, window_manager) = make_headless_compositor(AxiomConfig::default())?;
+
+    let error = run_against(
+        &mut compositor,
+        &window_manager,
+        bind_virtual_keyboard_manager,
+    )?;
+    assert_eq!(
+        error,
+        Some((
+            zwp_virtual_keyboard_manager_v1::Error::Unauthorized as u32,
+            "zwp_virtual_keyboard_manager_v1".to_string()
+        ))
+    );
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! `window.max_windows` under both overflow policies, `reject` and
+//! `close_oldest`.
+
+mod common;
+
+use anyhow::Result;
+use axiom::{compositor::AxiomCompositor, config::AxiomConfig, window::WindowManager};
+use common::make_headless_compositor;
+use parking_lot::RwLock;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use wayland_client::{
+    delegate_noop,
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{wl_compositor, wl_registry, wl_surface},
+    Connection, Dispatch, EventQueue, QueueHandle,
+};
+use wayland_protocols::xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base};
+
+/// One client with one toplevel; remembers whether it was asked to close.
+#[derive(Default)]
+struct TestClient {
+    closed: bool,
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for TestClient {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(TestClient: ignore wl_compositor::WlCompositor);
+delegate_noop!(TestClient: ignore wl_surface::WlSurface);
+
+impl Dispatch<xdg_wm_base::XdgWmBase, ()> for TestClient {
+    fn event(
+        _: &mut Self,
+        wm_base: &xdg_wm_base::XdgWmBase,
+        event: xdg_wm_base::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<xdg_surface::XdgSurface, ()> for TestClient {
+    fn event(
+        _: &mut Self,
+        xdg_surface: &xdg_surface::XdgSurface,
+        event: xdg_surface::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_surface::Event::Configure { serial } = event {
+            xdg_surface.ack_configure(serial);
+        }
+    }
+}
+
+impl Dispatch<xdg_toplevel::XdgToplevel, ()> for TestClient {
+    fn event(
+        state: &mut Self,
+        _: &xdg_toplevel::XdgToplevel,
+        event: xdg_toplevel::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_toplevel::Event::Close = event {
+            state.closed = true;
+        }
+    }
+}
+
+struct Window {
+    conn: Connection,
+    queue: EventQueue<TestClient>,
+    state: TestClient,
+}
+
+/// Connect and map one toplevel.
+fn open_window() -> Result<Window> {
+    let conn = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<TestClient>(&conn)?;
+    let qh = queue.handle();
+    let compositor: wl_compositor::WlCompositor = globals.bind(&qh, 1..=4, ())?;
+    let wm_base: xdg_wm_base::XdgWmBase = globals.bind(&qh, 1..=1, ())?;
+    let surface = compositor.create_surface(&qh, ());
+    let xdg_surface = wm_base.get_xdg_surface(&surface, &qh, ());
+    let _toplevel = xdg_surface.get_toplevel(&qh, ());
+    surface.commit();
+    let mut state = TestClient::default();
+    // A rejected window errors out here; that's reported, not a failure
+    let _ = queue.roundtrip(&mut state);
+    Ok(Window { conn, queue, state })
+}
+
+/// What each of the three clients saw, in the order they connected.
+#[derive(Debug)]
+struct Seen {
+    protocol_error: bool,
+    closed: bool,
+}
+
+/// Open three windows, then give every client a chance to read what the
+/// compositor sent it. The connections stay open until the compositor
+/// thread has counted its windows.
+fn run_clients(results: mpsc::Sender<Result<Vec<Seen>, String>>, hold: mpsc::Receiver<()>) {
+    let windows = (0..3)
+        .map(|_| open_window())
+        .collect::<Result<Vec<_>>>()
+        .map_err(|e| e.to_string());
+    let mut windows = match windows {
+        Ok(windows) => windows,
+        Err(e) => {
+            let _ = results.send(Err(e));
+            return;
+        }
+    };
+    let seen = windows
+        .iter_mut()
+        .map(|w| {
+            let _ = w.queue.roundtrip(&mut w.state);
+            Seen {
+                protocol_error: w.conn.protocol_error().is_some(),
+                closed: w.state.closed,
+            }
+        })
+        .collect();
+    let _ = results.send(Ok(seen));
+    let _ = hold.recv_timeout(Duration::from_secs(5));
+}
+
+/// Tick `compositor` while the clients run. Returns what they saw and how
+/// many windows the compositor had at that point.
+fn run_against(
+    compositor: &mut AxiomCompositor,
+    window_manager: &RwLock<WindowManager>,
+) -> Result<(Vec<Seen>, usize)> {
+    let socket_name = format!("wayland-axiom-{}", std::process::id());
+    std::env::set_var("WAYLAND_DISPLAY", &socket_name);
+
+    let (results_tx, results_rx) = mpsc::channel();
+    let (hold_tx, hold_rx) = mpsc::channel();
+    let clients = thread::spawn(move || run_clients(results_tx, hold_rx));
+
+    let mut outcome = None;
+    for _ in 0..400 {
+        compositor.tick_for_test()?;
+        if let Ok(result) = results_rx.try_recv() {
+            outcome = Some(result);
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    let count = window_manager.read().window_count();
+    let _ = hold_tx.send(());
+    let _ = clients.join();
+    let seen = outcome
+        .expect("clients did not finish")
+        .map_err(|e| anyhow::anyhow!("client error: {e}"))?;
+    Ok((seen, count))
+}
+
+fn capped_config(policy: &str) -> AxiomConfig {
+    let mut config = AxiomConfig::default();
+    config.window.max_windows = Some(2);
+    config.window.overflow_policy = policy.to_string();
+    config
+}
+
+#[test]
+#[serial_test::serial]
+fn test_third_window_is_rejected_at_the_cap() -> Result<()> {
+    let (mut compositor, window_manager) = make_headless_compositor(capped_config("reject"))?;
+    let (seen, count) = run_against(&mut compositor, &window_manager)?;
+
+    assert!(
+        !seen[0].protocol_error && !seen[1].protocol_error,
+        "{seen:?}"
+    );
+    assert!(seen[2].protocol_error, "third window was let in: {seen:?}");
+    assert!(seen.iter().all(|s| !s.closed), "{seen:?}");
+    assert_eq!(count, 2);
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_third_window_closes_the_oldest_unfocused_one() -> Result<()> {
+    let (mut compositor, window_manager) = make_headless_compositor(capped_config("close_oldest"))?;
+    let (seen, count) = run_against(&mut compositor, &window_manager)?;
+
+    assert!(seen.iter().all(|s| !s.protocol_error), "{seen:?}");
+    // The first window took focus when it opened; the second never had it
+    assert!(
+        seen[1].closed,
+        "oldest unfocused window was not asked to close: {seen:?}"
+    );
+    assert!(!seen[0].closed && !seen[2].closed, "{seen:?}");
+    // The client hasn't acted on the close yet
+    assert_eq!(count, 3);
+    Ok(())
+}